@@ -0,0 +1,136 @@
+package main
+
+import (
+	"container/list"
+	"crypto/sha256"
+	"encoding/hex"
+	"encoding/json"
+	"fmt"
+	"sync"
+	"time"
+)
+
+// CacheConfig controls the manager's response cache
+type CacheConfig struct {
+	Enabled    bool          `json:"enabled"`
+	MaxEntries int           `json:"maxEntries"`
+	TTL        time.Duration `json:"ttl"`
+}
+
+func DefaultCacheConfig() CacheConfig {
+	return CacheConfig{
+		Enabled:    true,
+		MaxEntries: 100,
+		TTL:        time.Hour,
+	}
+}
+
+type cacheEntry struct {
+	key      string
+	response AIResponse
+	expires  time.Time
+}
+
+// ResponseCache is an LRU cache of responses with a per-entry TTL
+type ResponseCache struct {
+	mu      sync.Mutex
+	config  CacheConfig
+	order   *list.List // front is most recently used
+	entries map[string]*list.Element
+}
+
+func NewResponseCache(config CacheConfig) *ResponseCache {
+	return &ResponseCache{
+		config:  config,
+		order:   list.New(),
+		entries: make(map[string]*list.Element),
+	}
+}
+
+// cacheKey identifies a request by provider, model, conversation, attachments, sampling settings
+// and token limit
+func cacheKey(providerName, model string, request AIRequest) string {
+	messages, _ := json.Marshal(request.Messages())
+	sampling, _ := json.Marshal(request.Sampling)
+	hash := sha256.New()
+	hash.Write(messages)
+	hash.Write(sampling)
+	for _, a := range request.Attachments {
+		hash.Write([]byte(a.MimeType))
+		hash.Write(a.Data)
+	}
+	sum := hash.Sum(nil)
+	return fmt.Sprintf("%s\x00%s\x00%g\x00%d\x00%s", providerName, model, request.Temperature, request.MaxTokens, hex.EncodeToString(sum))
+}
+
+// Get returns a live cached response for key
+func (c *ResponseCache) Get(key string) (AIResponse, bool) {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+
+	if !c.config.Enabled {
+		return AIResponse{}, false
+	}
+	element, ok := c.entries[key]
+	if !ok {
+		return AIResponse{}, false
+	}
+	entry := element.Value.(*cacheEntry)
+	if time.Now().After(entry.expires) {
+		c.order.Remove(element)
+		delete(c.entries, key)
+		return AIResponse{}, false
+	}
+	c.order.MoveToFront(element)
+	return entry.response, true
+}
+
+// Put stores a response, evicting the least recently used entries beyond MaxEntries
+func (c *ResponseCache) Put(key string, response AIResponse) {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+
+	if !c.config.Enabled || c.config.MaxEntries <= 0 {
+		return
+	}
+	expires := time.Now().Add(c.config.TTL)
+	if element, ok := c.entries[key]; ok {
+		element.Value = &cacheEntry{key: key, response: response, expires: expires}
+		c.order.MoveToFront(element)
+		return
+	}
+
+	c.entries[key] = c.order.PushFront(&cacheEntry{key: key, response: response, expires: expires})
+	for c.order.Len() > c.config.MaxEntries {
+		oldest := c.order.Back()
+		c.order.Remove(oldest)
+		delete(c.entries, oldest.Value.(*cacheEntry).key)
+	}
+}
+
+// Configure replaces the cache settings, trimming or clearing entries as needed
+func (c *ResponseCache) Configure(config CacheConfig) {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+
+	c.config = config
+	if !config.Enabled {
+		c.order.Init()
+		c.entries = make(map[string]*list.Element)
+		return
+	}
+	for c.order.Len() > config.MaxEntries {
+		oldest := c.order.Back()
+		c.order.Remove(oldest)
+		delete(c.entries, oldest.Value.(*cacheEntry).key)
+	}
+}
+
+// Clear drops every cached response
+func (c *ResponseCache) Clear() {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+
+	c.order.Init()
+	c.entries = make(map[string]*list.Element)
+}
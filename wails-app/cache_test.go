@@ -0,0 +1,99 @@
+package main
+
+import (
+	"testing"
+	"time"
+)
+
+func TestResponseCache(t *testing.T) {
+	tests := []struct {
+		name    string
+		config  CacheConfig
+		put     []string
+		get     []string // looked up in order, after every put
+		wantHit map[string]bool
+	}{
+		{
+			name:    "hit and miss",
+			config:  CacheConfig{Enabled: true, MaxEntries: 10, TTL: time.Hour},
+			put:     []string{"a"},
+			get:     []string{"a", "b"},
+			wantHit: map[string]bool{"a": true},
+		},
+		{
+			name:    "evicts the least recently used",
+			config:  CacheConfig{Enabled: true, MaxEntries: 2, TTL: time.Hour},
+			put:     []string{"a", "b", "c"},
+			get:     []string{"a", "b", "c"},
+			wantHit: map[string]bool{"b": true, "c": true},
+		},
+		{
+			name:   "expired entries miss",
+			config: CacheConfig{Enabled: true, MaxEntries: 10, TTL: -time.Second},
+			put:    []string{"a"},
+			get:    []string{"a"},
+		},
+		{
+			name:   "disabled",
+			config: CacheConfig{Enabled: false, MaxEntries: 10, TTL: time.Hour},
+			put:    []string{"a"},
+			get:    []string{"a"},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			cache := NewResponseCache(tt.config)
+			for _, key := range tt.put {
+				cache.Put(key, AIResponse{Content: "answer " + key})
+			}
+			for _, key := range tt.get {
+				response, ok := cache.Get(key)
+				if ok != tt.wantHit[key] {
+					t.Errorf("Get(%q) hit = %v, want %v", key, ok, tt.wantHit[key])
+				}
+				if ok && response.Content != "answer "+key {
+					t.Errorf("Get(%q) = %q", key, response.Content)
+				}
+			}
+		})
+	}
+}
+
+func TestResponseCacheGetRefreshes(t *testing.T) {
+	cache := NewResponseCache(CacheConfig{Enabled: true, MaxEntries: 2, TTL: time.Hour})
+	cache.Put("a", AIResponse{})
+	cache.Put("b", AIResponse{})
+	cache.Get("a") // now b is the least recently used
+	cache.Put("c", AIResponse{})
+	if _, ok := cache.Get("b"); ok {
+		t.Error("b should have been evicted")
+	}
+	if _, ok := cache.Get("a"); !ok {
+		t.Error("a was used last and should be kept")
+	}
+}
+
+func TestCacheKey(t *testing.T) {
+	request := NewAIRequest("hi", nil)
+	warmer := request
+	warmer.Temperature = 1
+	shorter := request
+	shorter.MaxTokens = 100
+	followUp := NewAIRequest("hi", []ChatMessage{{Role: "user", Content: "before"}, {Role: "assistant", Content: "ok"}})
+
+	key := cacheKey("p", "m", request)
+	if cacheKey("p", "m", request) != key {
+		t.Error("the same request keyed differently")
+	}
+	for name, other := range map[string]string{
+		"provider":    cacheKey("q", "m", request),
+		"model":       cacheKey("p", "n", request),
+		"temperature": cacheKey("p", "m", warmer),
+		"max tokens":  cacheKey("p", "m", shorter),
+		"history":     cacheKey("p", "m", followUp),
+	} {
+		if other == key {
+			t.Errorf("a different %s has the same key", name)
+		}
+	}
+}
@@ -0,0 +1,50 @@
+package main
+
+// Capabilities lists the optional features a provider supports, so the
+// manager and UI can disable options up front instead of failing mid-request
+type Capabilities struct {
+	Streaming    bool `json:"streaming"`
+	ModelListing bool `json:"modelListing"`
+	Embeddings   bool `json:"embeddings"`
+	HealthCheck  bool `json:"healthCheck"`
+	Tools        bool `json:"tools"`
+	Vision       bool `json:"vision"`
+}
+
+// capabilityRestrictor is implemented by shared provider types whose
+// instances don't all support every method the type implements
+type capabilityRestrictor interface {
+	restrictCapabilities(Capabilities) Capabilities
+}
+
+// CapabilitiesOf reports what a provider supports from the optional interfaces it implements
+func CapabilitiesOf(provider Provider) Capabilities {
+	_, streaming := provider.(StreamingProvider)
+	_, listing := provider.(ModelLister)
+	_, embeddings := provider.(EmbeddingProvider)
+	_, health := provider.(HealthChecker)
+	vision, ok := provider.(VisionProvider)
+	capabilities := Capabilities{
+		Streaming:    streaming,
+		ModelListing: listing,
+		Embeddings:   embeddings,
+		HealthCheck:  health,
+		Vision:       ok && vision.AcceptsImages(),
+	}
+	if restrictor, ok := provider.(capabilityRestrictor); ok {
+		capabilities = restrictor.restrictCapabilities(capabilities)
+	}
+	return capabilities
+}
+
+// Capabilities returns the capabilities of every provider, in registration order
+func (m *ProviderManager) Capabilities() []Capabilities {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+
+	capabilities := make([]Capabilities, len(m.providers))
+	for i, p := range m.providers {
+		capabilities[i] = CapabilitiesOf(p)
+	}
+	return capabilities
+}
@@ -0,0 +1,61 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"strings"
+
+	wailsruntime "github.com/wailsapp/wails/v2/pkg/runtime"
+)
+
+// What copying a block takes
+const (
+	CopyCode     = "code"     // the code alone, as it would be saved
+	CopyMarkdown = "markdown" // the block as Markdown, code with its fence and info string
+)
+
+// BlockClipboardText is what copying a block in mode puts on the clipboard.
+// Only code, diffs, diagrams and math have code to copy; any block can be
+// copied as Markdown.
+func BlockClipboardText(block ContentBlock, mode string) (string, error) {
+	switch mode {
+	case CopyCode:
+		switch block.Type {
+		case BlockCode, BlockDiff, BlockDiagram, BlockMath:
+			return strings.TrimRight(block.Code, "\n") + "\n", nil
+		}
+		return "", fmt.Errorf("a %s block has no code to copy", block.Type)
+	case CopyMarkdown:
+		return renderBlock(block) + "\n", nil
+	}
+	return "", fmt.Errorf("unknown copy mode %q", mode)
+}
+
+// ResponseClipboardText is what copying a whole response puts on the
+// clipboard: the response as it came, fences and all, with its line endings
+// made Unix ones
+func ResponseClipboardText(response string) string {
+	return strings.TrimRight(strings.ReplaceAll(response, "\r\n", "\n"), "\n") + "\n"
+}
+
+func (a *App) setClipboard(text string) error {
+	if a.ctx == nil {
+		return errors.New("no window to copy from")
+	}
+	return wailsruntime.ClipboardSetText(a.ctx, text)
+}
+
+// CopyBlock puts a block of a response on the clipboard, its code alone or
+// as Markdown
+func (a *App) CopyBlock(block ContentBlock, mode string) error {
+	text, err := BlockClipboardText(block, mode)
+	if err != nil {
+		return err
+	}
+	return a.setClipboard(text)
+}
+
+// CopyResponse puts a whole response on the clipboard
+func (a *App) CopyResponse(response string) error {
+	return a.setClipboard(ResponseClipboardText(response))
+}
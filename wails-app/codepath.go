@@ -0,0 +1,63 @@
+package main
+
+import (
+	"regexp"
+	"strings"
+)
+
+var (
+	// title=src/main.rs, file="src/main.rs", filename=..., path=... in a fence info string
+	infoPathAttr = regexp.MustCompile(`(?i)\b(?:title|file|filename|path)=("[^"]+"|'[^']+'|\S+)`)
+	// "File: src/main.rs", "src/main.rs:" or a bare path, on its own line before a fence
+	annotationLine = regexp.MustCompile(`(?i)^(?:(?:file(?:name)?|path)\s*:\s*)?([\w.\-/\\]+\.\w+)\s*:?$`)
+	// "// src/main.rs", "# file: app.py" or "/* main.c */" as the first line of the code
+	commentPathLine = regexp.MustCompile(`(?i)^\s*(?://|#|--|/\*|<!--)\s*(?:file(?:name)?\s*:\s*)?([\w.\-/\\]+\.\w+)\s*(?:\*/|-->)?\s*$`)
+)
+
+// parseInfoString splits a code fence's info string into the language and any
+// file path given as an attribute or a second word ("go main.go")
+func parseInfoString(info string) (language, path string) {
+	if match := infoPathAttr.FindStringSubmatch(info); match != nil {
+		path = strings.Trim(match[1], `"'`)
+		info = strings.Replace(info, match[0], "", 1)
+	}
+	fields := strings.Fields(info)
+	if len(fields) == 0 {
+		return "", path
+	}
+	language = fields[0]
+	// "rust:src/main.rs"
+	if before, after, ok := strings.Cut(language, ":"); ok && looksLikePath(after) {
+		language = before
+		if path == "" {
+			path = after
+		}
+	}
+	if path == "" && len(fields) > 1 && looksLikePath(fields[1]) {
+		path = fields[1]
+	}
+	return language, path
+}
+
+// annotatedPath returns the file path a line before a code block names, or ""
+func annotatedPath(line string) string {
+	if match := annotationLine.FindStringSubmatch(strings.TrimSpace(plainText(parseInline(line, DefaultParserOptions())))); match != nil {
+		return match[1]
+	}
+	return ""
+}
+
+// commentPath returns the path named by a comment on the first line of code, or ""
+func commentPath(code string) string {
+	first, _, _ := strings.Cut(code, "\n")
+	if match := commentPathLine.FindStringSubmatch(first); match != nil {
+		return match[1]
+	}
+	return ""
+}
+
+// looksLikePath accepts names with an extension, like main.rs or src/app/page.tsx
+func looksLikePath(s string) bool {
+	dot := strings.LastIndexByte(s, '.')
+	return dot > 0 && dot < len(s)-1 && !strings.ContainsAny(s, "=\"'") && !strings.HasPrefix(s, ".")
+}
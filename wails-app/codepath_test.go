@@ -0,0 +1,62 @@
+package main
+
+import "testing"
+
+func TestParseInfoString(t *testing.T) {
+	tests := []struct {
+		info         string
+		wantLanguage string
+		wantPath     string
+	}{
+		{"rust title=src/main.rs", "rust", "src/main.rs"},
+		{`python file="app.py"`, "python", "app.py"},
+		{"go main.go", "go", "main.go"},
+		{"rust:src/lib.rs", "rust", "src/lib.rs"},
+		{"go", "go", ""},
+		{"", "", ""},
+		{"text some words", "text", ""},
+		{"sh .bashrc", "sh", ""},
+	}
+	for _, tt := range tests {
+		language, path := parseInfoString(tt.info)
+		if language != tt.wantLanguage || path != tt.wantPath {
+			t.Errorf("parseInfoString(%q) = %q, %q, want %q, %q", tt.info, language, path, tt.wantLanguage, tt.wantPath)
+		}
+	}
+}
+
+func TestAnnotatedPath(t *testing.T) {
+	tests := []struct {
+		line string
+		want string
+	}{
+		{"File: src/main.rs", "src/main.rs"},
+		{"**main.go**", "main.go"},
+		{"`src/app.tsx`:", "src/app.tsx"},
+		{"Here is the code:", ""},
+	}
+	for _, tt := range tests {
+		if got := annotatedPath(tt.line); got != tt.want {
+			t.Errorf("annotatedPath(%q) = %q, want %q", tt.line, got, tt.want)
+		}
+	}
+}
+
+func TestCommentPath(t *testing.T) {
+	tests := []struct {
+		code string
+		want string
+	}{
+		{"// src/index.js\nconsole.log(1)", "src/index.js"},
+		{"# file: a.py\nprint(1)", "a.py"},
+		{"/* main.c */", "main.c"},
+		{"<!-- page.html -->\n<p>hi</p>", "page.html"},
+		{"// just a comment\nx := 1", ""},
+		{"x := 1", ""},
+	}
+	for _, tt := range tests {
+		if got := commentPath(tt.code); got != tt.want {
+			t.Errorf("commentPath(%q) = %q, want %q", tt.code, got, tt.want)
+		}
+	}
+}
@@ -0,0 +1,206 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"strconv"
+	"strings"
+)
+
+// PluginCommand is a command a plugin offers as `/name args` in the prompt box
+type PluginCommand struct {
+	Name        string       `json:"name"`
+	Description string       `json:"description"`
+	Args        []CommandArg `json:"args"`
+	Plugin      string       `json:"plugin,omitempty"` // filled in by the registry
+}
+
+// CommandArg describes one argument of a PluginCommand
+type CommandArg struct {
+	Name        string `json:"name"`
+	Description string `json:"description"`
+	Type        string `json:"type"` // "string" (the default), "number" or "boolean"
+	Required    bool   `json:"required"`
+}
+
+// CommandRunner is implemented by plugins that declare Commands in their
+// metadata. Args are keyed by name and already converted to their declared
+// types; the output is markdown shown in place of a response.
+type CommandRunner interface {
+	RunCommand(name string, args map[string]any) (string, error)
+}
+
+// CommandResult is the outcome of a command typed in the prompt box
+type CommandResult struct {
+	Plugin  string `json:"plugin"`
+	Command string `json:"command"`
+	Output  string `json:"output"`
+}
+
+// ErrNotCommand is returned for input that doesn't start with a slash
+var ErrNotCommand = errors.New("not a command")
+
+// Commands returns the commands of every enabled plugin in run order. A name
+// two plugins share goes to the one that runs first.
+func (r *PluginRegistry) Commands() []PluginCommand {
+	commands := make([]PluginCommand, 0)
+	seen := make(map[string]bool)
+	for _, plugin := range r.enabled(PluginCustomCommand) {
+		metadata := plugin.Metadata()
+		for _, command := range metadata.Commands {
+			if seen[command.Name] {
+				continue
+			}
+			seen[command.Name] = true
+			command.Plugin = metadata.Name
+			commands = append(commands, command)
+		}
+	}
+	return commands
+}
+
+// RunCommand parses `/name args` and dispatches it to the plugin that owns
+// the command. Arguments are positional in declared order or name=value; the
+// last string argument takes the rest of the line.
+func (r *PluginRegistry) RunCommand(line string) (CommandResult, error) {
+	line = strings.TrimSpace(line)
+	if !strings.HasPrefix(line, "/") {
+		return CommandResult{}, ErrNotCommand
+	}
+	name, rest, _ := strings.Cut(line[1:], " ")
+	for _, command := range r.Commands() {
+		if command.Name != name {
+			continue
+		}
+		args, err := bindCommandArgs(command, rest)
+		if err != nil {
+			return CommandResult{}, fmt.Errorf("/%s: %w", name, err)
+		}
+		for _, plugin := range r.enabled(PluginCustomCommand) {
+			if plugin.Metadata().Name != command.Plugin {
+				continue
+			}
+			var output string
+			err := r.invoke(plugin, "run_command", func() (err error) {
+				output, err = plugin.(CommandRunner).RunCommand(name, args)
+				return err
+			})
+			if err != nil {
+				return CommandResult{}, &PluginError{Plugin: command.Plugin, Err: fmt.Errorf("/%s: %w", name, err)}
+			}
+			return CommandResult{Plugin: command.Plugin, Command: name, Output: output}, nil
+		}
+	}
+	return CommandResult{}, fmt.Errorf("unknown command /%s", name)
+}
+
+// bindCommandArgs matches the words after a command to its arguments
+func bindCommandArgs(command PluginCommand, input string) (map[string]any, error) {
+	words, err := splitCommandWords(input)
+	if err != nil {
+		return nil, err
+	}
+	declared := make(map[string]CommandArg, len(command.Args))
+	for _, arg := range command.Args {
+		declared[arg.Name] = arg
+	}
+
+	raw := make(map[string]string)
+	var positional []string
+	for _, word := range words {
+		if key, value, ok := strings.Cut(word, "="); ok {
+			if _, known := declared[key]; known {
+				raw[key] = value
+				continue
+			}
+		}
+		positional = append(positional, word)
+	}
+	for i, arg := range command.Args {
+		if len(positional) == 0 {
+			break
+		}
+		if _, set := raw[arg.Name]; set {
+			continue
+		}
+		if i == len(command.Args)-1 && (arg.Type == "" || arg.Type == "string") {
+			raw[arg.Name] = strings.Join(positional, " ")
+			positional = nil
+			break
+		}
+		raw[arg.Name], positional = positional[0], positional[1:]
+	}
+	if len(positional) > 0 {
+		return nil, fmt.Errorf("unexpected %q", strings.Join(positional, " "))
+	}
+
+	args := make(map[string]any, len(raw))
+	for _, arg := range command.Args {
+		value, set := raw[arg.Name]
+		if !set {
+			if arg.Required {
+				return nil, fmt.Errorf("missing %s", arg.Name)
+			}
+			continue
+		}
+		switch arg.Type {
+		case "number":
+			number, err := strconv.ParseFloat(value, 64)
+			if err != nil {
+				return nil, fmt.Errorf("%s must be a number, not %q", arg.Name, value)
+			}
+			args[arg.Name] = number
+		case "boolean":
+			flag, err := strconv.ParseBool(value)
+			if err != nil {
+				return nil, fmt.Errorf("%s must be true or false, not %q", arg.Name, value)
+			}
+			args[arg.Name] = flag
+		default:
+			args[arg.Name] = value
+		}
+	}
+	return args, nil
+}
+
+// splitCommandWords splits at spaces, keeping "quoted text" together
+func splitCommandWords(input string) ([]string, error) {
+	var words []string
+	var word strings.Builder
+	inWord, quoted := false, false
+	for _, r := range input {
+		switch {
+		case r == '"':
+			quoted = !quoted
+			inWord = true
+		case r == ' ' && !quoted:
+			if inWord {
+				words = append(words, word.String())
+				word.Reset()
+				inWord = false
+			}
+		default:
+			word.WriteRune(r)
+			inWord = true
+		}
+	}
+	if quoted {
+		return nil, errors.New("unclosed quote")
+	}
+	if inWord {
+		words = append(words, word.String())
+	}
+	return words, nil
+}
+
+// ListCommands returns the commands plugins offer, for completion in the prompt box
+func (a *App) ListCommands() []PluginCommand {
+	return a.manager.Plugins().Commands()
+}
+
+// RunCommand runs a `/command args` line typed in the prompt box
+func (a *App) RunCommand(line string) (CommandResult, error) {
+	defer a.crash.Capture()
+
+	return a.manager.Plugins().RunCommand(line)
+}
@@ -0,0 +1,121 @@
+package main
+
+import (
+	"hash/fnv"
+	"strings"
+	"sync"
+)
+
+const (
+	keepRecentMessages  = 6   // the latest turns are always sent verbatim
+	summaryMaxTokens    = 512 // length limit for a conversation summary
+	maxCachedSummaries  = 256
+	summaryMessageTitle = "Summary of the earlier conversation:\n"
+)
+
+// ContextCompressor keeps long conversations within the model's context window
+// by replacing older turns with a summary written by the model itself. Summaries
+// are cached by the turns they cover, so as a conversation grows only the new
+// turns are folded into the previous summary.
+type ContextCompressor struct {
+	mu        sync.Mutex
+	enabled   bool
+	summaries map[uint64]string // hash of a history prefix -> its summary
+}
+
+func NewContextCompressor() *ContextCompressor {
+	return &ContextCompressor{enabled: true, summaries: make(map[uint64]string)}
+}
+
+func (c *ContextCompressor) SetEnabled(enabled bool) {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+
+	c.enabled = enabled
+}
+
+func (c *ContextCompressor) Enabled() bool {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+
+	return c.enabled
+}
+
+// Compress summarizes the older history when request overflows model's window,
+// using send to call the model. If summarizing fails the request is returned
+// unchanged and FitToContext drops the oldest turns instead.
+func (c *ContextCompressor) Compress(request AIRequest, model string, send func(AIRequest) (AIResponse, error)) AIRequest {
+	if !c.Enabled() || len(request.History) <= keepRecentMessages || !budgetFor(request, model).Exceeds {
+		return request
+	}
+
+	// Keep whole exchanges: the verbatim part starts at a user turn
+	cut := len(request.History) - keepRecentMessages
+	for cut < len(request.History) && request.History[cut].Role != "user" {
+		cut++
+	}
+	older := request.History[:cut]
+	prefixes := prefixHashes(older)
+
+	c.mu.Lock()
+	summary, covered := "", 0
+	for i := len(older); i > 0; i-- {
+		if cached, ok := c.summaries[prefixes[i-1]]; ok {
+			summary, covered = cached, i
+			break
+		}
+	}
+	c.mu.Unlock()
+
+	if covered < len(older) {
+		response, err := send(FitToContext(summaryRequest(summary, older[covered:]), model))
+		if err != nil || strings.TrimSpace(response.Content) == "" {
+			return request
+		}
+		summary = strings.TrimSpace(response.Content)
+
+		c.mu.Lock()
+		if len(c.summaries) >= maxCachedSummaries {
+			c.summaries = make(map[uint64]string)
+		}
+		c.summaries[prefixes[len(older)-1]] = summary
+		c.mu.Unlock()
+	}
+
+	history := make([]ChatMessage, 0, len(request.History)-cut+1)
+	history = append(history, ChatMessage{Role: "system", Content: summaryMessageTitle + summary})
+	request.History = append(history, request.History[cut:]...)
+	return request
+}
+
+// summaryRequest asks the model to fold new turns into an existing summary
+func summaryRequest(summary string, turns []ChatMessage) AIRequest {
+	var transcript strings.Builder
+	if summary != "" {
+		transcript.WriteString("Summary so far:\n" + summary + "\n\n")
+	}
+	for _, m := range turns {
+		transcript.WriteString(m.Role + ": " + m.Content + "\n\n")
+	}
+
+	request := NewAIRequest("Summarize this conversation between a user and a coding assistant. "+
+		"Keep decisions, requirements, file names, code identifiers and open questions; drop pleasantries. "+
+		"Reply with the summary only.\n\n"+transcript.String(), nil)
+	request.Temperature = 0.2
+	request.MaxTokens = summaryMaxTokens
+	return request
+}
+
+// prefixHashes returns the hash of every prefix of messages; entry i covers messages[:i+1]
+func prefixHashes(messages []ChatMessage) []uint64 {
+	hash := fnv.New64a()
+	hashes := make([]uint64, len(messages))
+	for i, m := range messages {
+		hash.Write([]byte(m.Role))
+		hash.Write([]byte{0})
+		hash.Write([]byte(m.Content))
+		hash.Write([]byte{0})
+		hashes[i] = hash.Sum64()
+	}
+	return hashes
+}
@@ -0,0 +1,334 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"reflect"
+	"sync"
+	"time"
+)
+
+// AppConfig is the app's settings, kept in config.toml in the user config
+// dir ($XDG_CONFIG_HOME or ~/.config on Linux, ~/Library/Application Support
+// on macOS, %AppData% on Windows) over the other layers in
+// config_layers.go, and restored at startup
+type AppConfig struct {
+	Providers       []ProviderConfig `json:"providers"`
+	ActiveProvider  int              `json:"activeProvider"` // index into Providers, -1 for none
+	Offline         bool             `json:"offline"`
+	Redaction       string           `json:"redaction"`  // "mask", "block" or "off"
+	RequestLog      string           `json:"requestLog"` // "off", "metadata" or "full"
+	CompressHistory bool             `json:"compressHistory"`
+	General         GeneralConfig    `json:"general"`
+	UI              UIConfig         `json:"ui"`
+}
+
+// GeneralConfig is how much of what the app records it keeps. A zero limit
+// is no limit; pinned history entries are kept regardless.
+type GeneralConfig struct {
+	HistoryMaxEntries int   `json:"historyMaxEntries"`
+	HistoryMaxAgeDays int   `json:"historyMaxAgeDays"`
+	HistoryMaxBytes   int64 `json:"historyMaxBytes"` // of history.json
+	HistoryArchive    bool  `json:"historyArchive"`  // append pruned entries to history-archive.jsonl
+}
+
+// historyRetention is the retention the history is kept to
+func (c GeneralConfig) historyRetention() HistoryRetention {
+	retention := HistoryRetention{
+		MaxEntries: c.HistoryMaxEntries,
+		MaxAge:     time.Duration(c.HistoryMaxAgeDays) * 24 * time.Hour,
+		MaxBytes:   c.HistoryMaxBytes,
+	}
+	if c.HistoryArchive {
+		retention.Archive = appDataPath("history-archive.jsonl")
+	}
+	return retention
+}
+
+// UIConfig is how the frontend looks
+type UIConfig struct {
+	Theme      string             `json:"theme"` // "dark", "light" or one of Palettes
+	Style      string             `json:"style"` // "vscode" or "zed"
+	FontFamily string             `json:"fontFamily"`
+	FontSize   int                `json:"fontSize"`           // in pixels
+	Palettes   map[string]Palette `json:"palettes,omitempty"` // custom themes by name, set in config.toml
+}
+
+// Font sizes the UI accepts
+const (
+	minFontSize = 10
+	maxFontSize = 28
+)
+
+func DefaultAppConfig() AppConfig {
+	return AppConfig{
+		Providers:       []ProviderConfig{},
+		ActiveProvider:  -1,
+		Redaction:       RedactMask,
+		RequestLog:      LogOff,
+		CompressHistory: true,
+		General: GeneralConfig{
+			HistoryMaxEntries: sessionHistoryLimit,
+		},
+		UI: UIConfig{
+			Theme:      "dark",
+			Style:      "vscode",
+			FontFamily: "JetBrains Mono",
+			FontSize:   14,
+		},
+	}
+}
+
+// Validate reports the first setting that is out of range
+func (c AppConfig) Validate() error {
+	for i, provider := range c.Providers {
+		if provider.Type == "" || provider.Name == "" {
+			return fmt.Errorf("provider %d needs a type and a name", i+1)
+		}
+		if provider.Timeout < 0 {
+			return fmt.Errorf("provider %s: timeout must not be negative", provider.Name)
+		}
+	}
+	if c.ActiveProvider < -1 || c.ActiveProvider >= len(c.Providers) {
+		return fmt.Errorf("active provider %d is not one of the %d providers", c.ActiveProvider, len(c.Providers))
+	}
+	switch c.Redaction {
+	case RedactOff, RedactMask, RedactBlock:
+	default:
+		return fmt.Errorf("unknown redaction mode %q", c.Redaction)
+	}
+	switch c.RequestLog {
+	case LogOff, LogMetadata, LogFull:
+	default:
+		return fmt.Errorf("unknown request log mode %q", c.RequestLog)
+	}
+	if err := c.General.Validate(); err != nil {
+		return err
+	}
+	return c.UI.Validate()
+}
+
+func (c GeneralConfig) Validate() error {
+	if c.HistoryMaxEntries < 0 || c.HistoryMaxAgeDays < 0 || c.HistoryMaxBytes < 0 {
+		return errors.New("history limits must not be negative")
+	}
+	return nil
+}
+
+func (c UIConfig) Validate() error {
+	for name, palette := range c.Palettes {
+		if _, builtin := builtinPalettes[name]; builtin {
+			return fmt.Errorf("palette %q has the name of a built-in theme", name)
+		}
+		if err := palette.Validate(); err != nil {
+			return fmt.Errorf("palette %q: %w", name, err)
+		}
+	}
+	if _, builtin := builtinPalettes[c.Theme]; !builtin {
+		if _, custom := c.Palettes[c.Theme]; !custom {
+			return fmt.Errorf("unknown theme %q", c.Theme)
+		}
+	}
+	if c.Style != "vscode" && c.Style != "zed" {
+		return fmt.Errorf("unknown style %q", c.Style)
+	}
+	if c.FontFamily == "" {
+		return errors.New("font family must not be empty")
+	}
+	if c.FontSize < minFontSize || c.FontSize > maxFontSize {
+		return fmt.Errorf("font size must be between %d and %d", minFontSize, maxFontSize)
+	}
+	return nil
+}
+
+// ConfigStore holds the config resolved from its layers and saves every
+// change to the user layer
+type ConfigStore struct {
+	mu       sync.Mutex
+	sources  ConfigSources
+	layers   []configLayer
+	config   AppConfig
+	resolved map[string]any          // config as a tree, to tell what a change changed
+	origins  map[string]configOrigin // by dotted key
+	stamps   configStamps            // the files as last loaded or saved
+}
+
+// NewConfigStore resolves the config from sources; layers that can't be read
+// or aren't valid are reported and left out
+func NewConfigStore(sources ConfigSources) *ConfigStore {
+	s := &ConfigStore{sources: sources}
+	for _, err := range s.load() {
+		fmt.Fprintf(os.Stderr, "ignoring config: %v\n", err)
+	}
+	return s
+}
+
+// load reads and resolves every layer afresh; the caller holds s.mu
+func (s *ConfigStore) load() []error {
+	s.stamps = s.sources.stamps()
+	layers, errs := s.sources.layers(s.layers)
+	config, origins, resolveErrs := resolveConfig(layers)
+	s.layers, s.config, s.origins = layers, config, origins
+	s.resolved = configTree(config)
+	return append(errs, resolveErrs...)
+}
+
+func (s *ConfigStore) Get() AppConfig {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	config := s.config
+	config.Providers = append([]ProviderConfig{}, s.config.Providers...)
+	return config
+}
+
+// Update applies change to a copy of the config and saves the settings it
+// changed to the user file, leaving the rest to the layers they came from;
+// a change that leaves the config invalid is refused and nothing is saved.
+// A setting the project, environment or command line set takes the new
+// value for this run only and isn't saved, so what those layers set never
+// ends up in the user's own file.
+func (s *ConfigStore) Update(change func(*AppConfig)) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	config := s.config
+	config.Providers = append([]ProviderConfig{}, s.config.Providers...)
+	change(&config)
+	if err := config.Validate(); err != nil {
+		return err
+	}
+	tree := configTree(config)
+	user := s.userLayer()
+	values := copyConfigTree(user.values)
+	var changed, saved []string
+	configLeaves(tree, func(key string, value any) {
+		if old, _ := configValueAt(s.resolved, key); !reflect.DeepEqual(old, value) {
+			changed = append(changed, key)
+			if !s.overridden(key) {
+				setConfigValue(values, key, value)
+				saved = append(saved, key)
+			}
+		}
+	})
+	if len(changed) == 0 {
+		return nil
+	}
+	if len(saved) > 0 {
+		if err := writeConfigFile(s.sources.UserPath, values); err != nil {
+			return err
+		}
+		user.values, user.previous = values, values
+		s.stamps = s.sources.stamps()
+	}
+	s.config, s.resolved = config, tree
+	for _, key := range saved {
+		s.origins[key] = configOrigin{source: ConfigUser, origin: s.sources.UserPath}
+	}
+	return nil
+}
+
+// overridden reports whether a layer above the user file set key; the
+// caller holds s.mu
+func (s *ConfigStore) overridden(key string) bool {
+	switch s.origins[key].source {
+	case ConfigProject, ConfigEnv, ConfigFlag:
+		return true
+	}
+	return false
+}
+
+// userLayer is the layer read from and saved to the user file; the caller
+// holds s.mu
+func (s *ConfigStore) userLayer() *configLayer {
+	for i := range s.layers {
+		if s.layers[i].source == ConfigUser {
+			return &s.layers[i]
+		}
+	}
+	panic("config has no user layer")
+}
+
+// Values returns every setting with the layer it came from, by key, with
+// API keys masked
+func (s *ConfigStore) Values() []ConfigValue {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	var values []ConfigValue
+	configLeaves(s.resolved, func(key string, value any) {
+		origin, ok := s.origins[key]
+		if !ok {
+			origin = configOrigin{source: ConfigDefault}
+		}
+		values = append(values, ConfigValue{Key: key, Value: maskedConfigValue(value), Source: origin.source, Origin: origin.origin})
+	})
+	return values
+}
+
+// applyConfig sets up the manager as config describes. Providers are only
+// rebuilt when they changed, and without checking their model, which would
+// need the network at startup.
+func (a *App) applyConfig(config AppConfig) {
+	if !reflect.DeepEqual(config.Providers, a.manager.Configs()) {
+		a.manager.SetProviders(config.Providers)
+	}
+	if config.ActiveProvider >= 0 {
+		a.manager.SetActive(config.ActiveProvider)
+	}
+	a.manager.SetOffline(config.Offline)
+	a.manager.SetRedactionMode(config.Redaction)
+	a.manager.RequestLog().SetMode(config.RequestLog)
+	a.manager.SetContextCompression(config.CompressHistory)
+	a.history.SetRetention(config.General.historyRetention())
+}
+
+// saveConfig records the manager's current settings in the config file.
+// Only what the user file would set is written there: settings from the
+// command line stay this run's.
+func (a *App) saveConfig() {
+	err := a.config.Update(func(config *AppConfig) {
+		config.Providers = a.manager.Configs()
+		config.ActiveProvider = a.manager.ActiveIndex()
+		config.Offline = a.manager.Offline()
+		config.Redaction = a.manager.RedactionMode()
+		config.RequestLog = a.manager.RequestLog().Mode()
+		config.CompressHistory = a.manager.ContextCompression()
+	})
+	if err != nil {
+		a.logs.Warning(fmt.Sprintf("failed to save config: %v", err))
+	}
+}
+
+// GetConfigValues returns every setting with the layer that set it: default,
+// system, user, project, env or flag
+func (a *App) GetConfigValues() []ConfigValue {
+	return a.config.Values()
+}
+
+// GetUIConfig returns the saved theme, style and font settings
+func (a *App) GetUIConfig() UIConfig {
+	return a.config.Get().UI
+}
+
+// SetUIConfig saves the theme, style and font settings, telling the
+// frontend when the theme changed. Custom palettes are only set in
+// config.toml, so the ones there are kept.
+func (a *App) SetUIConfig(ui UIConfig) error {
+	current := a.config.Get().UI
+	ui.Palettes = current.Palettes
+	if err := ui.Validate(); err != nil {
+		return err
+	}
+	if reflect.DeepEqual(current, ui) {
+		return nil
+	}
+	err := a.config.Update(func(config *AppConfig) {
+		config.UI = ui
+	})
+	if err == nil && ui.Theme != current.Theme {
+		a.emitTheme(ui.theme())
+	}
+	return err
+}
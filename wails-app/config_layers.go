@@ -0,0 +1,468 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"reflect"
+	"runtime"
+	"slices"
+	"sort"
+	"strings"
+	"unicode"
+)
+
+// Config layers, lowest precedence first
+const (
+	ConfigDefault = "default"
+	ConfigSystem  = "system"
+	ConfigUser    = "user"
+	ConfigProject = "project"
+	ConfigEnv     = "env"
+	ConfigFlag    = "flag"
+)
+
+// projectConfigName is the file a project's own settings are read from, in
+// the working directory or the nearest directory above it
+const projectConfigName = ".vibe-coder.toml"
+
+// confinedConfigTables are the only settings the project file and the
+// environment may set. A checkout can change how the app looks and what it
+// keeps, but not the providers, their endpoints or keys, or the privacy
+// switches.
+var confinedConfigTables = []string{"general", "ui"}
+
+// configEnvPrefix starts the environment variables that override settings,
+// e.g. VIBE_CODER_UI_FONT_SIZE=16 for ui.fontSize
+const configEnvPrefix = "VIBE_CODER_"
+
+// ConfigSources are where each layer of the config is read from
+type ConfigSources struct {
+	SystemPath  string   // empty for none
+	UserPath    string   // also where changes are saved
+	ProjectPath string   // empty for none
+	Env         []string // KEY=value pairs, as os.Environ returns them
+	Args        []string // command-line arguments; --set key=value overrides a setting
+}
+
+// DefaultConfigSources reads the platform's system and user config files,
+// the project file nearest the working directory, the environment and the
+// command line
+func DefaultConfigSources() ConfigSources {
+	return ConfigSources{
+		SystemPath:  systemConfigPath(),
+		UserPath:    appDataPath("config.toml"),
+		ProjectPath: findProjectConfig(),
+		Env:         os.Environ(),
+		Args:        os.Args[1:],
+	}
+}
+
+// systemConfigPath is where an administrator sets defaults for every user
+func systemConfigPath() string {
+	switch runtime.GOOS {
+	case "windows":
+		if dir := os.Getenv("ProgramData"); dir != "" {
+			return filepath.Join(dir, "vibe-coder", "config.toml")
+		}
+		return ""
+	case "darwin":
+		return "/Library/Application Support/vibe-coder/config.toml"
+	default:
+		return "/etc/vibe-coder/config.toml"
+	}
+}
+
+func findProjectConfig() string {
+	dir, err := os.Getwd()
+	if err != nil {
+		return ""
+	}
+	for {
+		path := filepath.Join(dir, projectConfigName)
+		if _, err := os.Stat(path); err == nil {
+			return path
+		}
+		parent := filepath.Dir(dir)
+		if parent == dir {
+			return ""
+		}
+		dir = parent
+	}
+}
+
+// ConfigValue is one resolved setting and the layer it came from
+type ConfigValue struct {
+	Key    string `json:"key"` // dotted, e.g. "ui.fontSize"
+	Value  any    `json:"value"`
+	Source string `json:"source"`           // ConfigDefault through ConfigFlag
+	Origin string `json:"origin,omitempty"` // the file, variable or flag that set it
+}
+
+type configOrigin struct {
+	source, origin string
+}
+
+// configLayer is one layer's settings as a tree of TOML values
+type configLayer struct {
+	source   string
+	path     string            // the file it was read from, for file layers
+	values   map[string]any    // nil when there are none
+	origins  map[string]string // what set each key, for the env and flag layers
+	previous map[string]any    // the values last in use, kept when these aren't
+}
+
+func (l configLayer) describe() string {
+	switch {
+	case l.path != "":
+		return l.path
+	case l.source == ConfigEnv:
+		return "environment"
+	case l.source == ConfigFlag:
+		return "command line"
+	}
+	return l.source + " config"
+}
+
+func (l configLayer) originOf(key string) configOrigin {
+	if origin, ok := l.origins[key]; ok {
+		return configOrigin{source: l.source, origin: origin}
+	}
+	return configOrigin{source: l.source, origin: l.path}
+}
+
+// layers reads every layer, lowest first. A file that can't be read keeps
+// the values it had in previous, the layers last read, if any.
+func (c ConfigSources) layers(previous []configLayer) ([]configLayer, []error) {
+	var errs []error
+	defaults := configTree(DefaultAppConfig())
+	layers := []configLayer{{source: ConfigDefault, values: defaults}}
+	for _, file := range []struct{ source, path string }{
+		{ConfigSystem, c.SystemPath},
+		{ConfigUser, c.UserPath},
+		{ConfigProject, c.ProjectPath},
+	} {
+		layer := configLayer{source: file.source, path: file.path}
+		if i := len(layers); i < len(previous) {
+			layer.previous = previous[i].values
+		}
+		values, err := readConfigFile(file.path)
+		if err != nil {
+			errs = append(errs, err)
+			values = layer.previous
+		} else if file.source == ConfigProject {
+			values = confineConfigFile(file.path, values, &errs)
+		}
+		layer.values = values
+		layers = append(layers, layer)
+	}
+
+	env := configLayer{source: ConfigEnv, values: map[string]any{}, origins: map[string]string{}}
+	vars := make(map[string]string)
+	for _, pair := range c.Env {
+		if name, value, ok := strings.Cut(pair, "="); ok {
+			vars[name] = value
+		}
+	}
+	configLeaves(defaults, func(key string, like any) {
+		name := configEnvName(key)
+		raw, ok := vars[name]
+		if !ok {
+			return
+		}
+		if !confinedConfigKey(key) {
+			errs = append(errs, fmt.Errorf("%s: only %s settings can be set from the environment", name, strings.Join(confinedConfigTables, " and ")))
+			return
+		}
+		value, err := parseConfigValue(raw, like)
+		if err != nil {
+			errs = append(errs, fmt.Errorf("%s: %w", name, err))
+			return
+		}
+		setConfigValue(env.values, key, value)
+		env.origins[key] = name
+	})
+
+	flags := configLayer{source: ConfigFlag, values: map[string]any{}, origins: map[string]string{}}
+	for i := 0; i < len(c.Args); i++ {
+		var setting string
+		switch arg := c.Args[i]; {
+		case arg == "--set" && i+1 < len(c.Args):
+			i++
+			setting = c.Args[i]
+		case strings.HasPrefix(arg, "--set="):
+			setting = strings.TrimPrefix(arg, "--set=")
+		default:
+			continue // not ours
+		}
+		key, raw, ok := strings.Cut(setting, "=")
+		like, known := configValueAt(defaults, key)
+		if _, table := like.(map[string]any); !ok || !known || table {
+			errs = append(errs, fmt.Errorf("--set %s: expected a setting=value, such as ui.theme=light", setting))
+			continue
+		}
+		value, err := parseConfigValue(raw, like)
+		if err != nil {
+			errs = append(errs, fmt.Errorf("--set %s: %w", key, err))
+			continue
+		}
+		setConfigValue(flags.values, key, value)
+		flags.origins[key] = "--set " + key
+	}
+	return append(layers, env, flags), errs
+}
+
+// confinedConfigKey reports whether the project file and the environment
+// may set a dotted key
+func confinedConfigKey(key string) bool {
+	for _, table := range confinedConfigTables {
+		if strings.HasPrefix(key, table+".") {
+			return true
+		}
+	}
+	return false
+}
+
+// confineConfigFile drops the settings of a project file outside
+// confinedConfigTables, reporting each one
+func confineConfigFile(path string, values map[string]any, errs *[]error) map[string]any {
+	if values == nil {
+		return nil
+	}
+	confined := make(map[string]any)
+	for name, value := range values {
+		if _, table := value.(map[string]any); table && slices.Contains(confinedConfigTables, name) {
+			confined[name] = value
+			continue
+		}
+		*errs = append(*errs, fmt.Errorf("%s: ignoring %s; a project config can only set %s", path, name, strings.Join(confinedConfigTables, " and ")))
+	}
+	return confined
+}
+
+// readConfigFile reads a TOML file's settings; a missing file, or none, has
+// none
+func readConfigFile(path string) (map[string]any, error) {
+	if path == "" {
+		return nil, nil
+	}
+	data, err := os.ReadFile(path)
+	if errors.Is(err, os.ErrNotExist) {
+		return nil, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	values, err := parseTOML(data)
+	if err != nil {
+		return nil, fmt.Errorf("%s: %w", path, err)
+	}
+	return values, nil
+}
+
+// writeConfigFile writes settings to path atomically. API keys are written
+// as given, so the file is readable only by the user.
+func writeConfigFile(path string, values map[string]any) error {
+	if err := os.MkdirAll(filepath.Dir(path), 0o700); err != nil {
+		return err
+	}
+	data, err := MarshalTOML(values)
+	if err != nil {
+		return err
+	}
+	tmp := path + ".tmp"
+	if err := os.WriteFile(tmp, data, 0o600); err != nil {
+		return err
+	}
+	return os.Rename(tmp, path)
+}
+
+// resolveConfig merges the layers over each other, lowest first, recording
+// where each setting came from. A layer that would leave the config invalid
+// is reported and its previous values used instead, or none; each layer's
+// values are left as the ones used.
+func resolveConfig(layers []configLayer) (AppConfig, map[string]configOrigin, []error) {
+	var errs []error
+	var config AppConfig
+	merged := map[string]any{}
+	origins := map[string]configOrigin{}
+	for i := range layers {
+		layer := &layers[i]
+		candidates := []map[string]any{layer.values}
+		if layer.previous != nil && !reflect.DeepEqual(layer.previous, layer.values) {
+			candidates = append(candidates, layer.previous)
+		}
+		var used map[string]any
+		for attempt, values := range candidates {
+			next, nextOrigins := copyConfigTree(merged), make(map[string]configOrigin, len(origins))
+			for key, origin := range origins {
+				nextOrigins[key] = origin
+			}
+			mergeConfigTree(next, values, "", func(key string) { nextOrigins[key] = layer.originOf(key) })
+			resolved, err := decodeConfig(next)
+			if err != nil {
+				if attempt == 0 {
+					errs = append(errs, fmt.Errorf("%s: %w", layer.describe(), err))
+				}
+				continue
+			}
+			config, merged, origins, used = resolved, next, nextOrigins, values
+			break
+		}
+		layer.values = used
+	}
+	return config, origins, errs
+}
+
+func decodeConfig(tree map[string]any) (AppConfig, error) {
+	data, err := json.Marshal(tree)
+	if err != nil {
+		return AppConfig{}, err
+	}
+	var config AppConfig
+	if err := json.Unmarshal(data, &config); err != nil {
+		return AppConfig{}, err
+	}
+	return config, config.Validate()
+}
+
+// configTree is config as a tree of JSON values keyed like the config file
+func configTree(config AppConfig) map[string]any {
+	tree := map[string]any{}
+	// An AppConfig always encodes, and as an object
+	data, _ := json.Marshal(config)
+	json.Unmarshal(data, &tree)
+	return tree
+}
+
+// mergeConfigTree copies from's settings into tree, merging tables and
+// replacing everything else, arrays included, calling set with each key set
+func mergeConfigTree(tree, from map[string]any, prefix string, set func(key string)) {
+	for name, value := range from {
+		key := prefix + name
+		if table, ok := value.(map[string]any); ok {
+			existing, ok := tree[name].(map[string]any)
+			if !ok {
+				existing = map[string]any{}
+				tree[name] = existing
+			}
+			mergeConfigTree(existing, table, key+".", set)
+			continue
+		}
+		tree[name] = value
+		set(key)
+	}
+}
+
+func copyConfigTree(tree map[string]any) map[string]any {
+	copied := make(map[string]any, len(tree))
+	for name, value := range tree {
+		if table, ok := value.(map[string]any); ok {
+			value = copyConfigTree(table)
+		}
+		copied[name] = value
+	}
+	return copied
+}
+
+// configLeaves calls visit with each setting in tree by dotted key, in key
+// order; arrays are single settings
+func configLeaves(tree map[string]any, visit func(key string, value any)) {
+	var walk func(tree map[string]any, prefix string)
+	walk = func(tree map[string]any, prefix string) {
+		names := make([]string, 0, len(tree))
+		for name := range tree {
+			names = append(names, name)
+		}
+		sort.Strings(names)
+		for _, name := range names {
+			if table, ok := tree[name].(map[string]any); ok {
+				walk(table, prefix+name+".")
+			} else {
+				visit(prefix+name, tree[name])
+			}
+		}
+	}
+	walk(tree, "")
+}
+
+// configValueAt looks up a dotted key in tree
+func configValueAt(tree map[string]any, key string) (any, bool) {
+	var value any = tree
+	for _, name := range strings.Split(key, ".") {
+		table, ok := value.(map[string]any)
+		if !ok {
+			return nil, false
+		}
+		if value, ok = table[name]; !ok {
+			return nil, false
+		}
+	}
+	return value, true
+}
+
+// setConfigValue sets a dotted key in tree, creating tables on the way
+func setConfigValue(tree map[string]any, key string, value any) {
+	names := strings.Split(key, ".")
+	for _, name := range names[:len(names)-1] {
+		table, ok := tree[name].(map[string]any)
+		if !ok {
+			table = map[string]any{}
+			tree[name] = table
+		}
+		tree = table
+	}
+	tree[names[len(names)-1]] = value
+}
+
+// configEnvName is the variable overriding a setting: ui.fontSize is
+// VIBE_CODER_UI_FONT_SIZE
+func configEnvName(key string) string {
+	var name strings.Builder
+	name.WriteString(configEnvPrefix)
+	for i, r := range key {
+		switch {
+		case r == '.':
+			name.WriteByte('_')
+		case unicode.IsUpper(r) && i > 0 && key[i-1] != '.':
+			name.WriteByte('_')
+			name.WriteRune(r)
+		default:
+			name.WriteRune(unicode.ToUpper(r))
+		}
+	}
+	return name.String()
+}
+
+// parseConfigValue reads a setting given as text: as is where like, its
+// default, is a string, and as a TOML value otherwise
+func parseConfigValue(raw string, like any) (any, error) {
+	if _, ok := like.(string); ok {
+		return raw, nil
+	}
+	return parseTOMLValue(raw)
+}
+
+// maskedConfigValue hides the API keys in a setting's value
+func maskedConfigValue(value any) any {
+	switch value := value.(type) {
+	case []any:
+		masked := make([]any, len(value))
+		for i, item := range value {
+			masked[i] = maskedConfigValue(item)
+		}
+		return masked
+	case map[string]any:
+		masked := make(map[string]any, len(value))
+		for name, item := range value {
+			if name == "apiKey" && item != "" {
+				item = "********"
+			}
+			masked[name] = maskedConfigValue(item)
+		}
+		return masked
+	}
+	return value
+}
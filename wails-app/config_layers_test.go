@@ -0,0 +1,158 @@
+package main
+
+import (
+	"os"
+	"path/filepath"
+	"reflect"
+	"testing"
+)
+
+// writeConfig writes a config file into dir and returns its path
+func writeConfig(t *testing.T, dir, name, content string) string {
+	t.Helper()
+	path := filepath.Join(dir, name)
+	if err := os.WriteFile(path, []byte(content), 0o600); err != nil {
+		t.Fatal(err)
+	}
+	return path
+}
+
+// configSource returns the layer a setting came from
+func configSource(store *ConfigStore, key string) string {
+	for _, value := range store.Values() {
+		if value.Key == key {
+			return value.Source
+		}
+	}
+	return ""
+}
+
+func TestConfigStoreLayers(t *testing.T) {
+	tests := []struct {
+		name       string
+		system     string
+		user       string
+		project    string
+		env        []string
+		args       []string
+		wantSize   int
+		wantSource string
+	}{
+		{name: "defaults", wantSize: 14, wantSource: ConfigDefault},
+		{name: "system", system: "[ui]\nfontSize = 11\n", wantSize: 11, wantSource: ConfigSystem},
+		{
+			name:   "user over system",
+			system: "[ui]\nfontSize = 11\n", user: "[ui]\nfontSize = 12\n",
+			wantSize: 12, wantSource: ConfigUser,
+		},
+		{
+			name:   "project over user",
+			system: "[ui]\nfontSize = 11\n", user: "[ui]\nfontSize = 12\n", project: "[ui]\nfontSize = 13\n",
+			wantSize: 13, wantSource: ConfigProject,
+		},
+		{
+			name: "environment over project",
+			user: "[ui]\nfontSize = 12\n", project: "[ui]\nfontSize = 13\n",
+			env:      []string{"VIBE_CODER_UI_FONT_SIZE=15", "UNRELATED=1"},
+			wantSize: 15, wantSource: ConfigEnv,
+		},
+		{
+			name:     "flag over environment",
+			env:      []string{"VIBE_CODER_UI_FONT_SIZE=15"},
+			args:     []string{"--set", "ui.fontSize=16"},
+			wantSize: 16, wantSource: ConfigFlag,
+		},
+		{
+			name:     "the last flag wins",
+			args:     []string{"--set", "ui.fontSize=16", "--verbose", "--set=ui.fontSize=17"},
+			wantSize: 17, wantSource: ConfigFlag,
+		},
+		{
+			name:   "an invalid layer is left out",
+			system: "[ui]\nfontSize = 11\n", user: "[ui]\nfontSize = 99\n",
+			wantSize: 11, wantSource: ConfigSystem,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			dir := t.TempDir()
+			sources := ConfigSources{UserPath: filepath.Join(dir, "config.toml"), Env: tt.env, Args: tt.args}
+			if tt.system != "" {
+				sources.SystemPath = writeConfig(t, dir, "system.toml", tt.system)
+			}
+			if tt.user != "" {
+				sources.UserPath = writeConfig(t, dir, "config.toml", tt.user)
+			}
+			if tt.project != "" {
+				sources.ProjectPath = writeConfig(t, dir, projectConfigName, tt.project)
+			}
+			store := NewConfigStore(sources)
+			if got := store.Get().UI.FontSize; got != tt.wantSize {
+				t.Errorf("font size %d, want %d", got, tt.wantSize)
+			}
+			if got := configSource(store, "ui.fontSize"); got != tt.wantSource {
+				t.Errorf("font size from %q, want %q", got, tt.wantSource)
+			}
+		})
+	}
+}
+
+func TestConfigStoreConfinement(t *testing.T) {
+	tests := []struct {
+		name        string
+		user        string
+		project     string
+		env         []string
+		wantOffline bool
+	}{
+		{name: "user file", user: "offline = true\n", wantOffline: true},
+		{name: "project file", project: "offline = true\n[ui]\nfontSize = 13\n"},
+		{name: "environment", env: []string{"VIBE_CODER_OFFLINE=true"}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			dir := t.TempDir()
+			sources := ConfigSources{UserPath: filepath.Join(dir, "config.toml"), Env: tt.env}
+			if tt.user != "" {
+				sources.UserPath = writeConfig(t, dir, "config.toml", tt.user)
+			}
+			if tt.project != "" {
+				sources.ProjectPath = writeConfig(t, dir, projectConfigName, tt.project)
+			}
+			if got := NewConfigStore(sources).Get().Offline; got != tt.wantOffline {
+				t.Errorf("offline %v, want %v", got, tt.wantOffline)
+			}
+		})
+	}
+}
+
+func TestConfigStoreUpdate(t *testing.T) {
+	dir := t.TempDir()
+	store := NewConfigStore(ConfigSources{
+		UserPath: writeConfig(t, dir, "config.toml", "[ui]\ntheme = \"light\"\n"),
+		Env:      []string{"VIBE_CODER_UI_FONT_SIZE=16"},
+	})
+	err := store.Update(func(config *AppConfig) {
+		config.UI.FontFamily = "Fira Code"
+		config.UI.FontSize = 20
+	})
+	if err != nil {
+		t.Fatal(err)
+	}
+	// The environment set the font size, so it changes for this run but
+	// isn't written to the user's file
+	got, err := readConfigFile(filepath.Join(dir, "config.toml"))
+	if err != nil {
+		t.Fatal(err)
+	}
+	want := map[string]any{"ui": map[string]any{"theme": "light", "fontFamily": "Fira Code"}}
+	if !reflect.DeepEqual(got, want) {
+		t.Errorf("saved %v, want %v", got, want)
+	}
+	if got := store.Get().UI.FontSize; got != 20 {
+		t.Errorf("font size %d, want 20", got)
+	}
+	if err := store.Update(func(config *AppConfig) { config.UI.FontSize = 99 }); err == nil {
+		t.Error("an invalid font size was saved")
+	}
+}
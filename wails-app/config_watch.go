@@ -0,0 +1,92 @@
+package main
+
+import (
+	"context"
+	"os"
+	"time"
+
+	wailsruntime "github.com/wailsapp/wails/v2/pkg/runtime"
+)
+
+// configPollInterval is how often Watch looks for edits to the config file
+const configPollInterval = 2 * time.Second
+
+// stampOf stamps the file at path; a missing file has the zero stamp
+func stampOf(path string) fileStamp {
+	info, err := os.Stat(path)
+	if err != nil {
+		return fileStamp{}
+	}
+	return fileStamp{size: info.Size(), modified: info.ModTime().UnixNano()}
+}
+
+// configStamps stamp the system, user and project files
+type configStamps [3]fileStamp
+
+func (c ConfigSources) stamps() configStamps {
+	return configStamps{stampOf(c.SystemPath), stampOf(c.UserPath), stampOf(c.ProjectPath)}
+}
+
+// Watch reloads the config when one of its files is edited outside the app,
+// until ctx ends. Like plugin files, an edit is only picked up once the
+// files read the same on two polls in a row. reloaded is called with each
+// config loaded, and report with files that don't load, which keep the
+// settings they had.
+func (s *ConfigStore) Watch(ctx context.Context, reloaded func(AppConfig), report func(error)) {
+	s.mu.Lock()
+	seen := s.stamps
+	s.mu.Unlock()
+
+	ticker := time.NewTicker(configPollInterval)
+	defer ticker.Stop()
+	for {
+		select {
+		case <-ctx.Done():
+			return
+		case <-ticker.C:
+		}
+		current := s.sources.stamps()
+		stable := current == seen
+		seen = current
+		if !stable {
+			continue
+		}
+		config, changed, errs := s.reload(current)
+		for _, err := range errs {
+			report(err)
+		}
+		if changed {
+			reloaded(config)
+		}
+	}
+}
+
+// reload resolves the config again unless the files are as last loaded or
+// saved, which includes the app's own saves
+func (s *ConfigStore) reload(stamps configStamps) (AppConfig, bool, []error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	if stamps == s.stamps {
+		return AppConfig{}, false, nil
+	}
+	errs := s.load() // a broken file is reported once, not on every poll
+	config := s.config
+	config.Providers = append([]ProviderConfig{}, s.config.Providers...)
+	return config, true, errs
+}
+
+// configReloaded applies a config edited on disk and tells the frontend,
+// which picks up the new look from the event and re-reads the rest
+func (a *App) configReloaded(config AppConfig) {
+	a.applyConfig(config)
+	a.logs.Info("reloaded the config")
+	if a.ctx != nil {
+		wailsruntime.EventsEmit(a.ctx, configReloadedEvent, config.UI)
+		a.emitTheme(config.UI.theme())
+	}
+	goSafe(func() {
+		a.manager.CheckHealth()
+		a.emitHealth()
+	})
+}
@@ -0,0 +1,130 @@
+package main
+
+import (
+	"strings"
+	"sync"
+)
+
+// ModelPrice is the USD price per million tokens
+type ModelPrice struct {
+	Prompt     float64 `json:"prompt"`
+	Completion float64 `json:"completion"`
+}
+
+// Prices for known hosted models, matched by name prefix (longest first wins).
+// Local providers are always free.
+var modelPrices = map[string]ModelPrice{
+	"gpt-4o":            {Prompt: 2.50, Completion: 10.00},
+	"gpt-4o-mini":       {Prompt: 0.15, Completion: 0.60},
+	"gpt-4":             {Prompt: 30.00, Completion: 60.00},
+	"gpt-3.5":           {Prompt: 0.50, Completion: 1.50},
+	"claude-3-opus":     {Prompt: 15.00, Completion: 75.00},
+	"claude-3-sonnet":   {Prompt: 3.00, Completion: 15.00},
+	"claude-3-5-sonnet": {Prompt: 3.00, Completion: 15.00},
+	"claude-3-haiku":    {Prompt: 0.25, Completion: 1.25},
+	"gemini-pro":        {Prompt: 0.50, Completion: 1.50},
+	"gemini-1.5-pro":    {Prompt: 1.25, Completion: 5.00},
+	"gemini-1.5-flash":  {Prompt: 0.075, Completion: 0.30},
+}
+
+var localProviderTypes = map[string]bool{
+	"Ollama":   true,
+	"LlamaCpp": true,
+	"Mock":     true,
+}
+
+// UsageTotals accumulates token usage and spend
+type UsageTotals struct {
+	Requests         int     `json:"requests"`
+	PromptTokens     int     `json:"promptTokens"`
+	CompletionTokens int     `json:"completionTokens"`
+	Cost             float64 `json:"cost"`
+}
+
+func (t *UsageTotals) add(response AIResponse) {
+	t.Requests++
+	t.PromptTokens += response.PromptTokens
+	t.CompletionTokens += response.CompletionTokens
+	t.Cost += response.Cost
+}
+
+func (t *UsageTotals) merge(other UsageTotals) {
+	t.Requests += other.Requests
+	t.PromptTokens += other.PromptTokens
+	t.CompletionTokens += other.CompletionTokens
+	t.Cost += other.Cost
+}
+
+// CostSummary is the spend for the current session, overall and per provider
+type CostSummary struct {
+	Session    UsageTotals            `json:"session"`
+	ByProvider map[string]UsageTotals `json:"byProvider"`
+}
+
+// CostTracker prices responses and accumulates per-session and per-provider spend
+type CostTracker struct {
+	mu         sync.Mutex
+	session    UsageTotals
+	byProvider map[string]UsageTotals
+}
+
+func NewCostTracker() *CostTracker {
+	return &CostTracker{byProvider: make(map[string]UsageTotals)}
+}
+
+// PriceFor returns the price of a model served by the given provider type
+func PriceFor(providerType, model string) ModelPrice {
+	if localProviderTypes[providerType] {
+		return ModelPrice{}
+	}
+
+	name := strings.ToLower(model)
+	if i := strings.LastIndex(name, "/"); i >= 0 {
+		name = name[i+1:]
+	}
+
+	best, price := 0, ModelPrice{}
+	for prefix, p := range modelPrices {
+		if strings.HasPrefix(name, prefix) && len(prefix) > best {
+			best, price = len(prefix), p
+		}
+	}
+	return price
+}
+
+// Record prices the response, adds it to the totals and returns it with Cost set
+func (c *CostTracker) Record(config ProviderConfig, response AIResponse) AIResponse {
+	price := PriceFor(config.Type, response.Model)
+	response.Cost = (float64(response.PromptTokens)*price.Prompt + float64(response.CompletionTokens)*price.Completion) / 1e6
+
+	c.mu.Lock()
+	defer c.mu.Unlock()
+
+	c.session.add(response)
+	totals := c.byProvider[response.Provider]
+	totals.add(response)
+	c.byProvider[response.Provider] = totals
+
+	return response
+}
+
+// Summary returns a snapshot of the accumulated totals
+func (c *CostTracker) Summary() CostSummary {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+
+	summary := CostSummary{Session: c.session, ByProvider: make(map[string]UsageTotals, len(c.byProvider))}
+	for name, totals := range c.byProvider {
+		summary.ByProvider[name] = totals
+	}
+	return summary
+}
+
+// ResetSession clears all accumulated totals
+func (c *CostTracker) ResetSession() {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+
+	c.session = UsageTotals{}
+	c.byProvider = make(map[string]UsageTotals)
+}
@@ -0,0 +1,281 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"net/url"
+	"os"
+	"path/filepath"
+	"regexp"
+	"runtime"
+	"runtime/debug"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+
+	"github.com/wailsapp/wails/v2/pkg/logger"
+	wailsruntime "github.com/wailsapp/wails/v2/pkg/runtime"
+)
+
+const appVersion = "0.1.0"
+
+const crashIssueURL = "https://github.com/aavishay/vibe-coder/issues/new"
+
+// Issue links are kept to crashIssueURLLimit bytes, with the panic in the
+// body cut to crashPanicLength characters
+const (
+	crashIssueURLLimit = 8000
+	crashPanicLength   = 500
+)
+
+// Patterns scrubbed from crash reports before they are written to disk
+var crashRedactions = []*regexp.Regexp{
+	regexp.MustCompile(`(?i)(api[_-]?key|token|secret|password)(["']?\s*[:=]\s*["']?)[^\s"',}]+`),
+	regexp.MustCompile(`(?i)bearer\s+[A-Za-z0-9._\-]+`),
+	regexp.MustCompile(`sk-[A-Za-z0-9_\-]{16,}`),
+	regexp.MustCompile(`AKIA[0-9A-Z]{16}`),
+}
+
+func redactCrashText(text string) string {
+	for _, re := range crashRedactions {
+		text = re.ReplaceAllStringFunc(text, func(match string) string {
+			if sub := re.FindStringSubmatch(match); len(sub) == 3 {
+				return sub[1] + sub[2] + "[REDACTED]"
+			}
+			return "[REDACTED]"
+		})
+	}
+	return text
+}
+
+// LogBuffer is a Wails logger that forwards to the default logger and keeps the most recent lines
+type LogBuffer struct {
+	mu     sync.Mutex
+	lines  []string
+	limit  int
+	output logger.Logger
+}
+
+func NewLogBuffer(limit int) *LogBuffer {
+	return &LogBuffer{
+		lines:  make([]string, 0, limit),
+		limit:  limit,
+		output: logger.NewDefaultLogger(),
+	}
+}
+
+func (l *LogBuffer) record(level, message string) {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+
+	line := fmt.Sprintf("%s %-7s %s", time.Now().Format(time.RFC3339), level, message)
+	if len(l.lines) == l.limit {
+		l.lines = l.lines[1:]
+	}
+	l.lines = append(l.lines, line)
+}
+
+// Recent returns up to n of the most recent log lines
+func (l *LogBuffer) Recent(n int) []string {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+
+	if n > len(l.lines) {
+		n = len(l.lines)
+	}
+	recent := make([]string, n)
+	copy(recent, l.lines[len(l.lines)-n:])
+	return recent
+}
+
+func (l *LogBuffer) Print(message string)   { l.record("PRINT", message); l.output.Print(message) }
+func (l *LogBuffer) Trace(message string)   { l.record("TRACE", message); l.output.Trace(message) }
+func (l *LogBuffer) Debug(message string)   { l.record("DEBUG", message); l.output.Debug(message) }
+func (l *LogBuffer) Info(message string)    { l.record("INFO", message); l.output.Info(message) }
+func (l *LogBuffer) Warning(message string) { l.record("WARNING", message); l.output.Warning(message) }
+func (l *LogBuffer) Error(message string)   { l.record("ERROR", message); l.output.Error(message) }
+func (l *LogBuffer) Fatal(message string)   { l.record("FATAL", message); l.output.Fatal(message) }
+
+// CrashReport is a redacted snapshot of the application state at the time of a panic.
+// Prompt and response contents are never included.
+type CrashReport struct {
+	ID                 string   `json:"id"`
+	Time               string   `json:"time"`
+	AppVersion         string   `json:"appVersion"`
+	Platform           string   `json:"platform"`
+	GoVersion          string   `json:"goVersion"`
+	ActiveProviderType string   `json:"activeProviderType"`
+	Panic              string   `json:"panic"`
+	Backtrace          string   `json:"backtrace"`
+	RecentLogs         []string `json:"recentLogs"`
+}
+
+// CrashReporter writes crash reports to the user config directory and surfaces them on next launch
+type CrashReporter struct {
+	app *App
+	dir string
+}
+
+func NewCrashReporter(app *App) *CrashReporter {
+	return &CrashReporter{app: app, dir: appDataPath("crash-reports")}
+}
+
+// crashes is the reporter goroutines started with goSafe report panics to,
+// set once the App has one
+var crashes *CrashReporter
+
+// goSafe runs f in a new goroutine, writing a crash report if it panics
+// before the panic ends the app, as the bound methods do. Every goroutine
+// the app starts goes through it.
+func goSafe(f func()) {
+	go func() {
+		if crashes != nil {
+			defer crashes.Capture()
+		}
+		f()
+	}()
+}
+
+// Capture must be deferred; it records a crash report for an in-flight panic and re-panics
+func (c *CrashReporter) Capture() {
+	r := recover()
+	if r == nil {
+		return
+	}
+	if err := c.write(r, debug.Stack()); err != nil {
+		fmt.Fprintf(os.Stderr, "failed to write crash report: %v\n", err)
+	}
+	panic(r)
+}
+
+func (c *CrashReporter) write(value interface{}, stack []byte) error {
+	now := time.Now()
+	report := CrashReport{
+		ID:                 now.UTC().Format("20060102T150405.000000000Z"),
+		Time:               now.Format(time.RFC3339),
+		AppVersion:         appVersion,
+		Platform:           runtime.GOOS + "/" + runtime.GOARCH,
+		GoVersion:          runtime.Version(),
+		ActiveProviderType: c.app.activeProviderType(),
+		Panic:              redactCrashText(fmt.Sprint(value)),
+		Backtrace:          string(stack),
+		RecentLogs:         make([]string, 0),
+	}
+	for _, line := range c.app.logs.Recent(50) {
+		report.RecentLogs = append(report.RecentLogs, redactCrashText(line))
+	}
+
+	if err := os.MkdirAll(c.dir, 0o700); err != nil {
+		return err
+	}
+	data, err := json.MarshalIndent(report, "", "  ")
+	if err != nil {
+		return err
+	}
+	return os.WriteFile(c.path(report.ID), data, 0o600)
+}
+
+func (c *CrashReporter) path(id string) string {
+	return filepath.Join(c.dir, "crash-"+filepath.Base(id)+".json")
+}
+
+// Pending returns the most recent crash report that has not been submitted or dismissed
+func (c *CrashReporter) Pending() (*CrashReport, error) {
+	matches, err := filepath.Glob(filepath.Join(c.dir, "crash-*.json"))
+	if err != nil || len(matches) == 0 {
+		return nil, err
+	}
+	sort.Strings(matches)
+
+	data, err := os.ReadFile(matches[len(matches)-1])
+	if err != nil {
+		return nil, err
+	}
+	var report CrashReport
+	if err := json.Unmarshal(data, &report); err != nil {
+		return nil, fmt.Errorf("invalid crash report: %v", err)
+	}
+	return &report, nil
+}
+
+// Dismiss removes a crash report from disk
+func (c *CrashReporter) Dismiss(id string) error {
+	if err := os.Remove(c.path(id)); err != nil && !os.IsNotExist(err) {
+		return err
+	}
+	return nil
+}
+
+// IssueURL builds a prefilled GitHub issue for the report. GitHub turns
+// away links much over 8 KB, so when the whole report doesn't fit the oldest
+// log lines are cut first and then the bottom of the backtrace, keeping its
+// top frames.
+func (c *CrashReporter) IssueURL(report *CrashReport) string {
+	backtrace := strings.Split(strings.TrimRight(report.Backtrace, "\n"), "\n")
+	logs := report.RecentLogs
+	for cut := 0; ; cut++ {
+		link := crashIssueLink(report, backtrace, logs, cut)
+		if len(link) <= crashIssueURLLimit || len(backtrace) <= 1 {
+			return link
+		}
+		if len(logs) > 0 {
+			logs = logs[1:]
+		} else {
+			backtrace = backtrace[:len(backtrace)-1]
+		}
+	}
+}
+
+// crashIssueLink is the issue link for the report with only the backtrace
+// and log lines given, noting how many were cut
+func crashIssueLink(report *CrashReport, backtrace, logs []string, cut int) string {
+	panicText := report.Panic
+	if runes := []rune(panicText); len(runes) > crashPanicLength {
+		panicText = string(runes[:crashPanicLength-1]) + "…"
+	}
+	var body strings.Builder
+	fmt.Fprintf(&body, "**Version:** %s\n**Platform:** %s (%s)\n**Provider type:** %s\n**Panic:** %s\n\n",
+		report.AppVersion, report.Platform, report.GoVersion, report.ActiveProviderType, panicText)
+	fmt.Fprintf(&body, "<details><summary>Backtrace</summary>\n\n```\n%s\n```\n</details>\n\n", strings.Join(backtrace, "\n"))
+	fmt.Fprintf(&body, "<details><summary>Recent logs</summary>\n\n```\n%s\n```\n</details>\n", strings.Join(logs, "\n"))
+	if cut > 0 {
+		fmt.Fprintf(&body, "\n_%d log and backtrace lines were cut to fit the link._\n", cut)
+	}
+
+	query := url.Values{}
+	query.Set("title", "Crash report: "+truncateTitle(report.Panic))
+	query.Set("body", body.String())
+	return crashIssueURL + "?" + query.Encode()
+}
+
+func (a *App) activeProviderType() string {
+	if a.manager.ActiveIndex() == -1 {
+		return "none"
+	}
+	_, config := a.manager.Active()
+	return config.Type
+}
+
+// PendingCrashReport returns the crash report left by the previous run, if any
+func (a *App) PendingCrashReport() (*CrashReport, error) {
+	return a.crash.Pending()
+}
+
+// SubmitCrashReport opens a prefilled issue for the report in the browser and removes it locally
+func (a *App) SubmitCrashReport(id string) error {
+	report, err := a.crash.Pending()
+	if err != nil {
+		return err
+	}
+	if report == nil || report.ID != id {
+		return fmt.Errorf("crash report %s not found", id)
+	}
+	wailsruntime.BrowserOpenURL(a.ctx, a.crash.IssueURL(report))
+	return a.crash.Dismiss(id)
+}
+
+// DismissCrashReport discards the crash report without submitting it
+func (a *App) DismissCrashReport(id string) error {
+	return a.crash.Dismiss(id)
+}
@@ -0,0 +1,135 @@
+package main
+
+import (
+	"regexp"
+	"strconv"
+	"strings"
+)
+
+const (
+	DiffContext = "context"
+	DiffAdded   = "added"
+	DiffRemoved = "removed"
+)
+
+// DiffHunk is one @@ section of a unified diff. Diffs without hunk headers,
+// as models often write them, become a single hunk with zero line numbers.
+type DiffHunk struct {
+	OldFile  string     `json:"oldFile,omitempty"` // from the preceding --- line
+	NewFile  string     `json:"newFile,omitempty"` // from the preceding +++ line
+	Header   string     `json:"header,omitempty"`  // text after the closing @@, often the enclosing function
+	OldStart int        `json:"oldStart"`
+	OldLines int        `json:"oldLines"`
+	NewStart int        `json:"newStart"`
+	NewLines int        `json:"newLines"`
+	Lines    []DiffLine `json:"lines"`
+}
+
+// DiffLine is a line of a hunk without its +, - or space prefix
+type DiffLine struct {
+	Kind string `json:"kind"` // DiffContext, DiffAdded or DiffRemoved
+	Text string `json:"text"`
+}
+
+var hunkHeader = regexp.MustCompile(`^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@ ?(.*)$`)
+
+// isDiffLanguage reports whether a code fence's info string marks a patch
+func isDiffLanguage(language string) bool {
+	fields := strings.Fields(language)
+	if len(fields) == 0 {
+		return false
+	}
+	switch strings.ToLower(fields[0]) {
+	case "diff", "patch", "udiff":
+		return true
+	}
+	return false
+}
+
+// parseDiff splits unified diff text into hunks, classifying each line
+func parseDiff(text string) []DiffHunk {
+	hunks := make([]DiffHunk, 0)
+	var oldFile, newFile string
+	var hunk *DiffHunk
+	start := func() {
+		hunks = append(hunks, DiffHunk{OldFile: oldFile, NewFile: newFile, Lines: make([]DiffLine, 0)})
+		hunk = &hunks[len(hunks)-1]
+	}
+
+	for _, line := range strings.Split(text, "\n") {
+		switch {
+		case strings.HasPrefix(line, "--- ") && (hunk == nil || hunkComplete(hunk)):
+			oldFile, newFile, hunk = diffPath(line[4:]), "", nil
+		case strings.HasPrefix(line, "+++ ") && hunk == nil:
+			newFile = diffPath(line[4:])
+		case strings.HasPrefix(line, "diff ") || strings.HasPrefix(line, "index "):
+			hunk = nil // git headers between files
+		case hunkHeader.MatchString(line):
+			match := hunkHeader.FindStringSubmatch(line)
+			start()
+			hunk.OldStart, hunk.OldLines = hunkRange(match[1], match[2])
+			hunk.NewStart, hunk.NewLines = hunkRange(match[3], match[4])
+			hunk.Header = match[5]
+		default:
+			if hunk == nil {
+				start()
+			}
+			switch {
+			case strings.HasPrefix(line, "+"):
+				hunk.Lines = append(hunk.Lines, DiffLine{Kind: DiffAdded, Text: line[1:]})
+			case strings.HasPrefix(line, "-"):
+				hunk.Lines = append(hunk.Lines, DiffLine{Kind: DiffRemoved, Text: line[1:]})
+			case strings.HasPrefix(line, `\`):
+				// "\ No newline at end of file"
+			default:
+				hunk.Lines = append(hunk.Lines, DiffLine{Kind: DiffContext, Text: strings.TrimPrefix(line, " ")})
+			}
+		}
+	}
+
+	// Drop the empty hunk a trailing newline or header-only diff leaves behind
+	if n := len(hunks); n > 0 && len(hunks[n-1].Lines) == 0 && hunks[n-1].OldStart == 0 {
+		hunks = hunks[:n-1]
+	}
+	return hunks
+}
+
+// hunkComplete reports whether a hunk with line counts has all its lines,
+// so a following "--- " line starts a new file rather than removing "-- "
+func hunkComplete(hunk *DiffHunk) bool {
+	if hunk.OldStart == 0 && hunk.NewStart == 0 {
+		return false
+	}
+	oldCount, newCount := 0, 0
+	for _, line := range hunk.Lines {
+		if line.Kind != DiffAdded {
+			oldCount++
+		}
+		if line.Kind != DiffRemoved {
+			newCount++
+		}
+	}
+	return oldCount >= hunk.OldLines && newCount >= hunk.NewLines
+}
+
+// hunkRange parses "start,count" where a missing count means one line
+func hunkRange(start, count string) (int, int) {
+	s, _ := strconv.Atoi(start)
+	if count == "" {
+		return s, 1
+	}
+	n, _ := strconv.Atoi(count)
+	return s, n
+}
+
+// diffPath strips the a/ or b/ prefix and any timestamp from a file header
+func diffPath(header string) string {
+	if tab := strings.IndexByte(header, '\t'); tab >= 0 {
+		header = header[:tab]
+	}
+	header = strings.TrimSpace(header)
+	if strings.HasPrefix(header, "a/") || strings.HasPrefix(header, "b/") {
+		header = header[2:]
+	}
+	return header
+}
@@ -0,0 +1,61 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+func TestParseDiff(t *testing.T) {
+	tests := []struct {
+		name string
+		diff string
+		want []DiffHunk
+	}{
+		{
+			name: "unified diff",
+			diff: "--- a/x.go\n+++ b/x.go\n@@ -1,3 +1,3 @@ func main() {\n context\n-old\n+new\n\\ No newline at end of file",
+			want: []DiffHunk{{
+				OldFile: "x.go", NewFile: "x.go", Header: "func main() {",
+				OldStart: 1, OldLines: 3, NewStart: 1, NewLines: 3,
+				Lines: []DiffLine{{DiffContext, "context"}, {DiffRemoved, "old"}, {DiffAdded, "new"}},
+			}},
+		},
+		{
+			name: "without hunk headers",
+			diff: " keep\n-drop\n+add",
+			want: []DiffHunk{{Lines: []DiffLine{{DiffContext, "keep"}, {DiffRemoved, "drop"}, {DiffAdded, "add"}}}},
+		},
+		{
+			name: "removing a line that starts with dashes",
+			diff: "@@ -1 +0,0 @@\n--- not a header\n--- b.go\n+++ b.go\n@@ -5 +5 @@\n-x\n+y",
+			want: []DiffHunk{
+				{OldStart: 1, OldLines: 1, Lines: []DiffLine{{DiffRemoved, "-- not a header"}}},
+				{OldFile: "b.go", NewFile: "b.go", OldStart: 5, OldLines: 1, NewStart: 5, NewLines: 1, Lines: []DiffLine{{DiffRemoved, "x"}, {DiffAdded, "y"}}},
+			},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := parseDiff(tt.diff); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %+v, want %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestDiffPath(t *testing.T) {
+	tests := []struct {
+		header string
+		want   string
+	}{
+		{"a/src/x.go", "src/x.go"},
+		{"b/x.go\t2024-01-01 10:00:00", "x.go"},
+		{" plain.go ", "plain.go"},
+		{"/dev/null", "/dev/null"},
+	}
+	for _, tt := range tests {
+		if got := diffPath(tt.header); got != tt.want {
+			t.Errorf("diffPath(%q) = %q, want %q", tt.header, got, tt.want)
+		}
+	}
+}
@@ -0,0 +1,415 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io/fs"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"time"
+
+	wailsruntime "github.com/wailsapp/wails/v2/pkg/runtime"
+)
+
+// Export formats
+const (
+	ExportMarkdown  = "markdown"
+	ExportJSON      = "json"
+	ExportHTML      = "html"
+	ExportPlainText = "text"
+	ExportPDF       = "pdf"  // A4 pages, with a table of contents
+	ExportCodeTree  = "code" // just the code blocks, as files in a directory
+	ExportCodeZip   = "zip"  // the same files in a zip archive
+)
+
+// exportExtensions are the file extensions of the export formats
+var exportExtensions = map[string]string{
+	ExportMarkdown:  ".md",
+	ExportJSON:      ".json",
+	ExportHTML:      ".html",
+	ExportPlainText: ".txt",
+	ExportPDF:       ".pdf",
+	ExportCodeTree:  "",
+	ExportCodeZip:   ".zip",
+}
+
+// exportVersion is written into JSON exports so they can be read back
+const exportVersion = 1
+
+// ErrExportExists is returned when an export would replace a file it wasn't
+// allowed to overwrite
+var ErrExportExists = errors.New("file already exists")
+
+// ExportEntry is one exchange in an export: a prompt, if there was one, and
+// its response
+type ExportEntry struct {
+	ID         int64             `json:"id,omitempty"` // the history entry's, for history exports
+	Time       string            `json:"time,omitempty"`
+	Prompt     string            `json:"prompt,omitempty"`
+	Response   string            `json:"response"`
+	Provider   string            `json:"provider,omitempty"`
+	Model      string            `json:"model,omitempty"`
+	TextFormat string            `json:"textFormat,omitempty"`
+	Tokens     int               `json:"tokens,omitempty"`
+	Cost       float64           `json:"cost,omitempty"`
+	Pinned     bool              `json:"pinned,omitempty"`
+	Tags       []string          `json:"tags,omitempty"`
+	Provenance *ProvenanceReport `json:"provenance,omitempty"`
+	Blocks     []ContentBlock    `json:"-"` // the response, parsed
+}
+
+// Exporter renders a titled run of exchanges in the export formats
+type Exporter struct {
+	Title   string
+	Entries []ExportEntry
+	Theme   string // HTML: "dark" or "light", or empty to follow the reader's system
+}
+
+// NewHistoryExporter exports history entries, oldest first as given
+func NewHistoryExporter(title string, entries []SessionEntry) *Exporter {
+	exporter := &Exporter{Title: title, Entries: make([]ExportEntry, 0, len(entries))}
+	for _, entry := range entries {
+		blocks := ParseResponseAs(entry.Response, entry.TextFormat)
+		provenance := entry.Provenance
+		if provenance == nil {
+			report := AnalyzeProvenance(blocks)
+			provenance = &report
+		}
+		exporter.Entries = append(exporter.Entries, ExportEntry{
+			ID:         entry.ID,
+			Time:       entry.Time,
+			Prompt:     entry.Prompt,
+			Response:   entry.Response,
+			Provider:   entry.Provider,
+			Model:      entry.Model,
+			TextFormat: entry.TextFormat,
+			Tokens:     entry.Tokens,
+			Cost:       entry.Cost,
+			Pinned:     entry.Pinned,
+			Tags:       entry.Tags,
+			Provenance: provenance,
+			Blocks:     blocks,
+		})
+	}
+	return exporter
+}
+
+// NewSessionExporter exports a conversation turn by turn, titled with its
+// name or first prompt
+func NewSessionExporter(session Session) *Exporter {
+	exporter := &Exporter{Title: session.summary("").Title}
+	var prompt string
+	for _, message := range session.Messages {
+		switch message.Role {
+		case "user":
+			prompt = message.Content
+		case "assistant":
+			blocks := ParseResponse(message.Content)
+			provenance := AnalyzeProvenance(blocks)
+			exporter.Entries = append(exporter.Entries, ExportEntry{
+				Prompt:     prompt,
+				Response:   message.Content,
+				Provenance: &provenance,
+				Blocks:     blocks,
+			})
+			prompt = ""
+		}
+	}
+	return exporter
+}
+
+// NewResponseExporter exports responses already parsed, without prompts
+func NewResponseExporter(title string, responses [][]ContentBlock) *Exporter {
+	exporter := &Exporter{Title: title, Entries: make([]ExportEntry, 0, len(responses))}
+	for _, blocks := range responses {
+		provenance := AnalyzeProvenance(blocks)
+		exporter.Entries = append(exporter.Entries, ExportEntry{Response: RenderMarkdown(blocks), Provenance: &provenance, Blocks: blocks})
+	}
+	return exporter
+}
+
+// Render writes the export out in format
+func (e *Exporter) Render(format string) ([]byte, error) {
+	switch format {
+	case ExportMarkdown:
+		return []byte(e.markdown()), nil
+	case ExportJSON:
+		return e.json()
+	case ExportHTML:
+		return []byte(e.html()), nil
+	case ExportPlainText:
+		return []byte(e.plainText()), nil
+	case ExportPDF:
+		return e.pdf(), nil
+	case ExportCodeZip:
+		return e.codeZip()
+	case ExportCodeTree:
+		return nil, errors.New("code exports are a directory, so they can only be saved")
+	}
+	return nil, fmt.Errorf("unknown export format %q", format)
+}
+
+// Save renders the export in format and writes it to path, which for code
+// exports is the directory to write into. An existing file is only replaced
+// with overwrite; otherwise it is left alone and ErrExportExists returned.
+func (e *Exporter) Save(path, format string, overwrite bool) error {
+	if format == ExportCodeTree {
+		return e.saveCodeTree(path, overwrite)
+	}
+	content, err := e.Render(format)
+	if err != nil {
+		return err
+	}
+	return saveExport(path, content, overwrite)
+}
+
+func saveExport(path string, content []byte, overwrite bool) error {
+	if err := os.MkdirAll(filepath.Dir(path), 0o755); err != nil {
+		return err
+	}
+	if !overwrite {
+		// Created exclusively, so a file that appears meanwhile isn't lost
+		file, err := os.OpenFile(path, os.O_WRONLY|os.O_CREATE|os.O_EXCL, 0o644)
+		if errors.Is(err, fs.ErrExist) {
+			return fmt.Errorf("%s: %w", path, ErrExportExists)
+		}
+		if err != nil {
+			return err
+		}
+		if _, err := file.Write(content); err != nil {
+			file.Close()
+			return err
+		}
+		return file.Close()
+	}
+	tmp := path + ".tmp"
+	if err := os.WriteFile(tmp, content, 0o644); err != nil {
+		return err
+	}
+	return os.Rename(tmp, path)
+}
+
+// provenancePrefix starts the line of a Markdown export that lists an
+// exchange's provenance flags, under its byline
+const provenancePrefix = "Provenance: "
+
+// heading is how an exchange is headed: its number and the start of its
+// prompt
+func (entry ExportEntry) heading(i int) string {
+	title := fmt.Sprintf("Response %d", i+1)
+	if entry.Prompt != "" {
+		title = fmt.Sprintf("%d. %s", i+1, truncateTitle(entry.Prompt))
+	}
+	return title
+}
+
+// byline is who answered and when, or empty when that isn't known
+func (entry ExportEntry) byline() string {
+	var parts []string
+	for _, part := range []string{entry.Provider, entry.Model} {
+		if part != "" {
+			parts = append(parts, part)
+		}
+	}
+	if t, err := time.Parse(time.RFC3339, entry.Time); err == nil {
+		parts = append(parts, t.Format("2006-01-02 15:04"))
+	}
+	return strings.Join(parts, " · ")
+}
+
+// nestedBlocks moves a response's headings down by levels so they sit
+// under the export's own
+func nestedBlocks(blocks []ContentBlock, levels int) []ContentBlock {
+	nested := make([]ContentBlock, len(blocks))
+	for i, block := range blocks {
+		if block.Type == BlockHeading {
+			block.Level = min(block.Level+levels, 6)
+		}
+		nested[i] = block
+	}
+	return nested
+}
+
+func (e *Exporter) markdown() string {
+	var out strings.Builder
+	fmt.Fprintf(&out, "# %s\n", escapeInline(e.Title))
+	for i, entry := range e.Entries {
+		fmt.Fprintf(&out, "\n## %s\n\n", escapeInline(entry.heading(i)))
+		if byline := entry.byline(); byline != "" {
+			fmt.Fprintf(&out, "_%s_\n\n", escapeInline(byline))
+		}
+		if entry.Provenance != nil && len(entry.Provenance.Flags) > 0 {
+			fmt.Fprintf(&out, "_%s%s_\n\n", provenancePrefix, escapeInline(entry.Provenance.summary()))
+		}
+		if entry.Prompt != "" {
+			for _, line := range strings.Split(entry.Prompt, "\n") {
+				out.WriteString(strings.TrimRight("> "+line, " ") + "\n")
+			}
+			out.WriteString("\n")
+		}
+		out.WriteString(RenderMarkdown(nestedBlocks(entry.Blocks, 2)) + "\n")
+	}
+	return out.String()
+}
+
+func (e *Exporter) json() ([]byte, error) {
+	return json.MarshalIndent(struct {
+		Version  int           `json:"version"`
+		Title    string        `json:"title"`
+		Exported string        `json:"exported"` // RFC 3339
+		Entries  []ExportEntry `json:"entries"`
+	}{exportVersion, e.Title, time.Now().Format(time.RFC3339), e.Entries}, "", "  ")
+}
+
+func (e *Exporter) plainText() string {
+	var out strings.Builder
+	fmt.Fprintf(&out, "%s\n%s\n", e.Title, strings.Repeat("=", max(len([]rune(e.Title)), 3)))
+	for i, entry := range e.Entries {
+		heading := entry.heading(i)
+		fmt.Fprintf(&out, "\n%s\n%s\n\n", heading, strings.Repeat("-", max(len([]rune(heading)), 3)))
+		if byline := entry.byline(); byline != "" {
+			out.WriteString(byline + "\n\n")
+		}
+		if entry.Prompt != "" {
+			out.WriteString("> " + indentLines(entry.Prompt, "> ") + "\n\n")
+		}
+		out.WriteString(plainBlocks(entry.Blocks) + "\n")
+	}
+	return out.String()
+}
+
+// plainBlocks renders blocks as text without markup: code is indented, list
+// items bulleted and table cells separated by bars
+func plainBlocks(blocks []ContentBlock) string {
+	parts := make([]string, 0, len(blocks))
+	for _, block := range blocks {
+		parts = append(parts, plainBlock(block))
+	}
+	return strings.Join(parts, "\n\n")
+}
+
+func plainBlock(block ContentBlock) string {
+	switch block.Type {
+	case BlockHeading:
+		return strings.ToUpper(block.Text)
+	case BlockParagraph:
+		return block.Text
+	case BlockCode, BlockDiff, BlockDiagram, BlockMath:
+		return "    " + indentLines(strings.TrimRight(block.Code, "\n"), "    ")
+	case BlockList, BlockTaskList, BlockDefList:
+		var out []string
+		for i, item := range block.Items {
+			marker := "- "
+			switch {
+			case block.Type == BlockTaskList && item.Checked:
+				marker = "[x] "
+			case block.Type == BlockTaskList:
+				marker = "[ ] "
+			case block.Ordered:
+				marker = fmt.Sprintf("%d. ", block.Start+i)
+			}
+			text := marker + item.Text
+			if len(item.Children) > 0 {
+				text += "\n   " + indentLines(plainBlocks(item.Children), "   ")
+			}
+			out = append(out, text)
+		}
+		return strings.Join(out, "\n")
+	case BlockQuote, BlockFootnote:
+		return "  " + indentLines(plainBlocks(block.Children), "  ")
+	case BlockTable:
+		rows := [][]string{block.Headers}
+		rows = append(rows, block.Rows...)
+		lines := make([]string, len(rows))
+		for i, row := range rows {
+			lines[i] = strings.Join(row, " | ")
+		}
+		return strings.Join(lines, "\n")
+	case BlockRule:
+		return "----"
+	case BlockImage:
+		return fmt.Sprintf("[%s] (%s)", block.Alt, block.URL)
+	}
+	return renderBlock(block)
+}
+
+// exportFileName is a file name for an export of title, with the format's
+// extension
+func exportFileName(title, format string) string {
+	name := strings.Map(func(r rune) rune {
+		if strings.ContainsRune(`<>:"/\|?*`, r) || r < ' ' {
+			return '-'
+		}
+		return r
+	}, strings.TrimSpace(title))
+	if name == "" {
+		name = "export"
+	}
+	return name + exportExtensions[format]
+}
+
+// export saves an export to path and tells plugins
+func (a *App) export(exporter *Exporter, format, path string, overwrite bool) error {
+	exporter.Theme = a.config.Get().UI.theme().Palette.Base
+	event := PluginEvent{Kind: PluginEventExport, Format: format, Path: path}
+	switch format {
+	case ExportCodeTree:
+		if err := exporter.saveCodeTree(path, overwrite); err != nil {
+			return err
+		}
+	default:
+		content, err := exporter.Render(format)
+		if err != nil {
+			return err
+		}
+		if err := saveExport(path, content, overwrite); err != nil {
+			return err
+		}
+		if format != ExportPDF && format != ExportCodeZip {
+			event.Content = string(content) // PDFs and archives are binary, so plugins read the file
+		}
+	}
+	a.emit(event)
+	return nil
+}
+
+// ChooseExportPath asks where to save an export of title in format with a
+// save dialog; it returns nothing if the dialog was cancelled
+func (a *App) ChooseExportPath(title, format string) (string, error) {
+	if a.ctx == nil {
+		return "", errors.New("no window to ask in")
+	}
+	return wailsruntime.SaveFileDialog(a.ctx, wailsruntime.SaveDialogOptions{
+		Title:           "Export " + title,
+		DefaultFilename: exportFileName(title, format),
+	})
+}
+
+// ExportSession writes a conversation to path as markdown, json, html, text
+// or pdf, or its code blocks alone as a code directory or zip; an existing
+// file is only replaced with overwrite
+func (a *App) ExportSession(id, format, path string, overwrite bool) error {
+	session, err := a.sessions.Get(id)
+	if err != nil {
+		return err
+	}
+	return a.export(NewSessionExporter(session), format, path, overwrite)
+}
+
+// ExportSessionHistory writes the recorded prompts and responses matching
+// query, oldest first, as ExportSession does
+func (a *App) ExportSessionHistory(query HistoryQuery, format, path string, overwrite bool) error {
+	matches, err := a.history.Query(query)
+	if err != nil {
+		return err
+	}
+	entries := make([]SessionEntry, len(matches))
+	for i, match := range matches {
+		entries[i] = match.Entry
+	}
+	sort.Slice(entries, func(i, j int) bool { return entries[i].ID < entries[j].ID })
+	return a.export(NewHistoryExporter("Vibe Coder history", entries), format, path, overwrite)
+}
@@ -0,0 +1,119 @@
+package main
+
+import (
+	"archive/zip"
+	"bytes"
+	"errors"
+	"fmt"
+	"io/fs"
+	"os"
+	"path"
+	"path/filepath"
+	"strings"
+	"time"
+)
+
+// ErrNoCode is returned when a code export has no code blocks to write
+var ErrNoCode = errors.New("no code blocks to export")
+
+// CodeFile is a file a code export writes: a code block, under the path the
+// response gave it or a name inferred from its language
+type CodeFile struct {
+	Path     string `json:"path"` // slash separated and relative to the export
+	Language string `json:"language,omitempty"`
+	Code     string `json:"code"`
+}
+
+// CodeFiles is the export's code blocks as files in the order they first
+// appear. Blocks with a path go where it says, a later block for the same
+// path replacing the earlier as a revision of the file; the rest are
+// snippets in a directory for their language.
+func (e *Exporter) CodeFiles() []CodeFile {
+	var files []CodeFile
+	index := make(map[string]int)
+	snippets := make(map[string]int)
+	for _, entry := range e.Entries {
+		walkBlocks(entry.Blocks, func(block ContentBlock) {
+			if (block.Type != BlockCode && block.Type != BlockDiagram) || strings.TrimSpace(block.Code) == "" {
+				return
+			}
+			language := normalizeLanguage(block.Language)
+			if block.Type == BlockDiagram && language == "" {
+				language = block.Kind
+			}
+			name := filepath.ToSlash(codeFileName(block, 0))
+			if block.FilePath == "" {
+				dir := language
+				if dir == "" {
+					dir = "text"
+				}
+				snippets[dir]++
+				name = path.Join(dir, filepath.ToSlash(codeFileName(block, snippets[dir])))
+			}
+			code := block.Code
+			if !strings.HasSuffix(code, "\n") {
+				code += "\n"
+			}
+			file := CodeFile{Path: name, Language: language, Code: code}
+			if i, ok := index[strings.ToLower(name)]; ok {
+				files[i] = file
+				return
+			}
+			// Keyed without case, so the tree is the same on every file system
+			index[strings.ToLower(name)] = len(files)
+			files = append(files, file)
+		})
+	}
+	return files
+}
+
+// codeZip is the export's code files archived under a directory named for
+// its title
+func (e *Exporter) codeZip() ([]byte, error) {
+	files := e.CodeFiles()
+	if len(files) == 0 {
+		return nil, ErrNoCode
+	}
+	root := strings.TrimSuffix(exportFileName(e.Title, ExportCodeZip), exportExtensions[ExportCodeZip])
+	var buf bytes.Buffer
+	archive := zip.NewWriter(&buf)
+	now := time.Now()
+	for _, file := range files {
+		w, err := archive.CreateHeader(&zip.FileHeader{Name: root + "/" + file.Path, Method: zip.Deflate, Modified: now})
+		if err != nil {
+			return nil, err
+		}
+		if _, err := w.Write([]byte(file.Code)); err != nil {
+			return nil, err
+		}
+	}
+	if err := archive.Close(); err != nil {
+		return nil, err
+	}
+	return buf.Bytes(), nil
+}
+
+// saveCodeTree writes the export's code files under dir. Unless overwrite
+// is set, nothing is written when any of them is there already.
+func (e *Exporter) saveCodeTree(dir string, overwrite bool) error {
+	files := e.CodeFiles()
+	if len(files) == 0 {
+		return ErrNoCode
+	}
+	if !overwrite {
+		for _, file := range files {
+			target := filepath.Join(dir, filepath.FromSlash(file.Path))
+			if _, err := os.Stat(target); err == nil {
+				return fmt.Errorf("%s: %w", target, ErrExportExists)
+			} else if !errors.Is(err, fs.ErrNotExist) {
+				return err
+			}
+		}
+	}
+	for _, file := range files {
+		if err := saveExport(filepath.Join(dir, filepath.FromSlash(file.Path)), []byte(file.Code), overwrite); err != nil {
+			return err
+		}
+	}
+	return nil
+}
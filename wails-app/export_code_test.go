@@ -0,0 +1,72 @@
+package main
+
+import (
+	"archive/zip"
+	"bytes"
+	"errors"
+	"os"
+	"path/filepath"
+	"reflect"
+	"testing"
+)
+
+// codeExporter exports two responses, the second revising main.go
+func codeExporter() *Exporter {
+	return NewResponseExporter("My code", [][]ContentBlock{
+		ParseResponse("```go title=main.go\npackage main\n```\n\n```python\nprint(1)\n```\n\n```python\n\n```"),
+		ParseResponse("```go title=main.go\npackage main // v2\n```\n\nThen:\n\n```\nplain\n```"),
+	})
+}
+
+func TestExporterCodeFiles(t *testing.T) {
+	want := []CodeFile{
+		{Path: "main.go", Language: "go", Code: "package main // v2\n"},
+		{Path: "python/snippet-1.py", Language: "python", Code: "print(1)\n"},
+		{Path: "text/snippet-1.txt", Code: "plain\n"},
+	}
+	if got := codeExporter().CodeFiles(); !reflect.DeepEqual(got, want) {
+		t.Errorf("got %+v, want %+v", got, want)
+	}
+}
+
+func TestExporterCodeZip(t *testing.T) {
+	data, err := codeExporter().Render(ExportCodeZip)
+	if err != nil {
+		t.Fatal(err)
+	}
+	archive, err := zip.NewReader(bytes.NewReader(data), int64(len(data)))
+	if err != nil {
+		t.Fatal(err)
+	}
+	var names []string
+	for _, file := range archive.File {
+		names = append(names, file.Name)
+	}
+	if want := []string{"My code/main.go", "My code/python/snippet-1.py", "My code/text/snippet-1.txt"}; !reflect.DeepEqual(names, want) {
+		t.Errorf("archived %q, want %q", names, want)
+	}
+}
+
+func TestExporterSaveCodeTree(t *testing.T) {
+	dir := t.TempDir()
+	if err := codeExporter().Save(dir, ExportCodeTree, false); err != nil {
+		t.Fatal(err)
+	}
+	if data, _ := os.ReadFile(filepath.Join(dir, "python", "snippet-1.py")); string(data) != "print(1)\n" {
+		t.Errorf("wrote %q", data)
+	}
+	if err := codeExporter().Save(dir, ExportCodeTree, false); !errors.Is(err, ErrExportExists) {
+		t.Errorf("saving over the tree got %v, want ErrExportExists", err)
+	}
+	if err := codeExporter().Save(dir, ExportCodeTree, true); err != nil {
+		t.Errorf("overwriting: %v", err)
+	}
+
+	prose := NewResponseExporter("Prose", [][]ContentBlock{ParseResponse("no code here")})
+	if err := prose.Save(t.TempDir(), ExportCodeTree, false); !errors.Is(err, ErrNoCode) {
+		t.Errorf("saving no code got %v, want ErrNoCode", err)
+	}
+	if _, err := prose.Render(ExportCodeZip); !errors.Is(err, ErrNoCode) {
+		t.Errorf("zipping no code got %v, want ErrNoCode", err)
+	}
+}
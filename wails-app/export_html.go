@@ -0,0 +1,301 @@
+package main
+
+import (
+	"fmt"
+	"html"
+	"net/url"
+	"strings"
+)
+
+// exportCSS styles HTML exports. The light and dark palettes follow the
+// reader's system unless the export names a theme on <html>.
+const exportCSS = `:root {
+  --bg: #ffffff; --fg: #1f2328; --muted: #656d76; --border: #d0d7de;
+  --code-bg: #f6f8fa; --quote: #57606a; --link: #0969da;
+  --keyword: #cf222e; --string: #0a3069; --comment: #6e7781; --number: #0550ae;
+  --type: #953800; --function: #8250df; --added: #dafbe1; --removed: #ffebe9;
+}
+@media (prefers-color-scheme: dark) {
+  :root:not([data-theme="light"]) {
+    --bg: #1e1e1e; --fg: #d4d4d4; --muted: #8b949e; --border: #3c3c3c;
+    --code-bg: #252526; --quote: #a0a0a0; --link: #4fc1ff;
+    --keyword: #569cd6; --string: #ce9178; --comment: #6a9955; --number: #b5cea8;
+    --type: #4ec9b0; --function: #dcdcaa; --added: #1e3a24; --removed: #4a1f1f;
+  }
+}
+:root[data-theme="dark"] {
+  --bg: #1e1e1e; --fg: #d4d4d4; --muted: #8b949e; --border: #3c3c3c;
+  --code-bg: #252526; --quote: #a0a0a0; --link: #4fc1ff;
+  --keyword: #569cd6; --string: #ce9178; --comment: #6a9955; --number: #b5cea8;
+  --type: #4ec9b0; --function: #dcdcaa; --added: #1e3a24; --removed: #4a1f1f;
+}
+body { margin: 0 auto; max-width: 50rem; padding: 2rem 1.5rem; background: var(--bg); color: var(--fg);
+  font: 15px/1.6 -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; }
+h1, h2, h3, h4, h5, h6 { line-height: 1.25; }
+section { border-top: 1px solid var(--border); margin-top: 2rem; }
+.byline { color: var(--muted); font-size: 0.85em; }
+.prompt { border-left: 3px solid var(--border); color: var(--quote); margin: 1rem 0; padding: 0 1rem; white-space: pre-wrap; }
+blockquote { border-left: 3px solid var(--border); color: var(--quote); margin: 1rem 0; padding: 0 1rem; }
+a { color: var(--link); }
+code, pre { font-family: "JetBrains Mono", Menlo, Consolas, monospace; font-size: 0.9em; }
+:not(pre) > code { background: var(--code-bg); border-radius: 4px; padding: 0.1em 0.3em; }
+pre { background: var(--code-bg); border: 1px solid var(--border); border-radius: 6px; overflow-x: auto; padding: 0.75rem 1rem; }
+.file { color: var(--muted); font-size: 0.8em; margin-bottom: -0.75rem; }
+.tok-keyword { color: var(--keyword); }
+.tok-string { color: var(--string); }
+.tok-comment { color: var(--comment); font-style: italic; }
+.tok-number { color: var(--number); }
+.tok-type { color: var(--type); }
+.tok-function { color: var(--function); }
+.diff-added { background: var(--added); display: block; }
+.diff-removed { background: var(--removed); display: block; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid var(--border); padding: 0.3rem 0.6rem; }
+ul.tasks { list-style: none; padding-left: 1.2rem; }
+img { max-width: 100%; }
+hr { border: none; border-top: 1px solid var(--border); }
+`
+
+// htmlText escapes text for an element or attribute
+func htmlText(text string) string {
+	return html.EscapeString(text)
+}
+
+// safeURL is url when it's safe to put in a link or image: web and mail
+// links, anchors and relative paths, and inline images; anything else, such
+// as javascript:, is dropped
+func safeURL(raw string, image bool) (string, bool) {
+	parsed, err := url.Parse(strings.TrimSpace(raw))
+	if err != nil {
+		return "", false
+	}
+	switch strings.ToLower(parsed.Scheme) {
+	case "", "http", "https", "mailto":
+		return parsed.String(), true
+	case "data":
+		return raw, image && strings.HasPrefix(strings.ToLower(parsed.Opaque), "image/")
+	}
+	return "", false
+}
+
+// html is the export as a standalone page: its title, then each exchange in
+// a section of its own with the response rendered from its blocks
+func (e *Exporter) html() string {
+	var out strings.Builder
+	theme := ""
+	if e.Theme == "light" || e.Theme == "dark" {
+		theme = fmt.Sprintf(` data-theme="%s"`, e.Theme)
+	}
+	title := htmlText(e.Title)
+	fmt.Fprintf(&out, "<!DOCTYPE html>\n<html%s>\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>%s</title>\n<style>\n%s</style>\n</head>\n<body>\n<h1>%s</h1>\n",
+		theme, title, exportCSS, title)
+	for i, entry := range e.Entries {
+		fmt.Fprintf(&out, "<section>\n<h2>%s</h2>\n", htmlText(entry.heading(i)))
+		if byline := entry.byline(); byline != "" {
+			fmt.Fprintf(&out, "<p class=\"byline\">%s</p>\n", htmlText(byline))
+		}
+		if entry.Prompt != "" {
+			fmt.Fprintf(&out, "<div class=\"prompt\">%s</div>\n", htmlText(entry.Prompt))
+		}
+		out.WriteString(htmlBlocks(nestedBlocks(entry.Blocks, 2)))
+		out.WriteString("</section>\n")
+	}
+	out.WriteString("</body>\n</html>\n")
+	return out.String()
+}
+
+func htmlBlocks(blocks []ContentBlock) string {
+	var out strings.Builder
+	for _, block := range blocks {
+		out.WriteString(htmlBlock(block))
+	}
+	return out.String()
+}
+
+func htmlBlock(block ContentBlock) string {
+	switch block.Type {
+	case BlockHeading:
+		level := min(max(block.Level, 1), 6)
+		return fmt.Sprintf("<h%d>%s</h%d>\n", level, htmlSpans(block.Spans, block.Text), level)
+	case BlockParagraph:
+		return "<p>" + htmlSpans(block.Spans, block.Text) + "</p>\n"
+	case BlockCode, BlockDiagram:
+		return htmlCode(block)
+	case BlockDiff:
+		return htmlDiff(block)
+	case BlockMath:
+		return "<pre class=\"math\">" + htmlText(block.Code) + "</pre>\n"
+	case BlockList, BlockTaskList:
+		return htmlList(block)
+	case BlockDefList:
+		var out strings.Builder
+		out.WriteString("<dl>\n")
+		for _, item := range block.Items {
+			out.WriteString("<dt>" + htmlSpans(item.Spans, item.Text) + "</dt>\n")
+			for _, definition := range item.Children {
+				out.WriteString("<dd>" + htmlBlock(definition) + "</dd>\n")
+			}
+		}
+		out.WriteString("</dl>\n")
+		return out.String()
+	case BlockQuote:
+		return "<blockquote>\n" + htmlBlocks(block.Children) + "</blockquote>\n"
+	case BlockFootnote:
+		label := htmlText(block.Label)
+		return fmt.Sprintf("<div class=\"footnote\" id=\"fn-%s\"><sup>%s</sup>\n%s</div>\n", label, label, htmlBlocks(block.Children))
+	case BlockTable:
+		return htmlTable(block)
+	case BlockRule:
+		return "<hr>\n"
+	case BlockImage:
+		if src, ok := safeURL(block.URL, true); ok {
+			return fmt.Sprintf("<p><img src=\"%s\" alt=\"%s\"></p>\n", htmlText(src), htmlText(block.Alt))
+		}
+		return "<p>" + htmlText(block.Alt) + "</p>\n"
+	}
+	return "<p>" + htmlText(block.Text) + "</p>\n"
+}
+
+// htmlCode is a code block colored by the highlighter, under the path of
+// its file when the response gave one
+func htmlCode(block ContentBlock) string {
+	var out strings.Builder
+	if block.FilePath != "" {
+		out.WriteString("<div class=\"file\">" + htmlText(block.FilePath) + "</div>\n")
+	}
+	language := normalizeLanguage(block.Language)
+	class := ""
+	if language != "" {
+		class = fmt.Sprintf(` class="language-%s"`, htmlText(language))
+	}
+	fmt.Fprintf(&out, "<pre><code%s>", class)
+	for _, token := range Highlight(strings.TrimRight(block.Code, "\n"), language) {
+		if token.Kind == TokenPlain {
+			out.WriteString(htmlText(token.Text))
+		} else {
+			fmt.Fprintf(&out, "<span class=\"tok-%s\">%s</span>", token.Kind, htmlText(token.Text))
+		}
+	}
+	out.WriteString("</code></pre>\n")
+	return out.String()
+}
+
+// htmlDiff is a diff with its added and removed lines shaded
+func htmlDiff(block ContentBlock) string {
+	var out strings.Builder
+	if block.FilePath != "" {
+		out.WriteString("<div class=\"file\">" + htmlText(block.FilePath) + "</div>\n")
+	}
+	out.WriteString("<pre><code class=\"language-diff\">")
+	for _, line := range strings.Split(strings.TrimRight(block.Code, "\n"), "\n") {
+		switch {
+		case strings.HasPrefix(line, "+") && !strings.HasPrefix(line, "+++"):
+			out.WriteString("<span class=\"diff-added\">" + htmlText(line) + "</span>")
+		case strings.HasPrefix(line, "-") && !strings.HasPrefix(line, "---"):
+			out.WriteString("<span class=\"diff-removed\">" + htmlText(line) + "</span>")
+		default:
+			out.WriteString(htmlText(line) + "\n")
+		}
+	}
+	out.WriteString("</code></pre>\n")
+	return out.String()
+}
+
+func htmlList(block ContentBlock) string {
+	var out strings.Builder
+	switch {
+	case block.Type == BlockTaskList:
+		out.WriteString("<ul class=\"tasks\">\n")
+	case block.Ordered && block.Start != 1:
+		fmt.Fprintf(&out, "<ol start=\"%d\">\n", block.Start)
+	case block.Ordered:
+		out.WriteString("<ol>\n")
+	default:
+		out.WriteString("<ul>\n")
+	}
+	for _, item := range block.Items {
+		out.WriteString("<li>")
+		if block.Type == BlockTaskList {
+			checked := ""
+			if item.Checked {
+				checked = " checked"
+			}
+			fmt.Fprintf(&out, "<input type=\"checkbox\" disabled%s> ", checked)
+		}
+		out.WriteString(htmlSpans(item.Spans, item.Text))
+		if len(item.Children) > 0 {
+			out.WriteString("\n" + htmlBlocks(item.Children))
+		}
+		out.WriteString("</li>\n")
+	}
+	if block.Ordered && block.Type != BlockTaskList {
+		out.WriteString("</ol>\n")
+	} else {
+		out.WriteString("</ul>\n")
+	}
+	return out.String()
+}
+
+func htmlTable(block ContentBlock) string {
+	var out strings.Builder
+	cell := func(tag string, column int, text string) {
+		style := ""
+		if column < len(block.Align) && block.Align[column] != "" {
+			style = fmt.Sprintf(` style="text-align: %s"`, block.Align[column])
+		}
+		fmt.Fprintf(&out, "<%s%s>%s</%s>", tag, style, htmlSpans(parseInline(text, DefaultParserOptions()), text), tag)
+	}
+	out.WriteString("<table>\n<thead><tr>")
+	for i, header := range block.Headers {
+		cell("th", i, header)
+	}
+	out.WriteString("</tr></thead>\n<tbody>\n")
+	for _, row := range block.Rows {
+		out.WriteString("<tr>")
+		for i, text := range row {
+			cell("td", i, text)
+		}
+		out.WriteString("</tr>\n")
+	}
+	out.WriteString("</tbody>\n</table>\n")
+	return out.String()
+}
+
+// htmlSpans is inline text with its formatting, or text itself when there
+// are no spans
+func htmlSpans(spans []InlineSpan, text string) string {
+	if len(spans) == 0 {
+		return htmlText(text)
+	}
+	var out strings.Builder
+	for _, span := range spans {
+		inner := htmlText(span.Text)
+		if len(span.Children) > 0 {
+			inner = htmlSpans(span.Children, span.Text)
+		}
+		switch span.Type {
+		case SpanBold:
+			out.WriteString("<strong>" + inner + "</strong>")
+		case SpanItalic:
+			out.WriteString("<em>" + inner + "</em>")
+		case SpanStrike:
+			out.WriteString("<del>" + inner + "</del>")
+		case SpanCode:
+			out.WriteString("<code>" + htmlText(span.Text) + "</code>")
+		case SpanMath:
+			out.WriteString("<code class=\"math\">" + htmlText(span.Text) + "</code>")
+		case SpanFootnote:
+			label := htmlText(span.Text)
+			fmt.Fprintf(&out, "<sup><a href=\"#fn-%s\">%s</a></sup>", label, label)
+		case SpanLink:
+			if href, ok := safeURL(span.URL, false); ok {
+				fmt.Fprintf(&out, "<a href=\"%s\">%s</a>", htmlText(href), inner)
+			} else {
+				out.WriteString(inner)
+			}
+		default:
+			out.WriteString(inner)
+		}
+	}
+	return out.String()
+}
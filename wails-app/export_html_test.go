@@ -0,0 +1,78 @@
+package main
+
+import (
+	"strings"
+	"testing"
+)
+
+func TestSafeURL(t *testing.T) {
+	tests := []struct {
+		raw   string
+		image bool
+		want  bool
+	}{
+		{"https://example.com/a?b=1", false, true},
+		{"mailto:me@example.com", false, true},
+		{"#section", false, true},
+		{"docs/page.html", false, true},
+		{"javascript:alert(1)", false, false},
+		{" JavaScript:alert(1)", false, false},
+		{"vbscript:msgbox", false, false},
+		{"data:image/png;base64,AAAA", true, true},
+		{"data:image/png;base64,AAAA", false, false},
+		{"data:text/html,hi", true, false},
+	}
+	for _, tt := range tests {
+		safe, ok := safeURL(tt.raw, tt.image)
+		if ok != tt.want {
+			t.Errorf("safeURL(%q, %v) = %q, %v, want ok %v", tt.raw, tt.image, safe, ok, tt.want)
+		}
+	}
+}
+
+func TestExporterHTML(t *testing.T) {
+	blocks := ParseResponse("a <script> and [safe](https://example.com) and [bad](javascript:steal)\n\n```go\nfmt.Println(1)\n```")
+	tests := []struct {
+		name    string
+		theme   string
+		want    []string
+		wantNot []string
+	}{
+		{
+			name:  "light",
+			theme: "light",
+			want: []string{
+				`<html data-theme="light">`,
+				"<title>T &lt;1&gt;</title>",
+				"<h2>Response 1</h2>",
+				"a &lt;script&gt; and ",
+				`<a href="https://example.com">safe</a>`,
+				" and bad</p>",
+				`<pre><code class="language-go">fmt.<span class="tok-function">Println</span>(<span class="tok-number">1</span>)</code></pre>`,
+			},
+			wantNot: []string{"javascript:", "<script>"},
+		},
+		{name: "following the system", want: []string{"<html>\n"}},
+		{name: "unknown theme", theme: "purple", want: []string{"<html>\n"}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			exporter := NewResponseExporter("T <1>", [][]ContentBlock{blocks})
+			exporter.Theme = tt.theme
+			data, err := exporter.Render(ExportHTML)
+			if err != nil {
+				t.Fatal(err)
+			}
+			for _, want := range tt.want {
+				if !strings.Contains(string(data), want) {
+					t.Errorf("no %q in\n%s", want, data)
+				}
+			}
+			for _, unwanted := range tt.wantNot {
+				if strings.Contains(string(data), unwanted) {
+					t.Errorf("%q in\n%s", unwanted, data)
+				}
+			}
+		})
+	}
+}
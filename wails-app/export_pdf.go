@@ -0,0 +1,475 @@
+package main
+
+import (
+	"bytes"
+	"fmt"
+	"math"
+	"strconv"
+	"strings"
+	"time"
+)
+
+// PDF page geometry in points: A4 with 2 cm margins
+const (
+	pdfPageWidth  = 595.28
+	pdfPageHeight = 841.89
+	pdfMargin     = 56.69
+	pdfTextWidth  = pdfPageWidth - 2*pdfMargin
+	pdfIndent     = 14.0 // for list items, quotes and prompts
+)
+
+// pdfFont is one of the standard fonts every PDF reader has, so nothing
+// needs embedding. Their text is WinAnsi-encoded.
+type pdfFont struct {
+	name   string   // its resource name in the page
+	base   string   // its PostScript name
+	widths *[95]int // of ' ' through '~' per 1000 units of size; nil for monospaced
+}
+
+// Advance widths from the fonts' Adobe metrics
+var (
+	helveticaWidths = [95]int{
+		278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+		556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+		1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+		667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+		333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+		556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+	}
+	helveticaBoldWidths = [95]int{
+		278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278, 278,
+		556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611,
+		975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778,
+		667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556,
+		333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611,
+		611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584,
+	}
+)
+
+var (
+	pdfRegular = pdfFont{"F1", "Helvetica", &helveticaWidths}
+	pdfBold    = pdfFont{"F2", "Helvetica-Bold", &helveticaBoldWidths}
+	pdfItalic  = pdfFont{"F3", "Helvetica-Oblique", &helveticaWidths}
+	pdfMono    = pdfFont{"F4", "Courier", nil}
+	pdfFonts   = []pdfFont{pdfRegular, pdfBold, pdfItalic, pdfMono}
+)
+
+// pdfStyle is how a run of text is set
+type pdfStyle struct {
+	font    pdfFont
+	size    float64
+	leading float64 // from one line to the next
+	gray    float64 // 0 is black
+}
+
+var (
+	pdfTitleStyle    = pdfStyle{pdfBold, 20, 26, 0}
+	pdfExchangeStyle = pdfStyle{pdfBold, 14, 19, 0}
+	pdfBodyStyle     = pdfStyle{pdfRegular, 10.5, 14, 0}
+	pdfPromptStyle   = pdfStyle{pdfItalic, 10.5, 14, 0.3}
+	pdfMetaStyle     = pdfStyle{pdfRegular, 8.5, 12, 0.45}
+	pdfCodeStyle     = pdfStyle{pdfMono, 8.5, 11, 0.1}
+)
+
+// pdfHeadingStyle is the style of a heading inside a response, smaller for
+// deeper levels
+func pdfHeadingStyle(level int) pdfStyle {
+	size := max(13-float64(level), 10.5)
+	return pdfStyle{pdfBold, size, size * 1.4, 0}
+}
+
+// width is how wide text, WinAnsi-encoded, is set in the style
+func (s pdfStyle) width(text []byte) float64 {
+	var units int
+	for _, c := range text {
+		switch {
+		case s.font.widths == nil:
+			units += 600
+		case c >= ' ' && c <= '~':
+			units += s.font.widths[c-' ']
+		default:
+			units += 556
+		}
+	}
+	return float64(units) * s.size / 1000
+}
+
+// winAnsiSpecials are the characters WinAnsi puts in 0x80-0x9F
+var winAnsiSpecials = map[rune]byte{
+	'€': 0x80, '‚': 0x82, 'ƒ': 0x83, '„': 0x84, '…': 0x85, '†': 0x86, '‡': 0x87, 'ˆ': 0x88,
+	'‰': 0x89, 'Š': 0x8A, '‹': 0x8B, 'Œ': 0x8C, 'Ž': 0x8E, '‘': 0x91, '’': 0x92, '“': 0x93,
+	'”': 0x94, '•': 0x95, '–': 0x96, '—': 0x97, '˜': 0x98, '™': 0x99, 'š': 0x9A, '›': 0x9B,
+	'œ': 0x9C, 'ž': 0x9E, 'Ÿ': 0x9F,
+}
+
+// winAnsi encodes text for the standard fonts: what WinAnsi lacks becomes
+// '?', tabs become four spaces and other control characters are dropped
+func winAnsi(text string) []byte {
+	encoded := make([]byte, 0, len(text))
+	for _, r := range text {
+		switch c, special := winAnsiSpecials[r]; {
+		case r == '\t':
+			encoded = append(encoded, "    "...)
+		case r == '\n' || (r >= ' ' && r <= '~') || (r >= 0xA0 && r <= 0xFF):
+			encoded = append(encoded, byte(r))
+		case special:
+			encoded = append(encoded, c)
+		case r >= ' ':
+			encoded = append(encoded, '?')
+		}
+	}
+	return encoded
+}
+
+// pdfString is text as a PDF literal string
+func pdfString(text []byte) string {
+	var out strings.Builder
+	out.WriteByte('(')
+	for _, c := range text {
+		switch {
+		case c == '(' || c == ')' || c == '\\':
+			out.WriteByte('\\')
+			out.WriteByte(c)
+		case c < ' ' || c > '~':
+			fmt.Fprintf(&out, "\\%03o", c)
+		default:
+			out.WriteByte(c)
+		}
+	}
+	out.WriteByte(')')
+	return out.String()
+}
+
+// pdfNumber writes n to two decimals, enough for positions in points
+func pdfNumber(n float64) string {
+	return strconv.FormatFloat(math.Round(n*100)/100, 'f', -1, 64)
+}
+
+// wrapPDFText breaks text into lines no wider than width, at spaces where
+// it can and anywhere in a word too long for a line of its own
+func wrapPDFText(text []byte, style pdfStyle, width float64) [][]byte {
+	var lines [][]byte
+	for _, paragraph := range bytes.Split(text, []byte("\n")) {
+		var line []byte
+		for _, word := range bytes.Split(paragraph, []byte(" ")) {
+			if len(word) == 0 {
+				continue
+			}
+			if len(line) > 0 {
+				candidate := append(append(append([]byte{}, line...), ' '), word...)
+				if style.width(candidate) <= width {
+					line = candidate
+					continue
+				}
+				lines = append(lines, line)
+				line = nil
+			}
+			for style.width(word) > width {
+				cut := 1
+				for cut < len(word) && style.width(word[:cut+1]) <= width {
+					cut++
+				}
+				lines = append(lines, word[:cut])
+				word = word[cut:]
+			}
+			line = append([]byte{}, word...)
+		}
+		lines = append(lines, line)
+	}
+	return lines
+}
+
+// pdfPage is a page's drawing operators and the links on it
+type pdfPage struct {
+	content bytes.Buffer
+	links   []pdfLink
+}
+
+// pdfLink is a clickable area that goes to a place in the document
+type pdfLink struct {
+	x1, y1, x2, y2 float64
+	target         pdfMark
+}
+
+// pdfMark is a place in the document: a page, counted from 0, and a height
+// on it
+type pdfMark struct {
+	title string
+	page  int
+	y     float64
+}
+
+// pdfLayout sets text down the pages, starting a new one whenever a line
+// doesn't fit
+type pdfLayout struct {
+	pages []*pdfPage
+	y     float64 // the top of the next line, up from the bottom of the page
+}
+
+func (l *pdfLayout) newPage() {
+	l.pages = append(l.pages, &pdfPage{})
+	l.y = pdfPageHeight - pdfMargin
+}
+
+func (l *pdfLayout) page() *pdfPage {
+	return l.pages[len(l.pages)-1]
+}
+
+// line makes room for a line height tall, on a new page if need be, and
+// returns its bottom
+func (l *pdfLayout) line(height float64) float64 {
+	if len(l.pages) == 0 || l.y-height < pdfMargin {
+		l.newPage()
+	}
+	l.y -= height
+	return l.y
+}
+
+// gap leaves space between blocks, except at the top of a page
+func (l *pdfLayout) gap(height float64) {
+	if len(l.pages) > 0 && l.y < pdfPageHeight-pdfMargin {
+		l.y -= height
+	}
+}
+
+// show draws text with its baseline at y
+func (p *pdfPage) show(style pdfStyle, x, y float64, text []byte) {
+	fmt.Fprintf(&p.content, "BT %s g /%s %s Tf %s %s Td %s Tj ET\n",
+		pdfNumber(style.gray), style.font.name, pdfNumber(style.size), pdfNumber(x), pdfNumber(y), pdfString(text))
+}
+
+// text sets text wrapped to the width left after indent. marker, if any,
+// goes before its first line, in the indent.
+func (l *pdfLayout) text(text string, style pdfStyle, indent float64, marker string) {
+	for i, line := range wrapPDFText(winAnsi(text), style, pdfTextWidth-indent) {
+		bottom := l.line(style.leading)
+		baseline := bottom + (style.leading-style.size)/2 + style.size*0.2
+		if i == 0 && marker != "" {
+			l.page().show(style, pdfMargin+indent-pdfIndent, baseline, winAnsi(marker))
+		}
+		l.page().show(style, pdfMargin+indent, baseline, line)
+	}
+}
+
+// code sets code in a monospaced font on a shaded background, line for line
+func (l *pdfLayout) code(code string, indent float64) {
+	style := pdfCodeStyle
+	width := pdfTextWidth - indent
+	perLine := max(int((width-8)/(0.6*style.size)), 1)
+	for _, line := range strings.Split(strings.TrimRight(code, "\n"), "\n") {
+		encoded := winAnsi(line)
+		for {
+			cut := min(len(encoded), perLine)
+			bottom := l.line(style.leading)
+			fmt.Fprintf(&l.page().content, "0.94 g %s %s %s %s re f\n",
+				pdfNumber(pdfMargin+indent), pdfNumber(bottom), pdfNumber(width), pdfNumber(style.leading))
+			l.page().show(style, pdfMargin+indent+4, bottom+style.leading*0.28, encoded[:cut])
+			encoded = encoded[cut:]
+			if len(encoded) == 0 {
+				break
+			}
+		}
+	}
+}
+
+// rule draws a thin line across the text width
+func (l *pdfLayout) rule(indent float64) {
+	y := l.line(10) + 5
+	fmt.Fprintf(&l.page().content, "0.7 G 0.5 w %s %s m %s %s l S\n",
+		pdfNumber(pdfMargin+indent), pdfNumber(y), pdfNumber(pdfMargin+pdfTextWidth), pdfNumber(y))
+}
+
+// blocks sets a parsed response
+func (l *pdfLayout) blocks(blocks []ContentBlock, indent float64) {
+	for i, block := range blocks {
+		if i > 0 {
+			l.gap(6)
+		}
+		switch block.Type {
+		case BlockHeading:
+			l.gap(4)
+			l.text(block.Text, pdfHeadingStyle(block.Level), indent, "")
+		case BlockParagraph:
+			l.text(block.Text, pdfBodyStyle, indent, "")
+		case BlockCode, BlockDiff, BlockDiagram, BlockMath:
+			l.code(block.Code, indent)
+		case BlockList, BlockTaskList, BlockDefList:
+			for n, item := range block.Items {
+				marker := "•"
+				switch {
+				case block.Type == BlockTaskList && item.Checked:
+					marker = "[x]"
+				case block.Type == BlockTaskList:
+					marker = "[ ]"
+				case block.Type == BlockDefList:
+					marker = ""
+				case block.Ordered:
+					marker = fmt.Sprintf("%d.", block.Start+n)
+				}
+				l.text(item.Text, pdfBodyStyle, indent+pdfIndent, marker)
+				if len(item.Children) > 0 {
+					l.blocks(item.Children, indent+2*pdfIndent)
+				}
+			}
+		case BlockQuote, BlockFootnote:
+			l.blocks(block.Children, indent+pdfIndent)
+		case BlockTable:
+			rows := append([][]string{block.Headers}, block.Rows...)
+			lines := make([]string, len(rows))
+			for n, row := range rows {
+				lines[n] = strings.Join(row, " | ")
+			}
+			l.code(strings.Join(lines, "\n"), indent)
+		case BlockRule:
+			l.rule(indent)
+		case BlockImage:
+			l.text(fmt.Sprintf("[%s] %s", block.Alt, block.URL), pdfPromptStyle, indent, "")
+		default:
+			l.text(renderBlock(block), pdfBodyStyle, indent, "")
+		}
+	}
+}
+
+// pdf lays the export out as A4 pages: a table of contents linking to each
+// exchange, then the exchanges a page apart, numbered at the foot and
+// bookmarked in the reader's outline
+func (e *Exporter) pdf() []byte {
+	var body pdfLayout
+	var marks []pdfMark
+	for i, entry := range e.Entries {
+		body.newPage()
+		marks = append(marks, pdfMark{title: entry.heading(i), page: len(body.pages) - 1, y: body.y})
+		body.text(entry.heading(i), pdfExchangeStyle, 0, "")
+		if byline := entry.byline(); byline != "" {
+			body.text(byline, pdfMetaStyle, 0, "")
+		}
+		if entry.Prompt != "" {
+			body.gap(6)
+			body.text(entry.Prompt, pdfPromptStyle, pdfIndent, "")
+		}
+		body.gap(10)
+		body.blocks(entry.Blocks, 0)
+	}
+
+	// The contents list every exchange on a line, so how many pages they
+	// take is known before the page numbers are
+	var toc pdfLayout
+	toc.text(e.Title, pdfTitleStyle, 0, "")
+	toc.text("Exported "+time.Now().Format("2006-01-02 15:04"), pdfMetaStyle, 0, "")
+	toc.gap(18)
+	if len(e.Entries) == 0 {
+		toc.text("Nothing to export.", pdfBodyStyle, 0, "")
+	} else {
+		toc.text("Contents", pdfExchangeStyle, 0, "")
+		toc.gap(4)
+	}
+	type tocLine struct {
+		page int
+		y    float64 // bottom
+	}
+	lines := make([]tocLine, len(marks))
+	for i, mark := range marks {
+		title := winAnsi(mark.title)
+		if room := pdfTextWidth - 40; pdfBodyStyle.width(title) > room {
+			// Cut to fit with an ellipsis, 0x85 in WinAnsi
+			for len(title) > 0 && pdfBodyStyle.width(title)+pdfBodyStyle.width([]byte{0x85}) > room {
+				title = title[:len(title)-1]
+			}
+			title = append(title, 0x85)
+		}
+		bottom := toc.line(pdfBodyStyle.leading)
+		toc.page().show(pdfBodyStyle, pdfMargin, bottom+3, title)
+		lines[i] = tocLine{page: len(toc.pages) - 1, y: bottom}
+	}
+	offset := len(toc.pages)
+	for i := range marks {
+		marks[i].page += offset
+		number := winAnsi(strconv.Itoa(marks[i].page + 1))
+		page := toc.pages[lines[i].page]
+		page.show(pdfBodyStyle, pdfMargin+pdfTextWidth-pdfBodyStyle.width(number), lines[i].y+3, number)
+		page.links = append(page.links, pdfLink{
+			x1: pdfMargin, y1: lines[i].y, x2: pdfMargin + pdfTextWidth, y2: lines[i].y + pdfBodyStyle.leading,
+			target: marks[i],
+		})
+	}
+
+	pages := append(toc.pages, body.pages...)
+	for i, page := range pages {
+		footer := winAnsi(fmt.Sprintf("%d / %d", i+1, len(pages)))
+		page.show(pdfMetaStyle, (pdfPageWidth-pdfMetaStyle.width(footer))/2, pdfMargin/2, footer)
+	}
+	return writePDF(e.Title, pages, marks)
+}
+
+// writePDF assembles pages into a PDF file with marks as its outline.
+// Objects are numbered: 1 the catalog, 2 the page tree, 3 the document
+// info, then the fonts, each page and its content, and the outline.
+func writePDF(title string, pages []*pdfPage, marks []pdfMark) []byte {
+	firstPage := 4 + len(pdfFonts)
+	pageRef := func(i int) string { return fmt.Sprintf("%d 0 R", firstPage+2*i) }
+	outlines := firstPage + 2*len(pages)
+	dest := func(mark pdfMark) string {
+		return fmt.Sprintf("[%s /XYZ 0 %s 0]", pageRef(mark.page), pdfNumber(mark.y))
+	}
+
+	var out bytes.Buffer
+	var offsets []int
+	object := func(body string) {
+		offsets = append(offsets, out.Len())
+		fmt.Fprintf(&out, "%d 0 obj\n%s\nendobj\n", len(offsets), body)
+	}
+	out.WriteString("%PDF-1.4\n%\xe2\xe3\xcf\xd3\n")
+
+	catalog := "<< /Type /Catalog /Pages 2 0 R"
+	if len(marks) > 0 {
+		catalog += fmt.Sprintf(" /Outlines %d 0 R /PageMode /UseOutlines", outlines)
+	}
+	object(catalog + " >>")
+	kids := make([]string, len(pages))
+	for i := range pages {
+		kids[i] = pageRef(i)
+	}
+	object(fmt.Sprintf("<< /Type /Pages /Kids [%s] /Count %d >>", strings.Join(kids, " "), len(pages)))
+	object(fmt.Sprintf("<< /Title %s /Producer (Vibe Coder) /CreationDate (D:%s) >>",
+		pdfString(winAnsi(title)), time.Now().Format("20060102150405")))
+	fonts := make([]string, len(pdfFonts))
+	for i, font := range pdfFonts {
+		object(fmt.Sprintf("<< /Type /Font /Subtype /Type1 /BaseFont /%s /Encoding /WinAnsiEncoding >>", font.base))
+		fonts[i] = fmt.Sprintf("/%s %d 0 R", font.name, 4+i)
+	}
+	for i, page := range pages {
+		annots := ""
+		if len(page.links) > 0 {
+			links := make([]string, len(page.links))
+			for n, link := range page.links {
+				links[n] = fmt.Sprintf("<< /Type /Annot /Subtype /Link /Rect [%s %s %s %s] /Border [0 0 0] /Dest %s >>",
+					pdfNumber(link.x1), pdfNumber(link.y1), pdfNumber(link.x2), pdfNumber(link.y2), dest(link.target))
+			}
+			annots = " /Annots [" + strings.Join(links, " ") + "]"
+		}
+		object(fmt.Sprintf("<< /Type /Page /Parent 2 0 R /MediaBox [0 0 %s %s] /Resources << /Font << %s >> >> /Contents %d 0 R%s >>",
+			pdfNumber(pdfPageWidth), pdfNumber(pdfPageHeight), strings.Join(fonts, " "), firstPage+2*i+1, annots))
+		object(fmt.Sprintf("<< /Length %d >>\nstream\n%sendstream", page.content.Len(), page.content.String()))
+	}
+	if len(marks) > 0 {
+		object(fmt.Sprintf("<< /Type /Outlines /First %d 0 R /Last %d 0 R /Count %d >>", outlines+1, outlines+len(marks), len(marks)))
+		for i, mark := range marks {
+			item := fmt.Sprintf("<< /Title %s /Parent %d 0 R /Dest %s", pdfString(winAnsi(mark.title)), outlines, dest(mark))
+			if i > 0 {
+				item += fmt.Sprintf(" /Prev %d 0 R", outlines+i)
+			}
+			if i < len(marks)-1 {
+				item += fmt.Sprintf(" /Next %d 0 R", outlines+i+2)
+			}
+			object(item + " >>")
+		}
+	}
+
+	xref := out.Len()
+	fmt.Fprintf(&out, "xref\n0 %d\n0000000000 65535 f \n", len(offsets)+1)
+	for _, offset := range offsets {
+		fmt.Fprintf(&out, "%010d 00000 n \n", offset)
+	}
+	fmt.Fprintf(&out, "trailer\n<< /Size %d /Root 1 0 R /Info 3 0 R >>\nstartxref\n%d\n%%%%EOF\n", len(offsets)+1, xref)
+	return out.Bytes()
+}
@@ -0,0 +1,88 @@
+package main
+
+import (
+	"bytes"
+	"fmt"
+	"reflect"
+	"regexp"
+	"strconv"
+	"strings"
+	"testing"
+)
+
+func TestExporterPDF(t *testing.T) {
+	exporter := demoExporter()
+	exporter.Entries = append(exporter.Entries, ExportEntry{Prompt: "And (again)?", Response: "Yes.", Blocks: ParseResponse("Yes.")})
+	data, err := exporter.Render(ExportPDF)
+	if err != nil {
+		t.Fatal(err)
+	}
+	if !bytes.HasPrefix(data, []byte("%PDF-1.4\n")) || !bytes.HasSuffix(data, []byte("%%EOF\n")) {
+		t.Fatalf("no PDF header and trailer in %q", data)
+	}
+	// The contents, then a page per exchange
+	for _, want := range []string{"/Count 3 >>", "/Title (1. How do I print?)", `/Title (2. And \(again\)?)`} {
+		if !bytes.Contains(data, []byte(want)) {
+			t.Errorf("no %q", want)
+		}
+	}
+
+	start := bytes.LastIndex(data, []byte("startxref\n"))
+	xref, err := strconv.Atoi(strings.Fields(string(data[start+len("startxref\n"):]))[0])
+	if err != nil || !bytes.HasPrefix(data[xref:], []byte("xref\n")) {
+		t.Fatalf("startxref doesn't point at the xref table: %v", err)
+	}
+	offsets := regexp.MustCompile(`(?m)^(\d{10}) 00000 n $`).FindAllSubmatch(data[xref:], -1)
+	if len(offsets) == 0 {
+		t.Fatal("no objects in the xref table")
+	}
+	for i, match := range offsets {
+		offset, _ := strconv.Atoi(string(match[1]))
+		if want := fmt.Sprintf("%d 0 obj\n", i+1); !bytes.HasPrefix(data[offset:], []byte(want)) {
+			t.Errorf("object %d is listed at %d, where there's %q", i+1, offset, data[offset:min(offset+20, len(data))])
+		}
+	}
+}
+
+func TestWrapPDFText(t *testing.T) {
+	// Courier at 8.5 points fits 10 characters in 51
+	tests := []struct {
+		name string
+		text string
+		want []string
+	}{
+		{"at spaces", "short words here", []string{"short", "words here"}},
+		{"inside a long word", strings.Repeat("x", 25), []string{strings.Repeat("x", 10), strings.Repeat("x", 10), "xxxxx"}},
+		{"blank lines kept", "one\n\ntwo", []string{"one", "", "two"}},
+		{"runs of spaces collapse", "a  b", []string{"a b"}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			var got []string
+			for _, line := range wrapPDFText([]byte(tt.text), pdfCodeStyle, 51) {
+				got = append(got, string(line))
+			}
+			if !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %q, want %q", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestWinAnsi(t *testing.T) {
+	tests := []struct {
+		text string
+		want string
+	}{
+		{"café", "caf\xe9"},
+		{"“quoted” – and…", "\x93quoted\x94 \x96 and\x85"},
+		{"\tindented", "    indented"},
+		{"check ✓", "check ?"},
+		{"a\x00b\nc", "ab\nc"},
+	}
+	for _, tt := range tests {
+		if got := string(winAnsi(tt.text)); got != tt.want {
+			t.Errorf("winAnsi(%q) = %q, want %q", tt.text, got, tt.want)
+		}
+	}
+}
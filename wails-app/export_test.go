@@ -0,0 +1,137 @@
+package main
+
+import (
+	"errors"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+// demoExporter exports a one-turn session with a heading and code in its
+// answer
+func demoExporter() *Exporter {
+	return NewSessionExporter(Session{Name: "Demo", Messages: []ChatMessage{
+		{Role: "user", Content: "How do I print?"},
+		{Role: "assistant", Content: "Use `fmt`:\n\n# Heading\n\n```go\nfmt.Println(1)\n```"},
+	}})
+}
+
+func TestExporterRender(t *testing.T) {
+	tests := []struct {
+		format string
+		want   []string // in the output, in order
+	}{
+		{ExportMarkdown, []string{"# Demo\n", "\n## 1. How do I print?\n\n", "> How do I print?\n", "### Heading", "```go\nfmt.Println(1)\n```"}},
+		{ExportPlainText, []string{"Demo\n====\n", "1. How do I print?\n------------------\n", "Use fmt:", "HEADING", "    fmt.Println(1)"}},
+		{ExportJSON, []string{`"version": 1`, `"title": "Demo"`, `"prompt": "How do I print?"`}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.format, func(t *testing.T) {
+			data, err := demoExporter().Render(tt.format)
+			if err != nil {
+				t.Fatal(err)
+			}
+			rest := string(data)
+			for _, want := range tt.want {
+				i := strings.Index(rest, want)
+				if i < 0 {
+					t.Fatalf("no %q after the earlier parts in\n%s", want, data)
+				}
+				rest = rest[i+len(want):]
+			}
+		})
+	}
+	if _, err := demoExporter().Render("bogus"); err == nil {
+		t.Error("an unknown format rendered")
+	}
+}
+
+func TestExportProvenance(t *testing.T) {
+	licensed := "```go\n// SPDX-License-Identifier: MIT\npackage main\n```"
+	stored := &ProvenanceReport{Flags: []ProvenanceFlag{{Kind: "verbatim-snippet", Detail: "kept"}}, Note: "Stored"}
+	tests := []struct {
+		name     string
+		entry    SessionEntry
+		markdown string
+		json     string
+	}{
+		{
+			name:     "worked out for older entries",
+			entry:    SessionEntry{ID: 1, Prompt: "License?", Response: licensed, Provider: "openai"},
+			markdown: "_openai_\n\n_Provenance: AI-generated code: 1 block(s) flagged for license/provenance review; code block 1 (go), license header: SPDX identifier: \"SPDX-License-Identifier: MIT\"_\n\n> License?\n",
+			json:     `"kind": "license-header"`,
+		},
+		{
+			name:     "kept from the history",
+			entry:    SessionEntry{ID: 1, Prompt: "License?", Response: licensed, Provider: "openai", Provenance: stored},
+			markdown: "_Provenance: Stored; code block 1, verbatim snippet: kept_\n",
+			json:     `"note": "Stored"`,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			exporter := NewHistoryExporter("History", []SessionEntry{tt.entry})
+			markdown, err := exporter.Render(ExportMarkdown)
+			if err != nil {
+				t.Fatal(err)
+			}
+			if !strings.Contains(string(markdown), tt.markdown) {
+				t.Errorf("no %q in\n%s", tt.markdown, markdown)
+			}
+			data, err := exporter.Render(ExportJSON)
+			if err != nil {
+				t.Fatal(err)
+			}
+			if !strings.Contains(string(data), tt.json) {
+				t.Errorf("no %q in\n%s", tt.json, data)
+			}
+
+			imported, err := ParseImport(markdown, "")
+			if err != nil {
+				t.Fatal(err)
+			}
+			if len(imported) != 1 || imported[0].Prompt != tt.entry.Prompt || imported[0].Response != tt.entry.Response {
+				t.Errorf("read back %+v, want the prompt and response without the provenance line", imported)
+			}
+		})
+	}
+}
+
+func TestExporterSave(t *testing.T) {
+	path := filepath.Join(t.TempDir(), "exports", "demo.md")
+	if err := demoExporter().Save(path, ExportMarkdown, false); err != nil {
+		t.Fatal(err)
+	}
+	if err := demoExporter().Save(path, ExportMarkdown, false); !errors.Is(err, ErrExportExists) {
+		t.Errorf("saving over the export got %v, want ErrExportExists", err)
+	}
+	if err := os.WriteFile(path, []byte("mine"), 0o644); err != nil {
+		t.Fatal(err)
+	}
+	if err := demoExporter().Save(path, ExportMarkdown, true); err != nil {
+		t.Fatal(err)
+	}
+	if data, _ := os.ReadFile(path); !strings.HasPrefix(string(data), "# Demo\n") {
+		t.Errorf("overwriting left %q", data)
+	}
+}
+
+func TestExportFileName(t *testing.T) {
+	tests := []struct {
+		title  string
+		format string
+		want   string
+	}{
+		{"Demo", ExportMarkdown, "Demo.md"},
+		{`a/b: c?`, ExportPDF, "a-b- c-.pdf"},
+		{"tab\there", ExportPlainText, "tab-here.txt"},
+		{"  ", ExportJSON, "export.json"},
+		{"Project", ExportCodeTree, "Project"},
+	}
+	for _, tt := range tests {
+		if got := exportFileName(tt.title, tt.format); got != tt.want {
+			t.Errorf("exportFileName(%q, %q) = %q, want %q", tt.title, tt.format, got, tt.want)
+		}
+	}
+}
@@ -0,0 +1,80 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+)
+
+// ErrNoJSON is returned when a response contains no JSON object
+var ErrNoJSON = errors.New("no JSON object found in response")
+
+// ExtractJSON decodes the first JSON object in a response into v, whether it's
+// fenced or surrounded by prose, for structured-output and tool-calling prompts
+func ExtractJSON(response string, v any) error {
+	objects := jsonObjects(response)
+	if len(objects) == 0 {
+		return ErrNoJSON
+	}
+	return json.Unmarshal([]byte(objects[0]), v)
+}
+
+// ExtractLastJSON is ExtractJSON for the last object, for models that reason
+// before giving their answer
+func ExtractLastJSON(response string, v any) error {
+	objects := jsonObjects(response)
+	if len(objects) == 0 {
+		return ErrNoJSON
+	}
+	return json.Unmarshal([]byte(objects[len(objects)-1]), v)
+}
+
+// jsonObjects returns the outermost valid JSON objects in text, in order.
+// Braces that don't open valid JSON, like "{name}" in prose, are skipped.
+func jsonObjects(text string) []string {
+	var objects []string
+	for i := 0; i < len(text); i++ {
+		if text[i] != '{' {
+			continue
+		}
+		end := matchingBrace(text, i)
+		if end < 0 || !json.Valid([]byte(text[i:end])) {
+			continue
+		}
+		objects = append(objects, text[i:end])
+		i = end - 1
+	}
+	return objects
+}
+
+// matchingBrace returns the index just past the brace closing the one at
+// text[start], ignoring braces inside strings, or -1 if it never closes
+func matchingBrace(text string, start int) int {
+	depth := 0
+	inString := false
+	for i := start; i < len(text); i++ {
+		switch c := text[i]; {
+		case inString && c == '\\':
+			i++
+		case c == '"':
+			inString = !inString
+		case inString:
+		case c == '{':
+			depth++
+		case c == '}':
+			depth--
+			if depth == 0 {
+				return i + 1
+			}
+		}
+	}
+	return -1
+}
+
+// ExtractJSON returns the first JSON object in a response, undecoded
+func (a *App) ExtractJSON(response string) (json.RawMessage, error) {
+	var raw json.RawMessage
+	if err := ExtractJSON(response, &raw); err != nil {
+		return nil, err
+	}
+	return raw, nil
+}
@@ -0,0 +1,49 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"testing"
+)
+
+func TestExtractJSON(t *testing.T) {
+	tests := []struct {
+		name     string
+		response string
+		want     string
+		wantErr  error
+	}{
+		{"fenced", "Here:\n```json\n{\"a\": 1}\n```", `{"a": 1}`, nil},
+		{"prose braces are skipped", "Fill in {name} like this: {\"b\": true}", `{"b": true}`, nil},
+		{"braces inside strings", `{"s": "a } b"} and more`, `{"s": "a } b"}`, nil},
+		{"nested", `{"a": {"b": [1, {"c": 2}]}}`, `{"a": {"b": [1, {"c": 2}]}}`, nil},
+		{"first of several", `{"n": 1} then {"n": 2}`, `{"n": 1}`, nil},
+		{"no JSON", "nothing to see", "", ErrNoJSON},
+		{"unclosed", `{"a": 1`, "", ErrNoJSON},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			var got json.RawMessage
+			err := ExtractJSON(tt.response, &got)
+			if !errors.Is(err, tt.wantErr) {
+				t.Fatalf("err = %v, want %v", err, tt.wantErr)
+			}
+			if string(got) != tt.want {
+				t.Errorf("got %s, want %s", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestExtractLastJSON(t *testing.T) {
+	var got struct{ N int }
+	if err := ExtractLastJSON("Thinking {\"n\": 1}, so the answer is {\"n\": 2}", &got); err != nil {
+		t.Fatal(err)
+	}
+	if got.N != 2 {
+		t.Errorf("got %d, want 2", got.N)
+	}
+	if err := ExtractLastJSON("no objects", &got); !errors.Is(err, ErrNoJSON) {
+		t.Errorf("err = %v, want ErrNoJSON", err)
+	}
+}
@@ -1,30 +1,525 @@
-import React, { useState } from 'react';
+import React, { useEffect, useLayoutEffect, useRef, useState } from 'react';
 import { Editor } from '@monaco-editor/react';
-import { FolderOpen, Brain, Cog, PlugZap, Send, X } from 'lucide-react';
+import { FolderOpen, Brain, Cog, PlugZap, RotateCw, Send, X } from 'lucide-react';
+import { ContentBlock, ContentView, CopyMode, HighlightToken, Section, StreamedBlocks } from './ContentView';
+import { PromptBubble, Transcript } from './Transcript';
 
 // Placeholder for Wails-bound API (after wails generate)
 declare global { 
   interface Window { 
     backend?: { 
       App?: { 
-        SendPrompt(prompt: string): Promise<string>;
+        SendPrompt(prompt: string, history: ChatMessage[], options: RequestOptions): Promise<AIResponse>;
+        SendPromptStream(prompt: string, history: ChatMessage[], options: RequestOptions): Promise<AIResponse>;
+        ComparePrompt(prompt: string, history: ChatMessage[], options: RequestOptions): Promise<ProviderResult[]>;
+        SendBatch(prompts: string[], options: RequestOptions, concurrency: number): Promise<ProviderResult[]>;
+        ClearResponseCache(): Promise<void>;
+        SetRedactionMode(mode: RedactionMode): Promise<void>;
+        GetRedactionMode(): Promise<RedactionMode>;
+        SetRequestLogMode(mode: RequestLogMode): Promise<void>;
+        GetRequestLogMode(): Promise<RequestLogMode>;
+        OpenRequestLog(): Promise<void>;
+        ClearRequestLog(): Promise<void>;
+        SetContextCompression(enabled: boolean): Promise<void>;
+        SetOfflineMode(offline: boolean): Promise<void>;
+        GetOfflineMode(): Promise<boolean>;
+        GetContextCompression(): Promise<boolean>;
         AddProvider(config: ProviderConfig): Promise<void>;
         ListProviders(): Promise<string[]>;
         SetActiveProvider(index: number): Promise<void>;
+        GetActiveProvider(): Promise<number>;
+        GetProviderHealth(): Promise<ProviderHealth[]>;
+        CheckProviders(): Promise<ProviderHealth[]>;
+        ListModels(config: ProviderConfig): Promise<string[]>;
+        GetProviderCapabilities(): Promise<Capabilities[]>;
+        CapabilitiesFor(config: ProviderConfig): Promise<Capabilities>;
+        AnalyzeProvenance(response: string, textFormat: string): Promise<ProvenanceReport>;
+        ParseResponse(response: string, format: string): Promise<ContentBlock[]>;
+        ExtractJSON(response: string): Promise<unknown>;
+        RenderMarkdown(blocks: ContentBlock[]): Promise<string>;
+        HighlightCode(code: string, language: string): Promise<HighlightToken[]>;
+        Sections(blocks: ContentBlock[]): Promise<Section[]>;
+        SectionMarkdown(section: Section): Promise<string>;
+        ListPlugins(): Promise<PluginMetadata[]>;
+        SetPluginOrder(names: string[]): Promise<void>;
+        SetPluginEnabled(name: string, enabled: boolean): Promise<void>;
+        SetPluginErrorPolicy(name: string, onError: PluginOnError, maxFailures: number): Promise<void>;
+        ReloadPlugin(name: string): Promise<void>;
+        UnloadPlugin(name: string): Promise<void>;
+        GetPluginStats(): Promise<PluginStats[]>;
+        SetPluginTimeout(name: string, stage: string, timeoutMs: number): Promise<void>;
+        GetMarketplaceSettings(): Promise<MarketplaceSettings>;
+        SetMarketplaceSettings(settings: MarketplaceSettings): Promise<void>;
+        ListMarketplace(): Promise<MarketplaceListing[]>;
+        InstallPlugin(name: string, version: string): Promise<InstalledPlugin>;
+        UninstallPlugin(name: string): Promise<void>;
+        GetUIConfig(): Promise<UIConfig>;
+        SetUIConfig(ui: UIConfig): Promise<void>;
+        GetTheme(): Promise<Theme>;
+        ListThemes(): Promise<string[]>;
+        GetConfigValues(): Promise<ConfigValue[]>;
+        StoreAPIKey(account: string, key: string): Promise<string>;
+        SearchSessionHistory(query: string): Promise<SessionEntry[]>;
+        QuerySessionHistory(query: HistoryQuery): Promise<HistoryMatch[]>;
+        SemanticSearchHistory(query: string, k: number): Promise<HistoryMatch[]>;
+        PinSessionEntry(id: number, pinned: boolean): Promise<SessionEntry>;
+        TagSessionEntry(id: number, tag: string): Promise<SessionEntry>;
+        UntagSessionEntry(id: number, tag: string): Promise<SessionEntry>;
+        ListHistoryTags(): Promise<string[]>;
+        ClearSessionHistory(): Promise<void>;
+        ChooseImportPath(): Promise<string>;
+        ImportSessionHistory(path: string, conflict: 'keep-both' | 'skip' | 'replace'): Promise<ImportResult>;
+        ImportChats(path: string): Promise<ChatImportResult>;
+        CopyBlock(block: ContentBlock, mode: CopyMode): Promise<void>;
+        CopyResponse(response: string): Promise<void>;
+        ConfigurePlugin(name: string, config: unknown): Promise<void>;
+        GetPluginConfig(name: string): Promise<unknown>;
+        ListCommands(): Promise<PluginCommand[]>;
+        RunCommand(line: string): Promise<CommandResult>;
+        StartSession(): Promise<void>;
+        ListSessions(): Promise<SessionSummary[]>;
+        ActiveSession(): Promise<Session>;
+        NewSession(name: string): Promise<Session>;
+        OpenSession(id: string): Promise<Session>;
+        ForkSession(id: string, turns: number): Promise<Session>;
+        ChooseExportPath(title: string, format: ExportFormat): Promise<string>;
+        ExportSession(id: string, format: ExportFormat, path: string, overwrite: boolean): Promise<void>;
+        ExportSessionHistory(query: HistoryQuery, format: ExportFormat, path: string, overwrite: boolean): Promise<void>;
+        RenameSession(id: string, name: string): Promise<void>;
+        DeleteSession(id: string): Promise<void>;
+        SaveDraft(text: string): Promise<void>;
+        InterruptedSessions(): Promise<Session[]>;
+        RecoverSession(id: string): Promise<Session>;
+        DiscardRecovery(id: string): Promise<void>;
+        CountTokens(prompt: string, history: ChatMessage[]): Promise<TokenBudget>;
+        Embed(texts: string[]): Promise<number[][]>;
+        GetCostSummary(): Promise<CostSummary>;
+        GetUsageReport(days: number): Promise<UsageReport>;
+        ClearUsageHistory(): Promise<void>;
+        PendingCrashReport(): Promise<CrashReport | null>;
+        SubmitCrashReport(id: string): Promise<void>;
+        DismissCrashReport(id: string): Promise<void>;
       } 
-    } 
+    };
+    runtime?: {
+      EventsOn(eventName: string, callback: (...data: any[]) => void): () => void;
+      BrowserOpenURL(url: string): void;
+    };
   } 
 }
 
+interface MockScript {
+  responses: string[];
+  latencyMs: number;
+  failEvery: number;
+}
+
 interface ProviderConfig {
   type: string;
   name: string;
   apiKey: string;
   endpoint: string;
   model: string;
+  timeout?: number;
+  safetyThreshold?: string;
+  requestsPerMinute?: number;
+  tokensPerMinute?: number;
+  systemPrompt?: string;
+  embeddingModel?: string;
+  proxyUrl?: string;
+  caBundlePath?: string;
+  disableTlsVerify?: boolean;
+  mockScript?: MockScript | null;
+  textFormat?: '' | 'markdown' | 'plain';
+}
+
+interface ManagerStatus {
+  provider: string;
+  kind: 'queued' | 'rate_limited' | 'summarizing';
+  message: string;
+  waitSeconds: number;
+  queued: number;
+}
+
+interface Capabilities {
+  streaming: boolean;
+  modelListing: boolean;
+  embeddings: boolean;
+  healthCheck: boolean;
+  tools: boolean;
+  vision: boolean;
+}
+
+interface PluginMetadata {
+  name: string;
+  version: string;
+  description: string;
+  priority: number;
+  capabilities: string[];
+  enabled: boolean;
+  path?: string; // the library or script a dynamic plugin was loaded from
+  requires?: string[];
+  conflictsWith?: string[];
+  errorPolicy: { onError: PluginOnError; maxFailures: number };
+}
+
+type PluginOnError = 'skip' | 'disable' | 'abort';
+
+interface PluginFailure {
+  plugin: string;
+  stage: string;
+  error: string;
+  disabled: boolean; // this failure disabled the plugin
+}
+
+interface PluginStats {
+  plugin: string;
+  stage: string;
+  calls: number;
+  failures: number; // timeouts included
+  timeouts: number;
+  totalMs: number;
+  maxMs: number;
+  timeoutMs: number;
+}
+
+interface UIConfig {
+  theme: string; // 'dark', 'light' or a palette from config.toml
+  style: 'vscode' | 'zed';
+  fontFamily: string;
+  fontSize: number;
+}
+
+// Mirrors Palette in theme.go
+interface Palette {
+  base: 'dark' | 'light';
+  background: string;
+  panel: string;
+  tab: string;
+  activity: string;
+  raised: string;
+  hover: string;
+  selection: string;
+  border: string;
+  code: string;
+  text: string;
+  softText: string;
+  muted: string;
+  accent: string;
+  keyword: string;
+  string: string;
+  comment: string;
+  number: string;
+  type: string;
+  function: string;
+}
+
+interface Theme {
+  name: string;
+  palette: Palette;
+}
+
+interface Session {
+  id: string;
+  name: string; // empty until named
+  created: string;
+  updated: string;
+  messages: ChatMessage[];
+  draft?: string; // the prompt being written
+  pending?: PendingTurn; // a prompt still waiting on its answer
+  forkedFrom?: string; // the session this one was forked from
+  forkedAt?: number; // the turns copied from it
+}
+
+interface PendingTurn {
+  prompt: string;
+  partial: string; // the answer as far as it streamed in
+  started: string;
+}
+
+interface SessionSummary {
+  id: string;
+  title: string; // the name, or the start of the first prompt
+  named: boolean;
+  updated: string;
+  turns: number;
+  active: boolean;
+}
+
+interface SessionEntry {
+  id: number;
+  time: string; // RFC 3339
+  prompt: string;
+  response: string;
+  provider: string;
+  model: string;
+  textFormat?: string;
+  tokens: number;
+  cost: number;
+  pinned?: boolean; // kept however full the history gets
+  tags?: string[];
+  stats?: ResponseStats; // all zeros in entries recorded before it was kept
+  provenance?: ProvenanceReport; // missing from entries recorded before it was kept
+}
+
+interface HistoryQuery {
+  text: string;
+  provider?: string; // empty for any
+  from?: string; // YYYY-MM-DD or RFC 3339, both ends included
+  to?: string;
+  tag?: string; // empty for any
+  pinned?: boolean; // only pinned entries
+  limit?: number;
+}
+
+interface HistoryMatch {
+  entry: SessionEntry;
+  score: number; // higher is better; cosine similarity for searches by meaning
+}
+
+interface ImportResult {
+  added: number;
+  replaced: number;
+  duplicates: number; // already in the history, so left out
+  conflicts: number; // ids the history had for another exchange
+  pruned: number;
+}
+
+interface ChatImportResult {
+  sessions: number;
+  skipped: number; // imported before, or empty
+  history: ImportResult;
+}
+
+interface ConfigValue {
+  key: string; // dotted, e.g. "ui.fontSize"
+  value: unknown;
+  source: 'default' | 'system' | 'user' | 'project' | 'env' | 'flag';
+  origin?: string; // the file, variable or flag that set it
+}
+
+interface MarketplaceSettings {
+  indexUrl: string;
+  publicKey?: string; // base64 Ed25519; when set, only signed plugins install
+}
+
+interface MarketplaceListing {
+  name: string;
+  description: string;
+  versions: string[]; // newest first
+  installed?: string;
+  update: boolean;
+}
+
+interface InstalledPlugin {
+  version: string;
+  path: string;
+  sha256: string;
+}
+
+interface CommandArg {
+  name: string;
+  description: string;
+  type: '' | 'string' | 'number' | 'boolean';
+  required: boolean;
+}
+
+// A plugin command, typed in the prompt box as /name args
+interface PluginCommand {
+  name: string;
+  description: string;
+  args: CommandArg[];
+  plugin?: string;
+}
+
+interface CommandResult {
+  plugin: string;
+  command: string;
+  output: string; // markdown
+}
+
+interface ProviderHealth {
+  name: string;
+  status: 'unknown' | 'ready' | 'unhealthy' | 'offline';
+  error: string;
+  checkedAt: string;
+}
+
+interface ChatMessage {
+  role: 'system' | 'user' | 'assistant';
+  content: string;
+  stats?: ResponseStats; // assistant turns; kept in the history, ignored by the backend
+  textFormat?: string; // assistant turns: the answering provider's format, for parsing content
+}
+
+interface RequestOptions {
+  noCache: boolean;
+  systemPrompt?: string; // overrides the provider's system prompt
+  provider?: string; // route to this provider instead of the active one
+  responseFormat?: ResponseFormat;
+  attachments?: Attachment[];
+  sampling?: Sampling;
+}
+
+// Advanced generation settings; zero values keep the provider's defaults
+interface Sampling {
+  topP: number;
+  frequencyPenalty: number;
+  presencePenalty: number;
+  stop: string[];
+}
+
+interface Attachment {
+  mimeType: string;
+  data: string; // base64
+}
+
+interface ResponseFormat {
+  type: '' | 'json' | 'json_schema';
+  schema?: unknown; // JSON Schema object, for json_schema
 }
 
-const PROVIDER_TYPES = ['Ollama', 'Copilot', 'Gemini', 'Claude', 'Mock'] as const;
+interface AIResponse {
+  content: string;
+  model: string;
+  promptTokens: number;
+  completionTokens: number;
+  cost: number;
+  provider: string;
+  attempts: number;
+  cached: boolean;
+  redacted?: string[] | null; // kinds of secret masked before sending
+  textFormat?: string;
+  pluginFailures?: PluginFailure[] | null; // plugins skipped over after failing
+  stats?: ResponseStats;
+}
+
+interface ResponseStats {
+  words: number;
+  characters: number;
+  tokens: number;
+  codeBlocks: number;
+  codeLines: number;
+  languages: string[];
+}
+
+type RedactionMode = 'mask' | 'block' | 'off';
+
+type ExportFormat = 'markdown' | 'json' | 'html' | 'text' | 'pdf' | 'code' | 'zip';
+
+const EXPORT_FORMATS: { format: ExportFormat; label: string }[] = [
+  { format: 'markdown', label: 'Markdown' },
+  { format: 'json', label: 'JSON' },
+  { format: 'html', label: 'HTML' },
+  { format: 'text', label: 'Plain text' },
+  { format: 'pdf', label: 'PDF' },
+  { format: 'code', label: 'Code files (folder)' },
+  { format: 'zip', label: 'Code files (zip)' },
+];
+
+const REDACTION_MODES: RedactionMode[] = ['mask', 'block', 'off'];
+
+// The CSS variable each palette color is set in
+const PALETTE_VARIABLES: Record<Exclude<keyof Palette, 'base'>, string> = {
+  background: '--vc-bg',
+  panel: '--vc-panel',
+  tab: '--vc-tab',
+  activity: '--vc-activity',
+  raised: '--vc-raised',
+  hover: '--vc-hover',
+  selection: '--vc-selection',
+  border: '--vc-border',
+  code: '--vc-code',
+  text: '--vc-text',
+  softText: '--vc-soft',
+  muted: '--vc-muted',
+  accent: '--vc-accent',
+  keyword: '--vc-tok-keyword',
+  string: '--vc-tok-string',
+  comment: '--vc-tok-comment',
+  number: '--vc-tok-number',
+  type: '--vc-tok-type',
+  function: '--vc-tok-function',
+};
+
+// How near the bottom, in pixels, the transcript counts as scrolled down,
+// following new content
+const TRANSCRIPT_FOLLOW_SLACK = 40;
+
+// Bounds of the prompt box's height in pixels, which grows with its text
+const PROMPT_MIN_HEIGHT = 80;
+const PROMPT_MAX_HEIGHT = 320;
+
+type RequestLogMode = 'off' | 'metadata' | 'full';
+
+const REQUEST_LOG_MODES: RequestLogMode[] = ['off', 'metadata', 'full'];
+
+interface ProviderResult {
+  provider: string;
+  response: AIResponse;
+  error: string;
+}
+
+interface UsageTotals {
+  requests: number;
+  promptTokens: number;
+  completionTokens: number;
+  cost: number;
+}
+
+interface UsageRecord extends UsageTotals {
+  day: string;
+  model: string;
+}
+
+interface UsageReport {
+  records: UsageRecord[];
+  byModel: Record<string, UsageTotals>;
+  total: UsageTotals;
+}
+
+// Day ranges offered in the usage report; 0 is all time
+const USAGE_RANGES = [7, 30, 0];
+
+interface CostSummary {
+  session: UsageTotals;
+  byProvider: Record<string, UsageTotals>;
+}
+
+interface TokenBudget {
+  model: string;
+  promptTokens: number;
+  historyTokens: number;
+  reservedTokens: number;
+  contextWindow: number;
+  exceeds: boolean;
+}
+
+interface ProvenanceFlag {
+  blockIndex: number;
+  language: string;
+  kind: 'license-header' | 'verbatim-snippet';
+  detail: string;
+}
+
+interface ProvenanceReport {
+  flags: ProvenanceFlag[];
+  note: string;
+}
+
+interface CrashReport {
+  id: string;
+  time: string;
+  appVersion: string;
+  platform: string;
+  goVersion: string;
+  activeProviderType: string;
+  panic: string;
+  backtrace: string;
+  recentLogs: string[];
+}
+
+const PROVIDER_TYPES = ['Ollama', 'Copilot', 'Gemini', 'Claude', 'OpenAI', 'OpenRouter', 'Mistral', 'Groq', 'LlamaCpp', 'Mock'] as const;
 type ProviderType = typeof PROVIDER_TYPES[number];
 
 const DEFAULT_ENDPOINTS: Record<ProviderType, string> = {
@@ -32,6 +527,11 @@ const DEFAULT_ENDPOINTS: Record<ProviderType, string> = {
   Copilot: 'https://api.githubcopilot.com',
   Gemini: 'https://generativelanguage.googleapis.com',
   Claude: 'https://api.anthropic.com',
+  OpenAI: 'https://api.openai.com/v1',
+  OpenRouter: 'https://openrouter.ai/api/v1',
+  Mistral: 'https://api.mistral.ai/v1',
+  Groq: 'https://api.groq.com/openai/v1',
+  LlamaCpp: '',
   Mock: '',
 };
 
@@ -40,28 +540,610 @@ const DEFAULT_MODELS: Record<ProviderType, string> = {
   Copilot: 'gpt-4o-mini',
   Gemini: 'gemini-pro',
   Claude: 'claude-3-opus',
+  OpenAI: 'gpt-4o-mini',
+  OpenRouter: 'openai/gpt-4o-mini',
+  Mistral: 'mistral-small-latest',
+  Groq: 'llama-3.1-8b-instant',
+  LlamaCpp: '/path/to/model.gguf',
   Mock: 'mock-model-v1',
 };
 
+// Provider types that run without any network connection
+const LOCAL_TYPES: ProviderType[] = ['LlamaCpp', 'Mock'];
+
+// Capability names shown in the provider list tooltip
+const CAPABILITY_LABELS: [keyof Capabilities, string][] = [
+  ['streaming', 'streaming'],
+  ['modelListing', 'model listing'],
+  ['embeddings', 'embeddings'],
+  ['tools', 'tools'],
+  ['vision', 'vision'],
+];
+
+// Model chips shown at once; longer lists are filtered by the model input
+const MAX_MODEL_CHIPS = 40;
+
+// History entries a search by meaning returns
+const SEMANTIC_HISTORY_RESULTS = 20;
+
+const GEMINI_SAFETY_THRESHOLDS = ['', 'BLOCK_NONE', 'BLOCK_ONLY_HIGH', 'BLOCK_MEDIUM_AND_ABOVE', 'BLOCK_LOW_AND_ABOVE'];
+
 const App: React.FC = () => {
   const [prompt, setPrompt] = useState('');
   const [response, setResponse] = useState<string>('');
+  const [conversation, setConversation] = useState<ChatMessage[]>([]);
+  const [answeredBy, setAnsweredBy] = useState('');
+  const [responseStats, setResponseStats] = useState<ResponseStats | null>(null);
+  const [redactionMode, setRedactionMode] = useState<RedactionMode>('mask');
+  const [requestLogMode, setRequestLogMode] = useState<RequestLogMode>('off');
+  const [summarizeHistory, setSummarizeHistory] = useState(true);
+  const [offlineMode, setOfflineMode] = useState(false);
+  const [compareResults, setCompareResults] = useState<ProviderResult[] | null>(null);
+  const [requestStatus, setRequestStatus] = useState('');
+  const [exportStatus, setExportStatus] = useState('');
+  const [tokenBudget, setTokenBudget] = useState<TokenBudget | null>(null);
+  const promptRef = useRef<HTMLTextAreaElement>(null);
+  const [pendingPrompt, setPendingPrompt] = useState(''); // sent and not yet answered
+  const responseAreaRef = useRef<HTMLDivElement>(null);
+  const followTranscript = useRef(true); // scrolled to the bottom, so new content keeps it there
+  const [costSummary, setCostSummary] = useState<CostSummary | null>(null);
   const [loading, setLoading] = useState(false);
+  const [provenance, setProvenance] = useState<ProvenanceReport | null>(null);
+  const [blocks, setBlocks] = useState<ContentBlock[] | null>(null);
+  const [showRaw, setShowRaw] = useState(false);
   const [style, setStyle] = useState<'vscode' | 'zed'>('vscode');
-  const [theme, setTheme] = useState<'dark' | 'light'>('dark');
+  const [theme, setTheme] = useState('dark');
+  const [themeBase, setThemeBase] = useState<Palette['base']>('dark');
+  const [themes, setThemes] = useState<string[]>(['dark', 'light']);
   const [fontFamily, setFontFamily] = useState<string>('JetBrains Mono');
   const [fontSize, setFontSize] = useState<number>(14);
+  const [uiLoaded, setUILoaded] = useState(false); // the saved look is applied, so changes may be saved
   const [showProviderDialog, setShowProviderDialog] = useState(false);
+  const [crashReport, setCrashReport] = useState<CrashReport | null>(null);
+  const [usageDays, setUsageDays] = useState<number | null>(null); // null hides the usage report
+  const [usageReport, setUsageReport] = useState<UsageReport | null>(null);
+  const [providers, setProviders] = useState<string[]>([]);
+  const [activeProvider, setActiveProvider] = useState(-1);
+  const [routeProvider, setRouteProvider] = useState(''); // '' sends to the active provider
+  const [formatType, setFormatType] = useState<ResponseFormat['type']>('');
+  const [attachments, setAttachments] = useState<Attachment[]>([]);
+  const [showSampling, setShowSampling] = useState(false);
+  const [topP, setTopP] = useState('');
+  const [frequencyPenalty, setFrequencyPenalty] = useState('');
+  const [presencePenalty, setPresencePenalty] = useState('');
+  const [stopSequences, setStopSequences] = useState(''); // one per line
+  const [responseSchema, setResponseSchema] = useState('');
+  const [providerHealth, setProviderHealth] = useState<ProviderHealth[]>([]);
+  const [providerCapabilities, setProviderCapabilities] = useState<Capabilities[]>([]);
+  const [typeCapabilities, setTypeCapabilities] = useState<Capabilities | null>(null);
+  const [plugins, setPlugins] = useState<PluginMetadata[]>([]);
+  const [commands, setCommands] = useState<PluginCommand[]>([]);
+  const [configuringPlugin, setConfiguringPlugin] = useState<string | null>(null);
+  const [pluginConfig, setPluginConfig] = useState(''); // JSON being edited
+  const [pluginConfigError, setPluginConfigError] = useState('');
+  const [pluginStats, setPluginStats] = useState<PluginStats[] | null>(null); // null while hidden
+  const [marketplace, setMarketplace] = useState<MarketplaceSettings>({ indexUrl: '' });
+  const [listings, setListings] = useState<MarketplaceListing[] | null>(null); // null until browsed
+  const [marketplaceError, setMarketplaceError] = useState('');
+  const [configValues, setConfigValues] = useState<ConfigValue[] | null>(null); // null while hidden
+  const [historyEntries, setHistoryEntries] = useState<SessionEntry[] | null>(null); // null while hidden
+  const [historyQuery, setHistoryQuery] = useState<HistoryQuery>({ text: '' });
+  const [historyError, setHistoryError] = useState('');
+  const [historyTags, setHistoryTags] = useState<string[]>([]);
+  const [sessions, setSessions] = useState<SessionSummary[]>([]);
+  const [sessionLoaded, setSessionLoaded] = useState(false); // the open session's draft is shown, so edits may be saved
+  const [interrupted, setInterrupted] = useState<Session[]>([]); // sessions the last run stopped in mid-answer
   
   // Provider dialog state
   const [providerTypeIndex, setProviderTypeIndex] = useState(0);
   const [providerName, setProviderName] = useState('');
   const [providerApiKey, setProviderApiKey] = useState('');
+  const [providerKeyring, setProviderKeyring] = useState(true); // keep the key in the OS keyring, not config.toml
   const [providerEndpoint, setProviderEndpoint] = useState('');
   const [providerModel, setProviderModel] = useState('');
+  const [providerTimeout, setProviderTimeout] = useState('');
+  const [providerSafety, setProviderSafety] = useState('');
+  const [providerSystemPrompt, setProviderSystemPrompt] = useState('');
+  const [providerTextFormat, setProviderTextFormat] = useState<'' | 'plain'>('');
+  const [providerEmbeddingModel, setProviderEmbeddingModel] = useState('');
+  const [providerProxy, setProviderProxy] = useState('');
+  const [providerCABundle, setProviderCABundle] = useState('');
+  const [providerInsecure, setProviderInsecure] = useState(false);
+  const [mockResponses, setMockResponses] = useState('');
+  const [mockLatency, setMockLatency] = useState('');
+  const [mockFailEvery, setMockFailEvery] = useState('');
+  const [providerRpm, setProviderRpm] = useState('');
+  const [providerTpm, setProviderTpm] = useState('');
+  const [availableModels, setAvailableModels] = useState<string[]>([]);
+  const [modelsError, setModelsError] = useState('');
+
+  const fonts = ['JetBrains Mono', 'Fira Code', 'SF Mono', 'Cascadia Code', 'Menlo'];
+  const currentProviderType = PROVIDER_TYPES[providerTypeIndex];
+
+  const refreshProviders = async () => {
+    const api = window.backend?.App;
+    if (!api?.ListProviders) return;
+    try {
+      setProviders(await api.ListProviders());
+      setActiveProvider(await api.GetActiveProvider());
+      setProviderHealth(await api.GetProviderHealth());
+      setProviderCapabilities(await api.GetProviderCapabilities());
+    } catch (e: any) {
+      console.error('Error listing providers:', e);
+    }
+  };
+
+  const selectProvider = async (index: number) => {
+    try {
+      await window.backend?.App?.SetActiveProvider(index);
+      setActiveProvider(index);
+    } catch (e: any) {
+      console.error('Error switching provider:', e);
+    }
+  };
+
+  // Moves a plugin one place earlier in the chain
+  const raisePlugin = async (index: number) => {
+    const names = plugins.map(plugin => plugin.name);
+    [names[index - 1], names[index]] = [names[index], names[index - 1]];
+    try {
+      await window.backend?.App?.SetPluginOrder(names);
+      const updated = await window.backend?.App?.ListPlugins();
+      if (updated) setPlugins(updated);
+    } catch (e: any) {
+      console.error('Error reordering plugins:', e);
+    }
+  };
+
+  const togglePlugin = async (plugin: PluginMetadata) => {
+    try {
+      await window.backend?.App?.SetPluginEnabled(plugin.name, !plugin.enabled);
+      setPlugins(plugins.map(p => p.name === plugin.name ? { ...p, enabled: !p.enabled } : p));
+      window.backend?.App?.ListCommands?.().then(setCommands).catch(() => {});
+    } catch (e: any) {
+      console.error('Error toggling plugin:', e);
+    }
+  };
+
+  // Picks up what changed in the plugins after a reload, unload or file change
+  const refreshPlugins = () => {
+    window.backend?.App?.ListPlugins?.().then(setPlugins).catch(() => {});
+    window.backend?.App?.ListCommands?.().then(setCommands).catch(() => {});
+  };
+
+  const reloadPlugin = async (name: string) => {
+    try {
+      await window.backend?.App?.ReloadPlugin(name);
+    } catch (e: any) {
+      console.error('Error reloading plugin:', e);
+    }
+    refreshPlugins();
+  };
+
+  const unloadPlugin = async (name: string) => {
+    try {
+      await window.backend?.App?.UnloadPlugin(name);
+    } catch (e: any) {
+      console.error('Error unloading plugin:', e);
+    }
+    refreshPlugins();
+  };
+
+  const togglePluginStats = async () => {
+    if (pluginStats) {
+      setPluginStats(null);
+      return;
+    }
+    const stats = await window.backend?.App?.GetPluginStats().catch(() => null);
+    setPluginStats(stats ?? []);
+  };
+
+  // Asks for a stage's timeout in seconds; an empty answer restores the default
+  const editPluginTimeout = async (stats: PluginStats) => {
+    const answer = window.prompt(`Timeout for ${stats.plugin} ${stats.stage}, in seconds (empty for the default)`, String(stats.timeoutMs / 1000));
+    if (answer === null) return;
+    try {
+      await window.backend?.App?.SetPluginTimeout(stats.plugin, stats.stage, Math.round(Number(answer || 0) * 1000));
+      const updated = await window.backend?.App?.GetPluginStats();
+      if (updated) setPluginStats(updated);
+    } catch (e: any) {
+      console.error('Error setting plugin timeout:', e);
+    }
+  };
+
+  const searchHistory = async (query: HistoryQuery) => {
+    setHistoryQuery(query);
+    setHistoryError('');
+    const matches = await window.backend?.App?.QuerySessionHistory(query).catch(() => null);
+    setHistoryEntries(matches?.map(match => match.entry) ?? []);
+    window.backend?.App?.ListHistoryTags?.().then(setHistoryTags).catch(() => {});
+  };
+
+  // Shows an entry's new pin or tags in place
+  const replaceHistoryEntry = (updated: SessionEntry) => {
+    setHistoryEntries(prev => prev && prev.map(entry => (entry.id === updated.id ? updated : entry)));
+    window.backend?.App?.ListHistoryTags?.().then(setHistoryTags).catch(() => {});
+  };
+
+  const togglePinned = async (entry: SessionEntry) => {
+    try {
+      const updated = await window.backend?.App?.PinSessionEntry(entry.id, !entry.pinned);
+      if (updated) replaceHistoryEntry(updated);
+    } catch (e: any) {
+      console.error('Error pinning history entry:', e);
+    }
+  };
+
+  const tagHistoryEntry = async (entry: SessionEntry) => {
+    const tag = window.prompt('Tag');
+    if (!tag?.trim()) return;
+    try {
+      const updated = await window.backend?.App?.TagSessionEntry(entry.id, tag);
+      if (updated) replaceHistoryEntry(updated);
+    } catch (e: any) {
+      console.error('Error tagging history entry:', e);
+    }
+  };
+
+  const untagHistoryEntry = async (entry: SessionEntry, tag: string) => {
+    try {
+      const updated = await window.backend?.App?.UntagSessionEntry(entry.id, tag);
+      if (updated) replaceHistoryEntry(updated);
+    } catch (e: any) {
+      console.error('Error untagging history entry:', e);
+    }
+  };
+
+  // Finds answers by what they are about rather than their words, with the
+  // active provider's embeddings; the first search embeds the whole history
+  const searchHistoryByMeaning = async () => {
+    const api = window.backend?.App;
+    if (!historyQuery.text.trim() || !api?.SemanticSearchHistory) return;
+    setHistoryError('');
+    try {
+      const matches = await api.SemanticSearchHistory(historyQuery.text, SEMANTIC_HISTORY_RESULTS);
+      setHistoryEntries(matches.map(match => match.entry));
+    } catch (e: any) {
+      setHistoryError(e.message || String(e));
+    }
+  };
+
+  const toggleHistory = () => {
+    if (historyEntries) {
+      setHistoryEntries(null);
+      return;
+    }
+    searchHistory(historyQuery);
+  };
+
+  // Merges an export, or another machine's history file, into the history;
+  // where an imported entry has the id of a different one, both are kept
+  // unless the user would rather it replace the one here. ChatGPT and Claude
+  // exports, a zip or its conversations.json, come in as sessions too.
+  const importHistory = async () => {
+    const api = window.backend?.App;
+    const path = await api?.ChooseImportPath();
+    if (!path) return;
+    setHistoryError('');
+    try {
+      if (/(\.zip|conversations\.json)$/i.test(path)) {
+        const result = await api!.ImportChats(path);
+        setExportStatus(`Imported ${result.sessions} conversations (${result.skipped} skipped) and ${result.history.added} exchanges`);
+        refreshSessions();
+        searchHistory(historyQuery);
+        return;
+      }
+      const replace = window.confirm('Where an imported entry has the id of a different one here, replace it? Cancel keeps both.');
+      const result = await api!.ImportSessionHistory(path, replace ? 'replace' : 'keep-both');
+      setExportStatus(`Imported ${result.added} new, ${result.replaced} replaced, ${result.duplicates} already here`);
+      searchHistory(historyQuery);
+    } catch (e: any) {
+      setHistoryError(e.message || String(e));
+    }
+  };
+
+  const clearHistory = async () => {
+    if (!window.confirm('Delete every recorded prompt and response?')) return;
+    try {
+      await window.backend?.App?.ClearSessionHistory();
+      setHistoryEntries([]);
+    } catch (e: any) {
+      console.error('Error clearing history:', e);
+    }
+  };
+
+  // Shows a recorded exchange again, with its prompt back in the prompt box
+  const openHistoryEntry = async (entry: SessionEntry) => {
+    const api = window.backend?.App;
+    setPrompt(entry.prompt);
+    setResponse(entry.response);
+    setAnsweredBy(`${entry.provider} · ${new Date(entry.time).toLocaleString()}`);
+    setResponseStats(entry.stats?.characters ? entry.stats : null);
+    setCompareResults(null);
+    setProvenance(entry.provenance || null);
+    setBlocks(api?.ParseResponse ? await api.ParseResponse(entry.response, entry.textFormat || '') : null);
+  };
+
+  const toggleConfigValues = async () => {
+    if (configValues) {
+      setConfigValues(null);
+      return;
+    }
+    const values = await window.backend?.App?.GetConfigValues().catch(() => null);
+    setConfigValues(values ?? []);
+  };
+
+  const saveMarketplace = async () => {
+    try {
+      await window.backend?.App?.SetMarketplaceSettings(marketplace);
+      setMarketplaceError('');
+    } catch (e: any) {
+      setMarketplaceError(e?.message || String(e));
+    }
+  };
+
+  const browseMarketplace = async () => {
+    try {
+      const offered = await window.backend?.App?.ListMarketplace();
+      setListings(offered ?? []);
+      setMarketplaceError('');
+    } catch (e: any) {
+      setMarketplaceError(e?.message || String(e));
+    }
+  };
+
+  // Installs the latest version; the plugin shows up once the watcher loads it
+  const installPlugin = async (name: string) => {
+    try {
+      await window.backend?.App?.InstallPlugin(name, '');
+      await browseMarketplace();
+    } catch (e: any) {
+      setMarketplaceError(e?.message || String(e));
+    }
+  };
+
+  const uninstallPlugin = async (name: string) => {
+    try {
+      await window.backend?.App?.UninstallPlugin(name);
+      await browseMarketplace();
+    } catch (e: any) {
+      setMarketplaceError(e?.message || String(e));
+    }
+  };
+
+  const setPluginErrorPolicy = async (plugin: PluginMetadata, onError: PluginOnError) => {
+    try {
+      await window.backend?.App?.SetPluginErrorPolicy(plugin.name, onError, plugin.errorPolicy.maxFailures);
+      setPlugins(plugins.map(p => p.name === plugin.name ? { ...p, errorPolicy: { ...p.errorPolicy, onError } } : p));
+    } catch (e: any) {
+      console.error('Error setting plugin error policy:', e);
+    }
+  };
+
+  const editPluginConfig = async (name: string) => {
+    if (configuringPlugin === name) {
+      setConfiguringPlugin(null);
+      return;
+    }
+    const config = await window.backend?.App?.GetPluginConfig(name).catch(() => null);
+    setPluginConfig(JSON.stringify(config ?? {}, null, 2));
+    setPluginConfigError('');
+    setConfiguringPlugin(name);
+  };
+
+  const savePluginConfig = async () => {
+    if (!configuringPlugin) return;
+    try {
+      await window.backend?.App?.ConfigurePlugin(configuringPlugin, JSON.parse(pluginConfig));
+      setConfiguringPlugin(null);
+    } catch (e: any) {
+      setPluginConfigError(e?.message || String(e));
+    }
+  };
+
+  const checkProviders = async () => {
+    try {
+      const health = await window.backend?.App?.CheckProviders();
+      if (health) setProviderHealth(health);
+    } catch (e: any) {
+      console.error('Error checking providers:', e);
+    }
+  };
+
+  useEffect(() => {
+    refreshProviders();
+    return window.runtime?.EventsOn('providers:health', (health: ProviderHealth[]) => setProviderHealth(health));
+  }, []);
+
+  useEffect(() => {
+    const api = window.backend?.App;
+    if (!api?.CapabilitiesFor) return;
+    api.CapabilitiesFor({ type: currentProviderType, name: '', apiKey: '', endpoint: '', model: '' })
+      .then(setTypeCapabilities)
+      .catch(() => setTypeCapabilities(null));
+  }, [currentProviderType]);
+
+  const refreshSettings = () => {
+    window.backend?.App?.GetRedactionMode?.().then(setRedactionMode).catch(() => {});
+    window.backend?.App?.GetRequestLogMode?.().then(setRequestLogMode).catch(() => {});
+    window.backend?.App?.GetContextCompression?.().then(setSummarizeHistory).catch(() => {});
+    window.backend?.App?.GetOfflineMode?.().then(setOfflineMode).catch(() => {});
+  };
+
+  // Colors the UI in a theme's palette, through the CSS variables behind
+  // the vc-* colors in tailwind.config.js
+  const applyTheme = ({ palette }: Theme) => {
+    const root = document.documentElement.style;
+    for (const [key, variable] of Object.entries(PALETTE_VARIABLES)) {
+      root.setProperty(variable, palette[key as keyof typeof PALETTE_VARIABLES]);
+    }
+    setThemeBase(palette.base);
+  };
+
+  const refreshThemes = () => {
+    window.backend?.App?.ListThemes?.().then(setThemes).catch(() => {});
+  };
+
+  const applyUIConfig = (ui: UIConfig) => {
+    setTheme(ui.theme);
+    setStyle(ui.style);
+    setFontFamily(ui.fontFamily);
+    setFontSize(ui.fontSize);
+  };
+
+  useEffect(() => {
+    refreshSettings();
+    refreshPlugins();
+    window.backend?.App?.GetMarketplaceSettings?.().then(setMarketplace).catch(() => {});
+    return window.runtime?.EventsOn('plugins:changed', refreshPlugins);
+  }, []);
+
+  useEffect(() => {
+    const api = window.backend?.App;
+    if (!api?.GetUIConfig) return;
+    api.GetUIConfig()
+      .then(applyUIConfig)
+      .catch((e: any) => console.error('Error loading UI settings:', e))
+      .finally(() => setUILoaded(true));
+    api.GetTheme?.().then(applyTheme).catch(() => {});
+    refreshThemes();
+    // config.toml edited by hand: take the new look and re-read the rest
+    const offReloaded = window.runtime?.EventsOn('config:reloaded', (ui: UIConfig) => {
+      applyUIConfig(ui);
+      refreshSettings();
+      refreshProviders();
+      refreshThemes();
+    });
+    const offTheme = window.runtime?.EventsOn('theme:changed', applyTheme);
+    return () => {
+      offReloaded?.();
+      offTheme?.();
+    };
+  }, []);
+
+  useEffect(() => {
+    if (!uiLoaded) return;
+    window.backend?.App?.SetUIConfig?.({ theme, style, fontFamily, fontSize })
+      .catch((e: any) => console.error('Error saving UI settings:', e));
+  }, [uiLoaded, theme, style, fontFamily, fontSize]);
+
+  useEffect(() => {
+    const api = window.backend?.App;
+    if (usageDays === null || !api?.GetUsageReport) return;
+    api.GetUsageReport(usageDays).then(setUsageReport).catch((e: any) => console.error('Error loading usage:', e));
+  }, [usageDays]);
+
+  const clearUsageHistory = async () => {
+    try {
+      await window.backend?.App?.ClearUsageHistory();
+      setUsageReport(null);
+    } catch (e: any) {
+      console.error('Error clearing usage:', e);
+    }
+  };
+
+  // Reads dropped or pasted image files into base64 attachments
+  const attachImages = (files: FileList | File[]) => {
+    Array.from(files)
+      .filter(file => file.type.startsWith('image/'))
+      .forEach(file => {
+        const reader = new FileReader();
+        reader.onload = () => {
+          const data = String(reader.result).split(',', 2)[1] || '';
+          setAttachments(prev => [...prev, { mimeType: file.type, data }]);
+        };
+        reader.readAsDataURL(file);
+      });
+  };
+
+  const cycleRequestLogMode = async () => {
+    const next = REQUEST_LOG_MODES[(REQUEST_LOG_MODES.indexOf(requestLogMode) + 1) % REQUEST_LOG_MODES.length];
+    try {
+      await window.backend?.App?.SetRequestLogMode(next);
+      setRequestLogMode(next);
+    } catch (e: any) {
+      console.error('Error setting request log mode:', e);
+    }
+  };
+
+  const toggleOfflineMode = async () => {
+    try {
+      await window.backend?.App?.SetOfflineMode(!offlineMode);
+      setOfflineMode(!offlineMode);
+    } catch (e: any) {
+      console.error('Error setting offline mode:', e);
+    }
+  };
+
+  const toggleSummarizeHistory = async () => {
+    try {
+      await window.backend?.App?.SetContextCompression(!summarizeHistory);
+      setSummarizeHistory(!summarizeHistory);
+    } catch (e: any) {
+      console.error('Error setting context compression:', e);
+    }
+  };
+
+  const cycleRedactionMode = async () => {
+    const next = REDACTION_MODES[(REDACTION_MODES.indexOf(redactionMode) + 1) % REDACTION_MODES.length];
+    try {
+      await window.backend?.App?.SetRedactionMode(next);
+      setRedactionMode(next);
+    } catch (e: any) {
+      console.error('Error setting redaction mode:', e);
+    }
+  };
+
+  useEffect(() => {
+    return window.runtime?.EventsOn('providers:status', (status: ManagerStatus) =>
+      setRequestStatus(`${status.provider}: ${status.message}${status.queued > 1 ? ` (${status.queued} queued)` : ''}`)
+    );
+  }, []);
+
+  // The prompt box grows with what is typed or pasted, up to a limit past
+  // which it scrolls
+  useLayoutEffect(() => {
+    const box = promptRef.current;
+    if (!box) return;
+    box.style.height = 'auto';
+    box.style.height = `${Math.min(Math.max(box.scrollHeight, PROMPT_MIN_HEIGHT), PROMPT_MAX_HEIGHT)}px`;
+  }, [prompt, fontSize]);
 
-  const fonts = ['JetBrains Mono', 'Fira Code', 'SF Mono', 'Cascadia Code', 'Menlo'];
-  const currentProviderType = PROVIDER_TYPES[providerTypeIndex];
+  // New turns and streamed text scroll the transcript down, unless it has
+  // been scrolled back to read
+  useLayoutEffect(() => {
+    const area = responseAreaRef.current;
+    if (area && followTranscript.current) area.scrollTop = area.scrollHeight;
+  }, [conversation, response, blocks, pendingPrompt]);
+
+  useEffect(() => {
+    const api = window.backend?.App;
+    if (!api?.CountTokens) return;
+    api.CountTokens(prompt, conversation)
+      .then(budget => setTokenBudget(budget))
+      .catch(e => console.error('Error counting tokens:', e));
+  }, [prompt, conversation, activeProvider]);
+
+  useEffect(() => {
+    window.backend?.App?.PendingCrashReport?.()
+      .then(report => setCrashReport(report))
+      .catch(e => console.error('Error reading crash report:', e));
+  }, []);
+
+  const resolveCrashReport = async (submit: boolean) => {
+    if (!crashReport) return;
+    try {
+      const api = window.backend?.App;
+      if (submit) {
+        await api?.SubmitCrashReport(crashReport.id);
+      } else {
+        await api?.DismissCrashReport(crashReport.id);
+      }
+    } catch (e: any) {
+      console.error('Error handling crash report:', e);
+    }
+    setCrashReport(null);
+  };
 
   const cycleFontFamily = () => {
     const idx = fonts.indexOf(fontFamily);
@@ -78,6 +1160,41 @@ const App: React.FC = () => {
     const nextType = PROVIDER_TYPES[nextIndex];
     setProviderEndpoint(DEFAULT_ENDPOINTS[nextType]);
     setProviderModel(DEFAULT_MODELS[nextType]);
+    setAvailableModels([]);
+    setModelsError('');
+  };
+
+  const fetchModels = async () => {
+    const api = window.backend?.App;
+    if (!api?.ListModels) {
+      setModelsError('Backend not available');
+      return;
+    }
+    try {
+      const models = await api.ListModels({
+        type: currentProviderType,
+        name: providerName,
+        apiKey: providerApiKey,
+        endpoint: providerEndpoint || DEFAULT_ENDPOINTS[currentProviderType],
+        model: providerModel,
+      });
+      setAvailableModels(models);
+      setModelsError(models.length === 0 ? 'No models found' : '');
+    } catch (e: any) {
+      setAvailableModels([]);
+      setModelsError(e.message || String(e));
+    }
+  };
+
+  // Canned responses are separated by lines containing only ---
+  const mockScript = (): MockScript | null => {
+    const responses = mockResponses.split(/^---$/m).map(r => r.trim()).filter(Boolean);
+    const latencyMs = parseInt(mockLatency, 10) || 0;
+    const failEvery = parseInt(mockFailEvery, 10) || 0;
+    if (responses.length === 0 && latencyMs === 0 && failEvery === 0) {
+      return null;
+    }
+    return { responses, latencyMs, failEvery };
   };
 
   const addProvider = async () => {
@@ -87,18 +1204,36 @@ const App: React.FC = () => {
       apiKey: providerApiKey,
       endpoint: providerEndpoint || DEFAULT_ENDPOINTS[currentProviderType],
       model: providerModel || DEFAULT_MODELS[currentProviderType],
+      timeout: parseInt(providerTimeout, 10) || 0,
+      safetyThreshold: currentProviderType === 'Gemini' ? providerSafety : '',
+      requestsPerMinute: parseInt(providerRpm, 10) || 0,
+      tokensPerMinute: parseInt(providerTpm, 10) || 0,
+      systemPrompt: providerSystemPrompt.trim(),
+      proxyUrl: providerProxy.trim(),
+      caBundlePath: providerCABundle.trim(),
+      disableTlsVerify: providerInsecure,
+      embeddingModel: typeCapabilities?.embeddings ? providerEmbeddingModel : '',
+      mockScript: currentProviderType === 'Mock' ? mockScript() : null,
+      textFormat: providerTextFormat,
     };
 
     try {
       const api = window.backend?.App;
+      if (providerKeyring && config.apiKey && !config.apiKey.startsWith('keyring:') && api?.StoreAPIKey) {
+        config.apiKey = await api.StoreAPIKey(config.name, config.apiKey);
+      }
       if (api?.AddProvider) {
         await api.AddProvider(config);
         console.log('Provider added successfully');
+        await refreshProviders();
       } else {
         console.log('Adding provider (backend not available):', config);
       }
     } catch (e: any) {
+      // Keep the dialog open so an unknown model can be corrected from the suggestions
       console.error('Error adding provider:', e);
+      setModelsError(e.message || String(e));
+      return;
     }
 
     setShowProviderDialog(false);
@@ -106,87 +1241,960 @@ const App: React.FC = () => {
     setProviderApiKey('');
     setProviderEndpoint('');
     setProviderModel('');
+    setProviderSystemPrompt('');
+    setProviderTextFormat('');
+    setProviderProxy('');
+    setProviderCABundle('');
+    setProviderInsecure(false);
+    setMockResponses('');
+    setMockLatency('');
+    setMockFailEvery('');
+    setProviderEmbeddingModel('');
+    setAvailableModels([]);
+    setModelsError('');
+  };
+
+  const clearResponse = () => {
+    setResponseStats(null);
+    setResponse('');
+    setAnsweredBy('');
+    setProvenance(null);
+    setBlocks(null);
+    setCompareResults(null);
+  };
+
+  // Copies a block, saying so in the status bar; resolves to whether it worked
+  const copyBlock = async (block: ContentBlock, mode: CopyMode) => {
+    const api = window.backend?.App;
+    if (!api?.CopyBlock) return false;
+    try {
+      await api.CopyBlock(block, mode);
+      const what = block.filePath || (block.language ? `${block.language} ${block.type}` : block.type);
+      setExportStatus(mode === 'code' ? `Copied the ${what}` : `Copied the ${what} as Markdown`);
+      return true;
+    } catch (e: any) {
+      setExportStatus(`Copy failed: ${e?.message ?? String(e)}`);
+      return false;
+    }
+  };
+
+  const copyResponse = async () => {
+    try {
+      await window.backend?.App?.CopyResponse(response);
+      setExportStatus('Copied the response');
+    } catch (e: any) {
+      setExportStatus(`Copy failed: ${e?.message ?? String(e)}`);
+    }
+  };
+
+  const refreshSessions = () => {
+    window.backend?.App?.ListSessions?.().then(setSessions).catch(() => {});
+  };
+
+  // Shows a session's conversation and draft, the response area cleared
+  const showSession = (session: Session) => {
+    setConversation(session.messages);
+    setPrompt(session.draft ?? '');
+    clearResponse();
+    refreshSessions();
+  };
+
+  const newChat = async () => {
+    const api = window.backend?.App;
+    if (!api?.NewSession) {
+      setConversation([]);
+      clearResponse();
+      return;
+    }
+    try {
+      showSession(await api.NewSession(''));
+    } catch (e: any) {
+      console.error('Error starting a session:', e);
+    }
+  };
+
+  const openSession = async (id: string) => {
+    try {
+      const session = await window.backend?.App?.OpenSession(id);
+      if (session) showSession(session);
+    } catch (e: any) {
+      console.error('Error opening session:', e);
+    }
+  };
+
+  // Continues a copy of the open session's first turns, leaving it as it was
+  const forkSession = async (turns: number) => {
+    const active = sessions.find(session => session.active);
+    if (!active) return;
+    try {
+      const session = await window.backend?.App?.ForkSession(active.id, turns);
+      if (session) showSession(session);
+    } catch (e: any) {
+      console.error('Error forking session:', e);
+    }
+  };
+
+  // Asks where to save an export, then writes it with write, asking again
+  // before replacing a file that is there already
+  const exportTo = async (title: string, format: ExportFormat, write: (path: string, overwrite: boolean) => Promise<void>) => {
+    const path = await window.backend?.App?.ChooseExportPath(title, format);
+    if (!path) return;
+    try {
+      try {
+        await write(path, false);
+      } catch (e: any) {
+        if (!String(e?.message ?? e).includes('already exists') || !window.confirm(`${path} already exists. Replace it?`)) throw e;
+        await write(path, true);
+      }
+      setExportStatus(`Exported to ${path}`);
+    } catch (e: any) {
+      setExportStatus(`Export failed: ${e?.message ?? String(e)}`);
+    }
+  };
+
+  const exportSession = (format: ExportFormat) => {
+    const active = sessions.find(session => session.active);
+    const api = window.backend?.App;
+    if (!active || !api?.ExportSession) return;
+    exportTo(active.title, format, (path, overwrite) => api.ExportSession(active.id, format, path, overwrite));
+  };
+
+  const renameSession = async (session: SessionSummary) => {
+    const name = window.prompt('Session name (empty to use its first prompt)', session.named ? session.title : '');
+    if (name === null) return;
+    try {
+      await window.backend?.App?.RenameSession(session.id, name);
+      refreshSessions();
+    } catch (e: any) {
+      console.error('Error renaming session:', e);
+    }
+  };
+
+  const deleteSession = async (session: SessionSummary) => {
+    if (!window.confirm(`Delete "${session.title}"?`)) return;
+    const api = window.backend?.App;
+    try {
+      await api?.DeleteSession(session.id);
+      if (session.active && api) {
+        showSession(await api.ActiveSession());
+      } else {
+        refreshSessions();
+      }
+    } catch (e: any) {
+      console.error('Error deleting session:', e);
+    }
+  };
+
+  useEffect(() => {
+    window.backend?.App?.ActiveSession?.()
+      .then(session => {
+        setConversation(session.messages);
+        setPrompt(session.draft ?? '');
+        setSessionLoaded(true);
+        refreshSessions();
+      })
+      .catch((e: any) => console.error('Error loading session:', e));
+    window.backend?.App?.InterruptedSessions?.()
+      .then(setInterrupted)
+      .catch((e: any) => console.error('Error reading interrupted sessions:', e));
+  }, []);
+
+  // The backend keeps the draft and saves it with its next autosave
+  useEffect(() => {
+    if (sessionLoaded) window.backend?.App?.SaveDraft?.(prompt);
+  }, [prompt, sessionLoaded]);
+
+  // Restores an interrupted session with its unanswered prompt and the
+  // answer as far as it got, or drops them
+  const resolveInterrupted = async (session: Session, restore: boolean) => {
+    const api = window.backend?.App;
+    setInterrupted(prev => prev.filter(s => s.id !== session.id));
+    try {
+      if (!restore) {
+        await api?.DiscardRecovery(session.id);
+        return;
+      }
+      const recovered = await api?.RecoverSession(session.id);
+      if (!recovered) return;
+      showSession(recovered);
+      if (recovered.pending) {
+        setPrompt(recovered.pending.prompt);
+        setResponse(recovered.pending.partial);
+      }
+    } catch (e: any) {
+      console.error('Error recovering session:', e);
+    }
+  };
+
+  const compare = async () => {
+    const api = window.backend?.App;
+    if (!prompt.trim() || !api?.ComparePrompt) return;
+    setLoading(true);
+    try {
+      setCompareResults(await api.ComparePrompt(prompt, conversation, { noCache: false }));
+      if (api.GetCostSummary) setCostSummary(await api.GetCostSummary());
+    } catch (e: any) {
+      setCompareResults([{ provider: 'Compare', response: { content: '', model: '', promptTokens: 0, completionTokens: 0, cost: 0, provider: '', attempts: 0, cached: false }, error: e.message || String(e) }]);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  // The plugin command the prompt invokes, if it starts with one
+  const commandName = prompt.trimStart().startsWith('/') ? prompt.trimStart().slice(1).split(/\s/)[0] : null;
+  const invokedCommand = commandName !== null ? commands.find(c => c.name === commandName) : undefined;
+
+  // Commands are answered by their plugin; nothing is sent or added to the conversation
+  const runCommand = async () => {
+    const api = window.backend?.App;
+    if (!api?.RunCommand) return;
+    setLoading(true);
+    setCompareResults(null);
+    try {
+      const result = await api.RunCommand(prompt);
+      setResponse(result.output);
+      setAnsweredBy(`/${result.command} · ${result.plugin}`);
+      setResponseStats(null);
+      setProvenance(null);
+      setBlocks(api.ParseResponse ? await api.ParseResponse(result.output, '') : null);
+    } catch (e: any) {
+      setResponse(`Error: ${e.message || String(e)}`);
+      setAnsweredBy('');
+      setBlocks(null);
+    } finally {
+      setLoading(false);
+    }
   };
 
-  async function send() {
+  async function send(requestOptions: RequestOptions = { noCache: false }) {
     if (!prompt.trim()) return;
+    if (invokedCommand) {
+      await runCommand();
+      return;
+    }
+    let responseFormat: ResponseFormat = { type: formatType };
+    if (formatType === 'json_schema') {
+      try {
+        responseFormat = { type: formatType, schema: JSON.parse(responseSchema) };
+      } catch (e: any) {
+        setResponse(`Error: invalid JSON Schema: ${e.message || String(e)}`);
+        return;
+      }
+    }
+    const sampling: Sampling = {
+      topP: parseFloat(topP) || 0,
+      frequencyPenalty: parseFloat(frequencyPenalty) || 0,
+      presencePenalty: parseFloat(presencePenalty) || 0,
+      stop: stopSequences.split('\n').filter(line => line !== ''),
+    };
+    const options = { ...requestOptions, provider: routeProvider, responseFormat, attachments, sampling };
     setLoading(true);
+    setCompareResults(null);
+    setBlocks(null);
+    setPendingPrompt(prompt);
+    followTranscript.current = true;
     try {
       const api = window.backend?.App;
-      const resp = api ? await api.SendPrompt(prompt) : `Local echo:\n${prompt}`;
+      let result: AIResponse;
+      const target = routeProvider ? providers.indexOf(routeProvider) : activeProvider;
+      const streams = providerCapabilities[target]?.streaming ?? true;
+      if (api?.SendPromptStream && window.runtime && streams) {
+        setResponse('');
+        const unsubscribe = window.runtime.EventsOn('response:chunk', (delta: string) => {
+          setRequestStatus('');
+          setResponse(prev => prev + delta);
+        });
+        // Completed blocks never change, so only the partial tail re-renders
+        let completed: ContentBlock[] = [];
+        const unsubscribeBlocks = window.runtime.EventsOn('response:blocks', (update: StreamedBlocks) => {
+          completed = completed.concat(update.completed);
+          setBlocks([...completed, ...update.partial]);
+        });
+        try {
+          result = await api.SendPromptStream(prompt, conversation, options);
+        } finally {
+          unsubscribe();
+          unsubscribeBlocks();
+        }
+      } else if (api) {
+        result = await api.SendPrompt(prompt, conversation, options);
+      } else {
+        result = { content: `Local echo:\n${prompt}`, model: '', promptTokens: 0, completionTokens: 0, cost: 0, provider: 'Local', attempts: 1, cached: false };
+      }
+      const resp = result.content;
       setResponse(resp);
+      setAnsweredBy(
+        (result.cached ? `${result.provider} (cached)`
+          : result.attempts > 1 ? `${result.provider} (after ${result.attempts} attempts)`
+          : result.provider)
+        + (result.redacted?.length ? ` · masked ${result.redacted.join(', ')}` : '')
+        + (result.pluginFailures?.length ? ` · skipped ${result.pluginFailures.map(f => f.plugin + (f.disabled ? ' (now disabled)' : '')).join(', ')}` : '')
+      );
+      if (result.pluginFailures?.some(f => f.disabled)) {
+        window.backend?.App?.ListPlugins?.().then(setPlugins).catch(() => {});
+      }
+      setConversation(prev => [...prev, { role: 'user', content: prompt }, { role: 'assistant', content: resp, stats: result.stats, textFormat: result.textFormat }]);
+      setResponseStats(result.stats || null);
+      setAttachments([]);
+      setProvenance(api?.AnalyzeProvenance ? await api.AnalyzeProvenance(resp, result.textFormat || '') : null);
+      setBlocks(api?.ParseResponse ? await api.ParseResponse(resp, result.textFormat || '') : null);
+      if (api?.GetCostSummary) setCostSummary(await api.GetCostSummary());
+      if (historyEntries) searchHistory(historyQuery);
+      refreshSessions();
     } catch (e: any) {
       setResponse(`Error: ${e.message || String(e)}`);
+      setAnsweredBy('');
+      setResponseStats(null);
+      setProvenance(null);
     } finally {
       setLoading(false);
+      setRequestStatus('');
+      setPendingPrompt('');
     }
   }
 
+  // The last answer is shown below the transcript, where it can be viewed
+  // raw, so the transcript stops short of it
+  const answeredLast = !pendingPrompt && conversation.length >= 2 && conversation[conversation.length - 1].content === response;
+  const earlierTurns = answeredLast ? conversation.slice(0, -2) : conversation;
+  const shownPrompt = pendingPrompt || (answeredLast ? conversation[conversation.length - 2].content : '');
+
   return (
-    <div className={`w-screen h-screen flex flex-col ${theme === 'dark' ? 'bg-[#1e1e1e]' : 'bg-white'} text-sm`}>      
+    <div className="w-screen h-screen flex flex-col bg-vc-bg text-sm">
       <div className="flex flex-1 overflow-hidden">
         {/* Activity Bar */}
-        <div className="w-12 flex flex-col items-center gap-4 py-4 bg-[#2c2c2c] text-gray-300">
+        <div className="w-12 flex flex-col items-center gap-4 py-4 bg-vc-activity text-vc-soft">
           <button><Brain size={20} /></button>
           <button><FolderOpen size={20} /></button>
           <button><PlugZap size={20} /></button>
           <button><Cog size={20} /></button>
         </div>
         {/* Sidebar */}
-        <div className="w-56 bg-[#252526] text-gray-300 p-3 flex flex-col gap-2">
+        <div className="w-56 bg-vc-panel text-vc-soft p-3 flex flex-col gap-2">
           <div className="text-xs uppercase tracking-wide font-semibold">Explorer</div>
-          <div className="text-xs opacity-70">Sessions</div>
-          <div className="text-xs opacity-70">Providers</div>
-          <div className="text-xs opacity-70">Plugins</div>
+          <div className="flex items-center justify-between text-xs opacity-70">
+            Sessions
+            <span className="flex gap-2">
+              <button onClick={newChat} title="New chat" className="opacity-60 hover:opacity-100">+</button>
+              <button onClick={toggleHistory} title="Every prompt and response" className="opacity-60 hover:opacity-100">{historyEntries ? 'Hide history' : 'History'}</button>
+            </span>
+          </div>
+          <div className="max-h-40 overflow-y-auto">
+            {sessions.map(session => (
+              <div
+                key={session.id}
+                title={`${session.turns} turn${session.turns === 1 ? '' : 's'} · ${new Date(session.updated).toLocaleString()}`}
+                className={`flex items-center justify-between text-xs pl-2 py-0.5 rounded ${session.active ? 'bg-vc-selection text-white' : 'opacity-70 hover:opacity-100'}`}
+              >
+                <button onClick={() => openSession(session.id)} className="flex-1 min-w-0 truncate text-left">{session.title}</button>
+                <span className="flex gap-1">
+                  <button onClick={() => renameSession(session)} title="Rename" className="opacity-60 hover:opacity-100">✎</button>
+                  <button onClick={() => deleteSession(session)} title="Delete" className="opacity-60 hover:opacity-100">✕</button>
+                </span>
+              </div>
+            ))}
+          </div>
+          {historyEntries && (
+            <div className="pl-2 space-y-0.5">
+              <div className="flex gap-1">
+                <input
+                  className="flex-1 min-w-0 px-1 bg-vc-bg border border-vc-border rounded text-vc-text focus:outline-none focus:border-blue-500 text-xs"
+                  placeholder="Search history"
+                  value={historyQuery.text}
+                  onChange={(e) => searchHistory({ ...historyQuery, text: e.target.value })}
+                  onKeyDown={(e) => {
+                    if (e.key === 'Enter') searchHistoryByMeaning();
+                  }}
+                  title="Matches as you type; Enter searches by meaning"
+                />
+                <button onClick={searchHistoryByMeaning} title="Search by meaning, with the active provider's embeddings" className="text-xs opacity-60 hover:opacity-100">≈</button>
+                <button onClick={importHistory} title="Import a JSON or Markdown export" className="text-xs opacity-60 hover:opacity-100">⇪</button>
+                <button onClick={clearHistory} title="Delete the history" className="text-xs opacity-60 hover:opacity-100">✕</button>
+              </div>
+              <div className="flex gap-1">
+                <select
+                  className="flex-1 min-w-0 bg-vc-bg border border-vc-border rounded text-vc-text text-xs"
+                  value={historyQuery.provider ?? ''}
+                  onChange={(e) => searchHistory({ ...historyQuery, provider: e.target.value })}
+                  title="Only answers from this provider"
+                >
+                  <option value="">Any provider</option>
+                  {providers.map(name => <option key={name} value={name}>{name}</option>)}
+                </select>
+                <select
+                  className="flex-1 min-w-0 bg-vc-bg border border-vc-border rounded text-vc-text text-xs"
+                  value={historyQuery.tag ?? ''}
+                  onChange={(e) => searchHistory({ ...historyQuery, tag: e.target.value })}
+                  title="Only entries with this tag"
+                >
+                  <option value="">Any tag</option>
+                  {historyTags.map(tag => <option key={tag} value={tag}>#{tag}</option>)}
+                </select>
+                <button
+                  onClick={() => searchHistory({ ...historyQuery, pinned: !historyQuery.pinned })}
+                  title="Only pinned entries"
+                  className={`text-xs ${historyQuery.pinned ? 'text-yellow-400' : 'opacity-60 hover:opacity-100'}`}
+                >
+                  ★
+                </button>
+              </div>
+              <div className="flex gap-1">
+                <input
+                  type="date"
+                  className="flex-1 min-w-0 bg-vc-bg border border-vc-border rounded text-vc-text text-xs"
+                  value={historyQuery.from ?? ''}
+                  onChange={(e) => searchHistory({ ...historyQuery, from: e.target.value })}
+                  title="From this day"
+                />
+                <input
+                  type="date"
+                  className="flex-1 min-w-0 bg-vc-bg border border-vc-border rounded text-vc-text text-xs"
+                  value={historyQuery.to ?? ''}
+                  onChange={(e) => searchHistory({ ...historyQuery, to: e.target.value })}
+                  title="Up to this day"
+                />
+              </div>
+              {historyError && <div className="text-xs text-red-400">{historyError}</div>}
+              {!historyError && historyEntries.length === 0 && (
+                <div className="text-xs opacity-50">
+                  {historyQuery.text || historyQuery.provider || historyQuery.from || historyQuery.to || historyQuery.tag || historyQuery.pinned ? 'No matches' : 'Nothing yet'}
+                </div>
+              )}
+              <div className="max-h-48 overflow-y-auto">
+                {historyEntries.map(entry => (
+                  <div key={entry.id} className="text-xs">
+                    <div className="flex items-center gap-1">
+                      <button
+                        onClick={() => togglePinned(entry)}
+                        title={entry.pinned ? 'Unpin' : 'Pin, so it is never dropped from the history'}
+                        className={entry.pinned ? 'text-yellow-400' : 'opacity-40 hover:opacity-100'}
+                      >
+                        {entry.pinned ? '★' : '☆'}
+                      </button>
+                      <button
+                        onClick={() => openHistoryEntry(entry)}
+                        title={`${entry.provider} ${entry.model} · ${new Date(entry.time).toLocaleString()}`}
+                        className="flex-1 min-w-0 truncate text-left opacity-60 hover:opacity-100"
+                      >
+                        {entry.prompt}
+                      </button>
+                      <button onClick={() => tagHistoryEntry(entry)} title="Add a tag" className="opacity-40 hover:opacity-100">#</button>
+                    </div>
+                    {entry.tags && entry.tags.length > 0 && (
+                      <div className="flex flex-wrap gap-1 pl-4">
+                        {entry.tags.map(tag => (
+                          <span key={tag} className="px-1 rounded bg-vc-raised opacity-70">
+                            <button onClick={() => searchHistory({ ...historyQuery, tag })} title="Show entries with this tag">#{tag}</button>
+                            <button onClick={() => untagHistoryEntry(entry, tag)} title="Remove the tag" className="ml-1 opacity-60 hover:opacity-100">×</button>
+                          </span>
+                        ))}
+                      </div>
+                    )}
+                  </div>
+                ))}
+              </div>
+            </div>
+          )}
+          <div className="flex items-center justify-between text-xs opacity-70">
+            <span>Providers</span>
+            {providers.length > 0 && (
+              <button onClick={checkProviders} title="Check provider readiness" className="hover:opacity-100">↻</button>
+            )}
+          </div>
+          {providers.length === 0 ? (
+            <button onClick={() => setShowProviderDialog(true)} className="text-left text-xs pl-2 opacity-50 hover:opacity-80">
+              No providers · Add one
+            </button>
+          ) : (
+            providers.map((name, i) => {
+              const health = providerHealth[i];
+              const dot = health?.status === 'ready' ? 'text-green-400' : health?.status === 'unhealthy' ? 'text-red-400' : 'text-vc-muted';
+              const supported = CAPABILITY_LABELS.filter(([key]) => providerCapabilities[i]?.[key]).map(([, label]) => label);
+              return (
+                <button
+                  key={i}
+                  onClick={() => selectProvider(i)}
+                  title={[health?.error || health?.status, supported.length > 0 && `Supports ${supported.join(', ')}`].filter(Boolean).join('\n')}
+                  className={`text-left text-xs pl-2 py-0.5 rounded ${i === activeProvider ? 'bg-vc-selection text-white' : 'opacity-70 hover:opacity-100'}`}
+                >
+                  <span className={dot}>{i === activeProvider ? '● ' : '○ '}</span>{name}
+                </button>
+              );
+            })
+          )}
+          <div className="flex items-center justify-between text-xs opacity-70">
+            Plugins
+            <button onClick={togglePluginStats} title="Calls, timings and failures" className="opacity-60 hover:opacity-100">{pluginStats ? 'Hide stats' : 'Stats'}</button>
+          </div>
+          {pluginStats && (
+            <div className="pl-2 space-y-0.5">
+              {pluginStats.length === 0 && <div className="text-xs opacity-50">No calls yet</div>}
+              {pluginStats.map(stats => (
+                <button
+                  key={`${stats.plugin}/${stats.stage}`}
+                  onClick={() => editPluginTimeout(stats)}
+                  title={`Max ${stats.maxMs.toFixed(1)} ms · times out after ${stats.timeoutMs / 1000} s (click to change)`}
+                  className={`block w-full text-left text-xs ${stats.failures > 0 ? 'text-amber-300' : 'opacity-60'} hover:opacity-100`}
+                >
+                  {stats.plugin} {stats.stage}: {stats.calls}× · {(stats.totalMs / stats.calls).toFixed(1)} ms avg
+                  {stats.failures > 0 && ` · ${stats.failures} failed${stats.timeouts > 0 ? ` (${stats.timeouts} timed out)` : ''}`}
+                </button>
+              ))}
+            </div>
+          )}
+          {plugins.length === 0 ? (
+            <div className="text-xs opacity-50 pl-2">None loaded</div>
+          ) : (
+            plugins.map((plugin, i) => (
+              <div key={plugin.name}>
+                <div
+                  title={[
+                    plugin.description,
+                    plugin.capabilities.join(', '),
+                    plugin.requires?.length ? `Requires ${plugin.requires.join(', ')}` : '',
+                    plugin.conflictsWith?.length ? `Conflicts with ${plugin.conflictsWith.join(', ')}` : '',
+                    plugin.path,
+                  ].filter(Boolean).join('\n')}
+                  className={`flex items-center justify-between text-xs pl-2 py-0.5 ${plugin.enabled ? 'opacity-70' : 'opacity-40'}`}
+                >
+                  <label className="flex items-center gap-1">
+                    <input type="checkbox" checked={plugin.enabled} onChange={() => togglePlugin(plugin)} />
+                    {plugin.name} <span className="opacity-60">{plugin.version}</span>
+                  </label>
+                  <span className="flex gap-1">
+                    <select
+                      className="bg-transparent opacity-60 hover:opacity-100 focus:outline-none"
+                      title={`When it fails (${plugin.errorPolicy.maxFailures} in a row disable it)`}
+                      value={plugin.errorPolicy.onError}
+                      onChange={(e) => setPluginErrorPolicy(plugin, e.target.value as PluginOnError)}
+                    >
+                      <option value="skip">skip</option>
+                      <option value="disable">disable</option>
+                      <option value="abort">abort</option>
+                    </select>
+                    {plugin.capabilities.includes('configurable') && (
+                      <button onClick={() => editPluginConfig(plugin.name)} title="Options" className="opacity-60 hover:opacity-100">⚙</button>
+                    )}
+                    {plugin.path && (
+                      <>
+                        <button onClick={() => reloadPlugin(plugin.name)} title="Reload from disk" className="opacity-60 hover:opacity-100">↻</button>
+                        <button onClick={() => unloadPlugin(plugin.name)} title="Unload" className="opacity-60 hover:opacity-100">✕</button>
+                      </>
+                    )}
+                    {i > 0 && (
+                      <button onClick={() => raisePlugin(i)} title="Run earlier" className="opacity-60 hover:opacity-100">↑</button>
+                    )}
+                  </span>
+                </div>
+                {configuringPlugin === plugin.name && (
+                  <div className="pl-2 py-1 space-y-1">
+                    <textarea
+                      rows={4}
+                      className="w-full px-2 py-1 bg-vc-bg border border-vc-border rounded text-vc-text focus:outline-none focus:border-blue-500 resize-none font-mono text-xs"
+                      value={pluginConfig}
+                      onChange={(e) => setPluginConfig(e.target.value)}
+                    />
+                    {pluginConfigError && <div className="text-xs text-red-400">{pluginConfigError}</div>}
+                    <button onClick={savePluginConfig} className="text-xs px-2 py-0.5 rounded bg-blue-600 hover:bg-blue-500 text-white">Save</button>
+                  </div>
+                )}
+              </div>
+            ))
+          )}
+          <div className="text-xs opacity-70">Marketplace</div>
+          <div className="flex gap-1 pl-2">
+            <input
+              className="flex-1 min-w-0 px-1 bg-vc-bg border border-vc-border rounded text-vc-text focus:outline-none focus:border-blue-500 text-xs"
+              placeholder="Index URL"
+              value={marketplace.indexUrl}
+              onChange={(e) => setMarketplace({ ...marketplace, indexUrl: e.target.value })}
+              onBlur={saveMarketplace}
+            />
+            <button onClick={browseMarketplace} disabled={!marketplace.indexUrl} className="text-xs opacity-60 hover:opacity-100 disabled:opacity-30">Browse</button>
+          </div>
+          {marketplaceError && <div className="text-xs text-red-400 pl-2">{marketplaceError}</div>}
+          {listings?.length === 0 && <div className="text-xs opacity-50 pl-2">Nothing for this platform</div>}
+          {listings?.map(listing => (
+            <div
+              key={listing.name}
+              title={[listing.description, `Versions: ${listing.versions.join(', ')}`].join('\n')}
+              className="flex items-center justify-between text-xs pl-2 py-0.5 opacity-70"
+            >
+              <span>
+                {listing.name} <span className="opacity-60">{listing.installed ?? listing.versions[0]}</span>
+              </span>
+              <span className="flex gap-1">
+                {(!listing.installed || listing.update) && (
+                  <button onClick={() => installPlugin(listing.name)} className="opacity-60 hover:opacity-100">
+                    {listing.installed ? `Update to ${listing.versions[0]}` : 'Install'}
+                  </button>
+                )}
+                {listing.installed && (
+                  <button onClick={() => uninstallPlugin(listing.name)} title="Uninstall" className="opacity-60 hover:opacity-100">✕</button>
+                )}
+              </span>
+            </div>
+          ))}
+          <div className="flex items-center justify-between text-xs opacity-70">
+            Settings
+            <button onClick={toggleConfigValues} title="Where each setting comes from" className="opacity-60 hover:opacity-100">{configValues ? 'Hide sources' : 'Sources'}</button>
+          </div>
+          {configValues?.map(setting => (
+            <div
+              key={setting.key}
+              title={setting.origin || setting.source}
+              className={`flex justify-between gap-2 text-xs pl-2 ${setting.source === 'default' ? 'opacity-40' : 'opacity-70'}`}
+            >
+              <span className="truncate">{setting.key} = {JSON.stringify(setting.value)}</span>
+              <span className="opacity-60">{setting.source}</span>
+            </div>
+          ))}
         </div>
         {/* Main Pane */}
         <div className="flex-1 flex flex-col">
           {/* Tab Bar */}
-          <div className="flex items-center h-9 bg-[#2d2d2d] text-gray-200 text-xs">
-            <div className="px-3 h-full flex items-center bg-[#1e1e1e] border-r border-[#3c3c3c]">Chat ⨉</div>
+          <div className="flex items-center h-9 bg-vc-tab text-vc-text text-xs">
+            <div className="px-3 h-full flex items-center gap-2 bg-vc-bg border-r border-vc-border">
+              Chat{conversation.length > 0 ? ` · ${conversation.length / 2} turn${conversation.length === 2 ? '' : 's'}` : ''}
+              <button onClick={newChat} title="New chat" className="opacity-60 hover:opacity-100">⨉</button>
+              {conversation.length >= 2 && (
+                <select
+                  className="bg-transparent text-xs opacity-60 hover:opacity-100 focus:outline-none"
+                  value=""
+                  onChange={(e) => forkSession(Number(e.target.value))}
+                  title="Start a new chat from the turns up to one, keeping this one as it is"
+                >
+                  <option value="" disabled>Fork…</option>
+                  {conversation.filter(message => message.role === 'user').map((message, i) => (
+                    <option key={i} value={i + 1}>
+                      {i + 1}. {message.content.length > 40 ? `${message.content.slice(0, 40)}…` : message.content}
+                    </option>
+                  ))}
+                </select>
+              )}
+              {conversation.length >= 2 && (
+                <select
+                  className="bg-transparent text-xs opacity-60 hover:opacity-100 focus:outline-none"
+                  value=""
+                  onChange={(e) => exportSession(e.target.value as ExportFormat)}
+                  title="Export session"
+                >
+                  <option value="" disabled>Export…</option>
+                  {EXPORT_FORMATS.map(({ format, label }) => <option key={format} value={format}>{label}</option>)}
+                </select>
+              )}
+            </div>
           </div>
+          {/* Provenance Notice */}
+          {provenance && provenance.flags.length > 0 && (
+            <div className="px-4 py-2 bg-[#3a2f12] border-b border-[#6b5417] text-amber-200 text-xs space-y-1">
+              <div className="font-semibold">{provenance.note}</div>
+              {provenance.flags.map((flag, i) => (
+                <div key={i} className="opacity-80">
+                  Block {flag.blockIndex + 1}{flag.language ? ` (${flag.language})` : ''} · {flag.kind === 'license-header' ? 'License header' : 'Verbatim-looking snippet'} · {flag.detail}
+                </div>
+              ))}
+            </div>
+          )}
           {/* Response Area */}
-          <div className="flex-1 overflow-auto bg-[#1e1e1e] p-4 space-y-4">
-            {response ? (
-              <Editor
-                theme={theme === 'dark' ? 'vs-dark' : 'light'}
-                height="100%"
-                defaultLanguage="markdown"
-                value={response}
-                options={{ readOnly: true, minimap: { enabled: false }, wordWrap: 'on' }}
-              />
-            ) : (
-              <div className="text-gray-500 text-sm">AI responses will appear here...</div>
+          <div
+            ref={responseAreaRef}
+            className="flex-1 overflow-auto bg-vc-bg p-4 space-y-4"
+            onScroll={(e) => {
+              const area = e.currentTarget;
+              followTranscript.current = area.scrollHeight - area.scrollTop - area.clientHeight < TRANSCRIPT_FOLLOW_SLACK;
+            }}
+          >
+            {!compareResults && <Transcript messages={earlierTurns} onCopy={copyBlock} />}
+            {!compareResults && shownPrompt && <PromptBubble text={shownPrompt} />}
+            {compareResults ? (
+              <div className="h-full flex flex-col gap-2">
+                <div className="flex items-center justify-between text-xs text-vc-muted">
+                  <span>Comparing {compareResults.length} provider{compareResults.length === 1 ? '' : 's'}</span>
+                  <button onClick={() => setCompareResults(null)} className="hover:text-vc-text">Close comparison</button>
+                </div>
+                <div className="flex-1 grid gap-3" style={{ gridTemplateColumns: `repeat(${Math.max(compareResults.length, 1)}, minmax(0, 1fr))` }}>
+                  {compareResults.map((result, i) => (
+                    <div key={i} className="flex flex-col border border-vc-border rounded-md overflow-hidden">
+                      <div className="px-3 py-1 bg-vc-panel text-xs text-vc-soft flex justify-between">
+                        <span>{result.provider}{result.response.model ? ` · ${result.response.model}` : ''}</span>
+                        {!result.error && <span className="opacity-70">{result.response.completionTokens} tok · ${result.response.cost.toFixed(4)}</span>}
+                      </div>
+                      {result.error ? (
+                        <div className="p-3 text-xs text-red-400">{result.error}</div>
+                      ) : (
+                        <Editor
+                          theme={themeBase === 'dark' ? 'vs-dark' : 'light'}
+                          height="100%"
+                          defaultLanguage="markdown"
+                          value={result.response.content}
+                          options={{ readOnly: true, minimap: { enabled: false }, wordWrap: 'on' }}
+                        />
+                      )}
+                    </div>
+                  ))}
+                </div>
+              </div>
+            ) : blocks && !showRaw ? (
+              <div className="relative">
+                <span className="absolute top-0 right-0 flex gap-3 text-xs text-vc-muted">
+                  <button onClick={copyResponse} title="Copy the whole response as it came, code fences and all" className="hover:text-vc-text">Copy all</button>
+                  <button onClick={() => setShowRaw(true)} className="hover:text-vc-text">Raw</button>
+                </span>
+                <ContentView blocks={blocks} onCopy={copyBlock} />
+              </div>
+            ) : response ? (
+              <div className="relative h-full">
+                {blocks && (
+                  <button onClick={() => setShowRaw(false)} className="absolute top-0 right-2 z-10 text-xs text-vc-muted hover:text-vc-text">Rendered</button>
+                )}
+                <Editor
+                  theme={themeBase === 'dark' ? 'vs-dark' : 'light'}
+                  height="100%"
+                  defaultLanguage="markdown"
+                  value={response}
+                  options={{ readOnly: true, minimap: { enabled: false }, wordWrap: 'on' }}
+                />
+              </div>
+            ) : earlierTurns.length === 0 && !shownPrompt && (
+              <div className="text-vc-muted text-sm">AI responses will appear here...</div>
             )}
           </div>
           {/* Prompt Input */}
-          <div className="flex items-center gap-3 p-3 bg-[#252526] border-t border-[#3c3c3c]">
+          {formatType === 'json_schema' && (
+            <textarea
+              className="mx-3 mt-3 bg-vc-bg rounded-md border border-vc-border p-2 text-xs font-mono text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500 resize-none h-24"
+              placeholder='JSON Schema, e.g. {"type": "object", "properties": {"answer": {"type": "string"}}, "required": ["answer"]}'
+              value={responseSchema}
+              onChange={(e) => setResponseSchema(e.target.value)}
+            />
+          )}
+          {showSampling && (
+            <div className="flex items-start gap-3 px-3 pt-3 bg-vc-panel text-xs text-vc-soft">
+              {([
+                ['Top P', topP, setTopP, '0–1'],
+                ['Frequency penalty', frequencyPenalty, setFrequencyPenalty, '-2–2'],
+                ['Presence penalty', presencePenalty, setPresencePenalty, '-2–2'],
+              ] as [string, string, (v: string) => void, string][]).map(([label, value, setValue, range]) => (
+                <label key={label} className="flex flex-col gap-1">
+                  {label}
+                  <input
+                    type="number"
+                    step={0.1}
+                    className="w-28 px-2 py-1 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
+                    placeholder={`Default (${range})`}
+                    value={value}
+                    onChange={(e) => setValue(e.target.value)}
+                  />
+                </label>
+              ))}
+              <label className="flex-1 flex flex-col gap-1">
+                Stop sequences (one per line)
+                <textarea
+                  className="px-2 py-1 bg-vc-bg border border-vc-border rounded-md text-vc-text font-mono focus:outline-none focus:border-blue-500 resize-none h-14"
+                  value={stopSequences}
+                  onChange={(e) => setStopSequences(e.target.value)}
+                />
+              </label>
+            </div>
+          )}
+          {commandName !== null && !prompt.trimStart().includes(' ') && commands.some(c => c.name.startsWith(commandName)) && (
+            <div className="px-3 pt-3 bg-vc-panel space-y-0.5">
+              {commands.filter(c => c.name.startsWith(commandName)).map(command => (
+                <button
+                  key={command.name}
+                  onClick={() => setPrompt(`/${command.name} `)}
+                  className="block w-full text-left text-xs text-vc-soft hover:text-white"
+                >
+                  <span className="font-mono">/{command.name}</span>
+                  {command.args.map(arg => (
+                    <span key={arg.name} className="font-mono opacity-60"> {arg.required ? `<${arg.name}>` : `[${arg.name}]`}</span>
+                  ))}
+                  <span className="opacity-50"> — {command.description}{command.plugin ? ` (${command.plugin})` : ''}</span>
+                </button>
+              ))}
+            </div>
+          )}
+          {attachments.length > 0 && (
+            <div className="flex items-center gap-2 px-3 pt-3 bg-vc-panel">
+              {attachments.map((a, i) => (
+                <div key={i} className="relative">
+                  <img src={`data:${a.mimeType};base64,${a.data}`} alt="" className="h-12 w-12 object-cover rounded border border-vc-border" />
+                  <button
+                    onClick={() => setAttachments(prev => prev.filter((_, j) => j !== i))}
+                    title="Remove image"
+                    className="absolute -top-1 -right-1 bg-vc-raised rounded-full text-vc-text"
+                  >
+                    <X size={12} />
+                  </button>
+                </div>
+              ))}
+              {!(providerCapabilities[routeProvider ? providers.indexOf(routeProvider) : activeProvider]?.vision ?? true) && (
+                <span className="text-xs text-amber-400">This provider does not accept images</span>
+              )}
+            </div>
+          )}
+          <div
+            className="flex items-center gap-3 p-3 bg-vc-panel border-t border-vc-border"
+            onDragOver={(e) => e.preventDefault()}
+            onDrop={(e) => {
+              e.preventDefault();
+              attachImages(e.dataTransfer.files);
+            }}
+          >
             <textarea
-              className="flex-1 bg-[#1e1e1e] rounded-md border border-[#3c3c3c] p-2 text-gray-200 focus:outline-none focus:border-blue-500 resize-none h-20"
+              ref={promptRef}
+              className="flex-1 bg-vc-bg rounded-md border border-vc-border p-2 text-vc-text focus:outline-none focus:border-blue-500 resize-none overflow-y-auto"
               style={{ fontFamily, fontSize: `${fontSize}px` }}
-              placeholder="Ask something..."
+              placeholder="Ask something... (Enter sends, Shift+Enter starts a new line; drop or paste an image to ask about it)"
               value={prompt}
+              spellCheck={false}
               onChange={(e) => setPrompt(e.target.value)}
+              onKeyDown={(e) => {
+                // Enter while an input method is composing picks a candidate instead
+                if (e.key !== 'Enter' || e.shiftKey || e.nativeEvent.isComposing) return;
+                e.preventDefault();
+                if (!loading) send();
+              }}
+              onPaste={(e) => {
+                if (e.clipboardData.files.length > 0) attachImages(e.clipboardData.files);
+              }}
             />
+            <div className="text-xs text-right w-28 text-vc-muted space-y-0.5">
+              <div title="Characters and lines in the prompt">
+                {prompt.length.toLocaleString()} chars{prompt.includes('\n') && ` · ${prompt.split('\n').length} lines`}
+              </div>
+              {tokenBudget && (
+                <div
+                  className={tokenBudget.exceeds ? 'text-amber-400' : undefined}
+                  title={tokenBudget.exceeds ? 'Older turns will be dropped and the prompt truncated to fit the context window' : `Context window of ${tokenBudget.model || 'model'}`}
+                >
+                  {(tokenBudget.promptTokens + tokenBudget.historyTokens).toLocaleString()} / {tokenBudget.contextWindow.toLocaleString()} tokens
+                  {tokenBudget.exceeds && <div>exceeds context</div>}
+                </div>
+              )}
+            </div>
+            {providers.length > 1 && (
+              <select
+                className="bg-vc-bg border border-vc-border rounded-md text-vc-text text-xs px-2 py-2 focus:outline-none focus:border-blue-500"
+                title="Provider for this prompt"
+                value={routeProvider}
+                onChange={(e) => setRouteProvider(e.target.value)}
+              >
+                <option value="">Active provider</option>
+                {providers.map((name, i) => (
+                  <option key={i} value={name}>{name}</option>
+                ))}
+              </select>
+            )}
+            <button
+              onClick={() => setShowSampling(!showSampling)}
+              title="Advanced sampling settings"
+              className={`flex items-center px-2 py-2 rounded-md ${showSampling ? 'bg-vc-hover' : 'bg-vc-raised'} hover:bg-vc-hover text-vc-text`}
+            >
+              <Cog size={16} />
+            </button>
+            <select
+              className="bg-vc-bg border border-vc-border rounded-md text-vc-text text-xs px-2 py-2 focus:outline-none focus:border-blue-500"
+              title="Response format"
+              value={formatType}
+              onChange={(e) => setFormatType(e.target.value as ResponseFormat['type'])}
+            >
+              <option value="">Text</option>
+              <option value="json">JSON</option>
+              <option value="json_schema">JSON schema</option>
+            </select>
             <button
-              onClick={send}
+              onClick={() => send()}
               disabled={loading}
               className="flex items-center gap-2 bg-blue-600 hover:bg-blue-500 text-white text-xs font-medium px-4 py-2 rounded-md disabled:opacity-50"
             >
               <Send size={16} /> {loading ? 'Sending...' : 'Send'}
             </button>
+            {providers.length > 1 && (
+              <button
+                onClick={compare}
+                disabled={loading}
+                title="Send to every provider and compare the answers side by side"
+                className="flex items-center bg-vc-raised hover:bg-vc-hover text-vc-text text-xs px-3 py-2 rounded-md disabled:opacity-50"
+              >
+                Compare
+              </button>
+            )}
+            <button
+              onClick={() => send({ noCache: true })}
+              disabled={loading}
+              title="Send without using cached responses"
+              className="flex items-center bg-vc-raised hover:bg-vc-hover text-vc-text px-2 py-2 rounded-md disabled:opacity-50"
+            >
+              <RotateCw size={16} />
+            </button>
           </div>
         </div>
       </div>
       {/* Status Bar */}
-      <div className="h-6 bg-[#007acc] flex items-center justify-between px-3 text-xs text-white">
-        <div>Vibe Coder Wails · Ready</div>
+      <div className="h-6 bg-vc-accent flex items-center justify-between px-3 text-xs text-white">
+        <div>
+          Vibe Coder Wails · {activeProvider >= 0 && providers[activeProvider] ? providers[activeProvider] : 'Mock'} · {loading ? 'Working…' : 'Ready'}
+          {requestStatus && loading && <span className="opacity-80"> · {requestStatus}</span>}
+          {answeredBy && !loading && <span className="opacity-80"> · Answered by {answeredBy}</span>}
+          {exportStatus && <span className="opacity-80"> · {exportStatus}</span>}
+          {responseStats && !loading && (
+            <span className="opacity-80" title={`${responseStats.characters.toLocaleString()} characters, ~${responseStats.tokens.toLocaleString()} tokens`}>
+              {' · '}{responseStats.words.toLocaleString()} words
+              {responseStats.codeBlocks > 0 && ` · ${responseStats.codeBlocks} code blocks, ${responseStats.codeLines} lines`}
+              {responseStats.languages.length > 0 && ` (${responseStats.languages.join(', ')})`}
+            </span>
+          )}
+        </div>
         <div className="flex items-center gap-3">
+          <button
+            onClick={cycleRedactionMode}
+            title="How API keys, credentials, private keys and emails in prompts are handled"
+            className="opacity-90 hover:opacity-100"
+          >
+            Secrets: {redactionMode}
+          </button>
+          <button
+            onClick={cycleRequestLogMode}
+            title="Request log: off, metadata only (latency, tokens, errors), or full with scrubbed prompts and responses"
+            className="opacity-90 hover:opacity-100"
+          >
+            Log: {requestLogMode}
+          </button>
+          <button
+            onClick={toggleOfflineMode}
+            title="Offline mode: only local providers (Mock, llama.cpp, servers on localhost) are used"
+            className={offlineMode ? 'font-semibold' : 'opacity-90 hover:opacity-100'}
+          >
+            {offlineMode ? 'Offline' : 'Online'}
+          </button>
+          <button
+            onClick={toggleSummarizeHistory}
+            title="When a conversation outgrows the model's context window, summarize older turns instead of dropping them"
+            className="opacity-90 hover:opacity-100"
+          >
+            Summarize: {summarizeHistory ? 'on' : 'off'}
+          </button>
+          {requestLogMode !== 'off' && (
+            <button onClick={() => window.backend?.App?.OpenRequestLog()} className="opacity-90 hover:opacity-100">Open Log</button>
+          )}
+          {costSummary && costSummary.session.requests > 0 && (
+            <span
+              className="opacity-90"
+              title={Object.entries(costSummary.byProvider)
+                .map(([name, t]) => `${name}: ${t.requests} req · ${(t.promptTokens + t.completionTokens).toLocaleString()} tok · $${t.cost.toFixed(4)}`)
+                .join('\n')}
+            >
+              ${costSummary.session.cost.toFixed(4)} · {(costSummary.session.promptTokens + costSummary.session.completionTokens).toLocaleString()} tok
+            </span>
+          )}
           <button onClick={() => setStyle(style === 'vscode' ? 'zed' : 'vscode')} className="opacity-90 hover:opacity-100">{style === 'vscode' ? 'VS Code' : 'Zed'}</button>
-          <button onClick={() => setTheme(theme === 'dark' ? 'light' : 'dark')} className="opacity-90 hover:opacity-100">{theme === 'dark' ? 'Dark' : 'Light'}</button>
+          <button onClick={() => setTheme(themes[(themes.indexOf(theme) + 1) % themes.length])} className="opacity-90 hover:opacity-100 capitalize" title="Switch theme">{theme}</button>
           <button onClick={cycleFontFamily} className="opacity-90 hover:opacity-100">Font</button>
           <button onClick={() => adjustFontSize(-1)} className="opacity-90 hover:opacity-100">A-</button>
           <button onClick={() => adjustFontSize(1)} className="opacity-90 hover:opacity-100">A+</button>
+          <button onClick={() => setUsageDays(7)} className="opacity-90 hover:opacity-100">Usage</button>
           <button onClick={() => setShowProviderDialog(true)} className="opacity-90 hover:opacity-100">Providers</button>
         </div>
       </div>
@@ -194,11 +2202,11 @@ const App: React.FC = () => {
       {/* Provider Dialog */}
       {showProviderDialog && (
         <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50">
-          <div className="bg-[#252526] border border-[#3c3c3c] rounded-lg w-[500px] max-h-[90vh] overflow-auto">
+          <div className="bg-vc-panel border border-vc-border rounded-lg w-[500px] max-h-[90vh] overflow-auto">
             {/* Header */}
-            <div className="flex items-center justify-between p-4 border-b border-[#3c3c3c]">
-              <h2 className="text-lg font-semibold text-gray-200">Add AI Provider</h2>
-              <button onClick={() => setShowProviderDialog(false)} className="text-gray-400 hover:text-gray-200">
+            <div className="flex items-center justify-between p-4 border-b border-vc-border">
+              <h2 className="text-lg font-semibold text-vc-text">Add AI Provider</h2>
+              <button onClick={() => setShowProviderDialog(false)} className="text-vc-muted hover:text-vc-text">
                 <X size={20} />
               </button>
             </div>
@@ -207,9 +2215,9 @@ const App: React.FC = () => {
             <div className="p-6 space-y-4">
               {/* Provider Type */}
               <div>
-                <label className="block text-sm font-medium text-gray-300 mb-2">Provider Type</label>
+                <label className="block text-sm font-medium text-vc-soft mb-2">Provider Type</label>
                 <div className="flex items-center gap-3">
-                  <div className="flex-1 px-3 py-2 bg-[#1e1e1e] border border-[#3c3c3c] rounded-md text-gray-200">
+                  <div className="flex-1 px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text">
                     {currentProviderType}
                   </div>
                   <button
@@ -223,10 +2231,10 @@ const App: React.FC = () => {
 
               {/* Provider Name */}
               <div>
-                <label className="block text-sm font-medium text-gray-300 mb-2">Name (optional)</label>
+                <label className="block text-sm font-medium text-vc-soft mb-2">Name (optional)</label>
                 <input
                   type="text"
-                  className="w-full px-3 py-2 bg-[#1e1e1e] border border-[#3c3c3c] rounded-md text-gray-200 placeholder-gray-500 focus:outline-none focus:border-blue-500"
+                  className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
                   placeholder={`Custom name (default: ${currentProviderType})`}
                   value={providerName}
                   onChange={(e) => setProviderName(e.target.value)}
@@ -235,22 +2243,26 @@ const App: React.FC = () => {
 
               {/* API Key */}
               <div>
-                <label className="block text-sm font-medium text-gray-300 mb-2">API Key</label>
+                <label className="block text-sm font-medium text-vc-soft mb-2">API Key</label>
                 <input
                   type="password"
-                  className="w-full px-3 py-2 bg-[#1e1e1e] border border-[#3c3c3c] rounded-md text-gray-200 placeholder-gray-500 focus:outline-none focus:border-blue-500"
+                  className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
                   placeholder="Enter your API key"
                   value={providerApiKey}
                   onChange={(e) => setProviderApiKey(e.target.value)}
                 />
+                <label className="flex items-center gap-2 mt-1 text-xs text-vc-muted">
+                  <input type="checkbox" checked={providerKeyring} onChange={(e) => setProviderKeyring(e.target.checked)} />
+                  Keep in the system keychain (config.toml gets a keyring: reference)
+                </label>
               </div>
 
               {/* Endpoint */}
               <div>
-                <label className="block text-sm font-medium text-gray-300 mb-2">API Endpoint</label>
+                <label className="block text-sm font-medium text-vc-soft mb-2">API Endpoint</label>
                 <input
                   type="text"
-                  className="w-full px-3 py-2 bg-[#1e1e1e] border border-[#3c3c3c] rounded-md text-gray-200 placeholder-gray-500 focus:outline-none focus:border-blue-500"
+                  className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
                   placeholder={DEFAULT_ENDPOINTS[currentProviderType]}
                   value={providerEndpoint}
                   onChange={(e) => setProviderEndpoint(e.target.value)}
@@ -259,22 +2271,215 @@ const App: React.FC = () => {
 
               {/* Model */}
               <div>
-                <label className="block text-sm font-medium text-gray-300 mb-2">Model</label>
+                <label className="block text-sm font-medium text-vc-soft mb-2">Model</label>
+                <div className="flex items-center gap-3">
+                  <input
+                    type="text"
+                    className="flex-1 px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
+                    placeholder={DEFAULT_MODELS[currentProviderType]}
+                    value={providerModel}
+                    onChange={(e) => setProviderModel(e.target.value)}
+                  />
+                  {typeCapabilities?.modelListing && (
+                    <button
+                      onClick={fetchModels}
+                      className="px-4 py-2 bg-vc-raised hover:bg-vc-hover text-vc-text text-sm rounded-md"
+                    >
+                      Fetch Models
+                    </button>
+                  )}
+                </div>
+                {modelsError && <div className="mt-2 text-xs text-red-400">{modelsError}</div>}
+                {availableModels.length > 0 && (
+                  <div className="mt-2 flex flex-wrap gap-2 max-h-40 overflow-auto">
+                    {availableModels
+                      .filter(model => availableModels.length <= MAX_MODEL_CHIPS || model.toLowerCase().includes(providerModel.toLowerCase()))
+                      .slice(0, MAX_MODEL_CHIPS)
+                      .map(model => (
+                        <button
+                          key={model}
+                          onClick={() => setProviderModel(model)}
+                          className={`px-2 py-1 text-xs rounded-md border ${providerModel === model ? 'border-blue-500 text-blue-300' : 'border-vc-border text-vc-soft'} hover:border-blue-500`}
+                        >
+                          {model}
+                        </button>
+                      ))}
+                  </div>
+                )}
+              </div>
+
+              {/* Embedding Model */}
+              {typeCapabilities?.embeddings && (
+                <div>
+                  <label className="block text-sm font-medium text-vc-soft mb-2">Embedding Model</label>
+                  <input
+                    type="text"
+                    className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
+                    placeholder={currentProviderType === 'OpenAI' ? 'text-embedding-3-small' : currentProviderType === 'Mistral' ? 'mistral-embed' : 'Same as model'}
+                    value={providerEmbeddingModel}
+                    onChange={(e) => setProviderEmbeddingModel(e.target.value)}
+                  />
+                </div>
+              )}
+
+              {/* Gemini Safety Settings */}
+              {currentProviderType === 'Gemini' && (
+                <div>
+                  <label className="block text-sm font-medium text-vc-soft mb-2">Safety Threshold</label>
+                  <select
+                    className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text focus:outline-none focus:border-blue-500"
+                    value={providerSafety}
+                    onChange={(e) => setProviderSafety(e.target.value)}
+                  >
+                    {GEMINI_SAFETY_THRESHOLDS.map(threshold => (
+                      <option key={threshold} value={threshold}>{threshold || 'API default'}</option>
+                    ))}
+                  </select>
+                </div>
+              )}
+
+              {/* Mock script */}
+              {currentProviderType === 'Mock' && (
+                <div className="space-y-3">
+                  <div>
+                    <label className="block text-sm font-medium text-vc-soft mb-2">Canned Responses</label>
+                    <textarea
+                      rows={4}
+                      className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500 resize-none font-mono text-xs"
+                      placeholder={'Returned in order, separated by lines of ---\nthen the default mock response'}
+                      value={mockResponses}
+                      onChange={(e) => setMockResponses(e.target.value)}
+                    />
+                  </div>
+                  <div className="grid grid-cols-2 gap-3">
+                    <div>
+                      <label className="block text-sm font-medium text-vc-soft mb-2">Latency (ms)</label>
+                      <input
+                        type="number"
+                        min={0}
+                        className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
+                        placeholder="0"
+                        value={mockLatency}
+                        onChange={(e) => setMockLatency(e.target.value)}
+                      />
+                    </div>
+                    <div>
+                      <label className="block text-sm font-medium text-vc-soft mb-2">Fail Every Nth Request</label>
+                      <input
+                        type="number"
+                        min={0}
+                        className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
+                        placeholder="Never"
+                        value={mockFailEvery}
+                        onChange={(e) => setMockFailEvery(e.target.value)}
+                      />
+                    </div>
+                  </div>
+                </div>
+              )}
+
+              {/* System prompt */}
+              <div>
+                <label className="block text-sm font-medium text-vc-soft mb-2">System Prompt</label>
+                <textarea
+                  rows={3}
+                  className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500 resize-none"
+                  placeholder="e.g. Always answer in Rust"
+                  value={providerSystemPrompt}
+                  onChange={(e) => setProviderSystemPrompt(e.target.value)}
+                />
+              </div>
+
+              {/* Response text format */}
+              <div>
+                <label className="block text-sm font-medium text-vc-soft mb-2">Responses Are</label>
+                <select
+                  className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text focus:outline-none focus:border-blue-500"
+                  value={providerTextFormat}
+                  onChange={(e) => setProviderTextFormat(e.target.value as '' | 'plain')}
+                >
+                  <option value="">Markdown</option>
+                  <option value="plain">Plain text / console output</option>
+                </select>
+              </div>
+
+              {/* Timeout */}
+              <div>
+                <label className="block text-sm font-medium text-vc-soft mb-2">Request Timeout (seconds)</label>
                 <input
-                  type="text"
-                  className="w-full px-3 py-2 bg-[#1e1e1e] border border-[#3c3c3c] rounded-md text-gray-200 placeholder-gray-500 focus:outline-none focus:border-blue-500"
-                  placeholder={DEFAULT_MODELS[currentProviderType]}
-                  value={providerModel}
-                  onChange={(e) => setProviderModel(e.target.value)}
+                  type="number"
+                  min={1}
+                  className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
+                  placeholder="120"
+                  value={providerTimeout}
+                  onChange={(e) => setProviderTimeout(e.target.value)}
                 />
               </div>
+
+              {/* Network */}
+              {!LOCAL_TYPES.includes(currentProviderType) && (
+                <div className="space-y-3">
+                  <div className="grid grid-cols-2 gap-3">
+                    <div>
+                      <label className="block text-sm font-medium text-vc-soft mb-2">Proxy URL</label>
+                      <input
+                        type="text"
+                        className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
+                        placeholder="From HTTPS_PROXY"
+                        value={providerProxy}
+                        onChange={(e) => setProviderProxy(e.target.value)}
+                      />
+                    </div>
+                    <div>
+                      <label className="block text-sm font-medium text-vc-soft mb-2">CA Bundle (PEM path)</label>
+                      <input
+                        type="text"
+                        className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
+                        placeholder="System roots only"
+                        value={providerCABundle}
+                        onChange={(e) => setProviderCABundle(e.target.value)}
+                      />
+                    </div>
+                  </div>
+                  <label className="flex items-center gap-2 text-sm text-vc-soft">
+                    <input type="checkbox" checked={providerInsecure} onChange={(e) => setProviderInsecure(e.target.checked)} />
+                    Disable TLS certificate verification (insecure)
+                  </label>
+                </div>
+              )}
+
+              {/* Rate limits */}
+              <div className="grid grid-cols-2 gap-3">
+                <div>
+                  <label className="block text-sm font-medium text-vc-soft mb-2">Requests / minute</label>
+                  <input
+                    type="number"
+                    min={0}
+                    className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
+                    placeholder="Unlimited"
+                    value={providerRpm}
+                    onChange={(e) => setProviderRpm(e.target.value)}
+                  />
+                </div>
+                <div>
+                  <label className="block text-sm font-medium text-vc-soft mb-2">Tokens / minute</label>
+                  <input
+                    type="number"
+                    min={0}
+                    className="w-full px-3 py-2 bg-vc-bg border border-vc-border rounded-md text-vc-text placeholder-vc-muted focus:outline-none focus:border-blue-500"
+                    placeholder="Unlimited"
+                    value={providerTpm}
+                    onChange={(e) => setProviderTpm(e.target.value)}
+                  />
+                </div>
+              </div>
             </div>
 
             {/* Footer */}
-            <div className="flex items-center justify-end gap-3 p-4 border-t border-[#3c3c3c]">
+            <div className="flex items-center justify-end gap-3 p-4 border-t border-vc-border">
               <button
                 onClick={() => setShowProviderDialog(false)}
-                className="px-4 py-2 bg-[#3c3c3c] hover:bg-[#4c4c4c] text-gray-200 text-sm rounded-md"
+                className="px-4 py-2 bg-vc-raised hover:bg-vc-hover text-vc-text text-sm rounded-md"
               >
                 Cancel
               </button>
@@ -288,6 +2493,150 @@ const App: React.FC = () => {
           </div>
         </div>
       )}
+
+      {/* Usage Report Dialog */}
+      {usageDays !== null && (
+        <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50">
+          <div className="bg-vc-panel border border-vc-border rounded-lg w-[640px] max-h-[90vh] overflow-auto">
+            <div className="flex items-center justify-between p-4 border-b border-vc-border">
+              <h2 className="text-lg font-semibold text-vc-text">Token Usage</h2>
+              <div className="flex items-center gap-3">
+                <select
+                  className="bg-vc-bg border border-vc-border rounded-md text-vc-text text-xs px-2 py-1 focus:outline-none focus:border-blue-500"
+                  value={usageDays}
+                  onChange={(e) => setUsageDays(parseInt(e.target.value, 10))}
+                >
+                  {USAGE_RANGES.map(days => (
+                    <option key={days} value={days}>{days ? `Last ${days} days` : 'All time'}</option>
+                  ))}
+                </select>
+                <button onClick={() => setUsageDays(null)} className="text-vc-muted hover:text-vc-text">
+                  <X size={20} />
+                </button>
+              </div>
+            </div>
+            <div className="p-6 space-y-4 text-vc-soft text-xs">
+              {!usageReport || usageReport.records.length === 0 ? (
+                <p className="text-vc-muted">No usage recorded in this period.</p>
+              ) : (
+                <>
+                  <p>
+                    {usageReport.total.requests} requests · {(usageReport.total.promptTokens + usageReport.total.completionTokens).toLocaleString()} tokens · ${usageReport.total.cost.toFixed(4)}
+                  </p>
+                  <table className="w-full text-left">
+                    <thead className="text-vc-muted">
+                      <tr>
+                        <th className="py-1">Day</th>
+                        <th>Model</th>
+                        <th className="text-right">Requests</th>
+                        <th className="text-right">Prompt</th>
+                        <th className="text-right">Completion</th>
+                        <th className="text-right">Cost</th>
+                      </tr>
+                    </thead>
+                    <tbody>
+                      {usageReport.records.map(r => (
+                        <tr key={`${r.day}-${r.model}`} className="border-t border-vc-border">
+                          <td className="py-1">{r.day}</td>
+                          <td>{r.model}</td>
+                          <td className="text-right">{r.requests}</td>
+                          <td className="text-right">{r.promptTokens.toLocaleString()}</td>
+                          <td className="text-right">{r.completionTokens.toLocaleString()}</td>
+                          <td className="text-right">${r.cost.toFixed(4)}</td>
+                        </tr>
+                      ))}
+                    </tbody>
+                  </table>
+                </>
+              )}
+            </div>
+            <div className="flex items-center justify-end gap-3 p-4 border-t border-vc-border">
+              <button
+                onClick={clearUsageHistory}
+                className="px-4 py-2 bg-vc-raised hover:bg-vc-hover text-vc-text text-sm rounded-md"
+              >
+                Clear History
+              </button>
+            </div>
+          </div>
+        </div>
+      )}
+
+      {/* Interrupted Session Dialog */}
+      {!crashReport && interrupted.length > 0 && (
+        <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50">
+          <div className="bg-vc-panel border border-vc-border rounded-lg w-[560px] max-h-[90vh] overflow-auto">
+            <div className="flex items-center justify-between p-4 border-b border-vc-border">
+              <h2 className="text-lg font-semibold text-vc-text">Restore unfinished conversation?</h2>
+              <button onClick={() => resolveInterrupted(interrupted[0], false)} className="text-vc-muted hover:text-vc-text">
+                <X size={20} />
+              </button>
+            </div>
+            <div className="p-6 space-y-3 text-vc-soft text-xs">
+              <p>
+                Vibe Coder closed on {new Date(interrupted[0].pending!.started).toLocaleString()} while waiting for an
+                answer{interrupted[0].name ? ` in "${interrupted[0].name}"` : ''}. Restoring puts the prompt back and
+                shows what had arrived so far.
+              </p>
+              <pre className="bg-vc-bg border border-vc-border rounded-md p-3 max-h-60 overflow-auto whitespace-pre-wrap">
+                {interrupted[0].pending!.prompt}
+                {interrupted[0].pending!.partial && `\n\n${interrupted[0].pending!.partial}`}
+              </pre>
+            </div>
+            <div className="flex items-center justify-end gap-3 p-4 border-t border-vc-border">
+              <button
+                onClick={() => resolveInterrupted(interrupted[0], false)}
+                className="px-4 py-2 bg-vc-raised hover:bg-vc-hover text-vc-text text-sm rounded-md"
+              >
+                Discard
+              </button>
+              <button
+                onClick={() => resolveInterrupted(interrupted[0], true)}
+                className="px-4 py-2 bg-blue-600 hover:bg-blue-500 text-white text-sm rounded-md"
+              >
+                Restore
+              </button>
+            </div>
+          </div>
+        </div>
+      )}
+
+      {/* Crash Report Dialog */}
+      {crashReport && (
+        <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50">
+          <div className="bg-vc-panel border border-vc-border rounded-lg w-[560px] max-h-[90vh] overflow-auto">
+            <div className="flex items-center justify-between p-4 border-b border-vc-border">
+              <h2 className="text-lg font-semibold text-vc-text">Vibe Coder closed unexpectedly</h2>
+              <button onClick={() => resolveCrashReport(false)} className="text-vc-muted hover:text-vc-text">
+                <X size={20} />
+              </button>
+            </div>
+            <div className="p-6 space-y-3 text-vc-soft text-xs">
+              <p>
+                A crash report was saved on {new Date(crashReport.time).toLocaleString()}. It contains the backtrace,
+                app version, active provider type, and recent log lines. Prompts and responses are never included.
+              </p>
+              <pre className="bg-vc-bg border border-vc-border rounded-md p-3 max-h-60 overflow-auto whitespace-pre-wrap">
+                {`Version: ${crashReport.appVersion} (${crashReport.platform}, ${crashReport.goVersion})\nProvider: ${crashReport.activeProviderType}\nPanic: ${crashReport.panic}\n\n${crashReport.backtrace}`}
+              </pre>
+            </div>
+            <div className="flex items-center justify-end gap-3 p-4 border-t border-vc-border">
+              <button
+                onClick={() => resolveCrashReport(false)}
+                className="px-4 py-2 bg-vc-raised hover:bg-vc-hover text-vc-text text-sm rounded-md"
+              >
+                Don't Send
+              </button>
+              <button
+                onClick={() => resolveCrashReport(true)}
+                className="px-4 py-2 bg-blue-600 hover:bg-blue-500 text-white text-sm rounded-md"
+              >
+                Submit Report
+              </button>
+            </div>
+          </div>
+        </div>
+      )}
     </div>
   );
 };
@@ -0,0 +1,352 @@
+import React, { createContext, useContext, useEffect, useState } from 'react';
+
+// Mirrors ContentBlock in parser.go
+export interface ContentBlock {
+  type: 'heading' | 'paragraph' | 'code' | 'list' | 'quote' | 'table' | 'diff' | 'tasklist' | 'math' | 'rule' | 'image' | 'footnote' | 'deflist' | 'diagram';
+  source: { start: number; end: number }; // UTF-8 byte offsets into the raw response
+  level?: number;
+  text?: string;
+  spans?: InlineSpan[];
+  language?: string;
+  code?: string;
+  kind?: 'mermaid' | 'graphviz' | 'plantuml';
+  hunks?: DiffHunk[];
+  filePath?: string;
+  items?: ListItem[];
+  ordered?: boolean;
+  start?: number;
+  children?: ContentBlock[];
+  url?: string;
+  alt?: string;
+  label?: string;
+  headers?: string[];
+  rows?: string[][];
+  align?: ('left' | 'center' | 'right' | '')[];
+}
+
+export interface ListItem {
+  text: string;
+  spans?: InlineSpan[];
+  children?: ContentBlock[];
+  checked?: boolean;
+}
+
+// Mirrors InlineSpan in inline.go
+export interface InlineSpan {
+  type: 'text' | 'bold' | 'italic' | 'code' | 'link' | 'math' | 'footnote' | 'strike';
+  text?: string;
+  url?: string;
+  children?: InlineSpan[];
+}
+
+const Spans: React.FC<{ spans?: InlineSpan[]; text?: string }> = ({ spans, text }) => {
+  if (!spans) return <>{text}</>;
+  return (
+    <>
+      {spans.map((span, i) => {
+        switch (span.type) {
+          case 'bold':
+            return <strong key={i}><Spans spans={span.children} /></strong>;
+          case 'italic':
+            return <em key={i}><Spans spans={span.children} /></em>;
+          case 'code':
+            return <code key={i} className="px-1 rounded bg-vc-tab font-mono text-xs">{span.text}</code>;
+          case 'strike':
+            return <s key={i}><Spans spans={span.children} /></s>;
+          case 'footnote':
+            return <sup key={i}><a href={`#fn-${span.text}`} className="text-blue-400 hover:underline">[{span.text}]</a></sup>;
+          case 'math':
+            return <span key={i} className="px-0.5 font-serif italic text-amber-200">{span.text}</span>;
+          case 'link':
+            // Open links in the system browser rather than navigating the app's webview
+            return (
+              <a
+                key={i}
+                href={span.url}
+                onClick={(e) => { e.preventDefault(); window.runtime?.BrowserOpenURL(span.url || ''); }}
+                className="text-blue-400 hover:underline"
+              >
+                <Spans spans={span.children} />
+              </a>
+            );
+          default:
+            return <React.Fragment key={i}>{span.text}</React.Fragment>;
+        }
+      })}
+    </>
+  );
+};
+
+// Mirrors DiffHunk in diff.go
+export interface DiffHunk {
+  oldFile?: string;
+  newFile?: string;
+  header?: string;
+  oldStart: number;
+  oldLines: number;
+  newStart: number;
+  newLines: number;
+  lines: { kind: 'context' | 'added' | 'removed'; text: string }[];
+}
+
+const DIFF_LINE_STYLES = {
+  context: 'text-vc-soft',
+  added: 'bg-green-900/40 text-green-300',
+  removed: 'bg-red-900/40 text-red-300',
+};
+
+const DIFF_LINE_PREFIXES = { context: ' ', added: '+', removed: '-' };
+
+// Mirrors Section in sections.go
+export interface Section {
+  heading: ContentBlock | null;
+  level: number;
+  blocks: ContentBlock[];
+  subsections: Section[] | null;
+}
+
+// Mirrors StreamedBlocks in parser_stream.go
+export interface StreamedBlocks {
+  completed: ContentBlock[];
+  partial: ContentBlock[];
+}
+
+// Mirrors HighlightToken in highlight.go
+export interface HighlightToken {
+  kind: 'plain' | 'keyword' | 'string' | 'comment' | 'number' | 'type' | 'function';
+  text: string;
+}
+
+// The theme's syntax colors, set by App
+const TOKEN_COLORS: Record<HighlightToken['kind'], string | undefined> = {
+  plain: undefined,
+  keyword: 'var(--vc-tok-keyword)',
+  string: 'var(--vc-tok-string)',
+  comment: 'var(--vc-tok-comment)',
+  number: 'var(--vc-tok-number)',
+  type: 'var(--vc-tok-type)',
+  function: 'var(--vc-tok-function)',
+};
+
+// CodeBody colors code with the backend highlighter, showing it plain until the tokens arrive,
+// beside a gutter of line numbers that is left out when the code is selected
+const CodeBody: React.FC<{ code: string; language?: string }> = ({ code, language }) => {
+  const [tokens, setTokens] = useState<HighlightToken[] | null>(null);
+  useEffect(() => {
+    let current = true;
+    setTokens(null);
+    window.backend?.App?.HighlightCode?.(code, language || '')
+      .then(result => { if (current) setTokens(result); })
+      .catch(() => {});
+    return () => { current = false; };
+  }, [code, language]);
+
+  const lines = code.replace(/\n$/, '').split('\n').length;
+  return (
+    <pre className="flex py-3 overflow-x-auto text-xs font-mono text-vc-text bg-vc-code">
+      <code className="select-none shrink-0 px-3 text-right text-vc-muted border-r border-vc-tab" aria-hidden>
+        {Array.from({ length: lines }, (_, i) => i + 1).join('\n')}
+      </code>
+      <code className="px-3">
+        {tokens
+          ? tokens.map((token, i) => (
+              <span key={i} style={{ color: TOKEN_COLORS[token.kind], fontStyle: token.kind === 'comment' ? 'italic' : undefined }}>{token.text}</span>
+            ))
+          : code}
+      </code>
+    </pre>
+  );
+};
+
+// Mirrors the copy modes in clipboard.go
+export type CopyMode = 'code' | 'markdown';
+
+// CopyContext carries how to copy a block down to nested blocks, resolving
+// to whether it was copied; without it no copy buttons are shown
+const CopyContext = createContext<((block: ContentBlock, mode: CopyMode) => Promise<boolean>) | undefined>(undefined);
+
+// How long a copy button says it copied
+const COPIED_MS = 1500;
+
+const CopyButtons: React.FC<{ block: ContentBlock }> = ({ block }) => {
+  const copy = useContext(CopyContext);
+  const [copied, setCopied] = useState<CopyMode | null>(null);
+  useEffect(() => {
+    if (!copied) return;
+    const timer = setTimeout(() => setCopied(null), COPIED_MS);
+    return () => clearTimeout(timer);
+  }, [copied]);
+  if (!copy) return null;
+  const press = async (mode: CopyMode) => {
+    if (await copy(block, mode)) setCopied(mode);
+  };
+  return (
+    <span className="flex gap-2 shrink-0">
+      <button onClick={() => press('code')} title="Copy the code" className="hover:text-vc-text">{copied === 'code' ? 'Copied ✓' : 'Copy'}</button>
+      <button onClick={() => press('markdown')} title="Copy as Markdown, with its code fence" className="hover:text-vc-text">{copied === 'markdown' ? 'Copied ✓' : 'MD'}</button>
+    </span>
+  );
+};
+
+const HEADING_SIZES = ['text-2xl', 'text-xl', 'text-lg', 'text-base', 'text-sm', 'text-sm'];
+
+// TaskList tracks ticks locally so suggested steps can be checked off as they're done
+const TaskList: React.FC<{ items: ListItem[] }> = ({ items }) => {
+  const [checked, setChecked] = useState(() => items.map(item => !!item.checked));
+  return (
+    <ul className="space-y-1">
+      {items.map((item, i) => (
+        <li key={i} className="flex items-start gap-2">
+          <input
+            type="checkbox"
+            className="mt-1"
+            checked={checked[i] ?? !!item.checked}
+            onChange={() => setChecked(prev => { const next = [...prev]; next[i] = !next[i]; return next; })}
+          />
+          <div className={checked[i] ? 'line-through text-vc-muted' : ''}>
+            <Spans spans={item.spans} text={item.text} />
+            {item.children && <div className="mt-1 space-y-1">{item.children.map((child, j) => <Block key={j} block={child} />)}</div>}
+          </div>
+        </li>
+      ))}
+    </ul>
+  );
+};
+
+const Block: React.FC<{ block: ContentBlock }> = ({ block }) => {
+  switch (block.type) {
+    case 'heading':
+      return <div className={`${HEADING_SIZES[(block.level || 1) - 1]} font-semibold text-vc-text`}><Spans spans={block.spans} text={block.text} /></div>;
+    case 'code':
+      return (
+        <div className="rounded-md border border-vc-border overflow-hidden">
+          <div className="px-3 py-1 bg-vc-panel text-xs text-vc-muted flex justify-between gap-3">
+            <span className="font-mono text-vc-soft">{block.filePath}</span>
+            <span className="flex items-center gap-3">
+              <span className="px-1.5 rounded bg-vc-raised text-vc-soft uppercase tracking-wide text-[10px]" title="Language">{block.language || 'text'}</span>
+              <CopyButtons block={block} />
+            </span>
+          </div>
+          <CodeBody code={block.code || ''} language={block.language} />
+        </div>
+      );
+    case 'diagram':
+      // Not drawn yet; the source is kept verbatim for copying and export
+      return (
+        <div className="rounded-md border border-dashed border-vc-border overflow-hidden">
+          <div className="px-3 py-1 bg-vc-panel text-xs text-vc-muted flex justify-between gap-3">
+            <span>{block.kind} diagram</span>
+            <CopyButtons block={block} />
+          </div>
+          <pre className="p-3 overflow-x-auto text-xs font-mono text-vc-text bg-vc-code">{block.code}</pre>
+        </div>
+      );
+    case 'diff':
+      return (
+        <div className="relative rounded-md border border-vc-border overflow-hidden text-xs font-mono">
+          <div className="absolute top-1 right-3 text-vc-muted font-sans"><CopyButtons block={block} /></div>
+          {(block.hunks || []).map((hunk, h) => (
+            <div key={h}>
+              {(hunk.newFile || hunk.oldFile || (h === 0 && block.filePath)) && (h === 0 || hunk.newFile !== block.hunks![h - 1].newFile) && (
+                <div className="px-3 py-1 bg-vc-panel text-vc-soft">{hunk.newFile || hunk.oldFile || block.filePath}</div>
+              )}
+              {hunk.oldStart > 0 && (
+                <div className="px-3 py-0.5 bg-[#1f2a3a] text-blue-300">
+                  @@ -{hunk.oldStart},{hunk.oldLines} +{hunk.newStart},{hunk.newLines} @@ {hunk.header}
+                </div>
+              )}
+              <pre className="overflow-x-auto bg-vc-code">
+                {hunk.lines.map((line, i) => (
+                  <div key={i} className={`px-3 ${DIFF_LINE_STYLES[line.kind]}`}>{DIFF_LINE_PREFIXES[line.kind]}{line.text}</div>
+                ))}
+              </pre>
+            </div>
+          ))}
+        </div>
+      );
+    case 'math':
+      // Shown as TeX source, set apart from the prose so formulas stay readable
+      return <pre className="px-3 py-2 rounded-md bg-vc-bg border border-vc-border text-center font-serif italic text-amber-200 overflow-x-auto">{block.code}</pre>;
+    case 'rule':
+      return <hr className="border-vc-border" />;
+    case 'image':
+      return (
+        <figure className="space-y-1">
+          <img src={block.url} alt={block.alt} className="max-w-full rounded-md border border-vc-border" />
+          {block.alt && <figcaption className="text-xs text-vc-muted">{block.alt}</figcaption>}
+        </figure>
+      );
+    case 'footnote':
+      return (
+        <div id={`fn-${block.label}`} className="flex gap-2 text-xs text-vc-muted border-t border-vc-border pt-2">
+          <sup>{block.label}</sup>
+          <div className="space-y-1">{(block.children || []).map((child, i) => <Block key={i} block={child} />)}</div>
+        </div>
+      );
+    case 'deflist':
+      return (
+        <dl className="space-y-2">
+          {(block.items || []).map((item, i) => (
+            <div key={i}>
+              <dt className="font-semibold text-vc-text"><Spans spans={item.spans} text={item.text} /></dt>
+              {(item.children || []).map((child, j) => <dd key={j} className="pl-4"><Block block={child} /></dd>)}
+            </div>
+          ))}
+        </dl>
+      );
+    case 'tasklist':
+      return <TaskList items={block.items || []} />;
+    case 'list': {
+      const items = (block.items || []).map((item, i) => (
+        <li key={i}>
+          <Spans spans={item.spans} text={item.text} />
+          {item.children && <div className="mt-1 space-y-1">{item.children.map((child, j) => <Block key={j} block={child} />)}</div>}
+        </li>
+      ));
+      return block.ordered
+        ? <ol start={block.start} className="list-decimal pl-6 space-y-1">{items}</ol>
+        : <ul className="list-disc pl-6 space-y-1 [&_ul]:list-[circle]">{items}</ul>;
+    }
+    case 'quote':
+      return (
+        <blockquote className="border-l-4 border-vc-border pl-3 text-vc-muted space-y-2">
+          {(block.children || []).map((child, i) => <Block key={i} block={child} />)}
+        </blockquote>
+      );
+    case 'table':
+      return (
+        <div className="overflow-x-auto">
+          <table className="text-sm border-collapse">
+            <thead>
+              <tr>
+                {(block.headers || []).map((header, i) => (
+                  <th key={i} className="border border-vc-border px-3 py-1 bg-vc-panel font-semibold" style={{ textAlign: block.align?.[i] || 'left' }}>{header}</th>
+                ))}
+              </tr>
+            </thead>
+            <tbody>
+              {(block.rows || []).map((row, r) => (
+                <tr key={r}>
+                  {row.map((cell, i) => (
+                    <td key={i} className="border border-vc-border px-3 py-1" style={{ textAlign: block.align?.[i] || 'left' }}>{cell}</td>
+                  ))}
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      );
+    default:
+      return <p className="leading-relaxed"><Spans spans={block.spans} text={block.text} /></p>;
+  }
+};
+
+// ContentView renders a parsed response; with onCopy, code, diffs and
+// diagrams get buttons to copy them
+export const ContentView: React.FC<{ blocks: ContentBlock[]; onCopy?: (block: ContentBlock, mode: CopyMode) => Promise<boolean> }> = ({ blocks, onCopy }) => (
+  <CopyContext.Provider value={onCopy}>
+    <div className="space-y-3 text-sm text-vc-text">
+      {blocks.map((block, i) => <Block key={i} block={block} />)}
+    </div>
+  </CopyContext.Provider>
+);
@@ -0,0 +1,54 @@
+import React, { useEffect, useState } from 'react';
+import { ContentBlock, ContentView, CopyMode } from './ContentView';
+
+// A turn of the conversation, as the session keeps it
+export interface TranscriptMessage {
+  role: 'system' | 'user' | 'assistant';
+  content: string;
+  textFormat?: string; // assistant turns: how content is parsed, '' for markdown
+}
+
+// PromptBubble is a prompt the user sent, kept as typed
+export const PromptBubble: React.FC<{ text: string }> = ({ text }) => (
+  <div className="flex justify-end">
+    <div className="max-w-[80%] rounded-md bg-vc-selection px-3 py-2 text-sm text-vc-text whitespace-pre-wrap break-words">{text}</div>
+  </div>
+);
+
+// Answer renders an earlier response, parsing it once in the format it was given in; it
+// shows as text until then
+const Answer: React.FC<{ text: string; format?: string; onCopy?: (block: ContentBlock, mode: CopyMode) => Promise<boolean> }> = ({ text, format, onCopy }) => {
+  const [blocks, setBlocks] = useState<ContentBlock[] | null>(null);
+  useEffect(() => {
+    let current = true;
+    window.backend?.App?.ParseResponse?.(text, format || '')
+      .then(result => { if (current) setBlocks(result); })
+      .catch(() => {});
+    return () => { current = false; };
+  }, [text, format]);
+
+  return blocks
+    ? <ContentView blocks={blocks} onCopy={onCopy} />
+    : <div className="text-sm text-vc-text whitespace-pre-wrap">{text}</div>;
+};
+
+// Transcript shows a conversation turn by turn, prompts on the right and responses rendered
+// beneath them; system messages are left out
+export const Transcript: React.FC<{ messages: TranscriptMessage[]; onCopy?: (block: ContentBlock, mode: CopyMode) => Promise<boolean> }> = ({ messages, onCopy }) => (
+  <>
+    {messages.map((message, i) => {
+      switch (message.role) {
+        case 'user':
+          return <PromptBubble key={i} text={message.content} />;
+        case 'assistant':
+          return (
+            <div key={i} className="border-b border-vc-tab pb-4">
+              <Answer text={message.content} format={message.textFormat} onCopy={onCopy} />
+            </div>
+          );
+        default:
+          return null;
+      }
+    })}
+  </>
+);
@@ -36,6 +36,22 @@ export default {
           DEFAULT: "hsl(var(--card))",
           foreground: "hsl(var(--card-foreground))",
         },
+        // The theme's palette, set by App from Palette in theme.go
+        vc: {
+          bg: "var(--vc-bg)",
+          panel: "var(--vc-panel)",
+          tab: "var(--vc-tab)",
+          activity: "var(--vc-activity)",
+          raised: "var(--vc-raised)",
+          hover: "var(--vc-hover)",
+          selection: "var(--vc-selection)",
+          border: "var(--vc-border)",
+          code: "var(--vc-code)",
+          text: "var(--vc-text)",
+          soft: "var(--vc-soft)",
+          muted: "var(--vc-muted)",
+          accent: "var(--vc-accent)",
+        },
       },
       borderRadius: {
         lg: "var(--radius)",
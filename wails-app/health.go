@@ -0,0 +1,136 @@
+package main
+
+import (
+	"fmt"
+	"sync"
+	"time"
+)
+
+// HealthChecker is implemented by providers that can verify their endpoint
+// and credentials with a lightweight call
+type HealthChecker interface {
+	HealthCheck() error
+}
+
+const (
+	HealthUnknown   = "unknown"
+	HealthReady     = "ready"
+	HealthUnhealthy = "unhealthy"
+	HealthOffline   = "offline" // remote provider not probed because offline mode is on
+)
+
+// ProviderHealth is the result of the most recent readiness probe for a provider
+type ProviderHealth struct {
+	Name      string `json:"name"`
+	Status    string `json:"status"`
+	Error     string `json:"error"`
+	CheckedAt string `json:"checkedAt"`
+}
+
+// checkProvider probes a single provider; providers without a check are assumed
+// ready. offlineErr, when set, skips the probe and reports the provider offline.
+func checkProvider(provider Provider, offlineErr error) ProviderHealth {
+	health := ProviderHealth{
+		Name:      provider.GetName(),
+		Status:    HealthReady,
+		CheckedAt: time.Now().Format(time.RFC3339),
+	}
+	if offlineErr != nil {
+		health.Status = HealthOffline
+		health.Error = offlineErr.Error()
+		return health
+	}
+	if checker, ok := provider.(HealthChecker); ok {
+		if err := checker.HealthCheck(); err != nil {
+			health.Status = HealthUnhealthy
+			health.Error = err.Error()
+		}
+	}
+	return health
+}
+
+// initialHealth is a new provider's status: unknown until probed, unless its
+// API key couldn't be read from the keyring
+func initialHealth(provider Provider, secretErr error) ProviderHealth {
+	if secretErr != nil {
+		return ProviderHealth{Name: provider.GetName(), Status: HealthUnhealthy, Error: secretErr.Error()}
+	}
+	return ProviderHealth{Name: provider.GetName(), Status: HealthUnknown}
+}
+
+// CheckHealth probes every provider concurrently and returns their statuses
+func (m *ProviderManager) CheckHealth() []ProviderHealth {
+	m.mu.RLock()
+	providers := append([]Provider(nil), m.providers...)
+	replaced := m.replaced
+	offline := make([]error, len(providers))
+	for i, provider := range providers {
+		offline[i] = m.checkOffline(provider, m.configs[i])
+	}
+	m.mu.RUnlock()
+
+	results := make([]ProviderHealth, len(providers))
+	var wg sync.WaitGroup
+	for i, provider := range providers {
+		wg.Add(1)
+		goSafe(func() {
+			defer wg.Done()
+			results[i] = checkProvider(provider, offline[i])
+		})
+	}
+	wg.Wait()
+
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	for i := range results {
+		// Providers are only appended between replacements, so indices stay stable
+		if replaced == m.replaced && i < len(m.health) {
+			m.health[i] = results[i]
+		}
+	}
+	return append([]ProviderHealth(nil), m.health...)
+}
+
+// CheckHealthAt probes the provider at index and records the result
+func (m *ProviderManager) CheckHealthAt(index int) (ProviderHealth, error) {
+	m.mu.RLock()
+	if index < 0 || index >= len(m.providers) {
+		m.mu.RUnlock()
+		return ProviderHealth{}, fmt.Errorf("invalid provider index")
+	}
+	provider := m.providers[index]
+	replaced := m.replaced
+	offlineErr := m.checkOffline(provider, m.configs[index])
+	m.mu.RUnlock()
+
+	health := checkProvider(provider, offlineErr)
+
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	if replaced == m.replaced {
+		m.health[index] = health
+	}
+	return health, nil
+}
+
+// Health returns the last known status of every provider
+func (m *ProviderManager) Health() []ProviderHealth {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+
+	return append([]ProviderHealth(nil), m.health...)
+}
+
+// HealthCheck verifies the Ollama server is reachable and serves the configured model
+func (p *OllamaProvider) HealthCheck() error {
+	models, err := p.ListModels()
+	if err != nil || p.config.Model == "" {
+		return err
+	}
+	for _, model := range models {
+		if modelMatches(model, p.config.Model) {
+			return nil
+		}
+	}
+	return fmt.Errorf("model %q is not installed (run `ollama pull %s`)", p.config.Model, p.config.Model)
+}
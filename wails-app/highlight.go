@@ -0,0 +1,220 @@
+package main
+
+import (
+	"strings"
+	"unicode"
+	"unicode/utf8"
+)
+
+const (
+	TokenPlain    = "plain"
+	TokenKeyword  = "keyword"
+	TokenString   = "string"
+	TokenComment  = "comment"
+	TokenNumber   = "number"
+	TokenType     = "type"     // capitalized identifiers
+	TokenFunction = "function" // identifiers directly followed by (
+)
+
+// HighlightToken is a run of code of one kind; concatenating the tokens gives
+// back the code
+type HighlightToken struct {
+	Kind string `json:"kind"`
+	Text string `json:"text"`
+}
+
+// syntax describes a language closely enough to color it: keywords, comments
+// and string delimiters. There is no grammar, so highlighting is lexical only.
+type syntax struct {
+	keywords     string // space separated
+	lineComments []string
+	blockComment [2]string
+	quotes       string // characters that open a string
+	multiline    string // quotes whose strings may span lines
+	tripleQuotes bool   // Python's """ and ''' strings
+	charLiterals bool   // a ' only opens a string when it closes within a rune, so Rust lifetimes stay plain
+	types        bool   // color capitalized identifiers as types
+}
+
+var cStyleComments = [2]string{"/*", "*/"}
+
+var syntaxes = map[string]*syntax{
+	"rust": {
+		keywords:     "as async await break const continue crate dyn else enum extern false fn for if impl in let loop match mod move mut pub ref return self Self static struct super trait true type unsafe use where while",
+		lineComments: []string{"//"}, blockComment: cStyleComments, quotes: `"'`, multiline: `"`, charLiterals: true, types: true,
+	},
+	"go": {
+		keywords:     "break case chan const continue default defer else fallthrough false for func go goto if import interface map nil package range return select struct switch true type var",
+		lineComments: []string{"//"}, blockComment: cStyleComments, quotes: "\"'`", multiline: "`", types: true,
+	},
+	"python": {
+		keywords:     "False None True and as assert async await break class continue def del elif else except finally for from global if import in is lambda nonlocal not or pass raise return self try while with yield",
+		lineComments: []string{"#"}, quotes: `"'`, tripleQuotes: true, types: true,
+	},
+	"javascript": {
+		keywords:     "async await break case catch class const continue debugger default delete do else export extends false finally for from function if import in instanceof let new null of return static super switch this throw true try typeof undefined var void while with yield",
+		lineComments: []string{"//"}, blockComment: cStyleComments, quotes: "\"'`", multiline: "`", types: true,
+	},
+	"typescript": {
+		keywords:     "abstract any as async await boolean break case catch class const continue declare default delete do else enum export extends false finally for from function if implements import in instanceof interface keyof let never new null number of private protected public readonly return static string super switch this throw true try type typeof undefined unknown var void while yield",
+		lineComments: []string{"//"}, blockComment: cStyleComments, quotes: "\"'`", multiline: "`", types: true,
+	},
+	"java": {
+		keywords:     "abstract boolean break byte case catch char class continue default do double else enum extends false final finally float for if implements import instanceof int interface long new null package private protected public return short static super switch this throw throws true try void volatile while",
+		lineComments: []string{"//"}, blockComment: cStyleComments, quotes: `"'`, types: true,
+	},
+	"c": {
+		keywords:     "auto break case char const continue default do double else enum extern float for goto if inline int long register return short signed sizeof static struct switch typedef union unsigned void volatile while NULL",
+		lineComments: []string{"//"}, blockComment: cStyleComments, quotes: `"'`,
+	},
+	"cpp": {
+		keywords:     "auto bool break case catch char class const constexpr continue default delete do double else enum explicit false float for friend if inline int long namespace new noexcept nullptr operator private protected public return short signed sizeof static struct switch template this throw true try typedef typename union unsigned using virtual void volatile while",
+		lineComments: []string{"//"}, blockComment: cStyleComments, quotes: `"'`, types: true,
+	},
+	"bash": {
+		keywords:     "case do done elif else esac export fi for function if in local return then until while",
+		lineComments: []string{"#"}, quotes: `"'`, multiline: `"'`,
+	},
+	"sql": {
+		keywords:     "and as asc by create delete desc distinct drop from group having in index inner insert into is join key left limit not null on or order outer primary references right select set table union update values where",
+		lineComments: []string{"--"}, blockComment: cStyleComments, quotes: `'"`,
+	},
+	"json": {
+		keywords: "true false null",
+		quotes:   `"`,
+	},
+	"yaml": {
+		keywords:     "true false null yes no",
+		lineComments: []string{"#"}, quotes: `"'`,
+	},
+	"ruby": {
+		keywords:     "alias and begin break case class def do else elsif end ensure false for if in module next nil not or redo rescue retry return self super then true unless until when while yield",
+		lineComments: []string{"#"}, quotes: `"'`, types: true,
+	},
+}
+
+// Highlight splits code into tokens for coloring. Languages without a syntax
+// come back as a single plain token.
+func Highlight(code, language string) []HighlightToken {
+	language = normalizeLanguage(language)
+	lang, ok := syntaxes[language]
+	if !ok {
+		return []HighlightToken{{Kind: TokenPlain, Text: code}}
+	}
+	keywords := make(map[string]bool)
+	for _, keyword := range strings.Fields(lang.keywords) {
+		keywords[keyword] = true
+	}
+	caseless := language == "sql"
+
+	var tokens []HighlightToken
+	emit := func(kind, text string) {
+		if n := len(tokens); n > 0 && tokens[n-1].Kind == kind && kind == TokenPlain {
+			tokens[n-1].Text += text
+			return
+		}
+		tokens = append(tokens, HighlightToken{Kind: kind, Text: text})
+	}
+
+	for i := 0; i < len(code); {
+		rest := code[i:]
+		if n := lang.commentAt(rest); n > 0 {
+			emit(TokenComment, rest[:n])
+			i += n
+			continue
+		}
+		if n := lang.stringAt(rest); n > 0 {
+			emit(TokenString, rest[:n])
+			i += n
+			continue
+		}
+
+		r, size := utf8.DecodeRuneInString(rest)
+		previous, _ := utf8.DecodeLastRuneInString(code[:i])
+		wordBefore := i > 0 && isIdentRune(previous)
+		switch {
+		case unicode.IsDigit(r) && !wordBefore:
+			n := len(rest) - len(strings.TrimLeftFunc(rest, func(r rune) bool { return isIdentRune(r) || r == '.' }))
+			emit(TokenNumber, rest[:n])
+			i += n
+		case (unicode.IsLetter(r) || r == '_') && !wordBefore:
+			n := len(rest) - len(strings.TrimLeftFunc(rest, isIdentRune))
+			word := rest[:n]
+			switch {
+			case keywords[word] || caseless && keywords[strings.ToLower(word)]:
+				emit(TokenKeyword, word)
+			case strings.HasPrefix(strings.TrimLeft(rest[n:], " "), "("):
+				emit(TokenFunction, word)
+			case lang.types && unicode.IsUpper(r):
+				emit(TokenType, word)
+			default:
+				emit(TokenPlain, word)
+			}
+			i += n
+		default:
+			emit(TokenPlain, rest[:size])
+			i += size
+		}
+	}
+	return tokens
+}
+
+// commentAt returns the length of a comment starting s, or 0
+func (lang *syntax) commentAt(s string) int {
+	for _, prefix := range lang.lineComments {
+		if strings.HasPrefix(s, prefix) {
+			if end := strings.IndexByte(s, '\n'); end >= 0 {
+				return end
+			}
+			return len(s)
+		}
+	}
+	if opener, closer := lang.blockComment[0], lang.blockComment[1]; opener != "" && strings.HasPrefix(s, opener) {
+		if end := strings.Index(s[len(opener):], closer); end >= 0 {
+			return len(opener) + end + len(closer)
+		}
+		return len(s) // still streaming, or never closed
+	}
+	return 0
+}
+
+// stringAt returns the length of a string literal starting s, or 0. An
+// unterminated string runs to the end of its line.
+func (lang *syntax) stringAt(s string) int {
+	if s == "" || !strings.ContainsRune(lang.quotes, rune(s[0])) {
+		return 0
+	}
+	quote := s[:1]
+	if lang.tripleQuotes && strings.HasPrefix(s, strings.Repeat(quote, 3)) {
+		if end := strings.Index(s[3:], s[:3]); end >= 0 {
+			return 3 + end + 3
+		}
+		return len(s)
+	}
+	multiline := strings.Contains(lang.multiline, quote)
+	for i := 1; i < len(s); i++ {
+		switch s[i] {
+		case '\\':
+			i++
+		case quote[0]:
+			return i + 1
+		case '\n':
+			if !multiline {
+				return i
+			}
+		}
+		if lang.charLiterals && quote == "'" && i >= 2 && s[1] != '\\' {
+			return 0 // 'a is a lifetime, not the start of a char
+		}
+	}
+	return len(s)
+}
+
+func isIdentRune(r rune) bool {
+	return unicode.IsLetter(r) || unicode.IsDigit(r) || r == '_'
+}
+
+// HighlightCode exposes the highlighter to the frontend
+func (a *App) HighlightCode(code, language string) []HighlightToken {
+	return Highlight(code, language)
+}
@@ -0,0 +1,68 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestHighlight(t *testing.T) {
+	tests := []struct {
+		name     string
+		code     string
+		language string
+		want     []HighlightToken
+	}{
+		{
+			name: "go string and comment", code: `x := "hi" // note`, language: "go",
+			want: []HighlightToken{{TokenPlain, "x := "}, {TokenString, `"hi"`}, {TokenPlain, " "}, {TokenComment, "// note"}},
+		},
+		{
+			name: "keyword and function through an alias", code: "func Main(n int) {}", language: "golang",
+			want: []HighlightToken{{TokenKeyword, "func"}, {TokenPlain, " "}, {TokenFunction, "Main"}, {TokenPlain, "(n int) {}"}},
+		},
+		{
+			name: "type", code: "var x Foo", language: "go",
+			want: []HighlightToken{{TokenKeyword, "var"}, {TokenPlain, " x "}, {TokenType, "Foo"}},
+		},
+		{
+			name: "number", code: "x = 42", language: "python",
+			want: []HighlightToken{{TokenPlain, "x = "}, {TokenNumber, "42"}},
+		},
+		{
+			name: "triple-quoted string", code: "s = \"\"\"a\nb\"\"\"", language: "python",
+			want: []HighlightToken{{TokenPlain, "s = "}, {TokenString, "\"\"\"a\nb\"\"\""}},
+		},
+		{
+			name: "rust char", code: "let c = 'x';", language: "rust",
+			want: []HighlightToken{{TokenKeyword, "let"}, {TokenPlain, " c = "}, {TokenString, "'x'"}, {TokenPlain, ";"}},
+		},
+		{
+			name: "rust lifetime", code: "&'a str", language: "rust",
+			want: []HighlightToken{{TokenPlain, "&'a str"}},
+		},
+		{
+			name: "caseless sql keywords", code: "SELECT 1", language: "sql",
+			want: []HighlightToken{{TokenKeyword, "SELECT"}, {TokenPlain, " "}, {TokenNumber, "1"}},
+		},
+		{
+			name: "unknown language", code: "+++ ---", language: "brainfuck",
+			want: []HighlightToken{{TokenPlain, "+++ ---"}},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got := Highlight(tt.code, tt.language)
+			if !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %+v, want %+v", got, tt.want)
+			}
+			var text strings.Builder
+			for _, token := range got {
+				text.WriteString(token.Text)
+			}
+			if text.String() != tt.code {
+				t.Errorf("tokens give back %q, want %q", text.String(), tt.code)
+			}
+		})
+	}
+}
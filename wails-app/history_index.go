@@ -0,0 +1,120 @@
+package main
+
+import (
+	"math"
+	"strings"
+	"unicode"
+)
+
+// historyPromptWeight is how much more a word counts in a prompt than in its
+// response
+const historyPromptWeight = 2
+
+// Okapi BM25's term saturation and length normalization
+const (
+	bm25K1 = 1.2
+	bm25B  = 0.75
+)
+
+// historyIndex is an inverted index of the words in the history's prompts
+// and responses, for ranked search; the history's lock guards it
+type historyIndex struct {
+	postings map[string]map[int64]float64 // word to entry ID to weighted count
+	lengths  map[int64]float64            // weighted words in each entry
+	total    float64                      // of lengths
+}
+
+func newHistoryIndex() *historyIndex {
+	return &historyIndex{postings: make(map[string]map[int64]float64), lengths: make(map[int64]float64)}
+}
+
+// searchWords splits text into lowercase words of letters and digits
+func searchWords(text string) []string {
+	return strings.FieldsFunc(strings.ToLower(text), func(r rune) bool {
+		return !unicode.IsLetter(r) && !unicode.IsDigit(r)
+	})
+}
+
+func entryWords(entry SessionEntry) map[string]float64 {
+	counts := make(map[string]float64)
+	for _, word := range searchWords(entry.Prompt) {
+		counts[word] += historyPromptWeight
+	}
+	for _, word := range searchWords(entry.Response) {
+		counts[word]++
+	}
+	return counts
+}
+
+func (x *historyIndex) add(entry SessionEntry) {
+	var length float64
+	for word, count := range entryWords(entry) {
+		posting, ok := x.postings[word]
+		if !ok {
+			posting = make(map[int64]float64)
+			x.postings[word] = posting
+		}
+		posting[entry.ID] = count
+		length += count
+	}
+	x.lengths[entry.ID] = length
+	x.total += length
+}
+
+func (x *historyIndex) remove(entry SessionEntry) {
+	for word := range entryWords(entry) {
+		delete(x.postings[word], entry.ID)
+		if len(x.postings[word]) == 0 {
+			delete(x.postings, word)
+		}
+	}
+	x.total -= x.lengths[entry.ID]
+	delete(x.lengths, entry.ID)
+}
+
+// score ranks the entries containing every one of words by BM25, keyed by
+// ID. A word also matches the longer words it starts, at half weight, so a
+// search can be typed a letter at a time.
+func (x *historyIndex) score(words []string) map[int64]float64 {
+	n := float64(len(x.lengths))
+	if n == 0 || len(words) == 0 {
+		return nil
+	}
+	average := x.total / n
+	var scores map[int64]float64
+	for _, word := range words {
+		best := make(map[int64]float64)
+		for indexed, posting := range x.postings {
+			if !strings.HasPrefix(indexed, word) {
+				continue
+			}
+			weight := 1.0
+			if indexed != word {
+				weight = 0.5
+			}
+			found := float64(len(posting))
+			idf := math.Log(1 + (n-found+0.5)/(found+0.5))
+			for id, count := range posting {
+				saturated := count * (bm25K1 + 1) / (count + bm25K1*(1-bm25B+bm25B*x.lengths[id]/average))
+				if score := weight * idf * saturated; score > best[id] {
+					best[id] = score
+				}
+			}
+		}
+		if scores == nil {
+			scores = best
+		} else {
+			for id := range scores {
+				if score, ok := best[id]; ok {
+					scores[id] += score
+				} else {
+					delete(scores, id)
+				}
+			}
+		}
+		if len(scores) == 0 {
+			break
+		}
+	}
+	return scores
+}
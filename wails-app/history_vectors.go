@@ -0,0 +1,223 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"math"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+)
+
+// Entries embedded per request, and the length they are cut to so they fit
+// the embedding models' inputs
+const (
+	historyEmbedBatch = 64
+	historyEmbedChars = 8000
+)
+
+// Embedder embeds texts with one model, named so vectors from different
+// models are never compared
+type Embedder struct {
+	Model string
+	Embed func(texts []string) ([][]float32, error)
+}
+
+// historyVectors is a small vector store of the history's entries, each
+// embedded once and kept on disk next to the history; the history's lock
+// guards it
+type historyVectors struct {
+	path    string
+	Model   string              `json:"model"`
+	Vectors map[int64][]float32 `json:"vectors"` // by entry ID, of unit length
+}
+
+// historyVectorsPath keeps the vectors beside the history file
+func historyVectorsPath(historyPath string) string {
+	if historyPath == "" {
+		return ""
+	}
+	return strings.TrimSuffix(historyPath, filepath.Ext(historyPath)) + "-vectors.json"
+}
+
+func loadHistoryVectors(path string) *historyVectors {
+	vectors := &historyVectors{path: path, Vectors: make(map[int64][]float32)}
+	if path == "" {
+		return vectors
+	}
+	data, err := os.ReadFile(path)
+	if err != nil {
+		if !errors.Is(err, os.ErrNotExist) {
+			fmt.Fprintf(os.Stderr, "failed to read history vectors: %v\n", err)
+		}
+		return vectors
+	}
+	if err := json.Unmarshal(data, vectors); err != nil || vectors.Vectors == nil {
+		fmt.Fprintf(os.Stderr, "ignoring corrupt history vectors %s: %v\n", path, err)
+		vectors.Model, vectors.Vectors = "", make(map[int64][]float32)
+	}
+	return vectors
+}
+
+// use switches to model, dropping the vectors another model made
+func (v *historyVectors) use(model string) {
+	if v.Model != model {
+		v.Model, v.Vectors = model, make(map[int64][]float32)
+	}
+}
+
+// save writes the vectors atomically
+func (v *historyVectors) save() error {
+	if v.path == "" {
+		return nil
+	}
+	if err := os.MkdirAll(filepath.Dir(v.path), 0o700); err != nil {
+		return err
+	}
+	data, err := json.Marshal(v)
+	if err != nil {
+		return err
+	}
+	tmp := v.path + ".tmp"
+	if err := os.WriteFile(tmp, data, 0o600); err != nil {
+		return err
+	}
+	return os.Rename(tmp, v.path)
+}
+
+// clear forgets every vector and deletes the file
+func (v *historyVectors) clear() error {
+	v.Vectors = make(map[int64][]float32)
+	if v.path == "" {
+		return nil
+	}
+	if err := os.Remove(v.path); err != nil && !errors.Is(err, os.ErrNotExist) {
+		return err
+	}
+	return nil
+}
+
+// normalized scales vector to unit length, so cosine similarity is a dot
+// product
+func normalized(vector []float32) []float32 {
+	var sum float64
+	for _, x := range vector {
+		sum += float64(x) * float64(x)
+	}
+	if sum == 0 {
+		return vector
+	}
+	norm := float32(math.Sqrt(sum))
+	unit := make([]float32, len(vector))
+	for i, x := range vector {
+		unit[i] = x / norm
+	}
+	return unit
+}
+
+func dot(a, b []float32) float64 {
+	if len(a) != len(b) {
+		return 0
+	}
+	var sum float64
+	for i := range a {
+		sum += float64(a[i]) * float64(b[i])
+	}
+	return sum
+}
+
+func embeddingText(entry SessionEntry) string {
+	text := entry.Prompt + "\n\n" + entry.Response
+	if len(text) > historyEmbedChars {
+		text = strings.ToValidUTF8(text[:historyEmbedChars], "")
+	}
+	return text
+}
+
+// SemanticSearch returns the k entries closest in meaning to query, by the
+// cosine similarity of their embeddings, best first. Entries not yet
+// embedded with embedder's model are embedded first and the vectors kept.
+func (h *SessionHistory) SemanticSearch(query string, k int, embedder Embedder) ([]HistoryMatch, error) {
+	if strings.TrimSpace(query) == "" || k <= 0 {
+		return []HistoryMatch{}, nil
+	}
+
+	// Embedding needs the network, so the lock is only held to read and
+	// store; entries dropped meanwhile are skipped
+	h.mu.Lock()
+	h.vectors.use(embedder.Model)
+	var ids []int64
+	var texts []string
+	for _, entry := range h.entries {
+		if _, ok := h.vectors.Vectors[entry.ID]; !ok {
+			ids = append(ids, entry.ID)
+			texts = append(texts, embeddingText(entry))
+		}
+	}
+	h.mu.Unlock()
+
+	embedded := make(map[int64][]float32, len(ids))
+	for start := 0; start < len(texts); start += historyEmbedBatch {
+		end := min(start+historyEmbedBatch, len(texts))
+		vectors, err := embedder.Embed(texts[start:end])
+		if err != nil {
+			return nil, err
+		}
+		if len(vectors) != end-start {
+			return nil, fmt.Errorf("expected %d embeddings, got %d", end-start, len(vectors))
+		}
+		for i, vector := range vectors {
+			embedded[ids[start+i]] = normalized(vector)
+		}
+	}
+	vectors, err := embedder.Embed([]string{query})
+	if err != nil {
+		return nil, err
+	}
+	if len(vectors) != 1 {
+		return nil, fmt.Errorf("expected 1 embedding, got %d", len(vectors))
+	}
+	target := normalized(vectors[0])
+
+	h.mu.Lock()
+	defer h.mu.Unlock()
+
+	if h.vectors.Model == embedder.Model && len(embedded) > 0 {
+		live := make(map[int64]bool, len(h.entries))
+		for _, entry := range h.entries {
+			live[entry.ID] = true
+		}
+		for id, vector := range embedded {
+			if live[id] {
+				h.vectors.Vectors[id] = vector
+			}
+		}
+		if err := h.vectors.save(); err != nil {
+			fmt.Fprintf(os.Stderr, "failed to save history vectors: %v\n", err)
+		}
+	}
+	matches := make([]HistoryMatch, 0, len(h.entries))
+	for i := len(h.entries) - 1; i >= 0; i-- {
+		entry := h.entries[i]
+		vector, ok := h.vectors.Vectors[entry.ID]
+		if !ok {
+			vector, ok = embedded[entry.ID]
+		}
+		if ok {
+			matches = append(matches, HistoryMatch{Entry: entry, Score: dot(target, vector)})
+		}
+	}
+	sort.SliceStable(matches, func(i, j int) bool { return matches[i].Score > matches[j].Score })
+	if len(matches) > k {
+		matches = matches[:k]
+	}
+	return matches, nil
+}
+
+// SemanticSearchHistory returns the k recorded prompts and responses closest
+// in meaning to query, embedded by the active provider
+func (a *App) SemanticSearchHistory(query string, k int) ([]HistoryMatch, error) {
+	return a.history.SemanticSearch(query, k, Embedder{Model: a.manager.EmbeddingModel(), Embed: a.manager.Embed})
+}
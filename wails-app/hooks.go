@@ -0,0 +1,91 @@
+package main
+
+import "context"
+
+// Plugin events, the moments in the app's life a Hooks plugin hears about
+const (
+	PluginEventSessionStart     = "session_start"     // the app started or a new chat began
+	PluginEventResponseReceived = "response_received" // a response arrived, after post-processing
+	PluginEventExport           = "export"            // a conversation or response was exported
+	PluginEventShutdown         = "shutdown"          // the app is closing
+)
+
+// PluginEvent is what a Hooks plugin is told; fields that don't apply to
+// the kind of event are left empty
+type PluginEvent struct {
+	Kind     string `json:"kind"`
+	Provider string `json:"provider,omitempty"`
+	Model    string `json:"model,omitempty"`
+	Content  string `json:"content,omitempty"` // the response, or what was exported
+	Format   string `json:"format,omitempty"`  // the response's TextFormat, or the export format
+	Path     string `json:"path,omitempty"`    // where an export was written
+}
+
+// Hooks is implemented by plugins that react to what happens in the app,
+// such as logging responses or sending notifications, without changing it.
+// Events arrive in run order and block the app while they are handled, so
+// slow work belongs on a goroutine.
+type Hooks interface {
+	OnEvent(event PluginEvent) error
+}
+
+// Emit tells every enabled Hooks plugin about an event. A failing plugin
+// doesn't stop the others, whatever its error policy, but a run of failures
+// still disables it under PluginOnErrorDisable. Errors are returned together.
+func (r *PluginRegistry) Emit(event PluginEvent) []error {
+	var errs []error
+	for _, plugin := range r.enabled(PluginHooks) {
+		name := plugin.Metadata().Name
+		err := r.invoke(plugin, "on_event", func() error { return plugin.(Hooks).OnEvent(event) })
+		if err != nil {
+			r.failed(name, "on_event", err)
+			errs = append(errs, &PluginError{Plugin: name, Err: err})
+			continue
+		}
+		r.succeeded(name)
+	}
+	return errs
+}
+
+// emit sends an event to the plugins, logging what fails
+func (a *App) emit(event PluginEvent) {
+	for _, err := range a.manager.Plugins().Emit(event) {
+		a.logs.Warning(err.Error())
+	}
+}
+
+// received records a prompt's response in the session history and tells
+// plugins about it
+func (a *App) received(prompt string, response AIResponse) {
+	a.history.Record(prompt, response)
+	a.emit(PluginEvent{
+		Kind:     PluginEventResponseReceived,
+		Provider: response.Provider,
+		Model:    response.Model,
+		Content:  response.Content,
+		Format:   response.TextFormat,
+	})
+}
+
+// receivedAll records and tells plugins about each response in results that
+// succeeded, results[i] answering prompts[i]
+func (a *App) receivedAll(prompts []string, results []ProviderResult) []ProviderResult {
+	for i, result := range results {
+		if result.Error == "" {
+			a.received(prompts[i], result.Response)
+		}
+	}
+	return results
+}
+
+// StartSession tells plugins a new chat has begun
+func (a *App) StartSession() {
+	a.emit(PluginEvent{Kind: PluginEventSessionStart})
+}
+
+// shutdown saves what autosave hasn't yet and tells plugins the app is
+// closing
+func (a *App) shutdown(ctx context.Context) {
+	a.sessions.Flush()
+	a.emit(PluginEvent{Kind: PluginEventShutdown})
+}
@@ -0,0 +1,358 @@
+package main
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"slices"
+	"sort"
+	"strings"
+	"time"
+
+	wailsruntime "github.com/wailsapp/wails/v2/pkg/runtime"
+)
+
+// Import conflict policies: what becomes of an imported entry whose id the
+// history already has for a different exchange
+const (
+	ImportKeepBoth = "keep-both" // the imported entry is added under a new id
+	ImportSkip     = "skip"      // the history's entry stays and the imported one is dropped
+	ImportReplace  = "replace"   // the imported entry takes the other's place
+)
+
+// ImportResult is what an import did to the history
+type ImportResult struct {
+	Added      int `json:"added"`
+	Replaced   int `json:"replaced"`
+	Duplicates int `json:"duplicates"` // already in the history, so left out
+	Conflicts  int `json:"conflicts"`  // ids the history had for another exchange
+	Pruned     int `json:"pruned"`     // entries retention dropped afterwards
+}
+
+// ParseImport reads the exchanges back out of a json or markdown export;
+// with no format it is told from the content. JSON may also be the history
+// file itself, copied from another machine.
+func ParseImport(data []byte, format string) ([]ExportEntry, error) {
+	if format == "" {
+		format = ExportMarkdown
+		if trimmed := bytes.TrimSpace(data); len(trimmed) > 0 && (trimmed[0] == '{' || trimmed[0] == '[') {
+			format = ExportJSON
+		}
+	}
+	switch format {
+	case ExportJSON:
+		return parseJSONImport(data)
+	case ExportMarkdown:
+		return parseMarkdownImport(string(data))
+	}
+	return nil, fmt.Errorf("can't import %q; only json and markdown exports can be read back", format)
+}
+
+func parseJSONImport(data []byte) ([]ExportEntry, error) {
+	data = bytes.TrimSpace(data)
+	if bytes.HasPrefix(data, []byte("[")) {
+		var entries []SessionEntry
+		if err := json.Unmarshal(data, &entries); err != nil {
+			return nil, fmt.Errorf("not a history file: %w", err)
+		}
+		for _, entry := range entries {
+			if entry.ID <= 0 {
+				return nil, errors.New("not a history file: its entries have no ids")
+			}
+		}
+		return NewHistoryExporter("", entries).Entries, nil
+	}
+	var export struct {
+		Version int           `json:"version"`
+		Entries []ExportEntry `json:"entries"`
+	}
+	if err := json.Unmarshal(data, &export); err != nil {
+		return nil, fmt.Errorf("not a JSON export: %w", err)
+	}
+	if export.Version == 0 {
+		return nil, errors.New("not a Vibe Coder export: it has no version")
+	}
+	if export.Version > exportVersion {
+		return nil, fmt.Errorf("the export is version %d, newer than the %d this version reads", export.Version, exportVersion)
+	}
+	return export.Entries, nil
+}
+
+// parseMarkdownImport splits a Markdown export at its exchange headings and
+// takes each one's byline, quoted prompt and response, moving the
+// response's headings back up to where they were
+func parseMarkdownImport(text string) ([]ExportEntry, error) {
+	var sections [][]string
+	fence := ""
+	for _, line := range strings.Split(strings.ReplaceAll(text, "\r\n", "\n"), "\n") {
+		if fence == "" && strings.HasPrefix(line, "## ") {
+			sections = append(sections, nil)
+			continue
+		}
+		fence = codeFence(fence, line)
+		if len(sections) > 0 {
+			sections[len(sections)-1] = append(sections[len(sections)-1], line)
+		}
+	}
+	if len(sections) == 0 {
+		return nil, errors.New("no exchanges found; only Markdown exported from Vibe Coder can be imported")
+	}
+
+	entries := make([]ExportEntry, 0, len(sections))
+	for _, lines := range sections {
+		var entry ExportEntry
+		blank := func() {
+			for len(lines) > 0 && strings.TrimSpace(lines[0]) == "" {
+				lines = lines[1:]
+			}
+		}
+		blank()
+		if len(lines) > 0 && len(lines[0]) > 2 && strings.HasPrefix(lines[0], "_") && strings.HasSuffix(lines[0], "_") {
+			entry.parseByline(unescapeInline(lines[0][1 : len(lines[0])-1]))
+			lines = lines[1:]
+			blank()
+		}
+		// The provenance flags are worked out again from the response
+		if len(lines) > 0 && strings.HasPrefix(lines[0], "_"+provenancePrefix) && strings.HasSuffix(lines[0], "_") {
+			lines = lines[1:]
+			blank()
+		}
+		var prompt []string
+		for len(lines) > 0 && strings.HasPrefix(lines[0], ">") {
+			prompt = append(prompt, strings.TrimPrefix(strings.TrimPrefix(lines[0], ">"), " "))
+			lines = lines[1:]
+		}
+		entry.Prompt = strings.Join(prompt, "\n")
+
+		var response []string
+		fence := ""
+		for _, line := range lines {
+			if fence == "" {
+				if level := len(line) - len(strings.TrimLeft(line, "#")); level >= 3 && strings.HasPrefix(line[level:], " ") {
+					line = line[2:]
+				}
+			}
+			fence = codeFence(fence, line)
+			response = append(response, line)
+		}
+		entry.Response = strings.TrimSpace(strings.Join(response, "\n"))
+		entries = append(entries, entry)
+	}
+	return entries, nil
+}
+
+// codeFence is the code fence open after line, given the one open before
+// it, so headings and exchange breaks inside code are left alone
+func codeFence(open, line string) string {
+	trimmed := strings.TrimLeft(line, " ")
+	if open != "" {
+		if strings.HasPrefix(trimmed, open) && strings.TrimRight(trimmed, open[:1]+" ") == "" {
+			return ""
+		}
+		return open
+	}
+	for _, marker := range []string{"```", "~~~"} {
+		if strings.HasPrefix(trimmed, marker) {
+			return trimmed[:len(trimmed)-len(strings.TrimLeft(trimmed, marker[:1]))]
+		}
+	}
+	return ""
+}
+
+// unescapeInline undoes escapeInline, dropping the backslash before any
+// punctuation
+func unescapeInline(text string) string {
+	var out strings.Builder
+	for i := 0; i < len(text); i++ {
+		if text[i] == '\\' && i+1 < len(text) && strings.IndexByte("!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~", text[i+1]) >= 0 {
+			i++
+		}
+		out.WriteByte(text[i])
+	}
+	return out.String()
+}
+
+// parseByline reads back what byline wrote: the provider, model and time an
+// exchange has
+func (entry *ExportEntry) parseByline(byline string) {
+	parts := strings.Split(byline, " · ")
+	if t, err := time.ParseInLocation("2006-01-02 15:04", parts[len(parts)-1], time.Local); err == nil {
+		entry.Time = t.Format(time.RFC3339)
+		parts = parts[:len(parts)-1]
+	}
+	if len(parts) > 0 {
+		entry.Provider = parts[0]
+	}
+	if len(parts) > 1 {
+		entry.Model = parts[1]
+	}
+}
+
+// sessionEntry is the exchange as a history entry, its tags cleaned up as
+// Tag would, its stats measured and its provenance worked out if the export
+// didn't carry it
+func (entry ExportEntry) sessionEntry() SessionEntry {
+	imported := SessionEntry{
+		ID:         entry.ID,
+		Time:       entry.Time,
+		Prompt:     entry.Prompt,
+		Response:   entry.Response,
+		Provider:   entry.Provider,
+		Model:      entry.Model,
+		TextFormat: entry.TextFormat,
+		Tokens:     entry.Tokens,
+		Cost:       entry.Cost,
+		Pinned:     entry.Pinned,
+		Provenance: entry.Provenance,
+	}
+	if imported.Time == "" {
+		imported.Time = time.Now().Format(time.RFC3339)
+	}
+	blocks := ParseResponseAs(imported.Response, imported.TextFormat)
+	imported.Stats = ComputeResponseStats(imported.Response, blocks)
+	if imported.Provenance == nil {
+		provenance := AnalyzeProvenance(blocks)
+		imported.Provenance = &provenance
+	}
+	for _, tag := range entry.Tags {
+		if tag, err := normalizeTag(tag); err == nil {
+			imported.Tags = append(imported.Tags, tag)
+		}
+	}
+	sort.Strings(imported.Tags)
+	imported.Tags = slices.Compact(imported.Tags)
+	return imported
+}
+
+// exchangeKey is what makes two entries the same exchange, whatever their
+// ids. Exchanges without an id are also looked up by what was said alone,
+// as their times may be missing or, from Markdown, to the minute.
+func exchangeKey(when, prompt, response string) string {
+	return when + "\x00" + prompt + "\x00" + response
+}
+
+// Import merges exchanges into the history by id. One it has already,
+// under any id, is left out; one whose id it has for a different exchange
+// is a conflict, settled by conflict: keep-both (the default), skip or
+// replace. Exchanges without an id, as from Markdown or session exports,
+// are added under new ones.
+func (h *SessionHistory) Import(imported []ExportEntry, conflict string) (ImportResult, error) {
+	switch conflict {
+	case "":
+		conflict = ImportKeepBoth
+	case ImportKeepBoth, ImportSkip, ImportReplace:
+	default:
+		return ImportResult{}, fmt.Errorf("unknown conflict policy %q", conflict)
+	}
+
+	h.mu.Lock()
+	defer h.mu.Unlock()
+
+	var result ImportResult
+	known := make(map[string]bool, len(h.entries))
+	for _, entry := range h.entries {
+		known[exchangeKey(entry.Time, entry.Prompt, entry.Response)] = true
+		known[exchangeKey("", entry.Prompt, entry.Response)] = true
+	}
+	var unnumbered []SessionEntry
+	for _, item := range imported {
+		key := exchangeKey(item.Time, item.Prompt, item.Response)
+		if known[key] || (item.ID <= 0 && known[exchangeKey("", item.Prompt, item.Response)]) {
+			result.Duplicates++
+			continue
+		}
+		entry := item.sessionEntry()
+		known[key] = true
+		known[exchangeKey("", entry.Prompt, entry.Response)] = true
+		if entry.ID <= 0 {
+			unnumbered = append(unnumbered, entry)
+			continue
+		}
+		i := sort.Search(len(h.entries), func(i int) bool { return h.entries[i].ID >= entry.ID })
+		if i == len(h.entries) || h.entries[i].ID != entry.ID {
+			h.entries = slices.Insert(h.entries, i, entry)
+			h.index.add(entry)
+			result.Added++
+			continue
+		}
+		result.Conflicts++
+		switch conflict {
+		case ImportSkip:
+		case ImportReplace:
+			existing := h.entries[i]
+			delete(known, exchangeKey(existing.Time, existing.Prompt, existing.Response))
+			h.index.remove(existing)
+			delete(h.vectors.Vectors, existing.ID)
+			h.entries[i] = entry
+			h.index.add(entry)
+			result.Replaced++
+		default:
+			unnumbered = append(unnumbered, entry)
+		}
+	}
+	// Numbered after the rest, so they can't take an id imported later
+	for _, entry := range unnumbered {
+		entry.ID = 1
+		if n := len(h.entries); n > 0 {
+			entry.ID = h.entries[n-1].ID + 1
+		}
+		h.entries = append(h.entries, entry)
+		h.index.add(entry)
+		result.Added++
+	}
+	if result.Added+result.Replaced == 0 {
+		return result, nil
+	}
+	pruned, err := h.prune()
+	if err != nil {
+		fmt.Fprintf(os.Stderr, "failed to prune session history: %v\n", err)
+	}
+	result.Pruned = pruned
+	return result, h.save()
+}
+
+// importFormat is the export format a file's extension says it is in, or
+// empty to tell from its content
+func importFormat(path string) string {
+	switch strings.ToLower(filepath.Ext(path)) {
+	case ".json":
+		return ExportJSON
+	case ".md", ".markdown":
+		return ExportMarkdown
+	}
+	return ""
+}
+
+// ChooseImportPath asks for an export to import, from Vibe Coder or another
+// chat service, with an open dialog; it returns nothing if the dialog was
+// cancelled
+func (a *App) ChooseImportPath() (string, error) {
+	if a.ctx == nil {
+		return "", errors.New("no window to ask in")
+	}
+	return wailsruntime.OpenFileDialog(a.ctx, wailsruntime.OpenDialogOptions{
+		Title: "Import history",
+		Filters: []wailsruntime.FileFilter{
+			{DisplayName: "Vibe Coder exports (*.json, *.md)", Pattern: "*.json;*.md;*.markdown"},
+			{DisplayName: "ChatGPT or Claude exports (*.zip, conversations.json)", Pattern: "*.zip;*.json"},
+		},
+	})
+}
+
+// ImportSessionHistory merges a JSON or Markdown export, or another
+// machine's history file, into the history, settling id conflicts with
+// conflict: keep-both, skip or replace
+func (a *App) ImportSessionHistory(path, conflict string) (ImportResult, error) {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return ImportResult{}, err
+	}
+	entries, err := ParseImport(data, importFormat(path))
+	if err != nil {
+		return ImportResult{}, fmt.Errorf("%s: %w", filepath.Base(path), err)
+	}
+	return a.history.Import(entries, conflict)
+}
@@ -0,0 +1,353 @@
+package main
+
+import (
+	"archive/zip"
+	"bytes"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"math"
+	"os"
+	"path"
+	"strings"
+	"time"
+)
+
+// Chat services whose exports can be imported as sessions
+const (
+	ChatSourceChatGPT = "chatgpt"
+	ChatSourceClaude  = "claude"
+)
+
+// ImportedChat is a conversation from another chat service, ready to become
+// a session
+type ImportedChat struct {
+	Source   string // ChatSourceChatGPT or ChatSourceClaude
+	SourceID string // the service's id for it, so it is imported once
+	Title    string
+	Created  time.Time
+	Updated  time.Time
+	Messages []ChatMessage // user and assistant turns, alternating from the first prompt
+	Times    []time.Time   // when each message was sent; zero when the export didn't say
+	Model    string        // the last model that answered, when known
+}
+
+// ChatImportResult is what importing a chat service's export did
+type ChatImportResult struct {
+	Sessions int          `json:"sessions"` // conversations added as sessions
+	Skipped  int          `json:"skipped"`  // imported before, or with nothing said
+	History  ImportResult `json:"history"`  // their exchanges, added to the searchable history
+}
+
+// ParseChatExport reads the conversations out of an OpenAI (ChatGPT) or
+// Anthropic (Claude) data export, given as its conversations.json or the zip
+// it was downloaded in; which service it is from is told from the content
+func ParseChatExport(data []byte) ([]ImportedChat, error) {
+	if bytes.HasPrefix(data, []byte("PK\x03\x04")) {
+		var err error
+		if data, err = conversationsFromZip(data); err != nil {
+			return nil, err
+		}
+	}
+	var raw []json.RawMessage
+	if err := json.Unmarshal(data, &raw); err != nil {
+		return nil, fmt.Errorf("not a conversations.json export: %w", err)
+	}
+	var probe struct {
+		Mapping      json.RawMessage `json:"mapping"`
+		ChatMessages json.RawMessage `json:"chat_messages"`
+	}
+	if len(raw) > 0 {
+		if err := json.Unmarshal(raw[0], &probe); err != nil {
+			return nil, fmt.Errorf("not a conversations.json export: %w", err)
+		}
+	}
+	switch {
+	case len(raw) == 0:
+		return nil, nil
+	case probe.Mapping != nil:
+		return parseChatGPTExport(data)
+	case probe.ChatMessages != nil:
+		return parseClaudeExport(data)
+	}
+	return nil, errors.New("not a ChatGPT or Claude export: its conversations have neither a mapping nor chat_messages")
+}
+
+// conversationsFromZip is the conversations.json in an export's zip
+func conversationsFromZip(data []byte) ([]byte, error) {
+	archive, err := zip.NewReader(bytes.NewReader(data), int64(len(data)))
+	if err != nil {
+		return nil, err
+	}
+	for _, file := range archive.File {
+		if path.Base(file.Name) != "conversations.json" {
+			continue
+		}
+		r, err := file.Open()
+		if err != nil {
+			return nil, err
+		}
+		defer r.Close()
+		return io.ReadAll(r)
+	}
+	return nil, errors.New("the zip has no conversations.json")
+}
+
+// chatGPTNode is a message in a ChatGPT conversation, which is a tree:
+// editing a prompt or regenerating an answer branches it
+type chatGPTNode struct {
+	Parent  string `json:"parent"`
+	Message *struct {
+		Author struct {
+			Role string `json:"role"`
+		} `json:"author"`
+		CreateTime float64 `json:"create_time"`
+		Content    struct {
+			ContentType string            `json:"content_type"`
+			Parts       []json.RawMessage `json:"parts"`
+		} `json:"content"`
+		Metadata struct {
+			ModelSlug string `json:"model_slug"`
+			Hidden    bool   `json:"is_visually_hidden_from_conversation"`
+		} `json:"metadata"`
+	} `json:"message"`
+}
+
+// parseChatGPTExport reads OpenAI's conversations.json, following each
+// conversation from its current message back to the start, so it comes in
+// as it was last seen and abandoned branches are left out
+func parseChatGPTExport(data []byte) ([]ImportedChat, error) {
+	var conversations []struct {
+		ID          string                 `json:"id"`
+		Title       string                 `json:"title"`
+		CreateTime  float64                `json:"create_time"`
+		UpdateTime  float64                `json:"update_time"`
+		CurrentNode string                 `json:"current_node"`
+		Mapping     map[string]chatGPTNode `json:"mapping"`
+	}
+	if err := json.Unmarshal(data, &conversations); err != nil {
+		return nil, fmt.Errorf("not a ChatGPT export: %w", err)
+	}
+	chats := make([]ImportedChat, 0, len(conversations))
+	for _, conversation := range conversations {
+		chat := ImportedChat{
+			Source:   ChatSourceChatGPT,
+			SourceID: conversation.ID,
+			Title:    conversation.Title,
+			Created:  unixSeconds(conversation.CreateTime),
+			Updated:  unixSeconds(conversation.UpdateTime),
+		}
+		var branch []chatGPTNode
+		seen := make(map[string]bool)
+		for id := conversation.CurrentNode; id != "" && !seen[id]; id = conversation.Mapping[id].Parent {
+			seen[id] = true
+			branch = append(branch, conversation.Mapping[id])
+		}
+		for i := len(branch) - 1; i >= 0; i-- {
+			message := branch[i].Message
+			if message == nil || message.Metadata.Hidden {
+				continue
+			}
+			if kind := message.Content.ContentType; kind != "text" && kind != "multimodal_text" {
+				continue // code run, browsing and the like
+			}
+			var parts []string
+			for _, part := range message.Content.Parts {
+				// Images and files are objects; only the text comes across
+				var text string
+				if json.Unmarshal(part, &text) == nil && text != "" {
+					parts = append(parts, text)
+				}
+			}
+			chat.add(message.Author.Role, strings.Join(parts, "\n"), unixSeconds(message.CreateTime))
+			if message.Author.Role == "assistant" && message.Metadata.ModelSlug != "" {
+				chat.Model = message.Metadata.ModelSlug
+			}
+		}
+		chats = append(chats, chat)
+	}
+	return chats, nil
+}
+
+// parseClaudeExport reads Anthropic's conversations.json, where each
+// conversation is a list of messages from the human and the assistant
+func parseClaudeExport(data []byte) ([]ImportedChat, error) {
+	var conversations []struct {
+		UUID         string `json:"uuid"`
+		Name         string `json:"name"`
+		CreatedAt    string `json:"created_at"`
+		UpdatedAt    string `json:"updated_at"`
+		ChatMessages []struct {
+			Sender    string `json:"sender"`
+			Text      string `json:"text"`
+			CreatedAt string `json:"created_at"`
+			Content   []struct {
+				Type string `json:"type"`
+				Text string `json:"text"`
+			} `json:"content"`
+		} `json:"chat_messages"`
+	}
+	if err := json.Unmarshal(data, &conversations); err != nil {
+		return nil, fmt.Errorf("not a Claude export: %w", err)
+	}
+	chats := make([]ImportedChat, 0, len(conversations))
+	for _, conversation := range conversations {
+		chat := ImportedChat{
+			Source:   ChatSourceClaude,
+			SourceID: conversation.UUID,
+			Title:    conversation.Name,
+			Created:  rfc3339(conversation.CreatedAt),
+			Updated:  rfc3339(conversation.UpdatedAt),
+		}
+		for _, message := range conversation.ChatMessages {
+			text := message.Text
+			if len(message.Content) > 0 {
+				// Newer exports split messages into content blocks; tool use
+				// and thinking are left out
+				var parts []string
+				for _, block := range message.Content {
+					if block.Type == "text" && block.Text != "" {
+						parts = append(parts, block.Text)
+					}
+				}
+				text = strings.Join(parts, "\n\n")
+			}
+			role := message.Sender
+			if role == "human" {
+				role = "user"
+			}
+			chat.add(role, text, rfc3339(message.CreatedAt))
+		}
+		chats = append(chats, chat)
+	}
+	return chats, nil
+}
+
+// add appends a message, joining it to the one before when both are from
+// the same side so turns alternate; anything before the first prompt, or
+// from neither side, is dropped
+func (chat *ImportedChat) add(role, text string, sent time.Time) {
+	text = strings.TrimSpace(text)
+	if (role != "user" && role != "assistant") || text == "" {
+		return
+	}
+	if len(chat.Messages) == 0 && role != "user" {
+		return
+	}
+	if n := len(chat.Messages); n > 0 && chat.Messages[n-1].Role == role {
+		chat.Messages[n-1].Content += "\n\n" + text
+		chat.Times[n-1] = sent
+		return
+	}
+	chat.Messages = append(chat.Messages, ChatMessage{Role: role, Content: text})
+	chat.Times = append(chat.Times, sent)
+}
+
+// exchanges is the chat's prompts and answers as history entries
+func (chat *ImportedChat) exchanges() []ExportEntry {
+	var entries []ExportEntry
+	for i := 0; i+1 < len(chat.Messages); i += 2 {
+		sent := chat.Times[i+1]
+		if sent.IsZero() {
+			sent = chat.Updated
+		}
+		entry := ExportEntry{
+			Prompt:   chat.Messages[i].Content,
+			Response: chat.Messages[i+1].Content,
+			Provider: chat.Source,
+			Model:    chat.Model,
+			Tags:     []string{"imported"},
+		}
+		if !sent.IsZero() {
+			entry.Time = sent.Format(time.RFC3339)
+		}
+		entries = append(entries, entry)
+	}
+	return entries
+}
+
+func unixSeconds(seconds float64) time.Time {
+	if seconds <= 0 {
+		return time.Time{}
+	}
+	whole, fraction := math.Modf(seconds)
+	return time.Unix(int64(whole), int64(fraction*1e9))
+}
+
+func rfc3339(value string) time.Time {
+	t, _ := time.Parse(time.RFC3339, value)
+	return t
+}
+
+// ImportChats adds conversations from other chat services as sessions,
+// leaving the open one open. Each is imported once: one from a service and
+// id already here, or with nothing said, is skipped.
+func (s *SessionStore) ImportChats(chats []ImportedChat) (added, skipped int, err error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	imported := make(map[string]bool)
+	for _, session := range s.sessions {
+		if session.ImportedFrom != "" {
+			imported[session.ImportedFrom] = true
+		}
+	}
+	active := s.active
+	defer func() {
+		if s.active = active; active == "" {
+			s.active = s.latest()
+		}
+	}()
+	for _, chat := range chats {
+		from := chat.Source + ":" + chat.SourceID
+		if len(chat.Messages) == 0 || (chat.SourceID != "" && imported[from]) {
+			skipped++
+			continue
+		}
+		created, createErr := s.create(strings.TrimSpace(chat.Title))
+		if createErr != nil {
+			return added, skipped, createErr
+		}
+		session := s.sessions[created.ID]
+		session.Messages = chat.Messages
+		session.ImportedFrom = from
+		if !chat.Created.IsZero() {
+			session.Created = chat.Created.UTC().Format(time.RFC3339)
+		}
+		if !chat.Updated.IsZero() {
+			session.Updated = chat.Updated.UTC().Format(time.RFC3339)
+		}
+		if err := s.save(session); err != nil {
+			return added, skipped, err
+		}
+		imported[from] = true
+		added++
+	}
+	return added, skipped, nil
+}
+
+// ImportChats adds the conversations in a ChatGPT or Claude data export, its
+// zip or the conversations.json in it, as sessions, and their exchanges to
+// the history so they can be searched
+func (a *App) ImportChats(path string) (ChatImportResult, error) {
+	data, err := os.ReadFile(path)
+	if err != nil {
+		return ChatImportResult{}, err
+	}
+	chats, err := ParseChatExport(data)
+	if err != nil {
+		return ChatImportResult{}, err
+	}
+	var result ChatImportResult
+	if result.Sessions, result.Skipped, err = a.sessions.ImportChats(chats); err != nil {
+		return result, err
+	}
+	var entries []ExportEntry
+	for i := range chats {
+		entries = append(entries, chats[i].exchanges()...)
+	}
+	// Exchanges imported before are duplicates, so skipping changes nothing
+	result.History, err = a.history.Import(entries, ImportSkip)
+	return result, err
+}
@@ -0,0 +1,128 @@
+package main
+
+import (
+	"archive/zip"
+	"bytes"
+	"reflect"
+	"testing"
+	"time"
+)
+
+const chatGPTExport = `[{"id": "c1", "title": "Greeting", "create_time": 1700000000, "update_time": 1700000020, "current_node": "n4", "mapping": {
+	"root": {"parent": "", "message": null},
+	"n1": {"parent": "root", "message": {"author": {"role": "system"}, "content": {"content_type": "text", "parts": ["be nice"]}, "metadata": {"is_visually_hidden_from_conversation": true}}},
+	"n2": {"parent": "n1", "message": {"author": {"role": "user"}, "create_time": 1700000010, "content": {"content_type": "text", "parts": ["hi"]}}},
+	"n3": {"parent": "n2", "message": {"author": {"role": "assistant"}, "content": {"content_type": "text", "parts": ["old answer"]}}},
+	"n4": {"parent": "n2", "message": {"author": {"role": "assistant"}, "create_time": 1700000020, "content": {"content_type": "text", "parts": ["hello"]}, "metadata": {"model_slug": "gpt-4o"}}}
+}}]`
+
+const claudeExport = `[{"uuid": "u1", "name": "Plan", "created_at": "2026-01-02T03:04:05Z", "updated_at": "2026-01-02T03:05:00Z", "chat_messages": [
+	{"sender": "human", "text": "first", "created_at": "2026-01-02T03:04:05Z"},
+	{"sender": "human", "text": "second", "created_at": "2026-01-02T03:04:30Z"},
+	{"sender": "assistant", "text": "answer, with thinking", "created_at": "2026-01-02T03:05:00Z", "content": [{"type": "thinking", "text": "hmm"}, {"type": "text", "text": "answer"}]}
+]}, {"uuid": "u2", "name": "Empty", "created_at": "2026-01-03T00:00:00Z", "updated_at": "2026-01-03T00:00:00Z", "chat_messages": []}]`
+
+// zipped wraps conversations.json in a folder of a zip, as the services
+// deliver it
+func zipped(t *testing.T, conversations string) string {
+	t.Helper()
+	var buf bytes.Buffer
+	archive := zip.NewWriter(&buf)
+	w, err := archive.Create("export/conversations.json")
+	if err != nil {
+		t.Fatal(err)
+	}
+	if _, err := w.Write([]byte(conversations)); err != nil {
+		t.Fatal(err)
+	}
+	if err := archive.Close(); err != nil {
+		t.Fatal(err)
+	}
+	return buf.String()
+}
+
+func TestParseChatExport(t *testing.T) {
+	chatGPT := []ImportedChat{{
+		Source: ChatSourceChatGPT, SourceID: "c1", Title: "Greeting",
+		Created: time.Unix(1700000000, 0), Updated: time.Unix(1700000020, 0),
+		Messages: []ChatMessage{{Role: "user", Content: "hi"}, {Role: "assistant", Content: "hello"}},
+		Times:    []time.Time{time.Unix(1700000010, 0), time.Unix(1700000020, 0)},
+		Model:    "gpt-4o",
+	}}
+	claude := []ImportedChat{
+		{
+			Source: ChatSourceClaude, SourceID: "u1", Title: "Plan",
+			Created: rfc3339("2026-01-02T03:04:05Z"), Updated: rfc3339("2026-01-02T03:05:00Z"),
+			Messages: []ChatMessage{{Role: "user", Content: "first\n\nsecond"}, {Role: "assistant", Content: "answer"}},
+			Times:    []time.Time{rfc3339("2026-01-02T03:04:30Z"), rfc3339("2026-01-02T03:05:00Z")},
+		},
+		{Source: ChatSourceClaude, SourceID: "u2", Title: "Empty", Created: rfc3339("2026-01-03T00:00:00Z"), Updated: rfc3339("2026-01-03T00:00:00Z")},
+	}
+	tests := []struct {
+		name    string
+		data    string
+		want    []ImportedChat
+		wantErr bool
+	}{
+		{name: "chatgpt follows the current branch", data: chatGPTExport, want: chatGPT},
+		{name: "claude joins turns from one side", data: claudeExport, want: claude},
+		{name: "from the zip", data: zipped(t, claudeExport), want: claude},
+		{name: "no conversations", data: "[]"},
+		{name: "not JSON", data: "garbage", wantErr: true},
+		{name: "another service", data: `[{"messages": []}]`, wantErr: true},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got, err := ParseChatExport([]byte(tt.data))
+			if tt.wantErr {
+				if err == nil {
+					t.Errorf("got %+v, want an error", got)
+				}
+				return
+			}
+			if err != nil {
+				t.Fatalf("unexpected error: %v", err)
+			}
+			if !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got  %+v\nwant %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestSessionStoreImportChats(t *testing.T) {
+	var chats []ImportedChat
+	for _, export := range []string{chatGPTExport, claudeExport} {
+		parsed, err := ParseChatExport([]byte(export))
+		if err != nil {
+			t.Fatal(err)
+		}
+		chats = append(chats, parsed...)
+	}
+	store := NewSessionStore(t.TempDir())
+	open, _ := store.Create("mine")
+
+	steps := []struct {
+		wantAdded   int
+		wantSkipped int
+	}{
+		{2, 1}, // the empty chat is skipped
+		{0, 3}, // and then every one, as they're in already
+	}
+	for i, step := range steps {
+		added, skipped, err := store.ImportChats(chats)
+		if err != nil || added != step.wantAdded || skipped != step.wantSkipped {
+			t.Errorf("import %d: added %d and skipped %d, err %v; want %d and %d", i+1, added, skipped, err, step.wantAdded, step.wantSkipped)
+		}
+	}
+	if active, _ := store.Active(); active.ID != open.ID {
+		t.Errorf("importing opened %s, want %s left open", active.ID, open.ID)
+	}
+	titles := make(map[string]bool)
+	for _, summary := range store.List() {
+		titles[summary.Title] = true
+	}
+	if want := map[string]bool{"mine": true, "Greeting": true, "Plan": true}; !reflect.DeepEqual(titles, want) {
+		t.Errorf("sessions %v, want %v", titles, want)
+	}
+}
@@ -0,0 +1,124 @@
+package main
+
+import (
+	"fmt"
+	"reflect"
+	"strings"
+	"testing"
+	"time"
+)
+
+// importEntries are history entries with everything an export keeps
+func importEntries() []SessionEntry {
+	at := time.Date(2026, 3, 1, 9, 30, 0, 0, time.Local)
+	return []SessionEntry{
+		{ID: 1, Time: at.Format(time.RFC3339), Prompt: "first line\nsecond line", Response: "# Heading\n\nSome text.", Provider: "openai", Model: "gpt-4o"},
+		{ID: 2, Time: at.Add(time.Hour).Format(time.RFC3339), Prompt: "show me markdown", Response: "```markdown\n## inside\n```", Provider: "ollama"},
+	}
+}
+
+func TestParseImportRoundTrip(t *testing.T) {
+	exporter := NewHistoryExporter("History", importEntries())
+	tests := []struct {
+		format string
+		want   []ExportEntry
+	}{
+		{ExportJSON, exporter.Entries},
+		// Markdown keeps no ids, and times only to the minute
+		{ExportMarkdown, []ExportEntry{
+			{Time: importEntries()[0].Time, Prompt: "first line\nsecond line", Response: "# Heading\n\nSome text.", Provider: "openai", Model: "gpt-4o"},
+			{Time: importEntries()[1].Time, Prompt: "show me markdown", Response: "```markdown\n## inside\n```", Provider: "ollama"},
+		}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.format, func(t *testing.T) {
+			data, err := exporter.Render(tt.format)
+			if err != nil {
+				t.Fatal(err)
+			}
+			got, err := ParseImport(data, "")
+			if err != nil {
+				t.Fatalf("reading back\n%s\n%v", data, err)
+			}
+			want := make([]ExportEntry, len(tt.want))
+			for i, entry := range tt.want {
+				entry.Blocks = nil
+				want[i] = entry
+			}
+			if !reflect.DeepEqual(got, want) {
+				t.Errorf("read back\n%s\ngot  %+v\nwant %+v", data, got, want)
+			}
+		})
+	}
+}
+
+func TestParseImportErrors(t *testing.T) {
+	tests := []struct {
+		name    string
+		data    string
+		format  string
+		wantErr string
+	}{
+		{"no version", `{"entries": []}`, "", "no version"},
+		{"newer version", `{"version": 2, "entries": []}`, "", "newer than"},
+		{"history without ids", `[{"prompt": "x"}]`, "", "have no ids"},
+		{"broken JSON", `{"version": 1,`, "", "not a JSON export"},
+		{"markdown without exchanges", "just some notes", "", "no exchanges found"},
+		{"other formats", "<html>", ExportHTML, "can't import"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			_, err := ParseImport([]byte(tt.data), tt.format)
+			if err == nil || !strings.Contains(err.Error(), tt.wantErr) {
+				t.Errorf("err = %v, want one mentioning %q", err, tt.wantErr)
+			}
+		})
+	}
+}
+
+func TestSessionHistoryImport(t *testing.T) {
+	existing := []SessionEntry{{ID: 1, Time: "2026-01-01T00:00:00Z", Prompt: "A", Response: "a"}, {ID: 2, Time: "2026-01-02T00:00:00Z", Prompt: "B", Response: "b"}}
+	imported := []ExportEntry{
+		{ID: 2, Time: "2026-01-03T00:00:00Z", Prompt: "C", Response: "c"}, // the id B has
+		{ID: 3, Time: "2026-01-04T00:00:00Z", Prompt: "D", Response: "d"},
+		{Prompt: "A", Response: "a"}, // A again, without an id or its time
+		{Prompt: "E", Response: "e"},
+	}
+	tests := []struct {
+		conflict   string
+		wantResult ImportResult
+		want       []string // newest first
+		wantErr    bool
+	}{
+		{conflict: ImportKeepBoth, wantResult: ImportResult{Added: 3, Duplicates: 1, Conflicts: 1}, want: []string{"5 E", "4 C", "3 D", "2 B", "1 A"}},
+		{conflict: "", wantResult: ImportResult{Added: 3, Duplicates: 1, Conflicts: 1}, want: []string{"5 E", "4 C", "3 D", "2 B", "1 A"}},
+		{conflict: ImportSkip, wantResult: ImportResult{Added: 2, Duplicates: 1, Conflicts: 1}, want: []string{"4 E", "3 D", "2 B", "1 A"}},
+		{conflict: ImportReplace, wantResult: ImportResult{Added: 2, Replaced: 1, Duplicates: 1, Conflicts: 1}, want: []string{"4 E", "3 D", "2 C", "1 A"}},
+		{conflict: "merge", wantErr: true},
+	}
+	for _, tt := range tests {
+		t.Run(tt.conflict, func(t *testing.T) {
+			history := NewSessionHistory(writeHistory(t, existing), HistoryRetention{})
+			result, err := history.Import(imported, tt.conflict)
+			if tt.wantErr {
+				if err == nil {
+					t.Errorf("got %+v, want an error", result)
+				}
+				return
+			}
+			if err != nil {
+				t.Fatal(err)
+			}
+			if result != tt.wantResult {
+				t.Errorf("result %+v, want %+v", result, tt.wantResult)
+			}
+			var got []string
+			for _, entry := range history.Entries() {
+				got = append(got, fmt.Sprintf("%d %s", entry.ID, entry.Prompt))
+			}
+			if !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("history %q, want %q", got, tt.want)
+			}
+		})
+	}
+}
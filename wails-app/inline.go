@@ -0,0 +1,261 @@
+package main
+
+import (
+	"strings"
+	"unicode"
+	"unicode/utf8"
+)
+
+const (
+	SpanText     = "text"
+	SpanBold     = "bold"
+	SpanItalic   = "italic"
+	SpanCode     = "code"
+	SpanLink     = "link"
+	SpanMath     = "math"
+	SpanFootnote = "footnote"
+	SpanStrike   = "strike"
+)
+
+// InlineSpan is a run of formatted text. Text, code and math spans carry Text,
+// as do footnote references for their label; bold, italic, strike and link
+// spans wrap Children so formatting can nest.
+type InlineSpan struct {
+	Type     string       `json:"type"`
+	Text     string       `json:"text,omitempty"`
+	URL      string       `json:"url,omitempty"` // link
+	Children []InlineSpan `json:"children,omitempty"`
+}
+
+const markdownPunctuation = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~"
+
+// parseInline splits markdown text into formatted spans
+func parseInline(text string, opts ParserOptions) []InlineSpan {
+	spans := make([]InlineSpan, 0)
+	var plain strings.Builder
+	flushText := func() {
+		if plain.Len() > 0 {
+			if opts.SmartPunctuation {
+				spans = append(spans, InlineSpan{Type: SpanText, Text: smartPunctuation(plain.String())})
+			} else {
+				spans = append(spans, InlineSpan{Type: SpanText, Text: plain.String()})
+			}
+			plain.Reset()
+		}
+	}
+	for i := 0; i < len(text); {
+		span, n := inlineSpanAt(text, i, opts)
+		if n == 0 && text[i] == '\\' && i+1 < len(text) && strings.IndexByte(markdownPunctuation, text[i+1]) >= 0 {
+			plain.WriteByte(text[i+1])
+			i += 2
+			continue
+		}
+		if n == 0 {
+			plain.WriteByte(text[i])
+			i++
+			continue
+		}
+		if span.Type == SpanText {
+			plain.WriteString(span.Text) // e.g. unmatched backticks
+		} else {
+			flushText()
+			spans = append(spans, span)
+		}
+		i += n
+	}
+	flushText()
+	return spans
+}
+
+// inlineSpanAt matches a formatted span starting at text[i], returning it and
+// the number of bytes it covers, or 0 when text[i] is plain
+func inlineSpanAt(text string, i int, opts ParserOptions) (InlineSpan, int) {
+	rest := text[i:]
+	switch c := text[i]; c {
+	case '`':
+		ticks := len(rest) - len(strings.TrimLeft(rest, "`"))
+		end := strings.Index(rest[ticks:], rest[:ticks])
+		if end < 0 {
+			// No closing run: the backticks are literal
+			return InlineSpan{Type: SpanText, Text: rest[:ticks]}, ticks
+		}
+		code := rest[ticks : ticks+end]
+		if len(code) > 1 && code[0] == ' ' && code[len(code)-1] == ' ' {
+			code = code[1 : len(code)-1]
+		}
+		return InlineSpan{Type: SpanCode, Text: code}, 2*ticks + end
+
+	case '[':
+		if opts.Footnotes && strings.HasPrefix(rest, "[^") {
+			if end := strings.IndexByte(rest, ']'); end > 2 && !strings.ContainsAny(rest[2:end], " \t[") {
+				return InlineSpan{Type: SpanFootnote, Text: rest[2:end]}, end + 1
+			}
+		}
+		if label, url, n, ok := parseLink(rest); ok {
+			return InlineSpan{Type: SpanLink, URL: url, Children: parseInline(label, opts)}, n
+		}
+
+	case '!':
+		// Images inside text become links to the image, labelled by the alt text
+		if strings.HasPrefix(rest, "![") {
+			if alt, url, n, ok := parseLink(rest[1:]); ok {
+				return InlineSpan{Type: SpanLink, URL: url, Children: []InlineSpan{{Type: SpanText, Text: alt}}}, n + 1
+			}
+		}
+
+	case '<':
+		if end := strings.IndexByte(rest, '>'); end > 0 && isAutolink(rest[1:end]) {
+			url := rest[1:end]
+			return InlineSpan{Type: SpanLink, URL: url, Children: []InlineSpan{{Type: SpanText, Text: url}}}, end + 1
+		}
+
+	case '$':
+		if tex, n, ok := parseInlineMath(rest); ok {
+			return InlineSpan{Type: SpanMath, Text: tex}, n
+		}
+
+	case '\\':
+		if strings.HasPrefix(rest, `\(`) {
+			if end := strings.Index(rest[2:], `\)`); end > 0 {
+				return InlineSpan{Type: SpanMath, Text: strings.TrimSpace(rest[2 : 2+end])}, end + 4
+			}
+		}
+
+	case '~':
+		if inner, n, ok := parseEmphasis(rest, "~~"); ok && opts.Strikethrough {
+			return InlineSpan{Type: SpanStrike, Children: parseInline(inner, opts)}, n
+		}
+
+	case '*', '_':
+		previous, _ := utf8.DecodeLastRuneInString(text[:i])
+		if c == '_' && i > 0 && isWordRune(previous) {
+			return InlineSpan{}, 0 // snake_case, not emphasis
+		}
+		if inner, n, ok := parseEmphasis(rest, strings.Repeat(string(c), 2)); ok {
+			return InlineSpan{Type: SpanBold, Children: parseInline(inner, opts)}, n
+		}
+		if inner, n, ok := parseEmphasis(rest, string(c)); ok {
+			return InlineSpan{Type: SpanItalic, Children: parseInline(inner, opts)}, n
+		}
+	}
+	return InlineSpan{}, 0
+}
+
+// smartPunctuation turns straight quotes curly and --, --- and ... into
+// en dashes, em dashes and ellipses
+func smartPunctuation(text string) string {
+	text = strings.NewReplacer("---", "—", "--", "–", "...", "…").Replace(text)
+	var out strings.Builder
+	previous := ' '
+	for _, r := range text {
+		opening := unicode.IsSpace(previous) || strings.ContainsRune("([{—–", previous)
+		switch {
+		case r == '"' && opening:
+			out.WriteRune('“')
+		case r == '"':
+			out.WriteRune('”')
+		case r == '\'' && opening:
+			out.WriteRune('‘')
+		case r == '\'':
+			out.WriteRune('’') // closing quote or apostrophe
+		default:
+			out.WriteRune(r)
+		}
+		previous = r
+	}
+	return out.String()
+}
+
+// parseInlineMath matches $tex$ at the start of s. Like pandoc, the TeX can't
+// start or end with a space and the closing $ can't precede a digit, so
+// prices such as "$5 and $10" stay text.
+func parseInlineMath(s string) (string, int, bool) {
+	if strings.HasPrefix(s, "$$") || len(s) < 3 || s[1] == ' ' {
+		return "", 0, false
+	}
+	end := strings.IndexByte(s[1:], '$') + 1
+	if end <= 1 || s[end-1] == ' ' || s[end-1] == '\\' {
+		return "", 0, false
+	}
+	if end+1 < len(s) && s[end+1] >= '0' && s[end+1] <= '9' {
+		return "", 0, false
+	}
+	return s[1:end], end + 1, true
+}
+
+// parseEmphasis matches delim-wrapped text at the start of s, returning the
+// inner text and the length consumed. The inner text can't start or end with
+// a space, and an underscore closer can't be inside a word.
+func parseEmphasis(s, delim string) (string, int, bool) {
+	if !strings.HasPrefix(s, delim) || len(s) <= len(delim) || s[len(delim)] == ' ' {
+		return "", 0, false
+	}
+	for from := len(delim) + 1; from < len(s); {
+		end := strings.Index(s[from:], delim)
+		if end < 0 {
+			return "", 0, false
+		}
+		end += from
+		after := end + len(delim)
+		// A single delimiter must not be half of a double one
+		doubled := len(delim) == 1 && (after < len(s) && s[after] == delim[0] || s[end-1] == delim[0])
+		wordAfter := delim[0] == '_' && after < len(s) && isWordRune(rune(s[after]))
+		if s[end-1] != ' ' && !doubled && !wordAfter {
+			return s[len(delim):end], after, true
+		}
+		from = end + 1
+	}
+	return "", 0, false
+}
+
+// parseLink matches [label](url) or [label](url "title") at the start of s
+func parseLink(s string) (label, url string, n int, ok bool) {
+	depth := 0
+	for i := 0; i < len(s); i++ {
+		switch s[i] {
+		case '\\':
+			i++
+		case '[':
+			depth++
+		case ']':
+			depth--
+			if depth > 0 {
+				continue
+			}
+			if i+1 >= len(s) || s[i+1] != '(' {
+				return "", "", 0, false
+			}
+			end := strings.IndexByte(s[i+2:], ')')
+			if end < 0 {
+				return "", "", 0, false
+			}
+			target := strings.TrimSpace(s[i+2 : i+2+end])
+			if space := strings.IndexAny(target, " \t"); space >= 0 {
+				target = target[:space] // drop the optional title
+			}
+			return s[1:i], strings.Trim(target, "<>"), i + 3 + end, true
+		}
+	}
+	return "", "", 0, false
+}
+
+func isAutolink(s string) bool {
+	return !strings.ContainsAny(s, " <") && (strings.HasPrefix(s, "http://") || strings.HasPrefix(s, "https://") || strings.HasPrefix(s, "mailto:"))
+}
+
+func isWordRune(r rune) bool {
+	return unicode.IsLetter(r) || unicode.IsDigit(r)
+}
+
+// plainText is the text of spans with the formatting removed
+func plainText(spans []InlineSpan) string {
+	var text strings.Builder
+	for _, span := range spans {
+		if len(span.Children) > 0 {
+			text.WriteString(plainText(span.Children))
+		} else {
+			text.WriteString(span.Text)
+		}
+	}
+	return text.String()
+}
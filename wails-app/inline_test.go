@@ -0,0 +1,82 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+func TestParseInline(t *testing.T) {
+	tests := []struct {
+		name string
+		text string
+		want []InlineSpan
+	}{
+		{
+			name: "bold and italic",
+			text: "**bold** and *italic*",
+			want: []InlineSpan{
+				{Type: SpanBold, Children: []InlineSpan{{Type: SpanText, Text: "bold"}}},
+				{Type: SpanText, Text: " and "},
+				{Type: SpanItalic, Children: []InlineSpan{{Type: SpanText, Text: "italic"}}},
+			},
+		},
+		{
+			name: "nested",
+			text: "**bold with *italic* inside**",
+			want: []InlineSpan{{Type: SpanBold, Children: []InlineSpan{
+				{Type: SpanText, Text: "bold with "},
+				{Type: SpanItalic, Children: []InlineSpan{{Type: SpanText, Text: "italic"}}},
+				{Type: SpanText, Text: " inside"},
+			}}},
+		},
+		{
+			name: "code keeps its markup",
+			text: "`a*b*` code",
+			want: []InlineSpan{{Type: SpanCode, Text: "a*b*"}, {Type: SpanText, Text: " code"}},
+		},
+		{
+			name: "link with a title",
+			text: "[the docs](https://example.com \"Docs\")",
+			want: []InlineSpan{{Type: SpanLink, URL: "https://example.com", Children: []InlineSpan{{Type: SpanText, Text: "the docs"}}}},
+		},
+		{
+			name: "autolink",
+			text: "<https://example.com>",
+			want: []InlineSpan{{Type: SpanLink, URL: "https://example.com", Children: []InlineSpan{{Type: SpanText, Text: "https://example.com"}}}},
+		},
+		{"snake case", "snake_case_name", []InlineSpan{{Type: SpanText, Text: "snake_case_name"}}},
+		{"escaped", `\*not italic\*`, []InlineSpan{{Type: SpanText, Text: "*not italic*"}}},
+		{"unmatched backtick", "a `b", []InlineSpan{{Type: SpanText, Text: "a `b"}}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := parseInline(tt.text, DefaultParserOptions()); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %+v, want %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseInlineMath(t *testing.T) {
+	tests := []struct {
+		name string
+		text string
+		want []InlineSpan
+	}{
+		{
+			name: "dollars",
+			text: "where $x^2$ holds",
+			want: []InlineSpan{{Type: SpanText, Text: "where "}, {Type: SpanMath, Text: "x^2"}, {Type: SpanText, Text: " holds"}},
+		},
+		{"prices stay text", "costs $5 and $10", []InlineSpan{{Type: SpanText, Text: "costs $5 and $10"}}},
+		{"no space inside", "$ x$", []InlineSpan{{Type: SpanText, Text: "$ x$"}}},
+		{"parentheses", `\(a + b\)`, []InlineSpan{{Type: SpanMath, Text: "a + b"}}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := parseInline(tt.text, DefaultParserOptions()); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %+v, want %+v", got, tt.want)
+			}
+		})
+	}
+}
@@ -0,0 +1,119 @@
+package main
+
+import (
+	"path/filepath"
+	"regexp"
+	"strings"
+)
+
+// languageAliases maps the names models put on fences to one canonical name
+var languageAliases = map[string]string{
+	"js":         "javascript",
+	"jsx":        "javascript",
+	"mjs":        "javascript",
+	"node":       "javascript",
+	"ts":         "typescript",
+	"tsx":        "typescript",
+	"py":         "python",
+	"py3":        "python",
+	"python3":    "python",
+	"rs":         "rust",
+	"golang":     "go",
+	"rb":         "ruby",
+	"sh":         "bash",
+	"shell":      "bash",
+	"zsh":        "bash",
+	"console":    "bash",
+	"ps1":        "powershell",
+	"pwsh":       "powershell",
+	"yml":        "yaml",
+	"md":         "markdown",
+	"c++":        "cpp",
+	"cc":         "cpp",
+	"hpp":        "cpp",
+	"h":          "c",
+	"cs":         "csharp",
+	"c#":         "csharp",
+	"kt":         "kotlin",
+	"kts":        "kotlin",
+	"htm":        "html",
+	"xhtml":      "html",
+	"dockerfile": "docker",
+	"tf":         "hcl",
+	"patch":      "diff",
+	"udiff":      "diff",
+	"plaintext":  "text",
+	"txt":        "text",
+	"jsonc":      "json",
+	"postgresql": "sql",
+	"postgres":   "sql",
+	"mysql":      "sql",
+	"objc":       "objective-c",
+	"graphviz":   "dot",
+	"gv":         "dot",
+	"mmd":        "mermaid",
+	"puml":       "plantuml",
+}
+
+// normalizeLanguage lowercases a fence language and resolves aliases, so
+// "JS", "js" and "javascript" are all "javascript"
+func normalizeLanguage(language string) string {
+	language = strings.ToLower(strings.TrimSpace(language))
+	if canonical, ok := languageAliases[language]; ok {
+		return canonical
+	}
+	return language
+}
+
+// diagramKind returns the diagram kind for a diagram language, or ""
+func diagramKind(language string) string {
+	switch language {
+	case "mermaid", "plantuml":
+		return language
+	case "dot":
+		return "graphviz"
+	}
+	return ""
+}
+
+// diffSignature matches file headers or a hunk header anywhere in the code
+var diffSignature = regexp.MustCompile(`(?m)^(?:--- \S.*\n\+\+\+ \S|@@ -\d+(?:,\d+)? \+\d+(?:,\d+)? @@)`)
+
+// languageSignatures are checked in order; the first that matches wins
+var languageSignatures = []struct {
+	language string
+	pattern  *regexp.Regexp
+}{
+	{"json", regexp.MustCompile(`^\s*[\[{]\s*(?:"|\]|}|$)`)},
+	{"html", regexp.MustCompile(`(?i)^\s*(?:<!doctype html|<html\b|<(?:div|span|body|head)\b)`)},
+	{"rust", regexp.MustCompile(`(?m)^\s*(?:pub\s+)?(?:fn|impl|struct|enum|mod|use)\s+\w.*(?:\{|;)\s*$|\blet\s+mut\b|println!\(`)},
+	{"go", regexp.MustCompile(`(?m)^package\s+\w+\s*$|^func\s+(?:\(\w+\s+\*?\w+\)\s+)?\w+\(|:=`)},
+	{"python", regexp.MustCompile(`(?m)^\s*(?:def\s+\w+\(.*\)\s*(?:->.*)?:|class\s+\w+.*:|from\s+[\w.]+\s+import\s|import\s+\w+\s*$|if\s+__name__\s*==)`)},
+	{"typescript", regexp.MustCompile(`(?m)^\s*(?:interface\s+\w+\s*\{|type\s+\w+\s*=|(?:export\s+)?(?:const|let|function)\s+\w+.*:\s*\w+(?:\[\])?\s*[=),{])`)},
+	{"javascript", regexp.MustCompile(`(?m)^\s*(?:(?:const|let|var)\s+\w+\s*=|function\s+\w+\s*\(|import\s+.*\s+from\s+['"]|module\.exports|console\.log\()`)},
+	{"java", regexp.MustCompile(`(?m)^\s*(?:public|private|protected)\s+(?:static\s+)?(?:class|void|[\w<>]+\s+\w+\()`)},
+	{"cpp", regexp.MustCompile(`(?m)^#include\s*<(?:iostream|vector|string|memory)>|\bstd::`)},
+	{"c", regexp.MustCompile(`(?m)^#include\s*[<"]`)},
+	{"sql", regexp.MustCompile(`(?im)^\s*(?:select\s+.+\s+from|insert\s+into|create\s+table|update\s+\w+\s+set)\b`)},
+	{"bash", regexp.MustCompile(`(?m)^#!/(?:usr/)?bin/(?:env\s+)?(?:ba|z)?sh|^\s*\$\s+\w|^\s*(?:sudo|apt|brew|npm|cargo|go|pip|git|cd|export)\s+\S`)},
+	{"yaml", regexp.MustCompile(`(?m)^[\w-]+:(?:\s+\S.*)?\n(?:\s+[\w-]+:|\s*- )`)},
+}
+
+// detectLanguage guesses the language of an unlabelled code block from the
+// file it belongs in or, failing that, from telltale syntax. It returns ""
+// rather than guess when nothing stands out. A diff is a diff whatever file
+// it patches.
+func detectLanguage(code, path string) string {
+	if diffSignature.MatchString(code) {
+		return "diff"
+	}
+	if ext := strings.TrimPrefix(filepath.Ext(path), "."); ext != "" {
+		return normalizeLanguage(ext)
+	}
+	for _, signature := range languageSignatures {
+		if signature.pattern.MatchString(code) {
+			return signature.language
+		}
+	}
+	return ""
+}
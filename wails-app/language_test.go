@@ -0,0 +1,49 @@
+package main
+
+import "testing"
+
+func TestNormalizeLanguage(t *testing.T) {
+	tests := []struct {
+		language string
+		want     string
+	}{
+		{"JS", "javascript"},
+		{" Py ", "python"},
+		{"golang", "go"},
+		{"c++", "cpp"},
+		{"unknown", "unknown"},
+		{"", ""},
+	}
+	for _, tt := range tests {
+		if got := normalizeLanguage(tt.language); got != tt.want {
+			t.Errorf("normalizeLanguage(%q) = %q, want %q", tt.language, got, tt.want)
+		}
+	}
+}
+
+func TestDetectLanguage(t *testing.T) {
+	tests := []struct {
+		name string
+		code string
+		path string
+		want string
+	}{
+		{"go package", "package main\n\nfunc main() {}", "", "go"},
+		{"python def", "def f(x):\n    return x", "", "python"},
+		{"json", `{"a": 1}`, "", "json"},
+		{"c include", "#include <stdio.h>", "", "c"},
+		{"cpp include", "#include <vector>", "", "cpp"},
+		{"sql", "SELECT id FROM users", "", "sql"},
+		{"from the path", "puts 'hi'", "script.rb", "ruby"},
+		{"path alias", "echo hi", "run.sh", "bash"},
+		{"nothing stands out", "just some words", "", ""},
+		{"a diff whatever the path", "--- a/x.go\n+++ b/x.go\n", "x.go", "diff"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := detectLanguage(tt.code, tt.path); got != tt.want {
+				t.Errorf("got %q, want %q", got, tt.want)
+			}
+		})
+	}
+}
@@ -1,207 +1,346 @@
 package main
 
 import (
-	"bytes"
 	"context"
 	"embed"
-	"encoding/json"
 	"fmt"
-	"io"
-	"net/http"
-	"sync"
+	"net/url"
+	"os"
+	"path/filepath"
+	"strings"
 
 	"github.com/wailsapp/wails/v2"
 	"github.com/wailsapp/wails/v2/pkg/logger"
 	"github.com/wailsapp/wails/v2/pkg/options"
 	"github.com/wailsapp/wails/v2/pkg/options/assetserver"
+	wailsruntime "github.com/wailsapp/wails/v2/pkg/runtime"
 )
 
 //go:embed frontend/dist
 var assets embed.FS
 
-type ProviderConfig struct {
-	Type     string `json:"type"`
-	Name     string `json:"name"`
-	APIKey   string `json:"apiKey"`
-	Endpoint string `json:"endpoint"`
-	Model    string `json:"model"`
-}
+// Events emitted to the frontend
+const (
+	responseChunkEvent  = "response:chunk"   // each streamed content delta
+	responseBlocksEvent = "response:blocks"  // StreamedBlocks parsed from the deltas so far
+	providerHealthEvent = "providers:health" // provider readiness after a probe
+	providerStatusEvent = "providers:status" // in-flight request status, e.g. rate-limit queueing
+	pluginsChangedEvent = "plugins:changed"  // a plugin file was added, changed or removed
+	configReloadedEvent = "config:reloaded"  // a config file was edited outside the app
+)
 
-type Provider interface {
-	SendRequest(prompt string, temperature float64, maxTokens int) (string, error)
-	GetName() string
+// appDataPath returns a path under the app's directory in the user config dir,
+// falling back to the temp dir where there is no config dir
+func appDataPath(elem ...string) string {
+	base := filepath.Join(os.TempDir(), "vibe-coder")
+	if configDir, err := os.UserConfigDir(); err == nil {
+		base = filepath.Join(configDir, "vibe-coder")
+	}
+	return filepath.Join(append([]string{base}, elem...)...)
 }
 
-type OllamaProvider struct {
-	config ProviderConfig
-	client *http.Client
+type App struct {
+	ctx      context.Context
+	manager  *ProviderManager
+	crash    *CrashReporter
+	logs     *LogBuffer
+	market   *Marketplace
+	config   *ConfigStore
+	history  *SessionHistory
+	sessions *SessionStore
 }
 
-func NewOllamaProvider(config ProviderConfig) *OllamaProvider {
-	return &OllamaProvider{
-		config: config,
-		client: &http.Client{},
+func NewApp() *App {
+	config := NewConfigStore(DefaultConfigSources())
+	app := &App{
+		manager:  NewProviderManager(),
+		logs:     NewLogBuffer(200),
+		market:   NewMarketplace(appDataPath("marketplace.json"), appDataPath("plugins"), appDataPath("scripts")),
+		config:   config,
+		history:  NewSessionHistory(appDataPath("history.json"), config.Get().General.historyRetention()),
+		sessions: NewSessionStore(appDataPath("sessions")),
 	}
+	app.applyConfig(app.config.Get())
+	app.crash = NewCrashReporter(app)
+	crashes = app.crash
+	return app
 }
 
-func (p *OllamaProvider) GetName() string {
-	if p.config.Name != "" {
-		return p.config.Name
+func (a *App) startup(ctx context.Context) {
+	a.ctx = ctx
+	a.manager.SetStatusHandler(func(status ManagerStatus) {
+		wailsruntime.EventsEmit(ctx, providerStatusEvent, status)
+	})
+	goSafe(func() {
+		a.manager.CheckHealth()
+		a.emitHealth()
+	})
+	plugins := a.manager.Plugins()
+	for _, err := range append(plugins.LoadDir(appDataPath("plugins")), plugins.LoadScripts(appDataPath("scripts"))...) {
+		a.logs.Warning(err.Error())
 	}
-	return "Ollama"
+	changed := func() { wailsruntime.EventsEmit(ctx, pluginsChangedEvent) }
+	report := func(err error) { a.logs.Warning(err.Error()) }
+	goSafe(func() { plugins.Watch(ctx, appDataPath("plugins"), isPluginFile, changed, report) })
+	goSafe(func() { plugins.Watch(ctx, appDataPath("scripts"), isScriptFile, changed, report) })
+	goSafe(func() { a.config.Watch(ctx, a.configReloaded, report) })
+	goSafe(func() { a.sessions.Autosave(ctx, sessionAutosaveInterval) })
+	a.StartSession()
 }
 
-func (p *OllamaProvider) SendRequest(prompt string, temperature float64, maxTokens int) (string, error) {
-	url := fmt.Sprintf("%s/api/generate", p.config.Endpoint)
-
-	payload := map[string]interface{}{
-		"model":  p.config.Model,
-		"prompt": prompt,
-		"stream": false,
-		"options": map[string]interface{}{
-			"temperature": temperature,
-			"num_predict": maxTokens,
-		},
+// emitHealth pushes provider readiness to the frontend
+func (a *App) emitHealth() {
+	if a.ctx != nil {
+		wailsruntime.EventsEmit(a.ctx, providerHealthEvent, a.manager.Health())
 	}
+}
 
-	jsonData, err := json.Marshal(payload)
+// AddProvider validates and adds a new AI provider, then probes its readiness in the background
+func (a *App) AddProvider(config ProviderConfig) error {
+	index, err := a.manager.Configure(config)
 	if err != nil {
-		return "", err
+		return err
 	}
+	a.saveConfig()
+	goSafe(func() {
+		a.manager.CheckHealthAt(index)
+		a.emitHealth()
+	})
+	return nil
+}
 
-	resp, err := p.client.Post(url, "application/json", bytes.NewBuffer(jsonData))
-	if err != nil {
-		return "", fmt.Errorf("network error: %v", err)
-	}
-	defer resp.Body.Close()
+// ListProviders returns names of all configured providers
+func (a *App) ListProviders() []string {
+	return a.manager.Names()
+}
 
-	if resp.StatusCode != http.StatusOK {
-		body, _ := io.ReadAll(resp.Body)
-		return "", fmt.Errorf("HTTP %d: %s", resp.StatusCode, string(body))
+// ListModels returns the models available for a provider configuration
+func (a *App) ListModels(config ProviderConfig) ([]string, error) {
+	config, err := config.ResolveSecrets(a.manager.keyring)
+	if err != nil {
+		return nil, err
 	}
-
-	var result map[string]interface{}
-	if err := json.NewDecoder(resp.Body).Decode(&result); err != nil {
-		return "", fmt.Errorf("invalid response: %v", err)
+	provider := newProvider(config)
+	if a.manager.Offline() && !isLocalProvider(config) {
+		return nil, &OfflineError{Provider: provider.GetName()}
 	}
-
-	response, ok := result["response"].(string)
+	lister, ok := provider.(ModelLister)
 	if !ok {
-		return "", fmt.Errorf("missing 'response' field")
+		return nil, fmt.Errorf("provider type %q does not support model listing", config.Type)
 	}
+	return lister.ListModels()
+}
 
-	return response, nil
+// GetProviderCapabilities returns what each provider supports, in ListProviders order
+func (a *App) GetProviderCapabilities() []Capabilities {
+	return a.manager.Capabilities()
 }
 
-type MockProvider struct {
-	config ProviderConfig
+// CapabilitiesFor returns what a provider built from config would support
+func (a *App) CapabilitiesFor(config ProviderConfig) Capabilities {
+	return CapabilitiesOf(newProvider(config))
 }
 
-func NewMockProvider(config ProviderConfig) *MockProvider {
-	return &MockProvider{config: config}
+// GetProviderHealth returns the last known readiness of every provider, in ListProviders order
+func (a *App) GetProviderHealth() []ProviderHealth {
+	return a.manager.Health()
 }
 
-func (p *MockProvider) GetName() string {
-	if p.config.Name != "" {
-		return p.config.Name
-	}
-	return "Mock"
+// CheckProviders re-probes every provider and returns their readiness
+func (a *App) CheckProviders() []ProviderHealth {
+	return a.manager.CheckHealth()
 }
 
-func (p *MockProvider) SendRequest(prompt string, temperature float64, maxTokens int) (string, error) {
-	return fmt.Sprintf("# Mock AI Response\n\nYou asked: %s\n\n## Code Example\n\n```go\nfunc hello() {\n    fmt.Println(\"Hello from Vibe Coder!\")\n}\n```\n\n## Explanation\n\nThis is a mock response demonstrating the parsing capabilities.", prompt), nil
+// SetActiveProvider sets the active provider by index
+func (a *App) SetActiveProvider(index int) error {
+	if err := a.manager.SetActive(index); err != nil {
+		return err
+	}
+	a.saveConfig()
+	return nil
 }
 
-type App struct {
-	providers      []Provider
-	activeProvider int
-	providersMutex sync.RWMutex
+// GetActiveProvider returns the index of the active provider, or -1 if none is configured
+func (a *App) GetActiveProvider() int {
+	return a.manager.ActiveIndex()
 }
 
-func NewApp() *App {
-	return &App{
-		providers:      make([]Provider, 0),
-		activeProvider: -1,
+// SendPrompt sends a prompt to the active AI provider, continuing the conversation in history.
+// Transient failures are retried and, if needed, answered by another provider.
+func (a *App) SendPrompt(prompt string, history []ChatMessage, opts RequestOptions) (AIResponse, error) {
+	defer a.crash.Capture()
+
+	session := a.sessions.Begin(prompt)
+	response, err := a.manager.Send(opts.apply(NewAIRequest(prompt, history)))
+	a.answered(session, history, prompt, response, err)
+	if err == nil {
+		a.received(prompt, response)
 	}
+	return response, err
 }
 
-func (a *App) startup(ctx context.Context) {}
+// SendPromptStream is SendPrompt with "response:chunk" events emitted as content arrives.
+// Providers without streaming support emit a single chunk.
+func (a *App) SendPromptStream(prompt string, history []ChatMessage, opts RequestOptions) (AIResponse, error) {
+	defer a.crash.Capture()
+
+	session := a.sessions.Begin(prompt)
+	parser := NewStreamingParser(DefaultParserOptions())
+	response, err := a.manager.SendStream(opts.apply(NewAIRequest(prompt, history)), func(delta string) {
+		a.sessions.Stream(session, delta)
+		if a.ctx != nil {
+			wailsruntime.EventsEmit(a.ctx, responseChunkEvent, delta)
+			wailsruntime.EventsEmit(a.ctx, responseBlocksEvent, StreamedBlocks{Completed: parser.Write(delta), Partial: parser.Partial()})
+		}
+	})
+	a.answered(session, history, prompt, response, err)
+	if err == nil {
+		a.received(prompt, response)
+	}
+	return response, err
+}
 
-// AddProvider adds a new AI provider
-func (a *App) AddProvider(config ProviderConfig) error {
-	a.providersMutex.Lock()
-	defer a.providersMutex.Unlock()
+// ComparePrompt sends the prompt to every provider at once so their answers can be compared
+func (a *App) ComparePrompt(prompt string, history []ChatMessage, opts RequestOptions) []ProviderResult {
+	defer a.crash.Capture()
 
-	var provider Provider
-	switch config.Type {
-	case "Ollama":
-		provider = NewOllamaProvider(config)
-	case "Mock":
-		provider = NewMockProvider(config)
-	default:
-		// For now, unsupported providers default to Mock
-		provider = NewMockProvider(config)
+	results := a.manager.SendToAll(opts.apply(NewAIRequest(prompt, history)))
+	prompts := make([]string, len(results))
+	for i := range prompts {
+		prompts[i] = prompt
 	}
+	return a.receivedAll(prompts, results)
+}
 
-	a.providers = append(a.providers, provider)
+// SendBatch sends each prompt as its own request with the same options, running at
+// most concurrency at once (0 uses the default), and returns the results in order
+func (a *App) SendBatch(prompts []string, opts RequestOptions, concurrency int) []ProviderResult {
+	defer a.crash.Capture()
 
-	// Set as active if it's the first provider
-	if a.activeProvider == -1 {
-		a.activeProvider = 0
+	requests := make([]AIRequest, len(prompts))
+	for i, prompt := range prompts {
+		requests[i] = opts.apply(NewAIRequest(prompt, nil))
 	}
+	return a.receivedAll(prompts, a.manager.SendBatch(requests, concurrency))
+}
 
+// SetRedactionMode sets how secrets in prompts are handled: "mask", "block" or "off"
+func (a *App) SetRedactionMode(mode string) error {
+	if err := a.manager.SetRedactionMode(mode); err != nil {
+		return err
+	}
+	a.saveConfig()
 	return nil
 }
 
-// ListProviders returns names of all configured providers
-func (a *App) ListProviders() []string {
-	a.providersMutex.RLock()
-	defer a.providersMutex.RUnlock()
+// GetRedactionMode returns the current secret redaction mode
+func (a *App) GetRedactionMode() string {
+	return a.manager.RedactionMode()
+}
 
-	names := make([]string, len(a.providers))
-	for i, p := range a.providers {
-		names[i] = p.GetName()
-	}
-	return names
+// GetUsageReport returns token usage per model per day for the last days days (0 for all time)
+func (a *App) GetUsageReport(days int) UsageReport {
+	return a.manager.Usage(days)
 }
 
-// SetActiveProvider sets the active provider by index
-func (a *App) SetActiveProvider(index int) error {
-	a.providersMutex.Lock()
-	defer a.providersMutex.Unlock()
+// ClearUsageHistory deletes the recorded usage history
+func (a *App) ClearUsageHistory() error {
+	return a.manager.ClearUsage()
+}
 
-	if index < 0 || index >= len(a.providers) {
-		return fmt.Errorf("invalid provider index")
+// SetRequestLogMode sets what the request log records: "off", "metadata" or "full"
+func (a *App) SetRequestLogMode(mode string) error {
+	if err := a.manager.RequestLog().SetMode(mode); err != nil {
+		return err
 	}
-
-	a.activeProvider = index
+	a.saveConfig()
 	return nil
 }
 
-// SendPrompt sends a prompt to the active AI provider
-func (a *App) SendPrompt(prompt string) (string, error) {
-	a.providersMutex.RLock()
-	defer a.providersMutex.RUnlock()
+// GetRequestLogMode returns the current request log mode
+func (a *App) GetRequestLogMode() string {
+	return a.manager.RequestLog().Mode()
+}
+
+// OpenRequestLog reveals the request log file in the system's default viewer
+func (a *App) OpenRequestLog() {
+	wailsruntime.BrowserOpenURL(a.ctx, fileURL(a.manager.RequestLog().Path()))
+}
 
-	if a.activeProvider == -1 || len(a.providers) == 0 {
-		// No provider configured, return mock response
-		mock := NewMockProvider(ProviderConfig{Name: "Mock"})
-		return mock.SendRequest(prompt, 0.7, 2000)
+// fileURL is the file:// URL of an absolute path, escaped, with a Windows
+// drive letter after the leading slash
+func fileURL(path string) string {
+	path = filepath.ToSlash(path)
+	if !strings.HasPrefix(path, "/") {
+		path = "/" + path
 	}
+	return (&url.URL{Scheme: "file", Path: path}).String()
+}
+
+// ClearRequestLog deletes the request log file
+func (a *App) ClearRequestLog() error {
+	return a.manager.RequestLog().Clear()
+}
+
+// SetOfflineMode restricts every request to local providers, refreshing provider health
+func (a *App) SetOfflineMode(offline bool) {
+	a.manager.SetOffline(offline)
+	a.saveConfig()
+	goSafe(func() {
+		a.manager.CheckHealth()
+		a.emitHealth()
+	})
+}
+
+// GetOfflineMode reports whether only local providers may be used
+func (a *App) GetOfflineMode() bool {
+	return a.manager.Offline()
+}
+
+// SetContextCompression turns summarization of history that overflows the context window on or off
+func (a *App) SetContextCompression(enabled bool) {
+	a.manager.SetContextCompression(enabled)
+	a.saveConfig()
+}
+
+// GetContextCompression reports whether overflowing history is summarized
+func (a *App) GetContextCompression() bool {
+	return a.manager.ContextCompression()
+}
+
+// ClearResponseCache drops every cached response
+func (a *App) ClearResponseCache() {
+	a.manager.ClearCache()
+}
+
+// GetCostSummary returns token usage and spend for this session, overall and per provider
+func (a *App) GetCostSummary() CostSummary {
+	return a.manager.Costs()
+}
+
+// Embed returns an embedding per text from the active provider
+func (a *App) Embed(texts []string) ([][]float32, error) {
+	return a.manager.Embed(texts)
+}
 
-	provider := a.providers[a.activeProvider]
-	return provider.SendRequest(prompt, 0.7, 2000)
+// CountTokens estimates how a prompt and its history fit the active model's context window
+func (a *App) CountTokens(prompt string, history []ChatMessage) TokenBudget {
+	_, config := a.manager.Active()
+	return budgetFor(NewAIRequest(prompt, history).withSystemPrompt(config), config.Model)
 }
 
 func main() {
 	app := NewApp()
+	defer app.crash.Capture()
 
 	err := wails.Run(&options.App{
 		Title:            "Vibe Coder (Wails)",
 		Width:            1200,
 		Height:           800,
+		Logger:           app.logs,
 		LogLevel:         logger.INFO,
 		OnStartup:        app.startup,
+		OnShutdown:       app.shutdown,
 		Bind:             []interface{}{app},
 		AssetServer:      &assetserver.Options{Assets: assets},
 		BackgroundColour: &options.RGBA{R: 30, G: 30, B: 30, A: 255},
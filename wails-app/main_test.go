@@ -0,0 +1,22 @@
+package main
+
+import "testing"
+
+func TestFileURL(t *testing.T) {
+	tests := []struct {
+		name string
+		path string
+		want string
+	}{
+		{"plain", "/home/me/.config/vibe-coder/requests.jsonl", "file:///home/me/.config/vibe-coder/requests.jsonl"},
+		{"escaped", "/tmp/my logs/100% #1.jsonl", "file:///tmp/my%20logs/100%25%20%231.jsonl"},
+		{"drive letter", "C:/Users/me/AppData/Roaming/vibe-coder/requests.jsonl", "file:///C:/Users/me/AppData/Roaming/vibe-coder/requests.jsonl"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := fileURL(tt.path); got != tt.want {
+				t.Errorf("got %q, want %q", got, tt.want)
+			}
+		})
+	}
+}
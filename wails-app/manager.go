@@ -0,0 +1,700 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"sync"
+	"time"
+)
+
+// RetryPolicy controls how the manager handles transient provider failures
+type RetryPolicy struct {
+	MaxAttempts    int           `json:"maxAttempts"`    // attempts per provider, including the first
+	InitialBackoff time.Duration `json:"initialBackoff"` // delay before the first retry, doubled each time
+	MaxBackoff     time.Duration `json:"maxBackoff"`
+	Failover       bool          `json:"failover"` // try the remaining providers once the active one gives up
+
+	// Rate-limited (HTTP 429) attempts don't count against MaxAttempts; each
+	// provider gets this many, waiting as long as Retry-After asks up to MaxRetryAfter
+	RateLimitRetries int           `json:"rateLimitRetries"`
+	MaxRetryAfter    time.Duration `json:"maxRetryAfter"`
+}
+
+func DefaultRetryPolicy() RetryPolicy {
+	return RetryPolicy{
+		MaxAttempts:      3,
+		InitialBackoff:   500 * time.Millisecond,
+		MaxBackoff:       8 * time.Second,
+		Failover:         true,
+		RateLimitRetries: 5,
+		MaxRetryAfter:    60 * time.Second,
+	}
+}
+
+// isRetryable reports whether err is a transient failure worth retrying
+func isRetryable(err error) bool {
+	var netErr *NetworkError
+	var rateErr *RateLimitError
+	return errors.As(err, &netErr) || errors.As(err, &rateErr)
+}
+
+// ProviderManager owns the configured providers and the active selection.
+// It is safe for concurrent use; requests run outside the lock so switching
+// providers never waits on an in-flight request.
+type ProviderManager struct {
+	mu        sync.RWMutex
+	providers []Provider
+	configs   []ProviderConfig // with keyring: API keys resolved
+	stored    []ProviderConfig // as configured, for saving
+	health    []ProviderHealth
+	limiters  []*RateLimiter
+	active    int
+	replaced  int // bumped by SetProviders, so probes of the old set are dropped
+	retry     RetryPolicy
+	costs     *CostTracker
+	cache     *ResponseCache
+	redactor  *Redactor
+	usage     *UsageStats
+	log       *RequestLog
+	compress  *ContextCompressor
+	plugins   *PluginRegistry
+	keyring   Keyring
+	session   []ChatMessage // the latest conversation, for plugins
+	offline   bool
+	onStatus  func(ManagerStatus)
+}
+
+// ManagerStatus reports a transient condition of an in-flight request, such as
+// waiting on a rate limit, so the UI can explain a delay
+type ManagerStatus struct {
+	Provider    string  `json:"provider"`
+	Kind        string  `json:"kind"` // "queued", "rate_limited" or "summarizing"
+	Message     string  `json:"message"`
+	WaitSeconds float64 `json:"waitSeconds"`
+	Queued      int     `json:"queued"`
+}
+
+func NewProviderManager() *ProviderManager {
+	m := &ProviderManager{
+		providers: make([]Provider, 0),
+		configs:   make([]ProviderConfig, 0),
+		stored:    make([]ProviderConfig, 0),
+		health:    make([]ProviderHealth, 0),
+		limiters:  make([]*RateLimiter, 0),
+		active:    -1,
+		retry:     DefaultRetryPolicy(),
+		costs:     NewCostTracker(),
+		cache:     NewResponseCache(DefaultCacheConfig()),
+		redactor:  NewRedactor(),
+		usage:     NewUsageStats(appDataPath("usage.json")),
+		log:       NewRequestLog(appDataPath("logs", "requests.jsonl")),
+		compress:  NewContextCompressor(),
+		plugins:   NewPluginRegistry(appDataPath("plugins.json")),
+		keyring:   systemKeyring(),
+	}
+	m.plugins.SetBridge(m)
+	m.plugins.RegisterBuiltins()
+	return m
+}
+
+// Configure validates config and registers the provider, returning its index.
+// A model the provider doesn't list is rejected with a ConfigError carrying suggestions.
+func (m *ProviderManager) Configure(config ProviderConfig) (int, error) {
+	resolved, err := config.ResolveSecrets(m.keyring)
+	if err != nil {
+		return -1, err
+	}
+	provider := newProvider(resolved)
+	// Listing models is a network call, so offline the model is taken on trust
+	if !m.Offline() || isLocalProvider(config) {
+		if err := ValidateModel(provider, config.Model); err != nil {
+			return -1, err
+		}
+	}
+	return m.add(provider, config, resolved, nil), nil
+}
+
+// Add registers a provider built from config and returns its index. One
+// whose API key can't be read from the keyring is added without it and
+// shows as unhealthy.
+func (m *ProviderManager) Add(config ProviderConfig) int {
+	resolved, err := config.ResolveSecrets(m.keyring)
+	return m.add(newProvider(resolved), config, resolved, err)
+}
+
+func (m *ProviderManager) add(provider Provider, stored, config ProviderConfig, secretErr error) int {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	m.providers = append(m.providers, provider)
+	m.configs = append(m.configs, config)
+	m.stored = append(m.stored, stored)
+	m.health = append(m.health, initialHealth(provider, secretErr))
+	m.limiters = append(m.limiters, NewRateLimiter(config))
+
+	// Set as active if it's the first provider
+	if m.active == -1 {
+		m.active = 0
+	}
+
+	return len(m.providers) - 1
+}
+
+// SetProviders replaces every provider with ones built from configs, as Add
+// would, keeping the active index where it is still valid
+func (m *ProviderManager) SetProviders(configs []ProviderConfig) {
+	resolved := make([]ProviderConfig, len(configs))
+	secretErrs := make([]error, len(configs))
+	for i, config := range configs {
+		// Keyrings may prompt, so they are asked outside the lock
+		resolved[i], secretErrs[i] = config.ResolveSecrets(m.keyring)
+	}
+
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	m.providers = make([]Provider, len(configs))
+	m.configs = resolved
+	m.stored = append([]ProviderConfig{}, configs...)
+	m.health = make([]ProviderHealth, len(configs))
+	m.limiters = make([]*RateLimiter, len(configs))
+	for i, config := range resolved {
+		m.providers[i] = newProvider(config)
+		m.health[i] = initialHealth(m.providers[i], secretErrs[i])
+		m.limiters[i] = NewRateLimiter(config)
+	}
+	m.replaced++
+	if m.active >= len(configs) || m.active == -1 {
+		m.active = min(0, len(configs)-1)
+	}
+}
+
+// Names returns the display names of all providers in registration order
+func (m *ProviderManager) Names() []string {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+
+	names := make([]string, len(m.providers))
+	for i, p := range m.providers {
+		names[i] = p.GetName()
+	}
+	return names
+}
+
+// Configs returns the configuration of every provider in registration order,
+// API keys kept in the keyring as their keyring: references
+func (m *ProviderManager) Configs() []ProviderConfig {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+
+	return append([]ProviderConfig{}, m.stored...)
+}
+
+// SetActive switches the active provider by index
+func (m *ProviderManager) SetActive(index int) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	if index < 0 || index >= len(m.providers) {
+		return fmt.Errorf("invalid provider index")
+	}
+
+	m.active = index
+	return nil
+}
+
+// ActiveIndex returns the index of the active provider, or -1 if none is configured
+func (m *ProviderManager) ActiveIndex() int {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+
+	return m.active
+}
+
+// Active returns the active provider and its configuration.
+// With no providers configured it falls back to a mock provider.
+func (m *ProviderManager) Active() (Provider, ProviderConfig) {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+
+	if m.active == -1 || len(m.providers) == 0 {
+		config := ProviderConfig{Type: "Mock", Name: "Mock"}
+		return NewMockProvider(config), config
+	}
+
+	return m.providers[m.active], m.configs[m.active]
+}
+
+// SetRetryPolicy replaces the retry and failover policy
+func (m *ProviderManager) SetRetryPolicy(policy RetryPolicy) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	if policy.MaxAttempts < 1 {
+		policy.MaxAttempts = 1
+	}
+	if policy.MaxRetryAfter <= 0 {
+		policy.MaxRetryAfter = DefaultRetryPolicy().MaxRetryAfter
+	}
+	m.retry = policy
+}
+
+// managedProvider pairs a provider with the configuration it was built from
+type managedProvider struct {
+	provider Provider
+	config   ProviderConfig
+	limiter  *RateLimiter
+}
+
+// candidates returns the providers to try in order: the active one first,
+// then (with failover) every other provider in registration order.
+// A request naming a provider is routed only to that provider. In offline
+// mode remote providers are left out, failing fast if none remain.
+func (m *ProviderManager) candidates(request AIRequest) ([]managedProvider, RetryPolicy, error) {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+
+	if request.Provider != "" {
+		for i, p := range m.providers {
+			if p.GetName() == request.Provider {
+				if err := m.checkOffline(p, m.configs[i]); err != nil {
+					return nil, m.retry, err
+				}
+				return []managedProvider{{p, m.configs[i], m.limiters[i]}}, m.retry, nil
+			}
+		}
+		return nil, m.retry, fmt.Errorf("unknown provider %q", request.Provider)
+	}
+
+	if m.active == -1 || len(m.providers) == 0 {
+		config := ProviderConfig{Type: "Mock", Name: "Mock"}
+		return []managedProvider{{NewMockProvider(config), config, nil}}, m.retry, nil
+	}
+
+	ordered := make([]managedProvider, 0, len(m.providers))
+	add := func(i int) {
+		if m.checkOffline(m.providers[i], m.configs[i]) == nil {
+			ordered = append(ordered, managedProvider{m.providers[i], m.configs[i], m.limiters[i]})
+		}
+	}
+	add(m.active)
+	if m.retry.Failover {
+		for i := range m.providers {
+			if i != m.active {
+				add(i)
+			}
+		}
+	}
+	if len(ordered) == 0 {
+		return nil, m.retry, m.checkOffline(m.providers[m.active], m.configs[m.active])
+	}
+	return ordered, m.retry, nil
+}
+
+// withRetry runs call against each candidate provider, retrying transient
+// failures with exponential backoff before failing over to the next one.
+// The request is trimmed to fit each provider's context window first.
+func (m *ProviderManager) withRetry(request AIRequest, call func(Provider, AIRequest) (AIResponse, error)) (AIResponse, error) {
+	candidates, policy, err := m.candidates(request)
+	if err != nil {
+		return AIResponse{}, err
+	}
+	// Plugins see the prompt as typed, before secrets are masked
+	var failures []PluginFailure
+	asked := request
+	if !request.fromPlugin {
+		m.remember(asked, "", "")
+		request.Prompt, failures, err = m.plugins.PreProcessAll(request.Prompt)
+		if err != nil {
+			return AIResponse{}, err
+		}
+	}
+	request, redacted, err := m.redactor.Apply(request, m.plugins.AllowsSecret)
+	if err != nil {
+		return AIResponse{}, err
+	}
+
+	primary := candidates[0]
+	key := cacheKey(primary.provider.GetName(), primary.config.Model, request.withSystemPrompt(primary.config))
+	if !request.NoCache {
+		if cached, ok := m.cache.Get(key); ok {
+			cached.Cached = true
+			cached.Attempts = 0
+			cached.Cost = 0
+			cached.Redacted = redacted
+			cached.PluginFailures = failures
+			if !request.fromPlugin {
+				m.remember(asked, cached.Content, cached.TextFormat)
+			}
+			return cached, nil
+		}
+	}
+
+	var lastErr error
+	attempts := 0
+	for _, candidate := range candidates {
+		provider := candidate.provider
+		if len(request.Attachments) > 0 && !CapabilitiesOf(provider).Vision {
+			lastErr = fmt.Errorf("%s does not accept image attachments", provider.GetName())
+			continue
+		}
+		fitted := FitToContext(m.compressHistory(candidate, request.withSystemPrompt(candidate.config)), candidate.config.Model)
+		backoff := policy.InitialBackoff
+		rateLimited := 0
+		for attempt := 1; attempt <= policy.MaxAttempts; attempt++ {
+			attempts++
+			m.throttle(candidate, fitted)
+			started := time.Now()
+			response, err := call(provider, fitted)
+			var postFailures []PluginFailure
+			if err == nil && !request.fromPlugin {
+				response.Content, postFailures, err = m.plugins.PostProcessAll(response.Content, candidate.config.TextFormat)
+			}
+			if err == nil {
+				err = ValidateResponse(response.Content, fitted.ResponseFormat)
+			}
+			m.log.Record(candidate.config, provider.GetName(), attempts, time.Since(started), fitted, response, err)
+			if err == nil {
+				response = m.finish(candidate, fitted, response, attempts)
+				// Content a plugin failed to post-process isn't what it should be, so it isn't kept.
+				// It is kept as the answer of the provider that gave it, not the primary's, so a
+				// failover answer isn't served as the primary's next time.
+				if len(postFailures) == 0 {
+					m.cache.Put(cacheKey(provider.GetName(), candidate.config.Model, request.withSystemPrompt(candidate.config)), response)
+				}
+				response.Redacted = redacted
+				response.PluginFailures = append(failures, postFailures...)
+				if !request.fromPlugin {
+					m.remember(asked, response.Content, response.TextFormat)
+				}
+				return response, nil
+			}
+
+			lastErr = fmt.Errorf("%s: %w", provider.GetName(), err)
+			if !isRetryable(err) {
+				return AIResponse{}, lastErr
+			}
+			var rateErr *RateLimitError
+			if errors.As(err, &rateErr) {
+				if rateLimited >= policy.RateLimitRetries {
+					break // give up on this provider and fail over
+				}
+				rateLimited++
+				wait := rateErr.RetryAfter
+				if wait <= 0 {
+					wait = backoff
+					backoff = min(backoff*2, policy.MaxBackoff)
+				}
+				wait = min(wait, policy.MaxRetryAfter)
+				m.notify(ManagerStatus{
+					Provider:    provider.GetName(),
+					Kind:        "rate_limited",
+					Message:     fmt.Sprintf("Rate limited, retrying in %.1fs (%d/%d)", wait.Seconds(), rateLimited, policy.RateLimitRetries),
+					WaitSeconds: wait.Seconds(),
+				})
+				time.Sleep(wait)
+				attempt-- // rate limits have their own budget
+				continue
+			}
+			if attempt < policy.MaxAttempts {
+				time.Sleep(backoff)
+				backoff = min(backoff*2, policy.MaxBackoff)
+			}
+		}
+	}
+
+	return AIResponse{}, lastErr
+}
+
+// compressHistory summarizes older turns that no longer fit the provider's context window
+func (m *ProviderManager) compressHistory(candidate managedProvider, request AIRequest) AIRequest {
+	return m.compress.Compress(request, candidate.config.Model, func(summary AIRequest) (AIResponse, error) {
+		m.notify(ManagerStatus{
+			Provider: candidate.provider.GetName(),
+			Kind:     "summarizing",
+			Message:  "Summarizing earlier turns to fit the context window",
+		})
+		m.throttle(candidate, summary)
+		response, err := candidate.provider.SendRequest(summary)
+		if err != nil {
+			return AIResponse{}, err
+		}
+		return m.finish(candidate, summary, response, 1), nil
+	})
+}
+
+// throttle waits for the provider's rate limiter, reporting the delay
+func (m *ProviderManager) throttle(candidate managedProvider, request AIRequest) {
+	tokens := estimateMessageTokens(request.Messages()) + request.MaxTokens
+	candidate.limiter.Wait(tokens, func(queued int, wait time.Duration) {
+		m.notify(ManagerStatus{
+			Provider:    candidate.provider.GetName(),
+			Kind:        "queued",
+			Message:     fmt.Sprintf("Rate limit reached, request queued for %.1fs", wait.Seconds()),
+			WaitSeconds: wait.Seconds(),
+			Queued:      queued,
+		})
+	})
+}
+
+// SetStatusHandler registers a callback for in-flight request status updates
+func (m *ProviderManager) SetStatusHandler(handler func(ManagerStatus)) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	m.onStatus = handler
+}
+
+func (m *ProviderManager) notify(status ManagerStatus) {
+	m.mu.RLock()
+	handler := m.onStatus
+	m.mu.RUnlock()
+
+	if handler != nil {
+		handler(status)
+	}
+}
+
+// finish fills in what the provider didn't report and records the spend
+func (m *ProviderManager) finish(candidate managedProvider, request AIRequest, response AIResponse, attempts int) AIResponse {
+	response.Provider = candidate.provider.GetName()
+	response.Attempts = attempts
+	response.TextFormat = candidate.config.TextFormat
+	if response.Model == "" {
+		response.Model = candidate.config.Model
+	}
+	if response.PromptTokens == 0 {
+		response.PromptTokens = estimateMessageTokens(request.Messages())
+	}
+	if response.CompletionTokens == 0 {
+		response.CompletionTokens = EstimateTokens(response.Content)
+	}
+	response.Stats = ComputeResponseStats(response.Content, ParseResponseAs(response.Content, response.TextFormat))
+	response = m.costs.Record(candidate.config, response)
+	m.usage.Record(response)
+	return response
+}
+
+// SetRedactionMode controls how secrets in outgoing prompts are handled
+func (m *ProviderManager) SetRedactionMode(mode string) error {
+	return m.redactor.SetMode(mode)
+}
+
+// RedactionMode returns the current secret redaction mode
+func (m *ProviderManager) RedactionMode() string {
+	return m.redactor.Mode()
+}
+
+// SetContextCompression turns summarization of overflowing history on or off;
+// when off, the oldest turns are dropped instead
+func (m *ProviderManager) SetContextCompression(enabled bool) {
+	m.compress.SetEnabled(enabled)
+}
+
+func (m *ProviderManager) ContextCompression() bool {
+	return m.compress.Enabled()
+}
+
+// RequestLog returns the log of provider calls
+func (m *ProviderManager) RequestLog() *RequestLog {
+	return m.log
+}
+
+// Plugins returns the registry of plugins run on every request
+func (m *ProviderManager) Plugins() *PluginRegistry {
+	return m.plugins
+}
+
+// SetCacheConfig replaces the response cache settings
+func (m *ProviderManager) SetCacheConfig(config CacheConfig) {
+	m.cache.Configure(config)
+}
+
+// ClearCache drops every cached response
+func (m *ProviderManager) ClearCache() {
+	m.cache.Clear()
+}
+
+// Usage returns the persisted usage per model per day for the last days days
+func (m *ProviderManager) Usage(days int) UsageReport {
+	return m.usage.Report(days)
+}
+
+// ClearUsage deletes the persisted usage history
+func (m *ProviderManager) ClearUsage() error {
+	return m.usage.Clear()
+}
+
+// Costs returns the accumulated spend for this session
+func (m *ProviderManager) Costs() CostSummary {
+	return m.costs.Summary()
+}
+
+// EmbeddingModel names the provider and model Embed uses; embeddings are
+// only comparable when it is the same
+func (m *ProviderManager) EmbeddingModel() string {
+	_, config := m.Active()
+	return config.Type + "/" + config.Name + "/" + config.EmbeddingModelName()
+}
+
+// Embed embeds texts with the active provider
+func (m *ProviderManager) Embed(texts []string) ([][]float32, error) {
+	if len(texts) == 0 {
+		return nil, nil
+	}
+	provider, config := m.Active()
+	m.mu.RLock()
+	err := m.checkOffline(provider, config)
+	m.mu.RUnlock()
+	if err != nil {
+		return nil, err
+	}
+	if !CapabilitiesOf(provider).Embeddings {
+		return nil, fmt.Errorf("%s does not support embeddings", provider.GetName())
+	}
+	embeddings, err := provider.(EmbeddingProvider).Embed(texts)
+	if err != nil {
+		return nil, fmt.Errorf("%s: %w", provider.GetName(), err)
+	}
+	return embeddings, nil
+}
+
+// ProviderResult is one outcome in a SendToAll fan-out or a SendBatch
+type ProviderResult struct {
+	Provider string     `json:"provider"`
+	Response AIResponse `json:"response"`
+	Error    string     `json:"error"`
+}
+
+// SendToAll sends the same request to every provider concurrently, each with
+// its own retries but no failover, and returns the results in registration order
+func (m *ProviderManager) SendToAll(request AIRequest) []ProviderResult {
+	names := m.Names()
+	results := make([]ProviderResult, len(names))
+	var wg sync.WaitGroup
+	for i, name := range names {
+		wg.Add(1)
+		goSafe(func() {
+			defer wg.Done()
+			response, err := m.SendWith(name, request)
+			results[i] = ProviderResult{Provider: name, Response: response}
+			if err != nil {
+				results[i].Error = err.Error()
+			}
+		})
+	}
+	wg.Wait()
+	return results
+}
+
+// defaultBatchConcurrency bounds SendBatch when the caller doesn't choose a limit
+const defaultBatchConcurrency = 4
+
+// SendBatch sends many requests with at most concurrency in flight, each with the
+// usual retries and failover, and returns the results in request order
+func (m *ProviderManager) SendBatch(requests []AIRequest, concurrency int) []ProviderResult {
+	if concurrency <= 0 {
+		concurrency = defaultBatchConcurrency
+	}
+	results := make([]ProviderResult, len(requests))
+	slots := make(chan struct{}, concurrency)
+	var wg sync.WaitGroup
+	for i, request := range requests {
+		wg.Add(1)
+		slots <- struct{}{}
+		goSafe(func() {
+			defer wg.Done()
+			defer func() { <-slots }()
+			response, err := m.Send(request)
+			results[i] = ProviderResult{Provider: response.Provider, Response: response}
+			if err != nil {
+				results[i].Error = err.Error()
+			}
+		})
+	}
+	wg.Wait()
+	return results
+}
+
+// SendWith routes a request to the named provider regardless of the active selection
+func (m *ProviderManager) SendWith(providerName string, request AIRequest) (AIResponse, error) {
+	request.Provider = providerName
+	return m.Send(request)
+}
+
+// Send routes a request through the active provider with retry and failover,
+// or to request.Provider when set
+func (m *ProviderManager) Send(request AIRequest) (AIResponse, error) {
+	return m.withRetry(request, func(p Provider, request AIRequest) (AIResponse, error) {
+		return p.SendRequest(request)
+	})
+}
+
+// Complete answers a plugin's own prompt, for its PluginHost
+func (m *ProviderManager) Complete(prompt, systemPrompt string) (string, error) {
+	request := NewAIRequest(prompt, nil)
+	request.SystemPrompt = systemPrompt
+	request.fromPlugin = true
+	response, err := m.Send(request)
+	return response.Content, err
+}
+
+// ActiveModel names the active provider and its model, for plugins
+func (m *ProviderManager) ActiveModel() (provider, model string) {
+	active, config := m.Active()
+	return active.GetName(), config.Model
+}
+
+// History returns the latest conversation, for plugins
+func (m *ProviderManager) History() []ChatMessage {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+	return append([]ChatMessage(nil), m.session...)
+}
+
+// remember keeps the conversation a request continues, with its answer in
+// its text format once there is one
+func (m *ProviderManager) remember(request AIRequest, answer, format string) {
+	session := make([]ChatMessage, 0, len(request.History)+2)
+	session = append(session, request.History...)
+	session = append(session, ChatMessage{Role: "user", Content: request.Prompt})
+	if answer != "" {
+		session = append(session, ChatMessage{Role: "assistant", Content: answer, TextFormat: format})
+	}
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.session = session
+}
+
+// SendStream is Send for streaming. Once any content has been delivered the
+// request is no longer retried, so the caller never sees duplicated text.
+// Cached responses are delivered as a single chunk. Plugins post-process the
+// final content, not the chunks.
+func (m *ProviderManager) SendStream(request AIRequest, onChunk func(delta string)) (AIResponse, error) {
+	emitted := false
+	emit := func(delta string) {
+		emitted = true
+		onChunk(delta)
+	}
+
+	response, err := m.withRetry(request, func(p Provider, request AIRequest) (AIResponse, error) {
+		streamer, ok := p.(StreamingProvider)
+		if !ok {
+			response, err := p.SendRequest(request)
+			if err == nil {
+				emit(response.Content)
+			}
+			return response, err
+		}
+
+		response, err := streamer.SendRequestStream(request, emit)
+		if err != nil && emitted {
+			return response, fmt.Errorf("stream interrupted: %v", err)
+		}
+		return response, err
+	})
+	if err == nil && response.Cached {
+		onChunk(response.Content)
+	}
+	return response, err
+}
@@ -0,0 +1,194 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"reflect"
+	"strings"
+	"testing"
+	"time"
+)
+
+// newTestManager returns a manager with a scripted mock provider per script,
+// named mock-1, mock-2 and so on, keeping its files out of the real config dir
+func newTestManager(t *testing.T, failover bool, scripts ...*MockScript) *ProviderManager {
+	t.Helper()
+	dir := t.TempDir()
+	t.Setenv("XDG_CONFIG_HOME", dir)
+	t.Setenv("HOME", dir)
+	t.Setenv("AppData", dir)
+
+	m := NewProviderManager()
+	m.SetRetryPolicy(RetryPolicy{
+		MaxAttempts:    3,
+		InitialBackoff: time.Millisecond,
+		MaxBackoff:     time.Millisecond,
+		Failover:       failover,
+	})
+	for i, script := range scripts {
+		m.Add(ProviderConfig{Type: "Mock", Name: fmt.Sprintf("mock-%d", i+1), Model: "mock", MockScript: script})
+	}
+	return m
+}
+
+func TestProviderManagerRetry(t *testing.T) {
+	tests := []struct {
+		name         string
+		scripts      []*MockScript
+		failover     bool
+		warmup       int // requests sent first, to move FailEvery along
+		wantContent  string
+		wantProvider string
+		wantAttempts int
+		wantErr      error
+	}{
+		{
+			name:         "answers first time",
+			scripts:      []*MockScript{{Responses: []string{"hello"}}},
+			wantContent:  "hello",
+			wantProvider: "mock-1",
+			wantAttempts: 1,
+		},
+		{
+			name:         "retries a transient failure",
+			scripts:      []*MockScript{{Responses: []string{"first", "second"}, FailEvery: 2}},
+			warmup:       1,
+			wantContent:  "second",
+			wantProvider: "mock-1",
+			wantAttempts: 2,
+		},
+		{
+			name:         "fails over once retries run out",
+			scripts:      []*MockScript{{FailEvery: 1}, {Responses: []string{"backup"}}},
+			failover:     true,
+			wantContent:  "backup",
+			wantProvider: "mock-2",
+			wantAttempts: 4,
+		},
+		{
+			name:     "stays on the active provider without failover",
+			scripts:  []*MockScript{{FailEvery: 1}, {Responses: []string{"backup"}}},
+			failover: false,
+			wantErr:  ErrMockFailure,
+		},
+		{
+			name:     "every provider failing",
+			scripts:  []*MockScript{{FailEvery: 1}, {FailEvery: 1}},
+			failover: true,
+			wantErr:  ErrMockFailure,
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			m := newTestManager(t, tt.failover, tt.scripts...)
+			request := NewAIRequest("hi", nil)
+			request.NoCache = true
+			for i := 0; i < tt.warmup; i++ {
+				if _, err := m.Send(request); err != nil {
+					t.Fatalf("warm-up request: %v", err)
+				}
+			}
+
+			response, err := m.Send(request)
+			if tt.wantErr != nil {
+				if !errors.Is(err, tt.wantErr) {
+					t.Fatalf("err = %v, want %v", err, tt.wantErr)
+				}
+				return
+			}
+			if err != nil {
+				t.Fatalf("unexpected error: %v", err)
+			}
+			if response.Content != tt.wantContent || response.Provider != tt.wantProvider || response.Attempts != tt.wantAttempts {
+				t.Errorf("got %q from %s after %d attempts, want %q from %s after %d",
+					response.Content, response.Provider, response.Attempts, tt.wantContent, tt.wantProvider, tt.wantAttempts)
+			}
+		})
+	}
+}
+
+func TestProviderManagerCachesFailoverUnderItsProvider(t *testing.T) {
+	m := newTestManager(t, true, &MockScript{FailEvery: 1}, &MockScript{Responses: []string{"backup"}})
+	request := NewAIRequest("hi", nil)
+
+	first, err := m.Send(request)
+	if err != nil || first.Provider != "mock-2" {
+		t.Fatalf("first answer from %q, err %v; want mock-2", first.Provider, err)
+	}
+	direct, err := m.SendWith("mock-2", request)
+	if err != nil || !direct.Cached || direct.Content != "backup" {
+		t.Errorf("asking mock-2 got %q (cached %v, err %v), want its cached answer", direct.Content, direct.Cached, err)
+	}
+	again, err := m.Send(request)
+	if err != nil || again.Cached {
+		t.Errorf("asking the active provider again got cached %v, err %v; the failover answer isn't its own", again.Cached, err)
+	}
+}
+
+func TestProviderManagerRemembersCachedAnswers(t *testing.T) {
+	m := newTestManager(t, false, &MockScript{Responses: []string{"first answer", "second answer"}})
+	for _, prompt := range []string{"hi", "something else", "hi"} {
+		if _, err := m.Send(NewAIRequest(prompt, nil)); err != nil {
+			t.Fatalf("asking %q: %v", prompt, err)
+		}
+	}
+	history := m.History()
+	if len(history) != 2 || history[0].Content != "hi" || history[1].Role != "assistant" || history[1].Content != "first answer" {
+		t.Errorf("history %+v, want the first exchange again, answered from the cache", history)
+	}
+}
+
+func TestProviderManagerSendStream(t *testing.T) {
+	tests := []struct {
+		name         string
+		scripts      []*MockScript
+		wantChunks   []string
+		wantProvider string
+		wantErr      string
+	}{
+		{
+			name:         "streams the whole response",
+			scripts:      []*MockScript{{Responses: []string{"one two three"}}},
+			wantChunks:   []string{"one ", "two ", "three"},
+			wantProvider: "mock-1",
+		},
+		{
+			name:         "fails over when nothing was sent yet",
+			scripts:      []*MockScript{{FailEvery: 1}, {Responses: []string{"backup"}}},
+			wantChunks:   []string{"backup"},
+			wantProvider: "mock-2",
+		},
+		{
+			// Retrying would repeat what the caller already has
+			name:       "gives up once content was sent",
+			scripts:    []*MockScript{{Responses: []string{"one two three"}, FailStreamAfter: 2}, {Responses: []string{"backup"}}},
+			wantChunks: []string{"one ", "two "},
+			wantErr:    "stream interrupted",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			m := newTestManager(t, true, tt.scripts...)
+			request := NewAIRequest("hi", nil)
+			request.NoCache = true
+
+			var chunks []string
+			response, err := m.SendStream(request, func(delta string) { chunks = append(chunks, delta) })
+			if !reflect.DeepEqual(chunks, tt.wantChunks) {
+				t.Errorf("chunks = %q, want %q", chunks, tt.wantChunks)
+			}
+			if tt.wantErr != "" {
+				if err == nil || !strings.Contains(err.Error(), tt.wantErr) {
+					t.Errorf("err = %v, want %q", err, tt.wantErr)
+				}
+				return
+			}
+			if err != nil {
+				t.Fatalf("unexpected error: %v", err)
+			}
+			if response.Provider != tt.wantProvider || response.Content != strings.Join(tt.wantChunks, "") {
+				t.Errorf("got %q from %s, want the chunks from %s", response.Content, response.Provider, tt.wantProvider)
+			}
+		})
+	}
+}
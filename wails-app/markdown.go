@@ -0,0 +1,259 @@
+package main
+
+import (
+	"strconv"
+	"strings"
+	"unicode/utf8"
+)
+
+// RenderMarkdown turns parsed blocks back into canonical markdown that parses
+// to the same blocks, so code that edits the block model can hand the result
+// on as markdown again
+func RenderMarkdown(blocks []ContentBlock) string {
+	parts := make([]string, 0, len(blocks))
+	for _, block := range blocks {
+		parts = append(parts, renderBlock(block))
+	}
+	return strings.Join(parts, "\n\n")
+}
+
+func renderBlock(block ContentBlock) string {
+	switch block.Type {
+	case BlockHeading:
+		return strings.Repeat("#", max(block.Level, 1)) + " " + renderSpans(block.Spans, block.Text)
+	case BlockCode, BlockDiff, BlockDiagram:
+		return renderFence(block)
+	case BlockMath:
+		return "$$\n" + block.Code + "\n$$"
+	case BlockList, BlockTaskList:
+		return renderList(block)
+	case BlockQuote:
+		quoted := strings.Split(RenderMarkdown(block.Children), "\n")
+		for i, line := range quoted {
+			quoted[i] = strings.TrimRight("> "+line, " ")
+		}
+		return strings.Join(quoted, "\n")
+	case BlockTable:
+		return renderTable(block)
+	case BlockRule:
+		return "---"
+	case BlockImage:
+		return "![" + block.Alt + "](" + block.URL + ")"
+	case BlockFootnote:
+		return "[^" + block.Label + "]: " + indentLines(RenderMarkdown(block.Children), "    ")
+	case BlockDefList:
+		var out []string
+		for _, item := range block.Items {
+			out = append(out, renderSpans(item.Spans, item.Text))
+			for _, definition := range item.Children {
+				out = append(out, ": "+renderBlock(definition))
+			}
+		}
+		return strings.Join(out, "\n")
+	default:
+		return escapeBlockStart(renderSpans(block.Spans, block.Text))
+	}
+}
+
+// escapeBlockStart keeps a paragraph that begins like a heading, list item,
+// quote or rule from reading as one
+func escapeBlockStart(text string) string {
+	if match := listItemLine.FindStringSubmatch(text); match != nil && match[3] != "" {
+		return match[3] + `\` + text[len(match[3]):] // "1\. not a list"
+	}
+	if startsBlock(text) || listItemLine.MatchString(text) || strings.HasPrefix(text, ": ") {
+		return `\` + text
+	}
+	return text
+}
+
+// renderFence writes a fenced block, naming its file in the info string only
+// when the code itself doesn't already say
+func renderFence(block ContentBlock) string {
+	fence := strings.Repeat("`", max(3, longestRun(block.Code, '`')+1))
+	info := block.Language
+	path := block.FilePath
+	if path == commentPath(block.Code) || len(block.Hunks) > 0 && block.Hunks[0].NewFile == path {
+		path = ""
+	}
+	if path != "" {
+		info += " title=" + path
+	}
+	return fence + info + "\n" + block.Code + "\n" + fence
+}
+
+func renderList(block ContentBlock) string {
+	lines := make([]string, 0, len(block.Items))
+	for i, item := range block.Items {
+		marker := "- "
+		if block.Ordered {
+			marker = strconv.Itoa(block.Start+i) + ". "
+		}
+		box := ""
+		if block.Type == BlockTaskList {
+			box = "[ ] "
+			if item.Checked {
+				box = "[x] "
+			}
+		}
+		entry := marker + box + renderSpans(item.Spans, item.Text)
+		for _, child := range item.Children {
+			// A nested list follows its item directly; anything else needs a
+			// blank line or it would read as more of the item's text
+			separator := "\n\n"
+			if child.Type == BlockList || child.Type == BlockTaskList {
+				separator = "\n"
+			}
+			entry += separator + strings.Repeat(" ", len(marker)) + indentLines(renderBlock(child), strings.Repeat(" ", len(marker)))
+		}
+		lines = append(lines, entry)
+	}
+	return strings.Join(lines, "\n")
+}
+
+func renderTable(block ContentBlock) string {
+	row := func(cells []string) string {
+		escaped := make([]string, len(cells))
+		for i, cell := range cells {
+			escaped[i] = escapePipes(cell)
+		}
+		return "| " + strings.Join(escaped, " | ") + " |"
+	}
+	delimiters := make([]string, len(block.Headers))
+	for i := range delimiters {
+		align := ""
+		if i < len(block.Align) {
+			align = block.Align[i]
+		}
+		switch align {
+		case "left":
+			delimiters[i] = ":---"
+		case "center":
+			delimiters[i] = ":---:"
+		case "right":
+			delimiters[i] = "---:"
+		default:
+			delimiters[i] = "---"
+		}
+	}
+
+	lines := []string{row(block.Headers), "| " + strings.Join(delimiters, " | ") + " |"}
+	for _, cells := range block.Rows {
+		lines = append(lines, row(cells))
+	}
+	return strings.Join(lines, "\n")
+}
+
+// escapePipes escapes pipes outside code spans so they stay in their cell
+func escapePipes(cell string) string {
+	var out strings.Builder
+	inCode := false
+	for i := 0; i < len(cell); i++ {
+		switch c := cell[i]; {
+		case c == '`':
+			inCode = !inCode
+		case c == '|' && !inCode:
+			out.WriteByte('\\')
+		}
+		out.WriteByte(cell[i])
+	}
+	return out.String()
+}
+
+// renderSpans writes inline spans as markdown, or text when there are none
+func renderSpans(spans []InlineSpan, text string) string {
+	if spans == nil {
+		return escapeInline(text)
+	}
+	var out strings.Builder
+	for _, span := range spans {
+		switch span.Type {
+		case SpanBold:
+			out.WriteString("**" + renderSpans(span.Children, "") + "**")
+		case SpanItalic:
+			out.WriteString("*" + renderSpans(span.Children, "") + "*")
+		case SpanStrike:
+			out.WriteString("~~" + renderSpans(span.Children, "") + "~~")
+		case SpanCode:
+			ticks := strings.Repeat("`", longestRun(span.Text, '`')+1)
+			code := span.Text
+			if strings.HasPrefix(code, "`") || strings.HasSuffix(code, "`") {
+				code = " " + code + " "
+			}
+			out.WriteString(ticks + code + ticks)
+		case SpanLink:
+			if label := plainText(span.Children); label == span.URL && isAutolink(label) {
+				out.WriteString("<" + span.URL + ">")
+			} else {
+				out.WriteString("[" + renderSpans(span.Children, "") + "](" + span.URL + ")")
+			}
+		case SpanMath:
+			out.WriteString("$" + span.Text + "$")
+		case SpanFootnote:
+			out.WriteString("[^" + span.Text + "]")
+		default:
+			out.WriteString(escapeInline(span.Text))
+		}
+	}
+	return out.String()
+}
+
+// escapeInline backslash-escapes what would otherwise start formatting. An
+// underscore inside a word never does, so snake_case is left alone.
+func escapeInline(text string) string {
+	var out strings.Builder
+	dollars := strings.Count(text, "$") > 1
+	for i := 0; i < len(text); i++ {
+		c := text[i]
+		switch c {
+		case '\\', '`', '*', '[', ']', '<':
+			out.WriteByte('\\')
+		case '~':
+			if i+1 < len(text) && text[i+1] == '~' {
+				out.WriteByte('\\')
+			}
+		case '$':
+			if dollars {
+				out.WriteByte('\\')
+			}
+		case '_':
+			before, _ := utf8.DecodeLastRuneInString(text[:i])
+			after, _ := utf8.DecodeRuneInString(text[i+1:])
+			if i == 0 || i+1 == len(text) || !isWordRune(before) || !isWordRune(after) {
+				out.WriteByte('\\')
+			}
+		}
+		out.WriteByte(c)
+	}
+	return out.String()
+}
+
+// longestRun returns the length of the longest run of c in s
+func longestRun(s string, c byte) int {
+	longest, run := 0, 0
+	for i := 0; i < len(s); i++ {
+		if s[i] == c {
+			run++
+			longest = max(longest, run)
+		} else {
+			run = 0
+		}
+	}
+	return longest
+}
+
+// indentLines indents every line after the first, leaving blank lines empty
+func indentLines(text, indent string) string {
+	lines := strings.Split(text, "\n")
+	for i := 1; i < len(lines); i++ {
+		if lines[i] != "" {
+			lines[i] = indent + lines[i]
+		}
+	}
+	return strings.Join(lines, "\n")
+}
+
+// RenderMarkdown exposes the markdown writer to the frontend
+func (a *App) RenderMarkdown(blocks []ContentBlock) string {
+	return RenderMarkdown(blocks)
+}
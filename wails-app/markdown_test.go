@@ -0,0 +1,53 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+// withoutSources drops the source spans, which change when blocks are rendered
+// back to markdown
+func withoutSources(blocks []ContentBlock) []ContentBlock {
+	out := make([]ContentBlock, len(blocks))
+	for i, block := range blocks {
+		block.Source = SourceSpan{}
+		block.Children = withoutSources(block.Children)
+		items := make([]ListItem, len(block.Items))
+		for j, item := range block.Items {
+			item.Children = withoutSources(item.Children)
+			items[j] = item
+		}
+		if block.Items != nil {
+			block.Items = items
+		}
+		out[i] = block
+	}
+	return out
+}
+
+func TestParseResponseRoundTrip(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+	}{
+		{"headings and paragraphs", "# Title\n\nSome **bold**, *italic* and `code` with a [link](https://example.com).\n\n### Smaller"},
+		{"lists", "- one\n- two\n  - nested\n\n3. three\n4. four\n\n- [x] done\n- [ ] todo"},
+		{"quote", "> quoted **text**\n>\n> second paragraph"},
+		{"code", "```go title=main.go\nfunc main() {}\n```\n\n```\nplain text\n```"},
+		{"fence holding a fence", "````markdown\n```go\nx := 1\n```\n````"},
+		{"diff", "```diff\n--- a/x.go\n+++ b/x.go\n@@ -1,2 +1,2 @@\n context\n-old\n+new\n```"},
+		{"table", "| left | middle | right |\n| :--- | :---: | ---: |\n| a | b | c |\n| `x \\| y` | | z |"},
+		{"rule, math and image", "before\n\n---\n\n$$\nx^2\n$$\n\n![a chart](chart.png)"},
+		{"paragraph that looks like a list", "1\\. not a list\n\n\\# not a heading"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			blocks := ParseResponse(tt.markdown)
+			rendered := RenderMarkdown(blocks)
+			again := ParseResponse(rendered)
+			if got, want := withoutSources(again), withoutSources(blocks); !reflect.DeepEqual(got, want) {
+				t.Errorf("rendered as\n%s\ngot  %+v\nwant %+v", rendered, got, want)
+			}
+		})
+	}
+}
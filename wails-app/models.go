@@ -0,0 +1,104 @@
+package main
+
+import (
+	"fmt"
+	"sort"
+	"strings"
+)
+
+// UnknownModelError reports a configured model the provider doesn't serve,
+// with the closest names it does
+type UnknownModelError struct {
+	Model       string
+	Suggestions []string
+}
+
+func (e *UnknownModelError) Error() string {
+	if len(e.Suggestions) == 0 {
+		return fmt.Sprintf("model %q is not available", e.Model)
+	}
+	return fmt.Sprintf("model %q is not available; did you mean %s?", e.Model, strings.Join(e.Suggestions, ", "))
+}
+
+// modelMatches reports whether an available model name satisfies the configured one,
+// allowing Ollama's implicit :latest tag
+func modelMatches(available, model string) bool {
+	return available == model || available == model+":latest"
+}
+
+// ValidateModel checks that a provider which can list its models serves the
+// configured one. Providers that can't list models, and listing failures such
+// as an unreachable server, are not treated as invalid; the health check
+// reports those.
+func ValidateModel(provider Provider, model string) error {
+	lister, ok := provider.(ModelLister)
+	if !ok || model == "" {
+		return nil
+	}
+	models, err := lister.ListModels()
+	if err != nil || len(models) == 0 {
+		return nil
+	}
+	for _, available := range models {
+		if modelMatches(available, model) {
+			return nil
+		}
+	}
+	return &ConfigError{Err: &UnknownModelError{Model: model, Suggestions: suggestModels(model, models, 3)}}
+}
+
+// suggestModels returns up to limit models closest to model by edit distance,
+// preferring names that contain it
+func suggestModels(model string, models []string, limit int) []string {
+	type scored struct {
+		name     string
+		distance int
+	}
+	target := strings.ToLower(model)
+	candidates := make([]scored, 0, len(models))
+	for _, name := range models {
+		lower := strings.ToLower(name)
+		distance := editDistance(target, lower)
+		if strings.Contains(lower, target) || strings.Contains(target, lower) {
+			distance = 0
+		}
+		// Skip names that share almost nothing with the request
+		if distance > max(len(target), len(lower))/2 {
+			continue
+		}
+		candidates = append(candidates, scored{name, distance})
+	}
+	sort.SliceStable(candidates, func(i, j int) bool {
+		return candidates[i].distance < candidates[j].distance
+	})
+
+	suggestions := make([]string, 0, limit)
+	for _, c := range candidates {
+		if len(suggestions) == limit {
+			break
+		}
+		suggestions = append(suggestions, c.name)
+	}
+	return suggestions
+}
+
+// editDistance is the Levenshtein distance between a and b
+func editDistance(a, b string) int {
+	previous := make([]int, len(b)+1)
+	current := make([]int, len(b)+1)
+	for j := range previous {
+		previous[j] = j
+	}
+	for i := 1; i <= len(a); i++ {
+		current[0] = i
+		for j := 1; j <= len(b); j++ {
+			cost := 1
+			if a[i-1] == b[j-1] {
+				cost = 0
+			}
+			current[j] = min(previous[j]+1, current[j-1]+1, previous[j-1]+cost)
+		}
+		previous, current = current, previous
+	}
+	return previous[len(b)]
+}
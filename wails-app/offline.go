@@ -0,0 +1,69 @@
+package main
+
+import (
+	"fmt"
+	"net"
+	"net/url"
+)
+
+// OfflineError is returned without any network call when offline mode is on
+// and a request would go to a remote provider
+type OfflineError struct {
+	Provider string
+}
+
+func (e *OfflineError) Error() string {
+	return fmt.Sprintf("offline mode: %s is a remote provider; use a local provider or turn offline mode off", e.Provider)
+}
+
+// inProcessProviderTypes never touch the network
+var inProcessProviderTypes = map[string]bool{
+	"LlamaCpp": true,
+	"Mock":     true,
+}
+
+// isLocalProvider reports whether a provider is usable offline: it runs in
+// process, or its endpoint is on this machine (a local Ollama or llama-server)
+func isLocalProvider(config ProviderConfig) bool {
+	if inProcessProviderTypes[config.Type] {
+		return true
+	}
+	endpoint := config.Endpoint
+	if endpoint == "" && config.Type == "Ollama" {
+		endpoint = defaultOllamaEndpoint
+	}
+	target, err := url.Parse(endpoint)
+	if err != nil {
+		return false
+	}
+	host := target.Hostname()
+	if host == "localhost" {
+		return true
+	}
+	ip := net.ParseIP(host)
+	return ip != nil && ip.IsLoopback()
+}
+
+// SetOffline turns offline mode on or off. While offline only local providers
+// are used, for requests, failover, embeddings and health checks alike.
+func (m *ProviderManager) SetOffline(offline bool) {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+
+	m.offline = offline
+}
+
+func (m *ProviderManager) Offline() bool {
+	m.mu.RLock()
+	defer m.mu.RUnlock()
+
+	return m.offline
+}
+
+// checkOffline fails fast for a remote provider while offline; the caller holds m.mu
+func (m *ProviderManager) checkOffline(provider Provider, config ProviderConfig) error {
+	if m.offline && !isLocalProvider(config) {
+		return &OfflineError{Provider: provider.GetName()}
+	}
+	return nil
+}
@@ -0,0 +1,611 @@
+package main
+
+import (
+	"regexp"
+	"strconv"
+	"strings"
+)
+
+const (
+	BlockHeading   = "heading"
+	BlockParagraph = "paragraph"
+	BlockCode      = "code"
+	BlockList      = "list"
+	BlockQuote     = "quote"
+	BlockTable     = "table"
+	BlockDiff      = "diff"
+	BlockTaskList  = "tasklist"
+	BlockMath      = "math"
+	BlockRule      = "rule"
+	BlockImage     = "image"
+	BlockFootnote  = "footnote"
+	BlockDefList   = "deflist"
+	BlockDiagram   = "diagram"
+)
+
+// ContentBlock is one structural element of a markdown response. Type selects
+// which of the other fields are set.
+type ContentBlock struct {
+	Type   string     `json:"type"`
+	Source SourceSpan `json:"source"`
+
+	Level    int            `json:"level,omitempty"`    // heading: 1-6
+	Text     string         `json:"text,omitempty"`     // heading, paragraph; plain, without formatting
+	Spans    []InlineSpan   `json:"spans,omitempty"`    // heading, paragraph: Text with its formatting
+	Language string         `json:"language,omitempty"` // code, diff, diagram
+	Code     string         `json:"code,omitempty"`     // code, diff, diagram: the raw text; math: the TeX
+	Kind     string         `json:"kind,omitempty"`     // diagram: "mermaid", "graphviz" or "plantuml"
+	Hunks    []DiffHunk     `json:"hunks,omitempty"`    // diff
+	FilePath string         `json:"filePath,omitempty"` // code, diff: the file the code belongs in, when the response says
+	Items    []ListItem     `json:"items,omitempty"`    // list, tasklist; deflist: terms, with their definitions as Children
+	Ordered  bool           `json:"ordered,omitempty"`  // list: numbered rather than bulleted
+	Start    int            `json:"start,omitempty"`    // ordered list: number of the first item
+	Children []ContentBlock `json:"children,omitempty"` // quote, footnote: the paragraphs, code, nested quotes and so on inside
+	URL      string         `json:"url,omitempty"`      // image
+	Alt      string         `json:"alt,omitempty"`      // image: the description in ![...]
+	Label    string         `json:"label,omitempty"`    // footnote: the 1 in [^1]
+
+	// table: rows are padded or cut to the header width; Align holds
+	// "left", "center", "right" or "" per column
+	Headers []string   `json:"headers,omitempty"`
+	Rows    [][]string `json:"rows,omitempty"`
+	Align   []string   `json:"align,omitempty"`
+}
+
+// SourceSpan is the byte range [Start, End) a block was parsed from in the
+// original response, so the UI can map a block back to its raw markdown
+type SourceSpan struct {
+	Start int `json:"start"`
+	End   int `json:"end"`
+}
+
+// ListItem is one entry of a list. Children holds what follows the item's
+// first paragraph: nested lists, further paragraphs, code and so on.
+type ListItem struct {
+	Text     string         `json:"text"`
+	Spans    []InlineSpan   `json:"spans,omitempty"`
+	Children []ContentBlock `json:"children,omitempty"`
+	Checked  bool           `json:"checked,omitempty"` // tasklist: the box is ticked
+}
+
+var (
+	headingLine    = regexp.MustCompile(`^ {0,3}(#{1,6})\s+(.*?)(?:\s+#+)?\s*$`)
+	listItemLine   = regexp.MustCompile(`^( *)([-*+]|(\d{1,9})[.)])( +)(.*)$`)
+	taskMarker     = regexp.MustCompile(`^\[([ xX])\]\s+`)
+	tableDelimiter = regexp.MustCompile(`^\s*\|?\s*:?-+:?\s*(\|\s*:?-+:?\s*)*\|?\s*$`)
+	footnoteLine   = regexp.MustCompile(`^ {0,3}\[\^([^\]\s]+)\]:\s*(.*)$`)
+	ruleLine       = regexp.MustCompile(`^ {0,3}(?:(?:-[ \t]*){3,}|(?:\*[ \t]*){3,}|(?:_[ \t]*){3,})$`)
+)
+
+// ParserOptions selects the markdown extensions the parser recognizes
+type ParserOptions struct {
+	Tables           bool `json:"tables"`
+	Footnotes        bool `json:"footnotes"`        // [^1] references and their definitions
+	Strikethrough    bool `json:"strikethrough"`    // ~~text~~
+	SmartPunctuation bool `json:"smartPunctuation"` // curly quotes, dashes and ellipses in text
+	MaxBlocks        int  `json:"maxBlocks"`        // top-level blocks kept, 0 for no limit
+}
+
+func DefaultParserOptions() ParserOptions {
+	return ParserOptions{Tables: true, Footnotes: true, Strikethrough: true}
+}
+
+// ParseResponse splits a markdown response into content blocks
+func ParseResponse(markdown string) []ContentBlock {
+	return ParseResponseWith(markdown, DefaultParserOptions())
+}
+
+// ParseResponseWith is ParseResponse with the given options
+func ParseResponseWith(markdown string, opts ParserOptions) []ContentBlock {
+	blocks := parseSource(markdown, 0, opts)
+	if opts.MaxBlocks > 0 && len(blocks) > opts.MaxBlocks {
+		blocks = blocks[:opts.MaxBlocks]
+	}
+	return blocks
+}
+
+// parseSource parses markdown that begins base bytes into the response, so
+// blocks parsed from part of a response still get spans into all of it
+func parseSource(markdown string, base int, opts ParserOptions) []ContentBlock {
+	lines := strings.Split(markdown, "\n")
+	offsets := make([]int, len(lines))
+	for i, line := range lines {
+		offsets[i] = base
+		base += len(line) + 1
+		lines[i] = strings.TrimSuffix(line, "\r")
+	}
+	return parseBlocks(lines, offsets, opts)
+}
+
+// parseBlocks parses lines whose starts are at offsets in the response.
+// Nested content is parsed from dedented lines, with offsets moved to match.
+func parseBlocks(lines []string, offsets []int, opts ParserOptions) []ContentBlock {
+	span := func(first, last int) SourceSpan {
+		return SourceSpan{Start: offsets[first], End: offsets[last] + len(lines[last])}
+	}
+
+	blocks := make([]ContentBlock, 0)
+	var paragraph []int // indexes of the paragraph's lines
+	flush := func() {
+		if len(paragraph) > 0 {
+			text := make([]string, len(paragraph))
+			for j, line := range paragraph {
+				text[j] = strings.TrimSpace(lines[line])
+			}
+			block := textBlock(BlockParagraph, strings.Join(text, " "), opts)
+			block.Source = span(paragraph[0], paragraph[len(paragraph)-1])
+			blocks = append(blocks, block)
+			paragraph = nil
+		}
+	}
+
+	for i := 0; i < len(lines); i++ {
+		line := lines[i]
+		trimmed := strings.TrimSpace(line)
+
+		switch {
+		case trimmed == "":
+			flush()
+
+		case strings.HasPrefix(trimmed, "```") || strings.HasPrefix(trimmed, "~~~"):
+			// A "File: src/main.rs" line right before the fence names its file
+			// and is folded into the block rather than shown separately
+			annotated, start := "", offsets[i]
+			if n := len(paragraph); n > 0 {
+				if annotated = annotatedPath(lines[paragraph[n-1]]); annotated != "" {
+					start = offsets[paragraph[n-1]]
+					paragraph = paragraph[:n-1]
+				}
+			} else if n := len(blocks); n > 0 && blocks[n-1].Type == BlockParagraph {
+				if annotated = annotatedPath(blocks[n-1].Text); annotated != "" {
+					start = blocks[n-1].Source.Start
+					blocks = blocks[:n-1]
+				}
+			}
+			flush()
+
+			fence := openingFence(trimmed)
+			language, path := parseInfoString(trimmed[len(fence):])
+			block := ContentBlock{Type: BlockCode}
+			var body []string
+			for i++; i < len(lines); i++ {
+				if closesFence(lines[i], fence) {
+					break
+				}
+				body = append(body, lines[i])
+			}
+			block.Code = strings.Join(body, "\n")
+			last := min(i, len(lines)-1) // an unclosed fence runs to the end
+			block.Source = SourceSpan{Start: start, End: offsets[last] + len(lines[last])}
+			block.FilePath = firstNonEmpty(path, annotated, commentPath(block.Code))
+			block.Language = normalizeLanguage(language)
+			if block.Language == "" {
+				block.Language = detectLanguage(block.Code, block.FilePath)
+			}
+			if isDiffLanguage(block.Language) {
+				block.Type = BlockDiff
+				block.Hunks = parseDiff(block.Code)
+			} else if kind := diagramKind(block.Language); kind != "" {
+				block.Type, block.Kind = BlockDiagram, kind
+			}
+			if block.FilePath == "" && len(block.Hunks) > 0 {
+				block.FilePath = block.Hunks[0].NewFile
+			}
+			blocks = append(blocks, block)
+
+		case strings.HasPrefix(trimmed, "$$") || strings.HasPrefix(trimmed, `\[`):
+			tex, end, ok := parseMathBlock(lines, i)
+			if !ok {
+				paragraph = append(paragraph, i) // never closed, so just text
+				continue
+			}
+			flush()
+			blocks = append(blocks, ContentBlock{Type: BlockMath, Source: span(i, end), Code: tex})
+			i = end
+
+		case ruleLine.MatchString(line):
+			flush()
+			blocks = append(blocks, ContentBlock{Type: BlockRule, Source: span(i, i)})
+
+		case strings.HasPrefix(trimmed, "!["):
+			alt, url, ok := imageLine(trimmed)
+			if !ok {
+				paragraph = append(paragraph, i)
+				continue
+			}
+			flush()
+			blocks = append(blocks, ContentBlock{Type: BlockImage, Source: span(i, i), URL: url, Alt: alt})
+
+		case opts.Footnotes && footnoteLine.MatchString(line):
+			flush()
+			var block ContentBlock
+			block, i = parseFootnote(lines, offsets, i, opts)
+			blocks = append(blocks, block)
+
+		case strings.HasPrefix(trimmed, ": ") && (len(paragraph) > 0 || len(blocks) > 0 && blocks[len(blocks)-1].Type == BlockDefList):
+			// "Term" then ": definition"; the paragraph's last line is the term
+			definition := textBlock(BlockParagraph, strings.TrimSpace(trimmed[2:]), opts)
+			definition.Source = span(i, i)
+			if n := len(paragraph); n > 0 {
+				term := paragraph[n-1]
+				paragraph = paragraph[:n-1]
+				flush()
+				title := textBlock(BlockParagraph, strings.TrimSpace(lines[term]), opts)
+				item := ListItem{Text: title.Text, Spans: title.Spans}
+				if n := len(blocks); n > 0 && blocks[n-1].Type == BlockDefList {
+					blocks[n-1].Items = append(blocks[n-1].Items, item)
+				} else {
+					blocks = append(blocks, ContentBlock{Type: BlockDefList, Source: span(term, term), Items: []ListItem{item}})
+				}
+			}
+			list := &blocks[len(blocks)-1]
+			item := &list.Items[len(list.Items)-1]
+			item.Children = append(item.Children, definition)
+			list.Source.End = definition.Source.End
+
+		case headingLine.MatchString(line):
+			flush()
+			match := headingLine.FindStringSubmatch(line)
+			block := textBlock(BlockHeading, match[2], opts)
+			block.Level = len(match[1])
+			block.Source = span(i, i)
+			blocks = append(blocks, block)
+
+		case strings.HasPrefix(trimmed, ">"):
+			flush()
+			// Strip one level of > and parse what's inside like a response of its own
+			first := i
+			var quote []string
+			var quoteOffsets []int
+			for ; i < len(lines) && strings.HasPrefix(strings.TrimSpace(lines[i]), ">"); i++ {
+				inner := strings.TrimPrefix(strings.TrimPrefix(strings.TrimLeft(lines[i], " "), ">"), " ")
+				quote = append(quote, inner)
+				quoteOffsets = append(quoteOffsets, offsets[i]+len(lines[i])-len(inner))
+			}
+			i--
+			blocks = append(blocks, ContentBlock{Type: BlockQuote, Source: span(first, i), Children: parseBlocks(quote, quoteOffsets, opts)})
+
+		case listItemLine.MatchString(line):
+			flush()
+			var block ContentBlock
+			block, i = parseList(lines, offsets, i, opts)
+			i--
+			blocks = append(blocks, block)
+
+		case opts.Tables && strings.Contains(line, "|") && i+1 < len(lines) && tableDelimiter.MatchString(lines[i+1]) && strings.Contains(lines[i+1], "-"):
+			flush()
+			first := i
+			block := ContentBlock{Type: BlockTable, Headers: splitTableRow(line), Rows: make([][]string, 0)}
+			for _, cell := range splitTableRow(lines[i+1]) {
+				block.Align = append(block.Align, columnAlignment(cell))
+			}
+			block.Align = fitRow(block.Align, len(block.Headers))
+			for i += 2; i < len(lines) && strings.Contains(lines[i], "|") && strings.TrimSpace(lines[i]) != ""; i++ {
+				block.Rows = append(block.Rows, fitRow(splitTableRow(lines[i]), len(block.Headers)))
+			}
+			i--
+			block.Source = span(first, i)
+			blocks = append(blocks, block)
+
+		default:
+			paragraph = append(paragraph, i)
+		}
+	}
+	flush()
+
+	return blocks
+}
+
+// openingFence is the run of backticks or tildes a fence line starts with.
+// It may be longer than three, so a block can hold shorter fences.
+func openingFence(trimmed string) string {
+	return trimmed[:len(trimmed)-len(strings.TrimLeft(trimmed, trimmed[:1]))]
+}
+
+// closesFence reports whether line closes the block fence opened: a run of
+// the same character at least as long, with nothing after it
+func closesFence(line, fence string) bool {
+	trimmed := strings.TrimSpace(line)
+	return strings.HasPrefix(trimmed, fence) && strings.TrimLeft(trimmed, fence[:1]) == ""
+}
+
+func firstNonEmpty(values ...string) string {
+	for _, value := range values {
+		if value != "" {
+			return value
+		}
+	}
+	return ""
+}
+
+// textBlock builds a block of inline-formatted text
+func textBlock(kind, text string, opts ParserOptions) ContentBlock {
+	spans := parseInline(text, opts)
+	return ContentBlock{Type: kind, Text: plainText(spans), Spans: spans}
+}
+
+// listMarker is a parsed list item line
+type listMarker struct {
+	indent  int // spaces before the marker
+	content int // column where the item's text starts
+	ordered bool
+	number  int
+	text    string
+}
+
+func parseListMarker(line string) (listMarker, bool) {
+	match := listItemLine.FindStringSubmatch(line)
+	if match == nil || ruleLine.MatchString(line) { // "* * *" is a rule, not an item
+		return listMarker{}, false
+	}
+	marker := listMarker{
+		indent:  len(match[1]),
+		content: len(match[1]) + len(match[2]) + len(match[4]),
+		ordered: match[3] != "",
+		text:    match[5],
+	}
+	if marker.ordered {
+		marker.number, _ = strconv.Atoi(match[3])
+	}
+	return marker, true
+}
+
+// parseList reads the list starting at lines[start], returning it and the
+// index of the first line after it. Lines indented past an item's marker
+// belong to that item and are parsed recursively, so lists nest.
+func parseList(lines []string, offsets []int, start int, opts ParserOptions) (ContentBlock, int) {
+	first, _ := parseListMarker(lines[start])
+	block := ContentBlock{Type: BlockList, Ordered: first.ordered}
+	if first.ordered {
+		block.Start = first.number
+	}
+
+	// A marker up to one space deeper than the first is a sibling; models
+	// often indent nested lists by two spaces regardless of the marker width
+	sibling := func(line string) (listMarker, bool) {
+		marker, ok := parseListMarker(line)
+		return marker, ok && marker.indent <= first.indent+1
+	}
+
+	// Each body line's offset is where its text starts after the dedent
+	textOffset := func(line int, text string) int {
+		return offsets[line] + len(lines[line]) - len(text)
+	}
+
+	current := first
+	body := []string{first.text}
+	bodyOffsets := []int{textOffset(start, first.text)}
+	last := start           // the list's last non-blank line
+	var checkboxes []string // each item's [ ] or [x] marker, or ""
+	finishItem := func() {
+		item := ListItem{}
+		checkbox := taskMarker.FindString(body[0])
+		if checkbox != "" {
+			item.Checked = !strings.HasPrefix(checkbox, "[ ]")
+			body[0] = body[0][len(checkbox):]
+			bodyOffsets[0] += len(checkbox)
+		}
+		checkboxes = append(checkboxes, checkbox)
+		children := parseBlocks(body, bodyOffsets, opts)
+		if len(children) > 0 && children[0].Type == BlockParagraph {
+			item.Text, item.Spans = children[0].Text, children[0].Spans
+			children = children[1:]
+		}
+		if len(children) > 0 {
+			item.Children = children
+		}
+		block.Items = append(block.Items, item)
+	}
+	done := func() ContentBlock {
+		finishItem()
+		block.Source = SourceSpan{Start: offsets[start], End: offsets[last] + len(lines[last])}
+		return asTaskList(block, checkboxes)
+	}
+
+	i := start + 1
+	for ; i < len(lines); i++ {
+		line := lines[i]
+		if strings.TrimSpace(line) == "" {
+			// A blank line continues the list only if more of it follows
+			next := i + 1
+			for next < len(lines) && strings.TrimSpace(lines[next]) == "" {
+				next++
+			}
+			if next == len(lines) {
+				break
+			}
+			marker, isSibling := sibling(lines[next])
+			nested := leadingSpaces(lines[next]) > first.indent+1
+			if !nested && !(isSibling && marker.ordered == first.ordered && marker.indent >= first.indent) {
+				break
+			}
+			body = append(body, "")
+			bodyOffsets = append(bodyOffsets, offsets[i])
+			continue
+		}
+
+		if marker, ok := sibling(line); ok {
+			if marker.ordered != first.ordered || marker.indent < first.indent {
+				break // a different kind of list, or one an outer list owns
+			}
+			finishItem()
+			current, last = marker, i
+			body = []string{marker.text}
+			bodyOffsets = []int{textOffset(i, marker.text)}
+			continue
+		}
+
+		indent := leadingSpaces(line)
+		switch {
+		case indent > first.indent+1:
+			body = append(body, line[min(indent, current.content):])
+		case body[len(body)-1] != "" && !startsBlock(line):
+			// A lazy continuation of the item's paragraph
+			body = append(body, strings.TrimLeft(line, " \t"))
+		default:
+			return done(), i
+		}
+		bodyOffsets = append(bodyOffsets, textOffset(i, body[len(body)-1]))
+		last = i
+	}
+	return done(), i
+}
+
+// asTaskList turns a list whose every item has a checkbox into a task list.
+// In a mixed list the markers are put back so it reads as written.
+func asTaskList(block ContentBlock, checkboxes []string) ContentBlock {
+	tasks := true
+	for _, checkbox := range checkboxes {
+		tasks = tasks && checkbox != ""
+	}
+	if tasks {
+		block.Type = BlockTaskList
+		return block
+	}
+	for i, checkbox := range checkboxes {
+		if checkbox != "" {
+			marker := strings.TrimSpace(checkbox) + " "
+			block.Items[i].Checked = false
+			block.Items[i].Text = marker + block.Items[i].Text
+			block.Items[i].Spans = append([]InlineSpan{{Type: SpanText, Text: marker}}, block.Items[i].Spans...)
+		}
+	}
+	return block
+}
+
+// parseFootnote reads the footnote defined at lines[start], returning it and
+// the index of its last line. Indented lines below continue it, as do
+// unindented ones straight after its text.
+func parseFootnote(lines []string, offsets []int, start int, opts ParserOptions) (ContentBlock, int) {
+	match := footnoteLine.FindStringSubmatch(lines[start])
+	body := []string{match[2]}
+	bodyOffsets := []int{offsets[start] + len(lines[start]) - len(match[2])}
+	indented := func(line string) bool {
+		return leadingSpaces(line) >= 2 && strings.TrimSpace(line) != ""
+	}
+	continues := func(j int) bool {
+		switch line := lines[j]; {
+		case indented(line):
+			return true
+		case strings.TrimSpace(line) == "":
+			return j+1 < len(lines) && indented(lines[j+1])
+		default:
+			return strings.TrimSpace(lines[j-1]) != "" && !startsBlock(line) && !listItemLine.MatchString(line)
+		}
+	}
+
+	i := start
+	for ; i+1 < len(lines) && continues(i+1); i++ {
+		next := lines[i+1]
+		dedented := next[min(leadingSpaces(next), 4):]
+		body = append(body, dedented)
+		bodyOffsets = append(bodyOffsets, offsets[i+1]+len(next)-len(dedented))
+	}
+	block := ContentBlock{Type: BlockFootnote, Label: match[1], Children: parseBlocks(body, bodyOffsets, opts)}
+	block.Source = SourceSpan{Start: offsets[start], End: offsets[i] + len(lines[i])}
+	return block, i
+}
+
+// parseMathBlock reads display math opened by $$ or \[ on lines[start], on
+// one line or running to the line that closes it. It returns the TeX and the
+// index of the closing line.
+func parseMathBlock(lines []string, start int) (string, int, bool) {
+	trimmed := strings.TrimSpace(lines[start])
+	opener, closer := "$$", "$$"
+	if strings.HasPrefix(trimmed, `\[`) {
+		opener, closer = `\[`, `\]`
+	}
+	first := strings.TrimPrefix(trimmed, opener)
+	if strings.HasSuffix(first, closer) {
+		return strings.TrimSpace(strings.TrimSuffix(first, closer)), start, true
+	}
+
+	body := []string{first}
+	for i := start + 1; i < len(lines); i++ {
+		line := strings.TrimSpace(lines[i])
+		if strings.HasSuffix(line, closer) {
+			body = append(body, strings.TrimSuffix(line, closer))
+			return strings.TrimSpace(strings.Join(body, "\n")), i, true
+		}
+		body = append(body, line)
+	}
+	return "", start, false
+}
+
+// imageLine matches a line that is nothing but ![alt](url)
+func imageLine(trimmed string) (alt, url string, ok bool) {
+	alt, url, n, ok := parseLink(trimmed[1:])
+	return alt, url, ok && n == len(trimmed)-1
+}
+
+// startsBlock reports whether line opens a block that ends a paragraph
+func startsBlock(line string) bool {
+	trimmed := strings.TrimSpace(line)
+	return headingLine.MatchString(line) || ruleLine.MatchString(line) || footnoteLine.MatchString(line) || strings.HasPrefix(trimmed, ">") ||
+		strings.HasPrefix(trimmed, "```") || strings.HasPrefix(trimmed, "~~~")
+}
+
+func leadingSpaces(line string) int {
+	return len(line) - len(strings.TrimLeft(line, " "))
+}
+
+// splitTableRow splits a table line into its trimmed cells; pipes escaped with
+// a backslash or inside `code` belong to the cell
+func splitTableRow(line string) []string {
+	line = strings.TrimSpace(line)
+	line = strings.TrimPrefix(line, "|")
+	if strings.HasSuffix(line, "|") && !strings.HasSuffix(line, `\|`) {
+		line = line[:len(line)-1]
+	}
+
+	var cells []string
+	var cell strings.Builder
+	inCode := false
+	for i := 0; i < len(line); i++ {
+		switch c := line[i]; {
+		case c == '\\' && i+1 < len(line) && line[i+1] == '|':
+			cell.WriteByte('|')
+			i++
+		case c == '`':
+			inCode = !inCode
+			cell.WriteByte(c)
+		case c == '|' && !inCode:
+			cells = append(cells, strings.TrimSpace(cell.String()))
+			cell.Reset()
+		default:
+			cell.WriteByte(c)
+		}
+	}
+	return append(cells, strings.TrimSpace(cell.String()))
+}
+
+// columnAlignment reads a delimiter cell such as ":--" or ":-:"
+func columnAlignment(cell string) string {
+	left, right := strings.HasPrefix(cell, ":"), strings.HasSuffix(cell, ":")
+	switch {
+	case left && right:
+		return "center"
+	case right:
+		return "right"
+	case left:
+		return "left"
+	}
+	return ""
+}
+
+// fitRow pads or truncates cells to width
+func fitRow(cells []string, width int) []string {
+	if len(cells) > width {
+		return cells[:width]
+	}
+	for len(cells) < width {
+		cells = append(cells, "")
+	}
+	return cells
+}
+
+// ParseResponse exposes the parser to the frontend; format is the response's
+// TextFormat
+func (a *App) ParseResponse(response, format string) []ContentBlock {
+	return ParseResponseAs(response, format)
+}
@@ -0,0 +1,93 @@
+package main
+
+import "strings"
+
+// StreamingParser parses a response as it streams in. Text is held until it
+// can no longer change how it parses, then emitted once as completed blocks,
+// so each chunk only re-parses the unfinished tail.
+type StreamingParser struct {
+	options ParserOptions
+	pending string // text after the last completed block
+	offset  int    // where pending starts in the response, for block spans
+	emitted int    // completed blocks so far, for options.MaxBlocks
+}
+
+// StreamedBlocks is what a chunk adds to a streaming response
+type StreamedBlocks struct {
+	Completed []ContentBlock `json:"completed"` // newly finished blocks, in order
+	Partial   []ContentBlock `json:"partial"`   // the unfinished tail; replaces the previous partial
+}
+
+func NewStreamingParser(options ParserOptions) *StreamingParser {
+	return &StreamingParser{options: options}
+}
+
+// limit cuts blocks to what options.MaxBlocks leaves after the completed ones
+func (p *StreamingParser) limit(blocks []ContentBlock) []ContentBlock {
+	if n := p.options.MaxBlocks; n > 0 && p.emitted+len(blocks) > n {
+		return blocks[:max(n-p.emitted, 0)]
+	}
+	return blocks
+}
+
+// Write adds a chunk and returns the blocks it completed
+func (p *StreamingParser) Write(chunk string) []ContentBlock {
+	p.pending += chunk
+	cut := completedPrefix(p.pending)
+	if cut == 0 {
+		return make([]ContentBlock, 0)
+	}
+	completed := p.limit(parseSource(p.pending[:cut], p.offset, p.options))
+	p.pending = p.pending[cut:]
+	p.offset += cut
+	p.emitted += len(completed)
+	return completed
+}
+
+// Partial parses the text not yet completed; its last block may still grow
+func (p *StreamingParser) Partial() []ContentBlock {
+	return p.limit(parseSource(p.pending, p.offset, p.options))
+}
+
+// Finish returns the remaining blocks once the stream has ended
+func (p *StreamingParser) Finish() []ContentBlock {
+	blocks := p.Partial()
+	p.pending, p.offset, p.emitted = "", 0, 0
+	return blocks
+}
+
+// completedPrefix returns the length of the longest prefix of text whose blocks
+// are final: it ends before an unindented line that follows a blank line and
+// can't continue a list, or after an unindented code fence closes
+func completedPrefix(text string) int {
+	// Only whole lines count; the last line may still be streaming
+	end := strings.LastIndexByte(text, '\n')
+	if end < 0 {
+		return 0
+	}
+
+	cut, offset := 0, 0
+	var fence string
+	topLevelFence, blank := false, false
+	for _, line := range strings.Split(text[:end], "\n") {
+		trimmed := strings.TrimSpace(line)
+		unindented := trimmed != "" && leadingSpaces(line) == 0
+		switch {
+		case fence != "":
+			if closesFence(line, fence) {
+				fence = ""
+				if topLevelFence {
+					cut = offset + len(line) + 1
+				}
+			}
+		case strings.HasPrefix(trimmed, "```") || strings.HasPrefix(trimmed, "~~~"):
+			// No cut before the fence: a "File: ..." line above it belongs to the block
+			fence, topLevelFence = openingFence(trimmed), unindented
+		case blank && unindented && !listItemLine.MatchString(line):
+			cut = offset
+		}
+		blank = trimmed == "" && fence == ""
+		offset += len(line) + 1
+	}
+	return cut
+}
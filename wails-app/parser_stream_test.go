@@ -0,0 +1,55 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+func TestStreamingParserMatchesParseResponse(t *testing.T) {
+	doc := "# Title\n\nFirst paragraph\nstill first.\n\n```go\nfunc main() {\n\n}\n```\n\n- one\n\n- two\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\nLast words"
+	want := ParseResponse(doc)
+	for _, size := range []int{1, 3, 7, len(doc)} {
+		parser := NewStreamingParser(DefaultParserOptions())
+		var got []ContentBlock
+		for start := 0; start < len(doc); start += size {
+			got = append(got, parser.Write(doc[start:min(start+size, len(doc))])...)
+		}
+		got = append(got, parser.Finish()...)
+		if !reflect.DeepEqual(got, want) {
+			t.Errorf("in chunks of %d:\ngot  %+v\nwant %+v", size, got, want)
+		}
+	}
+}
+
+func TestStreamingParserWrite(t *testing.T) {
+	parser := NewStreamingParser(DefaultParserOptions())
+	steps := []struct {
+		chunk         string
+		wantCompleted []ContentBlock
+		wantPartial   []ContentBlock
+	}{
+		{
+			// Nothing is final until a whole line follows the blank one
+			chunk:         "# Title\n\nHalf",
+			wantCompleted: []ContentBlock{},
+			wantPartial:   []ContentBlock{{Type: BlockHeading, Level: 1, Text: "Title"}, {Type: BlockParagraph, Text: "Half"}},
+		},
+		{
+			chunk:         " done\n\nNext",
+			wantCompleted: []ContentBlock{{Type: BlockHeading, Level: 1, Text: "Title"}},
+			wantPartial:   []ContentBlock{{Type: BlockParagraph, Text: "Half done"}, {Type: BlockParagraph, Text: "Next"}},
+		},
+	}
+	for i, step := range steps {
+		if got := outline(parser.Write(step.chunk)); !reflect.DeepEqual(got, step.wantCompleted) {
+			t.Errorf("step %d: completed %+v, want %+v", i+1, got, step.wantCompleted)
+		}
+		if got := outline(parser.Partial()); !reflect.DeepEqual(got, step.wantPartial) {
+			t.Errorf("step %d: partial %+v, want %+v", i+1, got, step.wantPartial)
+		}
+	}
+	want := []ContentBlock{{Type: BlockParagraph, Text: "Half done"}, {Type: BlockParagraph, Text: "Next"}}
+	if got := outline(parser.Finish()); !reflect.DeepEqual(got, want) {
+		t.Errorf("finish: %+v, want %+v", got, want)
+	}
+}
@@ -0,0 +1,483 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+// outline drops what a test doesn't spell out: source spans, inline spans
+// and diff hunks, which have tests of their own
+func outline(blocks []ContentBlock) []ContentBlock {
+	if blocks == nil {
+		return nil
+	}
+	out := make([]ContentBlock, len(blocks))
+	for i, block := range blocks {
+		block.Source, block.Spans, block.Hunks = SourceSpan{}, nil, nil
+		block.Children = outline(block.Children)
+		if block.Items != nil {
+			items := make([]ListItem, len(block.Items))
+			for j, item := range block.Items {
+				item.Spans = nil
+				item.Children = outline(item.Children)
+				items[j] = item
+			}
+			block.Items = items
+		}
+		out[i] = block
+	}
+	return out
+}
+
+func TestParseResponseBlocks(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []ContentBlock
+	}{
+		{
+			name:     "headings and paragraphs",
+			markdown: "# Title\n\nSome text\nwrapped here.\n\n### Smaller ###",
+			want: []ContentBlock{
+				{Type: BlockHeading, Level: 1, Text: "Title"},
+				{Type: BlockParagraph, Text: "Some text wrapped here."},
+				{Type: BlockHeading, Level: 3, Text: "Smaller"},
+			},
+		},
+		{
+			name:     "table rows fitted to the header",
+			markdown: "| a | b | c |\n|:--|:-:|--:|\n| 1 | 2 |\n| 3 | 4 | 5 | 6 |",
+			want: []ContentBlock{{
+				Type:    BlockTable,
+				Headers: []string{"a", "b", "c"},
+				Align:   []string{"left", "center", "right"},
+				Rows:    [][]string{{"1", "2", ""}, {"3", "4", "5"}},
+			}},
+		},
+		{
+			name:     "escaped bar in a cell",
+			markdown: "| a | b |\n|---|---|\n| `x \\| y` | z |",
+			want: []ContentBlock{{
+				Type:    BlockTable,
+				Headers: []string{"a", "b"},
+				Align:   []string{"", ""},
+				Rows:    [][]string{{"`x | y`", "z"}},
+			}},
+		},
+		{
+			name:     "fenced code",
+			markdown: "```python\nprint(1)\n```",
+			want:     []ContentBlock{{Type: BlockCode, Language: "python", Code: "print(1)"}},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := outline(ParseResponse(tt.markdown)); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got  %+v\nwant %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseLists(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []ContentBlock
+	}{
+		{
+			name:     "nested",
+			markdown: "- one\n- two\n  - inner\n  - inner two\n- three",
+			want: []ContentBlock{{Type: BlockList, Items: []ListItem{
+				{Text: "one"},
+				{Text: "two", Children: []ContentBlock{{Type: BlockList, Items: []ListItem{{Text: "inner"}, {Text: "inner two"}}}}},
+				{Text: "three"},
+			}}},
+		},
+		{
+			name:     "ordered from its first number",
+			markdown: "3. three\n4. four",
+			want:     []ContentBlock{{Type: BlockList, Ordered: true, Start: 3, Items: []ListItem{{Text: "three"}, {Text: "four"}}}},
+		},
+		{
+			name:     "item with a second paragraph",
+			markdown: "1. first\n\n   more about first\n2. second",
+			want: []ContentBlock{{Type: BlockList, Ordered: true, Start: 1, Items: []ListItem{
+				{Text: "first", Children: []ContentBlock{{Type: BlockParagraph, Text: "more about first"}}},
+				{Text: "second"},
+			}}},
+		},
+		{
+			name:     "lazy continuation",
+			markdown: "- a long\nitem text\n- next",
+			want:     []ContentBlock{{Type: BlockList, Items: []ListItem{{Text: "a long item text"}, {Text: "next"}}}},
+		},
+		{
+			name:     "a new marker starts a new list",
+			markdown: "- a\n1. b",
+			want: []ContentBlock{
+				{Type: BlockList, Items: []ListItem{{Text: "a"}}},
+				{Type: BlockList, Ordered: true, Start: 1, Items: []ListItem{{Text: "b"}}},
+			},
+		},
+		{
+			name:     "ended by a paragraph",
+			markdown: "- a\n\nafter",
+			want: []ContentBlock{
+				{Type: BlockList, Items: []ListItem{{Text: "a"}}},
+				{Type: BlockParagraph, Text: "after"},
+			},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := outline(ParseResponse(tt.markdown)); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got  %+v\nwant %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseTaskLists(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []ContentBlock
+	}{
+		{
+			name:     "checked and unchecked",
+			markdown: "- [x] done\n- [ ] todo\n- [X] also done",
+			want: []ContentBlock{{Type: BlockTaskList, Items: []ListItem{
+				{Text: "done", Checked: true},
+				{Text: "todo"},
+				{Text: "also done", Checked: true},
+			}}},
+		},
+		{
+			// Only a list whose every item has a box is a task list
+			name:     "mixed with plain items",
+			markdown: "- [x] done\n- plain",
+			want:     []ContentBlock{{Type: BlockList, Items: []ListItem{{Text: "[x] done"}, {Text: "plain"}}}},
+		},
+		{
+			name:     "ordered",
+			markdown: "1. [ ] first",
+			want:     []ContentBlock{{Type: BlockTaskList, Ordered: true, Start: 1, Items: []ListItem{{Text: "first"}}}},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := outline(ParseResponse(tt.markdown)); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got  %+v\nwant %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseFilePaths(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []ContentBlock // Type, Language and FilePath only
+	}{
+		{"info string", "```rust title=src/main.rs\nfn main() {}\n```", []ContentBlock{{Type: BlockCode, Language: "rust", FilePath: "src/main.rs"}}},
+		{"annotation line folded in", "File: app.py\n```python\nprint(1)\n```", []ContentBlock{{Type: BlockCode, Language: "python", FilePath: "app.py"}}},
+		{"first line comment", "```js\n// src/index.js\nconsole.log(1)\n```", []ContentBlock{{Type: BlockCode, Language: "javascript", FilePath: "src/index.js"}}},
+		{
+			name:     "prose before the fence stays",
+			markdown: "Here is the fix:\n```go\nx := 1\n```",
+			want:     []ContentBlock{{Type: BlockParagraph}, {Type: BlockCode, Language: "go"}},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			var got []ContentBlock
+			for _, block := range ParseResponse(tt.markdown) {
+				got = append(got, ContentBlock{Type: block.Type, Language: block.Language, FilePath: block.FilePath})
+			}
+			if !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %+v, want %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseMathBlocks(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []ContentBlock
+	}{
+		{"dollars", "$$\nE = mc^2\n$$", []ContentBlock{{Type: BlockMath, Code: "E = mc^2"}}},
+		{"brackets on one line", "\\[ x^2 \\]", []ContentBlock{{Type: BlockMath, Code: "x^2"}}},
+		{
+			name:     "between paragraphs",
+			markdown: "text\n$$a$$\nmore",
+			want: []ContentBlock{
+				{Type: BlockParagraph, Text: "text"},
+				{Type: BlockMath, Code: "a"},
+				{Type: BlockParagraph, Text: "more"},
+			},
+		},
+		{"never closed", "$$ x\ny", []ContentBlock{{Type: BlockParagraph, Text: "$$ x y"}}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := outline(ParseResponse(tt.markdown)); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got  %+v\nwant %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseBlockquotes(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []ContentBlock
+	}{
+		{
+			name:     "paragraphs and a nested quote",
+			markdown: "> first\n> line\n>\n> > nested\n\nafter",
+			want: []ContentBlock{
+				{Type: BlockQuote, Children: []ContentBlock{
+					{Type: BlockParagraph, Text: "first line"},
+					{Type: BlockQuote, Children: []ContentBlock{{Type: BlockParagraph, Text: "nested"}}},
+				}},
+				{Type: BlockParagraph, Text: "after"},
+			},
+		},
+		{
+			name:     "code in a quote",
+			markdown: "> ```go\n> x := 1\n> ```",
+			want: []ContentBlock{{Type: BlockQuote, Children: []ContentBlock{
+				{Type: BlockCode, Language: "go", Code: "x := 1"},
+			}}},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := outline(ParseResponse(tt.markdown)); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got  %+v\nwant %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseRulesAndImages(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []ContentBlock
+	}{
+		{
+			name:     "rules",
+			markdown: "before\n\n---\n\n* * *\n\n___",
+			want: []ContentBlock{
+				{Type: BlockParagraph, Text: "before"},
+				{Type: BlockRule},
+				{Type: BlockRule},
+				{Type: BlockRule},
+			},
+		},
+		{
+			name:     "image with a title",
+			markdown: "![a chart](chart.png \"Title\")",
+			want:     []ContentBlock{{Type: BlockImage, URL: "chart.png", Alt: "a chart"}},
+		},
+		{
+			name:     "image inside text",
+			markdown: "see ![inline](i.png) here",
+			want:     []ContentBlock{{Type: BlockParagraph, Text: "see inline here"}},
+		},
+		{
+			name:     "image followed by text",
+			markdown: "![alt](x.png) trailing",
+			want:     []ContentBlock{{Type: BlockParagraph, Text: "alt trailing"}},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := outline(ParseResponse(tt.markdown)); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got  %+v\nwant %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseSourceSpans(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []string // each block's source
+	}{
+		{
+			name:     "blocks",
+			markdown: "# T\n\npara one\npara two\n\n```\ncode\n```\n\n> quoted\n\n- a\n- b",
+			want:     []string{"# T", "para one\npara two", "```\ncode\n```", "> quoted", "- a\n- b"},
+		},
+		{"carriage returns", "a\r\nb\r\n\r\nc", []string{"a\r\nb", "c"}},
+		{"unclosed fence", "text\n\n```go\nx := 1", []string{"text", "```go\nx := 1"}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			var got []string
+			for _, block := range ParseResponse(tt.markdown) {
+				got = append(got, tt.markdown[block.Source.Start:block.Source.End])
+			}
+			if !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %q, want %q", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseSourceSpansNested(t *testing.T) {
+	quote := "> quoted **text**"
+	if child := ParseResponse(quote)[0].Children[0]; quote[child.Source.Start:child.Source.End] != "quoted **text**" {
+		t.Errorf("quoted paragraph spans %q", quote[child.Source.Start:child.Source.End])
+	}
+	list := "1. first\n\n   more"
+	if child := ParseResponse(list)[0].Items[0].Children[0]; list[child.Source.Start:child.Source.End] != "more" {
+		t.Errorf("paragraph in a list item spans %q", list[child.Source.Start:child.Source.End])
+	}
+}
+
+func TestParseFootnotesAndDefinitions(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []ContentBlock
+	}{
+		{
+			// A reference reads as its label in the plain text
+			name:     "footnote",
+			markdown: "Text with a note[^1].\n\n[^1]: The note\n  continues here.",
+			want: []ContentBlock{
+				{Type: BlockParagraph, Text: "Text with a note1."},
+				{Type: BlockFootnote, Label: "1", Children: []ContentBlock{{Type: BlockParagraph, Text: "The note continues here."}}},
+			},
+		},
+		{
+			name:     "definition list",
+			markdown: "Term\n: first definition\n: second\nOther term\n: its definition",
+			want: []ContentBlock{{Type: BlockDefList, Items: []ListItem{
+				{Text: "Term", Children: []ContentBlock{{Type: BlockParagraph, Text: "first definition"}, {Type: BlockParagraph, Text: "second"}}},
+				{Text: "Other term", Children: []ContentBlock{{Type: BlockParagraph, Text: "its definition"}}},
+			}}},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := outline(ParseResponse(tt.markdown)); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got  %+v\nwant %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseFootnoteReference(t *testing.T) {
+	spans := ParseResponse("Text with a note[^1].")[0].Spans
+	want := []InlineSpan{{Type: SpanText, Text: "Text with a note"}, {Type: SpanFootnote, Text: "1"}, {Type: SpanText, Text: "."}}
+	if !reflect.DeepEqual(spans, want) {
+		t.Errorf("got %+v, want %+v", spans, want)
+	}
+}
+
+func TestParseDiagrams(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []ContentBlock // Type, Language and Kind only
+	}{
+		{"mermaid", "```mermaid\ngraph TD; A-->B\n```", []ContentBlock{{Type: BlockDiagram, Language: "mermaid", Kind: "mermaid"}}},
+		{"dot", "```dot\ndigraph { a -> b }\n```", []ContentBlock{{Type: BlockDiagram, Language: "dot", Kind: "graphviz"}}},
+		{"graphviz alias", "```graphviz\ndigraph { a -> b }\n```", []ContentBlock{{Type: BlockDiagram, Language: "dot", Kind: "graphviz"}}},
+		{"plantuml alias", "```puml\n@startuml\nA -> B\n@enduml\n```", []ContentBlock{{Type: BlockDiagram, Language: "plantuml", Kind: "plantuml"}}},
+		{"other code", "```go\nx := 1\n```", []ContentBlock{{Type: BlockCode, Language: "go"}}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			var got []ContentBlock
+			for _, block := range ParseResponse(tt.markdown) {
+				got = append(got, ContentBlock{Type: block.Type, Language: block.Language, Kind: block.Kind})
+			}
+			if !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %+v, want %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseResponseWithOptions(t *testing.T) {
+	table := "| a |\n|---|\n| 1 |"
+	tests := []struct {
+		name     string
+		markdown string
+		opts     ParserOptions
+		want     []ContentBlock
+	}{
+		{"tables on", table, DefaultParserOptions(), []ContentBlock{{Type: BlockTable, Headers: []string{"a"}, Align: []string{""}, Rows: [][]string{{"1"}}}}},
+		{"tables off", table, ParserOptions{}, []ContentBlock{{Type: BlockParagraph, Text: "| a | |---| | 1 |"}}},
+		{"footnotes off", "[^1]: note", ParserOptions{}, []ContentBlock{{Type: BlockParagraph, Text: "[^1]: note"}}},
+		{"strikethrough on", "~~gone~~", DefaultParserOptions(), []ContentBlock{{Type: BlockParagraph, Text: "gone"}}},
+		{"strikethrough off", "~~gone~~", ParserOptions{}, []ContentBlock{{Type: BlockParagraph, Text: "~~gone~~"}}},
+		{"smart punctuation", `"Hi" -- it's...`, ParserOptions{SmartPunctuation: true}, []ContentBlock{{Type: BlockParagraph, Text: "“Hi” – it’s…"}}},
+		{"max blocks", "a\n\nb\n\nc", ParserOptions{MaxBlocks: 2}, []ContentBlock{{Type: BlockParagraph, Text: "a"}, {Type: BlockParagraph, Text: "b"}}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := outline(ParseResponseWith(tt.markdown, tt.opts)); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got  %+v\nwant %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseResponseFences(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []ContentBlock // Type, Language and Code only
+	}{
+		{
+			name:     "longer fence holds a shorter one",
+			markdown: "````markdown\n```go\nx := 1\n```\n````",
+			want:     []ContentBlock{{Type: BlockCode, Language: "markdown", Code: "```go\nx := 1\n```"}},
+		},
+		{
+			name:     "tildes aren't closed by backticks",
+			markdown: "~~~python\nprint(1)\n```\n~~~",
+			want:     []ContentBlock{{Type: BlockCode, Language: "python", Code: "print(1)\n```"}},
+		},
+		{
+			name:     "a longer closing fence closes",
+			markdown: "```go\nx := 1\n`````\n\nafter",
+			want: []ContentBlock{
+				{Type: BlockCode, Language: "go", Code: "x := 1"},
+				{Type: BlockParagraph},
+			},
+		},
+		{
+			name:     "a fence with text after it doesn't close",
+			markdown: "```go\nx := 1\n``` not yet\n```",
+			want:     []ContentBlock{{Type: BlockCode, Language: "go", Code: "x := 1\n``` not yet"}},
+		},
+		{
+			name:     "an unclosed fence runs to the end",
+			markdown: "```go\nx := 1\ny := 2",
+			want:     []ContentBlock{{Type: BlockCode, Language: "go", Code: "x := 1\ny := 2"}},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			var got []ContentBlock
+			for _, block := range ParseResponse(tt.markdown) {
+				got = append(got, ContentBlock{Type: block.Type, Language: block.Language, Code: block.Code})
+			}
+			if !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %+v, want %+v", got, tt.want)
+			}
+		})
+	}
+}
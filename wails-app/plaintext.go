@@ -0,0 +1,93 @@
+package main
+
+import (
+	"regexp"
+	"strings"
+)
+
+const (
+	TextMarkdown = "markdown" // the default
+	TextPlain    = "plain"    // plain prose or console output, never read as markdown
+)
+
+// ansiEscape matches CSI sequences (colors, cursor movement), OSC sequences
+// (titles, hyperlinks) and the remaining two-byte escapes
+var ansiEscape = regexp.MustCompile(`\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)|\x1b[@-Z\\-_]`)
+
+// StripANSI removes terminal escape sequences. Text a carriage return
+// overwrote, like the earlier frames of a progress bar, is dropped too.
+func StripANSI(text string) string {
+	lines := strings.Split(ansiEscape.ReplaceAllString(text, ""), "\n")
+	for i, line := range lines {
+		line = strings.TrimSuffix(line, "\r")
+		if cr := strings.LastIndexByte(line, '\r'); cr >= 0 {
+			line = line[cr+1:]
+		}
+		lines[i] = line
+	}
+	return strings.Join(lines, "\n")
+}
+
+// ParsePlaintext splits a response that isn't markdown into blocks at blank
+// lines. Prose becomes paragraphs with lines rejoined; anything laid out with
+// indentation or aligned columns is kept verbatim as a "text" code block.
+func ParsePlaintext(text string) []ContentBlock {
+	lines := strings.Split(text, "\n")
+	offsets := make([]int, len(lines))
+	for i := 1; i < len(lines); i++ {
+		offsets[i] = offsets[i-1] + len(lines[i-1]) + 1
+	}
+
+	blocks := make([]ContentBlock, 0)
+	for i := 0; i < len(lines); i++ {
+		if strings.TrimSpace(StripANSI(lines[i])) == "" {
+			continue
+		}
+		first := i
+		var group []string
+		for ; i < len(lines); i++ {
+			line := StripANSI(lines[i])
+			if strings.TrimSpace(line) == "" {
+				break
+			}
+			group = append(group, strings.TrimRight(line, " "))
+		}
+		last := i - 1
+		source := SourceSpan{Start: offsets[first], End: offsets[last] + len(strings.TrimSuffix(lines[last], "\r"))}
+
+		if laidOut(group) {
+			blocks = append(blocks, ContentBlock{Type: BlockCode, Source: source, Language: "text", Code: strings.Join(group, "\n")})
+			continue
+		}
+		for j, line := range group {
+			group[j] = strings.TrimSpace(line)
+		}
+		paragraph := strings.Join(group, " ")
+		blocks = append(blocks, ContentBlock{
+			Type:   BlockParagraph,
+			Source: source,
+			Text:   paragraph,
+			Spans:  []InlineSpan{{Type: SpanText, Text: paragraph}},
+		})
+	}
+	return blocks
+}
+
+// laidOut reports whether whitespace in lines carries meaning: indentation,
+// tabs or runs of spaces lining up columns
+func laidOut(lines []string) bool {
+	for _, line := range lines {
+		if strings.HasPrefix(line, " ") || strings.Contains(line, "\t") || strings.Contains(strings.TrimSpace(line), "   ") {
+			return true
+		}
+	}
+	return false
+}
+
+// ParseResponseAs parses a response in the given text format
+func ParseResponseAs(response, format string) []ContentBlock {
+	if format == TextPlain {
+		return ParsePlaintext(response)
+	}
+	return ParseResponse(response)
+}
@@ -0,0 +1,82 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+func TestStripANSI(t *testing.T) {
+	tests := []struct {
+		name string
+		text string
+		want string
+	}{
+		{"colors", "\x1b[31mred\x1b[0m and \x1b[1;32mgreen\x1b[m", "red and green"},
+		{"progress overwritten by carriage returns", "50%\r100%\nok", "100%\nok"},
+		{"window title", "\x1b]0;build\x07done", "done"},
+		{"CRLF line endings", "a\r\nb\r\n", "a\nb\n"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := StripANSI(tt.text); got != tt.want {
+				t.Errorf("got %q, want %q", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParsePlaintext(t *testing.T) {
+	tests := []struct {
+		name string
+		text string
+		want []ContentBlock // Type, Text, Language and Code only
+	}{
+		{
+			name: "prose and layout",
+			text: "First line\nsecond line\n\n  indented\n  block\n\ncol1   col2",
+			want: []ContentBlock{
+				{Type: BlockParagraph, Text: "First line second line"},
+				{Type: BlockCode, Language: "text", Code: "  indented\n  block"},
+				{Type: BlockCode, Language: "text", Code: "col1   col2"},
+			},
+		},
+		{
+			name: "escapes are stripped",
+			text: "\x1b[1mBold\x1b[0m text",
+			want: []ContentBlock{{Type: BlockParagraph, Text: "Bold text"}},
+		},
+		{
+			name: "markdown stays text",
+			text: "# not a heading\n- nor a list",
+			want: []ContentBlock{{Type: BlockParagraph, Text: "# not a heading - nor a list"}},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			var got []ContentBlock
+			for _, block := range ParsePlaintext(tt.text) {
+				got = append(got, ContentBlock{Type: block.Type, Text: block.Text, Language: block.Language, Code: block.Code})
+			}
+			if !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %+v, want %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseResponseAs(t *testing.T) {
+	tests := []struct {
+		format string
+		want   string
+	}{
+		{TextPlain, BlockParagraph},
+		{TextMarkdown, BlockHeading},
+		{"", BlockHeading},
+	}
+	for _, tt := range tests {
+		blocks := ParseResponseAs("# Title", tt.format)
+		if len(blocks) != 1 || blocks[0].Type != tt.want {
+			t.Errorf("format %q: got %+v, want one %s block", tt.format, blocks, tt.want)
+		}
+	}
+}
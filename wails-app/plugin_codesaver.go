@@ -0,0 +1,180 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+	"sync"
+)
+
+// languageExtensions names files for code blocks the response gave no path
+var languageExtensions = map[string]string{
+	"bash":        ".sh",
+	"c":           ".c",
+	"cpp":         ".cpp",
+	"csharp":      ".cs",
+	"css":         ".css",
+	"dot":         ".dot",
+	"go":          ".go",
+	"hcl":         ".tf",
+	"html":        ".html",
+	"java":        ".java",
+	"javascript":  ".js",
+	"json":        ".json",
+	"kotlin":      ".kt",
+	"markdown":    ".md",
+	"mermaid":     ".mmd",
+	"objective-c": ".m",
+	"php":         ".php",
+	"plantuml":    ".puml",
+	"powershell":  ".ps1",
+	"python":      ".py",
+	"ruby":        ".rb",
+	"rust":        ".rs",
+	"sql":         ".sql",
+	"swift":       ".swift",
+	"toml":        ".toml",
+	"typescript":  ".ts",
+	"xml":         ".xml",
+	"yaml":        ".yaml",
+}
+
+// CodeSaverConfig is the options of CodeSaverPlugin
+type CodeSaverConfig struct {
+	OutputDir string `json:"outputDir"` // created as needed; ~/ is the home directory
+	Overwrite bool   `json:"overwrite"` // replace files of the same name rather than numbering new ones
+}
+
+// DefaultCodeSaverConfig saves into the app's directory without overwriting
+func DefaultCodeSaverConfig() CodeSaverConfig {
+	return CodeSaverConfig{OutputDir: appDataPath("saved-code")}
+}
+
+// CodeSaverPlugin writes the code blocks of each response to files under
+// its output directory and lists where at the end of the response. A block
+// is saved under the path the response gave it, as long as that stays
+// inside the directory, or else as snippet-N with its language's extension.
+type CodeSaverPlugin struct {
+	mu     sync.Mutex // also keeps two responses from picking the same new name
+	config CodeSaverConfig
+}
+
+func NewCodeSaverPlugin() *CodeSaverPlugin {
+	return &CodeSaverPlugin{config: DefaultCodeSaverConfig()}
+}
+
+func (p *CodeSaverPlugin) Metadata() PluginMetadata {
+	return PluginMetadata{
+		Name:        "code-saver",
+		Version:     "1.0.0",
+		Description: "Saves the code blocks of each response as files and lists where",
+	}
+}
+
+func (p *CodeSaverPlugin) Configure(config json.RawMessage) error {
+	next := DefaultCodeSaverConfig()
+	if err := json.Unmarshal(config, &next); err != nil {
+		return err
+	}
+	if next.OutputDir == "" {
+		return errors.New("outputDir is required")
+	}
+	if rest, ok := strings.CutPrefix(next.OutputDir, "~/"); ok {
+		home, err := os.UserHomeDir()
+		if err != nil {
+			return err
+		}
+		next.OutputDir = filepath.Join(home, rest)
+	}
+	dir, err := filepath.Abs(next.OutputDir)
+	if err != nil {
+		return err
+	}
+	next.OutputDir = dir
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	p.config = next
+	return nil
+}
+
+func (p *CodeSaverPlugin) PostProcess(response string) (string, error) {
+	p.mu.Lock()
+	defer p.mu.Unlock()
+
+	var saved []string
+	var err error
+	walkBlocks(ParseResponse(response), func(block ContentBlock) {
+		if err != nil || (block.Type != BlockCode && block.Type != BlockDiagram) || strings.TrimSpace(block.Code) == "" {
+			return
+		}
+		var path string
+		path, err = p.write(codeFileName(block, len(saved)+1), block.Code)
+		if err == nil {
+			saved = append(saved, path)
+		}
+	})
+	if err != nil {
+		return response, err
+	}
+	if len(saved) == 0 {
+		return response, nil
+	}
+
+	var report strings.Builder
+	report.WriteString(strings.TrimRight(response, "\n"))
+	report.WriteString("\n\n---\n\nSaved to disk:\n")
+	for _, path := range saved {
+		fmt.Fprintf(&report, "\n- `%s`", path)
+	}
+	report.WriteString("\n")
+	return report.String(), nil
+}
+
+// codeFileName is the name, relative to the output directory, a block is
+// saved as
+func codeFileName(block ContentBlock, index int) string {
+	if block.FilePath != "" {
+		path := filepath.Clean(filepath.FromSlash(strings.ReplaceAll(block.FilePath, `\`, "/")))
+		if filepath.IsLocal(path) {
+			return path
+		}
+		// An absolute or escaping path keeps just its file name
+		if base := filepath.Base(path); filepath.IsLocal(base) {
+			return base
+		}
+	}
+	extension, ok := languageExtensions[normalizeLanguage(block.Language)]
+	if !ok {
+		extension = ".txt"
+	}
+	return fmt.Sprintf("snippet-%d%s", index, extension)
+}
+
+// write saves code as name in the output directory, numbering the name
+// when a file has it already and overwriting is off; the caller holds p.mu
+func (p *CodeSaverPlugin) write(name, code string) (string, error) {
+	path := filepath.Join(p.config.OutputDir, name)
+	if !p.config.Overwrite {
+		extension := filepath.Ext(path)
+		stem := strings.TrimSuffix(path, extension)
+		for n := 2; ; n++ {
+			if _, err := os.Stat(path); err != nil {
+				break // free, or unreadable, in which case writing reports why
+			}
+			path = fmt.Sprintf("%s-%d%s", stem, n, extension)
+		}
+	}
+	if err := os.MkdirAll(filepath.Dir(path), 0o755); err != nil {
+		return "", err
+	}
+	if !strings.HasSuffix(code, "\n") {
+		code += "\n"
+	}
+	if err := os.WriteFile(path, []byte(code), 0o644); err != nil {
+		return "", err
+	}
+	return path, nil
+}
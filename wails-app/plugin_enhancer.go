@@ -0,0 +1,134 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"regexp"
+	"strings"
+	"sync"
+	"text/template"
+)
+
+// defaultEnhancerTemplate lays a terse prompt out as a structured request
+const defaultEnhancerTemplate = `Task: {{.Prompt}}
+{{- if .Language}}
+Language: {{.Language}}
+{{- end}}
+{{- if .Constraints}}
+Constraints:
+{{- range .Constraints}}
+- {{.}}
+{{- end}}
+{{- end}}
+{{- if .Format}}
+Output format: {{.Format}}
+{{- end}}`
+
+// PromptEnhancerConfig is the options of PromptEnhancerPlugin. Fields left
+// out of a configuration keep their defaults.
+type PromptEnhancerConfig struct {
+	// A text/template executed with .Prompt, .Language, .Constraints and .Format
+	Template    string   `json:"template"`
+	Language    string   `json:"language"`    // used when the prompt doesn't name one
+	Constraints []string `json:"constraints"` // listed under the task
+	Format      string   `json:"format"`      // how the answer should be laid out
+	MaxWords    int      `json:"maxWords"`    // longer prompts are taken as already specific
+}
+
+// DefaultPromptEnhancerConfig asks for idiomatic, careful code with an explanation
+func DefaultPromptEnhancerConfig() PromptEnhancerConfig {
+	return PromptEnhancerConfig{
+		Template: defaultEnhancerTemplate,
+		Constraints: []string{
+			"Follow the language's idioms and prefer its standard library",
+			"Handle errors and edge cases",
+		},
+		Format:   "a short explanation, then the complete code in one fenced block labelled with its language",
+		MaxWords: 30,
+	}
+}
+
+// promptLanguagePattern finds a language named as "in Go" or "using python"
+var promptLanguagePattern = regexp.MustCompile(`(?i)\b(?:in|using|with)\s+([\w#+]+)`)
+
+// promptLanguages are the languages a prompt can name, as normalizeLanguage
+// spells them
+var promptLanguages = map[string]bool{
+	"bash": true, "c": true, "cpp": true, "csharp": true, "go": true, "html": true,
+	"java": true, "javascript": true, "kotlin": true, "php": true, "powershell": true,
+	"python": true, "ruby": true, "rust": true, "sql": true, "swift": true, "typescript": true,
+}
+
+// PromptEnhancerPlugin rewrites terse one-line prompts into structured
+// requests naming the language, constraints and output format, so a quick
+// "parse a csv in go" gets a considered answer. Longer or multi-line
+// prompts are left alone.
+type PromptEnhancerPlugin struct {
+	mu       sync.RWMutex
+	config   PromptEnhancerConfig
+	template *template.Template
+}
+
+func NewPromptEnhancerPlugin() *PromptEnhancerPlugin {
+	config := DefaultPromptEnhancerConfig()
+	return &PromptEnhancerPlugin{
+		config:   config,
+		template: template.Must(template.New("enhancer").Parse(config.Template)),
+	}
+}
+
+func (p *PromptEnhancerPlugin) Metadata() PluginMetadata {
+	return PluginMetadata{
+		Name:        "prompt-enhancer",
+		Version:     "1.0.0",
+		Description: "Expands terse prompts into structured requests with language, constraints and output format",
+	}
+}
+
+func (p *PromptEnhancerPlugin) Configure(config json.RawMessage) error {
+	next := DefaultPromptEnhancerConfig()
+	if err := json.Unmarshal(config, &next); err != nil {
+		return err
+	}
+	if next.MaxWords <= 0 {
+		return fmt.Errorf("maxWords must be positive, not %d", next.MaxWords)
+	}
+	parsed, err := template.New("enhancer").Parse(next.Template)
+	if err != nil {
+		return fmt.Errorf("template: %v", err)
+	}
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	p.config, p.template = next, parsed
+	return nil
+}
+
+func (p *PromptEnhancerPlugin) PreProcess(prompt string) (string, error) {
+	p.mu.RLock()
+	config, tmpl := p.config, p.template
+	p.mu.RUnlock()
+
+	trimmed := strings.TrimSpace(prompt)
+	if trimmed == "" || strings.Contains(trimmed, "\n") || len(strings.Fields(trimmed)) > config.MaxWords {
+		return prompt, nil
+	}
+	language := config.Language
+	for _, match := range promptLanguagePattern.FindAllStringSubmatch(trimmed, -1) {
+		if named := normalizeLanguage(match[1]); promptLanguages[named] {
+			language = named
+			break
+		}
+	}
+
+	var enhanced strings.Builder
+	err := tmpl.Execute(&enhanced, struct {
+		Prompt      string
+		Language    string
+		Constraints []string
+		Format      string
+	}{trimmed, language, config.Constraints, config.Format})
+	if err != nil {
+		return prompt, err
+	}
+	return enhanced.String(), nil
+}
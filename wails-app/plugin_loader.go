@@ -0,0 +1,531 @@
+package main
+
+import (
+	"bytes"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"runtime"
+	"strings"
+	"sync"
+)
+
+// PluginABIVersion is the version of the C ABI below. Libraries built for
+// another version are refused rather than called with the wrong signatures.
+//
+// A dynamic plugin is a shared library exporting, with C linkage:
+//
+//	uint32_t    vibe_plugin_abi_version(void);              // returns PluginABIVersion
+//	const char *vibe_plugin_metadata(void);                 // JSON PluginMetadata; owned by the plugin
+//	void       *vibe_plugin_create(void);                   // the constructor; NULL on failure
+//	void        vibe_plugin_free_string(char *s);           // frees strings the plugin returned
+//
+// and optionally:
+//
+//	char       *vibe_plugin_pre_process(void *plugin, const char *prompt);
+//	char       *vibe_plugin_post_process(void *plugin, const char *response);
+//	char       *vibe_plugin_process_blocks(void *plugin, const char *blocks); // JSON []ContentBlock in and out
+//	char       *vibe_plugin_run_command(void *plugin, const char *command); // {"name", "args"} in, markdown out
+//	int32_t     vibe_plugin_configure(void *plugin, const char *config); // JSON options; 0 on success
+//	int32_t     vibe_plugin_on_event(void *plugin, const char *event); // JSON PluginEvent; 0 on success
+//	const char *vibe_plugin_last_error(void *plugin);       // why the last call failed
+//	void        vibe_plugin_destroy(void *plugin);
+//
+// Strings are NUL-terminated UTF-8. The process functions return a new string
+// for the host to free with vibe_plugin_free_string, or NULL on failure.
+// vibe_plugin_configure is called with the plugin's saved options, if any,
+// before its first use and again whenever they change.
+// Commands are declared in the metadata's "commands" list.
+// vibe_plugin_on_event hears the app's session, response, export and shutdown events.
+// Calls into one plugin are never made concurrently.
+const PluginABIVersion = 1
+
+const (
+	pluginSymbolABIVersion  = "vibe_plugin_abi_version"
+	pluginSymbolMetadata    = "vibe_plugin_metadata"
+	pluginSymbolCreate      = "vibe_plugin_create"
+	pluginSymbolFreeString  = "vibe_plugin_free_string"
+	pluginSymbolPreProcess  = "vibe_plugin_pre_process"
+	pluginSymbolPostProcess = "vibe_plugin_post_process"
+	pluginSymbolBlocks      = "vibe_plugin_process_blocks"
+	pluginSymbolRunCommand  = "vibe_plugin_run_command"
+	pluginSymbolConfigure   = "vibe_plugin_configure"
+	pluginSymbolOnEvent     = "vibe_plugin_on_event"
+	pluginSymbolLastError   = "vibe_plugin_last_error"
+	pluginSymbolDestroy     = "vibe_plugin_destroy"
+	pluginSymbolAlloc       = "vibe_plugin_alloc" // WASM only
+	pluginSymbolFree        = "vibe_plugin_free"  // WASM only
+)
+
+// A WASM plugin is a module exporting its linear memory as "memory" and:
+//
+//	vibe_plugin_abi_version() -> i32       ; returns PluginABIVersion
+//	vibe_plugin_metadata() -> i64          ; JSON PluginMetadata
+//	vibe_plugin_alloc(len: i32) -> i32     ; room for the host to write input into
+//
+// and optionally vibe_plugin_pre_process, vibe_plugin_post_process,
+// vibe_plugin_process_blocks and vibe_plugin_run_command, all
+// (ptr: i32, len: i32) -> i64, vibe_plugin_configure and vibe_plugin_on_event,
+// both (ptr: i32, len: i32) -> i32 (0 on success), vibe_plugin_last_error() -> i64
+// and vibe_plugin_free(ptr: i32, len: i32). Strings are UTF-8 and returned
+// packed as ptr<<32 | len, with 0 meaning failure. Input belongs to the guest
+// once passed in; returned strings are handed back to vibe_plugin_free.
+//
+// Modules run sandboxed: they may import WASI but get no arguments,
+// environment, stdio or network, and no filesystem beyond the directories a
+// WasmGrant names. Each call has a fuel budget, so a runaway plugin traps
+// instead of hanging the app.
+
+// WasmGrant is what a WASM plugin may reach outside its sandbox
+type WasmGrant struct {
+	Dirs     []string `json:"dirs"`     // host directories, mounted at the same path in the guest
+	ReadOnly bool     `json:"readOnly"` // open the directories for reading only
+}
+
+// pluginLibrary is an opened plugin speaking the ABI: a shared library,
+// opened per platform by openPluginLibrary, a WASM module from
+// openWasmLibrary, or a Lua script from openLuaScript
+type pluginLibrary interface {
+	Has(symbol string) bool
+	ABIVersion() uint32 // 0 when the library doesn't export one
+	// Create reads the metadata and constructs the plugin instance
+	Create() (metadata string, err error)
+	// Process calls a pre or post processing symbol with the instance
+	Process(symbol, text string) (string, error)
+	// Send passes JSON to a symbol returning a status, such as vibe_plugin_configure
+	Send(symbol, text string) error
+	Close()
+}
+
+// pluginWasmExtension marks WASM plugins, loaded the same way on every platform
+const pluginWasmExtension = ".wasm"
+
+// pluginScriptExtension marks the scripts LoadScripts runs
+const pluginScriptExtension = ".lua"
+
+// pluginLibraryExtension is the shared library extension of this platform
+func pluginLibraryExtension() string {
+	switch runtime.GOOS {
+	case "windows":
+		return ".dll"
+	case "darwin":
+		return ".dylib"
+	default:
+		return ".so"
+	}
+}
+
+// GrantWasm gives the WASM plugin in file (a name in the plugins directory)
+// access beyond its sandbox. Grants apply when LoadDir next loads it.
+func (r *PluginRegistry) GrantWasm(file string, grant WasmGrant) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	r.grants[file] = grant
+}
+
+// LoadDir loads and registers every shared library and WASM module in dir,
+// in name order. A missing dir is not an error; a plugin that fails to load
+// is skipped and reported, without stopping the rest.
+func (r *PluginRegistry) LoadDir(dir string) []error {
+	entries, err := os.ReadDir(dir)
+	if errors.Is(err, os.ErrNotExist) {
+		return nil
+	}
+	if err != nil {
+		return []error{err}
+	}
+	var errs []error
+	var loaded []loadedPlugin
+	for _, entry := range entries {
+		if entry.IsDir() || !isPluginFile(entry.Name()) {
+			continue
+		}
+		plugin, err := r.open(filepath.Join(dir, entry.Name()))
+		if err != nil {
+			errs = append(errs, &PluginError{Plugin: entry.Name(), Err: err})
+			continue
+		}
+		loaded = append(loaded, loadedPlugin{file: entry.Name(), plugin: plugin})
+	}
+	return append(errs, r.registerLoaded(loaded)...)
+}
+
+// LoadScripts loads and registers every Lua script in dir, in name order,
+// reporting the ones that fail as LoadDir does. A script is a plugin
+// defining global pre_process and/or post_process functions, each taking and
+// returning a string, and optionally a `plugin` table with its name,
+// version, description, priority, requires and conflicts_with. Scripts get Lua's string, table, math
+// and utf8 libraries but no io, os or package, and each call has an
+// instruction budget.
+func (r *PluginRegistry) LoadScripts(dir string) []error {
+	entries, err := os.ReadDir(dir)
+	if errors.Is(err, os.ErrNotExist) {
+		return nil
+	}
+	if err != nil {
+		return []error{err}
+	}
+	var errs []error
+	var loaded []loadedPlugin
+	for _, entry := range entries {
+		if entry.IsDir() || !isScriptFile(entry.Name()) {
+			continue
+		}
+		plugin, err := r.open(filepath.Join(dir, entry.Name()))
+		if err != nil {
+			errs = append(errs, &PluginError{Plugin: entry.Name(), Err: err})
+			continue
+		}
+		loaded = append(loaded, loadedPlugin{file: entry.Name(), plugin: plugin})
+	}
+	return append(errs, r.registerLoaded(loaded)...)
+}
+
+// isPluginFile reports whether LoadDir loads a file of this name
+func isPluginFile(name string) bool {
+	ext := filepath.Ext(name)
+	return strings.EqualFold(ext, pluginWasmExtension) || strings.EqualFold(ext, pluginLibraryExtension())
+}
+
+// isScriptFile reports whether LoadScripts loads a file of this name
+func isScriptFile(name string) bool {
+	return strings.EqualFold(filepath.Ext(name), pluginScriptExtension)
+}
+
+// open loads the plugin in a file, as a shared library, WASM module or
+// script by its extension
+func (r *PluginRegistry) open(path string) (*libraryPlugin, error) {
+	var library pluginLibrary
+	var err error
+	switch ext := filepath.Ext(path); {
+	case strings.EqualFold(ext, pluginWasmExtension):
+		r.mu.RLock()
+		grant := r.grants[filepath.Base(path)]
+		r.mu.RUnlock()
+		library, err = openWasmLibrary(path, grant)
+	case strings.EqualFold(ext, pluginLibraryExtension()):
+		library, err = openPluginLibrary(path)
+	case strings.EqualFold(ext, pluginScriptExtension):
+		library, err = openLuaScript(path)
+	default:
+		return nil, fmt.Errorf("%s is not a plugin", filepath.Base(path))
+	}
+	if err != nil {
+		return nil, err
+	}
+	return loadLibraryPlugin(library, path)
+}
+
+// LoadFile loads and registers the plugin in one file, as LoadDir and
+// LoadScripts do for each of theirs
+func (r *PluginRegistry) LoadFile(path string) error {
+	plugin, err := r.open(path)
+	if err == nil {
+		if err = r.Register(plugin); err != nil {
+			plugin.Close()
+		}
+	}
+	if err != nil {
+		return &PluginError{Plugin: filepath.Base(path), Err: err}
+	}
+	return nil
+}
+
+// Reload loads a dynamic plugin's file again and puts the new build where
+// the old one ran, keeping its settings. Built-in plugins have no file to
+// reload.
+func (r *PluginRegistry) Reload(name string) error {
+	r.mu.RLock()
+	index := r.index(name)
+	var path string
+	if index >= 0 {
+		path = r.plugins[index].Metadata().Path
+	}
+	r.mu.RUnlock()
+	if index < 0 {
+		return fmt.Errorf("plugin %q is not registered", name)
+	}
+	if path == "" {
+		return &PluginError{Plugin: name, Err: errors.New("is built in, not loaded from a file")}
+	}
+
+	if strings.EqualFold(filepath.Ext(path), pluginLibraryExtension()) {
+		// The system loader hands back the library already open at a path,
+		// so the old build has to be closed before the new one can load
+		if err := r.remove(name); err != nil {
+			return err
+		}
+		return r.LoadFile(path)
+	}
+	plugin, err := r.open(path)
+	if err != nil {
+		return &PluginError{Plugin: name, Err: err}
+	}
+	if err := r.replace(name, plugin); err != nil {
+		plugin.Close()
+		return err
+	}
+	return nil
+}
+
+// loadedFrom names the registered plugin loaded from path
+func (r *PluginRegistry) loadedFrom(path string) (string, bool) {
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	for _, plugin := range r.plugins {
+		if metadata := plugin.Metadata(); metadata.Path == path {
+			return metadata.Name, true
+		}
+	}
+	return "", false
+}
+
+// loadedPlugin is a plugin loaded from a directory, waiting to be registered
+type loadedPlugin struct {
+	file   string
+	plugin *libraryPlugin
+}
+
+// registerLoaded registers plugins each after the ones it requires, so files
+// needn't be named in dependency order. Plugins that fail to register are
+// closed and reported.
+func (r *PluginRegistry) registerLoaded(loaded []loadedPlugin) []error {
+	var errs []error
+	for len(loaded) > 0 {
+		var ready, waiting []loadedPlugin
+		for _, l := range loaded {
+			if r.registered(l.plugin.metadata.Requires) {
+				ready = append(ready, l)
+			} else {
+				waiting = append(waiting, l)
+			}
+		}
+		if len(ready) == 0 {
+			// What's left requires plugins that never loaded; Register says which
+			ready, waiting = waiting, nil
+		}
+		for _, l := range ready {
+			if err := r.Register(l.plugin); err != nil {
+				l.plugin.Close()
+				errs = append(errs, &PluginError{Plugin: l.file, Err: err})
+			}
+		}
+		loaded = waiting
+	}
+	return errs
+}
+
+// libraryPlugin adapts a loaded library to the Plugin interfaces
+type libraryPlugin struct {
+	metadata PluginMetadata
+
+	calls sync.Mutex // held through each call: the ABI doesn't require plugins to be thread-safe
+
+	mu      sync.Mutex    // guards the fields below, never held through a call
+	library pluginLibrary // nil once closed
+	busy    bool          // a call is in progress
+	closing bool          // Close came during a call, which unloads the library as it returns
+}
+
+// errPluginUnloaded fails calls into a plugin unregistered while they were on their way
+var errPluginUnloaded = errors.New("plugin was unloaded")
+
+// loadLibraryPlugin checks an opened library and wraps it, closing it if it
+// isn't a usable plugin
+func loadLibraryPlugin(library pluginLibrary, path string) (*libraryPlugin, error) {
+	plugin, err := newLibraryPlugin(library, path)
+	if err != nil {
+		library.Close()
+		return nil, err
+	}
+	return plugin, nil
+}
+
+func newLibraryPlugin(library pluginLibrary, path string) (*libraryPlugin, error) {
+	version := library.ABIVersion()
+	if version == 0 {
+		return nil, fmt.Errorf("not a Vibe Coder plugin: %s is missing", pluginSymbolABIVersion)
+	}
+	if version != PluginABIVersion {
+		return nil, fmt.Errorf("built for plugin ABI %d, but this version of Vibe Coder speaks %d", version, PluginABIVersion)
+	}
+
+	raw, err := library.Create()
+	if err != nil {
+		return nil, err
+	}
+	var metadata PluginMetadata
+	if err := json.Unmarshal([]byte(raw), &metadata); err != nil {
+		return nil, fmt.Errorf("invalid metadata: %v", err)
+	}
+	if metadata.Name == "" {
+		metadata.Name = strings.TrimSuffix(filepath.Base(path), filepath.Ext(path))
+	}
+	metadata.Path = path
+	// What the library exports decides what it can do, whatever it claims
+	metadata.Capabilities = make([]string, 0)
+	if library.Has(pluginSymbolPreProcess) {
+		metadata.Capabilities = append(metadata.Capabilities, PluginPreProcessor)
+	}
+	if library.Has(pluginSymbolPostProcess) {
+		metadata.Capabilities = append(metadata.Capabilities, PluginPostProcessor)
+	}
+	if library.Has(pluginSymbolBlocks) {
+		metadata.Capabilities = append(metadata.Capabilities, PluginBlockProcessor)
+	}
+	if library.Has(pluginSymbolRunCommand) {
+		metadata.Capabilities = append(metadata.Capabilities, PluginCustomCommand)
+	}
+	if library.Has(pluginSymbolOnEvent) {
+		metadata.Capabilities = append(metadata.Capabilities, PluginHooks)
+	}
+	if library.Has(pluginSymbolConfigure) {
+		metadata.Capabilities = append(metadata.Capabilities, PluginConfigurable)
+	}
+	return &libraryPlugin{metadata: metadata, library: library}, nil
+}
+
+func (p *libraryPlugin) Metadata() PluginMetadata {
+	return p.metadata
+}
+
+// PreProcess leaves the prompt alone when the library has no pre_process
+func (p *libraryPlugin) PreProcess(prompt string) (string, error) {
+	return p.call(pluginSymbolPreProcess, prompt)
+}
+
+// PostProcess leaves the response alone when the library has no post_process
+func (p *libraryPlugin) PostProcess(response string) (string, error) {
+	return p.call(pluginSymbolPostProcess, response)
+}
+
+// ProcessBlocks round-trips the blocks through the library as JSON
+func (p *libraryPlugin) ProcessBlocks(blocks []ContentBlock) ([]ContentBlock, error) {
+	if !p.exports(pluginSymbolBlocks) {
+		return blocks, nil
+	}
+	data, err := json.Marshal(blocks)
+	if err != nil {
+		return blocks, err
+	}
+	out, err := p.call(pluginSymbolBlocks, string(data))
+	if err != nil {
+		return blocks, err
+	}
+	var processed []ContentBlock
+	if err := json.Unmarshal([]byte(out), &processed); err != nil {
+		return blocks, fmt.Errorf("invalid blocks: %v", err)
+	}
+	return processed, nil
+}
+
+// RunCommand sends the command to the library as JSON
+func (p *libraryPlugin) RunCommand(name string, args map[string]any) (string, error) {
+	if !p.exports(pluginSymbolRunCommand) {
+		return "", errors.New("runs no commands")
+	}
+	request, err := json.Marshal(map[string]any{"name": name, "args": args})
+	if err != nil {
+		return "", err
+	}
+	return p.call(pluginSymbolRunCommand, string(request))
+}
+
+func (p *libraryPlugin) Configure(config json.RawMessage) error {
+	if !p.exports(pluginSymbolConfigure) {
+		return errors.New("takes no configuration")
+	}
+	if bytes.IndexByte(config, 0) >= 0 {
+		return errors.New("configuration contains a NUL byte")
+	}
+	return p.send(pluginSymbolConfigure, string(config))
+}
+
+// OnEvent sends the event to the library as JSON
+func (p *libraryPlugin) OnEvent(event PluginEvent) error {
+	if !p.exports(pluginSymbolOnEvent) {
+		return nil
+	}
+	data, err := json.Marshal(event)
+	if err != nil {
+		return err
+	}
+	return p.send(pluginSymbolOnEvent, string(data))
+}
+
+// Close unloads the library; calls still on their way fail. It doesn't wait
+// for a call in progress, which may never return: that call unloads the
+// library once it does.
+func (p *libraryPlugin) Close() {
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	if p.library == nil {
+		return
+	}
+	if p.busy {
+		p.closing = true
+		return
+	}
+	p.library.Close()
+	p.library = nil
+}
+
+// exports reports whether the library is loaded and has symbol
+func (p *libraryPlugin) exports(symbol string) bool {
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	return p.library != nil && !p.closing && p.library.Has(symbol)
+}
+
+// begin claims the library for a call, or returns nil once it is closed; the
+// caller holds p.calls and calls end when the call returns
+func (p *libraryPlugin) begin() pluginLibrary {
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	if p.library == nil || p.closing {
+		return nil
+	}
+	p.busy = true
+	return p.library
+}
+
+// end releases the library after a call, unloading it if Close came meanwhile
+func (p *libraryPlugin) end() {
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	p.busy = false
+	if p.closing {
+		p.library.Close()
+		p.library, p.closing = nil, false
+	}
+}
+
+func (p *libraryPlugin) call(symbol, text string) (string, error) {
+	p.calls.Lock()
+	defer p.calls.Unlock()
+	library := p.begin()
+	if library == nil {
+		return text, errPluginUnloaded
+	}
+	defer p.end()
+	if !library.Has(symbol) {
+		return text, nil
+	}
+	if strings.ContainsRune(text, 0) {
+		return text, errors.New("text contains a NUL byte")
+	}
+	return library.Process(symbol, text)
+}
+
+func (p *libraryPlugin) send(symbol, text string) error {
+	p.calls.Lock()
+	defer p.calls.Unlock()
+	library := p.begin()
+	if library == nil {
+		return errPluginUnloaded
+	}
+	defer p.end()
+	return library.Send(symbol, text)
+}
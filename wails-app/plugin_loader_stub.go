@@ -0,0 +1,10 @@
+//go:build !cgo && !windows
+
+package main
+
+import "errors"
+
+// openPluginLibrary needs dlopen, which is only reachable through cgo here
+func openPluginLibrary(path string) (pluginLibrary, error) {
+	return nil, errors.New("dynamic plugins need a build with cgo enabled")
+}
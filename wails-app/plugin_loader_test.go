@@ -0,0 +1,95 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"strings"
+	"testing"
+	"time"
+)
+
+// hangingLibrary is a native library whose calls hang until release is
+// closed
+type hangingLibrary struct {
+	release chan struct{}
+	closed  chan struct{}
+}
+
+func (l *hangingLibrary) Has(string) bool           { return true }
+func (l *hangingLibrary) ABIVersion() uint32        { return PluginABIVersion }
+func (l *hangingLibrary) Create() (string, error)   { return `{"name": "hung"}`, nil }
+func (l *hangingLibrary) Send(string, string) error { <-l.release; return nil }
+func (l *hangingLibrary) Close()                    { close(l.closed) }
+
+func (l *hangingLibrary) Process(_, text string) (string, error) {
+	<-l.release
+	return text + " late", nil
+}
+
+func TestPluginRegistryAbandonsHungPlugin(t *testing.T) {
+	library := &hangingLibrary{release: make(chan struct{}), closed: make(chan struct{})}
+	hung := &libraryPlugin{metadata: PluginMetadata{Name: "hung", Capabilities: []string{PluginPreProcessor}}, library: library}
+	r := newTestRegistry(t, hung, prePlugin{newTestPlugin(&calls{}, "after", 1)})
+	if err := r.SetTimeout("hung", "pre_process", 20*time.Millisecond); err != nil {
+		t.Fatal(err)
+	}
+
+	got, failures, _ := r.PreProcessAll("p")
+	if got != "p after" || len(failures) != 1 || !strings.Contains(failures[0].Error, ErrPluginTimeout.Error()) {
+		t.Fatalf("first run: %q, %+v", got, failures)
+	}
+	// The next call doesn't queue behind the hung one
+	start := time.Now()
+	got, failures, _ = r.PreProcessAll("p")
+	if got != "p after" || len(failures) != 1 || !strings.Contains(failures[0].Error, "abandoned") {
+		t.Errorf("second run: %q, %+v", got, failures)
+	}
+	if elapsed := time.Since(start); elapsed >= 20*time.Millisecond {
+		t.Errorf("second run waited %v for the plugin", elapsed)
+	}
+
+	// Unloading doesn't wait for the call, which closes the library as it returns
+	unloaded := make(chan error, 1)
+	go func() { unloaded <- r.Unregister("hung") }()
+	select {
+	case err := <-unloaded:
+		if err != nil {
+			t.Fatal(err)
+		}
+	case <-time.After(time.Second):
+		t.Fatal("Unregister waited for the hung call")
+	}
+	select {
+	case <-library.closed:
+		t.Fatal("the library was closed under the call")
+	default:
+	}
+	close(library.release)
+	select {
+	case <-library.closed:
+	case <-time.After(time.Second):
+		t.Error("the library wasn't closed once the call returned")
+	}
+}
+
+func TestPluginRegistryConfigureTimesOut(t *testing.T) {
+	library := &hangingLibrary{release: make(chan struct{}), closed: make(chan struct{})}
+	t.Cleanup(func() { close(library.release) })
+	r := newTestRegistry(t, &libraryPlugin{metadata: PluginMetadata{Name: "hung"}, library: library})
+	if err := r.SetTimeout("hung", "configure", 20*time.Millisecond); err != nil {
+		t.Fatal(err)
+	}
+
+	err := r.Configure("hung", json.RawMessage(`{"level": 2}`))
+	if !errors.Is(err, ErrPluginTimeout) {
+		t.Fatalf("err = %v, want a timeout", err)
+	}
+	// The registry isn't held up by the call, and options the plugin didn't
+	// take aren't saved
+	if got := len(r.List()); got != 1 {
+		t.Errorf("%d plugins listed, want 1", got)
+	}
+	if got := string(r.Config("hung")); got != "" {
+		t.Errorf("saved %s for a plugin that didn't take it", got)
+	}
+}
@@ -0,0 +1,113 @@
+//go:build cgo && !windows
+
+package main
+
+/*
+#cgo linux LDFLAGS: -ldl
+#include <dlfcn.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// cgo can't call C function pointers directly, so each ABI signature gets a trampoline
+static uint32_t vibe_call_u32(void *fn) { return ((uint32_t (*)(void))fn)(); }
+static const char *vibe_call_str(void *fn) { return ((const char *(*)(void))fn)(); }
+static void *vibe_call_create(void *fn) { return ((void *(*)(void))fn)(); }
+static char *vibe_call_process(void *fn, void *plugin, const char *text) { return ((char *(*)(void *, const char *))fn)(plugin, text); }
+static const char *vibe_call_last_error(void *fn, void *plugin) { return ((const char *(*)(void *))fn)(plugin); }
+static int32_t vibe_call_send(void *fn, void *plugin, const char *text) { return ((int32_t (*)(void *, const char *))fn)(plugin, text); }
+static void vibe_call_free(void *fn, char *s) { ((void (*)(char *))fn)(s); }
+static void vibe_call_destroy(void *fn, void *plugin) { ((void (*)(void *))fn)(plugin); }
+*/
+import "C"
+
+import (
+	"errors"
+	"fmt"
+	"unsafe"
+)
+
+// dlLibrary is a plugin opened with dlopen
+type dlLibrary struct {
+	handle   unsafe.Pointer
+	instance unsafe.Pointer
+}
+
+func openPluginLibrary(path string) (pluginLibrary, error) {
+	cPath := C.CString(path)
+	defer C.free(unsafe.Pointer(cPath))
+	handle := C.dlopen(cPath, C.RTLD_NOW|C.RTLD_LOCAL)
+	if handle == nil {
+		return nil, errors.New(C.GoString(C.dlerror()))
+	}
+	return &dlLibrary{handle: handle}, nil
+}
+
+func (l *dlLibrary) symbol(name string) unsafe.Pointer {
+	cName := C.CString(name)
+	defer C.free(unsafe.Pointer(cName))
+	return C.dlsym(l.handle, cName)
+}
+
+func (l *dlLibrary) Has(symbol string) bool {
+	return l.symbol(symbol) != nil
+}
+
+func (l *dlLibrary) ABIVersion() uint32 {
+	fn := l.symbol(pluginSymbolABIVersion)
+	if fn == nil {
+		return 0
+	}
+	return uint32(C.vibe_call_u32(fn))
+}
+
+func (l *dlLibrary) Create() (string, error) {
+	for _, required := range []string{pluginSymbolMetadata, pluginSymbolCreate, pluginSymbolFreeString} {
+		if !l.Has(required) {
+			return "", fmt.Errorf("missing %s", required)
+		}
+	}
+	metadata := C.GoString(C.vibe_call_str(l.symbol(pluginSymbolMetadata)))
+	l.instance = C.vibe_call_create(l.symbol(pluginSymbolCreate))
+	if l.instance == nil {
+		return "", fmt.Errorf("%s returned NULL", pluginSymbolCreate)
+	}
+	return metadata, nil
+}
+
+func (l *dlLibrary) Process(symbol, text string) (string, error) {
+	cText := C.CString(text)
+	defer C.free(unsafe.Pointer(cText))
+	out := C.vibe_call_process(l.symbol(symbol), l.instance, cText)
+	if out == nil {
+		return "", l.lastError(symbol)
+	}
+	defer C.vibe_call_free(l.symbol(pluginSymbolFreeString), out)
+	return C.GoString(out), nil
+}
+
+func (l *dlLibrary) Send(symbol, text string) error {
+	cText := C.CString(text)
+	defer C.free(unsafe.Pointer(cText))
+	if C.vibe_call_send(l.symbol(symbol), l.instance, cText) != 0 {
+		return l.lastError(symbol)
+	}
+	return nil
+}
+
+// lastError explains why a call to symbol failed
+func (l *dlLibrary) lastError(symbol string) error {
+	if fn := l.symbol(pluginSymbolLastError); fn != nil {
+		if reason := C.vibe_call_last_error(fn, l.instance); reason != nil {
+			return errors.New(C.GoString(reason))
+		}
+	}
+	return errors.New(symbol + " failed")
+}
+
+func (l *dlLibrary) Close() {
+	if fn := l.symbol(pluginSymbolDestroy); fn != nil && l.instance != nil {
+		C.vibe_call_destroy(fn, l.instance)
+	}
+	l.instance = nil
+	C.dlclose(l.handle)
+}
@@ -0,0 +1,114 @@
+//go:build windows
+
+package main
+
+import (
+	"errors"
+	"fmt"
+	"syscall"
+	"unsafe"
+)
+
+// dllLibrary is a plugin opened with LoadLibrary; the ABI's C calls go
+// through syscall, so no cgo is needed on Windows
+type dllLibrary struct {
+	dll      *syscall.DLL
+	instance uintptr
+}
+
+func openPluginLibrary(path string) (pluginLibrary, error) {
+	dll, err := syscall.LoadDLL(path)
+	if err != nil {
+		return nil, err
+	}
+	return &dllLibrary{dll: dll}, nil
+}
+
+func (l *dllLibrary) proc(name string) *syscall.Proc {
+	proc, err := l.dll.FindProc(name)
+	if err != nil {
+		return nil
+	}
+	return proc
+}
+
+func (l *dllLibrary) Has(symbol string) bool {
+	return l.proc(symbol) != nil
+}
+
+func (l *dllLibrary) ABIVersion() uint32 {
+	proc := l.proc(pluginSymbolABIVersion)
+	if proc == nil {
+		return 0
+	}
+	version, _, _ := proc.Call()
+	return uint32(version)
+}
+
+func (l *dllLibrary) Create() (string, error) {
+	for _, required := range []string{pluginSymbolMetadata, pluginSymbolCreate, pluginSymbolFreeString} {
+		if !l.Has(required) {
+			return "", fmt.Errorf("missing %s", required)
+		}
+	}
+	metadata, _, _ := l.proc(pluginSymbolMetadata).Call()
+	l.instance, _, _ = l.proc(pluginSymbolCreate).Call()
+	if l.instance == 0 {
+		return "", fmt.Errorf("%s returned NULL", pluginSymbolCreate)
+	}
+	return cString(metadata), nil
+}
+
+func (l *dllLibrary) Process(symbol, text string) (string, error) {
+	cText, err := syscall.BytePtrFromString(text)
+	if err != nil {
+		return "", err
+	}
+	out, _, _ := l.proc(symbol).Call(l.instance, uintptr(unsafe.Pointer(cText)))
+	if out == 0 {
+		return "", l.lastError(symbol)
+	}
+	defer l.proc(pluginSymbolFreeString).Call(out)
+	return cString(out), nil
+}
+
+func (l *dllLibrary) Send(symbol, text string) error {
+	cText, err := syscall.BytePtrFromString(text)
+	if err != nil {
+		return err
+	}
+	if status, _, _ := l.proc(symbol).Call(l.instance, uintptr(unsafe.Pointer(cText))); int32(status) != 0 {
+		return l.lastError(symbol)
+	}
+	return nil
+}
+
+// lastError explains why a call to symbol failed
+func (l *dllLibrary) lastError(symbol string) error {
+	if proc := l.proc(pluginSymbolLastError); proc != nil {
+		if reason, _, _ := proc.Call(l.instance); reason != 0 {
+			return errors.New(cString(reason))
+		}
+	}
+	return errors.New(symbol + " failed")
+}
+
+func (l *dllLibrary) Close() {
+	if proc := l.proc(pluginSymbolDestroy); proc != nil && l.instance != 0 {
+		proc.Call(l.instance)
+	}
+	l.instance = 0
+	l.dll.Release()
+}
+
+// cString copies a NUL-terminated string out of plugin memory
+func cString(p uintptr) string {
+	if p == 0 {
+		return ""
+	}
+	var text []byte
+	for ptr := unsafe.Pointer(p); *(*byte)(ptr) != 0; ptr = unsafe.Add(ptr, 1) {
+		text = append(text, *(*byte)(ptr))
+	}
+	return string(text)
+}
@@ -0,0 +1,255 @@
+//go:build lua
+
+package main
+
+/*
+#cgo LDFLAGS: -llua
+#include <stdlib.h>
+#include <lua.h>
+#include <lauxlib.h>
+#include <lualib.h>
+
+// vibe_lua_memory caps what one script may allocate
+typedef struct {
+	size_t used, limit;
+} vibe_lua_memory;
+
+static void *vibe_lua_alloc(void *ud, void *ptr, size_t osize, size_t nsize) {
+	vibe_lua_memory *memory = ud;
+	size_t old = ptr != NULL ? osize : 0; // for new blocks osize is the type of object
+	if (nsize == 0) {
+		memory->used -= old;
+		free(ptr);
+		return NULL;
+	}
+	if (memory->used - old + nsize > memory->limit) return NULL;
+	void *out = realloc(ptr, nsize);
+	if (out != NULL) memory->used = memory->used - old + nsize;
+	return out;
+}
+
+// vibe_lua_textload is load, its upvalue, refusing precompiled chunks
+// whatever mode the script asks for: bytecode isn't verified and can crash
+// the VM or read outside it
+static int vibe_lua_textload(lua_State *L) {
+	if (lua_gettop(L) < 3) lua_settop(L, 3);
+	lua_pushliteral(L, "t");
+	lua_replace(L, 3);
+	lua_pushvalue(L, lua_upvalueindex(1));
+	lua_insert(L, 1);
+	lua_call(L, lua_gettop(L) - 1, LUA_MULTRET);
+	return lua_gettop(L);
+}
+
+// vibe_lua_open makes a state with only the libraries that can't reach outside it
+static lua_State *vibe_lua_open(size_t limit) {
+	vibe_lua_memory *memory = calloc(1, sizeof(vibe_lua_memory));
+	if (memory == NULL) return NULL;
+	memory->limit = limit;
+	lua_State *L = lua_newstate(vibe_lua_alloc, memory);
+	if (L == NULL) {
+		free(memory);
+		return NULL;
+	}
+	luaL_requiref(L, LUA_GNAME, luaopen_base, 1);
+	luaL_requiref(L, LUA_STRLIBNAME, luaopen_string, 1);
+	luaL_requiref(L, LUA_TABLIBNAME, luaopen_table, 1);
+	luaL_requiref(L, LUA_MATHLIBNAME, luaopen_math, 1);
+	luaL_requiref(L, LUA_UTF8LIBNAME, luaopen_utf8, 1);
+	lua_settop(L, 0);
+	// The base library can still read files, and load and string.dump
+	// between them run bytecode
+	lua_pushnil(L);
+	lua_setglobal(L, "dofile");
+	lua_pushnil(L);
+	lua_setglobal(L, "loadfile");
+	lua_getglobal(L, "load");
+	lua_pushcclosure(L, vibe_lua_textload, 1);
+	lua_setglobal(L, "load");
+	lua_getglobal(L, LUA_STRLIBNAME);
+	lua_pushnil(L);
+	lua_setfield(L, -2, "dump");
+	lua_settop(L, 0);
+	return L;
+}
+
+static void vibe_lua_close(lua_State *L) {
+	void *memory;
+	lua_getallocf(L, &memory);
+	lua_close(L);
+	free(memory);
+}
+
+static void vibe_lua_budget(lua_State *L, lua_Debug *ar) {
+	luaL_error(L, "script ran past its instruction budget");
+}
+
+// vibe_lua_call is lua_pcall, a macro, with a fresh instruction budget
+static int vibe_lua_call(lua_State *L, int nargs, int nresults, int budget) {
+	lua_sethook(L, vibe_lua_budget, LUA_MASKCOUNT, budget);
+	int status = lua_pcall(L, nargs, nresults, 0);
+	lua_sethook(L, NULL, 0, 0);
+	return status;
+}
+*/
+import "C"
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"strconv"
+	"unsafe"
+)
+
+const (
+	luaInstructionsPerCall = 100_000_000 // roughly a second of script
+	luaMemoryLimit         = 64 << 20    // bytes a script may allocate
+)
+
+// luaScriptFunctions names the global functions a script defines for each
+// processing symbol of the ABI
+var luaScriptFunctions = map[string]string{
+	pluginSymbolPreProcess:  "pre_process",
+	pluginSymbolPostProcess: "post_process",
+}
+
+// luaLibrary is a Lua script speaking the plugin ABI through its globals.
+// Build with `-tags lua` against an installed liblua (5.4).
+type luaLibrary struct {
+	state *C.lua_State
+}
+
+func openLuaScript(path string) (pluginLibrary, error) {
+	state := C.vibe_lua_open(luaMemoryLimit)
+	if state == nil {
+		return nil, errors.New("cannot create a Lua state")
+	}
+	l := &luaLibrary{state: state}
+	cPath := C.CString(path)
+	defer C.free(unsafe.Pointer(cPath))
+	cMode := C.CString("t") // source only; precompiled chunks can crash the VM
+	defer C.free(unsafe.Pointer(cMode))
+	if C.luaL_loadfilex(state, cPath, cMode) != C.LUA_OK || C.vibe_lua_call(state, 0, 0, luaInstructionsPerCall) != C.LUA_OK {
+		err := l.failure()
+		l.Close()
+		return nil, err
+	}
+	return l, nil
+}
+
+// stringAt reads the string, or number, at index on the stack
+func (l *luaLibrary) stringAt(index C.int) (string, bool) {
+	if kind := C.lua_type(l.state, index); kind != C.LUA_TSTRING && kind != C.LUA_TNUMBER {
+		return "", false
+	}
+	var n C.size_t
+	text := C.lua_tolstring(l.state, index, &n)
+	return C.GoStringN(text, C.int(n)), true
+}
+
+// failure turns the error a failed call left on the stack into a Go error
+func (l *luaLibrary) failure() error {
+	defer C.lua_settop(l.state, 0)
+	if message, ok := l.stringAt(-1); ok {
+		return errors.New(message)
+	}
+	return errors.New("script error")
+}
+
+// global pushes a global onto the stack and returns its type
+func (l *luaLibrary) global(name string) C.int {
+	cName := C.CString(name)
+	defer C.free(unsafe.Pointer(cName))
+	return C.lua_getglobal(l.state, cName)
+}
+
+// field reads a field of the table on top of the stack
+func (l *luaLibrary) field(name string) string {
+	cName := C.CString(name)
+	defer C.free(unsafe.Pointer(cName))
+	C.lua_getfield(l.state, -1, cName)
+	value, _ := l.stringAt(-1)
+	C.lua_settop(l.state, -2)
+	return value
+}
+
+// list reads a field of the table on top of the stack holding a list of strings
+func (l *luaLibrary) list(name string) []string {
+	cName := C.CString(name)
+	defer C.free(unsafe.Pointer(cName))
+	var items []string
+	if C.lua_getfield(l.state, -1, cName) == C.LUA_TTABLE {
+		for i := C.lua_Integer(1); C.lua_rawgeti(l.state, -1, i) != C.LUA_TNIL; i++ {
+			if item, ok := l.stringAt(-1); ok {
+				items = append(items, item)
+			}
+			C.lua_settop(l.state, -2)
+		}
+		C.lua_settop(l.state, -2) // the nil that ended the list
+	}
+	C.lua_settop(l.state, -2)
+	return items
+}
+
+func (l *luaLibrary) Has(symbol string) bool {
+	name, ok := luaScriptFunctions[symbol]
+	if !ok {
+		return false
+	}
+	defer C.lua_settop(l.state, 0)
+	return l.global(name) == C.LUA_TFUNCTION
+}
+
+// ABIVersion is always current: scripts are interpreted by this build
+func (l *luaLibrary) ABIVersion() uint32 {
+	return PluginABIVersion
+}
+
+// Create reads the metadata from the script's global `plugin` table
+func (l *luaLibrary) Create() (string, error) {
+	defer C.lua_settop(l.state, 0)
+	var metadata PluginMetadata
+	if l.global("plugin") == C.LUA_TTABLE {
+		metadata.Name = l.field("name")
+		metadata.Version = l.field("version")
+		metadata.Description = l.field("description")
+		metadata.Priority, _ = strconv.Atoi(l.field("priority"))
+		metadata.Requires = l.list("requires")
+		metadata.ConflictsWith = l.list("conflicts_with")
+	}
+	data, err := json.Marshal(metadata)
+	return string(data), err
+}
+
+// Process calls the script's function for symbol with the text. A script
+// fails by raising an error, or by returning nil and a reason.
+func (l *luaLibrary) Process(symbol, text string) (string, error) {
+	defer C.lua_settop(l.state, 0)
+	name := luaScriptFunctions[symbol]
+	l.global(name)
+	cText := C.CString(text)
+	defer C.free(unsafe.Pointer(cText))
+	C.lua_pushlstring(l.state, cText, C.size_t(len(text)))
+	if C.vibe_lua_call(l.state, 1, 2, luaInstructionsPerCall) != C.LUA_OK {
+		return "", l.failure()
+	}
+	if out, ok := l.stringAt(-2); ok {
+		return out, nil
+	}
+	if reason, ok := l.stringAt(-1); ok {
+		return "", errors.New(reason)
+	}
+	return "", fmt.Errorf("%s returned no string", name)
+}
+
+func (l *luaLibrary) Send(symbol, text string) error {
+	return fmt.Errorf("scripts don't support %s", symbol)
+}
+
+func (l *luaLibrary) Close() {
+	if l.state != nil {
+		C.vibe_lua_close(l.state)
+		l.state = nil
+	}
+}
@@ -0,0 +1,11 @@
+//go:build !lua
+
+package main
+
+import "errors"
+
+// openLuaScript stands in for the Lua host in builds without the lua tag,
+// refusing every script with a hint on how to enable it
+func openLuaScript(path string) (pluginLibrary, error) {
+	return nil, errors.New("running Lua scripts needs a build with -tags lua")
+}
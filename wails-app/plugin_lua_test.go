@@ -0,0 +1,72 @@
+//go:build lua
+
+package main
+
+import (
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+// openTestScript writes a script and opens it
+func openTestScript(t *testing.T, script string) (pluginLibrary, error) {
+	t.Helper()
+	path := filepath.Join(t.TempDir(), "test.lua")
+	if err := os.WriteFile(path, []byte(script), 0o600); err != nil {
+		t.Fatal(err)
+	}
+	return openLuaScript(path)
+}
+
+func TestLuaSandbox(t *testing.T) {
+	tests := []struct {
+		name   string
+		script string
+		want   string
+	}{
+		{
+			name:   "source loads",
+			script: `function pre_process(p) return load("return ...")(p) end`,
+			want:   "prompt",
+		},
+		{
+			name:   "binary chunk",
+			script: `function pre_process(p) local f, err = load("\27Lua\84\0", "chunk", "b") return f and "loaded" or err end`,
+			want:   "attempt to load a binary chunk",
+		},
+		{
+			name:   "binary chunk from a reader",
+			script: `function pre_process(p) local done = false local f, err = load(function() if done then return nil end done = true return "\27Lua" end) return f and "loaded" or err end`,
+			want:   "attempt to load a binary chunk",
+		},
+		{
+			name:   "no string.dump",
+			script: `function pre_process(p) return tostring(string.dump) .. " " .. tostring(dofile) .. " " .. tostring(io) end`,
+			want:   "nil nil nil",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			library, err := openTestScript(t, tt.script)
+			if err != nil {
+				t.Fatal(err)
+			}
+			defer library.Close()
+			got, err := library.Process(pluginSymbolPreProcess, "prompt")
+			if err != nil {
+				t.Fatalf("unexpected error: %v", err)
+			}
+			if !strings.Contains(got, tt.want) {
+				t.Errorf("got %q, want it to contain %q", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestLuaScriptBinaryFile(t *testing.T) {
+	_, err := openTestScript(t, "\x1bLuaT\x00\x19\x93\r\n\x1a\n")
+	if err == nil || !strings.Contains(err.Error(), "binary chunk") {
+		t.Errorf("err = %v, want a binary chunk refused", err)
+	}
+}
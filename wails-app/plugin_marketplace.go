@@ -0,0 +1,440 @@
+package main
+
+import (
+	"crypto/ed25519"
+	"crypto/sha256"
+	"encoding/base64"
+	"encoding/hex"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"os"
+	"path/filepath"
+	"runtime"
+	"sort"
+	"strconv"
+	"strings"
+	"sync"
+	"time"
+)
+
+const (
+	marketplaceTimeout      = time.Minute
+	marketplaceIndexLimit   = 4 << 20  // bytes of index
+	marketplaceArtifactSize = 64 << 20 // bytes of one plugin
+)
+
+// MarketplaceIndex is the JSON document a marketplace serves: every release
+// of every plugin it offers
+type MarketplaceIndex struct {
+	Plugins []MarketplaceRelease `json:"plugins"`
+}
+
+// MarketplaceRelease is one version of a plugin in a MarketplaceIndex
+type MarketplaceRelease struct {
+	Name        string `json:"name"`
+	Version     string `json:"version"`
+	Description string `json:"description"`
+	File        string `json:"file"` // installed under this name, e.g. "wordcount.wasm"
+	URL         string `json:"url"`
+	SHA256      string `json:"sha256"`              // hex digest of the artifact
+	Signature   string `json:"signature,omitempty"` // base64 Ed25519 signature of the artifact
+	Platform    string `json:"platform,omitempty"`  // GOOS/GOARCH of a shared library; empty for WASM and scripts
+}
+
+// MarketplaceSettings says where plugins are installed from
+type MarketplaceSettings struct {
+	IndexURL  string `json:"indexUrl"`
+	PublicKey string `json:"publicKey,omitempty"` // base64 Ed25519 key; when set, only artifacts it signed install, and shared libraries need it
+}
+
+// InstalledPlugin is a plugin installed from the marketplace
+type InstalledPlugin struct {
+	Version string `json:"version"`
+	Path    string `json:"path"`
+	SHA256  string `json:"sha256"`
+}
+
+// MarketplaceListing is a plugin the marketplace offers on this platform
+type MarketplaceListing struct {
+	Name        string   `json:"name"`
+	Description string   `json:"description"` // of the latest version
+	Versions    []string `json:"versions"`    // newest first
+	Installed   string   `json:"installed,omitempty"`
+	Update      bool     `json:"update"` // a newer version than the installed one is offered
+}
+
+type marketplaceState struct {
+	Settings  MarketplaceSettings        `json:"settings"`
+	Installed map[string]InstalledPlugin `json:"installed,omitempty"` // by plugin name
+}
+
+// Marketplace installs plugins listed in a remote index into the plugins
+// and scripts directories, where the registry's watchers load them.
+// Artifacts are checked against the index's SHA-256 digest and, when a
+// public key is configured, its Ed25519 signature before they are written.
+// The settings and installed versions are saved at path.
+type Marketplace struct {
+	mu        sync.Mutex
+	path      string
+	pluginDir string
+	scriptDir string
+	client    *http.Client
+	state     marketplaceState
+}
+
+// NewMarketplace loads the state stored at path; a missing or unreadable file
+// starts with no index configured
+func NewMarketplace(path, pluginDir, scriptDir string) *Marketplace {
+	m := &Marketplace{
+		path:      path,
+		pluginDir: pluginDir,
+		scriptDir: scriptDir,
+		client:    &http.Client{Timeout: marketplaceTimeout},
+	}
+	if data, err := os.ReadFile(path); err == nil {
+		if err := json.Unmarshal(data, &m.state); err != nil {
+			fmt.Fprintf(os.Stderr, "ignoring corrupt marketplace state %s: %v\n", path, err)
+			m.state = marketplaceState{}
+		}
+	} else if !errors.Is(err, os.ErrNotExist) {
+		fmt.Fprintf(os.Stderr, "failed to read marketplace state: %v\n", err)
+	}
+	if m.state.Installed == nil {
+		m.state.Installed = make(map[string]InstalledPlugin)
+	}
+	return m
+}
+
+// save writes the state atomically; the caller holds m.mu
+func (m *Marketplace) save() {
+	err := os.MkdirAll(filepath.Dir(m.path), 0o700)
+	var data []byte
+	if err == nil {
+		data, err = json.MarshalIndent(m.state, "", "  ")
+	}
+	if err == nil {
+		tmp := m.path + ".tmp"
+		if err = os.WriteFile(tmp, data, 0o600); err == nil {
+			err = os.Rename(tmp, m.path)
+		}
+	}
+	if err != nil {
+		fmt.Fprintf(os.Stderr, "failed to save marketplace state: %v\n", err)
+	}
+}
+
+func (m *Marketplace) Settings() MarketplaceSettings {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	return m.state.Settings
+}
+
+// Configure checks and saves where plugins come from
+func (m *Marketplace) Configure(settings MarketplaceSettings) error {
+	if settings.IndexURL != "" {
+		if err := checkIndexURL(settings.IndexURL); err != nil {
+			return err
+		}
+	}
+	if settings.PublicKey != "" {
+		if _, err := decodePublicKey(settings.PublicKey); err != nil {
+			return err
+		}
+	}
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	m.state.Settings = settings
+	m.save()
+	return nil
+}
+
+// checkIndexURL refuses an index that isn't served over HTTPS, as its
+// checksums are all that vouch for unsigned artifacts
+func checkIndexURL(indexURL string) error {
+	target, err := url.Parse(indexURL)
+	if err != nil || target.Host == "" {
+		return fmt.Errorf("invalid marketplace index URL %q", indexURL)
+	}
+	if target.Scheme != "https" {
+		return fmt.Errorf("the marketplace index must be served over https, not %q", indexURL)
+	}
+	return nil
+}
+
+func decodePublicKey(encoded string) (ed25519.PublicKey, error) {
+	key, err := base64.StdEncoding.DecodeString(encoded)
+	if err != nil || len(key) != ed25519.PublicKeySize {
+		return nil, errors.New("the marketplace public key must be a base64 Ed25519 key")
+	}
+	return ed25519.PublicKey(key), nil
+}
+
+// fetch downloads address, refusing more than limit bytes
+func (m *Marketplace) fetch(address string, limit int64) ([]byte, error) {
+	resp, err := m.client.Get(address)
+	if err != nil {
+		return nil, err
+	}
+	defer resp.Body.Close()
+	if resp.StatusCode != http.StatusOK {
+		return nil, fmt.Errorf("%s: %s", address, resp.Status)
+	}
+	data, err := io.ReadAll(io.LimitReader(resp.Body, limit+1))
+	if err != nil {
+		return nil, err
+	}
+	if int64(len(data)) > limit {
+		return nil, fmt.Errorf("%s is larger than %d bytes", address, limit)
+	}
+	return data, nil
+}
+
+// releases fetches the index and groups the releases that run on this
+// platform by plugin, newest first
+func (m *Marketplace) releases() (map[string][]MarketplaceRelease, error) {
+	indexURL := m.Settings().IndexURL
+	if indexURL == "" {
+		return nil, errors.New("no marketplace index is configured")
+	}
+	// Saved before https was required, perhaps
+	if err := checkIndexURL(indexURL); err != nil {
+		return nil, err
+	}
+	data, err := m.fetch(indexURL, marketplaceIndexLimit)
+	if err != nil {
+		return nil, err
+	}
+	var index MarketplaceIndex
+	if err := json.Unmarshal(data, &index); err != nil {
+		return nil, fmt.Errorf("invalid marketplace index: %v", err)
+	}
+	platform := runtime.GOOS + "/" + runtime.GOARCH
+	byName := make(map[string][]MarketplaceRelease)
+	for _, release := range index.Plugins {
+		if release.Name == "" || (release.Platform != "" && release.Platform != platform) {
+			continue
+		}
+		byName[release.Name] = append(byName[release.Name], release)
+	}
+	for _, releases := range byName {
+		sort.SliceStable(releases, func(i, j int) bool {
+			return compareVersions(releases[i].Version, releases[j].Version) > 0
+		})
+	}
+	return byName, nil
+}
+
+// List returns the plugins offered for this platform, by name, with the
+// versions installed
+func (m *Marketplace) List() ([]MarketplaceListing, error) {
+	byName, err := m.releases()
+	if err != nil {
+		return nil, err
+	}
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	listings := make([]MarketplaceListing, 0, len(byName))
+	for name, releases := range byName {
+		listing := MarketplaceListing{Name: name, Description: releases[0].Description}
+		for _, release := range releases {
+			listing.Versions = append(listing.Versions, release.Version)
+		}
+		if installed, ok := m.state.Installed[name]; ok {
+			listing.Installed = installed.Version
+			listing.Update = compareVersions(releases[0].Version, installed.Version) > 0
+		}
+		listings = append(listings, listing)
+	}
+	sort.Slice(listings, func(i, j int) bool { return listings[i].Name < listings[j].Name })
+	return listings, nil
+}
+
+// Install downloads a version of a plugin, the latest if version is empty,
+// verifies it and writes it into place, replacing any version installed
+// before. Files the marketplace didn't install are never overwritten.
+func (m *Marketplace) Install(name, version string) (InstalledPlugin, error) {
+	byName, err := m.releases()
+	if err != nil {
+		return InstalledPlugin{}, err
+	}
+	releases := byName[name]
+	if len(releases) == 0 {
+		return InstalledPlugin{}, fmt.Errorf("the marketplace offers no %s for this platform", name)
+	}
+	release := releases[0]
+	if version != "" {
+		found := false
+		for _, r := range releases {
+			if r.Version == version {
+				release, found = r, true
+				break
+			}
+		}
+		if !found {
+			return InstalledPlugin{}, fmt.Errorf("the marketplace has no version %s of %s", version, name)
+		}
+	}
+
+	var dir string
+	switch {
+	case release.File != filepath.Base(release.File) || !filepath.IsLocal(release.File):
+		return InstalledPlugin{}, fmt.Errorf("%s has an invalid file name %q", name, release.File)
+	case isPluginFile(release.File):
+		dir = m.pluginDir
+	case isScriptFile(release.File):
+		dir = m.scriptDir
+	default:
+		return InstalledPlugin{}, fmt.Errorf("%s is not a plugin file", release.File)
+	}
+	path := filepath.Join(dir, release.File)
+
+	data, err := m.fetch(release.URL, marketplaceArtifactSize)
+	if err != nil {
+		return InstalledPlugin{}, err
+	}
+	if err := m.verify(release, data); err != nil {
+		return InstalledPlugin{}, &PluginError{Plugin: name, Err: err}
+	}
+
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	previous, upgrading := m.state.Installed[name]
+	if _, err := os.Stat(path); err == nil && (!upgrading || previous.Path != path) {
+		return InstalledPlugin{}, fmt.Errorf("%s already exists and wasn't installed from the marketplace", path)
+	}
+	if err := os.MkdirAll(dir, 0o700); err != nil {
+		return InstalledPlugin{}, err
+	}
+	// Written under a name the watchers ignore, then moved into place whole
+	tmp := path + ".download"
+	if err := os.WriteFile(tmp, data, 0o600); err != nil {
+		return InstalledPlugin{}, err
+	}
+	if err := os.Rename(tmp, path); err != nil {
+		os.Remove(tmp)
+		return InstalledPlugin{}, err
+	}
+	if upgrading && previous.Path != path {
+		os.Remove(previous.Path)
+	}
+	installed := InstalledPlugin{Version: release.Version, Path: path, SHA256: strings.ToLower(release.SHA256)}
+	m.state.Installed[name] = installed
+	m.save()
+	return installed, nil
+}
+
+// verify checks an artifact against its release's digest and, when a public
+// key is configured, its signature. A shared library runs in the app's own
+// process with nothing to sandbox it, so it must be signed whatever else.
+func (m *Marketplace) verify(release MarketplaceRelease, data []byte) error {
+	if release.SHA256 == "" {
+		return errors.New("the index gives no checksum")
+	}
+	sum := sha256.Sum256(data)
+	if got := hex.EncodeToString(sum[:]); !strings.EqualFold(got, release.SHA256) {
+		return fmt.Errorf("checksum mismatch: the index says %s, the download is %s", release.SHA256, got)
+	}
+	native := strings.EqualFold(filepath.Ext(release.File), pluginLibraryExtension())
+	publicKey := m.Settings().PublicKey
+	if publicKey == "" {
+		if native {
+			return errors.New("a shared library only installs signed, and no marketplace public key is configured")
+		}
+		return nil
+	}
+	key, err := decodePublicKey(publicKey)
+	if err != nil {
+		return err
+	}
+	if release.Signature == "" {
+		return errors.New("unsigned, and the marketplace requires signatures")
+	}
+	signature, err := base64.StdEncoding.DecodeString(release.Signature)
+	if err != nil || !ed25519.Verify(key, data, signature) {
+		return errors.New("invalid signature")
+	}
+	return nil
+}
+
+// Uninstall deletes a plugin the marketplace installed; the watchers unload it
+func (m *Marketplace) Uninstall(name string) error {
+	m.mu.Lock()
+	defer m.mu.Unlock()
+	installed, ok := m.state.Installed[name]
+	if !ok {
+		return fmt.Errorf("%s wasn't installed from the marketplace", name)
+	}
+	if err := os.Remove(installed.Path); err != nil && !errors.Is(err, os.ErrNotExist) {
+		return err
+	}
+	delete(m.state.Installed, name)
+	m.save()
+	return nil
+}
+
+// compareVersions orders dotted versions such as 1.10.2 numerically, part by
+// part, with an optional leading v; parts that aren't numbers compare as text
+func compareVersions(a, b string) int {
+	as := strings.Split(strings.TrimPrefix(a, "v"), ".")
+	bs := strings.Split(strings.TrimPrefix(b, "v"), ".")
+	for i := 0; i < max(len(as), len(bs)); i++ {
+		var x, y string
+		if i < len(as) {
+			x = as[i]
+		}
+		if i < len(bs) {
+			y = bs[i]
+		}
+		xn, xErr := strconv.Atoi(x)
+		yn, yErr := strconv.Atoi(y)
+		switch {
+		case xErr == nil && yErr == nil && xn != yn:
+			if xn < yn {
+				return -1
+			}
+			return 1
+		case (xErr != nil || yErr != nil) && x != y:
+			return strings.Compare(x, y)
+		}
+	}
+	return 0
+}
+
+// errOfflineMarketplace keeps the marketplace off the network in offline mode
+var errOfflineMarketplace = errors.New("offline mode: the plugin marketplace needs the network; turn offline mode off")
+
+// GetMarketplaceSettings returns the marketplace index URL and public key
+func (a *App) GetMarketplaceSettings() MarketplaceSettings {
+	return a.market.Settings()
+}
+
+// SetMarketplaceSettings saves the marketplace index URL and public key
+func (a *App) SetMarketplaceSettings(settings MarketplaceSettings) error {
+	return a.market.Configure(settings)
+}
+
+// ListMarketplace returns the plugins the marketplace offers for this platform
+func (a *App) ListMarketplace() ([]MarketplaceListing, error) {
+	if a.manager.Offline() {
+		return nil, errOfflineMarketplace
+	}
+	return a.market.List()
+}
+
+// InstallPlugin installs a version of a plugin from the marketplace, or the
+// latest if version is empty; it loads once the plugin watchers see it
+func (a *App) InstallPlugin(name, version string) (InstalledPlugin, error) {
+	if a.manager.Offline() {
+		return InstalledPlugin{}, errOfflineMarketplace
+	}
+	return a.market.Install(name, version)
+}
+
+// UninstallPlugin deletes a plugin installed from the marketplace
+func (a *App) UninstallPlugin(name string) error {
+	return a.market.Uninstall(name)
+}
@@ -0,0 +1,198 @@
+package main
+
+import (
+	"bytes"
+	"crypto/ed25519"
+	"crypto/sha256"
+	"encoding/base64"
+	"encoding/hex"
+	"encoding/json"
+	"net/http"
+	"net/http/httptest"
+	"os"
+	"path/filepath"
+	"strings"
+	"testing"
+)
+
+// testArtifact is the plugin the test marketplaces serve
+var testArtifact = []byte("\x00asm plugin bytes")
+
+// testSigningKey signs the test marketplaces' artifacts
+var testSigningKey = ed25519.NewKeyFromSeed(bytes.Repeat([]byte{7}, 32))
+
+func testChecksum(data []byte) string {
+	sum := sha256.Sum256(data)
+	return hex.EncodeToString(sum[:])
+}
+
+func testSignature(data []byte) string {
+	return base64.StdEncoding.EncodeToString(ed25519.Sign(testSigningKey, data))
+}
+
+func testPublicKey() string {
+	return base64.StdEncoding.EncodeToString(testSigningKey.Public().(ed25519.PublicKey))
+}
+
+// newTestMarketplace serves release, as version 1.0.0 of wordcount, over
+// HTTPS and returns a marketplace installing from it into a temporary
+// directory, trusting publicKey if it isn't empty
+func newTestMarketplace(t *testing.T, release MarketplaceRelease, publicKey string) *Marketplace {
+	t.Helper()
+	var server *httptest.Server
+	server = httptest.NewTLSServer(http.HandlerFunc(func(w http.ResponseWriter, r *http.Request) {
+		if r.URL.Path != "/index.json" {
+			w.Write(testArtifact)
+			return
+		}
+		release.Name, release.Version, release.URL = "wordcount", "1.0.0", server.URL+"/"+release.File
+		json.NewEncoder(w).Encode(MarketplaceIndex{Plugins: []MarketplaceRelease{release}})
+	}))
+	t.Cleanup(server.Close)
+
+	dir := t.TempDir()
+	m := NewMarketplace(filepath.Join(dir, "marketplace.json"), filepath.Join(dir, "plugins"), filepath.Join(dir, "scripts"))
+	m.client = server.Client()
+	if err := m.Configure(MarketplaceSettings{IndexURL: server.URL + "/index.json", PublicKey: publicKey}); err != nil {
+		t.Fatal(err)
+	}
+	return m
+}
+
+func TestMarketplaceInstall(t *testing.T) {
+	tests := []struct {
+		name      string
+		release   MarketplaceRelease
+		publicKey string
+		wantErr   string
+	}{
+		{
+			name:    "checksum matches",
+			release: MarketplaceRelease{File: "wordcount.wasm", SHA256: strings.ToUpper(testChecksum(testArtifact))},
+		},
+		{
+			name:    "checksum mismatch",
+			release: MarketplaceRelease{File: "wordcount.wasm", SHA256: testChecksum([]byte("something else"))},
+			wantErr: "checksum mismatch",
+		},
+		{
+			name:    "no checksum",
+			release: MarketplaceRelease{File: "wordcount.wasm"},
+			wantErr: "the index gives no checksum",
+		},
+		{
+			name:      "signed",
+			release:   MarketplaceRelease{File: "wordcount.wasm", SHA256: testChecksum(testArtifact), Signature: testSignature(testArtifact)},
+			publicKey: testPublicKey(),
+		},
+		{
+			name:      "signature of something else",
+			release:   MarketplaceRelease{File: "wordcount.wasm", SHA256: testChecksum(testArtifact), Signature: testSignature([]byte("something else"))},
+			publicKey: testPublicKey(),
+			wantErr:   "invalid signature",
+		},
+		{
+			name:      "unsigned with a key configured",
+			release:   MarketplaceRelease{File: "wordcount.wasm", SHA256: testChecksum(testArtifact)},
+			publicKey: testPublicKey(),
+			wantErr:   "unsigned, and the marketplace requires signatures",
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			m := newTestMarketplace(t, tt.release, tt.publicKey)
+			installed, err := m.Install("wordcount", "")
+			if tt.wantErr != "" {
+				if err == nil || !strings.Contains(err.Error(), tt.wantErr) {
+					t.Errorf("err = %v, want one mentioning %q", err, tt.wantErr)
+				}
+				// Nothing unverified is written where the watchers would load it
+				if entries, _ := os.ReadDir(m.pluginDir); len(entries) > 0 {
+					t.Errorf("%d files written to the plugins directory", len(entries))
+				}
+				return
+			}
+			if err != nil {
+				t.Fatalf("unexpected error: %v", err)
+			}
+			data, err := os.ReadFile(installed.Path)
+			if err != nil {
+				t.Fatal(err)
+			}
+			if !bytes.Equal(data, testArtifact) || installed.Version != "1.0.0" {
+				t.Errorf("installed %+v holding %q", installed, data)
+			}
+		})
+	}
+}
+
+func TestCompareVersions(t *testing.T) {
+	tests := []struct {
+		a, b string
+		want int
+	}{
+		{"1.0.0", "1.0.0", 0},
+		{"1.10.0", "1.9.0", 1},
+		{"v2", "1.9.9", 1},
+		{"1.0", "1.0.1", -1},
+		{"1.0.0-beta", "1.0.0-alpha", 1},
+	}
+	for _, tt := range tests {
+		if got := compareVersions(tt.a, tt.b); got != tt.want {
+			t.Errorf("compareVersions(%q, %q) = %d, want %d", tt.a, tt.b, got, tt.want)
+		}
+	}
+}
+
+func TestMarketplaceNativePlugin(t *testing.T) {
+	file := "wordcount" + pluginLibraryExtension()
+	tests := []struct {
+		name      string
+		release   MarketplaceRelease
+		publicKey string
+		wantErr   string
+	}{
+		{
+			name:    "no key configured",
+			release: MarketplaceRelease{File: file, SHA256: testChecksum(testArtifact), Signature: testSignature(testArtifact)},
+			wantErr: "a shared library only installs signed",
+		},
+		{
+			name:      "signed",
+			release:   MarketplaceRelease{File: file, SHA256: testChecksum(testArtifact), Signature: testSignature(testArtifact)},
+			publicKey: testPublicKey(),
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			_, err := newTestMarketplace(t, tt.release, tt.publicKey).Install("wordcount", "")
+			if tt.wantErr == "" && err != nil {
+				t.Errorf("unexpected error: %v", err)
+			}
+			if tt.wantErr != "" && (err == nil || !strings.Contains(err.Error(), tt.wantErr)) {
+				t.Errorf("err = %v, want one mentioning %q", err, tt.wantErr)
+			}
+		})
+	}
+}
+
+func TestMarketplaceIndexURL(t *testing.T) {
+	m := NewMarketplace(filepath.Join(t.TempDir(), "marketplace.json"), t.TempDir(), t.TempDir())
+	tests := []struct {
+		url     string
+		wantErr string
+	}{
+		{"https://plugins.example.com/index.json", ""},
+		{"http://plugins.example.com/index.json", "must be served over https"},
+		{"plugins.example.com/index.json", "invalid marketplace index URL"},
+	}
+	for _, tt := range tests {
+		err := m.Configure(MarketplaceSettings{IndexURL: tt.url})
+		if tt.wantErr == "" && err != nil {
+			t.Errorf("%s: unexpected error: %v", tt.url, err)
+		}
+		if tt.wantErr != "" && (err == nil || !strings.Contains(err.Error(), tt.wantErr)) {
+			t.Errorf("%s: err = %v, want one mentioning %q", tt.url, err, tt.wantErr)
+		}
+	}
+}
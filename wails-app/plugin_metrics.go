@@ -0,0 +1,196 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"sort"
+	"time"
+)
+
+// defaultPluginTimeout bounds one call into a plugin when its settings don't.
+// It is generous because plugins such as the translator make model calls.
+const defaultPluginTimeout = time.Minute
+
+// pluginStages are the calls into plugins that are timed, by the names
+// failures report
+var pluginStages = map[string]bool{
+	"pre_process":    true,
+	"post_process":   true,
+	"process_blocks": true,
+	"run_command":    true,
+	"on_event":       true,
+	"configure":      true,
+}
+
+// ErrPluginTimeout is wrapped in the error of a plugin call that ran past
+// its timeout
+var ErrPluginTimeout = errors.New("timed out")
+
+// PluginStats is how one plugin has done at one stage since the app started
+type PluginStats struct {
+	Plugin    string  `json:"plugin"`
+	Stage     string  `json:"stage"`
+	Calls     int     `json:"calls"`
+	Failures  int     `json:"failures"` // timeouts included
+	Timeouts  int     `json:"timeouts"`
+	TotalMs   float64 `json:"totalMs"`
+	MaxMs     float64 `json:"maxMs"`
+	TimeoutMs int     `json:"timeoutMs"` // the stage's current timeout
+}
+
+type pluginStage struct {
+	plugin, stage string
+}
+
+// SetTimeout bounds a plugin's calls at one stage; 0 restores the default
+func (r *PluginRegistry) SetTimeout(name, stage string, timeout time.Duration) error {
+	if !pluginStages[stage] {
+		return fmt.Errorf("unknown plugin stage %q", stage)
+	}
+	if timeout < 0 {
+		return errors.New("timeout must not be negative")
+	}
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	setting := r.settings.Plugins[name]
+	if timeout == 0 {
+		delete(setting.Timeouts, stage)
+	} else {
+		if setting.Timeouts == nil {
+			setting.Timeouts = make(map[string]int)
+		}
+		setting.Timeouts[stage] = int(timeout.Milliseconds())
+	}
+	r.settings.Plugins[name] = setting
+	r.save()
+	return nil
+}
+
+// timeout returns how long a plugin's call at a stage may take; the caller
+// holds the lock
+func (r *PluginRegistry) timeout(name, stage string) time.Duration {
+	if ms := r.settings.Plugins[name].Timeouts[stage]; ms > 0 {
+		return time.Duration(ms) * time.Millisecond
+	}
+	return defaultPluginTimeout
+}
+
+// errPluginAbandoned fails calls into a plugin that is still running one
+// that timed out
+var errPluginAbandoned = errors.New("abandoned after a call timed out; reload it to use it again")
+
+// pluginCall is one call into a plugin; its fields are guarded by the
+// registry's lock
+type pluginCall struct {
+	finished  bool
+	abandoned bool // it timed out before it finished
+}
+
+// invoke makes one call into a registered plugin, timing it and giving up
+// once the stage's timeout passes. A call that hangs is left to finish on
+// its own and its result dropped; the plugin is abandoned, taking no more
+// calls, as they would queue behind the hung one, and is closed once that
+// returns. One that panics fails like any other.
+func (r *PluginRegistry) invoke(plugin Plugin, stage string, call func() error) error {
+	r.mu.RLock()
+	_, abandoned := r.abandoned[plugin.Metadata().Name]
+	r.mu.RUnlock()
+	if abandoned {
+		return errPluginAbandoned
+	}
+	return r.timed(plugin, stage, true, call)
+}
+
+// timed makes one call into a plugin as invoke describes. One that isn't
+// registered, such as a new build being configured, is closed when a call
+// that hung returns but marks no registered plugin of its name abandoned.
+func (r *PluginRegistry) timed(plugin Plugin, stage string, registered bool, call func() error) error {
+	name := plugin.Metadata().Name
+	r.mu.RLock()
+	timeout := r.timeout(name, stage)
+	r.mu.RUnlock()
+
+	pending := &pluginCall{}
+	done := make(chan error, 1)
+	start := time.Now()
+	go func() {
+		defer func() {
+			if recovered := recover(); recovered != nil {
+				done <- fmt.Errorf("panicked: %v", recovered)
+			}
+			r.mu.Lock()
+			pending.finished = true
+			abandoned := pending.abandoned
+			r.mu.Unlock()
+			if abandoned {
+				closePlugin(plugin)
+			}
+		}()
+		done <- call()
+	}()
+	timer := time.NewTimer(timeout)
+	defer timer.Stop()
+	var err error
+	select {
+	case err = <-done:
+	case <-timer.C:
+		err = fmt.Errorf("%w after %v", ErrPluginTimeout, timeout)
+	}
+	elapsed := float64(time.Since(start).Microseconds()) / 1000
+
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	if errors.Is(err, ErrPluginTimeout) && !pending.finished {
+		pending.abandoned = true
+		if registered {
+			r.abandoned[name] = pending
+		}
+	}
+	key := pluginStage{plugin: name, stage: stage}
+	stats, ok := r.stats[key]
+	if !ok {
+		stats = &PluginStats{Plugin: name, Stage: stage}
+		r.stats[key] = stats
+	}
+	stats.Calls++
+	stats.TotalMs += elapsed
+	stats.MaxMs = max(stats.MaxMs, elapsed)
+	if err != nil {
+		stats.Failures++
+	}
+	if errors.Is(err, ErrPluginTimeout) {
+		stats.Timeouts++
+	}
+	return err
+}
+
+// Stats returns each plugin's calls, failures and timings per stage, by
+// plugin and stage
+func (r *PluginRegistry) Stats() []PluginStats {
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	list := make([]PluginStats, 0, len(r.stats))
+	for key, stats := range r.stats {
+		entry := *stats
+		entry.TimeoutMs = int(r.timeout(key.plugin, key.stage).Milliseconds())
+		list = append(list, entry)
+	}
+	sort.Slice(list, func(i, j int) bool {
+		if list[i].Plugin != list[j].Plugin {
+			return list[i].Plugin < list[j].Plugin
+		}
+		return list[i].Stage < list[j].Stage
+	})
+	return list
+}
+
+// GetPluginStats returns per-plugin call counts, failures and timings
+func (a *App) GetPluginStats() []PluginStats {
+	return a.manager.Plugins().Stats()
+}
+
+// SetPluginTimeout bounds a plugin's calls at one stage, in milliseconds;
+// 0 restores the default of a minute
+func (a *App) SetPluginTimeout(name, stage string, timeoutMs int) error {
+	return a.manager.Plugins().SetTimeout(name, stage, time.Duration(timeoutMs)*time.Millisecond)
+}
@@ -0,0 +1,133 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"regexp"
+	"sync"
+)
+
+// SecretScrubberConfig is the options of SecretScrubberPlugin
+type SecretScrubberConfig struct {
+	Builtin   bool           `json:"builtin"`   // mask the kinds secret redaction knows, as well as Patterns
+	Patterns  []ScrubPattern `json:"patterns"`  // more secrets to mask
+	Allowlist []string       `json:"allowlist"` // regular expressions; a match that one fits entirely is kept
+}
+
+// ScrubPattern is a kind of secret, found by a regular expression
+type ScrubPattern struct {
+	Kind    string `json:"kind"` // shown in the placeholder, as in [REDACTED:kind]
+	Pattern string `json:"pattern"`
+}
+
+// DefaultSecretScrubberConfig masks what secret redaction does, with no exceptions
+func DefaultSecretScrubberConfig() SecretScrubberConfig {
+	return SecretScrubberConfig{Builtin: true}
+}
+
+type scrubPattern struct {
+	kind    string
+	pattern *regexp.Regexp
+}
+
+// SecretScrubberPlugin masks API keys, tokens and passwords in prompts as
+// plugins first see them. Unlike secret redaction, which runs afterwards on
+// everything sent, its patterns are the user's own and an allowlist keeps
+// known-harmless matches, such as example keys; while the plugin is enabled,
+// secret redaction lets those through too, so they are sent as typed.
+type SecretScrubberPlugin struct {
+	mu        sync.RWMutex
+	patterns  []scrubPattern
+	allowlist []*regexp.Regexp
+}
+
+func NewSecretScrubberPlugin() *SecretScrubberPlugin {
+	p := &SecretScrubberPlugin{}
+	p.patterns, p.allowlist, _ = compileScrubberConfig(DefaultSecretScrubberConfig())
+	return p
+}
+
+func (p *SecretScrubberPlugin) Metadata() PluginMetadata {
+	return PluginMetadata{
+		Name:        "secret-scrubber",
+		Version:     "1.0.0",
+		Description: "Masks API keys, tokens and passwords in prompts, with your own patterns and an allowlist",
+	}
+}
+
+func (p *SecretScrubberPlugin) Configure(config json.RawMessage) error {
+	next := DefaultSecretScrubberConfig()
+	if err := json.Unmarshal(config, &next); err != nil {
+		return err
+	}
+	patterns, allowlist, err := compileScrubberConfig(next)
+	if err != nil {
+		return err
+	}
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	p.patterns, p.allowlist = patterns, allowlist
+	return nil
+}
+
+func compileScrubberConfig(config SecretScrubberConfig) ([]scrubPattern, []*regexp.Regexp, error) {
+	var patterns []scrubPattern
+	if config.Builtin {
+		for _, secret := range secretPatterns {
+			patterns = append(patterns, scrubPattern{kind: secret.kind, pattern: secret.pattern})
+		}
+	}
+	for _, secret := range config.Patterns {
+		if secret.Kind == "" {
+			return nil, nil, errors.New("every pattern needs a kind")
+		}
+		pattern, err := regexp.Compile(secret.Pattern)
+		if err != nil {
+			return nil, nil, fmt.Errorf("pattern for %s: %v", secret.Kind, err)
+		}
+		patterns = append(patterns, scrubPattern{kind: secret.Kind, pattern: pattern})
+	}
+	allowlist := make([]*regexp.Regexp, 0, len(config.Allowlist))
+	for _, allowed := range config.Allowlist {
+		pattern, err := regexp.Compile(`^(?:` + allowed + `)$`)
+		if err != nil {
+			return nil, nil, fmt.Errorf("allowlist entry %q: %v", allowed, err)
+		}
+		allowlist = append(allowlist, pattern)
+	}
+	return patterns, allowlist, nil
+}
+
+func (p *SecretScrubberPlugin) PreProcess(prompt string) (string, error) {
+	p.mu.RLock()
+	defer p.mu.RUnlock()
+	for _, secret := range p.patterns {
+		prompt = secret.pattern.ReplaceAllStringFunc(prompt, func(match string) string {
+			if p.allows(match) {
+				return match
+			}
+			return redactionPlaceholder(secret.kind)
+		})
+	}
+	return prompt, nil
+}
+
+// AllowsSecret reports whether the allowlist keeps a secret, for secret
+// redaction to keep it as well
+func (p *SecretScrubberPlugin) AllowsSecret(match string) bool {
+	p.mu.RLock()
+	defer p.mu.RUnlock()
+	return p.allows(match)
+}
+
+// allows reports whether an allowlist entry fits match entirely; the
+// caller holds p.mu
+func (p *SecretScrubberPlugin) allows(match string) bool {
+	for _, allowed := range p.allowlist {
+		if allowed.MatchString(match) {
+			return true
+		}
+	}
+	return false
+}
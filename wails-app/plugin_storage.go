@@ -0,0 +1,169 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"sync"
+)
+
+// pluginStorageLimit bounds what one plugin may store, keys and values
+// together, in bytes
+const pluginStorageLimit = 1 << 20
+
+// PluginStorage is a plugin's own key-value store, kept across sessions, for
+// state such as a glossary or usage counts. Each plugin sees only its own
+// keys.
+type PluginStorage interface {
+	Get(key string) (string, bool)
+	// Set stores value under key and saves it before returning
+	Set(key, value string) error
+	Delete(key string) error
+	// Keys returns the stored keys, sorted
+	Keys() []string
+}
+
+// PluginStore holds every plugin's storage, one JSON file per plugin in dir,
+// each read when the plugin first uses it. An empty dir keeps it in memory.
+type PluginStore struct {
+	mu         sync.Mutex
+	dir        string
+	namespaces map[string]map[string]string // by plugin name
+}
+
+func NewPluginStore(dir string) *PluginStore {
+	return &PluginStore{dir: dir, namespaces: make(map[string]map[string]string)}
+}
+
+// file is where a plugin's storage is saved; names are made safe to use as
+// file names
+func (s *PluginStore) file(plugin string) string {
+	safe := strings.Map(func(r rune) rune {
+		if r == '/' || r == '\\' || r == ':' || r < ' ' {
+			return '_'
+		}
+		return r
+	}, plugin)
+	return filepath.Join(s.dir, safe+".json")
+}
+
+// namespace returns a plugin's entries, loading them on first use; a
+// missing or unreadable file starts empty. The caller holds s.mu.
+func (s *PluginStore) namespace(plugin string) map[string]string {
+	if entries, ok := s.namespaces[plugin]; ok {
+		return entries
+	}
+	entries := make(map[string]string)
+	if s.dir != "" {
+		path := s.file(plugin)
+		if data, err := os.ReadFile(path); err == nil {
+			if err := json.Unmarshal(data, &entries); err != nil {
+				fmt.Fprintf(os.Stderr, "ignoring corrupt plugin storage %s: %v\n", path, err)
+				entries = make(map[string]string)
+			}
+		} else if !errors.Is(err, os.ErrNotExist) {
+			fmt.Fprintf(os.Stderr, "failed to read plugin storage: %v\n", err)
+		}
+	}
+	s.namespaces[plugin] = entries
+	return entries
+}
+
+// save writes a plugin's entries atomically; the caller holds s.mu
+func (s *PluginStore) save(plugin string, entries map[string]string) error {
+	if s.dir == "" {
+		return nil
+	}
+	if err := os.MkdirAll(s.dir, 0o700); err != nil {
+		return err
+	}
+	data, err := json.MarshalIndent(entries, "", "  ")
+	if err != nil {
+		return err
+	}
+	path := s.file(plugin)
+	tmp := path + ".tmp"
+	if err := os.WriteFile(tmp, data, 0o600); err != nil {
+		return err
+	}
+	return os.Rename(tmp, path)
+}
+
+// For returns the storage of the plugin called name
+func (s *PluginStore) For(name string) PluginStorage {
+	return &pluginStorage{store: s, plugin: name}
+}
+
+type pluginStorage struct {
+	store  *PluginStore
+	plugin string
+}
+
+func (p *pluginStorage) Get(key string) (string, bool) {
+	p.store.mu.Lock()
+	defer p.store.mu.Unlock()
+	value, ok := p.store.namespace(p.plugin)[key]
+	return value, ok
+}
+
+func (p *pluginStorage) Set(key, value string) error {
+	if key == "" {
+		return errors.New("storage keys must not be empty")
+	}
+	p.store.mu.Lock()
+	defer p.store.mu.Unlock()
+	entries := p.store.namespace(p.plugin)
+	size := len(key) + len(value)
+	for k, v := range entries {
+		if k != key {
+			size += len(k) + len(v)
+		}
+	}
+	if size > pluginStorageLimit {
+		return fmt.Errorf("plugin storage is limited to %d bytes", pluginStorageLimit)
+	}
+	previous, existed := entries[key]
+	entries[key] = value
+	if err := p.store.save(p.plugin, entries); err != nil {
+		// Keep memory and disk in step
+		if existed {
+			entries[key] = previous
+		} else {
+			delete(entries, key)
+		}
+		return err
+	}
+	return nil
+}
+
+func (p *pluginStorage) Delete(key string) error {
+	p.store.mu.Lock()
+	defer p.store.mu.Unlock()
+	entries := p.store.namespace(p.plugin)
+	previous, ok := entries[key]
+	if !ok {
+		return nil
+	}
+	delete(entries, key)
+	if err := p.store.save(p.plugin, entries); err != nil {
+		entries[key] = previous
+		return err
+	}
+	return nil
+}
+
+func (p *pluginStorage) Keys() []string {
+	p.store.mu.Lock()
+	defer p.store.mu.Unlock()
+	entries := p.store.namespace(p.plugin)
+	keys := make([]string, 0, len(entries))
+	for key := range entries {
+		keys = append(keys, key)
+	}
+	sort.Strings(keys)
+	return keys
+}
@@ -0,0 +1,146 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"regexp"
+	"strings"
+	"sync"
+	"unicode"
+)
+
+const (
+	translateToEnglishPrompt = "Translate the user's text into English. On the first line write only the name of the language it is in, in English; then write the translation. Keep code, identifiers, URLs and markdown formatting exactly as they are."
+	translateBackPrompt      = "Translate the user's text into %s. Keep code blocks, inline code, identifiers, URLs and markdown formatting exactly as they are. Reply with the translation only."
+)
+
+// fencedCode is skipped when judging what language a prompt is in
+var fencedCode = regexp.MustCompile("(?s)```.*?```")
+
+// englishWords are common enough that an English prompt of a few words
+// almost always has one, leaving out words other languages share, like "in"
+var englishWords = map[string]bool{
+	"an": true, "and": true, "are": true, "can": true, "does": true, "for": true, "from": true,
+	"how": true, "is": true, "it": true, "my": true, "of": true, "should": true, "that": true,
+	"the": true, "this": true, "to": true, "what": true, "when": true, "why": true, "with": true,
+	"you": true,
+}
+
+// TranslatorConfig is the options of TranslatorPlugin
+type TranslatorConfig struct {
+	TranslateResponses bool `json:"translateResponses"` // answer in the prompt's language, not English
+}
+
+// TranslatorPlugin sends non-English prompts in English, which models
+// answer best, and translates the answers back, using the active provider
+// through its PluginHost. The language to translate back into is the
+// most recent prompt's.
+type TranslatorPlugin struct {
+	mu       sync.Mutex
+	host     PluginHost
+	config   TranslatorConfig
+	language string // of the last prompt translated, or "" if it was English
+}
+
+func NewTranslatorPlugin() *TranslatorPlugin {
+	return &TranslatorPlugin{config: TranslatorConfig{TranslateResponses: true}}
+}
+
+func (p *TranslatorPlugin) Metadata() PluginMetadata {
+	return PluginMetadata{
+		Name:        "translator",
+		Version:     "1.0.0",
+		Description: "Translates non-English prompts into English and the responses back, using the active provider",
+	}
+}
+
+func (p *TranslatorPlugin) UseHost(host PluginHost) {
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	p.host = host
+}
+
+func (p *TranslatorPlugin) Configure(config json.RawMessage) error {
+	next := TranslatorConfig{TranslateResponses: true}
+	if err := json.Unmarshal(config, &next); err != nil {
+		return err
+	}
+	p.mu.Lock()
+	defer p.mu.Unlock()
+	p.config = next
+	return nil
+}
+
+func (p *TranslatorPlugin) PreProcess(prompt string) (string, error) {
+	p.mu.Lock()
+	host := p.host
+	p.language = ""
+	p.mu.Unlock()
+	if host == nil {
+		return prompt, errors.New("no plugin host")
+	}
+	if looksEnglish(prompt) {
+		return prompt, nil
+	}
+
+	reply, err := host.Complete(prompt, translateToEnglishPrompt)
+	if err != nil {
+		return prompt, err
+	}
+	language, translation, _ := strings.Cut(strings.TrimSpace(reply), "\n")
+	language = strings.Trim(strings.TrimSpace(language), ".:*")
+	translation = strings.TrimSpace(translation)
+	if translation == "" || strings.EqualFold(language, "english") {
+		return prompt, nil
+	}
+	p.mu.Lock()
+	p.language = language
+	p.mu.Unlock()
+	return translation, nil
+}
+
+func (p *TranslatorPlugin) PostProcess(response string) (string, error) {
+	p.mu.Lock()
+	host, language, translate := p.host, p.language, p.config.TranslateResponses
+	p.mu.Unlock()
+	if host == nil || language == "" || !translate {
+		return response, nil
+	}
+	translation, err := host.Complete(response, fmt.Sprintf(translateBackPrompt, language))
+	if err != nil {
+		return response, err
+	}
+	return translation, nil
+}
+
+// looksEnglish judges a prompt by its letters and words, leaving code aside,
+// so English prompts don't cost a model call
+func looksEnglish(prompt string) bool {
+	text := fencedCode.ReplaceAllString(prompt, "")
+	letters, foreign := 0, 0
+	for _, r := range text {
+		if unicode.IsLetter(r) {
+			letters++
+			if r > unicode.MaxASCII {
+				foreign++
+			}
+		}
+	}
+	if letters == 0 {
+		return true
+	}
+	if foreign*5 > letters {
+		return false
+	}
+	words := strings.FieldsFunc(strings.ToLower(text), func(r rune) bool { return !unicode.IsLetter(r) })
+	if len(words) < 4 {
+		return true // too short to tell; most likely a command or a name
+	}
+	for _, word := range words {
+		if englishWords[word] {
+			return true
+		}
+	}
+	return false
+}
@@ -0,0 +1,304 @@
+//go:build wasmtime
+
+package main
+
+/*
+#cgo LDFLAGS: -lwasmtime
+#include <stdlib.h>
+#include <string.h>
+#include <wasmtime.h>
+
+// Extern unions can't be read from Go, so export lookups happen here
+static bool vibe_export_func(wasmtime_context_t *context, const wasmtime_instance_t *instance, const char *name, wasmtime_func_t *out) {
+	wasmtime_extern_t item;
+	if (!wasmtime_instance_export_get(context, instance, name, strlen(name), &item)) return false;
+	if (item.kind != WASMTIME_EXTERN_FUNC) {
+		wasmtime_extern_delete(&item);
+		return false;
+	}
+	*out = item.of.func;
+	return true;
+}
+
+static bool vibe_export_memory(wasmtime_context_t *context, const wasmtime_instance_t *instance, wasmtime_memory_t *out) {
+	wasmtime_extern_t item;
+	if (!wasmtime_instance_export_get(context, instance, "memory", 6, &item)) return false;
+	if (item.kind != WASMTIME_EXTERN_MEMORY) {
+		wasmtime_extern_delete(&item);
+		return false;
+	}
+	*out = item.of.memory;
+	return true;
+}
+
+// vibe_message takes ownership of an error or trap and returns its message, to be freed
+static char *vibe_message(wasmtime_error_t *error, wasm_trap_t *trap) {
+	wasm_byte_vec_t message;
+	if (error != NULL) {
+		wasmtime_error_message(error, &message);
+		wasmtime_error_delete(error);
+	} else {
+		wasm_trap_message(trap, &message);
+		wasm_trap_delete(trap);
+	}
+	char *out = malloc(message.size + 1);
+	memcpy(out, message.data, message.size);
+	out[message.size] = 0;
+	wasm_byte_vec_delete(&message);
+	return out;
+}
+
+// vibe_call calls func with up to two i32 arguments, returning an error message or NULL
+static char *vibe_call(wasmtime_context_t *context, const wasmtime_func_t *func, int nargs, int32_t a, int32_t b, int nresults, int64_t *result) {
+	wasmtime_val_t args[2], results[1];
+	args[0].kind = WASMTIME_I32;
+	args[0].of.i32 = a;
+	args[1].kind = WASMTIME_I32;
+	args[1].of.i32 = b;
+	wasm_trap_t *trap = NULL;
+	wasmtime_error_t *error = wasmtime_func_call(context, func, args, nargs, results, nresults, &trap);
+	if (error != NULL || trap != NULL) return vibe_message(error, trap);
+	if (nresults > 0) *result = results[0].kind == WASMTIME_I64 ? results[0].of.i64 : (int64_t)(uint32_t)results[0].of.i32;
+	return NULL;
+}
+*/
+import "C"
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"unsafe"
+)
+
+const (
+	wasmFuelPerCall = 2_000_000_000 // roughly a second or two of guest instructions
+	wasmMemoryLimit = 256 << 20     // bytes of linear memory a guest may grow to
+)
+
+// wasmLibrary is a WASM plugin instantiated in its own wasmtime store. Build
+// with `-tags wasmtime` against an installed libwasmtime (C API, v25 or later).
+type wasmLibrary struct {
+	engine   *C.wasm_engine_t
+	store    *C.wasmtime_store_t
+	context  *C.wasmtime_context_t
+	instance C.wasmtime_instance_t
+	memory   C.wasmtime_memory_t
+}
+
+func wasmError(err *C.wasmtime_error_t, trap *C.wasm_trap_t) error {
+	if err == nil && trap == nil {
+		return nil
+	}
+	message := C.vibe_message(err, trap)
+	defer C.free(unsafe.Pointer(message))
+	return errors.New(C.GoString(message))
+}
+
+func openWasmLibrary(path string, grant WasmGrant) (pluginLibrary, error) {
+	wasm, err := os.ReadFile(path)
+	if err != nil {
+		return nil, err
+	}
+	if len(wasm) == 0 {
+		return nil, errors.New("empty module")
+	}
+
+	config := C.wasm_config_new()
+	C.wasmtime_config_consume_fuel_set(config, true)
+	l := &wasmLibrary{engine: C.wasm_engine_new_with_config(config)}
+	l.store = C.wasmtime_store_new(l.engine, nil, nil)
+	l.context = C.wasmtime_store_context(l.store)
+	C.wasmtime_store_limiter(l.store, wasmMemoryLimit, -1, -1, -1, -1)
+	if err := l.instantiate(wasm, grant); err != nil {
+		l.Close()
+		return nil, err
+	}
+	return l, nil
+}
+
+func (l *wasmLibrary) instantiate(wasm []byte, grant WasmGrant) error {
+	var module *C.wasmtime_module_t
+	if err := wasmError(C.wasmtime_module_new(l.engine, (*C.uint8_t)(unsafe.Pointer(&wasm[0])), C.size_t(len(wasm)), &module), nil); err != nil {
+		return err
+	}
+	defer C.wasmtime_module_delete(module)
+
+	// An empty WASI config: no args, env or stdio, and only the granted directories
+	wasi := C.wasi_config_new()
+	dirPerms := C.wasi_dir_perms(C.WASI_DIR_PERMS_READ)
+	filePerms := C.wasi_file_perms(C.WASI_FILE_PERMS_READ)
+	if !grant.ReadOnly {
+		dirPerms |= C.WASI_DIR_PERMS_WRITE
+		filePerms |= C.WASI_FILE_PERMS_WRITE
+	}
+	for _, dir := range grant.Dirs {
+		cDir := C.CString(dir)
+		ok := C.wasi_config_preopen_dir(wasi, cDir, cDir, dirPerms, filePerms)
+		C.free(unsafe.Pointer(cDir))
+		if !ok {
+			C.wasi_config_delete(wasi)
+			return fmt.Errorf("cannot grant access to %s", dir)
+		}
+	}
+	if err := wasmError(C.wasmtime_context_set_wasi(l.context, wasi), nil); err != nil {
+		return err
+	}
+	linker := C.wasmtime_linker_new(l.engine)
+	defer C.wasmtime_linker_delete(linker)
+	if err := wasmError(C.wasmtime_linker_define_wasi(linker), nil); err != nil {
+		return err
+	}
+
+	// Start functions run on the same fuel budget as calls
+	if err := wasmError(C.wasmtime_context_set_fuel(l.context, wasmFuelPerCall), nil); err != nil {
+		return err
+	}
+	var trap *C.wasm_trap_t
+	if err := wasmError(C.wasmtime_linker_instantiate(linker, l.context, module, &l.instance, &trap), trap); err != nil {
+		return err
+	}
+	if !C.vibe_export_memory(l.context, &l.instance, &l.memory) {
+		return errors.New("module exports no memory")
+	}
+	return nil
+}
+
+// call runs an export with refilled fuel and returns its result widened to int64
+func (l *wasmLibrary) call(name string, results int, args ...int32) (int64, error) {
+	cName := C.CString(name)
+	defer C.free(unsafe.Pointer(cName))
+	var fn C.wasmtime_func_t
+	if !C.vibe_export_func(l.context, &l.instance, cName, &fn) {
+		return 0, fmt.Errorf("missing %s", name)
+	}
+	if err := wasmError(C.wasmtime_context_set_fuel(l.context, wasmFuelPerCall), nil); err != nil {
+		return 0, err
+	}
+	var a [2]int32
+	copy(a[:], args)
+	var result C.int64_t
+	if message := C.vibe_call(l.context, &fn, C.int(len(args)), C.int32_t(a[0]), C.int32_t(a[1]), C.int(results), &result); message != nil {
+		defer C.free(unsafe.Pointer(message))
+		return 0, fmt.Errorf("%s: %s", name, C.GoString(message))
+	}
+	return int64(result), nil
+}
+
+// guestBytes returns the guest's memory from ptr for n bytes, after
+// checking the range is inside it. Memory can move when the guest grows it,
+// so the slice is only good until the next call.
+func (l *wasmLibrary) guestBytes(ptr, n uint32) ([]byte, error) {
+	size := uint64(C.wasmtime_memory_data_size(l.context, &l.memory))
+	if uint64(ptr)+uint64(n) > size {
+		return nil, fmt.Errorf("string at %d+%d is outside the guest's memory", ptr, n)
+	}
+	if n == 0 {
+		return nil, nil
+	}
+	data := unsafe.Pointer(C.wasmtime_memory_data(l.context, &l.memory))
+	return unsafe.Slice((*byte)(unsafe.Add(data, ptr)), n), nil
+}
+
+// read copies out a packed ptr<<32 | len string
+func (l *wasmLibrary) read(packed int64) (string, error) {
+	bytes, err := l.guestBytes(uint32(uint64(packed)>>32), uint32(packed))
+	return string(bytes), err
+}
+
+func (l *wasmLibrary) Has(symbol string) bool {
+	cName := C.CString(symbol)
+	defer C.free(unsafe.Pointer(cName))
+	var fn C.wasmtime_func_t
+	return bool(C.vibe_export_func(l.context, &l.instance, cName, &fn))
+}
+
+func (l *wasmLibrary) ABIVersion() uint32 {
+	version, err := l.call(pluginSymbolABIVersion, 1)
+	if err != nil {
+		return 0
+	}
+	return uint32(version)
+}
+
+func (l *wasmLibrary) Create() (string, error) {
+	if !l.Has(pluginSymbolAlloc) {
+		return "", fmt.Errorf("missing %s", pluginSymbolAlloc)
+	}
+	packed, err := l.call(pluginSymbolMetadata, 1)
+	if err != nil {
+		return "", err
+	}
+	return l.read(packed)
+}
+
+// write copies text into memory the guest allocated for it
+func (l *wasmLibrary) write(text string) (int32, error) {
+	ptr, err := l.call(pluginSymbolAlloc, 1, int32(len(text)))
+	if err != nil {
+		return 0, err
+	}
+	input, err := l.guestBytes(uint32(ptr), uint32(len(text)))
+	if err != nil {
+		return 0, err
+	}
+	copy(input, text)
+	return int32(ptr), nil
+}
+
+// lastError explains why a call to symbol failed
+func (l *wasmLibrary) lastError(symbol string) error {
+	if l.Has(pluginSymbolLastError) {
+		if reason, err := l.call(pluginSymbolLastError, 1); err == nil && reason != 0 {
+			if message, err := l.read(reason); err == nil {
+				return errors.New(message)
+			}
+		}
+	}
+	return errors.New(symbol + " failed")
+}
+
+func (l *wasmLibrary) Process(symbol, text string) (string, error) {
+	ptr, err := l.write(text)
+	if err != nil {
+		return "", err
+	}
+	packed, err := l.call(symbol, 1, ptr, int32(len(text)))
+	if err != nil {
+		return "", err
+	}
+	if packed == 0 {
+		return "", l.lastError(symbol)
+	}
+	out, err := l.read(packed)
+	if l.Has(pluginSymbolFree) {
+		l.call(pluginSymbolFree, 0, int32(uint64(packed)>>32), int32(packed))
+	}
+	return out, err
+}
+
+func (l *wasmLibrary) Send(symbol, text string) error {
+	ptr, err := l.write(text)
+	if err != nil {
+		return err
+	}
+	status, err := l.call(symbol, 1, ptr, int32(len(text)))
+	if err != nil {
+		return err
+	}
+	if status != 0 {
+		return l.lastError(symbol)
+	}
+	return nil
+}
+
+func (l *wasmLibrary) Close() {
+	if l.store != nil {
+		C.wasmtime_store_delete(l.store)
+		l.store = nil
+	}
+	if l.engine != nil {
+		C.wasm_engine_delete(l.engine)
+		l.engine = nil
+	}
+}
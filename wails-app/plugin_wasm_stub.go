@@ -0,0 +1,11 @@
+//go:build !wasmtime
+
+package main
+
+import "errors"
+
+// openWasmLibrary stands in for the wasmtime host in builds without the
+// wasmtime tag, refusing every module with a hint on how to enable it
+func openWasmLibrary(path string, grant WasmGrant) (pluginLibrary, error) {
+	return nil, errors.New("WASM plugins need a build with -tags wasmtime")
+}
@@ -0,0 +1,106 @@
+package main
+
+import (
+	"context"
+	"errors"
+	"os"
+	"path/filepath"
+	"time"
+)
+
+// pluginPollInterval is how often Watch looks for changed plugin files
+const pluginPollInterval = 2 * time.Second
+
+// fileStamp tells one version of a file from the next
+type fileStamp struct {
+	size     int64
+	modified int64 // Unix nanoseconds
+}
+
+// Watch keeps the plugins loaded from dir in step with the files match
+// accepts until ctx ends: new files are loaded, changed ones reloaded and
+// removed ones unregistered. The directory is polled, and a file is only
+// picked up once it reads the same on two polls in a row, so one still
+// being written isn't loaded half-finished. changed is called after each
+// poll that changed the plugins, and report with each failure.
+func (r *PluginRegistry) Watch(ctx context.Context, dir string, match func(name string) bool, changed func(), report func(error)) {
+	applied, err := scanPluginDir(dir, match)
+	if err != nil {
+		applied = make(map[string]fileStamp)
+	}
+	seen := make(map[string]fileStamp, len(applied))
+	for path, stamp := range applied {
+		seen[path] = stamp
+	}
+
+	ticker := time.NewTicker(pluginPollInterval)
+	defer ticker.Stop()
+	for {
+		select {
+		case <-ctx.Done():
+			return
+		case <-ticker.C:
+		}
+		current, err := scanPluginDir(dir, match)
+		if err != nil {
+			continue // unreadable for now; try again next poll
+		}
+
+		updated := false
+		for path, stamp := range current {
+			if stamp != seen[path] || stamp == applied[path] {
+				continue // still being written, or as it was
+			}
+			applied[path] = stamp
+			updated = true
+			if name, ok := r.loadedFrom(path); ok {
+				err = r.Reload(name)
+			} else {
+				err = r.LoadFile(path)
+			}
+			if err != nil {
+				report(err)
+			}
+		}
+		for path := range applied {
+			if _, ok := current[path]; ok {
+				continue
+			}
+			delete(applied, path)
+			if name, ok := r.loadedFrom(path); ok {
+				updated = true
+				if err := r.Unregister(name); err != nil {
+					report(err)
+				}
+			}
+		}
+		seen = current
+		if updated {
+			changed()
+		}
+	}
+}
+
+// scanPluginDir stamps the files in dir that match accepts; a missing dir
+// has none
+func scanPluginDir(dir string, match func(name string) bool) (map[string]fileStamp, error) {
+	stamps := make(map[string]fileStamp)
+	entries, err := os.ReadDir(dir)
+	if errors.Is(err, os.ErrNotExist) {
+		return stamps, nil
+	}
+	if err != nil {
+		return nil, err
+	}
+	for _, entry := range entries {
+		if entry.IsDir() || !match(entry.Name()) {
+			continue
+		}
+		info, err := entry.Info()
+		if err != nil {
+			continue // removed since the listing
+		}
+		stamps[filepath.Join(dir, entry.Name())] = fileStamp{size: info.Size(), modified: info.ModTime().UnixNano()}
+	}
+	return stamps, nil
+}
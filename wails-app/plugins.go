@@ -0,0 +1,980 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"sync"
+)
+
+// Plugin capabilities, reported from the interfaces a plugin implements
+const (
+	PluginPreProcessor   = "pre_processor"   // rewrites prompts before they are sent
+	PluginPostProcessor  = "post_processor"  // rewrites response content before it is shown
+	PluginConfigurable   = "configurable"    // takes options, such as a target language
+	PluginBlockProcessor = "block_processor" // edits the parsed blocks of markdown responses
+	PluginCustomCommand  = "custom_command"  // runs /commands typed in the prompt box
+	PluginHooks          = "hooks"           // hears about sessions, responses, exports and shutdown
+)
+
+// Plugin error policies, for what a failing plugin does to the request
+const (
+	PluginOnErrorSkip    = "skip"    // carry on without the plugin's change; the default
+	PluginOnErrorDisable = "disable" // skip, and disable the plugin after MaxFailures failures in a row
+	PluginOnErrorAbort   = "abort"   // fail the request, for plugins it can't do without
+)
+
+// defaultPluginMaxFailures is how many failures in a row disable a plugin
+// under PluginOnErrorDisable when its policy doesn't say
+const defaultPluginMaxFailures = 3
+
+// PluginMetadata describes a plugin to the user
+type PluginMetadata struct {
+	Name         string   `json:"name"`
+	Version      string   `json:"version"`
+	Description  string   `json:"description"`
+	Priority     int      `json:"priority"` // lower runs first
+	Capabilities []string `json:"capabilities"`
+	Enabled      bool     `json:"enabled"`        // reported by the registry; disabled plugins are skipped
+	Path         string   `json:"path,omitempty"` // the library or script a dynamic plugin was loaded from
+
+	// Offered in the prompt box as /name; run by a CommandRunner
+	Commands []PluginCommand `json:"commands,omitempty"`
+
+	// Names of plugins this one must run after, and of plugins it can't be
+	// loaded alongside
+	Requires      []string `json:"requires,omitempty"`
+	ConflictsWith []string `json:"conflictsWith,omitempty"`
+
+	// Reported by the registry, like Enabled
+	ErrorPolicy PluginErrorPolicy `json:"errorPolicy"`
+}
+
+// Plugin extends the request pipeline. What it does is optional: a plugin
+// implements any of PreProcessor, PostProcessor, BlockProcessor,
+// CommandRunner and Hooks, and Configurable if it takes options.
+type Plugin interface {
+	Metadata() PluginMetadata
+}
+
+// PreProcessor is implemented by plugins that rewrite the prompt
+type PreProcessor interface {
+	PreProcess(prompt string) (string, error)
+}
+
+// PostProcessor is implemented by plugins that rewrite the response content
+type PostProcessor interface {
+	PostProcess(response string) (string, error)
+}
+
+// BlockProcessor is implemented by plugins that work on the structure of a
+// response, such as retitling code blocks or renumbering lists, rather than
+// on its text. The blocks are written back out as markdown afterwards.
+type BlockProcessor interface {
+	ProcessBlocks(blocks []ContentBlock) ([]ContentBlock, error)
+}
+
+// Configurable is implemented by plugins that take options. Configure gets
+// the saved options when the plugin registers and new ones whenever they
+// change; a plugin decodes the JSON into its own settings type.
+type Configurable interface {
+	Configure(config json.RawMessage) error
+}
+
+// Closer is implemented by plugins holding resources, such as a loaded
+// library, to release when they are unregistered
+type Closer interface {
+	Close()
+}
+
+// PluginHost is a plugin's controlled way into the app, for plugins that do
+// more than rewrite text, such as summarizers making model calls of their
+// own. Each plugin gets its own host.
+type PluginHost interface {
+	// Complete answers a prompt with the active provider. The request skips
+	// the plugins, so a plugin can't end up calling itself, but is otherwise
+	// ordinary: retried, redacted, cached and costed.
+	Complete(prompt, systemPrompt string) (string, error)
+	// ActiveModel names the active provider and its model
+	ActiveModel() (provider, model string)
+	// History returns the latest conversation: the turns sent with the last
+	// request, its prompt and, once it has arrived, its answer
+	History() []ChatMessage
+	// Config returns the plugin's saved options, or nil
+	Config() json.RawMessage
+	// Storage returns the plugin's own persistent key-value store
+	Storage() PluginStorage
+}
+
+// HostUser is implemented by plugins that use a PluginHost; each is handed
+// its own when it registers
+type HostUser interface {
+	UseHost(host PluginHost)
+}
+
+// ProviderBridge is the app side of a PluginHost, implemented by the
+// ProviderManager
+type ProviderBridge interface {
+	Complete(prompt, systemPrompt string) (string, error)
+	ActiveModel() (provider, model string)
+	History() []ChatMessage
+}
+
+// pluginHost is the PluginHost of the plugin called name
+type pluginHost struct {
+	ProviderBridge
+	registry *PluginRegistry
+	name     string
+}
+
+func (h *pluginHost) Config() json.RawMessage {
+	return h.registry.Config(h.name)
+}
+
+func (h *pluginHost) Storage() PluginStorage {
+	return h.registry.store.For(h.name)
+}
+
+// PluginError is a failure inside a plugin, or in loading one
+type PluginError struct {
+	Plugin string
+	Err    error
+}
+
+func (e *PluginError) Error() string {
+	return fmt.Sprintf("plugin %s: %v", e.Plugin, e.Err)
+}
+
+func (e *PluginError) Unwrap() error {
+	return e.Err
+}
+
+// PluginSettings is the plugin setup that outlives a session: the run order
+// and each plugin's switch and options, by plugin name
+type PluginSettings struct {
+	Order   []string                 `json:"order,omitempty"`
+	Plugins map[string]PluginSetting `json:"plugins,omitempty"`
+}
+
+// PluginSetting is one plugin's entry in PluginSettings
+type PluginSetting struct {
+	Disabled    bool               `json:"disabled,omitempty"`
+	Config      json.RawMessage    `json:"config,omitempty"`      // handed to the plugin's Configure
+	ErrorPolicy *PluginErrorPolicy `json:"errorPolicy,omitempty"` // nil skips failures
+	Timeouts    map[string]int     `json:"timeouts,omitempty"`    // milliseconds by stage; missing ones use the default
+}
+
+// PluginErrorPolicy is what a plugin's failures do to the request
+type PluginErrorPolicy struct {
+	OnError     string `json:"onError"`     // PluginOnErrorSkip, PluginOnErrorDisable or PluginOnErrorAbort
+	MaxFailures int    `json:"maxFailures"` // failures in a row before PluginOnErrorDisable trips
+}
+
+// PluginFailure is a plugin error the request carried on past, reported
+// with the response
+type PluginFailure struct {
+	Plugin   string `json:"plugin"`
+	Stage    string `json:"stage"` // "pre_process", "post_process", "process_blocks" or "on_event"
+	Error    string `json:"error"`
+	Disabled bool   `json:"disabled"` // this failure disabled the plugin
+}
+
+// PluginRegistry holds the registered plugins and chains them, each one
+// seeing the previous one's output. Plugins named in SetOrder run first, in
+// that order; the rest follow by ascending priority, ties in registration
+// order; either way a plugin runs after the plugins it requires. Disabled
+// plugins stay registered but are skipped, as are the plugins requiring them. A plugin that
+// fails is skipped over, disabled or fails the request, by its error policy.
+// Order, switches, options and policies are saved, applying to plugins as
+// they register. It is safe for concurrent use.
+type PluginRegistry struct {
+	mu        sync.RWMutex
+	path      string
+	plugins   []Plugin                   // kept in run order
+	capable   map[string]map[string]bool // names of the plugins with each capability
+	settings  PluginSettings
+	bridge    ProviderBridge
+	grants    map[string]WasmGrant   // by file name in the plugins directory
+	failures  map[string]int         // failures in a row, by plugin name
+	abandoned map[string]*pluginCall // plugins left running a call that timed out, by name
+	stats     map[pluginStage]*PluginStats
+	store     *PluginStore
+}
+
+// NewPluginRegistry loads the settings stored at path; a missing or unreadable
+// file starts with the defaults. Plugins' storage is kept in plugin-data
+// beside it.
+func NewPluginRegistry(path string) *PluginRegistry {
+	storageDir := ""
+	if path != "" {
+		storageDir = filepath.Join(filepath.Dir(path), "plugin-data")
+	}
+	r := &PluginRegistry{
+		path:      path,
+		plugins:   make([]Plugin, 0),
+		capable:   make(map[string]map[string]bool),
+		settings:  PluginSettings{Plugins: make(map[string]PluginSetting)},
+		grants:    make(map[string]WasmGrant),
+		failures:  make(map[string]int),
+		abandoned: make(map[string]*pluginCall),
+		stats:     make(map[pluginStage]*PluginStats),
+		store:     NewPluginStore(storageDir),
+	}
+	if data, err := os.ReadFile(path); err == nil {
+		if err := json.Unmarshal(data, &r.settings); err != nil {
+			fmt.Fprintf(os.Stderr, "ignoring corrupt plugin settings %s: %v\n", path, err)
+			r.settings = PluginSettings{}
+		}
+	} else if !errors.Is(err, os.ErrNotExist) {
+		fmt.Fprintf(os.Stderr, "failed to read plugin settings: %v\n", err)
+	}
+	if r.settings.Plugins == nil {
+		r.settings.Plugins = make(map[string]PluginSetting)
+	}
+	return r
+}
+
+// builtinPlugins ship with the app
+func builtinPlugins() []Plugin {
+	return []Plugin{
+		NewPromptEnhancerPlugin(),
+		NewCodeSaverPlugin(),
+		NewSecretScrubberPlugin(),
+		NewTranslatorPlugin(),
+	}
+}
+
+// RegisterBuiltins registers the plugins that ship with the app. They change
+// what is sent, so they start disabled until the user turns them on.
+func (r *PluginRegistry) RegisterBuiltins() {
+	for _, plugin := range builtinPlugins() {
+		name := plugin.Metadata().Name
+		r.mu.Lock()
+		if _, saved := r.settings.Plugins[name]; !saved {
+			r.settings.Plugins[name] = PluginSetting{Disabled: true}
+		}
+		r.mu.Unlock()
+		if err := r.Register(plugin); err != nil {
+			fmt.Fprintf(os.Stderr, "failed to register built-in plugin: %v\n", err)
+		}
+	}
+}
+
+// save writes the settings atomically; the caller holds r.mu
+func (r *PluginRegistry) save() {
+	if r.path == "" {
+		return
+	}
+	err := os.MkdirAll(filepath.Dir(r.path), 0o700)
+	var data []byte
+	if err == nil {
+		data, err = json.MarshalIndent(r.settings, "", "  ")
+	}
+	if err == nil {
+		tmp := r.path + ".tmp"
+		if err = os.WriteFile(tmp, data, 0o600); err == nil {
+			err = os.Rename(tmp, r.path)
+		}
+	}
+	if err != nil {
+		fmt.Fprintf(os.Stderr, "failed to save plugin settings: %v\n", err)
+	}
+}
+
+// Register adds a plugin, configuring it first with its saved options. Names
+// must be unique, the plugins it requires must be registered already, and it
+// mustn't conflict with one that is, either way round.
+func (r *PluginRegistry) Register(plugin Plugin) error {
+	if err := r.configureSaved(plugin); err != nil {
+		return err
+	}
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	if err := r.admit(plugin, ""); err != nil {
+		return err
+	}
+	r.plugins = append(r.plugins, plugin)
+	r.sort()
+	return nil
+}
+
+// admit checks a plugin can join the registered ones, leaving out the one
+// called replacing, and hands it its host; the caller holds r.mu
+func (r *PluginRegistry) admit(plugin Plugin, replacing string) error {
+	metadata := plugin.Metadata()
+	name := metadata.Name
+	if name == "" {
+		return errors.New("plugin has no name")
+	}
+	names := make(map[string]bool, len(r.plugins))
+	for _, registered := range r.plugins {
+		other := registered.Metadata()
+		if other.Name == replacing {
+			continue
+		}
+		if other.Name == name {
+			return fmt.Errorf("plugin %q is already registered", name)
+		}
+		if contains(metadata.ConflictsWith, other.Name) || contains(other.ConflictsWith, name) {
+			return &PluginError{Plugin: name, Err: fmt.Errorf("conflicts with %s, which is already loaded", other.Name)}
+		}
+		names[other.Name] = true
+	}
+	for _, required := range metadata.Requires {
+		if !names[required] {
+			return &PluginError{Plugin: name, Err: fmt.Errorf("requires %s, which isn't loaded", required)}
+		}
+	}
+	if user, ok := plugin.(HostUser); ok && r.bridge != nil {
+		user.UseHost(&pluginHost{ProviderBridge: r.bridge, registry: r, name: name})
+	}
+	return nil
+}
+
+// configureSaved configures a plugin about to register with its saved
+// options, if it has any
+func (r *PluginRegistry) configureSaved(plugin Plugin) error {
+	r.mu.RLock()
+	config := r.settings.Plugins[plugin.Metadata().Name].Config
+	r.mu.RUnlock()
+	if config == nil {
+		return nil
+	}
+	return r.configure(plugin, config, false)
+}
+
+// Unregister removes a plugin, closing it if it is a Closer. Its settings
+// are kept for when it registers again. A plugin others require stays until
+// they are unregistered.
+func (r *PluginRegistry) Unregister(name string) error {
+	r.mu.RLock()
+	for _, plugin := range r.plugins {
+		if other := plugin.Metadata(); contains(other.Requires, name) {
+			r.mu.RUnlock()
+			return &PluginError{Plugin: name, Err: fmt.Errorf("%s requires it", other.Name)}
+		}
+	}
+	r.mu.RUnlock()
+	return r.remove(name)
+}
+
+// remove unregisters a plugin whether or not others require it; they are
+// skipped until it is back
+func (r *PluginRegistry) remove(name string) error {
+	plugin, err := r.detach(name)
+	if err != nil {
+		return err
+	}
+	closePlugin(plugin)
+	return nil
+}
+
+// detach takes a plugin out of the registry for remove to close
+func (r *PluginRegistry) detach(name string) (Plugin, error) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	index := r.index(name)
+	if index < 0 {
+		return nil, fmt.Errorf("plugin %q is not registered", name)
+	}
+	plugin := r.plugins[index]
+	r.plugins = append(r.plugins[:index], r.plugins[index+1:]...)
+	r.sort()
+	delete(r.failures, name)
+	delete(r.abandoned, name)
+	return plugin, nil
+}
+
+// replace swaps a new build of the plugin called name in for the registered
+// one, which is closed. A build that renames the plugin must not leave
+// others without the old name.
+func (r *PluginRegistry) replace(name string, plugin Plugin) error {
+	if err := r.configureSaved(plugin); err != nil {
+		return err
+	}
+	old, err := r.swap(name, plugin)
+	if err != nil {
+		return err
+	}
+	closePlugin(old)
+	return nil
+}
+
+// swap puts plugin where the one called name was, returning that one for
+// replace to close
+func (r *PluginRegistry) swap(name string, plugin Plugin) (Plugin, error) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	index := r.index(name)
+	if index < 0 {
+		return nil, fmt.Errorf("plugin %q is not registered", name)
+	}
+	if renamed := plugin.Metadata().Name; renamed != name {
+		for _, registered := range r.plugins {
+			if other := registered.Metadata(); contains(other.Requires, name) {
+				return nil, &PluginError{Plugin: name, Err: fmt.Errorf("renamed itself %s, but %s requires it", renamed, other.Name)}
+			}
+		}
+	}
+	if err := r.admit(plugin, name); err != nil {
+		return nil, err
+	}
+	old := r.plugins[index]
+	r.plugins[index] = plugin
+	delete(r.failures, name)
+	delete(r.abandoned, name)
+	r.sort()
+	return old, nil
+}
+
+// closePlugin closes a plugin that is a Closer. The registry's lock mustn't be
+// held, as closing may wait on the plugin.
+func closePlugin(plugin Plugin) {
+	if closer, ok := plugin.(Closer); ok {
+		closer.Close()
+	}
+}
+
+// index finds a registered plugin by name, or returns -1; the caller holds
+// the lock
+func (r *PluginRegistry) index(name string) int {
+	for i, plugin := range r.plugins {
+		if plugin.Metadata().Name == name {
+			return i
+		}
+	}
+	return -1
+}
+
+// SetBridge connects the plugins' hosts to the app, handing HostUser
+// plugins theirs now and as they register
+func (r *PluginRegistry) SetBridge(bridge ProviderBridge) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	r.bridge = bridge
+	for _, plugin := range r.plugins {
+		if user, ok := plugin.(HostUser); ok {
+			user.UseHost(&pluginHost{ProviderBridge: bridge, registry: r, name: plugin.Metadata().Name})
+		}
+	}
+}
+
+// SetOrder fixes the run order of the named plugins ahead of the others.
+// Names not registered yet take their place once they are.
+func (r *PluginRegistry) SetOrder(names []string) error {
+	seen := make(map[string]bool, len(names))
+	for _, name := range names {
+		if seen[name] {
+			return fmt.Errorf("plugin %q is listed twice", name)
+		}
+		seen[name] = true
+	}
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	r.settings.Order = append([]string(nil), names...)
+	r.sort()
+	r.save()
+	return nil
+}
+
+// Enable runs a disabled plugin again
+func (r *PluginRegistry) Enable(name string) {
+	r.setDisabled(name, false)
+}
+
+// Disable skips a plugin without unregistering it. Names not registered yet
+// are disabled once they are.
+func (r *PluginRegistry) Disable(name string) {
+	r.setDisabled(name, true)
+}
+
+func (r *PluginRegistry) setDisabled(name string, disabled bool) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	setting := r.settings.Plugins[name]
+	setting.Disabled = disabled
+	r.settings.Plugins[name] = setting
+	delete(r.failures, name)
+	r.save()
+}
+
+// SetErrorPolicy decides what a plugin's failures do to the request. A
+// MaxFailures of 0 uses the default.
+func (r *PluginRegistry) SetErrorPolicy(name string, policy PluginErrorPolicy) error {
+	switch policy.OnError {
+	case PluginOnErrorSkip, PluginOnErrorDisable, PluginOnErrorAbort:
+	default:
+		return fmt.Errorf("unknown plugin error policy %q", policy.OnError)
+	}
+	if policy.MaxFailures < 0 {
+		return errors.New("max failures must not be negative")
+	}
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	setting := r.settings.Plugins[name]
+	setting.ErrorPolicy = &policy
+	r.settings.Plugins[name] = setting
+	delete(r.failures, name)
+	r.save()
+	return nil
+}
+
+// errorPolicy returns a plugin's policy with the defaults filled in; the
+// caller holds the lock
+func (r *PluginRegistry) errorPolicy(name string) PluginErrorPolicy {
+	policy := PluginErrorPolicy{OnError: PluginOnErrorSkip}
+	if saved := r.settings.Plugins[name].ErrorPolicy; saved != nil {
+		policy = *saved
+	}
+	if policy.MaxFailures == 0 {
+		policy.MaxFailures = defaultPluginMaxFailures
+	}
+	return policy
+}
+
+// failed applies a plugin's error policy to err. Under PluginOnErrorAbort
+// it returns the error to fail the request with; otherwise the failure to
+// report.
+func (r *PluginRegistry) failed(name, stage string, err error) (PluginFailure, error) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	failure := PluginFailure{Plugin: name, Stage: stage, Error: err.Error()}
+	policy := r.errorPolicy(name)
+	switch policy.OnError {
+	case PluginOnErrorAbort:
+		return failure, &PluginError{Plugin: name, Err: err}
+	case PluginOnErrorDisable:
+		r.failures[name]++
+		if r.failures[name] >= policy.MaxFailures {
+			setting := r.settings.Plugins[name]
+			setting.Disabled = true
+			r.settings.Plugins[name] = setting
+			delete(r.failures, name)
+			r.save()
+			failure.Disabled = true
+		}
+	}
+	return failure, nil
+}
+
+// succeeded resets a plugin's run of failures
+func (r *PluginRegistry) succeeded(name string) {
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	delete(r.failures, name)
+}
+
+// Configure hands options to a registered plugin and saves them, or saves
+// them for a plugin that registers later. The plugin is called as at any
+// other stage, so one that hangs times out rather than holding up the
+// registry.
+func (r *PluginRegistry) Configure(name string, config json.RawMessage) error {
+	if !json.Valid(config) {
+		return errors.New("plugin configuration must be JSON")
+	}
+	r.mu.RLock()
+	var plugin Plugin
+	if index := r.index(name); index >= 0 {
+		plugin = r.plugins[index]
+	}
+	r.mu.RUnlock()
+	if plugin != nil {
+		if err := r.configure(plugin, config, true); err != nil {
+			return err
+		}
+	}
+
+	r.mu.Lock()
+	defer r.mu.Unlock()
+	setting := r.settings.Plugins[name]
+	setting.Config = append(json.RawMessage(nil), config...)
+	r.settings.Plugins[name] = setting
+	r.save()
+	return nil
+}
+
+// Config returns the options saved for a plugin, or nil
+func (r *PluginRegistry) Config(name string) json.RawMessage {
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	return r.settings.Plugins[name].Config
+}
+
+// configure hands a plugin its options as a timed call; registered says
+// whether it is the registered plugin of its name or one about to be
+func (r *PluginRegistry) configure(plugin Plugin, config json.RawMessage, registered bool) error {
+	name := plugin.Metadata().Name
+	configurable, ok := plugin.(Configurable)
+	if !ok {
+		return &PluginError{Plugin: name, Err: errors.New("takes no configuration")}
+	}
+	call := func() error { return configurable.Configure(config) }
+	var err error
+	if registered {
+		err = r.invoke(plugin, "configure", call)
+	} else {
+		err = r.timed(plugin, "configure", false, call)
+	}
+	if err != nil {
+		return &PluginError{Plugin: name, Err: fmt.Errorf("configure: %w", err)}
+	}
+	return nil
+}
+
+// registered reports whether every named plugin is registered
+func (r *PluginRegistry) registered(names []string) bool {
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	for _, name := range names {
+		found := false
+		for _, plugin := range r.plugins {
+			if plugin.Metadata().Name == name {
+				found = true
+				break
+			}
+		}
+		if !found {
+			return false
+		}
+	}
+	return true
+}
+
+func contains(list []string, item string) bool {
+	for _, entry := range list {
+		if entry == item {
+			return true
+		}
+	}
+	return false
+}
+
+// sort puts the plugins in run order; the caller holds the lock
+func (r *PluginRegistry) sort() {
+	rank := make(map[string]int, len(r.settings.Order))
+	for i, name := range r.settings.Order {
+		rank[name] = i
+	}
+	key := func(plugin Plugin) (int, int) {
+		metadata := plugin.Metadata()
+		if i, ok := rank[metadata.Name]; ok {
+			return i, 0
+		}
+		return len(r.settings.Order), metadata.Priority
+	}
+	sort.SliceStable(r.plugins, func(i, j int) bool {
+		ri, pi := key(r.plugins[i])
+		rj, pj := key(r.plugins[j])
+		if ri != rj {
+			return ri < rj
+		}
+		return pi < pj
+	})
+
+	// Then each plugin moves after its requirements, taking the first in
+	// that order whose requirements are all placed. Register only accepts
+	// plugins whose requirements came before, so there are no cycles.
+	placed := make(map[string]bool, len(r.plugins))
+	ordered := make([]Plugin, 0, len(r.plugins))
+	for len(ordered) < len(r.plugins) {
+		next := -1
+		for i, plugin := range r.plugins {
+			metadata := plugin.Metadata()
+			if placed[metadata.Name] {
+				continue
+			}
+			ready := true
+			for _, required := range metadata.Requires {
+				ready = ready && placed[required]
+			}
+			if ready {
+				next = i
+				break
+			}
+		}
+		if next < 0 {
+			break
+		}
+		placed[r.plugins[next].Metadata().Name] = true
+		ordered = append(ordered, r.plugins[next])
+	}
+	for _, plugin := range r.plugins {
+		if !placed[plugin.Metadata().Name] {
+			ordered = append(ordered, plugin)
+		}
+	}
+	r.plugins = ordered
+	r.indexCapabilities()
+}
+
+// indexCapabilities records which plugins can do what, so each stage only
+// calls the plugins made for it. A plugin has a capability when it
+// implements the interface and, if it reports capabilities, reports it too;
+// the caller holds the lock.
+func (r *PluginRegistry) indexCapabilities() {
+	r.capable = make(map[string]map[string]bool)
+	for _, plugin := range r.plugins {
+		name := plugin.Metadata().Name
+		for _, capability := range pluginCapabilities(plugin) {
+			if !pluginCan(plugin, capability) {
+				continue
+			}
+			if r.capable[capability] == nil {
+				r.capable[capability] = make(map[string]bool)
+			}
+			r.capable[capability][name] = true
+		}
+	}
+}
+
+// can reports whether the plugin called name has a capability
+func (r *PluginRegistry) can(name, capability string) bool {
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	return r.capable[capability][name]
+}
+
+// List returns the metadata of every plugin in the order they run
+func (r *PluginRegistry) List() []PluginMetadata {
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	list := make([]PluginMetadata, 0, len(r.plugins))
+	for _, plugin := range r.plugins {
+		metadata := plugin.Metadata()
+		if metadata.Capabilities == nil {
+			metadata.Capabilities = pluginCapabilities(plugin)
+		}
+		metadata.Enabled = !r.settings.Plugins[metadata.Name].Disabled
+		metadata.ErrorPolicy = r.errorPolicy(metadata.Name)
+		list = append(list, metadata)
+	}
+	return list
+}
+
+// pluginCapabilities names the optional interfaces a plugin implements
+func pluginCapabilities(plugin Plugin) []string {
+	capabilities := make([]string, 0)
+	if _, ok := plugin.(PreProcessor); ok {
+		capabilities = append(capabilities, PluginPreProcessor)
+	}
+	if _, ok := plugin.(PostProcessor); ok {
+		capabilities = append(capabilities, PluginPostProcessor)
+	}
+	if _, ok := plugin.(BlockProcessor); ok {
+		capabilities = append(capabilities, PluginBlockProcessor)
+	}
+	if _, ok := plugin.(CommandRunner); ok {
+		capabilities = append(capabilities, PluginCustomCommand)
+	}
+	if _, ok := plugin.(Hooks); ok {
+		capabilities = append(capabilities, PluginHooks)
+	}
+	if _, ok := plugin.(Configurable); ok {
+		capabilities = append(capabilities, PluginConfigurable)
+	}
+	return capabilities
+}
+
+// pluginCan reports whether a plugin has a capability, by what it reports
+// or else by the interfaces it implements
+func pluginCan(plugin Plugin, capability string) bool {
+	capabilities := plugin.Metadata().Capabilities
+	if capabilities == nil {
+		capabilities = pluginCapabilities(plugin)
+	}
+	for _, c := range capabilities {
+		if c == capability {
+			return true
+		}
+	}
+	return false
+}
+
+// enabled returns the plugins to run that have any of the capabilities,
+// in run order
+func (r *PluginRegistry) enabled(capabilities ...string) []Plugin {
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	plugins := make([]Plugin, 0, len(r.plugins))
+	for _, plugin := range r.running() {
+		name := plugin.Metadata().Name
+		for _, capability := range capabilities {
+			if r.capable[capability][name] {
+				plugins = append(plugins, plugin)
+				break
+			}
+		}
+	}
+	return plugins
+}
+
+// running returns the plugins that run, in order: those enabled whose
+// requirements run too; the caller holds the lock
+func (r *PluginRegistry) running() []Plugin {
+	plugins := make([]Plugin, 0, len(r.plugins))
+	running := make(map[string]bool, len(r.plugins))
+	for _, plugin := range r.plugins {
+		metadata := plugin.Metadata()
+		if r.settings.Plugins[metadata.Name].Disabled {
+			continue
+		}
+		// Requirements come first in run order, so they have been seen
+		ready := true
+		for _, required := range metadata.Requires {
+			ready = ready && running[required]
+		}
+		if ready {
+			running[metadata.Name] = true
+			plugins = append(plugins, plugin)
+		}
+	}
+	return plugins
+}
+
+// AllowsSecret reports whether a running plugin's allowlist lets a secret
+// through, for secret redaction to leave it alone as well
+func (r *PluginRegistry) AllowsSecret(match string) bool {
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+	for _, plugin := range r.running() {
+		if allowlist, ok := plugin.(RedactionAllowlist); ok && allowlist.AllowsSecret(match) {
+			return true
+		}
+	}
+	return false
+}
+
+// PreProcessAll passes the prompt through every enabled PreProcessor. A
+// plugin that fails is left out of the chain and reported, unless its
+// policy is to abort.
+func (r *PluginRegistry) PreProcessAll(prompt string) (string, []PluginFailure, error) {
+	var failures []PluginFailure
+	for _, plugin := range r.enabled(PluginPreProcessor) {
+		name := plugin.Metadata().Name
+		// A call that times out may still finish, so it gets its own copy
+		input, processed := prompt, ""
+		err := r.invoke(plugin, "pre_process", func() (err error) {
+			processed, err = plugin.(PreProcessor).PreProcess(input)
+			return err
+		})
+		if err != nil {
+			failure, err := r.failed(name, "pre_process", err)
+			if err != nil {
+				return prompt, failures, err
+			}
+			failures = append(failures, failure)
+			continue
+		}
+		r.succeeded(name)
+		prompt = processed
+	}
+	return prompt, failures, nil
+}
+
+// PostProcessAll passes response content through every enabled
+// PostProcessor and, for markdown responses, BlockProcessor, in run order.
+// Consecutive block processors share one parse, and the content is only
+// rewritten as canonical markdown when one ran. Failures are handled as in
+// PreProcessAll.
+func (r *PluginRegistry) PostProcessAll(response, format string) (string, []PluginFailure, error) {
+	var blocks []ContentBlock
+	var failures []PluginFailure
+	parsed := false
+	for _, plugin := range r.enabled(PluginPostProcessor, PluginBlockProcessor) {
+		name := plugin.Metadata().Name
+		if r.can(name, PluginPostProcessor) {
+			if parsed {
+				response, parsed = RenderMarkdown(blocks), false
+			}
+			input, processed := response, ""
+			err := r.invoke(plugin, "post_process", func() (err error) {
+				processed, err = plugin.(PostProcessor).PostProcess(input)
+				return err
+			})
+			if err != nil {
+				failure, err := r.failed(name, "post_process", err)
+				if err != nil {
+					return response, failures, err
+				}
+				failures = append(failures, failure)
+				continue
+			}
+			r.succeeded(name)
+			response = processed
+		}
+		if format != TextPlain && r.can(name, PluginBlockProcessor) {
+			if !parsed {
+				blocks, parsed = ParseResponse(response), true
+			}
+			input := append([]ContentBlock(nil), blocks...)
+			var processed []ContentBlock
+			err := r.invoke(plugin, "process_blocks", func() (err error) {
+				processed, err = plugin.(BlockProcessor).ProcessBlocks(input)
+				return err
+			})
+			if err != nil {
+				failure, err := r.failed(name, "process_blocks", err)
+				if err != nil {
+					return response, failures, err
+				}
+				failures = append(failures, failure)
+				continue
+			}
+			r.succeeded(name)
+			blocks = processed
+		}
+	}
+	if parsed {
+		response = RenderMarkdown(blocks)
+	}
+	return response, failures, nil
+}
+
+// ListPlugins returns the loaded plugins, in the order they run
+func (a *App) ListPlugins() []PluginMetadata {
+	return a.manager.Plugins().List()
+}
+
+// SetPluginEnabled turns a plugin on or off without unloading it
+func (a *App) SetPluginEnabled(name string, enabled bool) {
+	if enabled {
+		a.manager.Plugins().Enable(name)
+	} else {
+		a.manager.Plugins().Disable(name)
+	}
+}
+
+// ConfigurePlugin saves a plugin's options, as JSON, and applies them
+func (a *App) ConfigurePlugin(name string, config json.RawMessage) error {
+	return a.manager.Plugins().Configure(name, config)
+}
+
+// ReloadPlugin loads a dynamic plugin's file again, picking up a new build
+func (a *App) ReloadPlugin(name string) error {
+	return a.manager.Plugins().Reload(name)
+}
+
+// UnloadPlugin unregisters a plugin until the app restarts or its file changes
+func (a *App) UnloadPlugin(name string) error {
+	return a.manager.Plugins().Unregister(name)
+}
+
+// GetPluginConfig returns the options saved for a plugin, or null
+func (a *App) GetPluginConfig(name string) json.RawMessage {
+	return a.manager.Plugins().Config(name)
+}
+
+// SetPluginErrorPolicy decides what a plugin's failures do: "skip" them,
+// "disable" the plugin after maxFailures in a row (0 for the default), or
+// "abort" the request
+func (a *App) SetPluginErrorPolicy(name, onError string, maxFailures int) error {
+	return a.manager.Plugins().SetErrorPolicy(name, PluginErrorPolicy{OnError: onError, MaxFailures: maxFailures})
+}
+
+// SetPluginOrder runs the named plugins first, in the given order
+func (a *App) SetPluginOrder(names []string) error {
+	return a.manager.Plugins().SetOrder(names)
+}
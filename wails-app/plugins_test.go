@@ -0,0 +1,282 @@
+package main
+
+import (
+	"errors"
+	"reflect"
+	"strings"
+	"sync"
+	"testing"
+	"time"
+)
+
+// calls records which plugins ran at which stage, in order
+type calls struct {
+	mu   sync.Mutex
+	list []string
+}
+
+func (c *calls) add(call string) {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	c.list = append(c.list, call)
+}
+
+func (c *calls) get() []string {
+	c.mu.Lock()
+	defer c.mu.Unlock()
+	return append([]string(nil), c.list...)
+}
+
+// testPlugin appends its name to whatever it processes
+type testPlugin struct {
+	metadata PluginMetadata
+	calls    *calls
+}
+
+func (p *testPlugin) Metadata() PluginMetadata { return p.metadata }
+
+func (p *testPlugin) process(stage, text string) string {
+	p.calls.add(p.metadata.Name + ":" + stage)
+	return text + " " + p.metadata.Name
+}
+
+type prePlugin struct{ *testPlugin }
+
+func (p prePlugin) PreProcess(prompt string) (string, error) { return p.process("pre", prompt), nil }
+
+type postPlugin struct{ *testPlugin }
+
+func (p postPlugin) PostProcess(response string) (string, error) {
+	return p.process("post", response), nil
+}
+
+type bothPlugin struct{ *testPlugin }
+
+func (p bothPlugin) PreProcess(prompt string) (string, error) { return p.process("pre", prompt), nil }
+func (p bothPlugin) PostProcess(response string) (string, error) {
+	return p.process("post", response), nil
+}
+
+// failingPlugin fails at both stages
+type failingPlugin struct{ *testPlugin }
+
+func (p failingPlugin) PreProcess(string) (string, error) {
+	p.calls.add(p.metadata.Name + ":pre")
+	return "", errors.New("broken")
+}
+func (p failingPlugin) PostProcess(string) (string, error) {
+	p.calls.add(p.metadata.Name + ":post")
+	return "", errors.New("broken")
+}
+
+// blockingPlugin hangs at both stages until release is closed
+type blockingPlugin struct {
+	*testPlugin
+	release chan struct{}
+}
+
+func (p blockingPlugin) PreProcess(prompt string) (string, error) {
+	<-p.release
+	return prompt + " late", nil
+}
+func (p blockingPlugin) PostProcess(response string) (string, error) {
+	<-p.release
+	return response + " late", nil
+}
+
+func newTestPlugin(log *calls, name string, priority int) *testPlugin {
+	return &testPlugin{metadata: PluginMetadata{Name: name, Version: "1.0.0", Priority: priority}, calls: log}
+}
+
+func newTestRegistry(t *testing.T, plugins ...Plugin) *PluginRegistry {
+	t.Helper()
+	r := NewPluginRegistry("")
+	for _, plugin := range plugins {
+		if err := r.Register(plugin); err != nil {
+			t.Fatalf("Register(%s): %v", plugin.Metadata().Name, err)
+		}
+	}
+	return r
+}
+
+// mixedRegistry has pre-only, post-only and both-capability plugins, one
+// that hangs, a disabled one and one requiring it, and one that implements
+// both stages but only reports post-processing
+func mixedRegistry(t *testing.T, log *calls) *PluginRegistry {
+	t.Helper()
+	release := make(chan struct{})
+	t.Cleanup(func() { close(release) })
+
+	needsOff := newTestPlugin(log, "needs-off", 0)
+	needsOff.metadata.Requires = []string{"off"}
+	reportsPost := newTestPlugin(log, "reports-post", 4)
+	reportsPost.metadata.Capabilities = []string{PluginPostProcessor}
+
+	r := newTestRegistry(t,
+		blockingPlugin{newTestPlugin(log, "blocking", 3), release},
+		postPlugin{newTestPlugin(log, "post-only", 2)},
+		prePlugin{newTestPlugin(log, "pre-only", 1)},
+		bothPlugin{newTestPlugin(log, "both", 0)},
+		bothPlugin{newTestPlugin(log, "off", 0)},
+		prePlugin{needsOff},
+		bothPlugin{reportsPost},
+	)
+	r.Disable("off")
+	for _, stage := range []string{"pre_process", "post_process"} {
+		if err := r.SetTimeout("blocking", stage, 20*time.Millisecond); err != nil {
+			t.Fatal(err)
+		}
+	}
+	return r
+}
+
+func failedPlugins(failures []PluginFailure) []string {
+	var names []string
+	for _, failure := range failures {
+		names = append(names, failure.Plugin+":"+failure.Stage)
+	}
+	return names
+}
+
+func TestPluginRegistryMixedCapabilities(t *testing.T) {
+	tests := []struct {
+		name      string
+		run       func(r *PluginRegistry) (string, []PluginFailure, error)
+		want      string
+		wantCalls []string
+	}{
+		{
+			name: "pre-process",
+			run:  func(r *PluginRegistry) (string, []PluginFailure, error) { return r.PreProcessAll("prompt") },
+			// post-only and reports-post don't pre-process; off is disabled
+			// and needs-off requires it
+			want:      "prompt both pre-only",
+			wantCalls: []string{"both:pre", "pre-only:pre"},
+		},
+		{
+			name: "post-process",
+			run:  func(r *PluginRegistry) (string, []PluginFailure, error) { return r.PostProcessAll("response", TextMarkdown) },
+			want:      "response both post-only reports-post",
+			wantCalls: []string{"both:post", "post-only:post", "reports-post:post"},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			log := &calls{}
+			r := mixedRegistry(t, log)
+			got, failures, err := tt.run(r)
+			if err != nil {
+				t.Fatalf("unexpected error: %v", err)
+			}
+			if got != tt.want {
+				t.Errorf("got %q, want %q", got, tt.want)
+			}
+			if ran := log.get(); !reflect.DeepEqual(ran, tt.wantCalls) {
+				t.Errorf("calls = %v, want %v", ran, tt.wantCalls)
+			}
+			// The hung plugin is skipped once its timeout passes
+			if len(failures) != 1 || failures[0].Plugin != "blocking" || !strings.Contains(failures[0].Error, ErrPluginTimeout.Error()) {
+				t.Errorf("failures = %+v, want blocking timing out", failures)
+			}
+		})
+	}
+}
+
+func TestPluginRegistryOrder(t *testing.T) {
+	log := &calls{}
+	r := newTestRegistry(t,
+		prePlugin{newTestPlugin(log, "late", 5)},
+		prePlugin{newTestPlugin(log, "early", -5)},
+		prePlugin{newTestPlugin(log, "tie-a", 0)},
+		prePlugin{newTestPlugin(log, "tie-b", 0)},
+	)
+	got, _, _ := r.PreProcessAll("p")
+	if want := "p early tie-a tie-b late"; got != want {
+		t.Errorf("by priority: got %q, want %q", got, want)
+	}
+	if err := r.SetOrder([]string{"late", "tie-b"}); err != nil {
+		t.Fatal(err)
+	}
+	got, _, _ = r.PreProcessAll("p")
+	if want := "p late tie-b early tie-a"; got != want {
+		t.Errorf("with SetOrder: got %q, want %q", got, want)
+	}
+}
+
+func TestPluginRegistryErrorPolicies(t *testing.T) {
+	tests := []struct {
+		name      string
+		policy    PluginErrorPolicy
+		runs      int
+		want      string
+		wantErr   bool
+		wantCalls []string
+	}{
+		{
+			name:      "skip",
+			policy:    PluginErrorPolicy{OnError: PluginOnErrorSkip},
+			runs:      1,
+			want:      "p first last",
+			wantCalls: []string{"first:pre", "failing:pre", "last:pre"},
+		},
+		{
+			name:      "abort",
+			policy:    PluginErrorPolicy{OnError: PluginOnErrorAbort},
+			runs:      1,
+			want:      "p first",
+			wantErr:   true,
+			wantCalls: []string{"first:pre", "failing:pre"},
+		},
+		{
+			name:   "disable",
+			policy: PluginErrorPolicy{OnError: PluginOnErrorDisable, MaxFailures: 2},
+			runs:   3,
+			want:   "p first last",
+			// Disabled by the second failure, so the third run leaves it out
+			wantCalls: []string{
+				"first:pre", "failing:pre", "last:pre",
+				"first:pre", "failing:pre", "last:pre",
+				"first:pre", "last:pre",
+			},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			log := &calls{}
+			r := newTestRegistry(t,
+				prePlugin{newTestPlugin(log, "first", 0)},
+				failingPlugin{newTestPlugin(log, "failing", 1)},
+				prePlugin{newTestPlugin(log, "last", 2)},
+			)
+			if err := r.SetErrorPolicy("failing", tt.policy); err != nil {
+				t.Fatal(err)
+			}
+			var got string
+			var err error
+			var disabled bool
+			for i := 0; i < tt.runs; i++ {
+				var failures []PluginFailure
+				got, failures, err = r.PreProcessAll("p")
+				for _, failure := range failures {
+					disabled = disabled || failure.Disabled
+				}
+				if err == nil && i < 2 && !reflect.DeepEqual(failedPlugins(failures), []string{"failing:pre_process"}) {
+					t.Errorf("run %d: failures = %v", i+1, failures)
+				}
+			}
+			var pluginErr *PluginError
+			if tt.wantErr != (err != nil) || (err != nil && (!errors.As(err, &pluginErr) || pluginErr.Plugin != "failing")) {
+				t.Errorf("err = %v, want error %v", err, tt.wantErr)
+			}
+			if got != tt.want {
+				t.Errorf("got %q, want %q", got, tt.want)
+			}
+			if ran := log.get(); !reflect.DeepEqual(ran, tt.wantCalls) {
+				t.Errorf("calls = %v, want %v", ran, tt.wantCalls)
+			}
+			if tt.policy.OnError == PluginOnErrorDisable && !disabled {
+				t.Error("the failing plugin was never reported disabled")
+			}
+		})
+	}
+}
@@ -0,0 +1,101 @@
+package main
+
+import (
+	"fmt"
+	"regexp"
+	"strings"
+)
+
+// Code blocks longer than this are flagged as possibly verbatim copies
+const verbatimSnippetLines = 60
+
+var licenseMarkers = []struct {
+	pattern *regexp.Regexp
+	label   string
+}{
+	{regexp.MustCompile(`(?i)SPDX-License-Identifier:\s*\S+`), "SPDX identifier"},
+	{regexp.MustCompile(`(?i)copyright\s+(\(c\)|©)?\s*\d{4}`), "copyright notice"},
+	{regexp.MustCompile(`(?i)all rights reserved`), "all rights reserved"},
+	{regexp.MustCompile(`(?i)licensed under the`), "license grant"},
+	{regexp.MustCompile(`(?i)GNU (Lesser |Affero )?General Public License`), "GPL family"},
+	{regexp.MustCompile(`(?i)Apache License,?\s+Version`), "Apache License"},
+	{regexp.MustCompile(`(?i)Mozilla Public License`), "MPL"},
+	{regexp.MustCompile(`(?i)Permission is hereby granted, free of charge`), "MIT/X11 grant"},
+	{regexp.MustCompile(`(?i)Redistribution and use in source and binary forms`), "BSD grant"},
+}
+
+// ProvenanceFlag marks a code block that may warrant a licensing review
+type ProvenanceFlag struct {
+	BlockIndex int    `json:"blockIndex"`
+	Language   string `json:"language"`
+	Kind       string `json:"kind"` // "license-header" or "verbatim-snippet"
+	Detail     string `json:"detail"`
+}
+
+// ProvenanceReport is the result of analysing a response for generated-code provenance
+type ProvenanceReport struct {
+	Flags []ProvenanceFlag `json:"flags"`
+	Note  string           `json:"note"`
+}
+
+// AnalyzeProvenance flags the code blocks of a parsed response, nested ones
+// included, that contain license headers or look like large verbatim copies.
+// Flags count the code blocks in document order.
+func AnalyzeProvenance(blocks []ContentBlock) ProvenanceReport {
+	report := ProvenanceReport{Flags: make([]ProvenanceFlag, 0)}
+
+	i := -1
+	walkBlocks(blocks, func(block ContentBlock) {
+		if block.Type != BlockCode && block.Type != BlockDiff && block.Type != BlockDiagram {
+			return
+		}
+		i++
+		for _, marker := range licenseMarkers {
+			if match := marker.pattern.FindString(block.Code); match != "" {
+				report.Flags = append(report.Flags, ProvenanceFlag{
+					BlockIndex: i,
+					Language:   block.Language,
+					Kind:       "license-header",
+					Detail:     fmt.Sprintf("%s: %q", marker.label, strings.TrimSpace(match)),
+				})
+				break
+			}
+		}
+
+		if lines := strings.Count(block.Code, "\n") + 1; lines >= verbatimSnippetLines {
+			report.Flags = append(report.Flags, ProvenanceFlag{
+				BlockIndex: i,
+				Language:   block.Language,
+				Kind:       "verbatim-snippet",
+				Detail:     fmt.Sprintf("%d lines; may be reproduced from an existing source", lines),
+			})
+		}
+	})
+
+	if len(report.Flags) > 0 {
+		report.Note = fmt.Sprintf("AI-generated code: %d block(s) flagged for license/provenance review", len(report.Flags))
+	} else {
+		report.Note = "AI-generated code: no license headers or verbatim-looking snippets detected"
+	}
+
+	return report
+}
+
+// summary is the note followed by each flag, on one line
+func (report ProvenanceReport) summary() string {
+	parts := []string{report.Note}
+	for _, flag := range report.Flags {
+		block := fmt.Sprintf("code block %d", flag.BlockIndex+1)
+		if flag.Language != "" {
+			block += " (" + flag.Language + ")"
+		}
+		parts = append(parts, fmt.Sprintf("%s, %s: %s", block, strings.ReplaceAll(flag.Kind, "-", " "), flag.Detail))
+	}
+	return strings.Join(parts, "; ")
+}
+
+// AnalyzeProvenance exposes provenance analysis of a response, parsed in
+// its text format as ParseResponse does, to the frontend
+func (a *App) AnalyzeProvenance(response, format string) ProvenanceReport {
+	return AnalyzeProvenance(ParseResponseAs(response, format))
+}
@@ -0,0 +1,49 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestAnalyzeProvenance(t *testing.T) {
+	long := strings.Repeat("x := 1\n", verbatimSnippetLines)
+	tests := []struct {
+		name     string
+		response string
+		want     []ProvenanceFlag
+	}{
+		{"no code", "Just prose, copyright 2024 mentioned in passing.", []ProvenanceFlag{}},
+		{
+			name:     "license header",
+			response: "```go\n// SPDX-License-Identifier: MIT\npackage main\n```",
+			want:     []ProvenanceFlag{{BlockIndex: 0, Language: "go", Kind: "license-header", Detail: `SPDX identifier: "SPDX-License-Identifier: MIT"`}},
+		},
+		{
+			name:     "long snippet after a clean one",
+			response: "```go\nx := 1\n```\n\n```go\n" + long + "```",
+			want:     []ProvenanceFlag{{BlockIndex: 1, Language: "go", Kind: "verbatim-snippet", Detail: "60 lines; may be reproduced from an existing source"}},
+		},
+		{
+			name:     "nested in a quote",
+			response: "> Quoted:\n>\n> ```python\n> # Copyright (c) 2019 Someone\n> ```",
+			want:     []ProvenanceFlag{{BlockIndex: 0, Language: "python", Kind: "license-header", Detail: `copyright notice: "Copyright (c) 2019"`}},
+		},
+		{
+			name:     "fence inside a longer fence",
+			response: "````markdown\n```c\n/* All rights reserved */\n```\n````",
+			want:     []ProvenanceFlag{{BlockIndex: 0, Language: "markdown", Kind: "license-header", Detail: `all rights reserved: "All rights reserved"`}},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			report := AnalyzeProvenance(ParseResponse(tt.response))
+			if !reflect.DeepEqual(report.Flags, tt.want) {
+				t.Errorf("got %+v, want %+v", report.Flags, tt.want)
+			}
+			if flagged := strings.Contains(report.Note, "flagged"); flagged != (len(tt.want) > 0) {
+				t.Errorf("note %q doesn't match the flags", report.Note)
+			}
+		})
+	}
+}
@@ -0,0 +1,309 @@
+package main
+
+import (
+	"bufio"
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"io"
+	"net/http"
+	"net/url"
+	"strings"
+)
+
+const (
+	defaultGeminiEndpoint = "https://generativelanguage.googleapis.com"
+	defaultGeminiModel    = "gemini-pro"
+)
+
+// Harm categories covered by the configured safety threshold
+var geminiHarmCategories = []string{
+	"HARM_CATEGORY_HARASSMENT",
+	"HARM_CATEGORY_HATE_SPEECH",
+	"HARM_CATEGORY_SEXUALLY_EXPLICIT",
+	"HARM_CATEGORY_DANGEROUS_CONTENT",
+}
+
+// GeminiProvider talks to Google's Generative Language API
+type GeminiProvider struct {
+	config ProviderConfig
+	client *http.Client
+}
+
+func NewGeminiProvider(config ProviderConfig) *GeminiProvider {
+	if config.Endpoint == "" {
+		config.Endpoint = defaultGeminiEndpoint
+	}
+	if config.Model == "" {
+		config.Model = defaultGeminiModel
+	}
+	return &GeminiProvider{
+		config: config,
+		client: newHTTPClient(config),
+	}
+}
+
+func (p *GeminiProvider) GetName() string {
+	if p.config.Name != "" {
+		return p.config.Name
+	}
+	return "Gemini"
+}
+
+type geminiPart struct {
+	Text       string            `json:"text,omitempty"`
+	InlineData *geminiInlineData `json:"inlineData,omitempty"`
+}
+
+type geminiInlineData struct {
+	MimeType string `json:"mimeType"`
+	Data     []byte `json:"data"` // base64 in JSON
+}
+
+type geminiContent struct {
+	Role  string       `json:"role,omitempty"`
+	Parts []geminiPart `json:"parts"`
+}
+
+type geminiResponse struct {
+	Candidates []struct {
+		Content      geminiContent `json:"content"`
+		FinishReason string        `json:"finishReason"`
+	} `json:"candidates"`
+	PromptFeedback struct {
+		BlockReason string `json:"blockReason"`
+	} `json:"promptFeedback"`
+	UsageMetadata struct {
+		PromptTokenCount     int `json:"promptTokenCount"`
+		CandidatesTokenCount int `json:"candidatesTokenCount"`
+	} `json:"usageMetadata"`
+}
+
+// text joins the parts of the first candidate, or explains why there is none
+func (r geminiResponse) text() (string, error) {
+	if r.PromptFeedback.BlockReason != "" {
+		return "", fmt.Errorf("prompt blocked by safety filters: %s", r.PromptFeedback.BlockReason)
+	}
+	if len(r.Candidates) == 0 {
+		return "", fmt.Errorf("missing 'candidates' field")
+	}
+	candidate := r.Candidates[0]
+	if candidate.FinishReason == "SAFETY" && len(candidate.Content.Parts) == 0 {
+		return "", fmt.Errorf("response blocked by safety filters")
+	}
+
+	var text strings.Builder
+	for _, part := range candidate.Content.Parts {
+		text.WriteString(part.Text)
+	}
+	return text.String(), nil
+}
+
+func (p *GeminiProvider) payload(request AIRequest) map[string]interface{} {
+	contents := make([]geminiContent, 0, len(request.History)+1)
+	var system []geminiPart
+	for _, m := range request.Messages() {
+		switch m.Role {
+		case "system":
+			system = append(system, geminiPart{Text: m.Content})
+		case "assistant":
+			contents = append(contents, geminiContent{Role: "model", Parts: []geminiPart{{Text: m.Content}}})
+		default:
+			contents = append(contents, geminiContent{Role: "user", Parts: []geminiPart{{Text: m.Content}}})
+		}
+	}
+
+	generation := map[string]interface{}{
+		"temperature":     request.Temperature,
+		"maxOutputTokens": request.MaxTokens,
+	}
+	request.Sampling.set(generation, "topP", "frequencyPenalty", "presencePenalty", "stopSequences")
+	if request.ResponseFormat.JSON() {
+		generation["responseMimeType"] = "application/json"
+		if request.ResponseFormat.Type == FormatJSONSchema && len(request.ResponseFormat.Schema) > 0 {
+			generation["responseJsonSchema"] = request.ResponseFormat.Schema
+		}
+	}
+
+	payload := map[string]interface{}{
+		"contents":         contents,
+		"generationConfig": generation,
+	}
+	if len(request.Attachments) > 0 {
+		last := &contents[len(contents)-1]
+		for _, a := range request.Attachments {
+			last.Parts = append(last.Parts, geminiPart{InlineData: &geminiInlineData{MimeType: a.MimeType, Data: a.Data}})
+		}
+	}
+	if len(system) > 0 {
+		payload["systemInstruction"] = geminiContent{Parts: system}
+	}
+	if p.config.SafetyThreshold != "" {
+		settings := make([]map[string]string, len(geminiHarmCategories))
+		for i, category := range geminiHarmCategories {
+			settings[i] = map[string]string{"category": category, "threshold": p.config.SafetyThreshold}
+		}
+		payload["safetySettings"] = settings
+	}
+	return payload
+}
+
+// endpoint builds an API URL for the configured model; method is e.g. "generateContent"
+func (p *GeminiProvider) endpoint(method string, query url.Values) string {
+	model := strings.TrimPrefix(p.config.Model, "models/")
+	target := fmt.Sprintf("%s/v1beta/models/%s:%s", p.config.Endpoint, url.PathEscape(model), method)
+	if len(query) > 0 {
+		target += "?" + query.Encode()
+	}
+	return target
+}
+
+// do sends an authenticated request; the key goes in a header so it never appears in error URLs
+func (p *GeminiProvider) do(method, target string, body io.Reader) (*http.Response, error) {
+	req, err := http.NewRequest(method, target, body)
+	if err != nil {
+		return nil, err
+	}
+	req.Header.Set("Content-Type", "application/json")
+	req.Header.Set("x-goog-api-key", p.config.APIKey)
+
+	resp, err := p.client.Do(req)
+	if err != nil {
+		return nil, networkError(err)
+	}
+	return resp, nil
+}
+
+func (p *GeminiProvider) post(target string, request AIRequest) (*http.Response, error) {
+	jsonData, err := json.Marshal(p.payload(request))
+	if err != nil {
+		return nil, err
+	}
+
+	resp, err := p.do(http.MethodPost, target, bytes.NewBuffer(jsonData))
+	if err != nil {
+		return nil, err
+	}
+
+	if resp.StatusCode != http.StatusOK {
+		defer resp.Body.Close()
+		return nil, httpError(resp)
+	}
+
+	return resp, nil
+}
+
+func (p *GeminiProvider) SendRequest(request AIRequest) (AIResponse, error) {
+	resp, err := p.post(p.endpoint("generateContent", nil), request)
+	if err != nil {
+		return AIResponse{}, err
+	}
+	defer resp.Body.Close()
+
+	var result geminiResponse
+	if err := json.NewDecoder(resp.Body).Decode(&result); err != nil {
+		return AIResponse{}, fmt.Errorf("invalid response: %v", err)
+	}
+
+	content, err := result.text()
+	if err != nil {
+		return AIResponse{}, err
+	}
+
+	return AIResponse{
+		Content:          content,
+		Model:            p.config.Model,
+		PromptTokens:     result.UsageMetadata.PromptTokenCount,
+		CompletionTokens: result.UsageMetadata.CandidatesTokenCount,
+	}, nil
+}
+
+// SendRequestStream reads the server-sent events of streamGenerateContent
+func (p *GeminiProvider) SendRequestStream(request AIRequest, onChunk func(delta string)) (AIResponse, error) {
+	resp, err := p.post(p.endpoint("streamGenerateContent", url.Values{"alt": {"sse"}}), request)
+	if err != nil {
+		return AIResponse{}, err
+	}
+	defer resp.Body.Close()
+
+	response := AIResponse{Model: p.config.Model}
+	var full strings.Builder
+	scanner := bufio.NewScanner(resp.Body)
+	scanner.Buffer(make([]byte, 0, 64*1024), 1024*1024)
+	for scanner.Scan() {
+		data, ok := strings.CutPrefix(scanner.Text(), "data: ")
+		if !ok {
+			continue
+		}
+
+		var chunk geminiResponse
+		if err := json.Unmarshal([]byte(data), &chunk); err != nil {
+			response.Content = full.String()
+			return response, fmt.Errorf("invalid response: %v", err)
+		}
+		delta, err := chunk.text()
+		if err != nil {
+			response.Content = full.String()
+			return response, err
+		}
+		if delta != "" {
+			full.WriteString(delta)
+			onChunk(delta)
+		}
+		if chunk.UsageMetadata.PromptTokenCount > 0 {
+			response.PromptTokens = chunk.UsageMetadata.PromptTokenCount
+			response.CompletionTokens = chunk.UsageMetadata.CandidatesTokenCount
+		}
+	}
+	response.Content = full.String()
+	if err := scanner.Err(); err != nil {
+		return response, networkError(err)
+	}
+
+	return response, nil
+}
+
+// AcceptsImages reports vision support, which every current Gemini model has
+func (p *GeminiProvider) AcceptsImages() bool {
+	return true
+}
+
+// ListModels returns the models that support content generation
+func (p *GeminiProvider) ListModels() ([]string, error) {
+	resp, err := p.do(http.MethodGet, fmt.Sprintf("%s/v1beta/models?pageSize=1000", p.config.Endpoint), nil)
+	if err != nil {
+		return nil, err
+	}
+	defer resp.Body.Close()
+
+	if resp.StatusCode != http.StatusOK {
+		return nil, httpError(resp)
+	}
+
+	var result struct {
+		Models []struct {
+			Name                       string   `json:"name"`
+			SupportedGenerationMethods []string `json:"supportedGenerationMethods"`
+		} `json:"models"`
+	}
+	if err := json.NewDecoder(resp.Body).Decode(&result); err != nil {
+		return nil, fmt.Errorf("invalid response: %v", err)
+	}
+
+	models := make([]string, 0, len(result.Models))
+	for _, m := range result.Models {
+		for _, method := range m.SupportedGenerationMethods {
+			if method == "generateContent" {
+				models = append(models, strings.TrimPrefix(m.Name, "models/"))
+				break
+			}
+		}
+	}
+	return models, nil
+}
+
+// HealthCheck verifies the API key by listing models
+func (p *GeminiProvider) HealthCheck() error {
+	_, err := p.ListModels()
+	return err
+}
@@ -0,0 +1,218 @@
+//go:build llamacpp
+
+package main
+
+/*
+#cgo LDFLAGS: -lllama
+#include <stdlib.h>
+#include <llama.h>
+*/
+import "C"
+
+import (
+	"fmt"
+	"strings"
+	"sync"
+	"unsafe"
+)
+
+// LlamaCppProvider runs a GGUF model in-process through llama.cpp, so no
+// network is ever needed. Build with `-tags llamacpp` against an installed
+// libllama; config.Model is the path to the .gguf file.
+type LlamaCppProvider struct {
+	config ProviderConfig
+
+	mu      sync.Mutex // a llama.cpp context serves one request at a time
+	model   *C.struct_llama_model
+	loadErr error
+}
+
+func NewLlamaCppProvider(config ProviderConfig) *LlamaCppProvider {
+	return &LlamaCppProvider{config: config}
+}
+
+var llamaBackendOnce sync.Once
+
+func (p *LlamaCppProvider) GetName() string {
+	if p.config.Name != "" {
+		return p.config.Name
+	}
+	return "llama.cpp"
+}
+
+// load reads the model file on first use; the caller holds p.mu
+func (p *LlamaCppProvider) load() error {
+	if p.model != nil || p.loadErr != nil {
+		return p.loadErr
+	}
+	llamaBackendOnce.Do(func() { C.llama_backend_init() })
+
+	path := C.CString(p.config.Model)
+	defer C.free(unsafe.Pointer(path))
+	p.model = C.llama_model_load_from_file(path, C.llama_model_default_params())
+	if p.model == nil {
+		p.loadErr = fmt.Errorf("failed to load GGUF model %q", p.config.Model)
+	}
+	return p.loadErr
+}
+
+// HealthCheck verifies the model file loads
+func (p *LlamaCppProvider) HealthCheck() error {
+	p.mu.Lock()
+	defer p.mu.Unlock()
+
+	return p.load()
+}
+
+// applyTemplate renders the conversation with the model's built-in chat template
+func (p *LlamaCppProvider) applyTemplate(messages []ChatMessage) (string, error) {
+	chat := make([]C.struct_llama_chat_message, len(messages))
+	for i, m := range messages {
+		chat[i].role = C.CString(m.Role)
+		chat[i].content = C.CString(m.Content)
+	}
+	defer func() {
+		for _, m := range chat {
+			C.free(unsafe.Pointer(m.role))
+			C.free(unsafe.Pointer(m.content))
+		}
+	}()
+
+	template := C.llama_model_chat_template(p.model, nil)
+	buf := make([]byte, 4096)
+	for {
+		n := C.llama_chat_apply_template(template, &chat[0], C.size_t(len(chat)), true,
+			(*C.char)(unsafe.Pointer(&buf[0])), C.int32_t(len(buf)))
+		if n < 0 {
+			return "", fmt.Errorf("model has no usable chat template")
+		}
+		if int(n) <= len(buf) {
+			return string(buf[:n]), nil
+		}
+		buf = make([]byte, n)
+	}
+}
+
+func (p *LlamaCppProvider) SendRequest(request AIRequest) (AIResponse, error) {
+	return p.SendRequestStream(request, func(string) {})
+}
+
+// SendRequestStream generates tokens one at a time, emitting each decoded piece
+func (p *LlamaCppProvider) SendRequestStream(request AIRequest, onChunk func(delta string)) (AIResponse, error) {
+	p.mu.Lock()
+	defer p.mu.Unlock()
+
+	if err := p.load(); err != nil {
+		return AIResponse{}, err
+	}
+	vocab := C.llama_model_get_vocab(p.model)
+
+	prompt, err := p.applyTemplate(request.Messages())
+	if err != nil {
+		return AIResponse{}, err
+	}
+	// Token buffers live in C memory because llama_batch keeps pointers to them
+	tokens, count, err := llamaTokenize(vocab, prompt)
+	if err != nil {
+		return AIResponse{}, err
+	}
+	defer C.free(unsafe.Pointer(tokens))
+	next := (*C.llama_token)(C.malloc(C.size_t(unsafe.Sizeof(C.llama_token(0)))))
+	defer C.free(unsafe.Pointer(next))
+
+	// The context holds the prompt and the completion, up to what the model
+	// was trained on
+	trained := int(C.llama_model_n_ctx_train(p.model))
+	if trained <= 0 {
+		trained = ContextWindow(p.config.Model)
+	}
+	if count >= trained {
+		return AIResponse{}, fmt.Errorf("the prompt is %d tokens, more than the model's %d-token context", count, trained)
+	}
+	window := min(count+request.MaxTokens, trained)
+	params := C.llama_context_default_params()
+	params.n_ctx = C.uint32_t(window)
+	params.n_batch = C.uint32_t(count)
+	ctx := C.llama_init_from_model(p.model, params)
+	if ctx == nil {
+		return AIResponse{}, fmt.Errorf("failed to create llama.cpp context")
+	}
+	defer C.llama_free(ctx)
+
+	sampler := C.llama_sampler_chain_init(C.llama_sampler_chain_default_params())
+	defer C.llama_sampler_free(sampler)
+	sampling := request.Sampling
+	if sampling.FrequencyPenalty != 0 || sampling.PresencePenalty != 0 {
+		// Penalize over the last 64 tokens, with no extra repeat penalty
+		C.llama_sampler_chain_add(sampler, C.llama_sampler_init_penalties(64, 1.0,
+			C.float(sampling.FrequencyPenalty), C.float(sampling.PresencePenalty)))
+	}
+	if request.Temperature > 0 {
+		if sampling.TopP > 0 {
+			C.llama_sampler_chain_add(sampler, C.llama_sampler_init_top_p(C.float(sampling.TopP), 1))
+		}
+		C.llama_sampler_chain_add(sampler, C.llama_sampler_init_temp(C.float(request.Temperature)))
+		C.llama_sampler_chain_add(sampler, C.llama_sampler_init_dist(C.LLAMA_DEFAULT_SEED))
+	} else {
+		C.llama_sampler_chain_add(sampler, C.llama_sampler_init_greedy())
+	}
+
+	response := AIResponse{Model: p.config.Model, PromptTokens: count}
+	var full strings.Builder
+	batch := C.llama_batch_get_one(tokens, C.int32_t(count))
+	piece := make([]byte, 256)
+	sent := 0 // bytes of full passed to onChunk
+	for response.CompletionTokens < request.MaxTokens && count+response.CompletionTokens < window {
+		if C.llama_decode(ctx, batch) != 0 {
+			response.Content = full.String()
+			return response, fmt.Errorf("llama.cpp decode failed")
+		}
+		*next = C.llama_sampler_sample(sampler, ctx, -1)
+		if C.llama_vocab_is_eog(vocab, *next) {
+			break
+		}
+		response.CompletionTokens++
+
+		n := C.llama_token_to_piece(vocab, *next, (*C.char)(unsafe.Pointer(&piece[0])), C.int32_t(len(piece)), 0, false)
+		if n > 0 {
+			full.Write(piece[:n])
+			text, stopped := cutAtStop(full.String(), sampling.Stop)
+			if stopped {
+				// Emit whatever precedes the stop sequence that hasn't been sent yet
+				if len(text) > sent {
+					onChunk(text[sent:])
+				}
+				response.Content = text
+				return response, nil
+			}
+			// What could be the start of a stop sequence waits for the next piece
+			if ready := len(text) - stopHoldback(text, sampling.Stop); ready > sent {
+				onChunk(text[sent:ready])
+				sent = ready
+			}
+		}
+		batch = C.llama_batch_get_one(next, 1)
+	}
+	response.Content = full.String()
+	if len(response.Content) > sent {
+		onChunk(response.Content[sent:])
+	}
+
+	return response, nil
+}
+
+// llamaTokenize converts text to model tokens in a C buffer the caller must free
+func llamaTokenize(vocab *C.struct_llama_vocab, text string) (*C.llama_token, int, error) {
+	ctext := C.CString(text)
+	defer C.free(unsafe.Pointer(ctext))
+
+	// Never more tokens than bytes, plus room for BOS/EOS
+	capacity := len(text) + 2
+	tokens := (*C.llama_token)(C.malloc(C.size_t(capacity) * C.size_t(unsafe.Sizeof(C.llama_token(0)))))
+	n := C.llama_tokenize(vocab, ctext, C.int32_t(len(text)), tokens, C.int32_t(capacity), true, true)
+	if n <= 0 {
+		C.free(unsafe.Pointer(tokens))
+		return nil, 0, fmt.Errorf("failed to tokenize prompt")
+	}
+	return tokens, int(n), nil
+}
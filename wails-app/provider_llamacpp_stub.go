@@ -0,0 +1,32 @@
+//go:build !llamacpp
+
+package main
+
+import "fmt"
+
+// LlamaCppProvider stands in for the in-process provider in builds without
+// the llamacpp tag, failing every request with a hint on how to enable it
+type LlamaCppProvider struct {
+	config ProviderConfig
+}
+
+func NewLlamaCppProvider(config ProviderConfig) *LlamaCppProvider {
+	return &LlamaCppProvider{config: config}
+}
+
+func (p *LlamaCppProvider) GetName() string {
+	if p.config.Name != "" {
+		return p.config.Name
+	}
+	return "llama.cpp"
+}
+
+func (p *LlamaCppProvider) SendRequest(request AIRequest) (AIResponse, error) {
+	return AIResponse{}, fmt.Errorf("llama.cpp support is not compiled in; rebuild with -tags llamacpp")
+}
+
+// HealthCheck reports the provider as unhealthy so the sidebar explains why
+func (p *LlamaCppProvider) HealthCheck() error {
+	_, err := p.SendRequest(AIRequest{})
+	return err
+}
@@ -0,0 +1,115 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"strings"
+	"sync"
+	"time"
+)
+
+// MockScript makes the mock provider behave deterministically, so tests and
+// demos can exercise slow responses and error paths
+type MockScript struct {
+	Responses []string `json:"responses"` // returned in order, then the default mock response
+	LatencyMs int      `json:"latencyMs"` // delay before each response
+	FailEvery int      `json:"failEvery"` // every Nth request fails with a retryable NetworkError; 0 never fails
+
+	// A streamed response fails with a retryable NetworkError after this many
+	// chunks, when it has more; 0 streams it whole
+	FailStreamAfter int `json:"failStreamAfter"`
+}
+
+// ErrMockFailure is the injected failure returned by a scripted mock provider
+var ErrMockFailure = errors.New("injected mock failure")
+
+type MockProvider struct {
+	config ProviderConfig
+
+	mu       sync.Mutex
+	requests int // requests seen, for failure injection
+	next     int // index of the next scripted response
+}
+
+func NewMockProvider(config ProviderConfig) *MockProvider {
+	return &MockProvider{config: config}
+}
+
+func (p *MockProvider) GetName() string {
+	if p.config.Name != "" {
+		return p.config.Name
+	}
+	return "Mock"
+}
+
+// scripted advances the script, returning the canned response to use (if any)
+// or the injected failure for this request
+func (p *MockProvider) scripted() (string, bool, error) {
+	script := p.config.MockScript
+	if script == nil {
+		return "", false, nil
+	}
+
+	p.mu.Lock()
+	p.requests++
+	failing := script.FailEvery > 0 && p.requests%script.FailEvery == 0
+	content, ok := "", false
+	if !failing && p.next < len(script.Responses) {
+		content, ok = script.Responses[p.next], true
+		p.next++
+	}
+	p.mu.Unlock()
+
+	if script.LatencyMs > 0 {
+		time.Sleep(time.Duration(script.LatencyMs) * time.Millisecond)
+	}
+	if failing {
+		return "", false, &NetworkError{Err: ErrMockFailure}
+	}
+	return content, ok, nil
+}
+
+func (p *MockProvider) SendRequest(request AIRequest) (AIResponse, error) {
+	content, ok, err := p.scripted()
+	if err != nil {
+		return AIResponse{}, err
+	}
+	if !ok {
+		content = p.defaultContent(request)
+	}
+	// Honor stop sequences so the mock behaves like a real provider
+	content, _ = cutAtStop(content, request.Sampling.Stop)
+	return AIResponse{Content: content, Model: p.config.Model}, nil
+}
+
+// defaultContent is the response used when there is no script or it has run out
+func (p *MockProvider) defaultContent(request AIRequest) string {
+	turn := len(request.History)/2 + 1
+	if request.ResponseFormat.JSON() {
+		content, _ := json.Marshal(map[string]interface{}{"turn": turn, "prompt": request.Prompt})
+		return string(content)
+	}
+	return fmt.Sprintf("# Mock AI Response\n\nYou asked (turn %d): %s\n\n## Code Example\n\n```go\nfunc hello() {\n    fmt.Println(\"Hello from Vibe Coder!\")\n}\n```\n\n## Explanation\n\nThis is a mock response demonstrating the parsing capabilities.", turn, request.Prompt)
+}
+
+// SendRequestStream emits the mock response word by word
+func (p *MockProvider) SendRequestStream(request AIRequest, onChunk func(delta string)) (AIResponse, error) {
+	response, err := p.SendRequest(request)
+	if err != nil {
+		return AIResponse{}, err
+	}
+	failAfter := 0
+	if p.config.MockScript != nil {
+		failAfter = p.config.MockScript.FailStreamAfter
+	}
+	var sent strings.Builder
+	for i, word := range strings.SplitAfter(response.Content, " ") {
+		if failAfter > 0 && i == failAfter {
+			return AIResponse{Content: sent.String(), Model: response.Model}, &NetworkError{Err: ErrMockFailure}
+		}
+		sent.WriteString(word)
+		onChunk(word)
+	}
+	return response, nil
+}
@@ -0,0 +1,227 @@
+package main
+
+import (
+	"bufio"
+	"bytes"
+	"encoding/base64"
+	"encoding/json"
+	"fmt"
+	"net/http"
+	"strings"
+)
+
+const defaultOllamaEndpoint = "http://localhost:11434"
+
+type OllamaProvider struct {
+	config ProviderConfig
+	client *http.Client
+}
+
+func NewOllamaProvider(config ProviderConfig) *OllamaProvider {
+	if config.Endpoint == "" {
+		config.Endpoint = defaultOllamaEndpoint
+	}
+	return &OllamaProvider{
+		config: config,
+		client: newHTTPClient(config),
+	}
+}
+
+func (p *OllamaProvider) GetName() string {
+	if p.config.Name != "" {
+		return p.config.Name
+	}
+	return "Ollama"
+}
+
+// post sends a request to Ollama's chat API and returns the open response
+func (p *OllamaProvider) post(request AIRequest, stream bool) (*http.Response, error) {
+	url := fmt.Sprintf("%s/api/chat", p.config.Endpoint)
+
+	messages := make([]map[string]interface{}, 0, len(request.History)+2)
+	for _, m := range request.Messages() {
+		messages = append(messages, map[string]interface{}{"role": m.Role, "content": m.Content})
+	}
+	if len(request.Attachments) > 0 {
+		// Ollama takes base64 images alongside the message text
+		images := make([]string, len(request.Attachments))
+		for i, a := range request.Attachments {
+			images[i] = base64.StdEncoding.EncodeToString(a.Data)
+		}
+		messages[len(messages)-1]["images"] = images
+	}
+
+	options := map[string]interface{}{
+		"temperature": request.Temperature,
+		"num_predict": request.MaxTokens,
+	}
+	request.Sampling.set(options, "top_p", "frequency_penalty", "presence_penalty", "stop")
+
+	payload := map[string]interface{}{
+		"model":    p.config.Model,
+		"messages": messages,
+		"stream":   stream,
+		"options":  options,
+	}
+	// Ollama takes "json" or a JSON Schema object as the format
+	switch format := request.ResponseFormat; {
+	case format.Type == FormatJSONSchema && len(format.Schema) > 0:
+		payload["format"] = format.Schema
+	case format.JSON():
+		payload["format"] = "json"
+	}
+
+	jsonData, err := json.Marshal(payload)
+	if err != nil {
+		return nil, err
+	}
+
+	resp, err := p.client.Post(url, "application/json", bytes.NewBuffer(jsonData))
+	if err != nil {
+		return nil, networkError(err)
+	}
+
+	if resp.StatusCode != http.StatusOK {
+		defer resp.Body.Close()
+		return nil, httpError(resp)
+	}
+
+	return resp, nil
+}
+
+type ollamaChatChunk struct {
+	Message         *ChatMessage `json:"message"`
+	Done            bool         `json:"done"`
+	Error           string       `json:"error"`
+	PromptEvalCount int          `json:"prompt_eval_count"`
+	EvalCount       int          `json:"eval_count"`
+}
+
+func (p *OllamaProvider) SendRequest(request AIRequest) (AIResponse, error) {
+	resp, err := p.post(request, false)
+	if err != nil {
+		return AIResponse{}, err
+	}
+	defer resp.Body.Close()
+
+	var result ollamaChatChunk
+	if err := json.NewDecoder(resp.Body).Decode(&result); err != nil {
+		return AIResponse{}, fmt.Errorf("invalid response: %v", err)
+	}
+
+	if result.Message == nil {
+		return AIResponse{}, fmt.Errorf("missing 'message' field")
+	}
+
+	return AIResponse{
+		Content:          result.Message.Content,
+		Model:            p.config.Model,
+		PromptTokens:     result.PromptEvalCount,
+		CompletionTokens: result.EvalCount,
+	}, nil
+}
+
+// SendRequestStream streams the generation from Ollama's newline-delimited JSON API
+func (p *OllamaProvider) SendRequestStream(request AIRequest, onChunk func(delta string)) (AIResponse, error) {
+	resp, err := p.post(request, true)
+	if err != nil {
+		return AIResponse{}, err
+	}
+	defer resp.Body.Close()
+
+	response := AIResponse{Model: p.config.Model}
+	var full strings.Builder
+	scanner := bufio.NewScanner(resp.Body)
+	scanner.Buffer(make([]byte, 0, 64*1024), 1024*1024)
+	for scanner.Scan() {
+		var chunk ollamaChatChunk
+		if err := json.Unmarshal(scanner.Bytes(), &chunk); err != nil {
+			response.Content = full.String()
+			return response, fmt.Errorf("invalid response: %v", err)
+		}
+		if chunk.Error != "" {
+			response.Content = full.String()
+			return response, fmt.Errorf("ollama error: %s", chunk.Error)
+		}
+		if chunk.Message != nil && chunk.Message.Content != "" {
+			full.WriteString(chunk.Message.Content)
+			onChunk(chunk.Message.Content)
+		}
+		if chunk.Done {
+			response.PromptTokens = chunk.PromptEvalCount
+			response.CompletionTokens = chunk.EvalCount
+			break
+		}
+	}
+	response.Content = full.String()
+	if err := scanner.Err(); err != nil {
+		return response, networkError(err)
+	}
+
+	return response, nil
+}
+
+// AcceptsImages reports vision support, which multimodal models such as llava provide
+func (p *OllamaProvider) AcceptsImages() bool {
+	return true
+}
+
+// ListModels returns the models installed on the Ollama server
+func (p *OllamaProvider) ListModels() ([]string, error) {
+	resp, err := p.client.Get(fmt.Sprintf("%s/api/tags", p.config.Endpoint))
+	if err != nil {
+		return nil, networkError(err)
+	}
+	defer resp.Body.Close()
+
+	if resp.StatusCode != http.StatusOK {
+		return nil, httpError(resp)
+	}
+
+	var result struct {
+		Models []struct {
+			Name string `json:"name"`
+		} `json:"models"`
+	}
+	if err := json.NewDecoder(resp.Body).Decode(&result); err != nil {
+		return nil, fmt.Errorf("invalid response: %v", err)
+	}
+
+	models := make([]string, len(result.Models))
+	for i, m := range result.Models {
+		models[i] = m.Name
+	}
+	return models, nil
+}
+
+// Embed returns one embedding per text from Ollama's /api/embed endpoint
+func (p *OllamaProvider) Embed(texts []string) ([][]float32, error) {
+	jsonData, err := json.Marshal(map[string]interface{}{
+		"model": p.config.EmbeddingModelName(),
+		"input": texts,
+	})
+	if err != nil {
+		return nil, err
+	}
+
+	resp, err := p.client.Post(fmt.Sprintf("%s/api/embed", p.config.Endpoint), "application/json", bytes.NewBuffer(jsonData))
+	if err != nil {
+		return nil, networkError(err)
+	}
+	defer resp.Body.Close()
+
+	if resp.StatusCode != http.StatusOK {
+		return nil, httpError(resp)
+	}
+
+	var result struct {
+		Embeddings [][]float32 `json:"embeddings"`
+	}
+	if err := json.NewDecoder(resp.Body).Decode(&result); err != nil {
+		return nil, fmt.Errorf("invalid response: %v", err)
+	}
+	if len(result.Embeddings) != len(texts) {
+		return nil, fmt.Errorf("expected %d embeddings, got %d", len(texts), len(result.Embeddings))
+	}
+	return result.Embeddings, nil
+}
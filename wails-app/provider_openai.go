@@ -0,0 +1,342 @@
+package main
+
+import (
+	"bufio"
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"io"
+	"net/http"
+	"strings"
+)
+
+// OpenAICompatibleProvider talks to any API implementing OpenAI's chat
+// completions and models endpoints. Services differ only in base URL,
+// default name and any extra headers they expect.
+type OpenAICompatibleProvider struct {
+	config      ProviderConfig
+	client      *http.Client
+	defaultName string
+	headers     map[string]string
+	embeddings  bool // false for services without an /embeddings endpoint
+}
+
+func newOpenAICompatibleProvider(config ProviderConfig, defaultName, defaultEndpoint string, headers map[string]string) *OpenAICompatibleProvider {
+	if config.Endpoint == "" {
+		config.Endpoint = defaultEndpoint
+	}
+	config.Endpoint = strings.TrimRight(config.Endpoint, "/")
+	return &OpenAICompatibleProvider{
+		config:      config,
+		client:      newHTTPClient(config),
+		defaultName: defaultName,
+		headers:     headers,
+		embeddings:  true,
+	}
+}
+
+const (
+	defaultOpenAIEndpoint       = "https://api.openai.com/v1"
+	defaultOpenAIEmbeddingModel = "text-embedding-3-small"
+	defaultOpenRouterEndpoint   = "https://openrouter.ai/api/v1"
+	defaultMistralEndpoint      = "https://api.mistral.ai/v1"
+	defaultMistralEmbedModel    = "mistral-embed"
+	defaultGroqEndpoint         = "https://api.groq.com/openai/v1"
+)
+
+// NewOpenAIProvider talks to the OpenAI API directly
+func NewOpenAIProvider(config ProviderConfig) *OpenAICompatibleProvider {
+	if config.EmbeddingModel == "" {
+		config.EmbeddingModel = defaultOpenAIEmbeddingModel
+	}
+	return newOpenAICompatibleProvider(config, "OpenAI", defaultOpenAIEndpoint, nil)
+}
+
+// NewOpenRouterProvider routes requests through OpenRouter's unified API
+func NewOpenRouterProvider(config ProviderConfig) *OpenAICompatibleProvider {
+	return newOpenAICompatibleProvider(config, "OpenRouter", defaultOpenRouterEndpoint, map[string]string{
+		"HTTP-Referer": "https://github.com/aavishay/vibe-coder",
+		"X-Title":      "Vibe Coder",
+	})
+}
+
+// NewMistralProvider talks to Mistral's La Plateforme API
+func NewMistralProvider(config ProviderConfig) *OpenAICompatibleProvider {
+	if config.EmbeddingModel == "" {
+		config.EmbeddingModel = defaultMistralEmbedModel
+	}
+	return newOpenAICompatibleProvider(config, "Mistral", defaultMistralEndpoint, nil)
+}
+
+// NewGroqProvider talks to Groq's OpenAI-compatible endpoint, which serves no embeddings
+func NewGroqProvider(config ProviderConfig) *OpenAICompatibleProvider {
+	p := newOpenAICompatibleProvider(config, "Groq", defaultGroqEndpoint, nil)
+	p.embeddings = false
+	return p
+}
+
+func (p *OpenAICompatibleProvider) GetName() string {
+	if p.config.Name != "" {
+		return p.config.Name
+	}
+	return p.defaultName
+}
+
+// do sends an authenticated request to path under the configured endpoint
+func (p *OpenAICompatibleProvider) do(method, path string, payload interface{}) (*http.Response, error) {
+	var body io.Reader
+	if payload != nil {
+		jsonData, err := json.Marshal(payload)
+		if err != nil {
+			return nil, err
+		}
+		body = bytes.NewBuffer(jsonData)
+	}
+
+	req, err := http.NewRequest(method, p.config.Endpoint+path, body)
+	if err != nil {
+		return nil, err
+	}
+	req.Header.Set("Content-Type", "application/json")
+	if p.config.APIKey != "" {
+		req.Header.Set("Authorization", "Bearer "+p.config.APIKey)
+	}
+	for key, value := range p.headers {
+		req.Header.Set(key, value)
+	}
+
+	resp, err := p.client.Do(req)
+	if err != nil {
+		return nil, networkError(err)
+	}
+
+	if resp.StatusCode != http.StatusOK {
+		defer resp.Body.Close()
+		return nil, httpError(resp)
+	}
+
+	return resp, nil
+}
+
+func (p *OpenAICompatibleProvider) payload(request AIRequest, stream bool) map[string]interface{} {
+	payload := map[string]interface{}{
+		"model":       p.config.Model,
+		"messages":    p.messages(request),
+		"temperature": request.Temperature,
+		"max_tokens":  request.MaxTokens,
+		"stream":      stream,
+	}
+	if stream {
+		payload["stream_options"] = map[string]interface{}{"include_usage": true}
+	}
+	request.Sampling.set(payload, "top_p", "frequency_penalty", "presence_penalty", "stop")
+	switch format := request.ResponseFormat; {
+	case format.Type == FormatJSONSchema && len(format.Schema) > 0:
+		payload["response_format"] = map[string]interface{}{
+			"type":        "json_schema",
+			"json_schema": map[string]interface{}{"name": "response", "schema": format.Schema},
+		}
+	case format.JSON():
+		payload["response_format"] = map[string]string{"type": "json_object"}
+	}
+	return payload
+}
+
+// messages returns the conversation, with any attachments as image parts of the final user message
+func (p *OpenAICompatibleProvider) messages(request AIRequest) []interface{} {
+	messages := request.Messages()
+	result := make([]interface{}, len(messages))
+	for i, m := range messages {
+		result[i] = m
+	}
+	if len(request.Attachments) == 0 {
+		return result
+	}
+
+	parts := []map[string]interface{}{{"type": "text", "text": request.Prompt}}
+	for _, a := range request.Attachments {
+		parts = append(parts, map[string]interface{}{
+			"type":      "image_url",
+			"image_url": map[string]string{"url": a.dataURL()},
+		})
+	}
+	result[len(result)-1] = map[string]interface{}{"role": "user", "content": parts}
+	return result
+}
+
+// AcceptsImages reports vision support; whether a given model can see images is up to the service
+func (p *OpenAICompatibleProvider) AcceptsImages() bool {
+	return true
+}
+
+type openAIUsage struct {
+	PromptTokens     int `json:"prompt_tokens"`
+	CompletionTokens int `json:"completion_tokens"`
+}
+
+func (p *OpenAICompatibleProvider) SendRequest(request AIRequest) (AIResponse, error) {
+	resp, err := p.do(http.MethodPost, "/chat/completions", p.payload(request, false))
+	if err != nil {
+		return AIResponse{}, err
+	}
+	defer resp.Body.Close()
+
+	var result struct {
+		Model   string `json:"model"`
+		Choices []struct {
+			Message ChatMessage `json:"message"`
+		} `json:"choices"`
+		Usage openAIUsage `json:"usage"`
+	}
+	if err := json.NewDecoder(resp.Body).Decode(&result); err != nil {
+		return AIResponse{}, fmt.Errorf("invalid response: %v", err)
+	}
+
+	if len(result.Choices) == 0 {
+		return AIResponse{}, fmt.Errorf("missing 'choices' field")
+	}
+
+	model := result.Model
+	if model == "" {
+		model = p.config.Model
+	}
+	return AIResponse{
+		Content:          result.Choices[0].Message.Content,
+		Model:            model,
+		PromptTokens:     result.Usage.PromptTokens,
+		CompletionTokens: result.Usage.CompletionTokens,
+	}, nil
+}
+
+// SendRequestStream reads the server-sent events of a streamed chat completion
+func (p *OpenAICompatibleProvider) SendRequestStream(request AIRequest, onChunk func(delta string)) (AIResponse, error) {
+	resp, err := p.do(http.MethodPost, "/chat/completions", p.payload(request, true))
+	if err != nil {
+		return AIResponse{}, err
+	}
+	defer resp.Body.Close()
+
+	response := AIResponse{Model: p.config.Model}
+	var full strings.Builder
+	scanner := bufio.NewScanner(resp.Body)
+	scanner.Buffer(make([]byte, 0, 64*1024), 1024*1024)
+	for scanner.Scan() {
+		data, ok := strings.CutPrefix(scanner.Text(), "data: ")
+		if !ok {
+			continue
+		}
+		if data == "[DONE]" {
+			break
+		}
+
+		var chunk struct {
+			Choices []struct {
+				Delta struct {
+					Content string `json:"content"`
+				} `json:"delta"`
+			} `json:"choices"`
+			Usage *openAIUsage `json:"usage"`
+			Error *struct {
+				Message string `json:"message"`
+			} `json:"error"`
+		}
+		if err := json.Unmarshal([]byte(data), &chunk); err != nil {
+			response.Content = full.String()
+			return response, fmt.Errorf("invalid response: %v", err)
+		}
+		if chunk.Error != nil {
+			response.Content = full.String()
+			return response, fmt.Errorf("%s error: %s", p.defaultName, chunk.Error.Message)
+		}
+		for _, choice := range chunk.Choices {
+			if choice.Delta.Content != "" {
+				full.WriteString(choice.Delta.Content)
+				onChunk(choice.Delta.Content)
+			}
+		}
+		if chunk.Usage != nil {
+			response.PromptTokens = chunk.Usage.PromptTokens
+			response.CompletionTokens = chunk.Usage.CompletionTokens
+		}
+	}
+	response.Content = full.String()
+	if err := scanner.Err(); err != nil {
+		return response, networkError(err)
+	}
+
+	return response, nil
+}
+
+// ListModels returns the model IDs from the /models endpoint
+func (p *OpenAICompatibleProvider) ListModels() ([]string, error) {
+	resp, err := p.do(http.MethodGet, "/models", nil)
+	if err != nil {
+		return nil, err
+	}
+	defer resp.Body.Close()
+
+	var result struct {
+		Data []struct {
+			ID string `json:"id"`
+		} `json:"data"`
+	}
+	if err := json.NewDecoder(resp.Body).Decode(&result); err != nil {
+		return nil, fmt.Errorf("invalid response: %v", err)
+	}
+
+	models := make([]string, len(result.Data))
+	for i, m := range result.Data {
+		models[i] = m.ID
+	}
+	return models, nil
+}
+
+// Embed returns one embedding per text from the /embeddings endpoint
+func (p *OpenAICompatibleProvider) Embed(texts []string) ([][]float32, error) {
+	if !p.embeddings {
+		return nil, fmt.Errorf("%s does not support embeddings", p.defaultName)
+	}
+	resp, err := p.do(http.MethodPost, "/embeddings", map[string]interface{}{
+		"model": p.config.EmbeddingModelName(),
+		"input": texts,
+	})
+	if err != nil {
+		return nil, err
+	}
+	defer resp.Body.Close()
+
+	var result struct {
+		Data []struct {
+			Index     int       `json:"index"`
+			Embedding []float32 `json:"embedding"`
+		} `json:"data"`
+	}
+	if err := json.NewDecoder(resp.Body).Decode(&result); err != nil {
+		return nil, fmt.Errorf("invalid response: %v", err)
+	}
+
+	embeddings := make([][]float32, len(texts))
+	for _, d := range result.Data {
+		if d.Index < 0 || d.Index >= len(texts) {
+			return nil, fmt.Errorf("embedding index %d out of range", d.Index)
+		}
+		embeddings[d.Index] = d.Embedding
+	}
+	for i, e := range embeddings {
+		if e == nil {
+			return nil, fmt.Errorf("missing embedding for input %d", i)
+		}
+	}
+	return embeddings, nil
+}
+
+func (p *OpenAICompatibleProvider) restrictCapabilities(c Capabilities) Capabilities {
+	c.Embeddings = c.Embeddings && p.embeddings
+	return c
+}
+
+// HealthCheck verifies the endpoint and key by listing models
+func (p *OpenAICompatibleProvider) HealthCheck() error {
+	_, err := p.ListModels()
+	return err
+}
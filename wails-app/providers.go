@@ -0,0 +1,484 @@
+package main
+
+import (
+	"context"
+	"crypto/tls"
+	"crypto/x509"
+	"encoding/base64"
+	"errors"
+	"fmt"
+	"io"
+	"net"
+	"net/http"
+	"net/url"
+	"os"
+	"strconv"
+	"strings"
+	"sync/atomic"
+	"time"
+)
+
+type ProviderConfig struct {
+	Type     string `json:"type"`
+	Name     string `json:"name"`
+	APIKey   string `json:"apiKey"`
+	Endpoint string `json:"endpoint"`
+	Model    string `json:"model"`
+	Timeout  int    `json:"timeout"` // seconds a request may wait for the provider, for its answer to begin or between chunks; 0 uses the default
+
+	// Network settings for HTTP-backed providers
+	ProxyURL         string `json:"proxyUrl,omitempty"`     // e.g. "http://proxy.corp:8080"; empty uses HTTP(S)_PROXY from the environment
+	CABundlePath     string `json:"caBundlePath,omitempty"` // PEM file of extra root CAs trusted alongside the system pool
+	DisableTLSVerify bool   `json:"disableTlsVerify,omitempty"`
+
+	// Client-side rate limits; 0 disables the limit
+	RequestsPerMinute int `json:"requestsPerMinute,omitempty"`
+	TokensPerMinute   int `json:"tokensPerMinute,omitempty"`
+
+	// Model used for embeddings; empty uses Model
+	EmbeddingModel string `json:"embeddingModel,omitempty"`
+
+	// Persona prepended to every request as a system message, e.g. "Always answer in Rust"
+	SystemPrompt string `json:"systemPrompt,omitempty"`
+
+	// Gemini: threshold applied to every harm category, e.g. "BLOCK_ONLY_HIGH"; empty uses the API default
+	SafetyThreshold string `json:"safetyThreshold,omitempty"`
+
+	// How responses are parsed for display: TextMarkdown (the default) or
+	// TextPlain for providers answering in plain text or console output
+	TextFormat string `json:"textFormat,omitempty"`
+
+	// Mock: canned responses, latency and failures for tests and demos
+	MockScript *MockScript `json:"mockScript,omitempty"`
+}
+
+const defaultRequestTimeout = 120 * time.Second
+
+// RequestTimeout returns how long a request may go without hearing from the
+// provider
+func (c ProviderConfig) RequestTimeout() time.Duration {
+	if c.Timeout <= 0 {
+		return defaultRequestTimeout
+	}
+	return time.Duration(c.Timeout) * time.Second
+}
+
+// EmbeddingModelName returns the model to request embeddings from
+func (c ProviderConfig) EmbeddingModelName() string {
+	if c.EmbeddingModel != "" {
+		return c.EmbeddingModel
+	}
+	return c.Model
+}
+
+// newHTTPClient builds the HTTP client used by network-backed providers,
+// applying the configured proxy and TLS settings. A client built from invalid
+// settings fails every request with a ConfigError.
+//
+// The timeout bounds the wait for the response headers and then each gap in
+// the body, not the whole exchange, so a streamed generation runs for as
+// long as it keeps arriving.
+func newHTTPClient(config ProviderConfig) *http.Client {
+	transport, err := newTransport(config)
+	if err != nil {
+		return &http.Client{Transport: failingTransport{&ConfigError{Err: err}}}
+	}
+	transport.ResponseHeaderTimeout = config.RequestTimeout()
+	return &http.Client{Transport: stallTransport{base: transport, idle: config.RequestTimeout()}}
+}
+
+func newTransport(config ProviderConfig) (*http.Transport, error) {
+	transport := http.DefaultTransport.(*http.Transport).Clone()
+
+	if config.ProxyURL != "" {
+		proxy, err := url.Parse(config.ProxyURL)
+		if err != nil || proxy.Host == "" {
+			return nil, fmt.Errorf("invalid proxy URL %q", config.ProxyURL)
+		}
+		transport.Proxy = http.ProxyURL(proxy)
+	}
+
+	if config.CABundlePath != "" || config.DisableTLSVerify {
+		tlsConfig := &tls.Config{InsecureSkipVerify: config.DisableTLSVerify}
+		if config.CABundlePath != "" {
+			pem, err := os.ReadFile(config.CABundlePath)
+			if err != nil {
+				return nil, fmt.Errorf("reading CA bundle: %v", err)
+			}
+			pool, err := x509.SystemCertPool()
+			if err != nil {
+				pool = x509.NewCertPool()
+			}
+			if !pool.AppendCertsFromPEM(pem) {
+				return nil, fmt.Errorf("no certificates found in CA bundle %s", config.CABundlePath)
+			}
+			tlsConfig.RootCAs = pool
+		}
+		transport.TLSClientConfig = tlsConfig
+	}
+
+	return transport, nil
+}
+
+// ConfigError reports a provider misconfiguration; unlike a NetworkError it is never retried
+type ConfigError struct {
+	Err error
+}
+
+func (e *ConfigError) Error() string {
+	return fmt.Sprintf("configuration error: %v", e.Err)
+}
+
+func (e *ConfigError) Unwrap() error {
+	return e.Err
+}
+
+// failingTransport fails every request, for clients whose settings didn't load
+type failingTransport struct {
+	err error
+}
+
+func (t failingTransport) RoundTrip(*http.Request) (*http.Response, error) {
+	return nil, t.err
+}
+
+// stallTransport fails a response whose body stops arriving for longer than
+// idle
+type stallTransport struct {
+	base http.RoundTripper
+	idle time.Duration
+}
+
+func (t stallTransport) RoundTrip(req *http.Request) (*http.Response, error) {
+	ctx, cancel := context.WithCancel(req.Context())
+	resp, err := t.base.RoundTrip(req.WithContext(ctx))
+	if err != nil {
+		cancel()
+		return nil, err
+	}
+	body := &stallBody{ReadCloser: resp.Body, idle: t.idle, cancel: cancel}
+	body.timer = time.AfterFunc(t.idle, func() {
+		body.stalled.Store(true)
+		cancel()
+	})
+	resp.Body = body
+	return resp, nil
+}
+
+// stallBody is a response body that cancels its request when no data has
+// come for idle
+type stallBody struct {
+	io.ReadCloser
+	idle    time.Duration
+	timer   *time.Timer
+	stalled atomic.Bool
+	cancel  context.CancelFunc
+}
+
+func (b *stallBody) Read(p []byte) (int, error) {
+	n, err := b.ReadCloser.Read(p)
+	if err != nil && b.stalled.Load() {
+		// A timeout, like the deadline it stands in for
+		return n, fmt.Errorf("nothing received for %v: %w", b.idle, os.ErrDeadlineExceeded)
+	}
+	if n > 0 {
+		b.timer.Reset(b.idle)
+	}
+	return n, err
+}
+
+func (b *stallBody) Close() error {
+	b.timer.Stop()
+	b.cancel()
+	return b.ReadCloser.Close()
+}
+
+// ChatMessage is a single turn in a conversation
+type ChatMessage struct {
+	Role    string `json:"role"` // "system", "user" or "assistant"
+	Content string `json:"content"`
+
+	// Assistant turns: the answering provider's TextFormat, for parsing
+	// Content again when the conversation is shown. Never sent to a provider.
+	TextFormat string `json:"textFormat,omitempty"`
+}
+
+// Attachment is an image sent along with the prompt
+type Attachment struct {
+	MimeType string `json:"mimeType"` // e.g. "image/png"
+	Data     []byte `json:"data"`     // raw bytes; base64 in JSON
+}
+
+// dataURL encodes the attachment as a data: URL
+func (a Attachment) dataURL() string {
+	return "data:" + a.MimeType + ";base64," + base64.StdEncoding.EncodeToString(a.Data)
+}
+
+// Sampling holds the advanced generation settings; zero values leave the provider's defaults
+type Sampling struct {
+	TopP             float64  `json:"topP"`
+	FrequencyPenalty float64  `json:"frequencyPenalty"`
+	PresencePenalty  float64  `json:"presencePenalty"`
+	Stop             []string `json:"stop"` // generation ends before any of these sequences
+}
+
+// set copies the non-default settings into a provider's parameters under the given names
+func (s Sampling) set(params map[string]interface{}, topP, frequencyPenalty, presencePenalty, stop string) {
+	if s.TopP > 0 {
+		params[topP] = s.TopP
+	}
+	if s.FrequencyPenalty != 0 {
+		params[frequencyPenalty] = s.FrequencyPenalty
+	}
+	if s.PresencePenalty != 0 {
+		params[presencePenalty] = s.PresencePenalty
+	}
+	if len(s.Stop) > 0 {
+		params[stop] = s.Stop
+	}
+}
+
+// cutAtStop truncates text before the first stop sequence it contains
+func cutAtStop(text string, stops []string) (string, bool) {
+	cut := -1
+	for _, stop := range stops {
+		if i := strings.Index(text, stop); stop != "" && i >= 0 && (cut < 0 || i < cut) {
+			cut = i
+		}
+	}
+	if cut < 0 {
+		return text, false
+	}
+	return text[:cut], true
+}
+
+// stopHoldback is how many bytes at the end of text could be the start of a
+// stop sequence, which a stream holds back until more text settles it
+func stopHoldback(text string, stops []string) int {
+	held := 0
+	for _, stop := range stops {
+		for n := min(len(stop)-1, len(text)); n > held; n-- {
+			if strings.HasSuffix(text, stop[:n]) {
+				held = n
+				break
+			}
+		}
+	}
+	return held
+}
+
+// AIRequest is what the app sends to a provider
+type AIRequest struct {
+	Prompt       string        `json:"prompt"`
+	History      []ChatMessage `json:"history"`      // previous turns, oldest first
+	SystemPrompt string        `json:"systemPrompt"` // overrides the provider's configured system prompt
+	Temperature  float64       `json:"temperature"`
+	MaxTokens    int           `json:"maxTokens"`
+	NoCache      bool          `json:"noCache"`  // bypass the response cache and force a fresh answer
+	Provider     string        `json:"provider"` // name of the provider to use instead of the active one
+
+	ResponseFormat ResponseFormat `json:"responseFormat"`
+	Attachments    []Attachment   `json:"attachments"` // images for the prompt; needs a provider with vision
+	Sampling       Sampling       `json:"sampling"`
+
+	// Set on requests plugins make through their PluginHost, which skip the plugins
+	fromPlugin bool
+}
+
+// RequestOptions are the per-request settings the frontend can pass alongside a prompt
+type RequestOptions struct {
+	NoCache      bool   `json:"noCache"`
+	SystemPrompt string `json:"systemPrompt"`
+	Provider     string `json:"provider"`
+
+	ResponseFormat ResponseFormat `json:"responseFormat"`
+	Attachments    []Attachment   `json:"attachments"`
+	Sampling       Sampling       `json:"sampling"`
+}
+
+// apply copies the options onto the request
+func (o RequestOptions) apply(request AIRequest) AIRequest {
+	request.NoCache = o.NoCache
+	request.SystemPrompt = o.SystemPrompt
+	request.Provider = o.Provider
+	request.ResponseFormat = o.ResponseFormat
+	request.Attachments = o.Attachments
+	request.Sampling = o.Sampling
+	return request
+}
+
+// NewAIRequest builds a request with the app's default sampling settings
+func NewAIRequest(prompt string, history []ChatMessage) AIRequest {
+	return AIRequest{
+		Prompt:      prompt,
+		History:     history,
+		Temperature: 0.7,
+		MaxTokens:   2000,
+	}
+}
+
+// withSystemPrompt fills in the provider's system prompt unless the request sets its own
+func (r AIRequest) withSystemPrompt(config ProviderConfig) AIRequest {
+	if r.SystemPrompt == "" {
+		r.SystemPrompt = config.SystemPrompt
+	}
+	return r
+}
+
+// Messages returns the full conversation for the request: the system prompt
+// and any output format instruction, then history followed by the prompt
+func (r AIRequest) Messages() []ChatMessage {
+	messages := make([]ChatMessage, 0, len(r.History)+3)
+	if r.SystemPrompt != "" {
+		messages = append(messages, ChatMessage{Role: "system", Content: r.SystemPrompt})
+	}
+	if instruction := r.ResponseFormat.instruction(); instruction != "" {
+		messages = append(messages, ChatMessage{Role: "system", Content: instruction})
+	}
+	for _, m := range r.History {
+		messages = append(messages, ChatMessage{Role: m.Role, Content: m.Content})
+	}
+	return append(messages, ChatMessage{Role: "user", Content: r.Prompt})
+}
+
+// AIResponse is a provider's answer. Providers fill in the content, model and any
+// token usage they report; the ProviderManager fills in the rest.
+type AIResponse struct {
+	Content          string   `json:"content"`
+	Model            string   `json:"model"`
+	PromptTokens     int      `json:"promptTokens"`
+	CompletionTokens int      `json:"completionTokens"`
+	Cost             float64  `json:"cost"`     // USD, from the CostTracker price table
+	Provider         string   `json:"provider"` // name of the provider that answered
+	Attempts         int      `json:"attempts"` // total attempts across retries and failover
+	Cached           bool     `json:"cached"`   // served from the response cache without a provider call
+	Redacted         []string `json:"redacted"` // kinds of secret masked out of the prompt before sending
+
+	// The answering provider's TextFormat, for parsing Content
+	TextFormat string `json:"textFormat,omitempty"`
+
+	// Plugins that failed and were skipped over, by their error policies
+	PluginFailures []PluginFailure `json:"pluginFailures,omitempty"`
+
+	Stats ResponseStats `json:"stats"`
+}
+
+// NetworkError marks a transient transport failure that is safe to retry
+type NetworkError struct {
+	Err error
+}
+
+func (e *NetworkError) Error() string {
+	return fmt.Sprintf("network error: %v", e.Err)
+}
+
+func (e *NetworkError) Unwrap() error {
+	return e.Err
+}
+
+// ErrTimeout is wrapped in a NetworkError when a request exceeds its timeout
+var ErrTimeout = errors.New("timeout")
+
+// networkError wraps a transport error, normalizing timeouts to ErrTimeout.
+// Configuration errors pass through unwrapped so they aren't retried.
+func networkError(err error) error {
+	var configErr *ConfigError
+	if errors.As(err, &configErr) {
+		return configErr
+	}
+	var netErr net.Error
+	if errors.As(err, &netErr) && netErr.Timeout() {
+		return &NetworkError{Err: ErrTimeout}
+	}
+	return &NetworkError{Err: err}
+}
+
+// RateLimitError is an HTTP 429 from the provider. The manager waits for
+// RetryAfter (or backs off when the provider didn't say) and tries again.
+type RateLimitError struct {
+	RetryAfter time.Duration // zero when the response had no usable Retry-After header
+	Body       string
+}
+
+func (e *RateLimitError) Error() string {
+	if e.RetryAfter > 0 {
+		return fmt.Sprintf("rate limited (retry after %s): %s", e.RetryAfter, e.Body)
+	}
+	return fmt.Sprintf("rate limited: %s", e.Body)
+}
+
+// httpError reads a non-OK response into an error, recognizing rate limits
+func httpError(resp *http.Response) error {
+	body, _ := io.ReadAll(resp.Body)
+	if resp.StatusCode == http.StatusTooManyRequests {
+		return &RateLimitError{RetryAfter: parseRetryAfter(resp.Header.Get("Retry-After")), Body: string(body)}
+	}
+	return fmt.Errorf("HTTP %d: %s", resp.StatusCode, string(body))
+}
+
+// parseRetryAfter accepts both forms of the header: delay seconds or an HTTP date
+func parseRetryAfter(value string) time.Duration {
+	if value == "" {
+		return 0
+	}
+	if seconds, err := strconv.Atoi(strings.TrimSpace(value)); err == nil {
+		return max(time.Duration(seconds)*time.Second, 0)
+	}
+	if at, err := http.ParseTime(value); err == nil {
+		return max(time.Until(at), 0)
+	}
+	return 0
+}
+
+type Provider interface {
+	SendRequest(request AIRequest) (AIResponse, error)
+	GetName() string
+}
+
+// ModelLister is implemented by providers that can enumerate the models they serve
+type ModelLister interface {
+	ListModels() ([]string, error)
+}
+
+// StreamingProvider is implemented by providers that can deliver a response incrementally.
+// onChunk is called with each content delta; the full response is returned at the end.
+type StreamingProvider interface {
+	SendRequestStream(request AIRequest, onChunk func(delta string)) (AIResponse, error)
+}
+
+// VisionProvider is implemented by providers that can take image attachments
+type VisionProvider interface {
+	AcceptsImages() bool
+}
+
+// EmbeddingProvider is implemented by providers that can embed text as vectors,
+// returning one embedding per input in the same order
+type EmbeddingProvider interface {
+	Embed(texts []string) ([][]float32, error)
+}
+
+// newProvider builds a provider for the given configuration
+func newProvider(config ProviderConfig) Provider {
+	switch config.Type {
+	case "Ollama":
+		return NewOllamaProvider(config)
+	case "Gemini":
+		return NewGeminiProvider(config)
+	case "OpenAI":
+		return NewOpenAIProvider(config)
+	case "OpenRouter":
+		return NewOpenRouterProvider(config)
+	case "Mistral":
+		return NewMistralProvider(config)
+	case "Groq":
+		return NewGroqProvider(config)
+	case "LlamaCpp":
+		return NewLlamaCppProvider(config)
+	case "Mock":
+		return NewMockProvider(config)
+	default:
+		// For now, unsupported providers default to Mock
+		return NewMockProvider(config)
+	}
+}
+
@@ -0,0 +1,27 @@
+package main
+
+import "testing"
+
+func TestStopHoldback(t *testing.T) {
+	tests := []struct {
+		name  string
+		text  string
+		stops []string
+		want  int
+	}{
+		{"no stops", "hello\n", nil, 0},
+		{"nothing like a stop", "hello", []string{"\n\n"}, 0},
+		{"half a stop", "hello\n", []string{"\n\n"}, 1},
+		{"longest start wins", "a <end", []string{"<eot>", "<endofturn>"}, 4},
+		{"repeated characters", "x ###", []string{"####"}, 3},
+		{"text shorter than the stop", "<e", []string{"<endofturn>"}, 2},
+		{"empty stop", "hello", []string{""}, 0},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := stopHoldback(tt.text, tt.stops); got != tt.want {
+				t.Errorf("stopHoldback(%q, %q) = %d, want %d", tt.text, tt.stops, got, tt.want)
+			}
+		})
+	}
+}
@@ -0,0 +1,88 @@
+package main
+
+import (
+	"sync"
+	"time"
+)
+
+// bucket is a token bucket that refills continuously at rate per second.
+// Reservations may drive it negative, which queues later callers behind earlier ones.
+type bucket struct {
+	capacity float64
+	level    float64
+	rate     float64
+	last     time.Time
+}
+
+func newBucket(perMinute int) *bucket {
+	capacity := float64(perMinute)
+	return &bucket{capacity: capacity, level: capacity, rate: capacity / 60, last: time.Now()}
+}
+
+// reserve takes n units and returns how long the caller must wait before using them
+func (b *bucket) reserve(n float64, now time.Time) time.Duration {
+	b.level = min(b.capacity, b.level+now.Sub(b.last).Seconds()*b.rate)
+	b.last = now
+	b.level -= min(n, b.capacity)
+	if b.level >= 0 {
+		return 0
+	}
+	return time.Duration(-b.level / b.rate * float64(time.Second))
+}
+
+// RateLimiter enforces a provider's requests-per-minute and tokens-per-minute limits
+type RateLimiter struct {
+	mu       sync.Mutex
+	requests *bucket
+	tokens   *bucket
+	queued   int
+}
+
+// NewRateLimiter returns nil when the config sets no limits
+func NewRateLimiter(config ProviderConfig) *RateLimiter {
+	if config.RequestsPerMinute <= 0 && config.TokensPerMinute <= 0 {
+		return nil
+	}
+	limiter := &RateLimiter{}
+	if config.RequestsPerMinute > 0 {
+		limiter.requests = newBucket(config.RequestsPerMinute)
+	}
+	if config.TokensPerMinute > 0 {
+		limiter.tokens = newBucket(config.TokensPerMinute)
+	}
+	return limiter
+}
+
+// Wait blocks until a request of the given token size may be sent.
+// onQueued is called with the number of waiting requests and the wait before sleeping.
+func (l *RateLimiter) Wait(tokens int, onQueued func(queued int, wait time.Duration)) {
+	if l == nil {
+		return
+	}
+
+	l.mu.Lock()
+	now := time.Now()
+	var wait time.Duration
+	if l.requests != nil {
+		wait = l.requests.reserve(1, now)
+	}
+	if l.tokens != nil {
+		wait = max(wait, l.tokens.reserve(float64(tokens), now))
+	}
+	if wait <= 0 {
+		l.mu.Unlock()
+		return
+	}
+	l.queued++
+	queued := l.queued
+	l.mu.Unlock()
+
+	if onQueued != nil {
+		onQueued(queued, wait)
+	}
+	time.Sleep(wait)
+
+	l.mu.Lock()
+	l.queued--
+	l.mu.Unlock()
+}
@@ -0,0 +1,41 @@
+package main
+
+import (
+	"testing"
+	"time"
+)
+
+func TestBucketReserve(t *testing.T) {
+	start := time.Now()
+	b := newBucket(60) // one a second
+	b.last = start
+
+	steps := []struct {
+		after time.Duration // since start
+		n     float64
+		want  time.Duration
+	}{
+		{0, 60, 0},                                 // the whole burst is free
+		{0, 30, 30 * time.Second},                  // then it queues at the refill rate
+		{10 * time.Second, 1, 21 * time.Second},    // behind the earlier reservation
+		{60 * time.Second, 1000, 31 * time.Second}, // more than the capacity takes only the capacity
+	}
+	for i, step := range steps {
+		if got := b.reserve(step.n, start.Add(step.after)); got != step.want {
+			t.Errorf("step %d: wait %v, want %v", i+1, got, step.want)
+		}
+	}
+}
+
+func TestNewRateLimiter(t *testing.T) {
+	if NewRateLimiter(ProviderConfig{}) != nil {
+		t.Error("a config without limits should have no limiter")
+	}
+	limiter := NewRateLimiter(ProviderConfig{TokensPerMinute: 1000})
+	if limiter == nil || limiter.requests != nil || limiter.tokens == nil {
+		t.Errorf("got %+v, want only a token bucket", limiter)
+	}
+	// A nil limiter never waits
+	var none *RateLimiter
+	none.Wait(1, func(int, time.Duration) { t.Error("a nil limiter queued a request") })
+}
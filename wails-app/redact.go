@@ -0,0 +1,128 @@
+package main
+
+import (
+	"fmt"
+	"regexp"
+	"sort"
+	"strings"
+	"sync"
+)
+
+const (
+	RedactOff   = "off"   // send prompts unchanged
+	RedactMask  = "mask"  // replace secrets with a placeholder, the default
+	RedactBlock = "block" // refuse to send prompts containing secrets
+)
+
+// secretPatterns are matched against outgoing prompts, most specific first so
+// a private key isn't half-masked as a generic token
+var secretPatterns = []struct {
+	kind    string
+	pattern *regexp.Regexp
+}{
+	{"private-key", regexp.MustCompile(`-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----`)},
+	{"aws-access-key", regexp.MustCompile(`\b(AKIA|ASIA)[0-9A-Z]{16}\b`)},
+	{"aws-secret-key", regexp.MustCompile(`(?i)aws_?secret_?access_?key["']?\s*[:=]\s*["']?[A-Za-z0-9/+=]{40}`)},
+	{"api-key", regexp.MustCompile(`\b(sk-(ant-|proj-)?[A-Za-z0-9_\-]{20,}|AIza[0-9A-Za-z_\-]{35}|gh[pousr]_[A-Za-z0-9]{36,}|xox[abprs]-[A-Za-z0-9\-]{10,}|gsk_[A-Za-z0-9]{20,})\b`)},
+	{"api-key", regexp.MustCompile(`(?i)\b(api[_-]?key|secret|token|password)["']?\s*[:=]\s*["']?[A-Za-z0-9_\-./+=]{12,}`)},
+	{"email", regexp.MustCompile(`\b[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}\b`)},
+}
+
+// RedactionError blocks a request whose prompt contains secrets
+type RedactionError struct {
+	Kinds []string
+}
+
+func (e *RedactionError) Error() string {
+	return fmt.Sprintf("prompt blocked: it appears to contain %s; remove them or switch secret redaction to mask", strings.Join(e.Kinds, ", "))
+}
+
+// RedactionAllowlist is implemented by plugins that let known-harmless
+// secrets through, such as the secret scrubber's example keys. While such a
+// plugin runs, secret redaction leaves what it allows alone too.
+type RedactionAllowlist interface {
+	AllowsSecret(match string) bool
+}
+
+// Redactor scans outgoing requests for credentials and personal data
+type Redactor struct {
+	mu   sync.RWMutex
+	mode string
+}
+
+func NewRedactor() *Redactor {
+	return &Redactor{mode: RedactMask}
+}
+
+// SetMode switches between RedactOff, RedactMask and RedactBlock
+func (r *Redactor) SetMode(mode string) error {
+	switch mode {
+	case RedactOff, RedactMask, RedactBlock:
+	default:
+		return fmt.Errorf("unknown redaction mode %q", mode)
+	}
+	r.mu.Lock()
+	defer r.mu.Unlock()
+
+	r.mode = mode
+	return nil
+}
+
+func (r *Redactor) Mode() string {
+	r.mu.RLock()
+	defer r.mu.RUnlock()
+
+	return r.mode
+}
+
+// redactionPlaceholder stands in for a masked secret of a kind
+func redactionPlaceholder(kind string) string {
+	return "[REDACTED:" + kind + "]"
+}
+
+// redactText masks every secret in text, except those allowed keeps when
+// it isn't nil, recording the kinds found
+func redactText(text string, found map[string]bool, allowed func(string) bool) string {
+	for _, secret := range secretPatterns {
+		text = secret.pattern.ReplaceAllStringFunc(text, func(match string) string {
+			if allowed != nil && allowed(match) {
+				return match
+			}
+			found[secret.kind] = true
+			return redactionPlaceholder(secret.kind)
+		})
+	}
+	return text
+}
+
+// Apply masks or blocks secrets in the prompt, history and system prompt,
+// other than those allowed keeps, returning the request to send and the
+// kinds of secret that were masked
+func (r *Redactor) Apply(request AIRequest, allowed func(string) bool) (AIRequest, []string, error) {
+	mode := r.Mode()
+	if mode == RedactOff {
+		return request, nil, nil
+	}
+
+	found := make(map[string]bool)
+	request.Prompt = redactText(request.Prompt, found, allowed)
+	request.SystemPrompt = redactText(request.SystemPrompt, found, allowed)
+	history := make([]ChatMessage, len(request.History))
+	for i, m := range request.History {
+		history[i] = ChatMessage{Role: m.Role, Content: redactText(m.Content, found, allowed)}
+	}
+	request.History = history
+
+	if len(found) == 0 {
+		return request, nil, nil
+	}
+	kinds := make([]string, 0, len(found))
+	for kind := range found {
+		kinds = append(kinds, kind)
+	}
+	sort.Strings(kinds)
+	if mode == RedactBlock {
+		return AIRequest{}, nil, &RedactionError{Kinds: kinds}
+	}
+	return request, kinds, nil
+}
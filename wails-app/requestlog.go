@@ -0,0 +1,139 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"os"
+	"path/filepath"
+	"strings"
+	"sync"
+	"time"
+)
+
+const (
+	LogOff      = "off"      // no request log, the default
+	LogMetadata = "metadata" // provider, model, latency, tokens and errors only
+	LogFull     = "full"     // metadata plus scrubbed prompts and responses
+)
+
+// RequestLogEntry is one provider call, written as a line of JSON
+type RequestLogEntry struct {
+	Time             string `json:"time"`
+	Provider         string `json:"provider"`
+	Model            string `json:"model"`
+	Attempt          int    `json:"attempt"`
+	LatencyMs        int64  `json:"latencyMs"`
+	PromptTokens     int    `json:"promptTokens,omitempty"`
+	CompletionTokens int    `json:"completionTokens,omitempty"`
+	Error            string `json:"error,omitempty"`
+	Prompt           string `json:"prompt,omitempty"`
+	Response         string `json:"response,omitempty"`
+}
+
+// RequestLog appends provider calls to a JSONL file for debugging flaky
+// providers. API keys and other secrets are scrubbed before anything is written.
+type RequestLog struct {
+	mu   sync.Mutex
+	mode string
+	path string
+}
+
+func NewRequestLog(path string) *RequestLog {
+	return &RequestLog{mode: LogOff, path: path}
+}
+
+// SetMode switches between LogOff, LogMetadata and LogFull
+func (l *RequestLog) SetMode(mode string) error {
+	switch mode {
+	case LogOff, LogMetadata, LogFull:
+	default:
+		return fmt.Errorf("unknown request log mode %q", mode)
+	}
+	l.mu.Lock()
+	defer l.mu.Unlock()
+
+	l.mode = mode
+	return nil
+}
+
+func (l *RequestLog) Mode() string {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+
+	return l.mode
+}
+
+func (l *RequestLog) Path() string {
+	return l.path
+}
+
+// scrubLogText removes the provider's own API key and anything that looks like a secret
+func scrubLogText(text, apiKey string) string {
+	if apiKey != "" {
+		text = strings.ReplaceAll(text, apiKey, "[REDACTED:api-key]")
+	}
+	return redactText(text, make(map[string]bool), nil)
+}
+
+// Record logs one call to a provider. It never fails the request; write errors go to stderr.
+func (l *RequestLog) Record(config ProviderConfig, provider string, attempt int, latency time.Duration, request AIRequest, response AIResponse, err error) {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+
+	if l.mode == LogOff {
+		return
+	}
+
+	entry := RequestLogEntry{
+		Time:             time.Now().Format(time.RFC3339Nano),
+		Provider:         provider,
+		Model:            config.Model,
+		Attempt:          attempt,
+		LatencyMs:        latency.Milliseconds(),
+		PromptTokens:     response.PromptTokens,
+		CompletionTokens: response.CompletionTokens,
+	}
+	if response.Model != "" {
+		entry.Model = response.Model
+	}
+	if err != nil {
+		entry.Error = scrubLogText(err.Error(), config.APIKey)
+	}
+	if l.mode == LogFull {
+		entry.Prompt = scrubLogText(request.Prompt, config.APIKey)
+		entry.Response = scrubLogText(response.Content, config.APIKey)
+	}
+
+	if err := l.append(entry); err != nil {
+		fmt.Fprintf(os.Stderr, "failed to write request log: %v\n", err)
+	}
+}
+
+func (l *RequestLog) append(entry RequestLogEntry) error {
+	if err := os.MkdirAll(filepath.Dir(l.path), 0o700); err != nil {
+		return err
+	}
+	file, err := os.OpenFile(l.path, os.O_APPEND|os.O_CREATE|os.O_WRONLY, 0o600)
+	if err != nil {
+		return err
+	}
+	defer file.Close()
+
+	line, err := json.Marshal(entry)
+	if err != nil {
+		return err
+	}
+	_, err = file.Write(append(line, '\n'))
+	return err
+}
+
+// Clear deletes the log file
+func (l *RequestLog) Clear() error {
+	l.mu.Lock()
+	defer l.mu.Unlock()
+
+	if err := os.Remove(l.path); err != nil && !os.IsNotExist(err) {
+		return err
+	}
+	return nil
+}
@@ -0,0 +1,64 @@
+package main
+
+import (
+	"errors"
+	"fmt"
+	"strings"
+)
+
+// keyringPrefix marks an API key that is kept in the OS keyring: an apiKey
+// of "keyring:openai" is read from the keyring entry "openai" when the
+// provider is built, so the config file never holds the key itself
+const keyringPrefix = "keyring:"
+
+// keyringService groups the app's entries in the OS keyring
+const keyringService = "vibe-coder"
+
+// ErrSecretNotFound is returned for keyring entries that don't exist
+var ErrSecretNotFound = errors.New("not in the keyring")
+
+// Keyring stores secrets by account name: the macOS Keychain, the Secret
+// Service (GNOME Keyring, KWallet) on Linux, or the Windows Credential
+// Manager
+type Keyring interface {
+	Get(account string) (string, error)
+	// Set stores secret under account, replacing what was there
+	Set(account, secret string) error
+	// Delete removes account's entry; one that doesn't exist is no error
+	Delete(account string) error
+}
+
+// ResolveSecrets returns config with a keyring: API key replaced by the
+// secret it refers to
+func (c ProviderConfig) ResolveSecrets(keyring Keyring) (ProviderConfig, error) {
+	account, ok := strings.CutPrefix(c.APIKey, keyringPrefix)
+	if !ok {
+		return c, nil
+	}
+	if account == "" {
+		return c, fmt.Errorf("%s: %q names no keyring entry", c.Name, c.APIKey)
+	}
+	secret, err := keyring.Get(account)
+	if err != nil {
+		return c, fmt.Errorf("%s: API key %s: %w", c.Name, c.APIKey, err)
+	}
+	c.APIKey = secret
+	return c, nil
+}
+
+// StoreAPIKey saves an API key in the OS keyring under account and returns
+// the keyring: reference to configure a provider with instead of the key
+func (a *App) StoreAPIKey(account, key string) (string, error) {
+	if account == "" || key == "" {
+		return "", errors.New("an account and a key are needed")
+	}
+	if err := a.manager.keyring.Set(account, key); err != nil {
+		return "", fmt.Errorf("failed to store the API key: %w", err)
+	}
+	return keyringPrefix + account, nil
+}
+
+// DeleteAPIKey removes an API key StoreAPIKey saved
+func (a *App) DeleteAPIKey(account string) error {
+	return a.manager.keyring.Delete(account)
+}
@@ -0,0 +1,89 @@
+package main
+
+import (
+	"errors"
+	"strings"
+	"testing"
+)
+
+// memoryKeyring keeps secrets in a map, standing in for the OS keyring
+type memoryKeyring map[string]string
+
+func (k memoryKeyring) Get(account string) (string, error) {
+	secret, ok := k[account]
+	if !ok {
+		return "", ErrSecretNotFound
+	}
+	return secret, nil
+}
+
+func (k memoryKeyring) Set(account, secret string) error {
+	k[account] = secret
+	return nil
+}
+
+func (k memoryKeyring) Delete(account string) error {
+	delete(k, account)
+	return nil
+}
+
+func TestResolveSecrets(t *testing.T) {
+	keyring := memoryKeyring{"work": "sk-secret"}
+	tests := []struct {
+		name       string
+		apiKey     string
+		wantAPIKey string
+		wantErr    string
+	}{
+		{name: "plain key", apiKey: "sk-plain", wantAPIKey: "sk-plain"},
+		{name: "no key", apiKey: "", wantAPIKey: ""},
+		{name: "keyring entry", apiKey: "keyring:work", wantAPIKey: "sk-secret"},
+		{name: "no entry named", apiKey: "keyring:", wantErr: "names no keyring entry"},
+		{name: "missing entry", apiKey: "keyring:home", wantErr: ErrSecretNotFound.Error()},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got, err := ProviderConfig{Name: "p", APIKey: tt.apiKey}.ResolveSecrets(keyring)
+			if tt.wantErr != "" {
+				if err == nil || !strings.Contains(err.Error(), tt.wantErr) {
+					t.Errorf("err = %v, want one mentioning %q", err, tt.wantErr)
+				}
+				return
+			}
+			if err != nil {
+				t.Fatalf("unexpected error: %v", err)
+			}
+			if got.APIKey != tt.wantAPIKey {
+				t.Errorf("API key %q, want %q", got.APIKey, tt.wantAPIKey)
+			}
+		})
+	}
+
+	_, err := ProviderConfig{APIKey: "keyring:home"}.ResolveSecrets(keyring)
+	if !errors.Is(err, ErrSecretNotFound) {
+		t.Errorf("err = %v, want ErrSecretNotFound", err)
+	}
+}
+
+func TestProviderManagerKeyring(t *testing.T) {
+	m := newTestManager(t, false)
+	m.keyring = memoryKeyring{"work": "sk-secret"}
+	m.Add(ProviderConfig{Type: "Mock", Name: "work", Model: "mock", APIKey: "keyring:work"})
+	m.Add(ProviderConfig{Type: "Mock", Name: "home", Model: "mock", APIKey: "keyring:home"})
+
+	// The provider gets the secret, but the config keeps the reference
+	if got := m.configs[0].APIKey; got != "sk-secret" {
+		t.Errorf("provider built with %q, want the secret", got)
+	}
+	for i, want := range []string{"keyring:work", "keyring:home"} {
+		if got := m.Configs()[i].APIKey; got != want {
+			t.Errorf("config %d saved with %q, want %q", i, got, want)
+		}
+	}
+	if m.health[0].Status == HealthUnhealthy {
+		t.Errorf("work is unhealthy: %s", m.health[0].Error)
+	}
+	if m.health[1].Status != HealthUnhealthy || !strings.Contains(m.health[1].Error, "keyring:home") {
+		t.Errorf("home is %+v, want unhealthy naming its key", m.health[1])
+	}
+}
@@ -0,0 +1,108 @@
+//go:build !windows
+
+package main
+
+import (
+	"errors"
+	"fmt"
+	"os/exec"
+	"runtime"
+	"strings"
+)
+
+// systemKeyring uses the macOS Keychain through security(1), and elsewhere
+// the Secret Service through secret-tool(1) from libsecret
+func systemKeyring() Keyring {
+	if runtime.GOOS == "darwin" {
+		return keychain{}
+	}
+	return secretService{}
+}
+
+// runKeyringTool runs a keyring CLI with stdin as its input, returning its
+// output without the trailing newline
+func runKeyringTool(stdin string, name string, args ...string) (string, error) {
+	cmd := exec.Command(name, args...)
+	cmd.Stdin = strings.NewReader(stdin)
+	var stderr strings.Builder
+	cmd.Stderr = &stderr
+	out, err := cmd.Output()
+	if errors.Is(err, exec.ErrNotFound) {
+		return "", fmt.Errorf("%s is not installed", name)
+	}
+	if err != nil {
+		var exitErr *exec.ExitError
+		if errors.As(err, &exitErr) && strings.TrimSpace(stderr.String()) != "" {
+			return "", &keyringToolError{code: exitErr.ExitCode(), message: strings.TrimSpace(stderr.String())}
+		}
+		return "", err
+	}
+	return strings.TrimSuffix(string(out), "\n"), nil
+}
+
+type keyringToolError struct {
+	code    int
+	message string
+}
+
+func (e *keyringToolError) Error() string {
+	return e.message
+}
+
+// keychain keeps generic passwords in the user's login keychain
+type keychain struct{}
+
+// securityNotFound is security(1)'s exit code for a missing item
+const securityNotFound = 44
+
+func (keychain) Get(account string) (string, error) {
+	secret, err := runKeyringTool("", "security", "find-generic-password", "-s", keyringService, "-a", account, "-w")
+	var toolErr *keyringToolError
+	if errors.As(err, &toolErr) && toolErr.code == securityNotFound {
+		return "", ErrSecretNotFound
+	}
+	return secret, err
+}
+
+func (keychain) Set(account, secret string) error {
+	// security only takes the password as an argument, so it is briefly
+	// visible to other processes of the same user
+	_, err := runKeyringTool("", "security", "add-generic-password", "-U", "-s", keyringService, "-a", account, "-w", secret)
+	return err
+}
+
+func (keychain) Delete(account string) error {
+	_, err := runKeyringTool("", "security", "delete-generic-password", "-s", keyringService, "-a", account)
+	var toolErr *keyringToolError
+	if errors.As(err, &toolErr) && toolErr.code == securityNotFound {
+		return nil
+	}
+	return err
+}
+
+// secretService keeps secrets in the desktop's Secret Service collection
+type secretService struct{}
+
+func (secretService) Get(account string) (string, error) {
+	secret, err := runKeyringTool("", "secret-tool", "lookup", "service", keyringService, "account", account)
+	// A lookup that finds nothing fails without a message
+	var exitErr *exec.ExitError
+	if errors.As(err, &exitErr) || err == nil && secret == "" {
+		return "", ErrSecretNotFound
+	}
+	return secret, err
+}
+
+func (secretService) Set(account, secret string) error {
+	_, err := runKeyringTool(secret, "secret-tool", "store", "--label", keyringService+": "+account, "service", keyringService, "account", account)
+	return err
+}
+
+func (secretService) Delete(account string) error {
+	_, err := runKeyringTool("", "secret-tool", "clear", "service", keyringService, "account", account)
+	var exitErr *exec.ExitError
+	if errors.As(err, &exitErr) {
+		return nil // nothing to clear
+	}
+	return err
+}
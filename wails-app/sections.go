@@ -0,0 +1,62 @@
+package main
+
+// Section is a heading and everything under it up to the next heading of the
+// same or a higher level, with deeper headings nested as subsections
+type Section struct {
+	Heading     *ContentBlock  `json:"heading"` // nil for the blocks before the first heading
+	Level       int            `json:"level"`   // the heading's level, 0 without one
+	Blocks      []ContentBlock `json:"blocks"`  // before the first subsection
+	Subsections []Section      `json:"subsections"`
+}
+
+// Sections groups blocks under their headings. Blocks before the first
+// heading form a leading section without one.
+func Sections(blocks []ContentBlock) []Section {
+	root := &Section{Blocks: make([]ContentBlock, 0)}
+	// The open sections from the root down; each is the last subsection of
+	// the one before it
+	open := []*Section{root}
+	for _, block := range blocks {
+		if block.Type != BlockHeading {
+			current := open[len(open)-1]
+			current.Blocks = append(current.Blocks, block)
+			continue
+		}
+		for len(open) > 1 && open[len(open)-1].Level >= block.Level {
+			open = open[:len(open)-1]
+		}
+		heading := block
+		parent := open[len(open)-1]
+		parent.Subsections = append(parent.Subsections, Section{Heading: &heading, Level: block.Level, Blocks: make([]ContentBlock, 0)})
+		open = append(open, &parent.Subsections[len(parent.Subsections)-1])
+	}
+
+	sections := make([]Section, 0, len(root.Subsections)+1)
+	if len(root.Blocks) > 0 {
+		sections = append(sections, Section{Blocks: root.Blocks})
+	}
+	return append(sections, root.Subsections...)
+}
+
+// Flatten returns the section's heading and all its blocks in document order
+func (s Section) Flatten() []ContentBlock {
+	blocks := make([]ContentBlock, 0, len(s.Blocks)+1)
+	if s.Heading != nil {
+		blocks = append(blocks, *s.Heading)
+	}
+	blocks = append(blocks, s.Blocks...)
+	for _, sub := range s.Subsections {
+		blocks = append(blocks, sub.Flatten()...)
+	}
+	return blocks
+}
+
+// Sections exposes the section tree of parsed blocks to the frontend
+func (a *App) Sections(blocks []ContentBlock) []Section {
+	return Sections(blocks)
+}
+
+// SectionMarkdown returns a section as markdown, for copying just that part
+func (a *App) SectionMarkdown(section Section) string {
+	return RenderMarkdown(section.Flatten())
+}
@@ -0,0 +1,74 @@
+package main
+
+import (
+	"fmt"
+	"reflect"
+	"strings"
+	"testing"
+)
+
+// sectionTree prints one line per section, "heading (level): text", indented
+// by depth
+func sectionTree(sections []Section, depth int) []string {
+	var lines []string
+	for _, section := range sections {
+		heading := "-"
+		if section.Heading != nil {
+			heading = section.Heading.Text
+		}
+		line := fmt.Sprintf("%s%s (%d)", strings.Repeat("  ", depth), heading, section.Level)
+		var texts []string
+		for _, block := range section.Blocks {
+			texts = append(texts, block.Text)
+		}
+		if len(texts) > 0 {
+			line += ": " + strings.Join(texts, " | ")
+		}
+		lines = append(lines, line)
+		lines = append(lines, sectionTree(section.Subsections, depth+1)...)
+	}
+	return lines
+}
+
+func TestSections(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     []string
+	}{
+		{
+			name:     "nested headings",
+			markdown: "intro\n\n# A\n\na text\n\n## A.1\n\ndeep\n\n# B",
+			want:     []string{"- (0): intro", "A (1): a text", "  A.1 (2): deep", "B (1)"},
+		},
+		{
+			name:     "skipped level",
+			markdown: "# A\n\n### C\n\n## B\n\nb text",
+			want:     []string{"A (1)", "  C (3)", "  B (2): b text"},
+		},
+		{
+			name:     "no headings",
+			markdown: "one\n\ntwo",
+			want:     []string{"- (0): one | two"},
+		},
+		{"empty", "", nil},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			if got := sectionTree(Sections(ParseResponse(tt.markdown)), 0); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %q, want %q", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestSectionFlatten(t *testing.T) {
+	blocks := ParseResponse("intro\n\n# A\n\na text\n\n## A.1\n\ndeep\n\n# B\n\nlast")
+	var flattened []ContentBlock
+	for _, section := range Sections(blocks) {
+		flattened = append(flattened, section.Flatten()...)
+	}
+	if !reflect.DeepEqual(flattened, blocks) {
+		t.Errorf("got %+v, want %+v", flattened, blocks)
+	}
+}
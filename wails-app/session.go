@@ -0,0 +1,505 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+)
+
+// sessionHistoryLimit is how many entries the history keeps by default
+const sessionHistoryLimit = 1000
+
+// HistoryRetention is how much history is kept; a zero limit is no limit.
+// Pinned entries are kept regardless.
+type HistoryRetention struct {
+	MaxEntries int
+	MaxAge     time.Duration
+	MaxBytes   int64  // of the history file
+	Archive    string // the file pruned entries are appended to first; empty to only delete them
+}
+
+// SessionEntry is one prompt and the response it got
+type SessionEntry struct {
+	ID         int64             `json:"id"`
+	Time       string            `json:"time"` // RFC 3339
+	Prompt     string            `json:"prompt"`
+	Response   string            `json:"response"`
+	Provider   string            `json:"provider"`
+	Model      string            `json:"model"`
+	TextFormat string            `json:"textFormat,omitempty"`
+	Tokens     int               `json:"tokens"` // prompt and completion
+	Cost       float64           `json:"cost"`
+	Pinned     bool              `json:"pinned,omitempty"`     // kept past the limit
+	Tags       []string          `json:"tags,omitempty"`       // lowercase, sorted
+	Stats      ResponseStats     `json:"stats"`                // of the response; zero in entries recorded before it was kept
+	Provenance *ProvenanceReport `json:"provenance,omitempty"` // of the response; missing from entries recorded before it was kept
+}
+
+// HistoryQuery picks and ranks history entries. Dates are RFC 3339 times or
+// YYYY-MM-DD days, which take in the whole day, both ends included.
+type HistoryQuery struct {
+	Text     string `json:"text"`               // words to look for; empty matches everything
+	Provider string `json:"provider,omitempty"` // empty for any
+	From     string `json:"from,omitempty"`     // empty for no bound
+	To       string `json:"to,omitempty"`
+	Tag      string `json:"tag,omitempty"`    // empty for any
+	Pinned   bool   `json:"pinned,omitempty"` // only pinned entries
+	Limit    int    `json:"limit,omitempty"`  // 0 for every match
+}
+
+// HistoryMatch is an entry a query found and how well it matched
+type HistoryMatch struct {
+	Entry SessionEntry `json:"entry"`
+	Score float64      `json:"score"` // higher is better; 0 when the query has no words
+}
+
+// bounds parses the query's dates into the range [from, to); either is zero
+// when unbounded
+func (q HistoryQuery) bounds() (from, to time.Time, err error) {
+	parse := func(value string, end bool) (time.Time, error) {
+		if value == "" {
+			return time.Time{}, nil
+		}
+		if day, err := time.ParseInLocation(time.DateOnly, value, time.Local); err == nil {
+			if end {
+				day = day.AddDate(0, 0, 1)
+			}
+			return day, nil
+		}
+		t, err := time.Parse(time.RFC3339, value)
+		if err != nil {
+			return time.Time{}, fmt.Errorf("%q is not a date or an RFC 3339 time", value)
+		}
+		if end {
+			t = t.Add(time.Nanosecond)
+		}
+		return t, nil
+	}
+	if from, err = parse(q.From, false); err != nil {
+		return
+	}
+	to, err = parse(q.To, true)
+	return
+}
+
+// SessionHistory records every prompt answered, across sessions, and saves
+// it to disk after each one
+type SessionHistory struct {
+	mu        sync.Mutex
+	path      string
+	retention HistoryRetention
+	entries   []SessionEntry // oldest first
+	index     *historyIndex
+	vectors   *historyVectors
+}
+
+// NewSessionHistory loads the history stored at path and prunes it to
+// retention; a missing or unreadable file starts empty
+func NewSessionHistory(path string, retention HistoryRetention) *SessionHistory {
+	history := &SessionHistory{path: path, retention: retention, index: newHistoryIndex(), vectors: loadHistoryVectors(historyVectorsPath(path))}
+	if data, err := os.ReadFile(path); err == nil {
+		if err := json.Unmarshal(data, &history.entries); err != nil {
+			fmt.Fprintf(os.Stderr, "ignoring corrupt session history %s: %v\n", path, err)
+			history.entries = nil
+		}
+	} else if !errors.Is(err, os.ErrNotExist) {
+		fmt.Fprintf(os.Stderr, "failed to read session history: %v\n", err)
+	}
+	for _, entry := range history.entries {
+		history.index.add(entry)
+	}
+	history.pruneAndSave()
+	return history
+}
+
+// SetRetention changes how much history is kept, pruning it to fit
+func (h *SessionHistory) SetRetention(retention HistoryRetention) {
+	h.mu.Lock()
+	defer h.mu.Unlock()
+
+	if h.retention == retention {
+		return
+	}
+	h.retention = retention
+	h.pruneAndSave()
+}
+
+// pruneAndSave prunes the history and saves it if anything went; the caller
+// holds h.mu
+func (h *SessionHistory) pruneAndSave() {
+	pruned, err := h.prune()
+	if err != nil {
+		fmt.Fprintf(os.Stderr, "failed to prune session history: %v\n", err)
+	}
+	if pruned > 0 {
+		if err := h.save(); err != nil {
+			fmt.Fprintf(os.Stderr, "failed to save session history: %v\n", err)
+		}
+	}
+}
+
+// prune drops the unpinned entries, oldest first, that are older than the
+// retention's max age or leave the history over its max entries or size,
+// archiving them first if the retention says to; if they can't be archived
+// nothing is dropped. The caller holds h.mu.
+func (h *SessionHistory) prune() (int, error) {
+	r := h.retention
+	var cutoff time.Time
+	if r.MaxAge > 0 {
+		cutoff = time.Now().Add(-r.MaxAge)
+	}
+	// The file is the entries as a JSON array
+	sizes := make([]int64, len(h.entries))
+	size := int64(2)
+	if r.MaxBytes > 0 {
+		for i, entry := range h.entries {
+			data, _ := json.Marshal(entry)
+			sizes[i] = int64(len(data)) + 1
+			size += sizes[i]
+		}
+	}
+	count := len(h.entries)
+	drop := make(map[int]bool)
+	for i, entry := range h.entries {
+		if entry.Pinned {
+			continue
+		}
+		old := false
+		if !cutoff.IsZero() {
+			t, err := time.Parse(time.RFC3339, entry.Time)
+			old = err == nil && t.Before(cutoff)
+		}
+		over := (r.MaxEntries > 0 && count > r.MaxEntries) || (r.MaxBytes > 0 && size > r.MaxBytes)
+		if !old && !over {
+			// Later entries are newer and dropping none makes room
+			break
+		}
+		drop[i] = true
+		count--
+		size -= sizes[i]
+	}
+	if len(drop) == 0 {
+		return 0, nil
+	}
+
+	dropped := make([]SessionEntry, 0, len(drop))
+	kept := make([]SessionEntry, 0, len(h.entries)-len(drop))
+	for i, entry := range h.entries {
+		if drop[i] {
+			dropped = append(dropped, entry)
+		} else {
+			kept = append(kept, entry)
+		}
+	}
+	if r.Archive != "" {
+		if err := archiveHistory(r.Archive, dropped); err != nil {
+			return 0, fmt.Errorf("archiving to %s: %w", r.Archive, err)
+		}
+	}
+	for _, entry := range dropped {
+		h.index.remove(entry)
+		delete(h.vectors.Vectors, entry.ID)
+	}
+	h.entries = kept
+	return len(dropped), nil
+}
+
+// archiveHistory appends entries to path, one JSON object a line
+func archiveHistory(path string, entries []SessionEntry) error {
+	if err := os.MkdirAll(filepath.Dir(path), 0o700); err != nil {
+		return err
+	}
+	var lines []byte
+	for _, entry := range entries {
+		data, err := json.Marshal(entry)
+		if err != nil {
+			return err
+		}
+		lines = append(append(lines, data...), '\n')
+	}
+	file, err := os.OpenFile(path, os.O_WRONLY|os.O_APPEND|os.O_CREATE, 0o600)
+	if err != nil {
+		return err
+	}
+	if _, err := file.Write(lines); err != nil {
+		file.Close()
+		return err
+	}
+	return file.Close()
+}
+
+// find returns the position of the entry with id; the caller holds h.mu
+func (h *SessionHistory) find(id int64) (int, error) {
+	i := sort.Search(len(h.entries), func(i int) bool { return h.entries[i].ID >= id })
+	if i == len(h.entries) || h.entries[i].ID != id {
+		return 0, fmt.Errorf("no history entry %d", id)
+	}
+	return i, nil
+}
+
+// update changes the entry with id and saves the history
+func (h *SessionHistory) update(id int64, change func(*SessionEntry)) (SessionEntry, error) {
+	h.mu.Lock()
+	defer h.mu.Unlock()
+
+	i, err := h.find(id)
+	if err != nil {
+		return SessionEntry{}, err
+	}
+	entry := &h.entries[i]
+	change(entry)
+	return *entry, h.save()
+}
+
+// Pin keeps an entry however full the history gets, or lets it go again
+func (h *SessionHistory) Pin(id int64, pinned bool) (SessionEntry, error) {
+	return h.update(id, func(entry *SessionEntry) {
+		entry.Pinned = pinned
+	})
+}
+
+// normalizeTag trims a tag and lowers its case
+func normalizeTag(tag string) (string, error) {
+	tag = strings.ToLower(strings.TrimSpace(tag))
+	if tag == "" {
+		return "", errors.New("a tag must not be empty")
+	}
+	return tag, nil
+}
+
+// Tag adds a tag to an entry
+func (h *SessionHistory) Tag(id int64, tag string) (SessionEntry, error) {
+	tag, err := normalizeTag(tag)
+	if err != nil {
+		return SessionEntry{}, err
+	}
+	return h.update(id, func(entry *SessionEntry) {
+		if i := sort.SearchStrings(entry.Tags, tag); i == len(entry.Tags) || entry.Tags[i] != tag {
+			entry.Tags = append(entry.Tags[:i:i], append([]string{tag}, entry.Tags[i:]...)...)
+		}
+	})
+}
+
+// Untag removes a tag from an entry
+func (h *SessionHistory) Untag(id int64, tag string) (SessionEntry, error) {
+	tag, err := normalizeTag(tag)
+	if err != nil {
+		return SessionEntry{}, err
+	}
+	return h.update(id, func(entry *SessionEntry) {
+		if i := sort.SearchStrings(entry.Tags, tag); i < len(entry.Tags) && entry.Tags[i] == tag {
+			entry.Tags = append(entry.Tags[:i:i], entry.Tags[i+1:]...)
+		}
+	})
+}
+
+// Tags returns every tag in use, sorted
+func (h *SessionHistory) Tags() []string {
+	h.mu.Lock()
+	defer h.mu.Unlock()
+
+	seen := make(map[string]bool)
+	tags := make([]string, 0)
+	for _, entry := range h.entries {
+		for _, tag := range entry.Tags {
+			if !seen[tag] {
+				seen[tag] = true
+				tags = append(tags, tag)
+			}
+		}
+	}
+	sort.Strings(tags)
+	return tags
+}
+
+// Record adds a prompt and its response and saves the history
+func (h *SessionHistory) Record(prompt string, response AIResponse) SessionEntry {
+	blocks := ParseResponseAs(response.Content, response.TextFormat)
+	provenance := AnalyzeProvenance(blocks)
+
+	h.mu.Lock()
+	defer h.mu.Unlock()
+
+	entry := SessionEntry{
+		ID:         1,
+		Time:       time.Now().Format(time.RFC3339),
+		Prompt:     prompt,
+		Response:   response.Content,
+		Provider:   response.Provider,
+		Model:      response.Model,
+		TextFormat: response.TextFormat,
+		Tokens:     response.PromptTokens + response.CompletionTokens,
+		Cost:       response.Cost,
+		Stats:      ComputeResponseStats(response.Content, blocks),
+		Provenance: &provenance,
+	}
+	if n := len(h.entries); n > 0 {
+		entry.ID = h.entries[n-1].ID + 1
+	}
+	h.entries = append(h.entries, entry)
+	h.index.add(entry)
+	if _, err := h.prune(); err != nil {
+		fmt.Fprintf(os.Stderr, "failed to prune session history: %v\n", err)
+	}
+	if err := h.save(); err != nil {
+		fmt.Fprintf(os.Stderr, "failed to save session history: %v\n", err)
+	}
+	return entry
+}
+
+// Entries returns the history, newest first
+func (h *SessionHistory) Entries() []SessionEntry {
+	return h.Search("")
+}
+
+// Search returns the entries whose prompt or response has every word of
+// query, best match first; an empty query matches everything, newest first
+func (h *SessionHistory) Search(query string) []SessionEntry {
+	// Without dates a query can't fail
+	matches, _ := h.Query(HistoryQuery{Text: query})
+	entries := make([]SessionEntry, len(matches))
+	for i, match := range matches {
+		entries[i] = match.Entry
+	}
+	return entries
+}
+
+// Query returns the entries from the provider, dates, tag and pins asked
+// for whose prompt or response has every word of the query's text, ranked by
+// BM25 with prompts counting double, then newest first
+func (h *SessionHistory) Query(query HistoryQuery) ([]HistoryMatch, error) {
+	from, to, err := query.bounds()
+	if err != nil {
+		return nil, err
+	}
+	var tag string
+	if query.Tag != "" {
+		if tag, err = normalizeTag(query.Tag); err != nil {
+			return nil, err
+		}
+	}
+	words := searchWords(query.Text)
+
+	h.mu.Lock()
+	defer h.mu.Unlock()
+
+	scores := h.index.score(words)
+	matches := make([]HistoryMatch, 0)
+	for i := len(h.entries) - 1; i >= 0; i-- {
+		entry := h.entries[i]
+		score, found := scores[entry.ID]
+		if len(words) > 0 && !found {
+			continue
+		}
+		if query.Provider != "" && !strings.EqualFold(entry.Provider, query.Provider) {
+			continue
+		}
+		if query.Pinned && !entry.Pinned {
+			continue
+		}
+		if tag != "" {
+			if i := sort.SearchStrings(entry.Tags, tag); i == len(entry.Tags) || entry.Tags[i] != tag {
+				continue
+			}
+		}
+		if !from.IsZero() || !to.IsZero() {
+			t, err := time.Parse(time.RFC3339, entry.Time)
+			if err != nil || (!from.IsZero() && t.Before(from)) || (!to.IsZero() && !t.Before(to)) {
+				continue
+			}
+		}
+		matches = append(matches, HistoryMatch{Entry: entry, Score: score})
+	}
+	sort.SliceStable(matches, func(i, j int) bool { return matches[i].Score > matches[j].Score })
+	if query.Limit > 0 && len(matches) > query.Limit {
+		matches = matches[:query.Limit]
+	}
+	return matches, nil
+}
+
+// Clear deletes the history
+func (h *SessionHistory) Clear() error {
+	h.mu.Lock()
+	defer h.mu.Unlock()
+
+	h.entries = nil
+	h.index = newHistoryIndex()
+	if err := h.vectors.clear(); err != nil {
+		return err
+	}
+	if h.path == "" {
+		return nil
+	}
+	if err := os.Remove(h.path); err != nil && !errors.Is(err, os.ErrNotExist) {
+		return err
+	}
+	return nil
+}
+
+// save writes the history atomically; the caller holds h.mu
+func (h *SessionHistory) save() error {
+	if h.path == "" {
+		return nil
+	}
+	if err := os.MkdirAll(filepath.Dir(h.path), 0o700); err != nil {
+		return err
+	}
+	data, err := json.Marshal(h.entries)
+	if err != nil {
+		return err
+	}
+	tmp := h.path + ".tmp"
+	if err := os.WriteFile(tmp, data, 0o600); err != nil {
+		return err
+	}
+	return os.Rename(tmp, h.path)
+}
+
+// GetSessionHistory returns every recorded prompt and response, newest first
+func (a *App) GetSessionHistory() []SessionEntry {
+	return a.history.Entries()
+}
+
+// SearchSessionHistory returns the recorded prompts and responses containing
+// every word of query, newest first
+func (a *App) SearchSessionHistory(query string) []SessionEntry {
+	return a.history.Search(query)
+}
+
+// QuerySessionHistory returns the recorded prompts and responses matching
+// query, best first
+func (a *App) QuerySessionHistory(query HistoryQuery) ([]HistoryMatch, error) {
+	return a.history.Query(query)
+}
+
+// PinSessionEntry keeps a recorded prompt and response however full the
+// history gets, or lets it go again
+func (a *App) PinSessionEntry(id int64, pinned bool) (SessionEntry, error) {
+	return a.history.Pin(id, pinned)
+}
+
+// TagSessionEntry adds a tag to a recorded prompt and response
+func (a *App) TagSessionEntry(id int64, tag string) (SessionEntry, error) {
+	return a.history.Tag(id, tag)
+}
+
+// UntagSessionEntry removes a tag from a recorded prompt and response
+func (a *App) UntagSessionEntry(id int64, tag string) (SessionEntry, error) {
+	return a.history.Untag(id, tag)
+}
+
+// ListHistoryTags returns every tag on the history, sorted
+func (a *App) ListHistoryTags() []string {
+	return a.history.Tags()
+}
+
+// ClearSessionHistory deletes the recorded prompts and responses
+func (a *App) ClearSessionHistory() error {
+	return a.history.Clear()
+}
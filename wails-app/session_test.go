@@ -0,0 +1,287 @@
+package main
+
+import (
+	"bufio"
+	"encoding/json"
+	"os"
+	"path/filepath"
+	"reflect"
+	"strings"
+	"testing"
+	"time"
+)
+
+// newTestHistory returns an empty history kept in a temporary file
+func newTestHistory(t *testing.T, retention HistoryRetention) *SessionHistory {
+	t.Helper()
+	return NewSessionHistory(filepath.Join(t.TempDir(), "history.json"), retention)
+}
+
+func entryIDs(entries []SessionEntry) []int64 {
+	ids := make([]int64, len(entries))
+	for i, entry := range entries {
+		ids[i] = entry.ID
+	}
+	return ids
+}
+
+func TestSessionHistoryRecord(t *testing.T) {
+	history := newTestHistory(t, HistoryRetention{})
+	first := history.Record("hi", AIResponse{Content: "hello", Provider: "mock", Model: "m", PromptTokens: 3, CompletionTokens: 4})
+	second := history.Record("again", AIResponse{Content: "hello again\n\n```go\nx := 1\n```"})
+	if first.ID != 1 || second.ID != 2 || first.Tokens != 7 || first.Provider != "mock" {
+		t.Errorf("recorded %+v and %+v", first, second)
+	}
+	if got, want := second.Stats, (ResponseStats{Words: 2, Characters: 29, Tokens: EstimateTokens(second.Response), CodeBlocks: 1, CodeLines: 1, Languages: []string{"go"}}); !reflect.DeepEqual(got, want) {
+		t.Errorf("stats %+v, want %+v", got, want)
+	}
+
+	reloaded := NewSessionHistory(history.path, HistoryRetention{})
+	if got, want := reloaded.Entries(), []SessionEntry{second, first}; !reflect.DeepEqual(got, want) {
+		t.Errorf("reloaded %+v, want %+v", got, want)
+	}
+}
+
+func TestSessionHistorySearch(t *testing.T) {
+	history := newTestHistory(t, HistoryRetention{})
+	history.Record("sort a slice in go", AIResponse{Content: "Use sort.Slice"})
+	history.Record("reverse a string", AIResponse{Content: "In Go, convert to runes"})
+	history.Record("python list comprehension", AIResponse{Content: "[x for x in xs]"})
+
+	tests := []struct {
+		query string
+		want  []int64
+	}{
+		{"", []int64{3, 2, 1}},
+		{"go", []int64{1, 2}}, // in the prompt counts more than in the response
+		{"sort slice", []int64{1}},
+		{"pyth", []int64{3}},
+		{"missing", []int64{}},
+	}
+	for _, tt := range tests {
+		if got := entryIDs(history.Search(tt.query)); !reflect.DeepEqual(got, tt.want) {
+			t.Errorf("Search(%q) = %v, want %v", tt.query, got, tt.want)
+		}
+	}
+}
+
+// writeHistory saves entries as a history file and returns its path
+func writeHistory(t *testing.T, entries []SessionEntry) string {
+	t.Helper()
+	data, err := json.Marshal(entries)
+	if err != nil {
+		t.Fatal(err)
+	}
+	path := filepath.Join(t.TempDir(), "history.json")
+	if err := os.WriteFile(path, data, 0o600); err != nil {
+		t.Fatal(err)
+	}
+	return path
+}
+
+func matchIDs(matches []HistoryMatch) []int64 {
+	ids := make([]int64, len(matches))
+	for i, match := range matches {
+		ids[i] = match.Entry.ID
+	}
+	return ids
+}
+
+func TestSessionHistoryQuery(t *testing.T) {
+	history := NewSessionHistory(writeHistory(t, []SessionEntry{
+		{ID: 1, Time: "2026-01-05T12:00:00Z", Prompt: "go tests", Response: "ok", Provider: "openai"},
+		{ID: 2, Time: "2026-02-10T12:00:00Z", Prompt: "go modules", Response: "ok", Provider: "ollama"},
+		{ID: 3, Time: "2026-03-15T12:00:00Z", Prompt: "rust traits", Response: "ok", Provider: "OpenAI"},
+	}), HistoryRetention{})
+
+	tests := []struct {
+		name    string
+		query   HistoryQuery
+		want    []int64
+		wantErr bool
+	}{
+		{name: "provider ignores case", query: HistoryQuery{Provider: "openai"}, want: []int64{3, 1}},
+		{name: "from a day", query: HistoryQuery{From: "2026-02-01"}, want: []int64{3, 2}},
+		{name: "to a time, included", query: HistoryQuery{To: "2026-02-10T12:00:00Z"}, want: []int64{2, 1}},
+		{name: "words and dates", query: HistoryQuery{Text: "go", From: "2026-02-01"}, want: []int64{2}},
+		{name: "limit", query: HistoryQuery{Limit: 1}, want: []int64{3}},
+		{name: "bad date", query: HistoryQuery{From: "last week"}, wantErr: true},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			matches, err := history.Query(tt.query)
+			if tt.wantErr {
+				if err == nil {
+					t.Errorf("got %v, want an error", matchIDs(matches))
+				}
+				return
+			}
+			if err != nil {
+				t.Fatalf("unexpected error: %v", err)
+			}
+			if got := matchIDs(matches); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %v, want %v", got, tt.want)
+			}
+		})
+	}
+}
+
+// keywordEmbedder embeds a text as how often it mentions each keyword,
+// counting the texts it embeds
+func keywordEmbedder(model string, embedded *int, keywords ...string) Embedder {
+	return Embedder{Model: model, Embed: func(texts []string) ([][]float32, error) {
+		vectors := make([][]float32, len(texts))
+		for i, text := range texts {
+			*embedded++
+			for _, keyword := range keywords {
+				vectors[i] = append(vectors[i], float32(strings.Count(strings.ToLower(text), keyword)))
+			}
+		}
+		return vectors, nil
+	}}
+}
+
+func TestSessionHistorySemanticSearch(t *testing.T) {
+	history := newTestHistory(t, HistoryRetention{})
+	history.Record("my cat sleeps", AIResponse{Content: "cats nap a lot"})
+	history.Record("fix my car", AIResponse{Content: "check the car battery"})
+	history.Record("feed the cat", AIResponse{Content: "cat food twice daily"})
+
+	var embedded int
+	steps := []struct {
+		name         string
+		model        string
+		query        string
+		k            int
+		want         []int64
+		wantEmbedded int // texts embedded by this step
+	}{
+		{"embeds every entry first", "a", "cat", 2, []int64{3, 1}, 4},
+		{"then only the query", "a", "car", 1, []int64{2}, 1},
+		{"nothing to find", "a", "cat", 0, []int64{}, 0},
+		{"a new model embeds again", "b", "cat", 1, []int64{3}, 4},
+	}
+	for _, step := range steps {
+		before := embedded
+		matches, err := history.SemanticSearch(step.query, step.k, keywordEmbedder(step.model, &embedded, "cat", "car"))
+		if err != nil {
+			t.Fatalf("%s: %v", step.name, err)
+		}
+		if got := matchIDs(matches); !reflect.DeepEqual(got, step.want) {
+			t.Errorf("%s: got %v, want %v", step.name, got, step.want)
+		}
+		if got := embedded - before; got != step.wantEmbedded {
+			t.Errorf("%s: embedded %d texts, want %d", step.name, got, step.wantEmbedded)
+		}
+	}
+}
+
+func TestSessionHistoryTags(t *testing.T) {
+	history := newTestHistory(t, HistoryRetention{})
+	history.Record("hi", AIResponse{Content: "hello"})
+
+	steps := []struct {
+		name    string
+		change  func() (SessionEntry, error)
+		want    []string
+		wantErr bool
+	}{
+		{"trimmed and lowercased", func() (SessionEntry, error) { return history.Tag(1, " Go ") }, []string{"go"}, false},
+		{"kept sorted", func() (SessionEntry, error) { return history.Tag(1, "api") }, []string{"api", "go"}, false},
+		{"added once", func() (SessionEntry, error) { return history.Tag(1, "GO") }, []string{"api", "go"}, false},
+		{"removed", func() (SessionEntry, error) { return history.Untag(1, "go") }, []string{"api"}, false},
+		{"empty", func() (SessionEntry, error) { return history.Tag(1, " ") }, nil, true},
+		{"unknown entry", func() (SessionEntry, error) { return history.Tag(99, "x") }, nil, true},
+	}
+	for _, step := range steps {
+		entry, err := step.change()
+		if step.wantErr {
+			if err == nil {
+				t.Errorf("%s: got %+v, want an error", step.name, entry)
+			}
+			continue
+		}
+		if err != nil || !reflect.DeepEqual(entry.Tags, step.want) {
+			t.Errorf("%s: got %q, err %v; want %q", step.name, entry.Tags, err, step.want)
+		}
+	}
+	if got := history.Tags(); !reflect.DeepEqual(got, []string{"api"}) {
+		t.Errorf("Tags() = %q, want [api]", got)
+	}
+	matches, err := history.Query(HistoryQuery{Tag: "API"})
+	if err != nil || !reflect.DeepEqual(matchIDs(matches), []int64{1}) {
+		t.Errorf("querying the tag got %v, err %v", matchIDs(matches), err)
+	}
+}
+
+func TestSessionHistoryPinning(t *testing.T) {
+	history := newTestHistory(t, HistoryRetention{MaxEntries: 2})
+	history.Record("keep me", AIResponse{})
+	if _, err := history.Pin(1, true); err != nil {
+		t.Fatal(err)
+	}
+	history.Record("second", AIResponse{})
+	history.Record("third", AIResponse{})
+
+	if got := entryIDs(history.Entries()); !reflect.DeepEqual(got, []int64{3, 1}) {
+		t.Errorf("kept %v, want [3 1]", got)
+	}
+	matches, err := history.Query(HistoryQuery{Pinned: true})
+	if err != nil || !reflect.DeepEqual(matchIDs(matches), []int64{1}) {
+		t.Errorf("pinned entries %v, err %v; want [1]", matchIDs(matches), err)
+	}
+}
+
+func TestSessionHistoryPrune(t *testing.T) {
+	now := time.Now()
+	entries := []SessionEntry{
+		{ID: 1, Time: now.Add(-10 * 24 * time.Hour).Format(time.RFC3339), Prompt: "oldest"},
+		{ID: 2, Time: now.Add(-5 * 24 * time.Hour).Format(time.RFC3339), Prompt: "pinned", Pinned: true},
+		{ID: 3, Time: now.Add(-2 * 24 * time.Hour).Format(time.RFC3339), Prompt: "recent"},
+		{ID: 4, Time: now.Format(time.RFC3339), Prompt: "newest"},
+	}
+	tests := []struct {
+		name      string
+		retention HistoryRetention
+		want      []int64
+	}{
+		{"by entries, past the pinned one", HistoryRetention{MaxEntries: 2}, []int64{4, 2}},
+		{"by age", HistoryRetention{MaxAge: 3 * 24 * time.Hour}, []int64{4, 3, 2}},
+		{"by size, down to the pinned one", HistoryRetention{MaxBytes: 1}, []int64{2}},
+		{"no limits", HistoryRetention{}, []int64{4, 3, 2, 1}},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			history := NewSessionHistory(writeHistory(t, entries), tt.retention)
+			if got := entryIDs(history.Entries()); !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("kept %v, want %v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestSessionHistoryArchive(t *testing.T) {
+	path := writeHistory(t, []SessionEntry{{ID: 1, Prompt: "a"}, {ID: 2, Prompt: "b"}, {ID: 3, Prompt: "c"}})
+	archive := filepath.Join(filepath.Dir(path), "archive.jsonl")
+	history := NewSessionHistory(path, HistoryRetention{MaxEntries: 2, Archive: archive})
+	if got := entryIDs(history.Entries()); !reflect.DeepEqual(got, []int64{3, 2}) {
+		t.Errorf("kept %v, want [3 2]", got)
+	}
+
+	file, err := os.Open(archive)
+	if err != nil {
+		t.Fatal(err)
+	}
+	defer file.Close()
+	var archived []int64
+	for scanner := bufio.NewScanner(file); scanner.Scan(); {
+		var entry SessionEntry
+		if err := json.Unmarshal(scanner.Bytes(), &entry); err != nil {
+			t.Fatalf("archive line %q: %v", scanner.Text(), err)
+		}
+		archived = append(archived, entry.ID)
+	}
+	if !reflect.DeepEqual(archived, []int64{1}) {
+		t.Errorf("archived %v, want [1]", archived)
+	}
+}
@@ -0,0 +1,558 @@
+package main
+
+import (
+	"context"
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"strings"
+	"sync"
+	"time"
+)
+
+// sessionTitleLength is how much of its first prompt names an unnamed
+// session
+const sessionTitleLength = 40
+
+// sessionAutosaveInterval is how often drafts and answers still streaming in
+// are saved
+const sessionAutosaveInterval = 5 * time.Second
+
+// Session is one conversation, with a file of its own
+type Session struct {
+	ID           string        `json:"id"`
+	Name         string        `json:"name"`    // empty until named; listed by its first prompt
+	Created      string        `json:"created"` // RFC 3339, UTC
+	Updated      string        `json:"updated"`
+	Messages     []ChatMessage `json:"messages"`
+	Draft        string        `json:"draft,omitempty"`        // the prompt being written
+	Pending      *PendingTurn  `json:"pending,omitempty"`      // a prompt still waiting on its answer
+	ForkedFrom   string        `json:"forkedFrom,omitempty"`   // the session this one was forked from
+	ForkedAt     int           `json:"forkedAt,omitempty"`     // the turns copied from it
+	ImportedFrom string        `json:"importedFrom,omitempty"` // "chatgpt:<id>" or "claude:<uuid>" for imported chats
+}
+
+// PendingTurn is a prompt sent but not yet answered, with as much of the
+// answer as has streamed in. Finding one on launch means the app stopped
+// before the answer came.
+type PendingTurn struct {
+	Prompt  string `json:"prompt"`
+	Partial string `json:"partial"`
+	Started string `json:"started"` // RFC 3339, UTC
+}
+
+// SessionSummary is a session as the picker lists it
+type SessionSummary struct {
+	ID      string `json:"id"`
+	Title   string `json:"title"` // the name, or the start of the first prompt
+	Named   bool   `json:"named"`
+	Updated string `json:"updated"`
+	Turns   int    `json:"turns"`
+	Active  bool   `json:"active"`
+}
+
+func (s *Session) summary(active string) SessionSummary {
+	title := s.Name
+	if title == "" {
+		title = "New chat"
+		for _, message := range s.Messages {
+			if message.Role == "user" {
+				title = truncateTitle(message.Content)
+				break
+			}
+		}
+	}
+	turns := 0
+	for _, message := range s.Messages {
+		if message.Role == "user" {
+			turns++
+		}
+	}
+	return SessionSummary{ID: s.ID, Title: title, Named: s.Name != "", Updated: s.Updated, Turns: turns, Active: s.ID == active}
+}
+
+func truncateTitle(prompt string) string {
+	title := strings.Join(strings.Fields(prompt), " ")
+	if runes := []rune(title); len(runes) > sessionTitleLength {
+		title = string(runes[:sessionTitleLength-1]) + "…"
+	}
+	return title
+}
+
+// SessionStore keeps every conversation, one JSON file each in dir, and
+// which one is open. An empty dir keeps them in memory.
+type SessionStore struct {
+	mu          sync.Mutex
+	dir         string
+	sessions    map[string]*Session // by ID
+	active      string              // empty until the first session is made
+	dirty       map[string]bool     // sessions changed since they were saved
+	interrupted []string            // sessions left with a pending turn by the last run
+}
+
+// NewSessionStore loads the sessions saved in dir and opens the one last
+// updated; unreadable files are reported and skipped
+func NewSessionStore(dir string) *SessionStore {
+	store := &SessionStore{dir: dir, sessions: make(map[string]*Session), dirty: make(map[string]bool)}
+	if dir == "" {
+		return store
+	}
+	entries, err := os.ReadDir(dir)
+	if err != nil && !errors.Is(err, os.ErrNotExist) {
+		fmt.Fprintf(os.Stderr, "failed to read sessions: %v\n", err)
+	}
+	for _, entry := range entries {
+		if entry.IsDir() || filepath.Ext(entry.Name()) != ".json" {
+			continue
+		}
+		path := filepath.Join(dir, entry.Name())
+		data, err := os.ReadFile(path)
+		if err != nil {
+			fmt.Fprintf(os.Stderr, "failed to read session: %v\n", err)
+			continue
+		}
+		var session Session
+		if err := json.Unmarshal(data, &session); err != nil || session.ID == "" {
+			fmt.Fprintf(os.Stderr, "ignoring corrupt session %s: %v\n", path, err)
+			continue
+		}
+		store.sessions[session.ID] = &session
+		if session.Pending != nil {
+			store.interrupted = append(store.interrupted, session.ID)
+		}
+	}
+	sort.Strings(store.interrupted)
+	store.active = store.latest()
+	return store
+}
+
+// latest is the ID of the session updated last, or empty; the caller holds
+// s.mu
+func (s *SessionStore) latest() string {
+	latest := ""
+	for id, session := range s.sessions {
+		if latest == "" || session.Updated > s.sessions[latest].Updated || session.Updated == s.sessions[latest].Updated && id > latest {
+			latest = id
+		}
+	}
+	return latest
+}
+
+// file is where a session is saved; IDs are timestamps, safe as file names
+func (s *SessionStore) file(id string) string {
+	return filepath.Join(s.dir, id+".json")
+}
+
+// save writes a session atomically; the caller holds s.mu
+func (s *SessionStore) save(session *Session) error {
+	if s.dir == "" {
+		delete(s.dirty, session.ID)
+		return nil
+	}
+	if err := os.MkdirAll(s.dir, 0o700); err != nil {
+		return err
+	}
+	data, err := json.Marshal(session)
+	if err != nil {
+		return err
+	}
+	path := s.file(session.ID)
+	tmp := path + ".tmp"
+	if err := os.WriteFile(tmp, data, 0o600); err != nil {
+		return err
+	}
+	if err := os.Rename(tmp, path); err != nil {
+		return err
+	}
+	delete(s.dirty, session.ID)
+	return nil
+}
+
+// Autosave saves changed sessions every interval until ctx ends
+func (s *SessionStore) Autosave(ctx context.Context, interval time.Duration) {
+	ticker := time.NewTicker(interval)
+	defer ticker.Stop()
+	for {
+		select {
+		case <-ctx.Done():
+			return
+		case <-ticker.C:
+			s.Flush()
+		}
+	}
+}
+
+// Flush saves the sessions changed since they were last saved
+func (s *SessionStore) Flush() {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	for id := range s.dirty {
+		if err := s.save(s.sessions[id]); err != nil {
+			fmt.Fprintf(os.Stderr, "failed to autosave session: %v\n", err)
+		}
+	}
+}
+
+func copySession(session *Session) Session {
+	copied := *session
+	copied.Messages = append([]ChatMessage{}, session.Messages...)
+	if session.Pending != nil {
+		pending := *session.Pending
+		copied.Pending = &pending
+	}
+	return copied
+}
+
+// List returns every session, the last updated first
+func (s *SessionStore) List() []SessionSummary {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	list := make([]SessionSummary, 0, len(s.sessions))
+	for _, session := range s.sessions {
+		list = append(list, session.summary(s.active))
+	}
+	sort.Slice(list, func(i, j int) bool {
+		if list[i].Updated != list[j].Updated {
+			return list[i].Updated > list[j].Updated
+		}
+		return list[i].ID > list[j].ID
+	})
+	return list
+}
+
+// Create makes an empty session called name, which may be empty, and opens
+// it
+func (s *SessionStore) Create(name string) (Session, error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	return s.create(strings.TrimSpace(name))
+}
+
+// create is Create with the caller holding s.mu
+func (s *SessionStore) create(name string) (Session, error) {
+	now := time.Now()
+	id := now.UTC().Format("20060102T150405.000000000Z")
+	for s.sessions[id] != nil {
+		now = now.Add(time.Nanosecond)
+		id = now.UTC().Format("20060102T150405.000000000Z")
+	}
+	stamp := now.UTC().Format(time.RFC3339)
+	session := &Session{ID: id, Name: name, Created: stamp, Updated: stamp, Messages: []ChatMessage{}}
+	if err := s.save(session); err != nil {
+		return Session{}, err
+	}
+	s.sessions[id] = session
+	s.active = id
+	return copySession(session), nil
+}
+
+// Get returns a session without opening it
+func (s *SessionStore) Get(id string) (Session, error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	session, ok := s.sessions[id]
+	if !ok {
+		return Session{}, fmt.Errorf("no session %q", id)
+	}
+	return copySession(session), nil
+}
+
+// Open makes a session the one prompts are recorded in and returns it
+func (s *SessionStore) Open(id string) (Session, error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	session, ok := s.sessions[id]
+	if !ok {
+		return Session{}, fmt.Errorf("no session %q", id)
+	}
+	s.active = id
+	return copySession(session), nil
+}
+
+// Active returns the open session, making one if there is none
+func (s *SessionStore) Active() (Session, error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	if session, ok := s.sessions[s.active]; ok {
+		return copySession(session), nil
+	}
+	return s.create("")
+}
+
+func (s *SessionStore) Rename(id, name string) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	session, ok := s.sessions[id]
+	if !ok {
+		return fmt.Errorf("no session %q", id)
+	}
+	previous := session.Name
+	session.Name = strings.TrimSpace(name)
+	if err := s.save(session); err != nil {
+		session.Name = previous
+		return err
+	}
+	return nil
+}
+
+// Delete removes a session and its file; deleting the open one opens the
+// session updated last instead
+func (s *SessionStore) Delete(id string) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	if _, ok := s.sessions[id]; !ok {
+		return fmt.Errorf("no session %q", id)
+	}
+	if s.dir != "" {
+		if err := os.Remove(s.file(id)); err != nil && !errors.Is(err, os.ErrNotExist) {
+			return err
+		}
+	}
+	delete(s.sessions, id)
+	delete(s.dirty, id)
+	if s.active == id {
+		s.active = s.latest()
+	}
+	return nil
+}
+
+// Fork starts a session with the first turns of another, so a different
+// follow-up can be tried with the original left as it was, and opens it
+func (s *SessionStore) Fork(id string, turns int) (Session, error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	source, ok := s.sessions[id]
+	if !ok {
+		return Session{}, fmt.Errorf("no session %q", id)
+	}
+	if turns < 1 || turns*2 > len(source.Messages) {
+		return Session{}, fmt.Errorf("cannot fork at turn %d of %d", turns, len(source.Messages)/2)
+	}
+	name := ""
+	if source.Name != "" {
+		name = source.Name + " (fork)"
+	}
+	created, err := s.create(name)
+	if err != nil {
+		return Session{}, err
+	}
+	forked := s.sessions[created.ID]
+	forked.Messages = append([]ChatMessage{}, source.Messages[:turns*2]...)
+	forked.ForkedFrom, forked.ForkedAt = id, turns
+	return copySession(forked), s.save(forked)
+}
+
+// SetDraft keeps the prompt being written in the open session, for the next
+// autosave
+func (s *SessionStore) SetDraft(text string) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	if session, ok := s.sessions[s.active]; ok && session.Draft != text {
+		session.Draft = text
+		s.dirty[session.ID] = true
+	}
+}
+
+// Begin records prompt as pending in the open session, making a session if
+// there is none, and returns the session's ID for the calls that follow
+func (s *SessionStore) Begin(prompt string) string {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	session, ok := s.sessions[s.active]
+	if !ok {
+		if _, err := s.create(""); err != nil {
+			fmt.Fprintf(os.Stderr, "failed to save session: %v\n", err)
+			return ""
+		}
+		session = s.sessions[s.active]
+	}
+	session.Pending = &PendingTurn{Prompt: prompt, Started: time.Now().UTC().Format(time.RFC3339)}
+	if err := s.save(session); err != nil {
+		fmt.Fprintf(os.Stderr, "failed to save session: %v\n", err)
+	}
+	return session.ID
+}
+
+// Stream adds a streamed delta to a session's pending answer, for the next
+// autosave
+func (s *SessionStore) Stream(id, delta string) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	if session, ok := s.sessions[id]; ok && session.Pending != nil {
+		session.Pending.Partial += delta
+		s.dirty[id] = true
+	}
+}
+
+// Abandon drops a session's pending turn once its request has failed
+func (s *SessionStore) Abandon(id string) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	if session, ok := s.sessions[id]; ok && session.Pending != nil {
+		session.Pending = nil
+		if err := s.save(session); err != nil {
+			fmt.Fprintf(os.Stderr, "failed to save session: %v\n", err)
+		}
+	}
+}
+
+// Record saves a session as the conversation history continued with prompt
+// and its answer, in the given text format, in place of the pending turn
+func (s *SessionStore) Record(id string, history []ChatMessage, prompt, answer, format string) error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	session, ok := s.sessions[id]
+	if !ok {
+		return fmt.Errorf("no session %q", id)
+	}
+	messages := make([]ChatMessage, 0, len(history)+2)
+	messages = append(messages, history...)
+	messages = append(messages, ChatMessage{Role: "user", Content: prompt}, ChatMessage{Role: "assistant", Content: answer, TextFormat: format})
+	session.Messages = messages
+	session.Pending = nil
+	if session.Draft == prompt {
+		session.Draft = ""
+	}
+	session.Updated = time.Now().UTC().Format(time.RFC3339)
+	return s.save(session)
+}
+
+// Interrupted returns the sessions the last run left waiting on an answer,
+// pending turns included
+func (s *SessionStore) Interrupted() []Session {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	interrupted := make([]Session, 0, len(s.interrupted))
+	for _, id := range s.interrupted {
+		if session, ok := s.sessions[id]; ok && session.Pending != nil {
+			interrupted = append(interrupted, copySession(session))
+		}
+	}
+	return interrupted
+}
+
+// Recover opens an interrupted session and returns it with its pending turn,
+// which is then dropped; discard only drops it
+func (s *SessionStore) Recover(id string, discard bool) (Session, error) {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	session, ok := s.sessions[id]
+	if !ok {
+		return Session{}, fmt.Errorf("no session %q", id)
+	}
+	recovered := copySession(session)
+	if !discard {
+		s.active = id
+	}
+	session.Pending = nil
+	for i, interrupted := range s.interrupted {
+		if interrupted == id {
+			s.interrupted = append(s.interrupted[:i], s.interrupted[i+1:]...)
+			break
+		}
+	}
+	return recovered, s.save(session)
+}
+
+// answered saves the outcome of a prompt sent with history in the session
+// Begin returned: the turn on success, nothing on failure
+func (a *App) answered(id string, history []ChatMessage, prompt string, response AIResponse, err error) {
+	if err != nil {
+		a.sessions.Abandon(id)
+		return
+	}
+	if err := a.sessions.Record(id, history, prompt, response.Content, response.TextFormat); err != nil {
+		a.logs.Warning(fmt.Sprintf("failed to save the session: %v", err))
+	}
+}
+
+// ListSessions returns every conversation, the last updated first
+func (a *App) ListSessions() []SessionSummary {
+	return a.sessions.List()
+}
+
+// ActiveSession returns the open conversation, to show on launch
+func (a *App) ActiveSession() (Session, error) {
+	return a.sessions.Active()
+}
+
+// NewSession starts and opens an empty conversation called name (empty to
+// be named after its first prompt), telling plugins a new chat has begun
+func (a *App) NewSession(name string) (Session, error) {
+	session, err := a.sessions.Create(name)
+	if err != nil {
+		return Session{}, err
+	}
+	a.StartSession()
+	return session, nil
+}
+
+// ForkSession starts a conversation from the first turns of another, which
+// is kept as it was, and switches to it
+func (a *App) ForkSession(id string, turns int) (Session, error) {
+	session, err := a.sessions.Fork(id, turns)
+	if err != nil {
+		return Session{}, err
+	}
+	a.StartSession()
+	return session, nil
+}
+
+// OpenSession switches to a conversation and returns it
+func (a *App) OpenSession(id string) (Session, error) {
+	return a.sessions.Open(id)
+}
+
+// SaveDraft keeps the prompt being written with the open conversation; it is
+// saved with the next autosave and restored when the conversation is opened
+func (a *App) SaveDraft(text string) {
+	a.sessions.SetDraft(text)
+}
+
+// InterruptedSessions returns the conversations the last run stopped in
+// while an answer was on its way, with the prompt and what had streamed in
+func (a *App) InterruptedSessions() []Session {
+	return a.sessions.Interrupted()
+}
+
+// RecoverSession opens an interrupted conversation and returns it with its
+// unanswered prompt and partial answer, for the frontend to show
+func (a *App) RecoverSession(id string) (Session, error) {
+	return a.sessions.Recover(id, false)
+}
+
+// DiscardRecovery drops an interrupted conversation's unanswered prompt
+func (a *App) DiscardRecovery(id string) error {
+	_, err := a.sessions.Recover(id, true)
+	return err
+}
+
+// RenameSession names a conversation; an empty name goes back to its first
+// prompt
+func (a *App) RenameSession(id, name string) error {
+	return a.sessions.Rename(id, name)
+}
+
+// DeleteSession removes a conversation and its file
+func (a *App) DeleteSession(id string) error {
+	return a.sessions.Delete(id)
+}
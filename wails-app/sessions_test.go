@@ -0,0 +1,164 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestTruncateTitle(t *testing.T) {
+	tests := []struct {
+		prompt string
+		want   string
+	}{
+		{"  short \n prompt ", "short prompt"},
+		{strings.Repeat("a", 40), strings.Repeat("a", 40)},
+		{strings.Repeat("a", 41), strings.Repeat("a", 39) + "…"},
+		{strings.Repeat("é", 41), strings.Repeat("é", 39) + "…"},
+	}
+	for _, tt := range tests {
+		if got := truncateTitle(tt.prompt); got != tt.want {
+			t.Errorf("truncateTitle(%q) = %q, want %q", tt.prompt, got, tt.want)
+		}
+	}
+}
+
+func sessionTitles(list []SessionSummary) map[string]string {
+	titles := make(map[string]string, len(list))
+	for _, summary := range list {
+		titles[summary.ID] = summary.Title
+	}
+	return titles
+}
+
+func TestSessionStore(t *testing.T) {
+	dir := t.TempDir()
+	store := NewSessionStore(dir)
+	work, err := store.Create("  Work  ")
+	if err != nil {
+		t.Fatal(err)
+	}
+	chat, _ := store.Create("")
+	scratch, _ := store.Create("scratch")
+	if got := sessionTitles(store.List())[chat.ID]; got != "New chat" {
+		t.Errorf("an unnamed session is titled %q, want New chat", got)
+	}
+
+	if err := store.Record(chat.ID, nil, "How do I   reverse a slice in Go without allocating?", "In place.", TextMarkdown); err != nil {
+		t.Fatal(err)
+	}
+	if err := store.Rename(work.ID, "Work notes"); err != nil {
+		t.Fatal(err)
+	}
+	if err := store.Delete(scratch.ID); err != nil {
+		t.Fatal(err)
+	}
+	if active, _ := store.Active(); active.ID != chat.ID {
+		t.Errorf("deleting the open session opened %s, want the last updated %s", active.ID, chat.ID)
+	}
+
+	want := map[string]string{work.ID: "Work notes", chat.ID: "How do I reverse a slice in Go without …"}
+	if got := sessionTitles(store.List()); !reflect.DeepEqual(got, want) {
+		t.Errorf("listed %q, want %q", got, want)
+	}
+	if got := sessionTitles(NewSessionStore(dir).List()); !reflect.DeepEqual(got, want) {
+		t.Errorf("reloaded %q, want %q", got, want)
+	}
+}
+
+func TestSessionStoreRecover(t *testing.T) {
+	tests := []struct {
+		name       string
+		discard    bool
+		wantActive string // "a" or "b"
+	}{
+		{"restore", false, "a"},
+		{"discard", true, "b"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			dir := t.TempDir()
+			store := NewSessionStore(dir)
+			a, _ := store.Create("A")
+			store.SetDraft("next")
+			id := store.Begin("prompt")
+			store.Stream(id, "part")
+			store.Stream(id, "ial")
+			b, _ := store.Create("B")
+			store.Flush()
+
+			store = NewSessionStore(dir)
+			interrupted := store.Interrupted()
+			if len(interrupted) != 1 || interrupted[0].ID != a.ID || interrupted[0].Draft != "next" ||
+				interrupted[0].Pending == nil || interrupted[0].Pending.Prompt != "prompt" || interrupted[0].Pending.Partial != "partial" {
+				t.Fatalf("interrupted %+v, want A with its draft and partial answer", interrupted)
+			}
+
+			recovered, err := store.Recover(a.ID, tt.discard)
+			if err != nil {
+				t.Fatal(err)
+			}
+			if recovered.Pending == nil || recovered.Pending.Partial != "partial" {
+				t.Errorf("recovered %+v without its pending turn", recovered)
+			}
+			wantActive := map[string]string{"a": a.ID, "b": b.ID}[tt.wantActive]
+			if active, _ := store.Active(); active.ID != wantActive {
+				t.Errorf("open session %s, want %s", active.ID, wantActive)
+			}
+			if got := store.Interrupted(); !reflect.DeepEqual(got, []Session{}) {
+				t.Errorf("still interrupted: %+v", got)
+			}
+			if got := NewSessionStore(dir).Interrupted(); len(got) != 0 {
+				t.Errorf("interrupted again after reloading: %+v", got)
+			}
+		})
+	}
+}
+
+func TestSessionStoreFork(t *testing.T) {
+	store := NewSessionStore(t.TempDir())
+	source, _ := store.Create("Plan")
+	if err := store.Record(source.ID, nil, "p1", "a1", TextMarkdown); err != nil {
+		t.Fatal(err)
+	}
+	first, _ := store.Get(source.ID)
+	if err := store.Record(source.ID, first.Messages, "p2", "a2", TextMarkdown); err != nil {
+		t.Fatal(err)
+	}
+	source, _ = store.Get(source.ID)
+
+	tests := []struct {
+		turns        int
+		wantMessages int
+		wantErr      bool
+	}{
+		{turns: 1, wantMessages: 2},
+		{turns: 2, wantMessages: 4},
+		{turns: 0, wantErr: true},
+		{turns: 3, wantErr: true},
+	}
+	for _, tt := range tests {
+		forked, err := store.Fork(source.ID, tt.turns)
+		if tt.wantErr {
+			if err == nil {
+				t.Errorf("forking at turn %d: got %+v, want an error", tt.turns, forked)
+			}
+			continue
+		}
+		if err != nil {
+			t.Fatalf("forking at turn %d: %v", tt.turns, err)
+		}
+		if !reflect.DeepEqual(forked.Messages, source.Messages[:tt.wantMessages]) {
+			t.Errorf("forked at turn %d with %+v", tt.turns, forked.Messages)
+		}
+		if forked.Name != "Plan (fork)" || forked.ForkedFrom != source.ID || forked.ForkedAt != tt.turns {
+			t.Errorf("forked at turn %d as %q from %q at %d", tt.turns, forked.Name, forked.ForkedFrom, forked.ForkedAt)
+		}
+		if active, _ := store.Active(); active.ID != forked.ID {
+			t.Errorf("the fork wasn't opened")
+		}
+	}
+	if after, _ := store.Get(source.ID); !reflect.DeepEqual(after, source) {
+		t.Errorf("forking changed the source: %+v", after)
+	}
+}
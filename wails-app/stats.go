@@ -0,0 +1,68 @@
+package main
+
+import (
+	"sort"
+	"strings"
+	"unicode/utf8"
+)
+
+// ResponseStats summarizes the size and makeup of a response
+type ResponseStats struct {
+	Words      int      `json:"words"`      // in prose, outside code
+	Characters int      `json:"characters"` // of the whole response
+	Tokens     int      `json:"tokens"`     // estimated, like EstimateTokens
+	CodeBlocks int      `json:"codeBlocks"` // code and diff blocks, nested ones included
+	CodeLines  int      `json:"codeLines"`
+	Languages  []string `json:"languages"` // of the code blocks, sorted, without duplicates
+}
+
+// ComputeResponseStats measures a response from its text and parsed blocks
+func ComputeResponseStats(content string, blocks []ContentBlock) ResponseStats {
+	stats := ResponseStats{
+		Characters: utf8.RuneCountInString(content),
+		Tokens:     EstimateTokens(content),
+		Languages:  make([]string, 0),
+	}
+	languages := make(map[string]bool)
+	walkBlocks(blocks, func(block ContentBlock) {
+		switch block.Type {
+		case BlockCode, BlockDiff:
+			stats.CodeBlocks++
+			if block.Code != "" {
+				stats.CodeLines += strings.Count(block.Code, "\n") + 1
+			}
+			if block.Language != "" && !languages[block.Language] {
+				languages[block.Language] = true
+				stats.Languages = append(stats.Languages, block.Language)
+			}
+		case BlockTable:
+			for _, cell := range block.Headers {
+				stats.Words += len(strings.Fields(cell))
+			}
+			for _, row := range block.Rows {
+				for _, cell := range row {
+					stats.Words += len(strings.Fields(cell))
+				}
+			}
+		default:
+			stats.Words += len(strings.Fields(block.Text))
+			for _, item := range block.Items {
+				stats.Words += len(strings.Fields(item.Text))
+			}
+		}
+	})
+	sort.Strings(stats.Languages)
+	return stats
+}
+
+// walkBlocks calls fn for every block in document order, descending into
+// quotes, footnotes and list items
+func walkBlocks(blocks []ContentBlock, fn func(ContentBlock)) {
+	for _, block := range blocks {
+		fn(block)
+		walkBlocks(block.Children, fn)
+		for _, item := range block.Items {
+			walkBlocks(item.Children, fn)
+		}
+	}
+}
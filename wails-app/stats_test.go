@@ -0,0 +1,45 @@
+package main
+
+import (
+	"reflect"
+	"testing"
+)
+
+func TestComputeResponseStats(t *testing.T) {
+	tests := []struct {
+		name     string
+		markdown string
+		want     ResponseStats // Characters and Tokens aren't compared
+	}{
+		{
+			name:     "prose, lists, tables and code",
+			markdown: "# Stats here\n\nSome prose with five words.\n\n- one item\n- two\n\n| a b | c |\n|---|---|\n| d | e f |\n\n```python\nprint(1)\n```\n\n```go\nx := 1\ny := 2\n```",
+			want:     ResponseStats{Words: 16, CodeBlocks: 2, CodeLines: 3, Languages: []string{"go", "python"}},
+		},
+		{
+			name:     "code inside a quote",
+			markdown: "> ```js\n> a()\n> ```\n\n```javascript\nb()\n```",
+			want:     ResponseStats{CodeBlocks: 2, CodeLines: 2, Languages: []string{"javascript"}},
+		},
+		{
+			name: "empty",
+			want: ResponseStats{Languages: []string{}},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got := ComputeResponseStats(tt.markdown, ParseResponse(tt.markdown))
+			got.Characters, got.Tokens = 0, 0
+			if !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %+v, want %+v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestComputeResponseStatsSize(t *testing.T) {
+	got := ComputeResponseStats("héllo wörld", ParseResponse("héllo wörld"))
+	if got.Characters != 11 || got.Tokens != 4 {
+		t.Errorf("got %d characters and %d tokens, want 11 and 4", got.Characters, got.Tokens)
+	}
+}
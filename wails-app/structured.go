@@ -0,0 +1,221 @@
+package main
+
+import (
+	"encoding/json"
+	"fmt"
+	"math"
+	"sort"
+	"strings"
+)
+
+const (
+	FormatText       = ""            // freeform text, the default
+	FormatJSON       = "json"        // any valid JSON value
+	FormatJSONSchema = "json_schema" // JSON matching ResponseFormat.Schema
+)
+
+// ResponseFormat asks the provider for structured output. Providers map it to
+// their native JSON mode; the manager validates the reply either way.
+type ResponseFormat struct {
+	Type   string          `json:"type"`
+	Schema json.RawMessage `json:"schema,omitempty"` // JSON Schema, for FormatJSONSchema
+}
+
+// JSON reports whether the format asks for JSON output
+func (f ResponseFormat) JSON() bool {
+	return f.Type == FormatJSON || f.Type == FormatJSONSchema
+}
+
+// instruction tells the model about the format, for providers without a native
+// JSON mode and for APIs (like OpenAI's) that require JSON to be mentioned
+func (f ResponseFormat) instruction() string {
+	switch {
+	case f.Type == FormatJSONSchema && len(f.Schema) > 0:
+		return "Respond only with valid JSON, without code fences, matching this JSON Schema:\n" + string(f.Schema)
+	case f.JSON():
+		return "Respond only with valid JSON, without code fences."
+	}
+	return ""
+}
+
+// SchemaError reports a structured response that doesn't match its format
+type SchemaError struct {
+	Path    string
+	Message string
+}
+
+func (e *SchemaError) Error() string {
+	return fmt.Sprintf("response does not match schema at %s: %s", e.Path, e.Message)
+}
+
+// unfenceJSON strips a surrounding ```json fence, which some models add even in JSON mode
+func unfenceJSON(content string) string {
+	content = strings.TrimSpace(content)
+	if !strings.HasPrefix(content, "```") {
+		return content
+	}
+	content = strings.TrimPrefix(content, "```")
+	if newline := strings.IndexByte(content, '\n'); newline >= 0 {
+		content = content[newline+1:]
+	}
+	return strings.TrimSpace(strings.TrimSuffix(strings.TrimSpace(content), "```"))
+}
+
+// ValidateResponse checks content against the requested format
+func ValidateResponse(content string, format ResponseFormat) error {
+	if !format.JSON() {
+		return nil
+	}
+
+	var value interface{}
+	if err := json.Unmarshal([]byte(unfenceJSON(content)), &value); err != nil {
+		return &SchemaError{Path: "$", Message: fmt.Sprintf("invalid JSON: %v", err)}
+	}
+	if format.Type == FormatJSON || len(format.Schema) == 0 {
+		return nil
+	}
+
+	var schema map[string]interface{}
+	if err := json.Unmarshal(format.Schema, &schema); err != nil {
+		return fmt.Errorf("invalid schema: %v", err)
+	}
+	return validateSchema(value, schema, "$")
+}
+
+// validateSchema checks value against the commonly used JSON Schema keywords:
+// type, enum, const, properties, required, additionalProperties, items,
+// minItems/maxItems, minLength/maxLength and minimum/maximum
+func validateSchema(value interface{}, schema map[string]interface{}, path string) error {
+	fail := func(format string, args ...interface{}) error {
+		return &SchemaError{Path: path, Message: fmt.Sprintf(format, args...)}
+	}
+
+	if types, ok := schema["type"]; ok && !matchesType(value, types) {
+		return fail("expected %v, got %s", types, jsonType(value))
+	}
+	if options, ok := schema["enum"].([]interface{}); ok {
+		found := false
+		for _, option := range options {
+			if jsonEqual(value, option) {
+				found = true
+				break
+			}
+		}
+		if !found {
+			return fail("value is not one of %v", options)
+		}
+	}
+	if constant, ok := schema["const"]; ok && !jsonEqual(value, constant) {
+		return fail("expected %v", constant)
+	}
+
+	switch v := value.(type) {
+	case map[string]interface{}:
+		properties, _ := schema["properties"].(map[string]interface{})
+		if required, ok := schema["required"].([]interface{}); ok {
+			for _, name := range required {
+				if key, ok := name.(string); ok {
+					if _, present := v[key]; !present {
+						return fail("missing required property %q", key)
+					}
+				}
+			}
+		}
+		keys := make([]string, 0, len(v))
+		for key := range v {
+			keys = append(keys, key)
+		}
+		sort.Strings(keys)
+		for _, key := range keys {
+			if sub, ok := properties[key].(map[string]interface{}); ok {
+				if err := validateSchema(v[key], sub, path+"."+key); err != nil {
+					return err
+				}
+				continue
+			}
+			switch extra := schema["additionalProperties"].(type) {
+			case bool:
+				if !extra {
+					return fail("unexpected property %q", key)
+				}
+			case map[string]interface{}:
+				if err := validateSchema(v[key], extra, path+"."+key); err != nil {
+					return err
+				}
+			}
+		}
+	case []interface{}:
+		if n, ok := schema["minItems"].(float64); ok && float64(len(v)) < n {
+			return fail("expected at least %g items, got %d", n, len(v))
+		}
+		if n, ok := schema["maxItems"].(float64); ok && float64(len(v)) > n {
+			return fail("expected at most %g items, got %d", n, len(v))
+		}
+		if items, ok := schema["items"].(map[string]interface{}); ok {
+			for i, item := range v {
+				if err := validateSchema(item, items, fmt.Sprintf("%s[%d]", path, i)); err != nil {
+					return err
+				}
+			}
+		}
+	case string:
+		length := float64(len([]rune(v)))
+		if n, ok := schema["minLength"].(float64); ok && length < n {
+			return fail("expected at least %g characters", n)
+		}
+		if n, ok := schema["maxLength"].(float64); ok && length > n {
+			return fail("expected at most %g characters", n)
+		}
+	case float64:
+		if n, ok := schema["minimum"].(float64); ok && v < n {
+			return fail("expected a value >= %g", n)
+		}
+		if n, ok := schema["maximum"].(float64); ok && v > n {
+			return fail("expected a value <= %g", n)
+		}
+	}
+	return nil
+}
+
+// matchesType reports whether value has the schema type, or one of a list of types
+func matchesType(value interface{}, types interface{}) bool {
+	switch t := types.(type) {
+	case string:
+		actual := jsonType(value)
+		return actual == t || (t == "number" && actual == "integer")
+	case []interface{}:
+		for _, option := range t {
+			if matchesType(value, option) {
+				return true
+			}
+		}
+		return false
+	}
+	return true
+}
+
+func jsonType(value interface{}) string {
+	switch v := value.(type) {
+	case nil:
+		return "null"
+	case bool:
+		return "boolean"
+	case float64:
+		if v == math.Trunc(v) {
+			return "integer"
+		}
+		return "number"
+	case string:
+		return "string"
+	case []interface{}:
+		return "array"
+	default:
+		return "object"
+	}
+}
+
+func jsonEqual(a, b interface{}) bool {
+	left, _ := json.Marshal(a)
+	right, _ := json.Marshal(b)
+	return string(left) == string(right)
+}
@@ -0,0 +1,145 @@
+package main
+
+import (
+	"fmt"
+	"reflect"
+	"regexp"
+	"sort"
+
+	wailsruntime "github.com/wailsapp/wails/v2/pkg/runtime"
+)
+
+// themeChangedEvent tells the frontend the theme changed, with its colors
+const themeChangedEvent = "theme:changed"
+
+// Built-in themes; ui.palettes in the config adds more
+const (
+	ThemeDark  = "dark"
+	ThemeLight = "light"
+)
+
+// Palette is the colors the UI is drawn in, as #rgb or #rrggbb. A custom
+// palette starts from its base, so it need only give the colors it changes.
+type Palette struct {
+	Base       string `json:"base"`       // "dark" or "light", which also picks the editor's theme
+	Background string `json:"background"` // the response area and inputs
+	Panel      string `json:"panel"`      // the sidebar, headers and prompt bar
+	Tab        string `json:"tab"`        // the tab bar
+	Activity   string `json:"activity"`   // the activity bar
+	Raised     string `json:"raised"`     // buttons
+	Hover      string `json:"hover"`
+	Selection  string `json:"selection"` // sent prompts and selected items
+	Border     string `json:"border"`
+	Code       string `json:"code"` // behind code blocks
+	Text       string `json:"text"`
+	SoftText   string `json:"softText"`
+	Muted      string `json:"muted"`
+	Accent     string `json:"accent"` // the status bar
+	Keyword    string `json:"keyword"`
+	String     string `json:"string"`
+	Comment    string `json:"comment"`
+	Number     string `json:"number"`
+	Type       string `json:"type"`
+	Function   string `json:"function"`
+}
+
+// builtinPalettes are the dark theme, after VS Code's Dark+, and the light
+// one, after Light+
+var builtinPalettes = map[string]Palette{
+	ThemeDark: {
+		Base: ThemeDark, Background: "#1e1e1e", Panel: "#252526", Tab: "#2d2d2d", Activity: "#2c2c2c",
+		Raised: "#3c3c3c", Hover: "#4c4c4c", Selection: "#264f78", Border: "#3c3c3c", Code: "#1a1a1a",
+		Text: "#e5e7eb", SoftText: "#d1d5db", Muted: "#9ca3af", Accent: "#007acc",
+		Keyword: "#c586c0", String: "#ce9178", Comment: "#6a9955", Number: "#b5cea8", Type: "#4ec9b0", Function: "#dcdcaa",
+	},
+	ThemeLight: {
+		Base: ThemeLight, Background: "#ffffff", Panel: "#f3f3f3", Tab: "#ececec", Activity: "#e4e4e4",
+		Raised: "#e0e0e0", Hover: "#d4d4d4", Selection: "#cce4f7", Border: "#d4d4d4", Code: "#f6f8fa",
+		Text: "#1f2328", SoftText: "#3b4048", Muted: "#6e7781", Accent: "#007acc",
+		Keyword: "#af00db", String: "#a31515", Comment: "#008000", Number: "#098658", Type: "#267f99", Function: "#795e26",
+	},
+}
+
+var hexColor = regexp.MustCompile(`^#([0-9a-fA-F]{3}|[0-9a-fA-F]{6})$`)
+
+// Validate reports a base other than dark or light, or a color that isn't
+// hex
+func (p Palette) Validate() error {
+	if p.Base != "" && p.Base != ThemeDark && p.Base != ThemeLight {
+		return fmt.Errorf("base must be dark or light, not %q", p.Base)
+	}
+	v, t := reflect.ValueOf(p), reflect.TypeOf(p)
+	for i := 0; i < v.NumField(); i++ {
+		if name := t.Field(i).Name; name != "Base" {
+			if color := v.Field(i).String(); color != "" && !hexColor.MatchString(color) {
+				return fmt.Errorf("%s: %q is not a #rgb or #rrggbb color", t.Field(i).Tag.Get("json"), color)
+			}
+		}
+	}
+	return nil
+}
+
+// over fills in the colors p leaves out from base
+func (p Palette) over(base Palette) Palette {
+	merged := base
+	v, m := reflect.ValueOf(p), reflect.ValueOf(&merged).Elem()
+	for i := 0; i < v.NumField(); i++ {
+		if color := v.Field(i).String(); color != "" {
+			m.Field(i).SetString(color)
+		}
+	}
+	return merged
+}
+
+// Theme is a theme by name with every color it is drawn in
+type Theme struct {
+	Name    string  `json:"name"`
+	Palette Palette `json:"palette"`
+}
+
+// theme resolves the configured theme, a built-in one or a custom palette
+// over its base; one that doesn't exist falls back to dark
+func (c UIConfig) theme() Theme {
+	if palette, ok := builtinPalettes[c.Theme]; ok {
+		return Theme{Name: c.Theme, Palette: palette}
+	}
+	custom, ok := c.Palettes[c.Theme]
+	if !ok {
+		return Theme{Name: ThemeDark, Palette: builtinPalettes[ThemeDark]}
+	}
+	base := custom.Base
+	if base == "" {
+		base = ThemeDark
+	}
+	return Theme{Name: c.Theme, Palette: custom.over(builtinPalettes[base])}
+}
+
+// themes is the names of the built-in themes and then the custom ones
+func (c UIConfig) themes() []string {
+	custom := make([]string, 0, len(c.Palettes))
+	for name := range c.Palettes {
+		if _, builtin := builtinPalettes[name]; !builtin {
+			custom = append(custom, name)
+		}
+	}
+	sort.Strings(custom)
+	return append([]string{ThemeDark, ThemeLight}, custom...)
+}
+
+// emitTheme tells the frontend the theme is now theme
+func (a *App) emitTheme(theme Theme) {
+	if a.ctx != nil {
+		wailsruntime.EventsEmit(a.ctx, themeChangedEvent, theme)
+	}
+}
+
+// GetTheme returns the theme in use with its colors
+func (a *App) GetTheme() Theme {
+	return a.config.Get().UI.theme()
+}
+
+// ListThemes returns the themes to pick from: dark, light and the palettes
+// in the config
+func (a *App) ListThemes() []string {
+	return a.config.Get().UI.themes()
+}
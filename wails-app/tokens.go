@@ -0,0 +1,163 @@
+package main
+
+import (
+	"strings"
+	"unicode"
+)
+
+// Context windows for known model families, matched by name prefix (longest first wins)
+var contextWindows = map[string]int{
+	"llama3":         8192,
+	"llama3.1":       131072,
+	"llama3.2":       131072,
+	"llama2":         4096,
+	"codellama":      16384,
+	"mistral":        32768,
+	"mixtral":        32768,
+	"qwen2.5":        32768,
+	"deepseek-coder": 16384,
+	"phi3":           4096,
+	"gemma":          8192,
+	"gpt-4o":         128000,
+	"gpt-4o-mini":    128000,
+	"gpt-4":          8192,
+	"gpt-3.5":        16385,
+	"claude-3":       200000,
+	"gemini-pro":     32768,
+	"gemini-1.5":     1048576,
+	"mock-model":     8192,
+}
+
+const defaultContextWindow = 4096
+
+// Marker inserted where the middle of an oversized prompt was removed
+const truncationMarker = "\n\n[... truncated to fit the model's context window ...]\n\n"
+
+// EstimateTokens approximates BPE token counts: runs of letters/digits cost
+// roughly one token per four characters, every other non-space rune is one token
+func EstimateTokens(text string) int {
+	tokens := 0
+	run := 0
+	flush := func() {
+		if run > 0 {
+			tokens += (run + 3) / 4
+			run = 0
+		}
+	}
+
+	for _, r := range text {
+		switch {
+		case unicode.IsLetter(r) || unicode.IsDigit(r):
+			run++
+		case unicode.IsSpace(r):
+			flush()
+		default:
+			flush()
+			tokens++
+		}
+	}
+	flush()
+
+	return tokens
+}
+
+// estimateMessageTokens includes the per-message overhead chat formats add for role markers
+func estimateMessageTokens(messages []ChatMessage) int {
+	total := 0
+	for _, m := range messages {
+		total += EstimateTokens(m.Content) + 4
+	}
+	return total
+}
+
+// ContextWindow returns the context size of a model, falling back to a conservative default
+func ContextWindow(model string) int {
+	name := strings.ToLower(model)
+	if i := strings.LastIndex(name, "/"); i >= 0 {
+		name = name[i+1:]
+	}
+
+	best, window := 0, defaultContextWindow
+	for prefix, size := range contextWindows {
+		if strings.HasPrefix(name, prefix) && len(prefix) > best {
+			best, window = len(prefix), size
+		}
+	}
+	return window
+}
+
+// TokenBudget describes how a request fits into the model's context window
+type TokenBudget struct {
+	Model          string `json:"model"`
+	PromptTokens   int    `json:"promptTokens"`
+	HistoryTokens  int    `json:"historyTokens"`
+	ReservedTokens int    `json:"reservedTokens"` // kept free for the completion
+	ContextWindow  int    `json:"contextWindow"`
+	Exceeds        bool   `json:"exceeds"`
+}
+
+// systemTokens estimates the share of the context taken by the system prompt
+func systemTokens(request AIRequest) int {
+	if request.SystemPrompt == "" {
+		return 0
+	}
+	return EstimateTokens(request.SystemPrompt) + 4
+}
+
+func budgetFor(request AIRequest, model string) TokenBudget {
+	window := ContextWindow(model)
+	budget := TokenBudget{
+		Model:          model,
+		PromptTokens:   EstimateTokens(request.Prompt) + 4 + systemTokens(request),
+		HistoryTokens:  estimateMessageTokens(request.History),
+		ReservedTokens: min(request.MaxTokens, window/2),
+		ContextWindow:  window,
+	}
+	budget.Exceeds = budget.PromptTokens+budget.HistoryTokens+budget.ReservedTokens > window
+	return budget
+}
+
+// FitToContext trims a request so it fits the model's context window: the oldest
+// history turns are dropped first, then the middle of the prompt is cut
+func FitToContext(request AIRequest, model string) AIRequest {
+	budget := budgetFor(request, model)
+	if !budget.Exceeds {
+		return request
+	}
+
+	available := budget.ContextWindow - budget.ReservedTokens - budget.PromptTokens
+	history := request.History
+	for len(history) > 0 && estimateMessageTokens(history) > available {
+		// Drop a whole user/assistant exchange where possible
+		drop := 1
+		if len(history) > 1 && history[0].Role == "user" && history[1].Role == "assistant" {
+			drop = 2
+		}
+		history = history[drop:]
+	}
+	request.History = history
+
+	promptBudget := budget.ContextWindow - budget.ReservedTokens - estimateMessageTokens(history) - systemTokens(request) - 4
+	if EstimateTokens(request.Prompt) > promptBudget {
+		request.Prompt = truncateMiddle(request.Prompt, promptBudget)
+	}
+
+	return request
+}
+
+// truncateMiddle keeps the head and tail of text so that it fits in roughly maxTokens
+func truncateMiddle(text string, maxTokens int) string {
+	if maxTokens <= 0 {
+		return ""
+	}
+	runes := []rune(text)
+	// Scale by the observed characters-per-token ratio of this text
+	keep := len(runes) * maxTokens / max(EstimateTokens(text), 1)
+	keep -= len([]rune(truncationMarker))
+	if keep <= 0 {
+		return string(runes[:min(len(runes), maxTokens)])
+	}
+	head := keep * 2 / 3
+	tail := keep - head
+	return string(runes[:head]) + truncationMarker + string(runes[len(runes)-tail:])
+}
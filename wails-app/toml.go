@@ -0,0 +1,702 @@
+package main
+
+import (
+	"bytes"
+	"encoding/json"
+	"fmt"
+	"math"
+	"strconv"
+	"strings"
+	"unicode/utf8"
+)
+
+// The config file is TOML, read and written by the small codec below rather
+// than a dependency. It covers what configuration needs: tables, arrays of
+// tables, inline tables and arrays, strings of every kind, integers, floats
+// and booleans, but not dates. Values cross to and from Go types through
+// encoding/json, so structs are described by their json tags.
+
+// MarshalTOML encodes v as a TOML document. v must encode to a JSON object;
+// null fields are left out.
+func MarshalTOML(v any) ([]byte, error) {
+	data, err := json.Marshal(v)
+	if err != nil {
+		return nil, err
+	}
+	decoder := json.NewDecoder(bytes.NewReader(data))
+	decoder.UseNumber()
+	tree, err := decodeOrderedJSON(decoder)
+	if err != nil {
+		return nil, err
+	}
+	table, ok := tree.(tomlTable)
+	if !ok {
+		return nil, fmt.Errorf("toml: cannot encode %T as a document", v)
+	}
+	var out strings.Builder
+	writeTOMLTable(&out, nil, table)
+	return []byte(out.String()), nil
+}
+
+// UnmarshalTOML decodes a TOML document into v, as json.Unmarshal would the
+// same values as JSON
+func UnmarshalTOML(data []byte, v any) error {
+	root, err := parseTOML(data)
+	if err != nil {
+		return err
+	}
+	encoded, err := json.Marshal(root)
+	if err != nil {
+		return err
+	}
+	return json.Unmarshal(encoded, v)
+}
+
+// parseTOML reads a document into maps, slices and scalars
+func parseTOML(data []byte) (map[string]any, error) {
+	if !utf8.Valid(data) {
+		return nil, fmt.Errorf("toml: not UTF-8")
+	}
+	p := &tomlParser{src: string(data), line: 1}
+	return p.document()
+}
+
+// parseTOMLValue reads a lone TOML value, such as one given on the command
+// line
+func parseTOMLValue(s string) (any, error) {
+	p := &tomlParser{src: s, line: 1}
+	p.space()
+	value, err := p.value()
+	if err != nil {
+		return nil, err
+	}
+	p.space()
+	if p.pos < len(p.src) {
+		return nil, p.errorf("unexpected %q after value", p.peek())
+	}
+	return value, nil
+}
+
+// tomlTable is a JSON object with its keys in order, so documents are
+// written in struct field order
+type tomlTable []tomlEntry
+
+type tomlEntry struct {
+	key   string
+	value any // string, json.Number, bool, []any or tomlTable; nil for null
+}
+
+func decodeOrderedJSON(decoder *json.Decoder) (any, error) {
+	token, err := decoder.Token()
+	if err != nil {
+		return nil, err
+	}
+	switch token {
+	case json.Delim('{'):
+		table := tomlTable{}
+		for decoder.More() {
+			key, err := decoder.Token()
+			if err != nil {
+				return nil, err
+			}
+			value, err := decodeOrderedJSON(decoder)
+			if err != nil {
+				return nil, err
+			}
+			table = append(table, tomlEntry{key: key.(string), value: value})
+		}
+		_, err := decoder.Token()
+		return table, err
+	case json.Delim('['):
+		list := []any{}
+		for decoder.More() {
+			value, err := decodeOrderedJSON(decoder)
+			if err != nil {
+				return nil, err
+			}
+			list = append(list, value)
+		}
+		_, err := decoder.Token()
+		return list, err
+	}
+	return token, nil
+}
+
+// isTableArray reports whether a list is written as [[array.of.tables]]
+func isTableArray(value any) bool {
+	list, ok := value.([]any)
+	if !ok || len(list) == 0 {
+		return false
+	}
+	for _, item := range list {
+		if _, ok := item.(tomlTable); !ok {
+			return false
+		}
+	}
+	return true
+}
+
+// writeTOMLTable writes a table's plain values, then its subtables and
+// arrays of tables, which TOML requires to come after them
+func writeTOMLTable(out *strings.Builder, path []string, table tomlTable) {
+	for _, entry := range table {
+		if _, ok := entry.value.(tomlTable); ok || entry.value == nil || isTableArray(entry.value) {
+			continue
+		}
+		fmt.Fprintf(out, "%s = %s\n", tomlKey(entry.key), tomlValue(entry.value))
+	}
+	for _, entry := range table {
+		child := append(append([]string(nil), path...), entry.key)
+		switch value := entry.value.(type) {
+		case tomlTable:
+			fmt.Fprintf(out, "\n[%s]\n", tomlPath(child))
+			writeTOMLTable(out, child, value)
+		case []any:
+			if !isTableArray(value) {
+				continue
+			}
+			for _, item := range value {
+				fmt.Fprintf(out, "\n[[%s]]\n", tomlPath(child))
+				writeTOMLTable(out, child, item.(tomlTable))
+			}
+		}
+	}
+}
+
+func tomlPath(path []string) string {
+	keys := make([]string, len(path))
+	for i, key := range path {
+		keys[i] = tomlKey(key)
+	}
+	return strings.Join(keys, ".")
+}
+
+// tomlKey leaves keys of letters, digits, _ and - bare and quotes the rest
+func tomlKey(key string) string {
+	if key != "" && strings.IndexFunc(key, func(r rune) bool { return !isBareKeyRune(r) }) < 0 {
+		return key
+	}
+	return tomlString(key)
+}
+
+func isBareKeyRune(r rune) bool {
+	return r >= 'a' && r <= 'z' || r >= 'A' && r <= 'Z' || r >= '0' && r <= '9' || r == '_' || r == '-'
+}
+
+// tomlValue writes a value inline
+func tomlValue(value any) string {
+	switch value := value.(type) {
+	case string:
+		return tomlString(value)
+	case json.Number:
+		return value.String()
+	case bool:
+		return strconv.FormatBool(value)
+	case []any:
+		items := make([]string, 0, len(value))
+		for _, item := range value {
+			if item != nil {
+				items = append(items, tomlValue(item))
+			}
+		}
+		return "[" + strings.Join(items, ", ") + "]"
+	case tomlTable:
+		items := make([]string, 0, len(value))
+		for _, entry := range value {
+			if entry.value != nil {
+				items = append(items, tomlKey(entry.key)+" = "+tomlValue(entry.value))
+			}
+		}
+		return "{" + strings.Join(items, ", ") + "}"
+	}
+	return `""`
+}
+
+// tomlString writes a basic string, escaping what TOML requires
+func tomlString(s string) string {
+	var out strings.Builder
+	out.WriteByte('"')
+	for _, r := range s {
+		switch r {
+		case '"':
+			out.WriteString(`\"`)
+		case '\\':
+			out.WriteString(`\\`)
+		case '\n':
+			out.WriteString(`\n`)
+		case '\t':
+			out.WriteString(`\t`)
+		case '\r':
+			out.WriteString(`\r`)
+		default:
+			if r < 0x20 || r == 0x7f {
+				fmt.Fprintf(&out, `\u%04X`, r)
+			} else {
+				out.WriteRune(r)
+			}
+		}
+	}
+	out.WriteByte('"')
+	return out.String()
+}
+
+// tomlParser reads a document into maps, slices and scalars for
+// encoding/json to take from there
+type tomlParser struct {
+	src  string
+	pos  int
+	line int
+
+	// Tables opened by a header, which another header mustn't open again
+	defined map[string]bool
+}
+
+func (p *tomlParser) errorf(format string, args ...any) error {
+	return fmt.Errorf("toml: line %d: %s", p.line, fmt.Sprintf(format, args...))
+}
+
+func (p *tomlParser) peek() byte {
+	if p.pos < len(p.src) {
+		return p.src[p.pos]
+	}
+	return 0
+}
+
+func (p *tomlParser) next() byte {
+	c := p.peek()
+	p.pos++
+	if c == '\n' {
+		p.line++
+	}
+	return c
+}
+
+// space skips spaces and tabs
+func (p *tomlParser) space() {
+	for c := p.peek(); c == ' ' || c == '\t'; c = p.peek() {
+		p.pos++
+	}
+}
+
+// blank skips whitespace, newlines and comments
+func (p *tomlParser) blank() {
+	for {
+		p.space()
+		switch p.peek() {
+		case '\n', '\r':
+			p.next()
+		case '#':
+			p.comment()
+		default:
+			return
+		}
+	}
+}
+
+func (p *tomlParser) comment() {
+	for p.pos < len(p.src) && p.peek() != '\n' {
+		p.pos++
+	}
+}
+
+// endOfLine expects nothing but a comment before the next line
+func (p *tomlParser) endOfLine() error {
+	p.space()
+	if p.peek() == '#' {
+		p.comment()
+	}
+	switch {
+	case p.pos >= len(p.src):
+		return nil
+	case strings.HasPrefix(p.src[p.pos:], "\r\n"):
+		p.pos++
+		fallthrough
+	case p.peek() == '\n':
+		p.next()
+		return nil
+	}
+	return p.errorf("unexpected %q after value", p.peek())
+}
+
+func (p *tomlParser) document() (map[string]any, error) {
+	root := make(map[string]any)
+	p.defined = make(map[string]bool)
+	current := root
+	for {
+		p.blank()
+		if p.pos >= len(p.src) {
+			return root, nil
+		}
+		var err error
+		if p.peek() == '[' {
+			current, err = p.header(root)
+		} else {
+			err = p.keyValue(current)
+		}
+		if err != nil {
+			return nil, err
+		}
+		if err := p.endOfLine(); err != nil {
+			return nil, err
+		}
+	}
+}
+
+// header reads [table] or [[array.of.tables]] and returns the table that
+// the lines after it fill in
+func (p *tomlParser) header(root map[string]any) (map[string]any, error) {
+	p.next()
+	array := p.peek() == '['
+	if array {
+		p.next()
+	}
+	p.space()
+	path, err := p.key()
+	if err != nil {
+		return nil, err
+	}
+	p.space()
+	closing := "]"
+	if array {
+		closing = "]]"
+	}
+	if !strings.HasPrefix(p.src[p.pos:], closing) {
+		return nil, p.errorf("expected %s", closing)
+	}
+	p.pos += len(closing)
+
+	table := root
+	for _, key := range path[:len(path)-1] {
+		if table, err = p.descend(table, key); err != nil {
+			return nil, err
+		}
+	}
+	last := path[len(path)-1]
+	name := strings.Join(path, "\x00")
+	if array {
+		list, ok := table[last].([]any)
+		if _, exists := table[last]; exists && !ok {
+			return nil, p.errorf("%s is not an array of tables", strings.Join(path, "."))
+		}
+		item := make(map[string]any)
+		table[last] = append(list, item)
+		// The new element's subtables haven't been defined yet
+		for defined := range p.defined {
+			if strings.HasPrefix(defined, name+"\x00") {
+				delete(p.defined, defined)
+			}
+		}
+		return item, nil
+	}
+	if p.defined[name] {
+		return nil, p.errorf("table %s is defined twice", strings.Join(path, "."))
+	}
+	p.defined[name] = true
+	return p.descend(table, last)
+}
+
+// descend returns the subtable of table called key, creating it, or the
+// last table of an array of tables
+func (p *tomlParser) descend(table map[string]any, key string) (map[string]any, error) {
+	switch value := table[key].(type) {
+	case nil:
+		child := make(map[string]any)
+		table[key] = child
+		return child, nil
+	case map[string]any:
+		return value, nil
+	case []any:
+		if len(value) > 0 {
+			if child, ok := value[len(value)-1].(map[string]any); ok {
+				return child, nil
+			}
+		}
+	}
+	return nil, p.errorf("%s is not a table", key)
+}
+
+func (p *tomlParser) keyValue(table map[string]any) error {
+	path, err := p.key()
+	if err != nil {
+		return err
+	}
+	p.space()
+	if p.next() != '=' {
+		return p.errorf("expected = after %s", strings.Join(path, "."))
+	}
+	p.space()
+	value, err := p.value()
+	if err != nil {
+		return err
+	}
+	for _, key := range path[:len(path)-1] {
+		if table, err = p.descend(table, key); err != nil {
+			return err
+		}
+	}
+	last := path[len(path)-1]
+	if _, exists := table[last]; exists {
+		return p.errorf("%s is set twice", strings.Join(path, "."))
+	}
+	table[last] = value
+	return nil
+}
+
+// key reads a dotted key of bare and quoted parts
+func (p *tomlParser) key() ([]string, error) {
+	var path []string
+	for {
+		p.space()
+		var part string
+		switch c := p.peek(); {
+		case c == '"':
+			s, err := p.basicString()
+			if err != nil {
+				return nil, err
+			}
+			part = s
+		case c == '\'':
+			s, err := p.literalString()
+			if err != nil {
+				return nil, err
+			}
+			part = s
+		default:
+			start := p.pos
+			for p.pos < len(p.src) && isBareKeyRune(rune(p.src[p.pos])) {
+				p.pos++
+			}
+			if p.pos == start {
+				return nil, p.errorf("expected a key")
+			}
+			part = p.src[start:p.pos]
+		}
+		path = append(path, part)
+		p.space()
+		if p.peek() != '.' {
+			return path, nil
+		}
+		p.next()
+	}
+}
+
+func (p *tomlParser) value() (any, error) {
+	rest := p.src[p.pos:]
+	switch c := p.peek(); {
+	case strings.HasPrefix(rest, `"""`):
+		return p.multilineString(`"""`)
+	case strings.HasPrefix(rest, "'''"):
+		return p.multilineString("'''")
+	case c == '"':
+		return p.basicString()
+	case c == '\'':
+		return p.literalString()
+	case c == '[':
+		return p.array()
+	case c == '{':
+		return p.inlineTable()
+	case strings.HasPrefix(rest, "true"):
+		p.pos += 4
+		return true, nil
+	case strings.HasPrefix(rest, "false"):
+		p.pos += 5
+		return false, nil
+	}
+	return p.number()
+}
+
+// escape reads the escape sequence after a backslash
+func (p *tomlParser) escape(out *strings.Builder) error {
+	c := p.next()
+	switch c {
+	case 'b':
+		out.WriteByte('\b')
+	case 't':
+		out.WriteByte('\t')
+	case 'n':
+		out.WriteByte('\n')
+	case 'f':
+		out.WriteByte('\f')
+	case 'r':
+		out.WriteByte('\r')
+	case '"', '\\':
+		out.WriteByte(c)
+	case 'u', 'U':
+		digits := 4
+		if c == 'U' {
+			digits = 8
+		}
+		if p.pos+digits > len(p.src) {
+			return p.errorf("short unicode escape")
+		}
+		code, err := strconv.ParseUint(p.src[p.pos:p.pos+digits], 16, 32)
+		if err != nil || !utf8.ValidRune(rune(code)) {
+			return p.errorf("invalid unicode escape")
+		}
+		p.pos += digits
+		out.WriteRune(rune(code))
+	default:
+		return p.errorf("invalid escape \\%c", c)
+	}
+	return nil
+}
+
+func (p *tomlParser) basicString() (string, error) {
+	p.next()
+	var out strings.Builder
+	for {
+		switch c := p.next(); c {
+		case 0, '\n':
+			return "", p.errorf("unterminated string")
+		case '"':
+			return out.String(), nil
+		case '\\':
+			if err := p.escape(&out); err != nil {
+				return "", err
+			}
+		default:
+			out.WriteByte(c)
+		}
+	}
+}
+
+func (p *tomlParser) literalString() (string, error) {
+	p.next()
+	end := strings.IndexAny(p.src[p.pos:], "'\n")
+	if end < 0 || p.src[p.pos+end] != '\'' {
+		return "", p.errorf("unterminated string")
+	}
+	s := p.src[p.pos : p.pos+end]
+	p.pos += end + 1
+	return s, nil
+}
+
+// multilineString reads a """ or ''' string; a newline straight after the
+// opening quotes is dropped, and in """ strings a backslash at the end of a
+// line joins it to the next
+func (p *tomlParser) multilineString(quotes string) (string, error) {
+	p.pos += len(quotes)
+	if strings.HasPrefix(p.src[p.pos:], "\r\n") {
+		p.pos++
+	}
+	if p.peek() == '\n' {
+		p.next()
+	}
+	var out strings.Builder
+	for {
+		if p.pos >= len(p.src) {
+			return "", p.errorf("unterminated string")
+		}
+		if strings.HasPrefix(p.src[p.pos:], quotes) {
+			p.pos += len(quotes)
+			// Up to two quotes more belong to the string
+			for i := 0; i < 2 && strings.HasPrefix(p.src[p.pos:], quotes[:1]); i++ {
+				out.WriteByte(p.next())
+			}
+			return out.String(), nil
+		}
+		c := p.next()
+		if c != '\\' || quotes == "'''" {
+			out.WriteByte(c)
+			continue
+		}
+		rest := p.src[p.pos:]
+		if trimmed := strings.TrimLeft(rest, " \t\r"); strings.HasPrefix(trimmed, "\n") {
+			p.pos += len(rest) - len(trimmed)
+			p.blankLines()
+			continue
+		}
+		if err := p.escape(&out); err != nil {
+			return "", err
+		}
+	}
+}
+
+// blankLines skips the whitespace a line-ending backslash swallows
+func (p *tomlParser) blankLines() {
+	for c := p.peek(); c == ' ' || c == '\t' || c == '\n' || c == '\r'; c = p.peek() {
+		p.next()
+	}
+}
+
+func (p *tomlParser) array() ([]any, error) {
+	p.next()
+	list := []any{}
+	for {
+		p.blank()
+		if p.peek() == ']' {
+			p.next()
+			return list, nil
+		}
+		value, err := p.value()
+		if err != nil {
+			return nil, err
+		}
+		list = append(list, value)
+		p.blank()
+		switch p.next() {
+		case ',':
+		case ']':
+			return list, nil
+		default:
+			return nil, p.errorf("expected , or ] in array")
+		}
+	}
+}
+
+func (p *tomlParser) inlineTable() (map[string]any, error) {
+	p.next()
+	table := make(map[string]any)
+	p.space()
+	if p.peek() == '}' {
+		p.next()
+		return table, nil
+	}
+	for {
+		if err := p.keyValue(table); err != nil {
+			return nil, err
+		}
+		p.space()
+		switch p.next() {
+		case ',':
+		case '}':
+			return table, nil
+		default:
+			return nil, p.errorf("expected , or } in inline table")
+		}
+	}
+}
+
+// number reads an integer, in decimal, hex, octal or binary, or a float
+func (p *tomlParser) number() (any, error) {
+	start := p.pos
+	for p.pos < len(p.src) && strings.IndexByte("+-0123456789abcdefABCDEFxoinINF._", p.src[p.pos]) >= 0 {
+		p.pos++
+	}
+	text := strings.ReplaceAll(p.src[start:p.pos], "_", "")
+	if text == "" {
+		return nil, p.errorf("expected a value")
+	}
+	unsigned := strings.TrimLeft(text, "+-")
+	switch {
+	case unsigned == "inf" || unsigned == "nan":
+		return nil, p.errorf("%s can't be represented", text)
+	case strings.HasPrefix(unsigned, "0x") || strings.HasPrefix(unsigned, "0o") || strings.HasPrefix(unsigned, "0b"):
+		n, err := strconv.ParseInt(text, 0, 64)
+		if err != nil || unsigned != text {
+			return nil, p.errorf("invalid integer %q", text)
+		}
+		return n, nil
+	case !strings.ContainsAny(unsigned, ".eE"):
+		n, err := strconv.ParseInt(text, 10, 64)
+		if err != nil || len(unsigned) > 1 && unsigned[0] == '0' {
+			return nil, p.errorf("invalid integer %q", text)
+		}
+		return n, nil
+	}
+	f, err := strconv.ParseFloat(text, 64)
+	if err != nil || math.IsInf(f, 0) {
+		return nil, p.errorf("invalid value %q", text)
+	}
+	return f, nil
+}
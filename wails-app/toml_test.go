@@ -0,0 +1,152 @@
+package main
+
+import (
+	"reflect"
+	"strings"
+	"testing"
+)
+
+func TestTOMLRoundTrip(t *testing.T) {
+	config := DefaultAppConfig()
+	config.ActiveProvider = 1
+	config.Offline = true
+	config.General.HistoryMaxBytes = 1 << 40
+	config.UI.FontFamily = `"Fira Code", monospace`
+	config.UI.Palettes = map[string]Palette{
+		"solarized": {Base: "dark", Background: "#002b36", Text: "#839496"},
+		"two words": {Base: "light", Code: "#fdf6e3"},
+	}
+	config.Providers = []ProviderConfig{
+		{
+			Type:         "OpenAI",
+			Name:         "work",
+			APIKey:       "keyring:work",
+			Model:        "gpt-4o",
+			Timeout:      30,
+			SystemPrompt: "Answer tersely.\nQuote paths like \"C:\\src\"\tand use ünïcode ✓",
+		},
+		{
+			Type:  "Mock",
+			Name:  "demo",
+			Model: "mock",
+			MockScript: &MockScript{
+				Responses: []string{"first", "# Heading\n\n```go\nx := 1\n```"},
+				LatencyMs: 5,
+				FailEvery: 3,
+			},
+		},
+	}
+
+	t.Run("struct", func(t *testing.T) {
+		data, err := MarshalTOML(config)
+		if err != nil {
+			t.Fatal(err)
+		}
+		var decoded AppConfig
+		if err := UnmarshalTOML(data, &decoded); err != nil {
+			t.Fatalf("decoding:\n%s\n%v", data, err)
+		}
+		if !reflect.DeepEqual(decoded, config) {
+			t.Errorf("round trip changed the config:\n%s\ngot  %+v\nwant %+v", data, decoded, config)
+		}
+	})
+
+	t.Run("config tree", func(t *testing.T) {
+		data, err := MarshalTOML(configTree(config))
+		if err != nil {
+			t.Fatal(err)
+		}
+		tree, err := parseTOML(data)
+		if err != nil {
+			t.Fatalf("parsing:\n%s\n%v", data, err)
+		}
+		decoded, err := decodeConfig(tree)
+		if err != nil {
+			t.Fatal(err)
+		}
+		if !reflect.DeepEqual(decoded, config) {
+			t.Errorf("round trip changed the config:\n%s\ngot  %+v\nwant %+v", data, decoded, config)
+		}
+	})
+}
+
+func TestParseTOML(t *testing.T) {
+	tests := []struct {
+		name string
+		doc  string
+		want map[string]any
+	}{
+		{
+			name: "scalars",
+			doc:  "int = 1_000\nhex = 0xff\nneg = -7\nfloat = 1.5e3\nyes = true\nno = false # comment\n",
+			want: map[string]any{"int": int64(1000), "hex": int64(255), "neg": int64(-7), "float": 1500.0, "yes": true, "no": false},
+		},
+		{
+			name: "strings",
+			doc:  "basic = \"a\\tb \\\"q\\\" \\u00e9\"\nliteral = 'C:\\path'\nmulti = \"\"\"\nline one\nline \\\n   two\"\"\"\nraw = '''\n\\n stays'''\n",
+			want: map[string]any{"basic": "a\tb \"q\" é", "literal": `C:\path`, "multi": "line one\nline two", "raw": `\n stays`},
+		},
+		{
+			name: "dotted and quoted keys",
+			doc:  "ui.theme = \"dark\"\nui.\"font size\" = 12\n'a.b' = 1\n",
+			want: map[string]any{"ui": map[string]any{"theme": "dark", "font size": int64(12)}, "a.b": int64(1)},
+		},
+		{
+			name: "tables and arrays of tables",
+			doc:  "[general]\nhistoryMaxEntries = 5\n\n[[providers]]\nname = \"a\"\n[providers.mockScript]\nfailEvery = 2\n\n[[providers]]\nname = \"b\"\n",
+			want: map[string]any{
+				"general": map[string]any{"historyMaxEntries": int64(5)},
+				"providers": []any{
+					map[string]any{"name": "a", "mockScript": map[string]any{"failEvery": int64(2)}},
+					map[string]any{"name": "b"},
+				},
+			},
+		},
+		{
+			name: "inline tables and arrays",
+			doc:  "point = {x = 1, y = {z = \"deep\"}}\nlist = [\n  1,\n  2, # trailing comma\n]\nempty = []\n",
+			want: map[string]any{
+				"point": map[string]any{"x": int64(1), "y": map[string]any{"z": "deep"}},
+				"list":  []any{int64(1), int64(2)},
+				"empty": []any{},
+			},
+		},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			got, err := parseTOML([]byte(tt.doc))
+			if err != nil {
+				t.Fatalf("unexpected error: %v", err)
+			}
+			if !reflect.DeepEqual(got, tt.want) {
+				t.Errorf("got %#v, want %#v", got, tt.want)
+			}
+		})
+	}
+}
+
+func TestParseTOMLErrors(t *testing.T) {
+	tests := []struct {
+		name    string
+		doc     string
+		wantErr string
+	}{
+		{"missing value", "a =\n", "expected a value"},
+		{"unterminated string", "a = \"open\n", "unterminated string"},
+		{"unterminated multiline string", "a = '''open\n", "unterminated string"},
+		{"leading zero", "a = 012\n", "invalid integer"},
+		{"infinity", "a = inf\n", "can't be represented"},
+		{"key set twice", "a = 1\na = 2\n", "a is set twice"},
+		{"table defined twice", "[ui]\n[ui]\n", "table ui is defined twice"},
+		{"bad escape", "a = \"\\q\"\n", "invalid escape"},
+		{"two values on a line", "a = 1 b = 2\n", "after value"},
+	}
+	for _, tt := range tests {
+		t.Run(tt.name, func(t *testing.T) {
+			_, err := parseTOML([]byte(tt.doc))
+			if err == nil || !strings.Contains(err.Error(), tt.wantErr) {
+				t.Errorf("err = %v, want one mentioning %q", err, tt.wantErr)
+			}
+		})
+	}
+}
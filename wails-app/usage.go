@@ -0,0 +1,139 @@
+package main
+
+import (
+	"encoding/json"
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sort"
+	"sync"
+	"time"
+)
+
+const usageDayFormat = "2006-01-02"
+
+// UsageRecord is the usage of one model on one day
+type UsageRecord struct {
+	Day   string `json:"day"` // YYYY-MM-DD in local time
+	Model string `json:"model"`
+	UsageTotals
+}
+
+// UsageStats accumulates token usage per model per day and persists it to disk,
+// so usage reports cover every session rather than only the current one
+type UsageStats struct {
+	mu   sync.Mutex
+	path string
+	days map[string]map[string]UsageTotals // day -> model -> totals
+}
+
+// NewUsageStats loads the stats stored at path; a missing or unreadable file starts empty
+func NewUsageStats(path string) *UsageStats {
+	stats := &UsageStats{path: path, days: make(map[string]map[string]UsageTotals)}
+	if data, err := os.ReadFile(path); err == nil {
+		if err := json.Unmarshal(data, &stats.days); err != nil {
+			fmt.Fprintf(os.Stderr, "ignoring corrupt usage stats %s: %v\n", path, err)
+			stats.days = make(map[string]map[string]UsageTotals)
+		}
+	} else if !errors.Is(err, os.ErrNotExist) {
+		fmt.Fprintf(os.Stderr, "failed to read usage stats: %v\n", err)
+	}
+	return stats
+}
+
+// Record adds a priced response to today's totals for its model and saves the stats
+func (s *UsageStats) Record(response AIResponse) {
+	day := time.Now().Format(usageDayFormat)
+	model := response.Model
+	if model == "" {
+		model = "unknown"
+	}
+
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	models, ok := s.days[day]
+	if !ok {
+		models = make(map[string]UsageTotals)
+		s.days[day] = models
+	}
+	totals := models[model]
+	totals.add(response)
+	models[model] = totals
+
+	if err := s.save(); err != nil {
+		fmt.Fprintf(os.Stderr, "failed to save usage stats: %v\n", err)
+	}
+}
+
+// save writes the stats atomically; the caller holds s.mu
+func (s *UsageStats) save() error {
+	if s.path == "" {
+		return nil
+	}
+	if err := os.MkdirAll(filepath.Dir(s.path), 0o700); err != nil {
+		return err
+	}
+	data, err := json.Marshal(s.days)
+	if err != nil {
+		return err
+	}
+	tmp := s.path + ".tmp"
+	if err := os.WriteFile(tmp, data, 0o600); err != nil {
+		return err
+	}
+	return os.Rename(tmp, s.path)
+}
+
+// UsageReport is the usage over a range of days, per day and model and summed
+type UsageReport struct {
+	Records []UsageRecord          `json:"records"` // newest day first, models alphabetically within a day
+	ByModel map[string]UsageTotals `json:"byModel"`
+	Total   UsageTotals            `json:"total"`
+}
+
+// Report returns the usage for the last days days, including today; days <= 0 covers everything
+func (s *UsageStats) Report(days int) UsageReport {
+	cutoff := ""
+	if days > 0 {
+		cutoff = time.Now().AddDate(0, 0, -(days - 1)).Format(usageDayFormat)
+	}
+
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	records := make([]UsageRecord, 0)
+	for day, models := range s.days {
+		if day < cutoff {
+			continue
+		}
+		for model, totals := range models {
+			records = append(records, UsageRecord{Day: day, Model: model, UsageTotals: totals})
+		}
+	}
+	sort.Slice(records, func(i, j int) bool {
+		if records[i].Day != records[j].Day {
+			return records[i].Day > records[j].Day
+		}
+		return records[i].Model < records[j].Model
+	})
+
+	report := UsageReport{Records: records, ByModel: make(map[string]UsageTotals)}
+	for _, r := range records {
+		totals := report.ByModel[r.Model]
+		totals.merge(r.UsageTotals)
+		report.ByModel[r.Model] = totals
+		report.Total.merge(r.UsageTotals)
+	}
+	return report
+}
+
+// Clear deletes all recorded usage
+func (s *UsageStats) Clear() error {
+	s.mu.Lock()
+	defer s.mu.Unlock()
+
+	s.days = make(map[string]map[string]UsageTotals)
+	return s.save()
+}